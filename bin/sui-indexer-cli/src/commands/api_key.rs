@@ -0,0 +1,114 @@
+use clap::{Args, Subcommand};
+use eyre::Result;
+use sui_indexer_config::{ApiScope, IndexerConfig};
+use sui_indexer_core::{audit, auth};
+use sui_indexer_storage::StorageManager;
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `api-key` command
+#[derive(Args)]
+pub struct ApiKeyArgs {
+    #[command(subcommand)]
+    command: ApiKeyCommand,
+}
+
+#[derive(Subcommand)]
+enum ApiKeyCommand {
+    /// Generate a key and store its hash; the key is printed only once
+    Create {
+        /// Name the key's requests are logged under
+        name: String,
+        /// `read` for queries, `admin` for every request
+        #[arg(long, default_value_t = ApiScope::Read)]
+        scope: ApiScope,
+        /// Requests per second allowed with the key, unlimited if unset
+        #[arg(long)]
+        rate_limit: Option<f64>,
+    },
+    /// List the stored keys
+    List,
+    /// Revoke a stored key; running indexers reject it after their next refresh
+    Revoke {
+        /// Key to revoke
+        name: String,
+    },
+}
+
+/// Run an `api-key` subcommand against the configured database
+pub async fn run(config: IndexerConfig, args: ApiKeyArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+
+    match args.command {
+        ApiKeyCommand::Create {
+            name,
+            scope,
+            rate_limit,
+        } => {
+            if rate_limit.is_some_and(|rate| rate <= 0.0) {
+                eyre::bail!("--rate-limit must be greater than 0");
+            }
+            let key = auth::generate_key();
+            storage
+                .create_api_key(&name, &auth::hash_key(&key), scope, rate_limit)
+                .await?;
+            let details = serde_json::json!({
+                "name": name,
+                "scope": scope,
+                "rate_limit": rate_limit,
+            });
+            audit::record(&storage, audit::cli_entry("api_key_create", details)).await;
+            if output.is_json() {
+                return print_json(&serde_json::json!({
+                    "name": name,
+                    "scope": scope,
+                    "rate_limit": rate_limit,
+                    "key": key,
+                }));
+            }
+            println!("✅ API key `{name}` created with the `{scope}` scope");
+            println!("{key}");
+            println!("Store it now; only its hash is kept");
+        }
+        ApiKeyCommand::List => {
+            let keys = storage.api_keys().await?;
+            if output.is_json() {
+                return print_json(&keys);
+            }
+            if keys.is_empty() {
+                println!("No API keys stored");
+            }
+            for key in keys {
+                let rate_limit = key
+                    .rate_limit
+                    .map_or_else(|| "unlimited".to_string(), |rate| format!("{rate}/s"));
+                let state = key.revoked_at.map_or_else(
+                    || "active".to_string(),
+                    |at| format!("revoked {}", at.format("%Y-%m-%d %H:%M:%S")),
+                );
+                println!(
+                    "{:<20} {:<6} {:<12} created {}  {state}",
+                    key.name,
+                    key.scope,
+                    rate_limit,
+                    key.created_at.format("%Y-%m-%d %H:%M:%S"),
+                );
+            }
+        }
+        ApiKeyCommand::Revoke { name } => {
+            if !storage.revoke_api_key(&name).await? {
+                eyre::bail!("No active API key named `{name}`");
+            }
+            audit::record(
+                &storage,
+                audit::cli_entry("api_key_revoke", serde_json::json!({ "name": name })),
+            )
+            .await;
+            if output.is_json() {
+                return print_json(&serde_json::json!({ "name": name, "revoked": true }));
+            }
+            println!("✅ API key `{name}` revoked");
+        }
+    }
+    Ok(())
+}
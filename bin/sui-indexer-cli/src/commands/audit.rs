@@ -0,0 +1,38 @@
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::StorageManager;
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `audit` command
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Number of most recent entries to show
+    #[arg(long, default_value_t = 50)]
+    limit: u32,
+}
+
+/// List the most recent operator actions recorded in the audit log
+pub async fn run(config: IndexerConfig, args: AuditArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+    let records = storage.audit_log(args.limit).await?;
+
+    if output.is_json() {
+        return print_json(&records);
+    }
+    if records.is_empty() {
+        println!("No operator actions recorded");
+    }
+    for record in records {
+        println!(
+            "{}  {:<10} {:<16} {:<16} {}",
+            record.occurred_at.format("%Y-%m-%d %H:%M:%S"),
+            record.source,
+            record.actor,
+            record.action,
+            record.details
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use clap::Args;
+use eyre::Result;
+use sui_indexer_core::{CheckpointRange, CheckpointStats, IndexerCore};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    exit::interrupted,
+    output::{print_json, Output},
+};
+
+/// Interval between progress reports
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Arguments of the `backfill` command
+#[derive(Args)]
+pub struct BackfillArgs {
+    /// First checkpoint to index
+    #[arg(long)]
+    from: u64,
+    /// Last checkpoint to index (inclusive)
+    #[arg(long)]
+    to: u64,
+    /// Checkpoints processed at once (defaults to `runtime.fetch_concurrency`)
+    #[arg(long)]
+    concurrency: Option<usize>,
+    /// Comma-separated event filter names to apply (defaults to all configured filters)
+    #[arg(long, value_delimiter = ',')]
+    filters: Vec<String>,
+}
+
+/// Index a checkpoint range, reporting progress until it is complete
+pub async fn run(indexer: IndexerCore, args: BackfillArgs, output: Output) -> Result<()> {
+    let range = CheckpointRange::new(args.from, args.to)?;
+    indexer.verify_chain().await?;
+    let total = range.len();
+    let config = indexer.config();
+    let filters = if args.filters.is_empty() {
+        config.events.filters.clone()
+    } else {
+        config.events.named_filters(&args.filters)?
+    };
+
+    let mut backfill = indexer.backfill(range).with_filters(filters);
+    if let Some(concurrency) = args.concurrency {
+        backfill = backfill.with_concurrency(concurrency);
+    }
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("✋ Received shutdown signal (Ctrl+C), stopping backfill");
+            signal_shutdown.cancel();
+        }
+    });
+
+    let progress = backfill.progress();
+    let reporter = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            report(&progress.borrow(), total);
+        }
+    });
+
+    let stats = backfill.run(shutdown.clone()).await;
+    reporter.abort();
+    let stats = stats?;
+
+    if shutdown.is_cancelled() {
+        return Err(interrupted(format!(
+            "Backfill interrupted after {} of {} checkpoints",
+            stats.total_processed, total
+        )));
+    }
+
+    if output.is_json() {
+        return print_json(&stats);
+    }
+    info!(
+        "✅ Backfill complete: {} checkpoints at {:.1} checkpoints/s",
+        stats.total_processed, stats.processing_rate
+    );
+    Ok(())
+}
+
+pub(super) fn report(stats: &CheckpointStats, total: u64) {
+    let eta = stats
+        .estimated_time_remaining
+        .map(format_duration)
+        .unwrap_or_else(|| "unknown".to_string());
+    info!(
+        "📈 Backfilled {}/{} checkpoints ({:.1} checkpoints/s, ETA {})",
+        stats.total_processed, total, stats.processing_rate, eta
+    );
+}
+
+pub(super) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}h{:02}m{:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
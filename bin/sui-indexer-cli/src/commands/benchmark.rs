@@ -0,0 +1,100 @@
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::{
+    benchmark::{StageStats, DEFAULT_BENCHMARK_TABLE},
+    IndexerCore,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    exit::interrupted,
+    output::{print_json, Output},
+};
+
+/// Arguments of the `benchmark` command
+#[derive(Args)]
+pub struct BenchmarkArgs {
+    /// Number of checkpoints to benchmark
+    #[arg(long, default_value_t = 1000)]
+    checkpoints: u64,
+    /// First checkpoint to benchmark (defaults to ending at the network tip)
+    #[arg(long)]
+    from: Option<u64>,
+    /// Skip the store stage and write nothing to the database
+    #[arg(long)]
+    no_store: bool,
+    /// Table the store stage writes to; its rows for the range are replaced
+    #[arg(long, default_value = DEFAULT_BENCHMARK_TABLE)]
+    table: String,
+    /// Checkpoints fetched at once (defaults to `runtime.fetch_concurrency`)
+    #[arg(long)]
+    concurrency: Option<usize>,
+}
+
+/// Measure fetch, process and store throughput over a checkpoint range
+pub async fn run(config: IndexerConfig, args: BenchmarkArgs, output: Output) -> Result<()> {
+    // Without the store stage the database is never connected to
+    let indexer = if args.no_store {
+        IndexerCore::embedded(config).await?
+    } else {
+        let indexer = IndexerCore::new(config).await?;
+        indexer.initialize().await?;
+        indexer
+    };
+
+    let mut benchmark = indexer
+        .benchmark(args.checkpoints, !args.no_store)
+        .with_start(args.from)
+        .with_table(args.table);
+    if let Some(concurrency) = args.concurrency {
+        benchmark = benchmark.with_concurrency(concurrency);
+    }
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("✋ Received shutdown signal (Ctrl+C), stopping benchmark");
+            signal_shutdown.cancel();
+        }
+    });
+
+    let report = benchmark.run(shutdown.clone()).await?;
+    if shutdown.is_cancelled() {
+        return Err(interrupted("Benchmark interrupted"));
+    }
+    if output.is_json() {
+        return print_json(&report);
+    }
+
+    println!(
+        "Checkpoints {}..={} ({} checkpoints)",
+        report.range.start,
+        report.range.end,
+        report.range.len()
+    );
+    println!(
+        "{:<8} {:>10} {:>12} {:>10} {:>14} {:>12}",
+        "stage", "time", "checkpoints", "events", "checkpoints/s", "events/s"
+    );
+    print_stage("fetch", &report.fetch);
+    print_stage("process", &report.process);
+    match &report.store {
+        Some(store) => print_stage("store", store),
+        None => println!("{:<8} {:>10}", "store", "skipped"),
+    }
+    Ok(())
+}
+
+fn print_stage(name: &str, stats: &StageStats) {
+    println!(
+        "{name:<8} {:>9.2}s {:>12} {:>10} {:>14.1} {:>12.1}",
+        stats.elapsed.as_secs_f64(),
+        stats.checkpoints,
+        stats.events,
+        stats.checkpoints_per_second(),
+        stats.events_per_second()
+    );
+}
@@ -0,0 +1,308 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use chrono::{NaiveDateTime, Utc};
+use clap::{Args, Subcommand};
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::audit;
+use sui_indexer_storage::{CursorRecord, StorageManager, DEFAULT_PIPELINE};
+
+use super::control;
+use crate::output::{print_json, Output};
+
+/// Seconds since a cursor update within which an indexer is taken to be running
+const RECENT_CURSOR_SECS: i64 = 60;
+
+/// Arguments of the `checkpoint` command
+#[derive(Args)]
+pub struct CheckpointArgs {
+    #[command(subcommand)]
+    command: CheckpointCommand,
+}
+
+#[derive(Subcommand)]
+enum CheckpointCommand {
+    /// Show the stored checkpoint cursors
+    Show,
+    /// Overwrite a stored cursor; `start` resumes after the given checkpoint
+    Set {
+        /// Cursor to move
+        #[arg(long, default_value = DEFAULT_PIPELINE)]
+        pipeline: String,
+        /// Checkpoint to record as the last one processed
+        checkpoint: u64,
+        /// Skip the confirmation prompt and the running-indexer check
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the processing reports of a range of checkpoints
+    Reports {
+        /// Pipeline whose reports to show
+        #[arg(long, default_value = DEFAULT_PIPELINE)]
+        pipeline: String,
+        /// First checkpoint of the range
+        from: u64,
+        /// Last checkpoint of the range, the first one if omitted
+        to: Option<u64>,
+    },
+}
+
+/// Run a `checkpoint` subcommand against the configured database
+pub async fn run(config: IndexerConfig, args: CheckpointArgs, output: Output) -> Result<()> {
+    // Whether the control endpoint answers, if it is configured
+    let control = match config.admin.addr {
+        Some(_) => Some(control::status(&config).await?.is_some()),
+        None => None,
+    };
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+
+    match args.command {
+        CheckpointCommand::Show => {
+            let cursors = storage.list_cursors().await?;
+            if output.is_json() {
+                return print_json(&cursors);
+            }
+            if cursors.is_empty() {
+                println!("No checkpoint cursors stored");
+            }
+            for cursor in cursors {
+                let updated_at = cursor
+                    .updated_at
+                    .map_or_else(|| "-".to_string(), |at| at.to_string());
+                println!(
+                    "{:<16} {:>12}  (updated {updated_at})",
+                    cursor.pipeline, cursor.checkpoint_sequence
+                );
+            }
+        }
+        CheckpointCommand::Set {
+            pipeline,
+            checkpoint,
+            force,
+        } => {
+            let current = storage.get_cursor(&pipeline).await?;
+            // Keep stdout to the JSON result; the prompt goes to the terminal either way
+            if output.is_json() {
+                eprintln!("{}", describe_change(&pipeline, current, checkpoint));
+            } else {
+                println!("{}", describe_change(&pipeline, current, checkpoint));
+            }
+
+            if !force {
+                let cursors = storage.list_cursors().await?;
+                match liveness(control, &cursors, Utc::now().naive_utc()) {
+                    Liveness::Running(reason) => eyre::bail!(
+                        "An indexer is running ({reason}) and will overwrite the cursor; stop it first or pass --force"
+                    ),
+                    Liveness::Unknown => eyre::bail!(
+                        "Cannot tell whether an indexer is running, as admin.addr is not set; make sure none is and pass --force"
+                    ),
+                    Liveness::Stopped => {}
+                }
+                if !confirm()? {
+                    eyre::bail!("Aborted, cursor unchanged");
+                }
+            }
+
+            storage.set_cursor(&pipeline, checkpoint).await?;
+            let details = serde_json::json!({
+                "pipeline": pipeline,
+                "previous": current,
+                "checkpoint": checkpoint,
+                "forced": force,
+            });
+            audit::record(&storage, audit::cli_entry("cursor_set", details)).await;
+            if output.is_json() {
+                return print_json(&serde_json::json!({
+                    "pipeline": pipeline,
+                    "previous": current,
+                    "checkpoint": checkpoint,
+                }));
+            }
+            println!("✅ Cursor `{pipeline}` set to {checkpoint}");
+        }
+        CheckpointCommand::Reports { pipeline, from, to } => {
+            let reports = storage
+                .checkpoint_reports(&pipeline, from, to.unwrap_or(from))
+                .await?;
+            if output.is_json() {
+                return print_json(&reports);
+            }
+            if reports.is_empty() {
+                println!("No checkpoint reports recorded for `{pipeline}` in that range");
+                return Ok(());
+            }
+            println!(
+                "{:>12} {:>6} {:>7} {:>7} {:>7} {:>7} {:>8} {:>8} {:>8} {:>8}",
+                "CHECKPOINT",
+                "TXS",
+                "SEEN",
+                "MATCHED",
+                "SKIPPED",
+                "STORED",
+                "FETCH",
+                "TRANSFORM",
+                "STORE",
+                "DELIVER"
+            );
+            for report in reports {
+                println!(
+                    "{:>12} {:>6} {:>7} {:>7} {:>7} {:>7} {:>6}ms {:>7}ms {:>6}ms {:>6}ms",
+                    report.checkpoint_sequence,
+                    report.transactions,
+                    report.events_seen,
+                    report.events_matched,
+                    report.events_skipped,
+                    report.events_stored,
+                    report.fetch_ms,
+                    report.transform_ms,
+                    report.store_ms,
+                    report.deliver_ms
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether an indexer is working on the database
+#[derive(Debug, PartialEq, Eq)]
+enum Liveness {
+    /// Running, for the given reason
+    Running(String),
+    /// The control endpoint did not answer and no cursor moved lately
+    Stopped,
+    /// No control endpoint to ask, and no cursor moved lately
+    Unknown,
+}
+
+/// Tell whether an indexer is running from its control endpoint and the cursor updates
+///
+/// `control` is whether the control endpoint answered, `None` without
+/// `admin.addr`. A cursor updated within [`RECENT_CURSOR_SECS`] of `now`
+/// shows an indexer at work even if its control endpoint is elsewhere. An
+/// idle one moves no cursor, so without a control endpoint it cannot be
+/// told apart from a stopped one.
+fn liveness(control: Option<bool>, cursors: &[CursorRecord], now: NaiveDateTime) -> Liveness {
+    if control == Some(true) {
+        return Liveness::Running("its control endpoint answers".to_string());
+    }
+    let recent = cursors.iter().find(|cursor| {
+        cursor.updated_at.is_some_and(|updated_at| {
+            now - updated_at < chrono::Duration::seconds(RECENT_CURSOR_SECS)
+        })
+    });
+    if let Some(cursor) = recent {
+        return Liveness::Running(format!(
+            "cursor `{}` moved less than {RECENT_CURSOR_SECS}s ago",
+            cursor.pipeline
+        ));
+    }
+    match control {
+        Some(_) => Liveness::Stopped,
+        None => Liveness::Unknown,
+    }
+}
+
+/// Explain what moving a cursor from `current` to `target` does
+fn describe_change(pipeline: &str, current: Option<u64>, target: u64) -> String {
+    match current {
+        None => format!(
+            "Cursor `{pipeline}` is not stored yet; indexing will resume at {}",
+            target.saturating_add(1)
+        ),
+        Some(current) if target < current => format!(
+            "Cursor `{pipeline}` moves back from {current} to {target}: {} checkpoints will be re-indexed",
+            current - target
+        ),
+        Some(current) if target > current => format!(
+            "Cursor `{pipeline}` moves ahead from {current} to {target}: {} checkpoints will be skipped and never indexed",
+            target - current
+        ),
+        Some(current) => format!("Cursor `{pipeline}` is already at {current}"),
+    }
+}
+
+/// Ask for confirmation on an interactive terminal
+fn confirm() -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        eyre::bail!("Refusing to change the cursor without a terminal to confirm; pass --force");
+    }
+
+    eprint!("Continue? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(pipeline: &str, updated_at: Option<NaiveDateTime>) -> CursorRecord {
+        CursorRecord {
+            pipeline: pipeline.to_string(),
+            checkpoint_sequence: 100,
+            updated_at,
+        }
+    }
+
+    #[test]
+    fn test_liveness() {
+        let now =
+            NaiveDateTime::parse_from_str("2026-01-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let idle = [
+            cursor("default", Some(now - chrono::Duration::minutes(10))),
+            cursor("filter:swaps", None),
+        ];
+        let moving = [
+            cursor("default", Some(now - chrono::Duration::minutes(10))),
+            cursor("filter:swaps", Some(now - chrono::Duration::seconds(5))),
+        ];
+
+        assert!(matches!(
+            liveness(Some(true), &idle, now),
+            Liveness::Running(_)
+        ));
+        assert_eq!(liveness(Some(false), &idle, now), Liveness::Stopped);
+        assert_eq!(liveness(None, &idle, now), Liveness::Unknown);
+        assert_eq!(liveness(None, &[], now), Liveness::Unknown);
+        // A moving cursor gives away an indexer whose control endpoint is not reachable
+        for control in [None, Some(false)] {
+            assert_eq!(
+                liveness(control, &moving, now),
+                Liveness::Running(format!(
+                    "cursor `filter:swaps` moved less than {RECENT_CURSOR_SECS}s ago"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe_change() {
+        assert_eq!(
+            describe_change("default", None, 41),
+            "Cursor `default` is not stored yet; indexing will resume at 42"
+        );
+        assert_eq!(
+            describe_change("default", None, u64::MAX),
+            format!(
+                "Cursor `default` is not stored yet; indexing will resume at {}",
+                u64::MAX
+            )
+        );
+        assert_eq!(
+            describe_change("default", Some(100), 90),
+            "Cursor `default` moves back from 100 to 90: 10 checkpoints will be re-indexed"
+        );
+        assert_eq!(
+            describe_change("default", Some(100), 105),
+            "Cursor `default` moves ahead from 100 to 105: 5 checkpoints will be skipped and never indexed"
+        );
+        assert_eq!(
+            describe_change("default", Some(100), 100),
+            "Cursor `default` is already at 100"
+        );
+    }
+}
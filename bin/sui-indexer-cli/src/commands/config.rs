@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use eyre::{Result, WrapErr};
+use sui_indexer_config::ConfigLoader;
+
+use crate::{
+    exit::ExitCode,
+    output::{print_json, Output},
+};
+
+/// Arguments of the `config` command
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check a configuration file and report every problem found
+    Validate {
+        /// File to check (defaults to the `--config` file)
+        file: Option<PathBuf>,
+    },
+    /// Print an example configuration with default values
+    Generate {
+        /// Describe every setting in a comment above it
+        #[arg(long)]
+        with_comments: bool,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Run a `config` subcommand; `default_file` is the global `--config` path
+pub fn run(default_file: &str, args: ConfigArgs, output: Output) -> Result<()> {
+    match args.command {
+        ConfigCommand::Validate { file } => {
+            let file = file.unwrap_or_else(|| PathBuf::from(default_file));
+            if !validate(&file, output)? {
+                ExitCode::Config.exit();
+            }
+        }
+        ConfigCommand::Generate { with_comments, out } => {
+            let example = ConfigLoader::example(with_comments)?;
+            match out {
+                Some(path) => std::fs::write(&path, example)
+                    .wrap_err_with(|| format!("Failed to write {}", path.display()))?,
+                None => print!("{example}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the problems in a configuration file, returning whether it is valid
+fn validate(file: &Path, output: Output) -> Result<bool> {
+    let config = match ConfigLoader::from_file(file) {
+        Ok(config) => config,
+        Err(e) => {
+            if output.is_json() {
+                print_json(&serde_json::json!({
+                    "file": file,
+                    "valid": false,
+                    "errors": [{ "line": null, "message": format!("{e:#}") }],
+                }))?;
+            } else {
+                println!("❌ {}: {e:#}", file.display());
+            }
+            return Ok(false);
+        }
+    };
+
+    let Err(errors) = config.validate() else {
+        if output.is_json() {
+            print_json(&serde_json::json!({ "file": file, "valid": true, "errors": [] }))?;
+        } else {
+            println!("✅ {} is valid", file.display());
+        }
+        return Ok(true);
+    };
+
+    let source = std::fs::read_to_string(file)
+        .wrap_err_with(|| format!("Failed to read {}", file.display()))?;
+    if output.is_json() {
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|error| {
+                serde_json::json!({ "line": error.line_in(&source), "message": error.to_string() })
+            })
+            .collect();
+        print_json(&serde_json::json!({ "file": file, "valid": false, "errors": errors }))?;
+        return Ok(false);
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    for error in &errors {
+        match error.line_in(&source) {
+            Some(line) => {
+                println!("❌ {}:{line}: {error}", file.display());
+                println!("   {line:>4} | {}", lines[line - 1]);
+            }
+            None => println!("❌ {}: {error}", file.display()),
+        }
+    }
+    println!("{} problem(s) found", errors.len());
+    Ok(false)
+}
@@ -0,0 +1,295 @@
+use std::io::{self, Write};
+
+use eyre::{Result, WrapErr};
+use sui_indexer_config::{EventFilter, IndexerConfig};
+use sui_indexer_core::{filter_cursor, SuiClient};
+use sui_indexer_storage::{EventQuery, StorageManager};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::query::{parse_since, print_events, split_event_type, OutputFormat};
+use crate::output::Output;
+
+const HELP: &str = "\
+Commands:
+  events [type=..] [sender=..] [package=..] [module=..] [since=..] [from=..] [to=..] [limit=10]
+                  List stored events, newest first
+  lag             Compare the stored checkpoint with the network tip
+  filters         List configured filters with their latest stored match
+  cursors         Show the stored checkpoint cursors
+  help            Show this help
+  exit            Leave the console";
+
+/// A console command
+enum Command {
+    Events(EventQuery),
+    Lag,
+    Filters,
+    Cursors,
+    Help,
+    Exit,
+}
+
+/// Read commands from stdin and answer them from the configured database
+pub async fn run(config: IndexerConfig, output: Output) -> Result<()> {
+    let storage =
+        StorageManager::new_postgres(config.database.clone(), config.storage.clone()).await?;
+    let mut console = Console {
+        config,
+        storage,
+        client: None,
+        format: match output {
+            Output::Json => OutputFormat::Json,
+            Output::Text => OutputFormat::Table,
+        },
+    };
+
+    println!("Sui Indexer console, type `help` for commands");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("sui-indexer> ");
+        io::stdout().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            return Ok(());
+        };
+
+        let result = match parse(&line) {
+            Ok(Some(Command::Exit)) => return Ok(()),
+            Ok(Some(command)) => console.execute(command).await,
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        // A failed command is reported without leaving the console
+        if let Err(e) = result {
+            println!("error: {e:#}");
+        }
+    }
+}
+
+struct Console {
+    config: IndexerConfig,
+    storage: StorageManager,
+    /// Connected on the first command that needs the node
+    client: Option<SuiClient>,
+    format: OutputFormat,
+}
+
+impl Console {
+    async fn execute(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Events(query) => {
+                let events = self.storage.query_events(&query).await?;
+                print_events(&events, self.format)
+            }
+            Command::Lag => {
+                let stored = self.storage.get_latest_checkpoint().await?;
+                let client = match &self.client {
+                    Some(client) => client,
+                    None => self
+                        .client
+                        .insert(SuiClient::new_grpc_only(self.config.network.clone()).await?),
+                };
+                let network = client.get_latest_checkpoint().await?;
+                match stored {
+                    Some(stored) => println!(
+                        "stored {stored}, network {network}, lag {} checkpoints",
+                        network.saturating_sub(stored)
+                    ),
+                    None => println!("nothing stored yet, network at {network}"),
+                }
+                Ok(())
+            }
+            Command::Filters => {
+                let filters = self.config.events.filters.clone();
+                if filters.is_empty() {
+                    println!("No filters configured, every event is indexed");
+                }
+                for (index, filter) in filters.iter().enumerate() {
+                    let name = filter
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("filters[{index}]"));
+                    let mut query = EventQuery {
+                        event_type: filter.event_type.clone(),
+                        sender: filter.sender.clone(),
+                        package: filter.package.clone(),
+                        module: filter.module.clone(),
+                        limit: Some(1),
+                        ..EventQuery::default()
+                    };
+                    split_event_type(&mut query);
+                    let last = match self.storage.query_events(&query).await?.first() {
+                        Some(event) => format!(
+                            "last match at checkpoint {} ({})",
+                            event.checkpoint_sequence,
+                            event.timestamp.to_rfc3339()
+                        ),
+                        None => "no stored matches".to_string(),
+                    };
+                    println!("{name:<24} {:<48} {last}", describe(filter));
+                    if let Some(cursor) = self.storage.get_cursor(&filter_cursor(&name)).await? {
+                        println!("{:<24} re-indexed up to checkpoint {cursor}", "");
+                    }
+                }
+                Ok(())
+            }
+            Command::Cursors => {
+                let cursors = self.storage.list_cursors().await?;
+                if cursors.is_empty() {
+                    println!("No checkpoint cursors stored");
+                }
+                for cursor in cursors {
+                    println!("{:<24} {:>12}", cursor.pipeline, cursor.checkpoint_sequence);
+                }
+                Ok(())
+            }
+            Command::Help => {
+                println!("{HELP}");
+                Ok(())
+            }
+            Command::Exit => Ok(()),
+        }
+    }
+}
+
+/// Parse one input line, `None` for a blank line
+fn parse(line: &str) -> Result<Option<Command>> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(None);
+    };
+    let args: Vec<&str> = words.collect();
+    let no_args = |command: Command| {
+        if args.is_empty() {
+            Ok(Some(command))
+        } else {
+            eyre::bail!("`{name}` takes no arguments")
+        }
+    };
+
+    match name {
+        "events" => {
+            let mut query = EventQuery {
+                limit: Some(10),
+                ..EventQuery::default()
+            };
+            for arg in &args {
+                let (key, value) = arg
+                    .split_once('=')
+                    .ok_or_else(|| eyre::eyre!("Expected key=value, got `{arg}`"))?;
+                let value = value.to_string();
+                match key {
+                    "type" => query.event_type = Some(value),
+                    "sender" => query.sender = Some(value),
+                    "package" => query.package = Some(value),
+                    "module" => query.module = Some(value),
+                    "since" => query.since = Some(parse_since(&value)?),
+                    "from" => query.from_checkpoint = Some(number(key, &value)?),
+                    "to" => query.to_checkpoint = Some(number(key, &value)?),
+                    "limit" => query.limit = Some(number(key, &value)?),
+                    _ => eyre::bail!("Unknown `events` key `{key}`, see `help`"),
+                }
+            }
+            split_event_type(&mut query);
+            Ok(Some(Command::Events(query)))
+        }
+        "lag" => no_args(Command::Lag),
+        "filters" => no_args(Command::Filters),
+        "cursors" => no_args(Command::Cursors),
+        "help" | "?" => no_args(Command::Help),
+        "exit" | "quit" => no_args(Command::Exit),
+        _ => eyre::bail!("Unknown command `{name}`, see `help`"),
+    }
+}
+
+fn number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .wrap_err_with(|| format!("Invalid `{key}` value `{value}`"))
+}
+
+/// `package::module::type` of a filter, with `*` for unset parts
+fn describe(filter: &EventFilter) -> String {
+    let mut spec = format!(
+        "{}::{}::{}",
+        filter.package.as_deref().unwrap_or("*"),
+        filter.module.as_deref().unwrap_or("*"),
+        filter.event_type.as_deref().unwrap_or("*")
+    );
+    if let Some(sender) = &filter.sender {
+        spec.push_str(&format!(" from {sender}"));
+    }
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::FilterPriority;
+
+    use super::*;
+
+    fn events(line: &str) -> EventQuery {
+        match parse(line) {
+            Ok(Some(Command::Events(query))) => query,
+            _ => panic!("`{line}` should parse as an events command"),
+        }
+    }
+
+    #[test]
+    fn test_events_arguments() {
+        let query = events("events");
+        assert_eq!(query.limit, Some(10));
+        assert!(query.event_type.is_none() && query.from_checkpoint.is_none());
+
+        let query = events("  events type=0x2::pool::SwapEvent from=5 to=9 limit=3 sender=0xa ");
+        assert_eq!(query.package.as_deref(), Some("0x2"));
+        assert_eq!(query.module.as_deref(), Some("pool"));
+        assert_eq!(query.event_type.as_deref(), Some("SwapEvent"));
+        assert_eq!(query.sender.as_deref(), Some("0xa"));
+        assert_eq!(
+            (query.from_checkpoint, query.to_checkpoint),
+            (Some(5), Some(9))
+        );
+        assert_eq!(query.limit, Some(3));
+        assert!(events("events since=2h").since.is_some());
+    }
+
+    #[test]
+    fn test_commands_and_mistakes() {
+        assert!(matches!(parse("   "), Ok(None)));
+        assert!(matches!(parse("lag"), Ok(Some(Command::Lag))));
+        assert!(matches!(parse("?"), Ok(Some(Command::Help))));
+        assert!(matches!(parse("quit"), Ok(Some(Command::Exit))));
+
+        let error = |line: &str| match parse(line) {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("`{line}` should be refused"),
+        };
+        assert_eq!(error("lag now"), "`lag` takes no arguments");
+        assert_eq!(error("select *"), "Unknown command `select`, see `help`");
+        assert_eq!(error("events limit"), "Expected key=value, got `limit`");
+        assert_eq!(error("events limit=ten"), "Invalid `limit` value `ten`");
+        assert_eq!(
+            error("events color=red"),
+            "Unknown `events` key `color`, see `help`"
+        );
+    }
+
+    #[test]
+    fn test_describe_filter() {
+        let mut filter = EventFilter {
+            name: None,
+            package: Some("0x2".to_string()),
+            module: None,
+            event_type: Some("SwapEvent".to_string()),
+            sender: None,
+            priority: FilterPriority::Normal,
+        };
+        assert_eq!(describe(&filter), "0x2::*::SwapEvent");
+        filter.sender = Some("0xa".to_string());
+        assert_eq!(describe(&filter), "0x2::*::SwapEvent from 0xa");
+    }
+}
@@ -0,0 +1,136 @@
+use std::net::SocketAddr;
+
+use eyre::{Result, WrapErr};
+use sui_indexer_config::{ErrorKind, IndexerConfig, WithKind};
+use sui_indexer_core::{AdminClient, IndexerStatus, ReconciliationReport};
+use tracing::info;
+
+use crate::{
+    exit::ExitCode,
+    output::{print_json, Output},
+};
+
+/// Client for the control endpoint configured in `admin.addr`
+fn client(config: &IndexerConfig) -> Result<(AdminClient, SocketAddr)> {
+    let addr = config
+        .admin
+        .addr
+        .ok_or_else(|| eyre::eyre!("admin.addr is not set, so the control endpoint is disabled"))
+        .kind(ErrorKind::Config)?;
+    let token = config
+        .admin
+        .token
+        .as_ref()
+        .map(|token| token.resolve())
+        .transpose()
+        .kind(ErrorKind::Config)?;
+    Ok((AdminClient::new(addr)?.with_token(token), addr))
+}
+
+/// Ask the running indexer to shut down gracefully
+pub async fn stop(config: &IndexerConfig, output: Output) -> Result<()> {
+    let (client, addr) = client(config)?;
+    client
+        .stop()
+        .await
+        .wrap_err_with(|| format!("No running indexer reachable at {addr}"))?;
+    if output.is_json() {
+        return print_json(&serde_json::json!({ "stopping": true }));
+    }
+    info!("🛑 Stop requested; the indexer drains buffered checkpoints and exits");
+    Ok(())
+}
+
+/// Ask the running indexer to re-read its configuration file
+pub async fn reload(config: &IndexerConfig, output: Output) -> Result<()> {
+    let (client, addr) = client(config)?;
+    let report = client
+        .reload()
+        .await
+        .wrap_err_with(|| format!("Failed to reload the indexer at {addr}"))?;
+    if output.is_json() {
+        return print_json(&report);
+    }
+
+    if report.is_empty() {
+        info!("✅ Configuration unchanged");
+    }
+    if !report.applied.is_empty() {
+        info!("✅ Applied: {}", report.applied.join(", "));
+    }
+    if !report.requires_restart.is_empty() {
+        info!(
+            "⚠️ Changed but require a restart: {}",
+            report.requires_restart.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Print the event accounting of the running indexer, failing the check on unexplained deltas
+pub async fn reconcile(config: &IndexerConfig, output: Output) -> Result<()> {
+    let (client, addr) = client(config)?;
+    let report = client
+        .reconciliation()
+        .await
+        .wrap_err_with(|| format!("No running indexer reachable at {addr}"))?;
+    if output.is_json() {
+        print_json(&report)?;
+    } else {
+        print_reconciliation(&report);
+    }
+    if report.has_unexplained() {
+        ExitCode::CheckFailed.exit();
+    }
+    Ok(())
+}
+
+fn print_reconciliation(report: &ReconciliationReport) {
+    println!(
+        "{:<10} {:<24} {:>12} {:>12} {:>12} {:>12} {:>10} {:>9} {:>12}",
+        "",
+        "NAME",
+        "SEEN",
+        "FILTERED",
+        "MATCHED",
+        "STORED",
+        "ABANDONED",
+        "IN FLIGHT",
+        "UNEXPLAINED"
+    );
+    let rows = report
+        .pipelines
+        .iter()
+        .map(|row| ("pipeline", row))
+        .chain(report.filters.iter().map(|row| ("filter", row)));
+    for (kind, row) in rows {
+        let optional =
+            |count: Option<u64>| count.map_or_else(|| "-".to_string(), |c| c.to_string());
+        println!(
+            "{:<10} {:<24} {:>12} {:>12} {:>12} {:>12} {:>10} {:>9} {:>12}{}",
+            kind,
+            row.name,
+            optional(row.seen),
+            optional(row.filtered),
+            row.matched,
+            row.stored,
+            row.abandoned,
+            row.in_flight,
+            row.unexplained,
+            if row.unexplained != 0 { "  ⚠️" } else { "" }
+        );
+    }
+    if report.has_unexplained() {
+        println!("⚠️ Some events are not accounted for: they were lost or stored more than once");
+    } else {
+        println!("✅ Every seen event is filtered out, stored, abandoned for a retry or in flight");
+    }
+}
+
+/// Status of the running indexer, or `None` if none is reachable
+pub async fn status(config: &IndexerConfig) -> Result<Option<IndexerStatus>> {
+    let Ok((client, _)) = client(config) else {
+        return Ok(None);
+    };
+    Ok(client.status().await.ok())
+}
@@ -0,0 +1,34 @@
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::{doctor::Status, Doctor};
+
+use crate::{
+    exit::ExitCode,
+    output::{print_json, Output},
+};
+
+/// Check the node, database and filters of a configuration and print a report
+pub async fn run(config: IndexerConfig, output: Output) -> Result<()> {
+    let report = Doctor::new(config).run().await;
+    if output.is_json() {
+        print_json(&report)?;
+        if report.status() == Status::Red {
+            ExitCode::CheckFailed.exit();
+        }
+        return Ok(());
+    }
+
+    for check in &report.checks {
+        println!("{} {:<12} {}", check.status, check.name, check.detail);
+    }
+
+    match report.status() {
+        Status::Green => println!("\n✅ All checks passed"),
+        Status::Yellow => println!("\n⚠️ Some checks need attention"),
+        Status::Red => {
+            println!("\n❌ Some checks failed");
+            ExitCode::CheckFailed.exit();
+        }
+    }
+    Ok(())
+}
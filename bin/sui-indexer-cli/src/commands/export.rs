@@ -0,0 +1,114 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, ValueEnum};
+use eyre::{Result, WrapErr};
+use futures::{stream::BoxStream, TryStreamExt};
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::CheckpointRange;
+use sui_indexer_storage::{export::ExportRecord, ExportFormat, ExportWriter, StorageManager};
+use tracing::info;
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `export` command
+#[derive(Args)]
+pub struct ExportArgs {
+    /// First checkpoint to export
+    #[arg(long)]
+    from: u64,
+    /// Last checkpoint to export (inclusive)
+    #[arg(long)]
+    to: u64,
+    /// File format: parquet, csv or ndjson
+    #[arg(long, default_value_t = ExportFormat::Parquet)]
+    format: ExportFormat,
+    /// Directory the files are written to
+    #[arg(long)]
+    out: PathBuf,
+    /// Comma-separated data to export
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Data::Events, Data::Transactions])]
+    data: Vec<Data>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Data {
+    Events,
+    Transactions,
+}
+
+/// Write the stored events and transactions of a checkpoint range to files
+pub async fn run(config: IndexerConfig, args: ExportArgs, output: Output) -> Result<()> {
+    let range = CheckpointRange::new(args.from, args.to)?;
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+    std::fs::create_dir_all(&args.out)
+        .wrap_err_with(|| format!("Failed to create {}", args.out.display()))?;
+
+    let file = |name: &str| {
+        args.out.join(format!(
+            "{name}_{}_{}.{}",
+            range.start,
+            range.end,
+            args.format.extension()
+        ))
+    };
+
+    let mut files = Vec::new();
+    if args.data.contains(&Data::Events) {
+        let path = file("events");
+        let rows = export(
+            storage.export_events(range.start, range.end),
+            &path,
+            args.format,
+        )
+        .await?;
+        files.push(("events", path, rows));
+    }
+    if args.data.contains(&Data::Transactions) {
+        let path = file("transactions");
+        let rows = export(
+            storage.export_transactions(range.start, range.end),
+            &path,
+            args.format,
+        )
+        .await?;
+        files.push(("transactions", path, rows));
+    }
+
+    if output.is_json() {
+        let files: Vec<_> = files
+            .iter()
+            .map(|(data, path, rows)| serde_json::json!({ "data": data, "path": path, "rows": rows }))
+            .collect();
+        return print_json(&files);
+    }
+    for (data, path, rows) in files {
+        println!("✅ Exported {rows} {data} to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Stream records into a new file, returning the number of rows written
+async fn export<R: ExportRecord>(
+    mut records: BoxStream<'_, Result<R>>,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<u64> {
+    let file =
+        File::create(path).wrap_err_with(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = ExportWriter::new(BufWriter::new(file), format)?;
+
+    while let Some(record) = records.try_next().await? {
+        writer.write(record)?;
+        if writer.rows() % 100_000 == 0 {
+            info!(rows = writer.rows(), file = %path.display(), "Exporting");
+        }
+    }
+
+    let rows = writer.rows();
+    writer.finish()?;
+    Ok(rows)
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand, ValueEnum};
+use eyre::Result;
+use sui_indexer_config::{filter_dir, IndexerConfig};
+use sui_indexer_core::{filter_test::EventSource, FilterTest, SuiClient};
+use sui_indexer_storage::StorageManager;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    exit::interrupted,
+    output::{print_json, Output},
+};
+
+/// Arguments of the `filters` command
+#[derive(Args)]
+pub struct FiltersArgs {
+    #[command(subcommand)]
+    command: FiltersCommand,
+}
+
+#[derive(Subcommand)]
+enum FiltersCommand {
+    /// Count the events a filter file matches in recent checkpoints, without storing anything
+    Test {
+        /// TOML or JSON filter file, in the `events.filters_dir` format
+        #[arg(long)]
+        file: PathBuf,
+        /// Number of checkpoints up to the latest one to scan
+        #[arg(long, default_value_t = 500)]
+        last: u64,
+        /// Read checkpoints from the node or stored raw events from the database
+        #[arg(long, value_enum, default_value_t = Source::Node)]
+        source: Source,
+        /// Example events printed per filter
+        #[arg(long, default_value_t = 3)]
+        examples: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Source {
+    Node,
+    Db,
+}
+
+/// Run a `filters` subcommand
+pub async fn run(config: IndexerConfig, args: FiltersArgs, output: Output) -> Result<()> {
+    match args.command {
+        FiltersCommand::Test {
+            file,
+            last,
+            source,
+            examples,
+        } => {
+            let filters = filter_dir::load_filter_file(&file)?;
+            if filters.is_empty() {
+                eyre::bail!("{} defines no filters", file.display());
+            }
+
+            let source = match source {
+                Source::Node => EventSource::Node(SuiClient::new(config.network.clone()).await?),
+                Source::Db => EventSource::Database(
+                    StorageManager::new_postgres(config.database, config.storage).await?,
+                ),
+            };
+            let test = FilterTest::new(source, filters, last)
+                .with_examples(examples)
+                .with_concurrency(config.runtime.fetch_concurrency);
+
+            let shutdown = CancellationToken::new();
+            let signal_shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    info!("✋ Received shutdown signal (Ctrl+C), stopping filter test");
+                    signal_shutdown.cancel();
+                }
+            });
+
+            let report = test.run(shutdown.clone()).await?;
+            if shutdown.is_cancelled() {
+                return Err(interrupted(format!(
+                    "Filter test interrupted after {} events",
+                    report.events_scanned
+                )));
+            }
+            if output.is_json() {
+                return print_json(&report);
+            }
+
+            println!(
+                "Scanned {} events in checkpoints {}..={}",
+                report.events_scanned, report.range.start, report.range.end
+            );
+            if report.events_without_raw > 0 {
+                println!(
+                    "⚠️ {} stored events have no raw event data and were skipped",
+                    report.events_without_raw
+                );
+            }
+            for filter in &report.filters {
+                println!("\n{:<24} {:>10} matches", filter.filter, filter.matches);
+                for example in &filter.examples {
+                    println!(
+                        "  {} {}",
+                        example.checkpoint,
+                        serde_json::to_string(&example.event)?
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
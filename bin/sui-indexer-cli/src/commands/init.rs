@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use eyre::{Result, WrapErr};
+use sui_indexer_config::scaffold::{self, Starter, STARTER_DATABASE_URL, STARTER_FILTERS_DIR};
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `init` command
+#[derive(Args)]
+pub struct InitArgs {
+    /// Protocol the starter filters select
+    #[arg(long, value_enum, default_value_t = Protocol::Custom)]
+    protocol: Protocol,
+    /// Overwrite existing files
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Protocol {
+    Navi,
+    Cetus,
+    Custom,
+}
+
+impl From<Protocol> for Starter {
+    fn from(protocol: Protocol) -> Self {
+        match protocol {
+            Protocol::Navi => Self::Navi,
+            Protocol::Cetus => Self::Cetus,
+            Protocol::Custom => Self::Custom,
+        }
+    }
+}
+
+/// Write a starter configuration to `config_file` and a filter file next to it
+pub fn run(config_file: &str, args: InitArgs, output: Output) -> Result<()> {
+    let scaffold = scaffold::scaffold(args.protocol.into())?;
+    let config_file = PathBuf::from(config_file);
+    let filters_dir = config_file
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(STARTER_FILTERS_DIR);
+    let filter_file = filters_dir.join(&scaffold.filter_file);
+
+    if !args.force {
+        for path in [&config_file, &filter_file] {
+            if path.exists() {
+                eyre::bail!(
+                    "{} already exists; pass --force to overwrite",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    std::fs::create_dir_all(&filters_dir)
+        .wrap_err_with(|| format!("Failed to create {}", filters_dir.display()))?;
+    std::fs::write(&config_file, &scaffold.config)
+        .wrap_err_with(|| format!("Failed to write {}", config_file.display()))?;
+    std::fs::write(&filter_file, &scaffold.filters)
+        .wrap_err_with(|| format!("Failed to write {}", filter_file.display()))?;
+
+    if output.is_json() {
+        return print_json(&serde_json::json!({
+            "config": config_file,
+            "filters": filter_file,
+            "database_url": STARTER_DATABASE_URL,
+        }));
+    }
+
+    println!("✅ Wrote {}", config_file.display());
+    println!("✅ Wrote {}", filter_file.display());
+    println!();
+    println!("Next steps:");
+    println!("  docker run -d --name sui-indexer-db -p 5432:5432 -e POSTGRES_PASSWORD=postgres -e POSTGRES_DB=sui_indexer postgres:16");
+    println!("  sui-indexer -c {} doctor", config_file.display());
+    println!("  sui-indexer -c {} start", config_file.display());
+    Ok(())
+}
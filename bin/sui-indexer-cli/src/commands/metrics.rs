@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use eyre::{Result, WrapErr};
+use sui_indexer_core::grafana_dashboard;
+
+/// Arguments of the `metrics` command
+#[derive(Args)]
+pub struct MetricsArgs {
+    #[command(subcommand)]
+    command: MetricsCommand,
+}
+
+#[derive(Subcommand)]
+enum MetricsCommand {
+    /// Print a Grafana dashboard charting the exported metrics
+    Dashboard {
+        /// Dashboard title
+        #[arg(long, default_value = "Sui Indexer")]
+        title: String,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Run a `metrics` subcommand
+pub fn run(args: MetricsArgs) -> Result<()> {
+    match args.command {
+        MetricsCommand::Dashboard { title, out } => {
+            let dashboard = serde_json::to_string_pretty(&grafana_dashboard(&title))?;
+            match out {
+                Some(path) => std::fs::write(&path, dashboard + "\n")
+                    .wrap_err_with(|| format!("Failed to write {}", path.display()))?,
+                None => println!("{dashboard}"),
+            }
+        }
+    }
+    Ok(())
+}
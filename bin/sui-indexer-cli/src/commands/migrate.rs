@@ -0,0 +1,88 @@
+use clap::{Args, Subcommand};
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::audit;
+use sui_indexer_storage::{PostgresStorage, StorageManager};
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `migrate` command
+#[derive(Args)]
+pub struct MigrateArgs {
+    #[command(subcommand)]
+    command: MigrateCommand,
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// List known migrations and whether they are applied
+    Status,
+    /// Apply pending migrations
+    Up {
+        /// Last migration version to apply (defaults to all)
+        #[arg(long)]
+        target: Option<i64>,
+    },
+    /// Revert applied migrations
+    Down {
+        /// Revert every migration newer than this version (defaults to only the latest)
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+/// Run a `migrate` subcommand against the configured database
+pub async fn run(config: IndexerConfig, args: MigrateArgs, output: Output) -> Result<()> {
+    let storage = PostgresStorage::new(config.database, config.storage).await?;
+
+    match args.command {
+        MigrateCommand::Status => {
+            let migrations = storage.migration_status().await?;
+            if output.is_json() {
+                return print_json(&migrations);
+            }
+            for migration in migrations {
+                let state = match migration.applied_at {
+                    _ if migration.dirty => "failed".to_string(),
+                    Some(at) => format!("applied {}", at.format("%Y-%m-%d %H:%M:%S")),
+                    None => "pending".to_string(),
+                };
+                println!(
+                    "{:<16} {:<28} {state}",
+                    migration.version, migration.description
+                );
+            }
+        }
+        MigrateCommand::Up { target } => {
+            let applied = storage.migrate_up(target).await?;
+            if output.is_json() {
+                return print_json(&serde_json::json!({ "applied": applied }));
+            }
+            if applied.is_empty() {
+                println!("✅ No pending migrations");
+            }
+            for version in applied {
+                println!("✅ Applied {version}");
+            }
+        }
+        MigrateCommand::Down { target } => {
+            let reverted = storage.migrate_down(target).await?;
+            if !reverted.is_empty() {
+                // Lost along with the table when the audit log's own migration is reverted
+                let details = serde_json::json!({ "target": target, "reverted": reverted });
+                let storage = StorageManager::from_backend(storage);
+                audit::record(&storage, audit::cli_entry("migrate_down", details)).await;
+            }
+            if output.is_json() {
+                return print_json(&serde_json::json!({ "reverted": reverted }));
+            }
+            if reverted.is_empty() {
+                println!("No migrations to revert");
+            }
+            for version in reverted {
+                println!("↩️ Reverted {version}");
+            }
+        }
+    }
+    Ok(())
+}
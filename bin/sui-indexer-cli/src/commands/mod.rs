@@ -0,0 +1,28 @@
+pub mod api_key;
+pub mod audit;
+pub mod backfill;
+pub mod benchmark;
+pub mod checkpoint;
+pub mod config;
+pub mod console;
+pub mod control;
+pub mod doctor;
+pub mod export;
+pub mod filters;
+pub mod init;
+pub mod metrics;
+pub mod migrate;
+pub mod outbox;
+pub mod prune;
+pub mod query;
+pub mod reindex_filter;
+pub mod replay;
+pub mod schema;
+pub mod snapshot;
+pub mod start;
+pub mod stats;
+pub mod status;
+pub mod tail;
+pub mod verify;
+pub mod watchlist;
+pub mod webhook_deliveries;
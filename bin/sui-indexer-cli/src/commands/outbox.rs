@@ -0,0 +1,30 @@
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::StorageManager;
+
+use crate::output::{print_json, Output};
+
+/// Show the messages waiting in the outbox and the dead letters of each sink
+pub async fn run(config: IndexerConfig, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+    let backlog = storage.outbox_backlog().await?;
+
+    if output.is_json() {
+        return print_json(&backlog);
+    }
+    if backlog.is_empty() {
+        println!("No messages waiting in the outbox");
+    }
+    for sink in backlog {
+        println!(
+            "{:<16} {:>8} messages  {:>6} dead letters  oldest {}  {:>3} failed attempts  {}",
+            sink.sink,
+            sink.messages,
+            sink.dead_letters,
+            sink.oldest.format("%Y-%m-%d %H:%M:%S"),
+            sink.attempts,
+            sink.last_error.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::audit;
+use sui_indexer_storage::StorageManager;
+
+use super::query::parse_since;
+use crate::output::{print_json, Output};
+
+/// Arguments of the `prune` command
+#[derive(Args)]
+pub struct PruneArgs {
+    /// Remove data older than this (RFC 3339, or an age such as `90d`)
+    #[arg(long, value_parser = parse_since)]
+    older_than: DateTime<Utc>,
+    /// Only report what would be removed
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Remove events and transactions past the given age from the configured database
+pub async fn run(config: IndexerConfig, args: PruneArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+
+    let (report, verb) = if args.dry_run {
+        (
+            storage.prunable_before(args.older_than).await?,
+            "Would remove",
+        )
+    } else {
+        let report = storage.prune_before(args.older_than).await?;
+        let details = serde_json::json!({
+            "older_than": args.older_than,
+            "events": report.events,
+            "transactions": report.transactions,
+            "partitions": report.partitions,
+        });
+        audit::record(&storage, audit::cli_entry("prune", details)).await;
+        (report, "Removed")
+    };
+
+    if output.is_json() {
+        return print_json(&serde_json::json!({
+            "dry_run": args.dry_run,
+            "older_than": args.older_than,
+            "events": report.events,
+            "transactions": report.transactions,
+            "partitions": report.partitions,
+        }));
+    }
+    println!(
+        "{verb} {} events and {} transactions older than {}",
+        report.events, report.transactions, args.older_than
+    );
+    if !report.partitions.is_empty() {
+        println!(
+            "{verb} {} partition(s): {}",
+            report.partitions.len(),
+            report.partitions.join(", ")
+        );
+    }
+    Ok(())
+}
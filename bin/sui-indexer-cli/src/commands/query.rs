@@ -0,0 +1,353 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use eyre::{Result, WrapErr};
+use serde::Serialize;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::{
+    EventQuery, EventRecord, MoveCallQuery, MoveCallRecord, ObjectVersionQuery,
+    ObjectVersionRecord, StorageManager,
+};
+
+use crate::output::Output;
+
+/// Arguments of the `query` command
+#[derive(Args)]
+pub struct QueryArgs {
+    #[command(subcommand)]
+    target: QueryTarget,
+}
+
+#[derive(Subcommand)]
+enum QueryTarget {
+    /// List stored events, newest first
+    Events(EventsArgs),
+    /// List stored Move calls, newest first
+    MoveCalls(MoveCallsArgs),
+    /// List stored versions of a tracked object, newest first
+    ObjectVersions(ObjectVersionsArgs),
+}
+
+#[derive(Args)]
+struct EventsArgs {
+    /// Event struct name, or a full `package::module::Name` type
+    #[arg(long = "type")]
+    event_type: Option<String>,
+    /// Transaction sender address
+    #[arg(long)]
+    sender: Option<String>,
+    /// Package that defines the event
+    #[arg(long)]
+    package: Option<String>,
+    /// Module that emitted the event
+    #[arg(long)]
+    module: Option<String>,
+    /// Only events since an RFC 3339 time or a relative age such as `30m`, `2h` or `7d`
+    #[arg(long, value_parser = parse_since)]
+    since: Option<DateTime<Utc>>,
+    /// Maximum number of events to print
+    #[arg(long, default_value_t = 20)]
+    limit: u32,
+    /// Result format (defaults to `json` with `--output json`, otherwise `table`)
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+#[derive(Args)]
+struct MoveCallsArgs {
+    /// Called function as `package::module::function`, or a `package::module` prefix
+    #[arg(long)]
+    target: Option<String>,
+    /// Transaction sender address
+    #[arg(long)]
+    sender: Option<String>,
+    /// Only calls since an RFC 3339 time or a relative age such as `30m`, `2h` or `7d`
+    #[arg(long, value_parser = parse_since)]
+    since: Option<DateTime<Utc>>,
+    /// Maximum number of calls to print
+    #[arg(long, default_value_t = 20)]
+    limit: u32,
+    /// Result format (defaults to `json` with `--output json`, otherwise `table`)
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+#[derive(Args)]
+struct ObjectVersionsArgs {
+    /// Object listed in `objects.ids`
+    object_id: String,
+    /// Only versions since an RFC 3339 time or a relative age such as `30m`, `2h` or `7d`
+    #[arg(long, value_parser = parse_since)]
+    since: Option<DateTime<Utc>>,
+    /// Maximum number of versions to print
+    #[arg(long, default_value_t = 20)]
+    limit: u32,
+    /// Result format (defaults to `json` with `--output json`, otherwise `table`)
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(super) enum OutputFormat {
+    Json,
+    Table,
+    Csv,
+}
+
+/// Run a query against the configured database and print the results
+pub async fn run(config: IndexerConfig, args: QueryArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+
+    let default_format = match output {
+        Output::Json => OutputFormat::Json,
+        Output::Text => OutputFormat::Table,
+    };
+    match args.target {
+        QueryTarget::Events(args) => {
+            let events = storage.query_events(&args.to_query()).await?;
+            print_events(&events, args.format.unwrap_or(default_format))
+        }
+        QueryTarget::MoveCalls(args) => {
+            let calls = storage.query_move_calls(&args.to_query()?).await?;
+            print_rows(
+                &calls,
+                CALL_HEADERS,
+                call_columns,
+                "calls",
+                args.format.unwrap_or(default_format),
+            )
+        }
+        QueryTarget::ObjectVersions(args) => {
+            let versions = storage.query_object_versions(&args.to_query()?).await?;
+            print_rows(
+                &versions,
+                OBJECT_HEADERS,
+                object_columns,
+                "versions",
+                args.format.unwrap_or(default_format),
+            )
+        }
+    }
+}
+
+impl EventsArgs {
+    fn to_query(&self) -> EventQuery {
+        let mut query = EventQuery {
+            event_type: self.event_type.clone(),
+            sender: self.sender.clone(),
+            package: self.package.clone(),
+            module: self.module.clone(),
+            since: self.since,
+            limit: Some(self.limit),
+            ..EventQuery::default()
+        };
+        split_event_type(&mut query);
+        query
+    }
+}
+
+impl MoveCallsArgs {
+    fn to_query(&self) -> Result<MoveCallQuery> {
+        let mut query = MoveCallQuery {
+            sender: self.sender.clone(),
+            since: self.since,
+            limit: Some(self.limit),
+            ..MoveCallQuery::default()
+        };
+        if let Some(target) = &self.target {
+            let mut parts = target.split("::").map(str::to_string);
+            query.package = parts.next();
+            query.module = parts.next();
+            query.function = parts.next();
+            if parts.next().is_some() {
+                eyre::bail!("Invalid target `{target}`: expected `package::module::function`");
+            }
+        }
+        Ok(query)
+    }
+}
+
+impl ObjectVersionsArgs {
+    fn to_query(&self) -> Result<ObjectVersionQuery> {
+        Ok(ObjectVersionQuery {
+            object_id: Some(normalize_object_id(&self.object_id)?),
+            since: self.since,
+            limit: Some(self.limit),
+            ..ObjectVersionQuery::default()
+        })
+    }
+}
+
+/// Object ID in the full form it is stored in, so `0x5` finds `0x00…05`
+fn normalize_object_id(id: &str) -> Result<String> {
+    let hex = id.strip_prefix("0x").unwrap_or(id);
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        eyre::bail!("Invalid object ID `{id}`: expected up to 64 hex digits");
+    }
+    Ok(format!("0x{:0>64}", hex.to_ascii_lowercase()))
+}
+
+/// Split a full `package::module::Name` event type, as only the name is stored as the type
+pub(super) fn split_event_type(query: &mut EventQuery) {
+    let Some(full) = query.event_type.clone() else {
+        return;
+    };
+    if let [package, module, name] = full.split("::").collect::<Vec<_>>()[..] {
+        query.package.get_or_insert_with(|| package.to_string());
+        query.module.get_or_insert_with(|| module.to_string());
+        query.event_type = Some(name.to_string());
+    }
+}
+
+/// Parse an absolute RFC 3339 time or an age relative to now
+pub(super) fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let amount = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = &value[amount.len()..];
+    let amount: i64 = amount
+        .parse()
+        .wrap_err_with(|| format!("Invalid time `{value}`: expected RFC 3339 or e.g. `2h`"))?;
+    let age = match unit {
+        "s" => Duration::seconds(amount),
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        _ => eyre::bail!("Invalid time unit in `{value}`: expected one of s, m, h, d"),
+    };
+    Ok(Utc::now() - age)
+}
+
+pub(super) fn print_events(events: &[EventRecord], format: OutputFormat) -> Result<()> {
+    print_rows(events, HEADERS, columns, "events", format)
+}
+
+/// Print `records` as JSON, or as CSV or a table of `headers` and their `columns`
+fn print_rows<T: Serialize, const N: usize>(
+    records: &[T],
+    headers: [&str; N],
+    columns: impl Fn(&T) -> [String; N],
+    noun: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut out = io::stdout().lock();
+
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "{}", headers.join(","))?;
+            for record in records {
+                let row: Vec<String> = columns(record).iter().map(|c| csv_field(c)).collect();
+                writeln!(out, "{}", row.join(","))?;
+            }
+        }
+        OutputFormat::Table => {
+            let rows: Vec<[String; N]> = records.iter().map(columns).collect();
+            let mut widths = headers.map(str::len);
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+
+            let line = |cells: &[&str]| {
+                cells
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{cell:<width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            writeln!(out, "{}", line(&headers))?;
+            for row in &rows {
+                let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+                writeln!(out, "{}", line(&cells).trim_end())?;
+            }
+            writeln!(out, "({} {noun})", records.len())?;
+        }
+    }
+    Ok(())
+}
+
+const HEADERS: [&str; 7] = [
+    "checkpoint",
+    "timestamp",
+    "transaction",
+    "type",
+    "module",
+    "sender",
+    "fields",
+];
+
+fn columns(event: &EventRecord) -> [String; 7] {
+    [
+        event.checkpoint_sequence.to_string(),
+        event.timestamp.to_rfc3339(),
+        event.transaction_digest.clone(),
+        event.event_type.clone(),
+        format!("{}::{}", event.package_id, event.module_name),
+        event.sender.clone(),
+        event.fields.to_string(),
+    ]
+}
+
+const CALL_HEADERS: [&str; 6] = [
+    "checkpoint",
+    "timestamp",
+    "transaction",
+    "target",
+    "type_arguments",
+    "sender",
+];
+
+fn call_columns(call: &MoveCallRecord) -> [String; 6] {
+    [
+        call.checkpoint_sequence.to_string(),
+        call.timestamp.to_rfc3339(),
+        format!("{}#{}", call.transaction_digest, call.command_index),
+        call.target(),
+        call.type_arguments.join(", "),
+        call.sender.clone(),
+    ]
+}
+
+const OBJECT_HEADERS: [&str; 7] = [
+    "checkpoint",
+    "timestamp",
+    "version",
+    "kind",
+    "transaction",
+    "owner",
+    "contents",
+];
+
+fn object_columns(version: &ObjectVersionRecord) -> [String; 7] {
+    [
+        version.checkpoint_sequence.to_string(),
+        version.timestamp.to_rfc3339(),
+        version.version.to_string(),
+        version.change_kind.clone(),
+        version.transaction_digest.clone(),
+        version.owner.clone().unwrap_or_default(),
+        version
+            .contents
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+    ]
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
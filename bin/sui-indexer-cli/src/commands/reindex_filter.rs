@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use clap::Args;
+use eyre::Result;
+use sui_indexer_core::{audit, IndexerCore};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use super::backfill::report;
+use crate::{
+    exit::interrupted,
+    output::{print_json, Output},
+};
+
+/// Interval between progress reports
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Arguments of the `reindex-filter` command
+#[derive(Args)]
+pub struct ReindexFilterArgs {
+    /// Name of the configured filter to re-index
+    #[arg(long)]
+    name: String,
+    /// First checkpoint to re-index; later runs resume after the filter's cursor
+    #[arg(long)]
+    from: u64,
+}
+
+/// Backfill one filter up to where the live indexer took it over, reporting progress until it is complete
+pub async fn run(indexer: IndexerCore, args: ReindexFilterArgs, output: Output) -> Result<()> {
+    indexer.verify_chain().await?;
+    let Some(backfill) = indexer.reindex_filter(&args.name, args.from).await? else {
+        if output.is_json() {
+            return print_json(&serde_json::json!({ "filter": args.name, "reindexed": 0 }));
+        }
+        info!(
+            "✅ Filter `{}` is already re-indexed up to where the live indexer took it over",
+            args.name
+        );
+        return Ok(());
+    };
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("✋ Received shutdown signal (Ctrl+C), stopping re-index");
+            signal_shutdown.cancel();
+        }
+    });
+
+    let range = backfill.range().clone();
+    let total = range.len();
+    // Recorded up front: the filter's stored events are replaced as the re-index goes
+    let details = serde_json::json!({ "filter": args.name, "from": range.start, "to": range.end });
+    audit::record(
+        &indexer.storage(),
+        audit::cli_entry("reindex_filter", details),
+    )
+    .await;
+    info!(
+        "🔁 Re-indexing `{}` over checkpoints {}..={}",
+        args.name, range.start, range.end
+    );
+
+    let progress = backfill.progress();
+    let reporter = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            report(&progress.borrow(), total);
+        }
+    });
+
+    let stats = backfill.run(shutdown.clone()).await;
+    reporter.abort();
+    let stats = stats?;
+
+    if shutdown.is_cancelled() {
+        return Err(interrupted(format!(
+            "Re-index of `{}` interrupted after {} checkpoints; run it again to resume",
+            args.name, stats.total_processed
+        )));
+    }
+
+    if output.is_json() {
+        return print_json(&serde_json::json!({ "filter": args.name, "stats": stats }));
+    }
+    info!(
+        "✅ Re-indexed `{}` over {} checkpoints at {:.1} checkpoints/s",
+        args.name, stats.total_processed, stats.processing_rate
+    );
+    Ok(())
+}
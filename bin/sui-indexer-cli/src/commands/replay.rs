@@ -0,0 +1,72 @@
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::{
+    replay::{self, DEFAULT_PROCESSOR},
+    CheckpointRange, ReplayEngine,
+};
+use sui_indexer_storage::StorageManager;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    exit::interrupted,
+    output::{print_json, Output},
+};
+
+/// Arguments of the `replay` command
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// First checkpoint to replay
+    #[arg(long)]
+    from: u64,
+    /// Last checkpoint to replay (inclusive)
+    #[arg(long)]
+    to: u64,
+    /// Processor to run: `default` or the name of a single protocol decoder
+    #[arg(long, default_value = DEFAULT_PROCESSOR)]
+    processor: String,
+    /// Table the processed events are written to, starting with `replay_`; its rows for the range are replaced
+    #[arg(long)]
+    output_table: String,
+    /// Events processed and stored per write (defaults to `events.batch_size`)
+    #[arg(long)]
+    batch_size: Option<usize>,
+}
+
+/// Re-run a processor over stored events of a checkpoint range
+pub async fn run(config: IndexerConfig, args: ReplayArgs, output: Output) -> Result<()> {
+    let range = CheckpointRange::new(args.from, args.to)?;
+    let processor = replay::named_processor(&config, &args.processor)?;
+    let batch_size = args.batch_size.unwrap_or(config.events.batch_size);
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+
+    let engine =
+        ReplayEngine::new(storage, processor, range, args.output_table).with_batch_size(batch_size);
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("✋ Received shutdown signal (Ctrl+C), stopping replay");
+            signal_shutdown.cancel();
+        }
+    });
+
+    let report = engine.run(shutdown.clone()).await?;
+    if shutdown.is_cancelled() {
+        return Err(interrupted(format!(
+            "Replay interrupted after {} events",
+            report.events_read
+        )));
+    }
+    if output.is_json() {
+        return print_json(&report);
+    }
+
+    println!(
+        "✅ Replayed {} events with `{}`: {} written, {} skipped without raw event data",
+        report.events_read, args.processor, report.events_written, report.events_without_raw
+    );
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use eyre::{Result, WrapErr};
+use sui_indexer_storage::migrations;
+
+use crate::output::Output;
+
+/// Arguments of the `schema` command
+#[derive(Args)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    command: SchemaCommand,
+}
+
+#[derive(Subcommand)]
+enum SchemaCommand {
+    /// Print the tables, columns and indexes as Markdown, or as JSON with `--output json`
+    Dump {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Run a `schema` subcommand
+pub fn run(args: SchemaArgs, output: Output) -> Result<()> {
+    match args.command {
+        SchemaCommand::Dump { out } => {
+            let schema = migrations::schema_doc()?;
+            let dump = if output.is_json() {
+                serde_json::to_string_pretty(&schema)?
+            } else {
+                schema.to_markdown().trim_end().to_string()
+            };
+            match out {
+                Some(path) => std::fs::write(&path, dump + "\n")
+                    .wrap_err_with(|| format!("Failed to write {}", path.display()))?,
+                None => println!("{dump}"),
+            }
+        }
+    }
+    Ok(())
+}
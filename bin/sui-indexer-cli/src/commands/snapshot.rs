@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::audit;
+use sui_indexer_storage::{PostgresStorage, SnapshotManifest, StorageManager};
+
+use super::control;
+use crate::output::{print_json, Output};
+
+/// Arguments of the `snapshot` command
+#[derive(Args)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    command: SnapshotCommand,
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// Write the indexed data, cursors and schema version to a directory
+    Create {
+        /// Directory to write the snapshot to
+        #[arg(long)]
+        dir: PathBuf,
+    },
+    /// Load a snapshot into an empty database
+    Restore {
+        /// Directory holding the snapshot
+        #[arg(long)]
+        dir: PathBuf,
+    },
+}
+
+/// Run a `snapshot` subcommand against the configured database
+pub async fn run(config: IndexerConfig, args: SnapshotArgs, output: Output) -> Result<()> {
+    let manifest = match args.command {
+        SnapshotCommand::Create { dir } => {
+            let storage = PostgresStorage::new(config.database, config.storage).await?;
+            let manifest = storage.create_snapshot(&dir).await?;
+            if !output.is_json() {
+                println!("📸 Snapshot written to {}", dir.display());
+            }
+            manifest
+        }
+        SnapshotCommand::Restore { dir } => {
+            if control::status(&config).await?.is_some() {
+                eyre::bail!(
+                    "An indexer is running against this database; stop it before restoring"
+                );
+            }
+            let storage = PostgresStorage::new(config.database, config.storage).await?;
+            let manifest = storage.restore_snapshot(&dir).await?;
+            let details = serde_json::json!({
+                "dir": &dir,
+                "created_at": manifest.created_at,
+                "schema_version": manifest.schema_version,
+                "tables": &manifest.tables,
+            });
+            let storage = StorageManager::from_backend(storage);
+            audit::record(&storage, audit::cli_entry("snapshot_restore", details)).await;
+            if !output.is_json() {
+                println!("✅ Snapshot restored from {}", dir.display());
+            }
+            manifest
+        }
+    };
+
+    if output.is_json() {
+        return print_json(&manifest);
+    }
+    print_manifest(&manifest);
+    Ok(())
+}
+
+fn print_manifest(manifest: &SnapshotManifest) {
+    println!(
+        "   Taken:        {}",
+        manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    println!("   Schema:       {}", manifest.schema_version);
+    for (table, rows) in &manifest.tables {
+        println!("   Table:        {table} ({rows} rows)");
+    }
+    for cursor in &manifest.cursors {
+        println!(
+            "   Cursor:       {} at {}",
+            cursor.pipeline, cursor.checkpoint_sequence
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+use clap::Args;
+use eyre::Result;
+use sui_indexer_core::{http, IndexerCore};
+use tracing::error;
+
+use crate::logging::{self, LogFilterHandle};
+
+/// Arguments of the `start` command
+#[derive(Args)]
+pub struct StartArgs {
+    /// Create this file once the indexer is ready and remove it on shutdown
+    #[arg(long)]
+    readiness_file: Option<PathBuf>,
+    /// Serve `/healthz` and `/readyz` probes on this port on all interfaces
+    #[arg(long)]
+    health_port: Option<u16>,
+}
+
+/// Initialize the indexer and run it until it is stopped
+///
+/// Readiness is signalled only after migrations ran, the node answered and the
+/// first checkpoint was fetched, so orchestrators hold traffic and alerts
+/// during a cold start.
+pub async fn run(
+    mut indexer: IndexerCore,
+    config_path: &str,
+    log_filter: LogFilterHandle,
+    args: StartArgs,
+) -> Result<()> {
+    indexer = indexer.with_recent_errors(logging::recent_errors());
+    if let Some(path) = args.readiness_file {
+        indexer = indexer.with_readiness_file(path);
+    }
+    // Bound before initializing so liveness probes pass while migrations run
+    if let Some(port) = args.health_port {
+        let addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+        let probe = indexer.health_probe();
+        tokio::spawn(async move {
+            if let Err(e) = http::serve_health(addr, probe).await {
+                error!(%addr, error = %e, "Health endpoint failed");
+            }
+        });
+    }
+
+    // Initialize the indexer (run migrations, etc.)
+    indexer.initialize().await?;
+
+    // Apply filter, batch and log level changes on SIGHUP or config file edits
+    indexer.watch_config_file(config_path);
+    logging::follow_log_level(log_filter, indexer.subscribe_config());
+
+    // Start the indexer (this will run the main event loop)
+    indexer.start().await
+}
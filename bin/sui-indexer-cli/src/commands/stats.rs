@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::{StorageManager, ValueCount};
+
+use super::query::parse_since;
+use crate::output::{print_json, Output};
+
+/// Arguments of the `stats` command
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Summarize data since an RFC 3339 time or a relative age such as `30m`, `24h` or `7d`
+    #[arg(long, value_parser = parse_since, default_value = "24h")]
+    since: DateTime<Utc>,
+    /// Show cumulative event counts as of a checkpoint instead of a time window
+    #[arg(long, conflicts_with = "since")]
+    at_checkpoint: Option<u64>,
+    /// Number of event types and packages to list
+    #[arg(long, default_value_t = 10)]
+    top: u32,
+}
+
+/// Summarize the indexed data of a time window
+pub async fn run(config: IndexerConfig, args: StatsArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+    if let Some(checkpoint) = args.at_checkpoint {
+        return totals_at(&storage, checkpoint, args.top, output).await;
+    }
+    let stats = storage.stats_since(args.since, args.top).await?;
+    if output.is_json() {
+        return print_json(&stats);
+    }
+
+    println!("Since {}", stats.since.to_rfc3339());
+    println!();
+    println!("Events        {}", stats.events);
+    println!(
+        "Transactions  {} ({} failed)",
+        stats.transactions, stats.failed_transactions
+    );
+    println!(
+        "Average lag   {}",
+        stats
+            .average_lag_secs
+            .map_or_else(|| "-".to_string(), |lag| format!("{lag:.1}s"))
+    );
+    print_counts("EVENT TYPE", &stats.event_types);
+    print_counts("PACKAGE", &stats.packages);
+    Ok(())
+}
+
+/// Print the event counts of everything indexed up to `checkpoint`
+async fn totals_at(
+    storage: &StorageManager,
+    checkpoint: u64,
+    top: u32,
+    output: Output,
+) -> Result<()> {
+    let totals = storage.totals_at(checkpoint, top).await?;
+    if output.is_json() {
+        return print_json(&totals);
+    }
+
+    println!("As of checkpoint {}", totals.checkpoint);
+    println!();
+    println!("Events        {}", totals.events);
+    print_counts("EVENT TYPE", &totals.event_types);
+    print_counts("PACKAGE", &totals.packages);
+    Ok(())
+}
+
+fn print_counts(header: &str, counts: &[ValueCount]) {
+    if counts.is_empty() {
+        return;
+    }
+    let width = counts
+        .iter()
+        .map(|count| count.value.len())
+        .max()
+        .unwrap_or(0)
+        .max(header.len());
+    println!();
+    println!("{header:<width$}  {:>10}", "EVENTS");
+    for count in counts {
+        println!("{:<width$}  {:>10}", count.value, count.count);
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+use eyre::Result;
+use serde::Serialize;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::IndexerStatus;
+
+use super::{backfill::format_duration, control};
+use crate::output::{print_json, Output};
+
+/// Arguments of the `status` command
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Keep polling the running indexer and redraw a live view
+    #[arg(long)]
+    pub watch: bool,
+    /// Seconds between refreshes with `--watch`
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+/// Recent errors shown in the live view
+const WATCH_ERRORS: usize = 5;
+
+/// Throughput between two status snapshots
+#[derive(Serialize)]
+struct Rates {
+    checkpoints_per_sec: f64,
+    events_per_sec: f64,
+    /// Matches per second by filter name
+    filters: BTreeMap<String, f64>,
+}
+
+impl Rates {
+    fn between(previous: &IndexerStatus, current: &IndexerStatus, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        // Counters restart from zero with the indexer, so a drop reads as no progress
+        let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / secs;
+
+        Self {
+            checkpoints_per_sec: rate(
+                previous.checkpoints_processed,
+                current.checkpoints_processed,
+            ),
+            events_per_sec: rate(previous.events_processed, current.events_processed),
+            filters: current
+                .filter_matches
+                .iter()
+                .map(|(filter, matches)| {
+                    let before = previous.filter_matches.get(filter).copied().unwrap_or(0);
+                    (filter.clone(), rate(before, *matches))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Poll the control endpoint until Ctrl+C, redrawing the view on every refresh
+pub async fn watch(config: &IndexerConfig, args: StatusArgs, output: Output) -> Result<()> {
+    let Some(addr) = config.admin.addr else {
+        eyre::bail!("admin.addr is not set, so there is no running indexer to watch");
+    };
+    let interval = Duration::from_secs(args.interval.max(1));
+    let mut ticker = tokio::time::interval(interval);
+    let mut previous: Option<(Instant, IndexerStatus)> = None;
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = ticker.tick() => {}
+        }
+
+        let status = control::status(config).await?;
+        let now = Instant::now();
+        let rates = match (&previous, &status) {
+            (Some((at, before)), Some(current)) => Some(Rates::between(before, current, now - *at)),
+            _ => None,
+        };
+
+        if output.is_json() {
+            print_json(&serde_json::json!({ "status": status, "rates": rates }))?;
+        } else {
+            let mut out = io::stdout().lock();
+            // Clear the screen and move the cursor home before redrawing
+            write!(out, "\x1b[2J\x1b[H")?;
+            match &status {
+                Some(status) => render(&mut out, status, rates.as_ref())?,
+                None => writeln!(out, "⚪ No indexer reachable at {addr}, retrying")?,
+            }
+            writeln!(
+                out,
+                "\nRefreshing every {}s, Ctrl+C to quit",
+                interval.as_secs()
+            )?;
+            out.flush()?;
+        }
+
+        previous = status.map(|status| (now, status));
+    }
+    Ok(())
+}
+
+fn render(out: &mut impl Write, status: &IndexerStatus, rates: Option<&Rates>) -> Result<()> {
+    let checkpoint = |checkpoint: Option<u64>| {
+        checkpoint.map_or_else(|| "-".to_string(), |checkpoint| checkpoint.to_string())
+    };
+    let lag = match (
+        status.latest_processed_checkpoint,
+        status.latest_network_checkpoint,
+    ) {
+        (Some(processed), Some(network)) => network.saturating_sub(processed).to_string(),
+        _ => "-".to_string(),
+    };
+    let rate = |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| format!("{r:.1}"));
+
+    writeln!(
+        out,
+        "🟢 Sui Indexer {} on {}, up {}",
+        status.version,
+        status.network,
+        format_duration(Duration::from_secs(status.uptime_secs))
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Checkpoints   processed {}   network {}   lag {lag}",
+        checkpoint(status.latest_processed_checkpoint),
+        checkpoint(status.latest_network_checkpoint)
+    )?;
+    writeln!(
+        out,
+        "Throughput    {} checkpoints/s   {} events/s",
+        rate(rates.map(|r| r.checkpoints_per_sec)),
+        rate(rates.map(|r| r.events_per_sec))
+    )?;
+    writeln!(
+        out,
+        "Errors        fetch {}   storage {}",
+        status.fetch_errors, status.storage_errors
+    )?;
+    writeln!(out, "Resources     {}", status.resources)?;
+
+    // Indexers predating the breakdown only report matches
+    let count = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+
+    if !status.filter_matches.is_empty() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:<32} {:>12} {:>12} {:>8} {:>10}",
+            "FILTER", "MATCHES", "STORED", "ERRORS", "PER SEC"
+        )?;
+        for (filter, matches) in &status.filter_matches {
+            let stats = status.filters.get(filter);
+            let per_sec = rates.and_then(|r| r.filters.get(filter).copied());
+            writeln!(
+                out,
+                "{filter:<32} {matches:>12} {:>12} {:>8} {:>10}",
+                count(stats.map(|s| s.stored)),
+                count(stats.map(|s| s.errors)),
+                rate(per_sec)
+            )?;
+        }
+    }
+
+    if !status.pipelines.is_empty() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:<32} {:>12} {:>12} {:>8}",
+            "PIPELINE", "MATCHES", "STORED", "ERRORS"
+        )?;
+        for (pipeline, stats) in &status.pipelines {
+            writeln!(
+                out,
+                "{pipeline:<32} {:>12} {:>12} {:>8}",
+                stats.matched, stats.stored, stats.errors
+            )?;
+        }
+    }
+
+    if !status.recent_errors.is_empty() {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{:<20} {:<10} {:>12}  RECENT ERRORS",
+            "TIME", "STAGE", "CHECKPOINT"
+        )?;
+        let skip = status.recent_errors.len().saturating_sub(WATCH_ERRORS);
+        for error in status.recent_errors.iter().skip(skip) {
+            writeln!(
+                out,
+                "{:<20} {:<10} {:>12}  {}",
+                error.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                error.stage.to_string(),
+                checkpoint(error.checkpoint),
+                error.message
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use sui_indexer_core::{PipelineError, PipelineStage, ResourceUsage};
+
+    use super::*;
+
+    fn status(checkpoints: u64, events: u64, matches: &[(&str, u64)]) -> IndexerStatus {
+        IndexerStatus {
+            version: "1.0.0".to_string(),
+            network: "mainnet".to_string(),
+            uptime_secs: 3700,
+            latest_processed_checkpoint: Some(90),
+            latest_network_checkpoint: Some(100),
+            checkpoints_processed: checkpoints,
+            events_processed: events,
+            fetch_errors: 2,
+            storage_errors: 0,
+            filter_matches: matches
+                .iter()
+                .map(|(filter, count)| (filter.to_string(), *count))
+                .collect(),
+            filters: BTreeMap::new(),
+            pipelines: BTreeMap::new(),
+            resources: ResourceUsage::default(),
+            paused: false,
+            recent_errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rates_between_snapshots() {
+        let before = status(10, 100, &[("swaps", 40)]);
+        let after = status(30, 160, &[("swaps", 60), ("mints", 8)]);
+        let rates = Rates::between(&before, &after, Duration::from_secs(4));
+        assert_eq!(rates.checkpoints_per_sec, 5.0);
+        assert_eq!(rates.events_per_sec, 15.0);
+        assert_eq!(rates.filters["swaps"], 5.0);
+        // A filter first seen in the later snapshot counts from zero
+        assert_eq!(rates.filters["mints"], 2.0);
+
+        // A restarted indexer reads as no progress, not a negative rate
+        let rates = Rates::between(&after, &before, Duration::from_secs(4));
+        assert_eq!(rates.checkpoints_per_sec, 0.0);
+        assert_eq!(rates.filters["swaps"], 0.0);
+    }
+
+    #[test]
+    fn test_render_shows_lag_rates_and_latest_errors() -> Result<()> {
+        let mut current = status(30, 160, &[("swaps", 60)]);
+        current.recent_errors = (0..7)
+            .map(|error| PipelineError {
+                timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, error).unwrap(),
+                pipeline: "default".to_string(),
+                stage: PipelineStage::Fetch,
+                checkpoint: Some(91 + u64::from(error)),
+                message: format!("error {error}"),
+            })
+            .collect();
+        let rates = Rates::between(
+            &status(10, 100, &[("swaps", 40)]),
+            &current,
+            Duration::from_secs(4),
+        );
+
+        let mut out = Vec::new();
+        render(&mut out, &current, Some(&rates))?;
+        let view = String::from_utf8(out)?;
+        assert!(view.contains("Sui Indexer 1.0.0 on mainnet, up 1h01m40s"));
+        assert!(view.contains("processed 90   network 100   lag 10"));
+        assert!(view.contains("5.0 checkpoints/s   15.0 events/s"));
+        assert!(view.contains("fetch 2   storage 0"));
+        let swaps = view.lines().find(|line| line.starts_with("swaps")).unwrap();
+        assert_eq!(
+            swaps.split_whitespace().collect::<Vec<_>>(),
+            ["swaps", "60", "-", "-", "5.0"]
+        );
+        // Only the latest errors are shown
+        assert!(!view.contains("error 1"));
+        assert!(view.contains("error 2") && view.contains("error 6"));
+
+        // Without an earlier snapshot there are no rates yet
+        let mut out = Vec::new();
+        render(&mut out, &current, None)?;
+        assert!(String::from_utf8(out)?.contains("- checkpoints/s   - events/s"));
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::Tail;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Events buffered between the tail and stdout
+const OUTPUT_BUFFER: usize = 1024;
+
+/// Arguments of the `tail` command
+#[derive(Args)]
+pub struct TailArgs {
+    /// Comma-separated event filter names to match (defaults to all configured filters)
+    #[arg(long = "filter", value_delimiter = ',')]
+    filters: Vec<String>,
+}
+
+/// Print events matched at the network tip as JSON lines until interrupted
+pub async fn run(config: IndexerConfig, args: TailArgs) -> Result<()> {
+    let mut tail = Tail::from_config(&config).await?;
+    if !args.filters.is_empty() {
+        tail = tail.with_filters(config.events.named_filters(&args.filters)?);
+    }
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            signal_shutdown.cancel();
+        }
+    });
+
+    let (tx, mut rx) = mpsc::channel(OUTPUT_BUFFER);
+    let printer = tokio::spawn(async move {
+        let mut out = io::stdout();
+        while let Some(event) = rx.recv().await {
+            let line = serde_json::to_string(&event)?;
+            // Stop quietly when the reader goes away, e.g. `tail | head`
+            if writeln!(out, "{line}").and_then(|()| out.flush()).is_err() {
+                break;
+            }
+        }
+        eyre::Ok(())
+    });
+
+    tail.run(tx, shutdown).await?;
+    printer.await?
+}
@@ -0,0 +1,135 @@
+use clap::Args;
+use eyre::Result;
+use sui_indexer_core::{audit, CheckpointRange, IndexerCore, VerifyReport};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    exit::{interrupted, ExitCode},
+    output::{print_json, Output},
+};
+
+/// Arguments of the `verify` command
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// First checkpoint to verify
+    #[arg(long)]
+    from: u64,
+    /// Last checkpoint to verify (inclusive)
+    #[arg(long)]
+    to: u64,
+    /// Checkpoints compared at once (defaults to `runtime.fetch_concurrency`)
+    #[arg(long)]
+    concurrency: Option<usize>,
+    /// Comma-separated event filter names to check (defaults to all configured filters)
+    #[arg(long, value_delimiter = ',')]
+    filters: Vec<String>,
+    /// Re-index checkpoints whose stored events differ from the chain
+    #[arg(long)]
+    repair: bool,
+}
+
+/// Compare stored events with the chain, exiting with [`ExitCode::CheckFailed`] on unrepaired discrepancies
+pub async fn run(indexer: IndexerCore, args: VerifyArgs, output: Output) -> Result<()> {
+    let range = CheckpointRange::new(args.from, args.to)?;
+    let total = range.len();
+    let config = indexer.config();
+    let filters = if args.filters.is_empty() {
+        config.events.filters.clone()
+    } else {
+        config.events.named_filters(&args.filters)?
+    };
+
+    let mut verify = indexer
+        .verify(range)
+        .with_filters(filters)
+        .with_repair(args.repair);
+    if let Some(concurrency) = args.concurrency {
+        verify = verify.with_concurrency(concurrency);
+    }
+
+    let shutdown = CancellationToken::new();
+    let signal_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("✋ Received shutdown signal (Ctrl+C), stopping verification");
+            signal_shutdown.cancel();
+        }
+    });
+
+    let report = verify.run(shutdown.clone()).await?;
+    let repaired: Vec<_> = report
+        .discrepancies
+        .iter()
+        .filter(|discrepancy| discrepancy.repaired)
+        .map(|discrepancy| discrepancy.checkpoint)
+        .collect();
+    if !repaired.is_empty() {
+        let details = serde_json::json!({ "from": args.from, "to": args.to, "repaired": repaired });
+        audit::record(
+            &indexer.storage(),
+            audit::cli_entry("verify_repair", details),
+        )
+        .await;
+    }
+    if output.is_json() {
+        print_json(&report)?;
+    } else {
+        print_report(&report);
+    }
+
+    if shutdown.is_cancelled() {
+        return Err(interrupted(format!(
+            "Verification interrupted after {} of {} checkpoints",
+            report.checkpoints, total
+        )));
+    }
+    if report.has_unrepaired() {
+        ExitCode::CheckFailed.exit();
+    }
+    Ok(())
+}
+
+fn print_report(report: &VerifyReport) {
+    if !report.filters.is_empty() {
+        println!("{:<24} {:>10} {:>10}", "FILTER", "EXPECTED", "STORED");
+        for filter in &report.filters {
+            println!(
+                "{:<24} {:>10} {:>10}",
+                filter.filter, filter.expected, filter.stored
+            );
+        }
+        println!();
+    }
+
+    for discrepancy in &report.discrepancies {
+        println!(
+            "{} checkpoint {}: expected {}, stored {} ({} missing, {} unexpected){}{}",
+            if discrepancy.repaired { "🔧" } else { "❌" },
+            discrepancy.checkpoint,
+            discrepancy.expected,
+            discrepancy.stored,
+            discrepancy.missing,
+            discrepancy.unexpected,
+            discrepancy
+                .reported
+                .map(|reported| format!(", {reported} stored at commit"))
+                .unwrap_or_default(),
+            if discrepancy.repaired {
+                ", repaired"
+            } else {
+                ""
+            }
+        );
+    }
+
+    if report.discrepancies.is_empty() {
+        println!("✅ {} checkpoints match the chain", report.checkpoints);
+    } else {
+        println!(
+            "{} of {} checkpoints differ from the chain",
+            report.discrepancies.len(),
+            report.checkpoints
+        );
+    }
+}
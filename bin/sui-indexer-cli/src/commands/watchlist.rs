@@ -0,0 +1,119 @@
+use clap::{Args, Subcommand};
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_core::audit;
+use sui_indexer_storage::StorageManager;
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `watchlist` command
+#[derive(Args)]
+pub struct WatchlistArgs {
+    #[command(subcommand)]
+    command: WatchlistCommand,
+}
+
+#[derive(Subcommand)]
+enum WatchlistCommand {
+    /// Store addresses in a watchlist; running indexers watch them after their next refresh
+    Add {
+        /// Watchlist configured with `database = true`
+        watchlist: String,
+        /// Addresses to add, such as `0x2`
+        #[arg(required = true)]
+        addresses: Vec<String>,
+    },
+    /// Remove stored addresses from a watchlist
+    Remove {
+        /// Watchlist to remove the addresses from
+        watchlist: String,
+        /// Addresses to remove
+        #[arg(required = true)]
+        addresses: Vec<String>,
+    },
+    /// List the addresses stored in a watchlist
+    List {
+        /// Watchlist to list
+        watchlist: String,
+    },
+}
+
+/// Run a `watchlist` subcommand against the configured database
+pub async fn run(config: IndexerConfig, args: WatchlistArgs, output: Output) -> Result<()> {
+    let watched = |name: &str| match config.watchlists.get(name) {
+        Some(watchlist) if watchlist.database => Ok(()),
+        Some(_) => Err(eyre::eyre!(
+            "Watchlist `{name}` does not read the database; set `database = true`"
+        )),
+        None => Err(eyre::eyre!("No watchlist named `{name}` is configured")),
+    };
+
+    match args.command {
+        WatchlistCommand::Add {
+            watchlist,
+            addresses,
+        } => {
+            watched(&watchlist)?;
+            let addresses = normalize(&addresses)?;
+            let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+            let added = storage
+                .add_watchlist_addresses(&watchlist, &addresses)
+                .await?;
+            let details = serde_json::json!({ "watchlist": watchlist, "addresses": addresses });
+            audit::record(&storage, audit::cli_entry("watchlist_add", details)).await;
+            if output.is_json() {
+                return print_json(&serde_json::json!({ "watchlist": watchlist, "added": added }));
+            }
+            println!("✅ Added {added} address(es) to watchlist `{watchlist}`");
+        }
+        WatchlistCommand::Remove {
+            watchlist,
+            addresses,
+        } => {
+            watched(&watchlist)?;
+            let addresses = normalize(&addresses)?;
+            let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+            let removed = storage
+                .remove_watchlist_addresses(&watchlist, &addresses)
+                .await?;
+            let details = serde_json::json!({ "watchlist": watchlist, "addresses": addresses });
+            audit::record(&storage, audit::cli_entry("watchlist_remove", details)).await;
+            if output.is_json() {
+                return print_json(
+                    &serde_json::json!({ "watchlist": watchlist, "removed": removed }),
+                );
+            }
+            println!("✅ Removed {removed} address(es) from watchlist `{watchlist}`");
+        }
+        WatchlistCommand::List { watchlist } => {
+            let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+            let addresses = storage.watchlist_addresses(&watchlist).await?;
+            if output.is_json() {
+                return print_json(&addresses);
+            }
+            if addresses.is_empty() {
+                println!("No addresses stored in watchlist `{watchlist}`");
+            }
+            for address in addresses {
+                println!("{address}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Full-length lowercase form of each address, so `0x2` and `0x0…02` are stored once
+fn normalize(addresses: &[String]) -> Result<Vec<String>> {
+    addresses
+        .iter()
+        .map(|address| {
+            let hex = address
+                .strip_prefix("0x")
+                .filter(|hex| {
+                    (1..=64).contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+                })
+                .ok_or_else(|| eyre::eyre!("Invalid address `{address}`"))?;
+            Ok(format!("0x{:0>64}", hex.to_ascii_lowercase()))
+        })
+        .collect()
+}
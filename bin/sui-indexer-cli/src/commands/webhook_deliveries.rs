@@ -0,0 +1,45 @@
+use clap::Args;
+use eyre::Result;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::StorageManager;
+
+use crate::output::{print_json, Output};
+
+/// Arguments of the `webhook-deliveries` command
+#[derive(Args)]
+pub struct WebhookDeliveriesArgs {
+    /// Number of most recent deliveries to show
+    #[arg(long, default_value_t = 50)]
+    limit: u32,
+    /// Only show deliveries whose events were dropped
+    #[arg(long)]
+    failed: bool,
+}
+
+/// List the most recent webhook deliveries that were retried or failed
+pub async fn run(config: IndexerConfig, args: WebhookDeliveriesArgs, output: Output) -> Result<()> {
+    let storage = StorageManager::new_postgres(config.database, config.storage).await?;
+    let deliveries = storage.webhook_deliveries(args.limit, args.failed).await?;
+
+    if output.is_json() {
+        return print_json(&deliveries);
+    }
+    if deliveries.is_empty() {
+        println!("No retried or failed webhook deliveries recorded");
+    }
+    for delivery in deliveries {
+        println!(
+            "{}  {:<16} {:<9} {:>2} attempts  {:>4} events  checkpoints {}-{}  {}  {}",
+            delivery.recorded_at.format("%Y-%m-%d %H:%M:%S"),
+            delivery.sink,
+            delivery.status,
+            delivery.attempts,
+            delivery.events,
+            delivery.first_checkpoint,
+            delivery.last_checkpoint,
+            delivery.id,
+            delivery.error.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
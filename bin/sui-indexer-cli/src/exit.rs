@@ -0,0 +1,88 @@
+use sui_indexer_config::{error_kind, ErrorKind};
+use sui_indexer_storage::is_database_error;
+
+use crate::output::Output;
+
+/// Process exit codes, kept stable for scripts and orchestrators
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitCode {
+    /// The command failed for a reason without a more specific code
+    Failure = 1,
+    /// The configuration could not be loaded or is invalid
+    Config = 2,
+    /// The node or the control endpoint could not be reached
+    Connectivity = 3,
+    /// The database failed or could not be reached
+    Storage = 4,
+    /// The command ran but found problems: unhealthy, mismatches, red checks
+    CheckFailed = 5,
+    /// The command line could not be parsed
+    Usage = 64,
+    /// Ctrl+C stopped the command before it completed
+    Interrupted = 130,
+}
+
+impl ExitCode {
+    /// The code for the cause of `err`
+    pub fn of(err: &eyre::Report) -> Self {
+        match error_kind(err) {
+            Some(ErrorKind::Config) => Self::Config,
+            Some(ErrorKind::Connectivity) => Self::Connectivity,
+            Some(ErrorKind::Storage) => Self::Storage,
+            Some(ErrorKind::Interrupted) => Self::Interrupted,
+            None if is_database_error(err) => Self::Storage,
+            None => Self::Failure,
+        }
+    }
+
+    /// Short lowercase name of the code
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Failure => "failure",
+            Self::Config => "config",
+            Self::Connectivity => "connectivity",
+            Self::Storage => "storage",
+            Self::CheckFailed => "check_failed",
+            Self::Usage => "usage",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    /// Exit the process with this code, exporting the buffered trace spans first
+    pub fn exit(self) -> ! {
+        crate::logging::flush_traces();
+        std::process::exit(self as i32)
+    }
+}
+
+/// An error for a command stopped by Ctrl+C
+pub fn interrupted(message: impl Into<String>) -> eyre::Report {
+    eyre::eyre!(message.into()).wrap_err(ErrorKind::Interrupted)
+}
+
+/// Print `err` as a single line on stderr and exit with its code
+pub fn fail(err: &eyre::Report, output: Output) -> ! {
+    let code = ExitCode::of(err);
+    // The kind is reported on its own, so leave its context out of the message
+    let kind = error_kind(err).map(|kind| kind.to_string());
+    let message = err
+        .chain()
+        .map(|cause| cause.to_string())
+        .filter(|cause| Some(cause) != kind.as_ref())
+        .collect::<Vec<_>>()
+        .join(": ");
+    if output.is_json() {
+        let line = serde_json::json!({
+            "error": { "code": code as i32, "kind": code.name(), "message": message }
+        });
+        eprintln!("{line}");
+    } else {
+        // Debug formatting quotes the message and escapes any newline in it
+        eprintln!(
+            "error code={} kind={} message={message:?}",
+            code as i32,
+            code.name()
+        );
+    }
+    code.exit()
+}
@@ -0,0 +1,118 @@
+use std::{io, sync::OnceLock};
+
+use sui_indexer_config::{IndexerConfig, LogFormat, ObservabilityConfig};
+use sui_indexer_core::{
+    json_log::CorrelatedJson,
+    otlp::{self, TraceExporter},
+    RecentErrors,
+};
+use tokio::sync::watch;
+use tracing::{info, warn};
+use tracing_subscriber::{
+    fmt::{self, format::JsonFields},
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Registry,
+};
+
+/// Handle for swapping the active log filter at runtime
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Service name reported with exported traces
+const SERVICE_NAME: &str = "sui-indexer";
+
+/// Exporter of the installed trace layer, flushed before the process exits
+static TRACES: OnceLock<TraceExporter> = OnceLock::new();
+
+/// Warnings and errors recorded for the control endpoint
+static ERRORS: OnceLock<RecentErrors> = OnceLock::new();
+
+/// Install the global tracing subscriber from the observability settings
+///
+/// `RUST_LOG` overrides the configured log level when set. Logs go to stderr
+/// so command output on stdout can be piped. Spans are exported to
+/// `observability.tracing_endpoint` over OTLP/HTTP when it is set.
+pub fn init(config: &ObservabilityConfig) -> LogFilterHandle {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| parse_filter(&config.log_level));
+    let (filter, handle) = reload::Layer::new(filter);
+    let mut trace_error = None;
+    let traces = config.tracing_endpoint.as_ref().and_then(|endpoint| {
+        match otlp::layer(endpoint, SERVICE_NAME, config.trace_sample_every) {
+            Ok((layer, exporter)) => {
+                let _ = TRACES.set(exporter);
+                Some(layer)
+            }
+            Err(e) => {
+                trace_error = Some(e);
+                None
+            }
+        }
+    });
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(traces)
+        .with(recent_errors().layer());
+
+    match config.log_format {
+        LogFormat::Pretty => registry.with(fmt::layer().with_writer(io::stderr)).init(),
+        LogFormat::Json => registry
+            .with(
+                fmt::layer()
+                    .fmt_fields(JsonFields::new())
+                    .event_format(CorrelatedJson)
+                    .with_writer(io::stderr),
+            )
+            .init(),
+    }
+
+    if let Some(e) = trace_error {
+        warn!(error = %e, "Failed to set up trace export, continuing without it");
+    } else if let Some(endpoint) = &config.tracing_endpoint {
+        info!(%endpoint, "Exporting traces over OTLP");
+    }
+
+    handle
+}
+
+/// Warnings and errors logged since the subscriber was installed
+pub fn recent_errors() -> RecentErrors {
+    ERRORS.get_or_init(RecentErrors::default).clone()
+}
+
+/// Export the spans still buffered by the trace layer, if one is installed
+pub fn flush_traces() {
+    if let Some(traces) = TRACES.get() {
+        traces.flush();
+    }
+}
+
+/// Apply `observability.log_level` changes from hot-reloaded configuration
+pub fn follow_log_level(handle: LogFilterHandle, mut config: watch::Receiver<IndexerConfig>) {
+    tokio::spawn(async move {
+        let mut current = config.borrow().observability.log_level.clone();
+        while config.changed().await.is_ok() {
+            let log_level = config.borrow_and_update().observability.log_level.clone();
+            if log_level == current {
+                continue;
+            }
+
+            match EnvFilter::try_new(&log_level) {
+                Ok(filter) => match handle.reload(filter) {
+                    Ok(()) => info!(%log_level, "Updated log level"),
+                    Err(e) => warn!(error = %e, "Failed to update log level"),
+                },
+                Err(e) => warn!(%log_level, error = %e, "Ignoring invalid log level"),
+            }
+            current = log_level;
+        }
+    });
+}
+
+fn parse_filter(log_level: &str) -> EnvFilter {
+    EnvFilter::try_new(log_level).unwrap_or_else(|e| {
+        eprintln!("Invalid log level {log_level:?} ({e}), falling back to info");
+        EnvFilter::new("info")
+    })
+}
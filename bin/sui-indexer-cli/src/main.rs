@@ -2,9 +2,17 @@ use clap::{Parser, Subcommand};
 use eyre::Result;
 use sui_indexer_config::ConfigLoader;
 use sui_indexer_core::IndexerCore;
-use tokio;
-use tracing::{error, info};
-use tracing_subscriber;
+use tracing::{error, info, warn};
+
+use crate::{
+    exit::ExitCode,
+    output::{print_json, Output},
+};
+
+mod commands;
+mod exit;
+mod logging;
+mod output;
 
 #[derive(Parser)]
 #[command(name = "sui-indexer")]
@@ -13,6 +21,14 @@ struct Cli {
     #[arg(short, long, default_value = "config.toml")]
     config: String,
 
+    /// Work on this network of `networks` instead of all of them (or the top-level one)
+    #[arg(long, global = true)]
+    network: Option<String>,
+
+    /// Print command results as text or as JSON
+    #[arg(long, global = true, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,63 +36,144 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Start the indexer
-    Start,
-    /// Stop the indexer
+    Start(commands::start::StartArgs),
+    /// Stop a running indexer
     Stop,
+    /// Make a running indexer re-read its configuration file
+    Reload,
+    /// Check that every event seen by the running indexer was filtered out or stored
+    Reconcile,
     /// Check indexer health
     Health,
     /// Show detailed status information
-    Status,
+    Status(commands::status::StatusArgs),
+    /// Create, list and revoke API keys stored in the database
+    ApiKey(commands::api_key::ApiKeyArgs),
+    /// List recorded operator actions such as cursor resets, reloads, prunes and pauses
+    Audit(commands::audit::AuditArgs),
+    /// Index a fixed checkpoint range and exit
+    Backfill(commands::backfill::BackfillArgs),
+    /// Measure fetch, process and store throughput over a checkpoint range
+    Benchmark(commands::benchmark::BenchmarkArgs),
+    /// Show or overwrite the stored checkpoint cursors
+    Checkpoint(commands::checkpoint::CheckpointArgs),
+    /// Validate or generate configuration files
+    Config(commands::config::ConfigArgs),
+    /// Open an interactive prompt for ad-hoc queries against the database
+    Console,
+    /// Diagnose node, database, migration, cursor and filter problems
+    Doctor,
+    /// Write indexed events and transactions of a checkpoint range to files
+    Export(commands::export::ExportArgs),
+    /// Try out event filters before deploying them
+    Filters(commands::filters::FiltersArgs),
+    /// Write a starter configuration and filter file
+    Init(commands::init::InitArgs),
+    /// Generate monitoring assets for the exported metrics
+    Metrics(commands::metrics::MetricsArgs),
+    /// Show, apply or revert database migrations
+    Migrate(commands::migrate::MigrateArgs),
+    /// Show the sink messages waiting in the outbox
+    Outbox,
+    /// Remove indexed data past a given age
+    Prune(commands::prune::PruneArgs),
+    /// Inspect indexed data
+    Query(commands::query::QueryArgs),
+    /// Backfill a newly added filter up to where the running indexer took it over
+    ReindexFilter(commands::reindex_filter::ReindexFilterArgs),
+    /// Re-run a processor over stored events into a separate table
+    Replay(commands::replay::ReplayArgs),
+    /// Describe the tables, columns and indexes the migrations create
+    Schema(commands::schema::SchemaArgs),
+    /// Create or restore a consistent database snapshot
+    Snapshot(commands::snapshot::SnapshotArgs),
+    /// Summarize indexed events, packages, failures and lag over a time window
+    Stats(commands::stats::StatsArgs),
+    /// Print events matched at the network tip as JSON lines
+    Tail(commands::tail::TailArgs),
+    /// Compare stored events with the chain over a checkpoint range
+    Verify(commands::verify::VerifyArgs),
+    /// Add, remove and list the watchlist addresses stored in the database
+    Watchlist(commands::watchlist::WatchlistArgs),
+    /// List webhook deliveries that needed retries or were given up on
+    WebhookDeliveries(commands::webhook_deliveries::WebhookDeliveriesArgs),
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize tracing with info level by default
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
+async fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version requests are not errors
+        Err(err) if !err.use_stderr() => err.exit(),
+        Err(err) => {
+            let _ = err.print();
+            ExitCode::Usage.exit()
+        }
+    };
+    let output = cli.output;
+    match run(cli).await {
+        Ok(()) => logging::flush_traces(),
+        Err(err) => exit::fail(&err, output),
+    }
+}
 
-    let cli = Cli::parse();
+async fn run(cli: Cli) -> Result<()> {
+    // Load the configuration first so it can drive logging setup; commands
+    // that need it report load errors once tracing is up
+    let config = ConfigLoader::from_file(&cli.config).and_then(|config| match &cli.network {
+        Some(network) => config.network_context(network),
+        None => Ok(config),
+    });
+    let observability = config
+        .as_ref()
+        .map(|config| config.observability.clone())
+        .unwrap_or_default();
+    let log_filter = logging::init(&observability);
 
     match cli.command {
-        Commands::Start => {
+        Commands::Start(args) => {
             info!("Starting Sui Indexer");
 
-            let config = ConfigLoader::from_file(&cli.config)?;
-            let indexer = IndexerCore::new(config).await?;
-
-            // Initialize the indexer (run migrations, etc.)
-            indexer.initialize().await?;
-
-            info!("🚀 Sui Indexer initialized successfully");
-
-            // Start the indexer (this will run the main event loop)
-            indexer.start().await?;
+            let indexer = IndexerCore::new(config?)
+                .await?
+                .with_config_path(&cli.config);
+            commands::start::run(indexer, &cli.config, log_filter, args).await?;
         }
         Commands::Stop => {
             info!("Stopping Sui Indexer gracefully");
-            // NOTE: Add graceful shutdown logic when needed
-            std::process::exit(0);
+            commands::control::stop(&config?, cli.output).await?;
+        }
+        Commands::Reload => {
+            commands::control::reload(&config?, cli.output).await?;
+        }
+        Commands::Reconcile => {
+            commands::control::reconcile(&config?, cli.output).await?;
         }
         Commands::Health => {
-            let config = ConfigLoader::from_file(&cli.config)?;
-            let indexer = IndexerCore::new(config).await?;
+            let indexer = IndexerCore::new(config?).await?;
 
-            let healthy = indexer.health_check().await?;
+            let health = indexer.health().await?;
+            if cli.output.is_json() {
+                print_json(&health)?;
+                if !health.healthy {
+                    ExitCode::CheckFailed.exit();
+                }
+                return Ok(());
+            }
 
-            if healthy {
+            if health.healthy {
                 info!("✅ Indexer is healthy");
-                std::process::exit(0);
             } else {
                 info!("❌ Some components are unhealthy");
-                std::process::exit(1);
+                ExitCode::CheckFailed.exit();
             }
         }
-        Commands::Status => {
-            let config = ConfigLoader::from_file(&cli.config)?;
+        Commands::Status(args) if args.watch => {
+            commands::status::watch(&config?, args, cli.output).await?;
+        }
+        Commands::Status(_) => {
+            let config = config?;
+            let running = commands::control::status(&config).await?;
             let indexer = IndexerCore::new(config).await?;
 
             info!("Checking indexer status");
@@ -84,6 +181,19 @@ async fn main() -> Result<()> {
             // Get overall status
             let healthy = indexer.health_check().await?;
 
+            if cli.output.is_json() {
+                print_json(&serde_json::json!({
+                    "healthy": healthy,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "build": option_env!("BUILD_TIMESTAMP"),
+                    "process": running,
+                }))?;
+                if !healthy {
+                    ExitCode::CheckFailed.exit();
+                }
+                return Ok(());
+            }
+
             if healthy {
                 info!("🟢 Indexer Status: HEALTHY");
 
@@ -99,45 +209,156 @@ async fn main() -> Result<()> {
                 // Network status
                 info!("  📡 Network: Connected to Sui RPC");
 
-                // Memory usage
-                if let Ok(memory) = get_memory_usage() {
-                    info!("  💾 Memory: {}", memory);
+                // Processing statistics from the running indexer, if any
+                match running {
+                    Some(status) => {
+                        info!("  ⚙️ Indexer process: running for {}s", status.uptime_secs);
+                        info!("  💾 Resources: {}", status.resources);
+                        info!(
+                            "  📍 Checkpoints: processed {} / network {}",
+                            display_checkpoint(status.latest_processed_checkpoint),
+                            display_checkpoint(status.latest_network_checkpoint)
+                        );
+                        info!(
+                            "  📈 Since startup: {} checkpoints, {} events, {} fetch errors, {} storage errors",
+                            status.checkpoints_processed,
+                            status.events_processed,
+                            status.fetch_errors,
+                            status.storage_errors
+                        );
+                        for error in &status.recent_errors {
+                            warn!(
+                                "  ⚠️ {} {} at checkpoint {}: {}",
+                                error.timestamp.to_rfc3339(),
+                                error.stage,
+                                display_checkpoint(error.checkpoint),
+                                error.message
+                            );
+                        }
+                    }
+                    None => info!("  ⚪ Indexer process: not running"),
                 }
 
-                // NOTE: Add processing statistics when database schema is ready
-
                 info!("✅ Status check completed successfully");
             } else {
                 error!("❌ Indexer Status: UNHEALTHY");
-                std::process::exit(1);
+                ExitCode::CheckFailed.exit();
             }
         }
+        Commands::ApiKey(args) => {
+            commands::api_key::run(config?, args, cli.output).await?;
+        }
+        Commands::Audit(args) => {
+            commands::audit::run(config?, args, cli.output).await?;
+        }
+        Commands::Backfill(args) => {
+            let indexer = IndexerCore::new(config?).await?;
+            indexer.initialize().await?;
+            commands::backfill::run(indexer, args, cli.output).await?;
+        }
+        Commands::ReindexFilter(args) => {
+            let indexer = IndexerCore::new(config?).await?;
+            indexer.initialize().await?;
+            commands::reindex_filter::run(indexer, args, cli.output).await?;
+        }
+        Commands::Stats(args) => {
+            commands::stats::run(config?, args, cli.output).await?;
+        }
+        Commands::Benchmark(args) => {
+            commands::benchmark::run(config?, args, cli.output).await?;
+        }
+        Commands::Checkpoint(args) => {
+            commands::checkpoint::run(config?, args, cli.output).await?;
+        }
+        Commands::Config(args) => {
+            commands::config::run(&cli.config, args, cli.output)?;
+        }
+        Commands::Console => {
+            commands::console::run(config?, cli.output).await?;
+        }
+        Commands::Doctor => {
+            commands::doctor::run(config?, cli.output).await?;
+        }
+        Commands::Export(args) => {
+            commands::export::run(config?, args, cli.output).await?;
+        }
+        Commands::Filters(args) => {
+            commands::filters::run(config?, args, cli.output).await?;
+        }
+        Commands::Init(args) => {
+            commands::init::run(&cli.config, args, cli.output)?;
+        }
+        Commands::Metrics(args) => {
+            commands::metrics::run(args)?;
+        }
+        Commands::Migrate(args) => {
+            commands::migrate::run(config?, args, cli.output).await?;
+        }
+        Commands::Outbox => {
+            commands::outbox::run(config?, cli.output).await?;
+        }
+        Commands::Prune(args) => {
+            commands::prune::run(config?, args, cli.output).await?;
+        }
+        Commands::Query(args) => {
+            commands::query::run(config?, args, cli.output).await?;
+        }
+        Commands::Replay(args) => {
+            commands::replay::run(config?, args, cli.output).await?;
+        }
+        Commands::Schema(args) => {
+            commands::schema::run(args, cli.output)?;
+        }
+        Commands::Snapshot(args) => {
+            commands::snapshot::run(config?, args, cli.output).await?;
+        }
+        Commands::Tail(args) => {
+            commands::tail::run(config?, args).await?;
+        }
+        Commands::Verify(args) => {
+            let indexer = IndexerCore::new(config?).await?;
+            indexer.initialize().await?;
+            commands::verify::run(indexer, args, cli.output).await?;
+        }
+        Commands::Watchlist(args) => {
+            commands::watchlist::run(config?, args, cli.output).await?;
+        }
+        Commands::WebhookDeliveries(args) => {
+            commands::webhook_deliveries::run(config?, args, cli.output).await?;
+        }
     }
 
     Ok(())
 }
 
-/// Get memory usage information
-fn get_memory_usage() -> Result<String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        let output = Command::new("ps")
-            .args(&["-o", "rss=", "-p"])
-            .arg(std::process::id().to_string())
-            .output()?;
-
-        let rss_kb = String::from_utf8(output.stdout)?
-            .trim()
-            .parse::<u64>()
-            .unwrap_or(0);
-
-        let rss_mb = rss_kb / 1024;
-        Ok(format!("{}MB RSS", rss_mb))
+fn display_checkpoint(checkpoint: Option<u64>) -> String {
+    checkpoint.map_or_else(|| "-".to_string(), |checkpoint| checkpoint.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    fn parse(args: &str) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(args.split(' '))
     }
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        Ok("Memory info unavailable on this platform".to_string())
+    #[test]
+    fn test_global_output_flag_works_after_every_subcommand() {
+        Cli::command().debug_assert();
+        for args in [
+            "sui-indexer --output json config generate",
+            "sui-indexer config generate --output json --out config.toml",
+            "sui-indexer -c prod.toml query events --output json --format csv",
+            "sui-indexer status --output json",
+        ] {
+            let cli = parse(args).unwrap_or_else(|e| panic!("{args}: {e}"));
+            assert_eq!(cli.output, Output::Json, "{args}");
+        }
+        // The old `--output` of these subcommands is the global flag now
+        assert!(parse("sui-indexer query events --output csv").is_err());
+        assert!(parse("sui-indexer config generate -o config.toml").is_ok());
     }
 }
@@ -0,0 +1,26 @@
+use clap::ValueEnum;
+use eyre::Result;
+use serde::Serialize;
+
+/// How commands print their results on stdout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON document, for scripts and probes
+    Json,
+}
+
+impl Output {
+    /// Whether results are printed as JSON
+    pub fn is_json(self) -> bool {
+        self == Self::Json
+    }
+}
+
+/// Print `value` on stdout as one line of JSON
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
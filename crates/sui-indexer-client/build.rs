@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Shared by the client and the server in `sui-indexer-core`, which needs `protoc`
+    tonic_prost_build::compile_protos("proto/sui_indexer/v1/indexer.proto")?;
+    Ok(())
+}
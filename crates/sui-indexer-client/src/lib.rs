@@ -0,0 +1,343 @@
+//! Typed client for the gRPC API of a running Sui Indexer
+//!
+//! Services reading indexed data use [`IndexerClient`] instead of hand-rolled
+//! requests. The messages are generated from the same protobuf definition the
+//! server in `sui-indexer-core` is built from, and [`IndexerClient::check_version`]
+//! tells whether a server speaks the revision of the API this crate was built for.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use sui_indexer_client::{IndexerClient, QueryEventsRequest, SubscribeEventsRequest};
+//!
+//! # async fn run() -> sui_indexer_client::Result<()> {
+//! let client = IndexerClient::connect("http://127.0.0.1:9186")
+//!     .await?
+//!     .with_token(Some("secret".to_string()));
+//! client.check_version().await?;
+//!
+//! let page = client
+//!     .query_events(QueryEventsRequest {
+//!         event_type: Some("SwapEvent".to_string()),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! println!("{} swaps", page.events.len());
+//!
+//! let mut batches = client.subscribe(SubscribeEventsRequest::default()).await?;
+//! while let Some(batch) = batches.next().await {
+//!     println!("checkpoint {}", batch?.checkpoint);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream, TryStreamExt};
+use tonic::{
+    transport::{Channel, Endpoint},
+    Code, Request, Streaming,
+};
+
+/// Types, client and service generated from `proto/sui_indexer/v1/indexer.proto`
+pub mod proto {
+    tonic::include_proto!("sui_indexer.v1");
+}
+
+pub use proto::{
+    Event, EventBatch, GetStatusResponse, QueryEventsRequest, QueryEventsResponse,
+    QueryTransactionsRequest, QueryTransactionsResponse, SubscribeEventsRequest, Transaction,
+};
+
+/// Revision of the API this crate speaks, reported by servers as `api_version`
+///
+/// Raised whenever a change to the protobuf definition would break clients
+/// built for the previous revision.
+pub const API_VERSION: u32 = 1;
+
+/// Trailer carrying the checkpoint a lagging subscriber resumes from
+const RESUME_TRAILER: &str = "resume-from-checkpoint";
+
+/// Result of a client call
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// Stream of matched events per committed checkpoint
+pub type EventBatches = Pin<Box<dyn Stream<Item = Result<EventBatch>> + Send>>;
+
+/// Failure talking to an indexer
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The endpoint is invalid or could not be reached
+    #[error("Failed to connect to the indexer")]
+    Connect(#[from] tonic::transport::Error),
+    /// The indexer refused or failed the call
+    #[error("Indexer call failed with {:?}: {}", .0.code(), .0.message())]
+    Call(Box<tonic::Status>),
+    /// The token cannot be sent as a header value
+    #[error("Token is not valid in an authorization header")]
+    InvalidToken,
+    /// The server speaks another revision of the API
+    #[error("Indexer {version} serves API version {server}, this client needs version {client}")]
+    Incompatible {
+        version: String,
+        server: u32,
+        client: u32,
+    },
+}
+
+impl ClientError {
+    /// The status the indexer answered with, if the call got that far
+    pub fn status(&self) -> Option<&tonic::Status> {
+        match self {
+            Self::Call(status) => Some(status),
+            _ => None,
+        }
+    }
+}
+
+impl From<tonic::Status> for ClientError {
+    fn from(status: tonic::Status) -> Self {
+        Self::Call(Box::new(status))
+    }
+}
+
+/// Client of the `sui_indexer.v1.Indexer` service
+///
+/// Cheap to clone; clones share the connection.
+#[derive(Debug, Clone)]
+pub struct IndexerClient {
+    inner: proto::indexer_client::IndexerClient<Channel>,
+    token: Option<String>,
+}
+
+impl IndexerClient {
+    /// Connect to the API at `endpoint`, e.g. `http://127.0.0.1:9186`
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    /// Use an already configured channel, e.g. one with TLS or timeouts
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            inner: proto::indexer_client::IndexerClient::new(channel),
+            token: None,
+        }
+    }
+
+    /// Send this bearer token or API key with every call
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Progress and counters of the indexer
+    pub async fn status(&self) -> Result<GetStatusResponse> {
+        let request = self.request(proto::GetStatusRequest {})?;
+        Ok(self.inner.clone().get_status(request).await?.into_inner())
+    }
+
+    /// Status of the indexer, failing if it serves another revision of the API
+    pub async fn check_version(&self) -> Result<GetStatusResponse> {
+        compatible(self.status().await?)
+    }
+
+    /// One page of stored events, newest first
+    pub async fn query_events(&self, query: QueryEventsRequest) -> Result<QueryEventsResponse> {
+        let request = self.request(query)?;
+        Ok(self.inner.clone().query_events(request).await?.into_inner())
+    }
+
+    /// Every stored event matching `query`, newest first, fetching pages as they are read
+    pub fn events(&self, query: QueryEventsRequest) -> impl Stream<Item = Result<Event>> + Send {
+        let client = self.clone();
+        stream::try_unfold(Some(query), move |query| {
+            let client = client.clone();
+            async move {
+                let Some(query) = query else {
+                    return Ok(None);
+                };
+                let page = client.query_events(query.clone()).await?;
+                let next = page.next_cursor.map(|cursor| QueryEventsRequest {
+                    cursor: Some(cursor),
+                    ..query
+                });
+                Ok(Some((stream::iter(page.events.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// One page of stored transactions, newest first
+    pub async fn query_transactions(
+        &self,
+        query: QueryTransactionsRequest,
+    ) -> Result<QueryTransactionsResponse> {
+        let request = self.request(query)?;
+        Ok(self
+            .inner
+            .clone()
+            .query_transactions(request)
+            .await?
+            .into_inner())
+    }
+
+    /// Matched events of every committed checkpoint
+    ///
+    /// With `from_checkpoint`, stored events are replayed first and a batch
+    /// with `caught_up` set and no events marks the switch to live ones.
+    /// A subscription that falls behind is reopened from the checkpoint the
+    /// server hands back, so no batches are missed; the reopened replay ends
+    /// with another marker. Replaying needs `storage.store_raw_event` on the
+    /// server. Any other failure ends the stream.
+    pub async fn subscribe(&self, subscription: SubscribeEventsRequest) -> Result<EventBatches> {
+        let batches = self.open(subscription.clone()).await?;
+        let client = self.clone();
+        let stream = stream::unfold(Some((client, subscription, batches)), |state| async move {
+            let (client, mut subscription, mut batches) = state?;
+            loop {
+                match batches.message().await {
+                    Ok(Some(batch)) => {
+                        return Some((Ok(batch), Some((client, subscription, batches))))
+                    }
+                    Ok(None) => return None,
+                    Err(status) => {
+                        let Some(checkpoint) = resume_point(&status) else {
+                            return Some((Err(status.into()), None));
+                        };
+                        subscription.from_checkpoint = Some(checkpoint);
+                        match client.open(subscription.clone()).await {
+                            Ok(reopened) => batches = reopened,
+                            Err(e) => return Some((Err(e), None)),
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    async fn open(&self, subscription: SubscribeEventsRequest) -> Result<Streaming<EventBatch>> {
+        let request = self.request(subscription)?;
+        Ok(self
+            .inner
+            .clone()
+            .subscribe_events(request)
+            .await?
+            .into_inner())
+    }
+
+    fn request<T>(&self, message: T) -> Result<Request<T>> {
+        let mut request = Request::new(message);
+        if let Some(token) = &self.token {
+            let value = format!("Bearer {token}")
+                .parse()
+                .map_err(|_| ClientError::InvalidToken)?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        Ok(request)
+    }
+}
+
+impl Event {
+    /// Decoded event fields
+    pub fn fields(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_str(&self.fields_json)
+    }
+
+    /// When the checkpoint of the event was created
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.timestamp_ms)
+    }
+}
+
+impl Transaction {
+    /// When the checkpoint of the transaction was created
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.timestamp_ms)
+    }
+}
+
+/// `status` if the server speaks [`API_VERSION`]
+fn compatible(status: GetStatusResponse) -> Result<GetStatusResponse> {
+    if status.api_version != API_VERSION {
+        return Err(ClientError::Incompatible {
+            version: status.version,
+            server: status.api_version,
+            client: API_VERSION,
+        });
+    }
+    Ok(status)
+}
+
+/// Checkpoint to reopen a subscription from, if it ended by falling behind
+///
+/// Rate limited calls are also `RESOURCE_EXHAUSTED` but carry no cursor.
+fn resume_point(status: &tonic::Status) -> Option<u64> {
+    if status.code() != Code::ResourceExhausted {
+        return None;
+    }
+    status
+        .metadata()
+        .get(RESUME_TRAILER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::metadata::MetadataMap;
+
+    use super::*;
+
+    #[test]
+    fn test_version_check_rejects_other_revisions() {
+        let status = |api_version| GetStatusResponse {
+            version: "0.1.0".to_string(),
+            api_version,
+            ..Default::default()
+        };
+        assert!(compatible(status(API_VERSION)).is_ok());
+        assert!(matches!(
+            compatible(status(API_VERSION + 1)),
+            Err(ClientError::Incompatible { server, client, .. })
+                if server == API_VERSION + 1 && client == API_VERSION
+        ));
+    }
+
+    #[test]
+    fn test_only_lagging_subscriptions_resume() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(RESUME_TRAILER, 42u64.into());
+        let lagged = tonic::Status::with_metadata(
+            Code::ResourceExhausted,
+            "Subscriber fell behind",
+            metadata,
+        );
+        assert_eq!(resume_point(&lagged), Some(42));
+
+        let limited = tonic::Status::resource_exhausted("Rate limit exceeded");
+        assert_eq!(resume_point(&limited), None);
+        let mut metadata = MetadataMap::new();
+        metadata.insert(RESUME_TRAILER, 42u64.into());
+        let replay = tonic::Status::with_metadata(Code::Unavailable, "Replay failed", metadata);
+        assert_eq!(resume_point(&replay), None);
+    }
+
+    #[test]
+    fn test_event_fields_and_timestamp_decode() {
+        let event = Event {
+            timestamp_ms: 1_700_000_000_000,
+            fields_json: r#"{"amount": "1500"}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(event.fields().unwrap()["amount"], "1500");
+        assert_eq!(
+            event.timestamp().map(|t| t.timestamp()),
+            Some(1_700_000_000)
+        );
+    }
+}
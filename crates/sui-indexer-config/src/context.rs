@@ -0,0 +1,125 @@
+use eyre::Result;
+
+use crate::{ConfigError, IndexerConfig};
+
+impl IndexerConfig {
+    /// Name of the top-level network context, its network preset
+    pub fn context_name(&self) -> &'static str {
+        self.network.network.as_str()
+    }
+
+    /// Configuration of every context in `networks`, keyed by context name
+    ///
+    /// Each is this configuration with the context's network, filters, start
+    /// and sinks, storing its tables in the context's schema. The control
+    /// endpoint, the gRPC API, the Flight endpoint and the metrics server are
+    /// left to the top-level network.
+    pub fn network_contexts(&self) -> Vec<(String, IndexerConfig)> {
+        self.networks
+            .iter()
+            .map(|(name, context)| {
+                let mut config = self.clone();
+                config.networks.clear();
+
+                config.network.network = context.network;
+                config.network.grpc_url = context.grpc_url.clone();
+                config.network.fallback_grpc_urls = context.fallback_grpc_urls.clone();
+                config.network.chain_id = context.chain_id.clone();
+                config.database.schema =
+                    Some(context.schema.clone().unwrap_or_else(|| name.clone()));
+                if let Some(filters) = &context.filters {
+                    config.events.filters = filters.clone();
+                }
+                if let Some(start_mode) = context.start_mode {
+                    config.events.start_mode = start_mode;
+                }
+                config.events.start_checkpoint = context.start_checkpoint;
+                config.sinks = context.sinks.clone();
+                // Watchlists route to top-level sinks, which the context does not have
+                for watchlist in config.watchlists.values_mut() {
+                    watchlist.sinks.clear();
+                }
+
+                config.admin.addr = None;
+                config.api.addr = None;
+                config.grpc.addr = None;
+                config.flight.addr = None;
+                config.observability.metrics_addr = None;
+                (name.clone(), config)
+            })
+            .collect()
+    }
+
+    /// Configuration of the network context `name`, the top-level one included
+    pub fn network_context(&self, name: &str) -> Result<IndexerConfig> {
+        if name == self.context_name() {
+            let mut config = self.clone();
+            config.networks.clear();
+            return Ok(config);
+        }
+        self.network_contexts()
+            .into_iter()
+            .find_map(|(context, config)| (context == name).then_some(config))
+            .ok_or_else(|| ConfigError::UnknownNetwork(name.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Network, NetworkContextConfig, StartMode};
+
+    #[test]
+    fn test_contexts_override_network_and_storage() {
+        let mut config = IndexerConfig::default();
+        config.network.network = Network::Mainnet;
+        config.events.start_mode = StartMode::Latest;
+        config.admin.addr = Some("127.0.0.1:9185".parse().unwrap());
+        config.networks.insert(
+            "testnet".to_string(),
+            NetworkContextConfig {
+                network: Network::Testnet,
+                start_mode: Some(StartMode::Checkpoint(1000)),
+                ..NetworkContextConfig::default()
+            },
+        );
+        config.networks.insert(
+            "devnet".to_string(),
+            NetworkContextConfig {
+                network: Network::Devnet,
+                schema: Some("sui_devnet".to_string()),
+                filters: Some(vec![]),
+                ..NetworkContextConfig::default()
+            },
+        );
+
+        assert_eq!(config.context_name(), "mainnet");
+        let contexts = config.network_contexts();
+        let names: Vec<_> = contexts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["devnet", "testnet"]);
+
+        let (_, devnet) = &contexts[0];
+        assert_eq!(devnet.database.schema.as_deref(), Some("sui_devnet"));
+        assert!(devnet.events.filters.is_empty());
+        assert_eq!(devnet.events.start_mode, StartMode::Latest);
+
+        let (_, testnet) = &contexts[1];
+        assert_eq!(testnet.network.network, Network::Testnet);
+        assert_eq!(testnet.database.schema.as_deref(), Some("testnet"));
+        assert_eq!(testnet.events.filters.len(), config.events.filters.len());
+        assert_eq!(testnet.events.start_mode, StartMode::Checkpoint(1000));
+        assert!(testnet.admin.addr.is_none());
+        assert!(testnet.networks.is_empty());
+
+        assert!(config
+            .network_context("mainnet")
+            .unwrap()
+            .networks
+            .is_empty());
+        assert_eq!(
+            config.network_context("devnet").unwrap().network.network,
+            Network::Devnet
+        );
+        assert!(config.network_context("localnet").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use eyre::Result;
+
+/// Broad cause of a failure, attached to errors so callers can tell causes apart
+///
+/// Kinds are attached as error context with [`WithKind::kind`] and read back
+/// with [`error_kind`]; the innermost kind wins, so a storage error stays a
+/// storage error however much context is added on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The configuration could not be loaded or is invalid
+    Config,
+    /// The node or the control endpoint could not be reached
+    Connectivity,
+    /// The database failed or could not be reached
+    Storage,
+    /// The operation was cancelled before it completed
+    Interrupted,
+}
+
+impl ErrorKind {
+    /// Short lowercase name of the kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Connectivity => "connectivity",
+            Self::Storage => "storage",
+            Self::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Config => "configuration error",
+            Self::Connectivity => "connectivity error",
+            Self::Storage => "storage error",
+            Self::Interrupted => "interrupted",
+        })
+    }
+}
+
+/// Failure loading or resolving the configuration
+///
+/// Fixing the configuration is the only remedy, so none of these go away
+/// when retried. They carry [`ErrorKind::Config`] without being marked and
+/// are read back with [`ConfigError::find`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    /// A config or filter file or directory that could not be read
+    #[error("Failed to read {what}: {reason}")]
+    Read { what: String, reason: String },
+    /// A config or filter file that does not parse or does not fit the schema
+    #[error("Invalid {what}: {reason}")]
+    Parse { what: String, reason: String },
+    /// Config files that extend each other in a loop
+    #[error("Config extends cycle: {0}")]
+    ExtendsCycle(String),
+    /// An `extends` key that is not a path
+    #[error("{0}: `extends` must be a path to a base config file")]
+    InvalidExtends(String),
+    /// A `version` key that is not a config version
+    #[error("{file}: config version must be a non-negative integer, got {version}")]
+    InvalidVersion { file: String, version: String },
+    /// A config file written for a newer indexer
+    #[error(
+        "{file}: config version {version} is newer than the supported version {supported}; upgrade the indexer"
+    )]
+    UnsupportedVersion {
+        file: String,
+        version: u32,
+        supported: u32,
+    },
+    /// A filter name defined twice
+    #[error("Filter name {name:?} from {origin} is already defined in {existing}")]
+    DuplicateFilter {
+        name: String,
+        origin: String,
+        existing: String,
+    },
+    /// A filter name that is not configured
+    #[error("Unknown event filter `{0}`")]
+    UnknownFilter(String),
+    /// A network or network context name that is not known
+    #[error("Unknown network `{0}`")]
+    UnknownNetwork(String),
+    /// An API key scope that is not known
+    #[error("Unknown API scope `{0}`, expected `read` or `admin`")]
+    UnknownScope(String),
+    /// A custom network without an endpoint
+    #[error("network.grpc_url is required for custom networks")]
+    MissingGrpcUrl,
+    /// Two settings of which at most one may be set
+    #[error("{0} and {1} are mutually exclusive")]
+    Exclusive(&'static str, &'static str),
+}
+
+impl ConfigError {
+    /// The configuration error behind `err`, if that is what failed
+    pub fn find(err: &eyre::Report) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+}
+
+/// Attach an [`ErrorKind`] to the error of a result
+pub trait WithKind<T> {
+    /// Mark the error, if any, as caused by `kind`
+    fn kind(self, kind: ErrorKind) -> Result<T>;
+}
+
+impl<T> WithKind<T> for Result<T> {
+    fn kind(self, kind: ErrorKind) -> Result<T> {
+        self.map_err(|err| match error_kind(&err) {
+            Some(_) => err,
+            None => err.wrap_err(kind),
+        })
+    }
+}
+
+/// The kind attached to `err`, if any
+pub fn error_kind(err: &eyre::Report) -> Option<ErrorKind> {
+    match ConfigError::find(err) {
+        Some(_) => Some(ErrorKind::Config),
+        None => err.downcast_ref::<ErrorKind>().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::WrapErr;
+
+    use super::*;
+
+    #[test]
+    fn test_kind_survives_context() {
+        let err = Err::<(), _>(eyre::eyre!("connection refused"))
+            .kind(ErrorKind::Storage)
+            .wrap_err("Failed to store events")
+            .kind(ErrorKind::Connectivity)
+            .unwrap_err();
+        assert_eq!(error_kind(&err), Some(ErrorKind::Storage));
+        assert_eq!(error_kind(&eyre::eyre!("plain")), None);
+
+        let err = Err::<(), _>(ConfigError::UnknownFilter("cetus".to_string()))
+            .wrap_err("Failed to start the indexer")
+            .kind(ErrorKind::Connectivity)
+            .unwrap_err();
+        assert_eq!(error_kind(&err), Some(ErrorKind::Config));
+    }
+}
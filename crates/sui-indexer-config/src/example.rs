@@ -0,0 +1,104 @@
+use std::fmt::Write;
+
+use eyre::Result;
+use serde_json::Value;
+
+use crate::{ConfigLoader, IndexerConfig};
+
+/// Render the default configuration as TOML, optionally annotating every
+/// setting with its description from the JSON Schema
+pub fn example_config(with_comments: bool) -> Result<String> {
+    render_config(&IndexerConfig::default(), with_comments)
+}
+
+/// Render a configuration as TOML, optionally annotated like [`example_config`]
+pub fn render_config(config: &IndexerConfig, with_comments: bool) -> Result<String> {
+    if !with_comments {
+        return Ok(toml::to_string_pretty(config)?);
+    }
+
+    let schema = ConfigLoader::json_schema();
+    let table = toml::Table::try_from(config)?;
+    let mut out = String::new();
+    write_description(&mut out, Some(&schema))?;
+    write_table(&mut out, &schema, &schema, &table, "")?;
+    Ok(out.trim_start().to_string())
+}
+
+/// Write the plain values of `table` followed by its sub-tables
+fn write_table(
+    out: &mut String,
+    root: &Value,
+    schema: &Value,
+    table: &toml::Table,
+    path: &str,
+) -> std::fmt::Result {
+    let schema = resolve(root, schema);
+
+    for (key, value) in table.iter().filter(|(_, value)| !value.is_table()) {
+        write_description(out, property(root, schema, key))?;
+        writeln!(out, "{key} = {value}")?;
+    }
+
+    for (key, value) in table {
+        let toml::Value::Table(child) = value else {
+            continue;
+        };
+        let child_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        let child_schema = property(root, schema, key);
+
+        writeln!(out)?;
+        write_description(out, child_schema)?;
+        writeln!(out, "[{child_path}]")?;
+        write_table(
+            out,
+            root,
+            child_schema.unwrap_or(&Value::Null),
+            child,
+            &child_path,
+        )?;
+    }
+    Ok(())
+}
+
+fn write_description(out: &mut String, schema: Option<&Value>) -> std::fmt::Result {
+    let Some(description) = schema.and_then(|schema| schema["description"].as_str()) else {
+        return Ok(());
+    };
+    for line in description.lines() {
+        writeln!(out, "{}", format!("# {line}").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Schema of a property of an object schema, or of its map values
+fn property<'a>(root: &'a Value, schema: &'a Value, key: &str) -> Option<&'a Value> {
+    let schema = resolve(root, schema);
+    schema["properties"]
+        .get(key)
+        .or_else(|| schema.get("additionalProperties"))
+        .filter(|schema| schema.is_object())
+}
+
+/// Follow `$ref`s and unwrap `Option` schemas to the described object
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    if let Some(name) = schema["$ref"]
+        .as_str()
+        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+    {
+        return resolve(root, &root["$defs"][name]);
+    }
+    for combinator in ["anyOf", "oneOf", "allOf"] {
+        if let Some(inner) = schema[combinator]
+            .as_array()
+            .and_then(|variants| variants.iter().find(|variant| variant["type"] != "null"))
+        {
+            return resolve(root, inner);
+        }
+    }
+    schema
+}
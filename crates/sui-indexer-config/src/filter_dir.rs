@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use eyre::Result;
+use serde::Deserialize;
+
+use crate::{ConfigError, EventFilter, EventsConfig};
+
+/// File extensions recognized in a filters directory
+const FILTER_EXTENSIONS: &[&str] = &["toml", "json"];
+
+/// Contents of a single filter definition file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterFile {
+    filters: Vec<EventFilter>,
+}
+
+/// List the filter definition files in `dir`, sorted by file name
+pub fn filter_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| ConfigError::Read {
+        what: format!("filters directory {}", dir.display()),
+        reason: e.to_string(),
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let recognized = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FILTER_EXTENSIONS.contains(&ext));
+        if recognized && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Load every filter defined in `dir`
+///
+/// Filters without a `name` are named after their file, e.g. `cetus` or
+/// `cetus#2` for the second filter in `cetus.toml`. Names must be unique
+/// across the directory.
+pub fn load_filters(dir: &Path) -> Result<Vec<EventFilter>> {
+    let mut filters = Vec::new();
+    let mut origins: HashMap<String, PathBuf> = HashMap::new();
+
+    for path in filter_files(dir)? {
+        for filter in load_filter_file(&path)? {
+            let name = filter.name.clone().unwrap_or_default();
+            if let Some(existing) = origins.insert(name.clone(), path.clone()) {
+                return Err(ConfigError::DuplicateFilter {
+                    name,
+                    origin: path.display().to_string(),
+                    existing: existing.display().to_string(),
+                }
+                .into());
+            }
+            filters.push(filter);
+        }
+    }
+
+    Ok(filters)
+}
+
+/// Load the filters defined in one TOML or JSON file
+///
+/// Filters without a `name` are named after the file, as in [`load_filters`].
+pub fn load_filter_file(path: &Path) -> Result<Vec<EventFilter>> {
+    let what = format!("filter file {}", path.display());
+    let content = std::fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        what: what.clone(),
+        reason: e.to_string(),
+    })?;
+    let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        _ => toml::from_str(&content).map_err(|e| e.to_string()),
+    };
+    let file: FilterFile = parsed.map_err(|reason| ConfigError::Parse { what, reason })?;
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(file
+        .filters
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut filter)| {
+            filter.name.get_or_insert_with(|| match index {
+                0 => stem.clone(),
+                _ => format!("{stem}#{}", index + 1),
+            });
+            filter
+        })
+        .collect())
+}
+
+/// Append the filters from `events.filters_dir`, if set, to `events.filters`
+pub fn append_filters(events: &mut EventsConfig) -> Result<()> {
+    let Some(dir) = &events.filters_dir else {
+        return Ok(());
+    };
+
+    let loaded = load_filters(dir)?;
+    for filter in &loaded {
+        let name = filter.name.as_deref().unwrap_or_default();
+        if events
+            .filters
+            .iter()
+            .any(|f| f.name.as_deref() == Some(name))
+        {
+            return Err(ConfigError::DuplicateFilter {
+                name: name.to_string(),
+                origin: dir.display().to_string(),
+                existing: "events.filters".to_string(),
+            }
+            .into());
+        }
+    }
+    events.filters.extend(loaded);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_load_filters_from_dir() -> Result<()> {
+        let dir = TempDir::new()?;
+        std::fs::write(
+            dir.path().join("coin.toml"),
+            "[[filters]]\npackage = \"0x2\"\nmodule = \"coin\"\n\n[[filters]]\nname = \"sui-system\"\npackage = \"0x3\"\n",
+        )?;
+        std::fs::write(
+            dir.path().join("deepbook.json"),
+            r#"{"filters": [{"package": "0xdee9"}]}"#,
+        )?;
+        std::fs::write(dir.path().join("README.md"), "ignored")?;
+
+        let filters = load_filters(dir.path())?;
+        let names: Vec<_> = filters.iter().filter_map(|f| f.name.as_deref()).collect();
+        assert_eq!(names, vec!["coin", "sui-system", "deepbook"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_filter_names_rejected() -> Result<()> {
+        let dir = TempDir::new()?;
+        std::fs::write(dir.path().join("a.toml"), "[[filters]]\nname = \"dup\"\n")?;
+        std::fs::write(dir.path().join("b.toml"), "[[filters]]\nname = \"dup\"\n")?;
+
+        let err = load_filters(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("a.toml") && err.contains("b.toml"));
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use std::path::{Path, PathBuf};
+
+use eyre::Result;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::{filter_dir, migration, ConfigError, IndexerConfig};
+
+/// Key naming the base file a configuration file overrides
+pub const EXTENDS_KEY: &str = "extends";
+
+/// A configuration file merged over the chain of base files it extends
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// Merged TOML table with `extends` resolved and removed
+    pub table: toml::Table,
+    /// Contributing files, from the requested file down to the root base
+    pub sources: Vec<PathBuf>,
+    /// Changes made while upgrading files from older config versions
+    pub upgrades: Vec<String>,
+    /// Original text when no base file was involved, kept for precise error spans
+    content: Option<String>,
+}
+
+impl LayeredConfig {
+    /// Load a configuration file and every base file it transitively extends
+    ///
+    /// Tables are merged key by key with the overlay winning; any other value,
+    /// including arrays such as `events.filters`, is replaced as a whole.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut sources = Vec::new();
+        let mut upgrades = Vec::new();
+        let mut content = None;
+        let table = load_layer(path, &mut sources, &mut upgrades, &mut content)?;
+
+        Ok(Self {
+            table,
+            upgrades,
+            content: if sources.len() == 1 { content } else { None },
+            sources,
+        })
+    }
+
+    /// Human-readable chain of contributing files, e.g. `mainnet.toml -> base.toml`
+    pub fn provenance(&self) -> String {
+        describe_chain(&self.sources)
+    }
+
+    /// Filters directory resolved against the file that declared it
+    pub fn filters_dir(&self) -> Option<PathBuf> {
+        self.table
+            .get("events")?
+            .get("filters_dir")?
+            .as_str()
+            .map(PathBuf::from)
+    }
+
+    /// Watchlist files by watchlist name, resolved against the file that declared them
+    fn watchlist_files(&self) -> Vec<(String, PathBuf)> {
+        let Some(toml::Value::Table(watchlists)) = self.table.get("watchlists") else {
+            return Vec::new();
+        };
+        watchlists
+            .iter()
+            .filter_map(|(name, watchlist)| {
+                let file = watchlist.get("file")?.as_str()?;
+                Some((name.clone(), PathBuf::from(file)))
+            })
+            .collect()
+    }
+
+    /// Deserialize the merged table into an indexer configuration
+    ///
+    /// Filters from `events.filters_dir` are appended to `events.filters`, and
+    /// upgrades from older config versions are logged as warnings.
+    pub fn into_config(self) -> Result<IndexerConfig> {
+        for upgrade in &self.upgrades {
+            warn!("Upgraded configuration {upgrade}; update the file to silence this warning");
+        }
+
+        let provenance = self.provenance();
+        let filters_dir = self.filters_dir();
+        let watchlist_files = self.watchlist_files();
+        let result: Result<IndexerConfig, _> = match &self.content {
+            Some(content) => toml::from_str(content),
+            None => IndexerConfig::deserialize(toml::Value::Table(self.table)),
+        };
+        let mut config = result.map_err(|e| ConfigError::Parse {
+            what: format!("configuration in {provenance}"),
+            reason: e.to_string(),
+        })?;
+
+        config.version = migration::CONFIG_VERSION;
+        config.events.filters_dir = filters_dir;
+        for (name, file) in watchlist_files {
+            if let Some(watchlist) = config.watchlists.get_mut(&name) {
+                watchlist.file = Some(file);
+            }
+        }
+        filter_dir::append_filters(&mut config.events)?;
+        Ok(config)
+    }
+}
+
+fn load_layer(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    upgrades: &mut Vec<String>,
+    content_out: &mut Option<String>,
+) -> Result<toml::Table> {
+    let resolved = path.canonicalize().map_err(|e| ConfigError::Read {
+        what: match chain.last() {
+            Some(child) => format!(
+                "config file {} (extended by {})",
+                path.display(),
+                child.display()
+            ),
+            None => format!("config file {}", path.display()),
+        },
+        reason: e.to_string(),
+    })?;
+
+    if chain.contains(&resolved) {
+        chain.push(resolved);
+        return Err(ConfigError::ExtendsCycle(describe_chain(chain)).into());
+    }
+    chain.push(resolved.clone());
+
+    let content = std::fs::read_to_string(&resolved).map_err(|e| ConfigError::Read {
+        what: format!("config file {}", resolved.display()),
+        reason: e.to_string(),
+    })?;
+    let mut table: toml::Table = toml::from_str(&content).map_err(|e| ConfigError::Parse {
+        what: format!("config file {}", resolved.display()),
+        reason: e.to_string(),
+    })?;
+    let notes = migration::migrate(&mut table, &resolved.display().to_string())?;
+    if content_out.is_none() && notes.is_empty() {
+        *content_out = Some(content);
+    }
+    upgrades.extend(notes);
+    if let Some(dir) = resolved.parent() {
+        resolve_filters_dir(&mut table, dir);
+        resolve_watchlist_files(&mut table, dir);
+    }
+
+    let Some(extends) = table.remove(EXTENDS_KEY) else {
+        return Ok(table);
+    };
+    let Some(base) = extends.as_str() else {
+        return Err(ConfigError::InvalidExtends(resolved.display().to_string()).into());
+    };
+
+    // Base paths are relative to the file that names them
+    let base = resolved
+        .parent()
+        .map(|dir| dir.join(base))
+        .unwrap_or_else(|| PathBuf::from(base));
+    let mut merged = load_layer(&base, chain, upgrades, content_out)?;
+    merge_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Recursively merge `overlay` into `base`, with overlay values taking precedence
+pub fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Make a relative `events.filters_dir` relative to the directory of its config file
+fn resolve_filters_dir(table: &mut toml::Table, config_dir: &Path) {
+    let Some(toml::Value::String(filters_dir)) = table
+        .get_mut("events")
+        .and_then(|events| events.get_mut("filters_dir"))
+    else {
+        return;
+    };
+
+    if Path::new(filters_dir.as_str()).is_relative() {
+        *filters_dir = config_dir.join(&*filters_dir).display().to_string();
+    }
+}
+
+/// Make relative `watchlists.*.file` paths relative to the directory of their config file
+fn resolve_watchlist_files(table: &mut toml::Table, config_dir: &Path) {
+    let Some(toml::Value::Table(watchlists)) = table.get_mut("watchlists") else {
+        return;
+    };
+    for watchlist in watchlists.values_mut() {
+        if let Some(toml::Value::String(file)) = watchlist.get_mut("file") {
+            if Path::new(file.as_str()).is_relative() {
+                *file = config_dir.join(&*file).display().to_string();
+            }
+        }
+    }
+}
+
+fn describe_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{ConfigLoader, Network};
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_overlay_extends_base() -> Result<()> {
+        let dir = TempDir::new()?;
+        let base = toml::to_string(&IndexerConfig::default())?;
+        write(&dir, "base.toml", &base);
+        let overlay = write(
+            &dir,
+            "mainnet.toml",
+            "extends = \"base.toml\"\n[network]\nnetwork = \"mainnet\"\n[events]\nbatch_size = 7\n",
+        );
+
+        let layered = LayeredConfig::load(&overlay)?;
+        assert_eq!(layered.sources.len(), 2);
+
+        let config = ConfigLoader::from_file(&overlay)?;
+        assert_eq!(config.network.network, Network::Mainnet);
+        assert_eq!(config.events.batch_size, 7);
+        assert_eq!(
+            config.events.max_concurrent_batches,
+            IndexerConfig::default().events.max_concurrent_batches
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_filters_dir_relative_to_config() -> Result<()> {
+        let dir = TempDir::new()?;
+        std::fs::create_dir(dir.path().join("filters"))?;
+        write(
+            &dir,
+            "filters/coin.toml",
+            "[[filters]]\npackage = \"0x2\"\n",
+        );
+        let mut config = toml::Value::try_from(IndexerConfig::default())?;
+        config["events"]
+            .as_table_mut()
+            .unwrap()
+            .insert("filters_dir".to_string(), "filters".into());
+        let path = write(&dir, "config.toml", &toml::to_string(&config)?);
+
+        let config = ConfigLoader::from_file(&path)?;
+        assert_eq!(config.events.filters.len(), 1);
+        assert_eq!(config.events.filters[0].name.as_deref(), Some("coin"));
+
+        let sources = ConfigLoader::source_files(&path)?;
+        assert_eq!(sources.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extends_cycle_detected() -> Result<()> {
+        let dir = TempDir::new()?;
+        write(&dir, "a.toml", "extends = \"b.toml\"\n");
+        let b = write(&dir, "b.toml", "extends = \"a.toml\"\n");
+
+        let err = LayeredConfig::load(&b).unwrap_err().to_string();
+        assert!(err.contains("cycle"));
+        assert!(err.contains("a.toml"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_base_names_child() -> Result<()> {
+        let dir = TempDir::new()?;
+        let overlay = write(&dir, "testnet.toml", "extends = \"missing.toml\"\n");
+
+        let err = LayeredConfig::load(&overlay).unwrap_err().to_string();
+        assert!(err.contains("missing.toml"));
+        assert!(err.contains("extended by"));
+        Ok(())
+    }
+}
@@ -1,38 +1,195 @@
-use std::path::Path;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use chrono::{DateTime, Utc};
 use eyre::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+pub mod context;
+pub mod error;
+pub mod example;
+pub mod filter_dir;
+pub mod layering;
+pub mod migration;
+pub mod network;
+pub mod redact;
+pub mod reload;
+pub mod scaffold;
+pub mod secret;
+pub mod validate;
+
+pub use error::{error_kind, ConfigError, ErrorKind, WithKind};
+pub use layering::LayeredConfig;
+pub use migration::CONFIG_VERSION;
+pub use network::{FrameworkPackages, Network};
+pub use reload::{ReloadReport, RELOADABLE_FIELDS};
+pub use secret::SecretRef;
+pub use validate::ValidationError;
+
 /// Main configuration for the Sui Indexer
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct IndexerConfig {
+    /// Configuration file format version; files without one are upgraded from version 0
+    #[serde(default)]
+    pub version: u32,
+    /// Base configuration file this one overrides, relative to this file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<PathBuf>,
     /// Network configuration for connecting to Sui
     pub network: NetworkConfig,
     /// Database configuration
     pub database: DatabaseConfig,
     /// Event indexing configuration
     pub events: EventsConfig,
+    /// What gets persisted and for how long
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Tracking of package publishes and upgrades
+    #[serde(default)]
+    pub packages: PackagesConfig,
+    /// Indexing of Move calls by call target
+    #[serde(default)]
+    pub move_calls: MoveCallsConfig,
+    /// Version history of selected objects
+    #[serde(default)]
+    pub objects: ObjectsConfig,
+    /// Address watchlists tagging and routing the events that involve them, keyed by name
+    #[serde(default)]
+    pub watchlists: BTreeMap<String, WatchlistConfig>,
+    /// Rules tagging and notifying the stored events that meet their conditions, keyed by name
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleConfig>,
+    /// Built-in protocol decoders keyed by name (`navi`, `cetus`, `deepbook`)
+    #[serde(default)]
+    pub protocols: BTreeMap<String, ProtocolConfig>,
+    /// External outputs that receive stored events, keyed by sink name
+    #[serde(default)]
+    pub sinks: BTreeMap<String, SinkConfig>,
+    /// Transactional outbox between the event tables and the sinks
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+    /// Pipeline runtime tuning
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    /// Logging, metrics and tracing configuration
+    #[serde(default)]
+    pub observability: ObservabilityConfig,
+    /// Alert rules and notification channels
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    /// Control endpoint of a running indexer
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// HTTP API streaming matched events to clients
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// gRPC API serving indexed data to other services
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    /// Arrow Flight endpoint serving indexed data as record batches
+    #[serde(default)]
+    pub flight: FlightConfig,
+    /// API keys accepted by the control endpoint, the HTTP API, the gRPC API and the Flight endpoint
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Detection of ingestion that stopped making progress
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    /// Further networks indexed by the same process, keyed by context name
+    #[serde(default)]
+    pub networks: BTreeMap<String, NetworkContextConfig>,
 }
 
 /// Network configuration for Sui blockchain connection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct NetworkConfig {
-    /// Sui gRPC endpoint URL
-    pub grpc_url: Url,
-    /// Network name (mainnet, testnet, devnet, localnet)
-    pub network: String,
+    /// Sui gRPC endpoint URL, overriding the network preset
+    #[serde(default)]
+    pub grpc_url: Option<Url>,
+    /// Further gRPC endpoints, tried in order when the active one fails
+    #[serde(default)]
+    pub fallback_grpc_urls: Vec<Url>,
+    /// Network preset (mainnet, testnet, devnet, localnet, custom)
+    pub network: Network,
+    /// Expected chain identifier, overriding the network preset
+    #[serde(default)]
+    pub chain_id: Option<String>,
     /// Connection pool settings
     pub pool: PoolConfig,
     /// Retry configuration
     pub retry: RetryConfig,
 }
 
+/// Another network indexed next to the top-level one
+///
+/// A context runs its own client, pipeline and storage, with its tables in a
+/// PostgreSQL schema of their own on the same database. Settings not listed
+/// here are shared with the top-level configuration; the control endpoint,
+/// the gRPC API, the Flight endpoint and the metrics server only run once,
+/// for the top-level network.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct NetworkContextConfig {
+    /// Network preset (mainnet, testnet, devnet, localnet, custom)
+    pub network: Network,
+    /// Sui gRPC endpoint URL, overriding the network preset
+    pub grpc_url: Option<Url>,
+    /// Further gRPC endpoints, tried in order when the active one fails
+    pub fallback_grpc_urls: Vec<Url>,
+    /// Expected chain identifier, overriding the network preset
+    pub chain_id: Option<String>,
+    /// PostgreSQL schema holding this network's tables; defaults to the context name
+    pub schema: Option<String>,
+    /// Event filters of this network; defaults to the top-level `events.filters`
+    pub filters: Option<Vec<EventFilter>>,
+    /// Where this network starts indexing; defaults to the top-level `events.start_mode`
+    pub start_mode: Option<StartMode>,
+    /// Checkpoint the `resume` start mode falls back to without a stored cursor
+    pub start_checkpoint: Option<u64>,
+    /// Sinks receiving this network's events; the top-level sinks are not shared
+    pub sinks: BTreeMap<String, SinkConfig>,
+}
+
 /// Database connection configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct DatabaseConfig {
-    /// Database connection URL
-    pub url: String,
+    /// Database connection URL, overridden field by field by the structured settings below
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Server host name or Unix socket directory
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Server port
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// User to connect as
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Password in plain text; prefer `password_secret`
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Password read from an environment variable or file
+    #[serde(default)]
+    pub password_secret: Option<SecretRef>,
+    /// Database name
+    #[serde(default)]
+    pub dbname: Option<String>,
+    /// TLS negotiation mode
+    #[serde(default)]
+    pub sslmode: Option<SslMode>,
+    /// Server-side statement timeout in seconds
+    #[serde(default)]
+    pub statement_timeout: Option<u64>,
     /// Maximum number of connections in pool
     pub max_connections: u32,
     /// Minimum idle connections
@@ -41,14 +198,827 @@ pub struct DatabaseConfig {
     pub connect_timeout: u64,
     /// Idle connection timeout in seconds
     pub idle_timeout: Option<u64>,
-    /// Whether to run migrations on startup
+    /// Whether to run migrations on startup; when off, startup fails while migrations are pending
     pub auto_migrate: bool,
+    /// PostgreSQL schema holding the indexer tables, created if missing; defaults to the search path
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+/// PostgreSQL `sslmode` connection setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    /// Never use TLS
+    Disable,
+    /// Use TLS only if the server requires it
+    Allow,
+    /// Use TLS if the server supports it
+    Prefer,
+    /// Always use TLS without verifying the certificate
+    Require,
+    /// Always use TLS and verify the certificate authority
+    VerifyCa,
+    /// Always use TLS and verify the certificate and host name
+    VerifyFull,
+}
+
+/// Storage content and retention configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct StorageConfig {
+    /// Store the full original event JSON alongside the extracted columns
+    pub store_raw_event: bool,
+    /// Keep the BCS bytes inside stored raw events
+    pub store_bcs: bool,
+    /// Store transaction effects with processed transactions
+    pub store_transactions_effects: bool,
+    /// Delete events and transactions older than this many days (kept forever if unset)
+    pub retention_days: Option<u32>,
+    /// Table partitioning for processed events, applied when the table is first created
+    pub partitioning: PartitionStrategy,
+}
+
+/// Partitioning strategy for the processed events table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionStrategy {
+    /// A single unpartitioned table
+    #[default]
+    None,
+    /// One partition per day of event timestamp
+    Daily,
+    /// One partition per month of event timestamp
+    Monthly,
+}
+
+/// Package publish and upgrade tracking
+///
+/// Tracked package versions are stored in the `packages` table with the ID
+/// of their first version, and a filter's `package` then matches events of
+/// every version of that package.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct PackagesConfig {
+    /// Whether publishes and upgrades are tracked
+    pub enabled: bool,
+    /// Original package IDs whose upgrades are tracked; every package on the network if empty
+    pub original_ids: Vec<String>,
+}
+
+/// Move call indexing
+///
+/// Every Move call made by a programmable transaction that matches one of
+/// `filters` is stored in the `move_calls` table with its type arguments,
+/// whether or not the called function emits an event.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct MoveCallsConfig {
+    /// Whether Move calls are indexed
+    pub enabled: bool,
+    /// Call targets to index; every call if empty
+    pub filters: Vec<MoveCallFilter>,
+}
+
+/// Object version history
+///
+/// Every version of a listed object that a checkpoint creates, mutates,
+/// wraps or deletes is stored in the `object_versions` table with its
+/// owner and contents, e.g. to chart the reserves of a DEX pool over time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ObjectsConfig {
+    /// Object IDs whose versions are recorded; none if empty
+    pub ids: Vec<String>,
+}
+
+/// Move call target filter; a call matches if every set field does
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MoveCallFilter {
+    /// Package ID of the called function (optional)
+    pub package: Option<String>,
+    /// Module of the called function (optional)
+    pub module: Option<String>,
+    /// Name of the called function, e.g. `swap` (optional, requires `module`)
+    pub function: Option<String>,
+    /// Sender of the calling transaction (optional)
+    pub sender: Option<String>,
+}
+
+/// Addresses whose events are tagged, and optionally captured and routed
+///
+/// An event involves an address if its transaction was sent from it or any
+/// of its fields holds it. Involved events get the watchlist's tag, reach the
+/// sinks listed in `sinks` whatever their filters select, and with `capture`
+/// are indexed even when no event filter matches them.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct WatchlistConfig {
+    /// Whether events are checked against the watchlist
+    pub enabled: bool,
+    /// Watched addresses
+    pub addresses: Vec<String>,
+    /// File of further addresses, one per line; `#` starts a comment
+    pub file: Option<PathBuf>,
+    /// Also watch the addresses stored under this watchlist's name in the `watchlist_addresses` table
+    pub database: bool,
+    /// Seconds between reloads of `file` and the stored addresses
+    pub refresh_interval: u64,
+    /// Tag attached to involved events (`watchlist:<name>` if unset)
+    pub tag: Option<String>,
+    /// Index involved events even when no event filter matches them
+    pub capture: bool,
+    /// Sinks receiving every involved event in addition to the events their filters select
+    pub sinks: Vec<String>,
+    /// Share of unwatched addresses the bloom filter passes on to the exact check
+    pub false_positive_rate: f64,
+}
+
+impl WatchlistConfig {
+    /// Tag attached to the events involving the watchlist `name`
+    pub fn tag_for(&self, name: &str) -> String {
+        self.tag
+            .clone()
+            .unwrap_or_else(|| format!("watchlist:{name}"))
+    }
+}
+
+/// Rule evaluated against every stored event
+///
+/// An event meets the rule if one of `filters` selects it and it satisfies
+/// every condition. Matching events get `tag` before they are stored; once
+/// their checkpoint is committed, the rule's message is posted to each of
+/// `webhooks` and, with `alert`, to the channels under `[alerts]`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct RuleConfig {
+    /// Whether events are checked against the rule
+    pub enabled: bool,
+    /// Names of the event filters preselecting events (all stored events if empty)
+    pub filters: Vec<String>,
+    /// Conditions an event must all satisfy
+    pub conditions: Vec<RuleCondition>,
+    /// Message text with `{placeholder}`s, as in notification sinks, plus `{rule}`
+    pub template: String,
+    /// Tag attached to matching events
+    pub tag: Option<String>,
+    /// Endpoints receiving a JSON payload with the message and event per match
+    pub webhooks: Vec<Url>,
+    /// Also post the message to the channels of `[alerts]`
+    pub alert: bool,
+    /// Messages sent per minute; matches over the limit are counted in the next message
+    pub max_per_minute: u32,
+}
+
+/// Condition on one value of an event
+///
+/// Set `min` and `max` to compare numbers, which includes numeric strings,
+/// or `one_of` to require specific values such as pools or senders.
+/// Addresses in `one_of` match in their short or full form.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RuleCondition {
+    /// Placeholder of the value, e.g. `sender` or `fields.parsed_json.amount`
+    pub field: String,
+    /// Smallest value allowed
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Largest value allowed
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Values allowed
+    #[serde(default)]
+    pub one_of: Vec<String>,
+}
+
+/// Settings for a single protocol decoder
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ProtocolConfig {
+    /// Whether the decoder runs
+    pub enabled: bool,
+    /// Package IDs handled by the decoder, replacing its built-in defaults when non-empty
+    pub package_ids: Vec<String>,
+    /// Extra tags attached to every decoded event
+    pub tags: Vec<String>,
+}
+
+/// Output target that receives events after they are stored
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SinkConfig {
+    /// Kafka topic
+    Kafka(KafkaSinkConfig),
+    /// NATS subject
+    Nats(NatsSinkConfig),
+    /// HTTP endpoint receiving JSON batches
+    Webhook(WebhookSinkConfig),
+    /// Parquet files in an S3 bucket
+    S3Parquet(S3ParquetSinkConfig),
+    /// Redis stream or pub/sub channel
+    Redis(RedisSinkConfig),
+    /// Google Cloud Pub/Sub topic
+    #[serde(rename = "pubsub")]
+    PubSub(PubSubSinkConfig),
+    /// AWS SNS topic
+    Sns(SnsSinkConfig),
+    /// AWS SQS queue
+    Sqs(SqsSinkConfig),
+    /// Chat messages on Discord, Slack or Telegram
+    Notification(NotificationSinkConfig),
+    /// BigQuery tables, loaded with streaming inserts
+    #[serde(rename = "bigquery")]
+    BigQuery(BigQuerySinkConfig),
+    /// Snowflake tables, loaded by copying files from an external stage
+    Snowflake(SnowflakeSinkConfig),
+}
+
+/// Kafka sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON, a CloudEvents envelope around it,
+    /// or Avro or protobuf registered with `schema_registry`
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Schema registry the Avro and protobuf schemas are registered with
+    #[serde(default)]
+    pub schema_registry: Option<SchemaRegistryConfig>,
+    /// Comma-separated bootstrap servers
+    pub brokers: String,
+    /// Topic events are produced to, keyed by transaction digest
+    pub topic: String,
+    /// Extra librdkafka producer properties
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+}
+
+/// NATS sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NatsSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON, a CloudEvents envelope around it,
+    /// or Avro or protobuf registered with `schema_registry`
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Schema registry the Avro and protobuf schemas are registered with
+    #[serde(default)]
+    pub schema_registry: Option<SchemaRegistryConfig>,
+    /// Server URL, e.g. `nats://localhost:4222`
+    pub url: String,
+    /// Subject events are published to
+    pub subject: String,
+}
+
+/// Redis sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RedisSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON, a CloudEvents envelope around it,
+    /// or Avro or protobuf registered with `schema_registry`
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Schema registry the Avro and protobuf schemas are registered with
+    #[serde(default)]
+    pub schema_registry: Option<SchemaRegistryConfig>,
+    /// Server URL, e.g. `redis://localhost:6379`
+    pub url: String,
+    /// Whether events are appended to a stream or published on a channel
+    #[serde(default)]
+    pub mode: RedisMode,
+    /// Stream key or channel events are written to
+    pub key: String,
+    /// Length streams are trimmed to, approximately, on every append (untrimmed if unset)
+    #[serde(default)]
+    pub max_len: Option<u64>,
+}
+
+/// How a Redis sink writes events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RedisMode {
+    /// `XADD` to a stream, read by consumer groups
+    #[default]
+    Stream,
+    /// `PUBLISH` to a channel, seen only by connected subscribers
+    PubSub,
+}
+
+/// Payload format of message sinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EventFormat {
+    /// The processed event as JSON
+    #[default]
+    Json,
+    /// A CloudEvents 1.0 envelope in structured JSON mode, with the event as `data`
+    CloudEvents,
+    /// Avro binary in the Confluent wire format, framed with the registered schema ID
+    Avro,
+    /// Protobuf in the Confluent wire format, framed with the registered schema ID
+    Protobuf,
+}
+
+impl EventFormat {
+    /// Check if payloads are framed with a schema from a schema registry
+    pub fn uses_schema_registry(self) -> bool {
+        matches!(self, EventFormat::Avro | EventFormat::Protobuf)
+    }
+}
+
+/// Confluent Schema Registry settings of a message sink
+///
+/// Every event type gets its own subject, `<subject_prefix><address>::<module>::<name>`,
+/// so each type's compatibility is checked on its own. The schema describes the
+/// processed event, with the event fields as a JSON string.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaRegistryConfig {
+    /// Registry URL, e.g. `http://localhost:8081`
+    pub url: Url,
+    /// User of HTTP basic authentication
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password of HTTP basic authentication, e.g. a Confluent Cloud API secret
+    #[serde(default)]
+    pub password: Option<SecretRef>,
+    /// Prefix of every subject name
+    #[serde(default)]
+    pub subject_prefix: String,
+}
+
+/// Event field whose value orders the messages of cloud queue sinks
+///
+/// Messages sharing a value are delivered in order: Pub/Sub ordering keys,
+/// SNS and SQS FIFO message groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderingKey {
+    Sender,
+    Transaction,
+    Package,
+    EventType,
+}
+
+/// Google Cloud Pub/Sub sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PubSubSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON or a CloudEvents envelope around it
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Project owning the topic
+    pub project: String,
+    /// Topic events are published to
+    pub topic: String,
+    /// Service account key file, falling back to Application Default Credentials
+    #[serde(default)]
+    pub credentials_file: Option<PathBuf>,
+    /// Event field used as the ordering key (unordered if unset)
+    #[serde(default)]
+    pub ordering_key: Option<OrderingKey>,
+    /// Maximum messages per publish request, at most 1000
+    #[serde(default = "default_sink_batch_size")]
+    pub batch_size: usize,
+    /// API endpoint, e.g. a regional one to keep ordered messages in one region
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+}
+
+/// AWS SNS sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SnsSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON or a CloudEvents envelope around it
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Topic events are published to; FIFO topics end in `.fifo`
+    pub topic_arn: String,
+    /// Event field used as the message group of FIFO topics
+    #[serde(default)]
+    pub ordering_key: Option<OrderingKey>,
+    /// Region, falling back to the AWS configuration chain
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Named profile of the AWS configuration files
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Custom endpoint, e.g. LocalStack
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+}
+
+/// AWS SQS sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SqsSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON or a CloudEvents envelope around it
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Queue events are sent to; FIFO queues end in `.fifo`
+    pub queue_url: Url,
+    /// Event field used as the message group of FIFO queues
+    #[serde(default)]
+    pub ordering_key: Option<OrderingKey>,
+    /// Region, falling back to the AWS configuration chain
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Named profile of the AWS configuration files
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Custom endpoint, e.g. LocalStack
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+}
+
+/// Webhook sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Payload of every message: the event JSON or a CloudEvents envelope around it
+    #[serde(default)]
+    pub format: EventFormat,
+    /// Endpoint events are POSTed to as a JSON array
+    pub url: Url,
+    /// Extra HTTP headers sent with every request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Maximum events per request
+    #[serde(default = "default_sink_batch_size")]
+    pub batch_size: usize,
+    /// Request timeout in seconds
+    #[serde(default = "default_sink_timeout")]
+    pub timeout: u64,
+    /// Key signing every request body with HMAC-SHA256 (unsigned if unset)
+    #[serde(default)]
+    pub secret: Option<SecretRef>,
+    /// Attempts per request before its events are dropped
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Seconds before the first retry, doubled on every further attempt
+    #[serde(default = "default_webhook_retry_delay")]
+    pub retry_delay: u64,
+    /// Upper bound in seconds of the delay between retries
+    #[serde(default = "default_webhook_max_retry_delay")]
+    pub max_retry_delay: u64,
+    /// Requests in flight to the endpoint at once
+    #[serde(default = "default_webhook_concurrency")]
+    pub concurrency: usize,
+}
+
+/// Notification sink settings
+///
+/// Every selected event is rendered with `template` and posted to each
+/// configured chat channel.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Message text with `{placeholder}`s such as `{event_type}`, `{sender}` or `{fields.parsed_json.amount}`
+    #[serde(default = "default_notification_template")]
+    pub template: String,
+    /// Only notify events whose field reaches a minimum value
+    #[serde(default)]
+    pub threshold: Option<NotificationThreshold>,
+    /// Messages posted per minute; events over the limit are counted in the next message
+    #[serde(default = "default_notification_max_per_minute")]
+    pub max_per_minute: u32,
+    /// Discord channel webhook
+    #[serde(default)]
+    pub discord: Option<DiscordConfig>,
+    /// Slack channel
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    /// Telegram chat
+    #[serde(default)]
+    pub telegram: Option<TelegramConfig>,
+}
+
+/// Minimum value of an event field for a notification
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationThreshold {
+    /// Placeholder of the compared value, e.g. `fields.amount`; numeric strings count as numbers
+    pub field: String,
+    /// Smallest value notified
+    pub min: f64,
+}
+
+/// Discord webhook to post messages to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DiscordConfig {
+    /// Webhook URL of the channel, including its token
+    pub webhook_url: Url,
+}
+
+/// S3 Parquet sink settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct S3ParquetSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Bucket files are written to
+    pub bucket: String,
+    /// Key prefix for written files
+    #[serde(default)]
+    pub prefix: String,
+    /// Bucket region, falling back to `AWS_REGION`
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Custom S3-compatible endpoint, e.g. MinIO
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+    /// Rows buffered before a file is written
+    #[serde(default = "default_parquet_max_rows")]
+    pub max_rows: usize,
+    /// Maximum seconds buffered rows wait before a file is written
+    #[serde(default = "default_parquet_flush_interval")]
+    pub flush_interval: u64,
+}
+
+/// BigQuery sink settings
+///
+/// Rows hold the event metadata and a column per decoded field; the tables
+/// must exist with the columns to be loaded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BigQuerySinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Project owning the dataset
+    pub project: String,
+    /// Dataset holding the tables
+    pub dataset: String,
+    /// Table receiving the events not routed by `tables`
+    pub table: String,
+    /// Tables by event filter name, receiving the events matching that filter
+    #[serde(default)]
+    pub tables: BTreeMap<String, String>,
+    /// Service account key file, falling back to Application Default Credentials
+    #[serde(default)]
+    pub credentials_file: Option<PathBuf>,
+    /// Rows buffered before they are inserted
+    #[serde(default = "default_bigquery_max_rows")]
+    pub max_rows: usize,
+    /// Maximum seconds buffered rows wait before they are inserted
+    #[serde(default = "default_bigquery_flush_interval")]
+    pub flush_interval: u64,
+    /// API endpoint, e.g. an emulator
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+}
+
+/// Snowflake sink settings
+///
+/// Buffered rows are written as JSON files to the bucket behind an external
+/// stage, then loaded with `COPY INTO` matching columns by name.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SnowflakeSinkConfig {
+    /// Whether the sink receives events
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Names of the event filters selecting events for this sink (all events if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Account identifier, e.g. `myorg-myaccount`
+    pub account: String,
+    /// Token authenticating SQL API requests
+    pub token: SecretRef,
+    /// Kind of `token`
+    #[serde(default)]
+    pub token_type: SnowflakeTokenType,
+    /// Database holding the tables
+    pub database: String,
+    /// Schema holding the tables
+    pub schema: String,
+    /// Warehouse running the loads, falling back to the user default
+    #[serde(default)]
+    pub warehouse: Option<String>,
+    /// Role running the loads, falling back to the user default
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Table receiving the events not routed by `tables`
+    pub table: String,
+    /// Tables by event filter name, receiving the events matching that filter
+    #[serde(default)]
+    pub tables: BTreeMap<String, String>,
+    /// External stage reading `prefix` of `bucket`
+    pub stage: String,
+    /// S3 bucket files are written to
+    pub bucket: String,
+    /// Key prefix for written files, the location of the stage
+    #[serde(default)]
+    pub prefix: String,
+    /// Bucket region, falling back to `AWS_REGION`
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Custom S3-compatible endpoint
+    #[serde(default)]
+    pub endpoint: Option<Url>,
+    /// Rows buffered before a file is loaded
+    #[serde(default = "default_parquet_max_rows")]
+    pub max_rows: usize,
+    /// Maximum seconds buffered rows wait before a file is loaded
+    #[serde(default = "default_snowflake_flush_interval")]
+    pub flush_interval: u64,
+}
+
+/// Kind of token authenticating Snowflake SQL API requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SnowflakeTokenType {
+    /// Programmatic access token of a user
+    #[default]
+    ProgrammaticAccessToken,
+    /// OAuth access token
+    Oauth,
+    /// JWT signed with the key pair of a user
+    KeypairJwt,
+}
+
+impl SinkConfig {
+    /// Sink type as written in configuration files
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SinkConfig::Kafka(_) => "kafka",
+            SinkConfig::Nats(_) => "nats",
+            SinkConfig::Webhook(_) => "webhook",
+            SinkConfig::S3Parquet(_) => "s3-parquet",
+            SinkConfig::Redis(_) => "redis",
+            SinkConfig::PubSub(_) => "pubsub",
+            SinkConfig::Sns(_) => "sns",
+            SinkConfig::Sqs(_) => "sqs",
+            SinkConfig::Notification(_) => "notification",
+            SinkConfig::BigQuery(_) => "bigquery",
+            SinkConfig::Snowflake(_) => "snowflake",
+        }
+    }
+
+    /// Whether the sink receives events
+    pub fn enabled(&self) -> bool {
+        match self {
+            SinkConfig::Kafka(sink) => sink.enabled,
+            SinkConfig::Nats(sink) => sink.enabled,
+            SinkConfig::Webhook(sink) => sink.enabled,
+            SinkConfig::S3Parquet(sink) => sink.enabled,
+            SinkConfig::Redis(sink) => sink.enabled,
+            SinkConfig::PubSub(sink) => sink.enabled,
+            SinkConfig::Sns(sink) => sink.enabled,
+            SinkConfig::Sqs(sink) => sink.enabled,
+            SinkConfig::Notification(sink) => sink.enabled,
+            SinkConfig::BigQuery(sink) => sink.enabled,
+            SinkConfig::Snowflake(sink) => sink.enabled,
+        }
+    }
+
+    /// Names of the event filters selecting events for this sink
+    pub fn filters(&self) -> &[String] {
+        match self {
+            SinkConfig::Kafka(sink) => &sink.filters,
+            SinkConfig::Nats(sink) => &sink.filters,
+            SinkConfig::Webhook(sink) => &sink.filters,
+            SinkConfig::S3Parquet(sink) => &sink.filters,
+            SinkConfig::Redis(sink) => &sink.filters,
+            SinkConfig::PubSub(sink) => &sink.filters,
+            SinkConfig::Sns(sink) => &sink.filters,
+            SinkConfig::Sqs(sink) => &sink.filters,
+            SinkConfig::Notification(sink) => &sink.filters,
+            SinkConfig::BigQuery(sink) => &sink.filters,
+            SinkConfig::Snowflake(sink) => &sink.filters,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_sink_batch_size() -> usize {
+    100
+}
+
+fn default_sink_timeout() -> u64 {
+    30
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    5
+}
+
+fn default_webhook_retry_delay() -> u64 {
+    1
+}
+
+fn default_webhook_max_retry_delay() -> u64 {
+    60
+}
+
+fn default_webhook_concurrency() -> usize {
+    4
+}
+
+fn default_notification_template() -> String {
+    "{event_type} from {sender} in {transaction} (checkpoint {checkpoint})".to_string()
+}
+
+fn default_notification_max_per_minute() -> u32 {
+    20
+}
+
+fn default_parquet_max_rows() -> usize {
+    10_000
+}
+
+fn default_parquet_flush_interval() -> u64 {
+    300
+}
+
+fn default_bigquery_max_rows() -> usize {
+    500
+}
+
+fn default_bigquery_flush_interval() -> u64 {
+    5
+}
+
+fn default_snowflake_flush_interval() -> u64 {
+    60
+}
+
+fn default_priority_batch_size() -> usize {
+    10
+}
+
+fn default_priority_timeout_ms() -> u64 {
+    500
 }
 
 /// Event indexing configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct EventsConfig {
-    /// Starting checkpoint for indexing
+    /// How the first checkpoint to index is chosen at startup
+    #[serde(default)]
+    pub start_mode: StartMode,
+    /// Checkpoint `resume` starts from when nothing has been indexed yet
     pub start_checkpoint: Option<u64>,
     /// Number of events to process in a batch
     pub batch_size: usize,
@@ -56,15 +1026,62 @@ pub struct EventsConfig {
     pub max_concurrent_batches: usize,
     /// Event filters to apply
     pub filters: Vec<EventFilter>,
+    /// Directory of TOML/JSON filter files appended to `filters`, relative to the config file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters_dir: Option<PathBuf>,
     /// Whether to index transaction effects
     pub index_transactions: bool,
+    /// Which transactions of a checkpoint `index_transactions` fetches in full
+    #[serde(default)]
+    pub include_transaction_data: TransactionData,
     /// Whether to index object changes
     pub index_objects: bool,
+    /// Number of events of `priority = "high"` filters to process in a batch
+    #[serde(default = "default_priority_batch_size")]
+    pub priority_batch_size: usize,
+    /// Time allowed to store a priority batch ahead of its checkpoint in milliseconds
+    #[serde(default = "default_priority_timeout_ms")]
+    pub priority_timeout_ms: u64,
+}
+
+/// Transactions whose full data is fetched from the node and stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionData {
+    /// Only transactions that emitted a matched event, so narrow filters fetch little
+    #[default]
+    Matched,
+    /// Every transaction of the checkpoint
+    All,
+}
+
+/// Where indexing starts when the indexer launches
+///
+/// Every mode except `resume` ignores the stored cursor and re-indexes from
+/// the chosen checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StartMode {
+    /// First checkpoint of the chain
+    Genesis,
+    /// Current network tip, skipping history
+    Latest,
+    /// A specific checkpoint, e.g. `{ checkpoint = 1000 }`
+    Checkpoint(u64),
+    /// After the stored cursor, else `start_checkpoint`, else the network tip
+    #[default]
+    Resume,
+    /// First checkpoint at or after an RFC 3339 time, e.g. `{ timestamp = "2025-01-01T00:00:00Z" }`
+    Timestamp(DateTime<Utc>),
 }
 
 /// Event filter configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct EventFilter {
+    /// Name identifying the filter in logs and filter files (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// Package ID to filter by (optional)
     pub package: Option<String>,
     /// Module name to filter by (optional)
@@ -73,10 +1090,466 @@ pub struct EventFilter {
     pub event_type: Option<String>,
     /// Sender address to filter by (optional)
     pub sender: Option<String>,
+    /// Lane the matched events take through the pipeline
+    #[serde(default, skip_serializing_if = "FilterPriority::is_normal")]
+    pub priority: FilterPriority,
+}
+
+/// Lane the events of a filter take through the pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterPriority {
+    /// Processed, stored and delivered along with the rest of their checkpoint
+    #[default]
+    Normal,
+    /// Processed in small batches, then stored and pushed to sinks ahead of their checkpoint
+    High,
+}
+
+impl FilterPriority {
+    /// Check if events take the normal lane
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+}
+
+/// Runtime tuning for the ingestion pipeline
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct RuntimeConfig {
+    /// Number of checkpoints fetched from the node concurrently
+    pub fetch_concurrency: usize,
+    /// Number of concurrent storage writes per checkpoint
+    pub writer_concurrency: usize,
+    /// Number of checkpoints processed concurrently ahead of the in-order commit
+    pub process_concurrency: usize,
+    /// Number of fetched checkpoints buffered ahead of processing
+    pub checkpoint_channel_capacity: usize,
+    /// Interval between polls for new checkpoints in milliseconds
+    pub poll_interval: u64,
+    /// Timeout for a single node request in seconds
+    pub request_timeout: u64,
+    /// Time allowed to drain buffered checkpoints on shutdown in seconds
+    pub shutdown_timeout: u64,
+    /// Consecutive failed commits after which sinks and live subscribers pause until one succeeds (0 never pauses)
+    pub storage_breaker_failures: u32,
+    /// Processed events a backfill holds in memory while storage catches up
+    pub backfill_memory_events: usize,
+    /// Bytes of processed checkpoints a backfill may spill to disk before fetching pauses
+    pub backfill_spill_limit: u64,
+    /// Directory backfills spill to (the system temporary directory if unset)
+    pub backfill_spill_dir: Option<PathBuf>,
+    /// Directory fetched checkpoints are cached in by digest, so replays over the same range skip the node (no cache if unset)
+    pub checkpoint_cache_dir: Option<PathBuf>,
+    /// Bytes of cached checkpoints kept on disk, evicting the least recently used beyond it
+    pub checkpoint_cache_limit: u64,
+    /// Sharing of node requests and storage writes between the live pipeline and backfills
+    pub scheduler: SchedulerConfig,
+    /// Adjustment of writer concurrency and fetch fan-out while running
+    pub autotune: AutotuneConfig,
+}
+
+/// Weighted round-robin shares of node requests and storage writes per pipeline
+///
+/// Pipelines are the live pipeline (`default`) and backfills, named after
+/// their cursor or `backfill`. While several wait for the same resource,
+/// each gets turns in proportion to its weight, so a backfill with many
+/// checkpoints in flight cannot starve the live pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct SchedulerConfig {
+    /// Whether requests and writes are scheduled; unscheduled pipelines only obey their own concurrency
+    pub enabled: bool,
+    /// Node requests in flight at once across all pipelines
+    pub node_requests: usize,
+    /// Storage writes in flight at once across all pipelines
+    pub storage_writes: usize,
+    /// Weight of each pipeline by name; unlisted pipelines weigh 1
+    pub weights: BTreeMap<String, u32>,
+}
+
+/// Additive-increase, multiplicative-decrease tuning of pipeline concurrency
+///
+/// The configured `writer_concurrency` and `fetch_concurrency` are the
+/// starting points. Every `interval`, each grows by one while the database
+/// and the node keep up and halves when they do not, so one configuration
+/// finds its level on databases of different sizes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct AutotuneConfig {
+    /// Whether concurrency is tuned; otherwise the configured values stay fixed
+    pub enabled: bool,
+    /// Seconds between adjustments
+    pub interval: u64,
+    /// 95th percentile storage write latency in milliseconds above which writer concurrency halves
+    pub store_p95_ms: u64,
+    /// Share of failed node requests, between 0 and 1, above which fetch fan-out halves
+    pub rpc_error_rate: f64,
+    /// Most concurrent storage writes per checkpoint the tuner grows to
+    pub max_writer_concurrency: usize,
+    /// Most checkpoints fetched at once the tuner grows to
+    pub max_fetch_concurrency: usize,
+}
+
+/// Logging, metrics and tracing configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ObservabilityConfig {
+    /// Address to serve Prometheus metrics on (disabled if unset)
+    pub metrics_addr: Option<SocketAddr>,
+    /// OTLP/HTTP collector endpoint for trace export, e.g. `http://localhost:4318` (disabled if unset)
+    pub tracing_endpoint: Option<Url>,
+    /// Export the trace of one in this many checkpoints, plus every one that logged a warning or error
+    ///
+    /// 1 exports every trace; 0 exports only traces with a warning or error.
+    pub trace_sample_every: u64,
+    /// Checkpoints behind the network tip at which `/readyz` reports not ready
+    pub ready_max_lag: u64,
+    /// Warn when transforming and storing a checkpoint takes longer than this many milliseconds (disabled if unset)
+    pub slow_checkpoint_ms: Option<u64>,
+    /// Warn when the event processor takes longer than this many milliseconds on one event (disabled if unset)
+    ///
+    /// While set, events are handed to the processor one call at a time so
+    /// each call can be timed.
+    pub slow_event_ms: Option<u64>,
+    /// Latest pipeline errors kept for `status` and the control endpoint (0 keeps none)
+    pub error_history: usize,
+    /// Where panics and errors that stop the pipeline are reported
+    pub error_reporting: ErrorReportingConfig,
+    /// Latency objectives by name, tracked by the `slo_*` metrics
+    pub slos: BTreeMap<String, SloConfig>,
+    /// Log output format
+    pub log_format: LogFormat,
+    /// Log filter directive, e.g. `info` or `sui_indexer_core=debug,info`
+    ///
+    /// `RUST_LOG` takes precedence at startup when set.
+    pub log_level: String,
+}
+
+/// Error reporting to Sentry or a generic HTTP collector
+///
+/// Reports carry the pipeline, stage, checkpoint and matched filters of the
+/// failure. Both destinations may be set at once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ErrorReportingConfig {
+    /// Sentry DSN, e.g. `{ env = "SENTRY_DSN" }` (disabled if unset)
+    pub sentry_dsn: Option<SecretRef>,
+    /// Endpoint receiving every report as a JSON POST (disabled if unset)
+    pub url: Option<Url>,
+    /// Environment name attached to reports, e.g. `production`
+    pub environment: Option<String>,
+}
+
+impl ErrorReportingConfig {
+    /// Whether any destination is configured
+    pub fn is_enabled(&self) -> bool {
+        self.sentry_dsn.is_some() || self.url.is_some()
+    }
+}
+
+/// Objective for how soon events are stored after their checkpoint is final
+///
+/// For example `threshold_secs = 30` with `objective = 0.99` asks that 99%
+/// of checkpoints are stored within 30 seconds of their timestamp.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SloConfig {
+    /// Seconds after the checkpoint timestamp by which its events must be stored
+    pub threshold_secs: f64,
+    /// Share of checkpoints that must meet the threshold, between 0 and 1
+    pub objective: f64,
+    /// Seconds of history compliance and burn rate are computed over
+    #[serde(default = "default_slo_window")]
+    pub window: u64,
+}
+
+fn default_slo_window() -> u64 {
+    3600
+}
+
+/// Log output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable text
+    #[default]
+    Pretty,
+    /// One JSON object per line
+    Json,
+}
+
+/// Alert rules and the channels notifications are delivered to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct AlertsConfig {
+    /// Interval between alert rule evaluations in seconds
+    pub check_interval: u64,
+    /// Minimum time between repeated notifications for a firing alert in seconds
+    pub repeat_interval: u64,
+    /// Time after an alert resolves before it may fire again in seconds
+    ///
+    /// Keeps a value hovering around its threshold from flapping between
+    /// firing and resolved notifications.
+    pub cooldown: u64,
+    /// Webhooks that receive every notification as a JSON payload
+    pub webhook_urls: Vec<Url>,
+    /// Slack delivery (disabled if unset)
+    pub slack: Option<SlackConfig>,
+    /// Telegram delivery (disabled if unset)
+    pub telegram: Option<TelegramConfig>,
+    /// PagerDuty Events API delivery (disabled if unset)
+    pub pagerduty: Option<PagerDutyConfig>,
+    /// Fires when the indexer falls behind the network
+    pub lag: LagAlertConfig,
+    /// Fires when fetch and storage errors exceed a rate
+    pub error_rate: ErrorRateAlertConfig,
+    /// Fires when sinks drop events after exhausting delivery retries
+    pub dead_letters: DeadLetterAlertConfig,
+    /// Fires while the watchdog reports ingestion as stalled
+    pub stall: StallAlertConfig,
+}
+
+impl AlertsConfig {
+    /// Check if any alert rule is enabled
+    pub fn any_rule_enabled(&self) -> bool {
+        self.lag.enabled
+            || self.error_rate.enabled
+            || self.dead_letters.enabled
+            || self.stall.enabled
+    }
+
+    /// Check if any notification channel is configured
+    pub fn any_channel_configured(&self) -> bool {
+        !self.webhook_urls.is_empty()
+            || self.slack.is_some()
+            || self.telegram.is_some()
+            || self.pagerduty.is_some()
+    }
+}
+
+/// Control endpoint used by the `stop`, `reload` and `status` commands
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct AdminConfig {
+    /// Address a running indexer accepts control requests on (disabled if unset, the default)
+    ///
+    /// Without a `token`, requests are not authenticated, so keep this on a
+    /// loopback address.
+    pub addr: Option<SocketAddr>,
+    /// Bearer token every control request must present (unauthenticated if unset)
+    pub token: Option<SecretRef>,
+}
+
+/// HTTP API of a running indexer, serving the live event streams
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ApiConfig {
+    /// Address the HTTP API listens on (disabled if unset)
+    pub addr: Option<SocketAddr>,
+    /// Bearer token every request must present (unauthenticated if unset)
+    pub token: Option<SecretRef>,
+}
+
+/// gRPC API of a running indexer, available when built with the `grpc` feature
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct GrpcConfig {
+    /// Address the gRPC API listens on (disabled if unset)
+    pub addr: Option<SocketAddr>,
+    /// Bearer token every call must present in its `authorization` metadata (unauthenticated if unset)
+    pub token: Option<SecretRef>,
+}
+
+/// Arrow Flight endpoint of a running indexer, available when built with the `flight` feature
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct FlightConfig {
+    /// Address the Flight endpoint listens on (disabled if unset)
+    pub addr: Option<SocketAddr>,
+    /// Bearer token every call must present in its `authorization` metadata (unauthenticated if unset)
+    pub token: Option<SecretRef>,
+    /// Rows per record batch sent to clients
+    pub batch_size: usize,
+}
+
+/// API keys with their scopes and rate limits
+///
+/// Once any key is configured, or `database_keys` is on, every call to the
+/// control endpoint, the HTTP API, the gRPC API and the Flight endpoint must
+/// present a key or the endpoint token.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct AuthConfig {
+    /// Keys by name; the name identifies the caller in request logs
+    pub keys: BTreeMap<String, ApiKeyConfig>,
+    /// Also accept the keys stored in the `api_keys` table
+    pub database_keys: bool,
+    /// Seconds between reloads of the stored keys, so new and revoked keys take effect
+    pub refresh_interval: u64,
+}
+
+/// One API key
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// The key, sent as a bearer token
+    pub key: SecretRef,
+    /// What the key may do
+    #[serde(default)]
+    pub scope: ApiScope,
+    /// Requests per second allowed with this key, unlimited if unset
+    #[serde(default)]
+    pub rate_limit: Option<f64>,
+}
+
+/// Access granted to an API key
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Status, queries and live events
+    #[default]
+    Read,
+    /// Everything, including stop, reload, pause, resume and backfills
+    Admin,
+}
+
+impl fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Read => "read",
+            Self::Admin => "admin",
+        })
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = eyre::Report;
+
+    fn from_str(scope: &str) -> Result<Self> {
+        match scope {
+            "read" => Ok(Self::Read),
+            "admin" => Ok(Self::Admin),
+            _ => Err(ConfigError::UnknownScope(scope.to_string()).into()),
+        }
+    }
+}
+
+/// Slack bot credentials for alert and notification delivery
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SlackConfig {
+    /// Bot token with the `chat:write` scope
+    pub token: SecretRef,
+    /// Channel ID or name to post to
+    pub channel: String,
+}
+
+/// Telegram bot credentials for alert and notification delivery
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramConfig {
+    /// Bot token issued by BotFather
+    pub bot_token: SecretRef,
+    /// Chat to send messages to
+    pub chat_id: String,
+}
+
+/// PagerDuty Events API v2 integration for alert delivery
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PagerDutyConfig {
+    /// Integration key of the Events API v2 service
+    pub routing_key: String,
+    /// Severity of triggered incidents
+    #[serde(default)]
+    pub severity: PagerDutySeverity,
+}
+
+/// Incident severity understood by PagerDuty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PagerDutySeverity {
+    Critical,
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+/// Checkpoint lag alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct LagAlertConfig {
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+    /// Checkpoints behind the network tip before the alert fires
+    pub max_checkpoints: u64,
+}
+
+/// Error rate alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct ErrorRateAlertConfig {
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+    /// Fetch and storage errors per minute before the alert fires
+    pub max_errors_per_minute: f64,
+}
+
+/// Dead-letter growth alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct DeadLetterAlertConfig {
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+    /// Events dropped by sinks per minute before the alert fires
+    pub max_events_per_minute: f64,
+}
+
+/// Stalled ingestion alert rule, fed by the watchdog
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct StallAlertConfig {
+    /// Whether the rule is evaluated; requires `watchdog.enabled`
+    pub enabled: bool,
+}
+
+/// Outbox written in the same transaction as the events it carries to the sinks
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct OutboxConfig {
+    /// Whether sinks are fed from the outbox instead of in-memory queues
+    pub enabled: bool,
+    /// Milliseconds between checks of an idle sink's outbox
+    pub poll_interval: u64,
+    /// Outbox messages sent to a sink at once
+    pub batch_size: usize,
+    /// Seconds before retrying a failed delivery, doubled per retry
+    pub retry_delay: u64,
+    /// Upper bound of the retry delay in seconds
+    pub max_retry_delay: u64,
+    /// Failed deliveries of a message before it becomes a dead letter and is no longer sent
+    pub max_attempts: u32,
+}
+
+/// Watchdog noticing that ingestion stopped while the network advances
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, default)]
+pub struct WatchdogConfig {
+    /// Whether the watchdog runs
+    pub enabled: bool,
+    /// Seconds without a committed checkpoint, while the network tip moves, before ingestion counts as stalled
+    pub stall_timeout: u64,
+    /// Restart fetching and processing when ingestion stalls, and again every `stall_timeout` it stays stalled
+    pub restart: bool,
 }
 
 /// Connection pool configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct PoolConfig {
     /// Maximum number of connections
     pub max_connections: usize,
@@ -87,7 +1560,8 @@ pub struct PoolConfig {
 }
 
 /// Retry configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct RetryConfig {
     /// Maximum number of retry attempts
     pub max_attempts: usize,
@@ -99,15 +1573,50 @@ pub struct RetryConfig {
     pub backoff_multiplier: f64,
 }
 
-impl Default for NetworkConfig {
-    fn default() -> Self {
+impl NetworkConfig {
+    /// Create a configuration using the presets of the given network
+    pub fn for_network(network: Network) -> Self {
         Self {
-            grpc_url: "https://fullnode.testnet.sui.io:443"
-                .parse()
-                .expect("Default gRPC URL should be valid"),
-            network: "testnet".to_string(),
-            pool: PoolConfig::default(),
-            retry: RetryConfig::default(),
+            network,
+            ..Self::default()
+        }
+    }
+
+    /// Resolve the gRPC endpoint, falling back to the network preset
+    pub fn grpc_url(&self) -> Result<Url> {
+        self.grpc_url
+            .clone()
+            .or_else(|| self.network.default_grpc_url())
+            .ok_or_else(|| ConfigError::MissingGrpcUrl.into())
+    }
+
+    /// Resolve every configured gRPC endpoint, the primary one first
+    pub fn grpc_urls(&self) -> Result<Vec<Url>> {
+        let mut urls = vec![self.grpc_url()?];
+        for url in &self.fallback_grpc_urls {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        Ok(urls)
+    }
+
+    /// Resolve the expected chain identifier, falling back to the network preset
+    pub fn chain_id(&self) -> Option<&str> {
+        self.chain_id.as_deref().or(self.network.chain_id())
+    }
+}
+
+impl DatabaseConfig {
+    /// Resolve the configured password, reading `password_secret` if set
+    pub fn password(&self) -> Result<Option<String>> {
+        match (&self.password, &self.password_secret) {
+            (Some(_), Some(_)) => {
+                Err(ConfigError::Exclusive("database.password", "database.password_secret").into())
+            }
+            (Some(password), None) => Ok(Some(password.clone())),
+            (None, Some(secret)) => secret.resolve().map(Some),
+            (None, None) => Ok(None),
         }
     }
 }
@@ -115,25 +1624,334 @@ impl Default for NetworkConfig {
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
-            url: "postgresql://localhost/sui_indexer".to_string(),
+            url: Some("postgresql://localhost/sui_indexer".to_string()),
+            host: None,
+            port: None,
+            user: None,
+            password: None,
+            password_secret: None,
+            dbname: None,
+            sslmode: None,
+            statement_timeout: None,
             max_connections: 20,
             min_connections: 5,
             connect_timeout: 30,
             idle_timeout: Some(600),
             auto_migrate: true,
+            schema: None,
+        }
+    }
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            extends: None,
+            network: NetworkConfig::default(),
+            database: DatabaseConfig::default(),
+            events: EventsConfig::default(),
+            storage: StorageConfig::default(),
+            packages: PackagesConfig::default(),
+            move_calls: MoveCallsConfig::default(),
+            objects: ObjectsConfig::default(),
+            watchlists: BTreeMap::new(),
+            rules: BTreeMap::new(),
+            protocols: BTreeMap::new(),
+            sinks: BTreeMap::new(),
+            outbox: OutboxConfig::default(),
+            runtime: RuntimeConfig::default(),
+            observability: ObservabilityConfig::default(),
+            alerts: AlertsConfig::default(),
+            admin: AdminConfig::default(),
+            api: ApiConfig::default(),
+            grpc: GrpcConfig::default(),
+            flight: FlightConfig::default(),
+            auth: AuthConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            networks: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for NetworkContextConfig {
+    fn default() -> Self {
+        Self {
+            network: Network::default(),
+            grpc_url: None,
+            fallback_grpc_urls: Vec::new(),
+            chain_id: None,
+            schema: None,
+            filters: None,
+            start_mode: None,
+            start_checkpoint: None,
+            sinks: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            package_ids: vec![],
+            tags: vec![],
+        }
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            store_raw_event: true,
+            store_bcs: true,
+            store_transactions_effects: true,
+            retention_days: None,
+            partitioning: PartitionStrategy::default(),
+        }
+    }
+}
+
+impl Default for PackagesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            original_ids: Vec::new(),
+        }
+    }
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            addresses: Vec::new(),
+            file: None,
+            database: false,
+            refresh_interval: 60,
+            tag: None,
+            capture: false,
+            sinks: Vec::new(),
+            false_positive_rate: 0.01,
+        }
+    }
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            filters: Vec::new(),
+            conditions: Vec::new(),
+            template:
+                "{rule}: {event_type} from {sender} in {transaction} (checkpoint {checkpoint})"
+                    .to_string(),
+            tag: None,
+            webhooks: Vec::new(),
+            alert: false,
+            max_per_minute: default_notification_max_per_minute(),
+        }
+    }
+}
+
+impl Default for MoveCallsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            filters: Vec::new(),
         }
     }
 }
 
+impl EventsConfig {
+    /// Look up event filters by name, failing on names that are not configured
+    pub fn named_filters(&self, names: &[String]) -> Result<Vec<EventFilter>> {
+        names
+            .iter()
+            .map(|name| {
+                self.filters
+                    .iter()
+                    .find(|filter| filter.name.as_deref() == Some(name.as_str()))
+                    .cloned()
+                    .ok_or_else(|| eyre::Report::from(ConfigError::UnknownFilter(name.clone())))
+            })
+            .collect()
+    }
+}
+
 impl Default for EventsConfig {
     fn default() -> Self {
         Self {
+            start_mode: StartMode::default(),
             start_checkpoint: None,
             batch_size: 100,
             max_concurrent_batches: 10,
             filters: vec![],
+            filters_dir: None,
             index_transactions: true,
+            include_transaction_data: TransactionData::default(),
             index_objects: true,
+            priority_batch_size: default_priority_batch_size(),
+            priority_timeout_ms: default_priority_timeout_ms(),
+        }
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            fetch_concurrency: 8,
+            writer_concurrency: 4,
+            process_concurrency: 4,
+            checkpoint_channel_capacity: 100,
+            poll_interval: 1000,
+            request_timeout: 30,
+            shutdown_timeout: 30,
+            storage_breaker_failures: 3,
+            backfill_memory_events: 50_000,
+            backfill_spill_limit: 1 << 30,
+            backfill_spill_dir: None,
+            checkpoint_cache_dir: None,
+            checkpoint_cache_limit: 10 << 30,
+            scheduler: SchedulerConfig::default(),
+            autotune: AutotuneConfig::default(),
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            node_requests: 16,
+            storage_writes: 8,
+            weights: BTreeMap::from([("default".to_string(), 4), ("backfill".to_string(), 1)]),
+        }
+    }
+}
+
+impl Default for AutotuneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 10,
+            store_p95_ms: 250,
+            rpc_error_rate: 0.05,
+            max_writer_concurrency: 32,
+            max_fetch_concurrency: 64,
+        }
+    }
+}
+
+impl Default for ObservabilityConfig {
+    fn default() -> Self {
+        Self {
+            metrics_addr: None,
+            tracing_endpoint: None,
+            trace_sample_every: 1,
+            ready_max_lag: 1000,
+            slow_checkpoint_ms: None,
+            slow_event_ms: None,
+            error_history: 50,
+            error_reporting: ErrorReportingConfig::default(),
+            slos: BTreeMap::new(),
+            log_format: LogFormat::default(),
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: 30,
+            repeat_interval: 3600,
+            cooldown: 300,
+            webhook_urls: vec![],
+            slack: None,
+            telegram: None,
+            pagerduty: None,
+            lag: LagAlertConfig::default(),
+            error_rate: ErrorRateAlertConfig::default(),
+            dead_letters: DeadLetterAlertConfig::default(),
+            stall: StallAlertConfig::default(),
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            keys: BTreeMap::new(),
+            database_keys: false,
+            refresh_interval: 60,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Whether calls must present a key
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty() || self.database_keys
+    }
+}
+
+impl Default for LagAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_checkpoints: 1000,
+        }
+    }
+}
+
+impl Default for ErrorRateAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_errors_per_minute: 10.0,
+        }
+    }
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval: 1000,
+            batch_size: 500,
+            retry_delay: 1,
+            max_retry_delay: 60,
+            max_attempts: 10,
+        }
+    }
+}
+
+impl Default for FlightConfig {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            token: None,
+            batch_size: 8192,
+        }
+    }
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stall_timeout: 300,
+            restart: false,
+        }
+    }
+}
+
+impl Default for DeadLetterAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_events_per_minute: 0.0,
         }
     }
 }
@@ -163,11 +1981,24 @@ impl Default for RetryConfig {
 pub struct ConfigLoader;
 
 impl ConfigLoader {
-    /// Load configuration from a TOML file
+    /// Load configuration from a TOML file, resolving any `extends` chain
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<IndexerConfig> {
-        let content = std::fs::read_to_string(path)?;
-        let config: IndexerConfig = toml::from_str(&content)?;
-        Ok(config)
+        LayeredConfig::load(path)
+            .and_then(LayeredConfig::into_config)
+            .kind(ErrorKind::Config)
+    }
+
+    /// List the files a configuration is built from, starting with `path` itself
+    ///
+    /// Includes any filters directory and the filter files in it.
+    pub fn source_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+        let layered = LayeredConfig::load(path)?;
+        let mut files = layered.sources.clone();
+        if let Some(dir) = layered.filters_dir() {
+            files.extend(filter_dir::filter_files(&dir)?);
+            files.push(dir);
+        }
+        Ok(files)
     }
 
     /// Load configuration from environment variables and command line arguments
@@ -187,10 +2018,12 @@ impl ConfigLoader {
         // Add default values
         builder = builder.add_source(config::Config::try_from(&IndexerConfig::default())?);
 
-        // Add config file if provided
+        // Add config file (merged over its base files) if provided
         if let Some(path) = config_file {
             if path.as_ref().exists() {
-                builder = builder.add_source(config::File::from(path.as_ref()));
+                let layered = toml::to_string(&LayeredConfig::load(path)?.table)?;
+                builder =
+                    builder.add_source(config::File::from_str(&layered, config::FileFormat::Toml));
             }
         }
 
@@ -202,7 +2035,8 @@ impl ConfigLoader {
         );
 
         let settings = builder.build()?;
-        let config: IndexerConfig = settings.try_deserialize()?;
+        let mut config: IndexerConfig = settings.try_deserialize()?;
+        filter_dir::append_filters(&mut config.events)?;
 
         Ok(config)
     }
@@ -214,11 +2048,14 @@ impl ConfigLoader {
         Ok(())
     }
 
-    /// Generate example configuration file
-    pub fn generate_example() -> String {
-        let config = IndexerConfig::default();
-        toml::to_string_pretty(&config)
-            .unwrap_or_else(|_| "# Failed to generate example config".to_string())
+    /// Generate a JSON Schema describing the configuration file format
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(IndexerConfig).to_value()
+    }
+
+    /// Generate an example configuration file, optionally with every setting described
+    pub fn example(with_comments: bool) -> Result<String> {
+        example::example_config(with_comments)
     }
 }
 
@@ -231,7 +2068,7 @@ mod tests {
     #[test]
     fn test_default_config() {
         let config = IndexerConfig::default();
-        assert_eq!(config.network.network, "testnet");
+        assert_eq!(config.network.network, Network::Testnet);
         assert_eq!(config.database.max_connections, 20);
         assert_eq!(config.events.batch_size, 100);
     }
@@ -265,10 +2102,234 @@ mod tests {
     }
 
     #[test]
-    fn test_example_generation() {
-        let example = ConfigLoader::generate_example();
-        assert!(!example.is_empty());
-        assert!(example.contains("[network]"));
-        assert!(example.contains("[database]"));
+    fn test_network_config_overrides() -> Result<()> {
+        let mut network = NetworkConfig::for_network(Network::Mainnet);
+        assert_eq!(
+            network.grpc_url()?.host_str(),
+            Some("fullnode.mainnet.sui.io")
+        );
+        assert_eq!(network.chain_id(), Some("35834a8a"));
+
+        network.grpc_url = Some("http://localhost:9000".parse()?);
+        network.chain_id = Some("deadbeef".to_string());
+        assert_eq!(network.grpc_url()?.as_str(), "http://localhost:9000/");
+        assert_eq!(network.chain_id(), Some("deadbeef"));
+
+        network.fallback_grpc_urls = vec![
+            "http://localhost:9001".parse()?,
+            "http://localhost:9000".parse()?,
+        ];
+        let urls = network.grpc_urls()?;
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[1].as_str(), "http://localhost:9001/");
+
+        assert!(NetworkConfig::for_network(Network::Custom)
+            .grpc_url()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_section_optional() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.remove("runtime");
+        table.insert(
+            "runtime".to_string(),
+            toml::from_str::<toml::Value>("fetch_concurrency = 2")?,
+        );
+
+        let config: IndexerConfig = value.try_into()?;
+        assert_eq!(config.runtime.fetch_concurrency, 2);
+        assert_eq!(
+            config.runtime.writer_concurrency,
+            RuntimeConfig::default().writer_concurrency
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_storage_section() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.insert(
+            "storage".to_string(),
+            toml::from_str::<toml::Value>(
+                "store_bcs = false\nretention_days = 30\npartitioning = \"monthly\"",
+            )?,
+        );
+
+        let config: IndexerConfig = value.try_into()?;
+        assert!(config.storage.store_raw_event);
+        assert!(!config.storage.store_bcs);
+        assert_eq!(config.storage.retention_days, Some(30));
+        assert_eq!(config.storage.partitioning, PartitionStrategy::Monthly);
+        Ok(())
+    }
+
+    #[test]
+    fn test_observability_section() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.insert(
+            "observability".to_string(),
+            toml::from_str::<toml::Value>(
+                "log_format = \"json\"\nmetrics_addr = \"127.0.0.1:9184\"",
+            )?,
+        );
+
+        let config: IndexerConfig = value.try_into()?;
+        assert_eq!(config.observability.log_format, LogFormat::Json);
+        assert_eq!(
+            config.observability.metrics_addr,
+            Some("127.0.0.1:9184".parse()?)
+        );
+        assert_eq!(config.observability.log_level, "info");
+        Ok(())
+    }
+
+    #[test]
+    fn test_admin_section() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.remove("admin");
+
+        // The control endpoint is only served once an address is configured
+        let config: IndexerConfig = value.clone().try_into()?;
+        assert_eq!(config.admin.addr, None);
+
+        let table = value.as_table_mut().expect("config serializes to a table");
+        let mut admin = toml::Table::new();
+        admin.insert("addr".to_string(), "127.0.0.1:9185".into());
+        table.insert("admin".to_string(), admin.into());
+        let config: IndexerConfig = value.try_into()?;
+        assert_eq!(config.admin.addr, Some("127.0.0.1:9185".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_alerts_section() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.insert(
+            "alerts".to_string(),
+            toml::from_str::<toml::Value>(
+                "webhook_urls = [\"https://hooks.example.com/indexer\"]\n\
+                 [telegram]\nbot_token = \"123:abc\"\nchat_id = \"-100\"\n\
+                 [pagerduty]\nrouting_key = \"R0UT1NG\"\n\
+                 [lag]\nenabled = true\nmax_checkpoints = 50",
+            )?,
+        );
+
+        let config: IndexerConfig = value.try_into()?;
+        assert_eq!(config.alerts.webhook_urls.len(), 1);
+        assert_eq!(
+            config.alerts.telegram.map(|t| t.chat_id),
+            Some("-100".into())
+        );
+        assert!(config.alerts.slack.is_none());
+        assert!(config.alerts.lag.enabled);
+        assert_eq!(config.alerts.lag.max_checkpoints, 50);
+        assert!(!config.alerts.error_rate.enabled);
+        assert_eq!(config.alerts.check_interval, 30);
+        assert_eq!(
+            config.alerts.pagerduty.map(|p| p.severity),
+            Some(PagerDutySeverity::Error)
+        );
+        assert!(!config.alerts.dead_letters.enabled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinks_section() -> Result<()> {
+        let mut value = toml::Value::try_from(IndexerConfig::default())?;
+        let table = value.as_table_mut().expect("config serializes to a table");
+        table.insert(
+            "sinks".to_string(),
+            toml::from_str::<toml::Value>(
+                "[trades]\ntype = \"kafka\"\nbrokers = \"localhost:9092\"\ntopic = \"trades\"\n\
+                 filters = [\"cetus\"]\nformat = \"avro\"\n\
+                 [trades.schema_registry]\nurl = \"http://localhost:8081\"\n\
+                 [archive]\ntype = \"s3-parquet\"\nbucket = \"sui-events\"\nenabled = false\n\
+                 [feed]\ntype = \"redis\"\nurl = \"redis://localhost:6379\"\nkey = \"sui:events\"\n\
+                 max_len = 100000",
+            )?,
+        );
+
+        let config: IndexerConfig = value.try_into()?;
+        let trades = &config.sinks["trades"];
+        assert_eq!(trades.kind(), "kafka");
+        assert_eq!(trades.filters(), ["cetus"]);
+        assert!(trades.enabled());
+        let SinkConfig::Kafka(kafka) = trades else {
+            panic!("expected a kafka sink");
+        };
+        assert_eq!(kafka.format, EventFormat::Avro);
+        assert!(kafka.schema_registry.is_some());
+        let SinkConfig::S3Parquet(archive) = &config.sinks["archive"] else {
+            panic!("expected an s3-parquet sink");
+        };
+        assert!(!archive.enabled);
+        assert_eq!(archive.max_rows, 10_000);
+        let SinkConfig::Redis(feed) = &config.sinks["feed"] else {
+            panic!("expected a redis sink");
+        };
+        assert_eq!(feed.mode, RedisMode::Stream);
+        assert_eq!(feed.max_len, Some(100_000));
+        Ok(())
+    }
+
+    #[test]
+    fn test_start_modes() -> Result<()> {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            start_mode: StartMode,
+        }
+        let parse = |value: &str| -> Result<StartMode> {
+            Ok(toml::from_str::<Wrapper>(&format!("start_mode = {value}"))?.start_mode)
+        };
+
+        assert_eq!(parse("\"genesis\"")?, StartMode::Genesis);
+        assert_eq!(parse("{ checkpoint = 42 }")?, StartMode::Checkpoint(42));
+        assert_eq!(
+            parse("{ timestamp = \"2025-01-01T00:00:00Z\" }")?,
+            StartMode::Timestamp("2025-01-01T00:00:00Z".parse()?)
+        );
+        assert!(parse("\"yesterday\"").is_err());
+        assert_eq!(EventsConfig::default().start_mode, StartMode::Resume);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        let mut toml_str = ConfigLoader::example(false).unwrap();
+        toml_str = toml_str.replace("batch_size", "batchsize");
+
+        let err = toml::from_str::<IndexerConfig>(&toml_str).unwrap_err();
+        assert!(err.to_string().contains("batchsize"));
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = ConfigLoader::json_schema();
+        assert_eq!(schema["title"], "IndexerConfig");
+        assert!(schema["properties"]["events"].is_object());
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_example_generation() -> Result<()> {
+        for with_comments in [false, true] {
+            let example = ConfigLoader::example(with_comments)?;
+            assert!(example.contains("[network]"));
+            assert!(example.contains("[database]"));
+            toml::from_str::<IndexerConfig>(&example)?;
+        }
+
+        let annotated = ConfigLoader::example(true)?;
+        assert!(annotated.starts_with("# Main configuration for the Sui Indexer"));
+        assert!(annotated.contains("# Number of events to process in a batch\nbatch_size = 100"));
+        assert!(annotated.contains("# Retry configuration\n[network.retry]"));
+        Ok(())
     }
 }
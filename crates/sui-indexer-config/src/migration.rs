@@ -0,0 +1,180 @@
+use eyre::Result;
+
+use crate::{ConfigError, Network};
+
+/// Current configuration file format version
+pub const CONFIG_VERSION: u32 = 1;
+
+/// An upgrade step from one configuration version to the next
+struct Migration {
+    /// Version this step upgrades from
+    from: u32,
+    /// Rewrite the TOML table in place, returning a note for every change made
+    apply: fn(&mut toml::Table) -> Vec<String>,
+}
+
+/// Upgrade steps in version order
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    apply: normalize_network_name,
+}];
+
+/// Upgrade a parsed configuration file to [`CONFIG_VERSION`]
+///
+/// Files without a `version` key predate versioning and are treated as version 0.
+/// Returns a note for every change made.
+pub fn migrate(table: &mut toml::Table, source: &str) -> Result<Vec<String>> {
+    let version = match table.get("version") {
+        None => 0,
+        Some(toml::Value::Integer(version)) => {
+            u32::try_from(*version).map_err(|_| ConfigError::InvalidVersion {
+                file: source.to_string(),
+                version: version.to_string(),
+            })?
+        }
+        Some(other) => {
+            return Err(ConfigError::InvalidVersion {
+                file: source.to_string(),
+                version: other.to_string(),
+            }
+            .into())
+        }
+    };
+    if version > CONFIG_VERSION {
+        return Err(ConfigError::UnsupportedVersion {
+            file: source.to_string(),
+            version,
+            supported: CONFIG_VERSION,
+        }
+        .into());
+    }
+
+    let mut notes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        notes.extend((migration.apply)(table));
+    }
+    table.insert("version".to_string(), i64::from(CONFIG_VERSION).into());
+
+    Ok(notes
+        .into_iter()
+        .map(|note| format!("{source} (version {version}): {note}"))
+        .collect())
+}
+
+/// Move the value at dotted path `from` to `to`, creating intermediate tables
+///
+/// Returns `false` without changes if `from` is missing or `to` already exists.
+pub fn move_key(table: &mut toml::Table, from: &str, to: &str) -> bool {
+    if lookup(table, to).is_some() {
+        return false;
+    }
+
+    let (from_parent, from_key) = split_path(from);
+    let Some(value) = lookup_table(table, from_parent).and_then(|parent| parent.remove(from_key))
+    else {
+        return false;
+    };
+
+    let (to_parent, to_key) = split_path(to);
+    let mut parent = table;
+    for segment in to_parent.iter() {
+        parent = match parent
+            .entry(segment.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            toml::Value::Table(next) => next,
+            _ => return false,
+        };
+    }
+    parent.insert(to_key.to_string(), value);
+    true
+}
+
+fn split_path(path: &str) -> (Vec<&str>, &str) {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let key = segments.pop().unwrap_or_default();
+    (segments, key)
+}
+
+fn lookup<'a>(table: &'a toml::Table, path: &str) -> Option<&'a toml::Value> {
+    let (parent, key) = split_path(path);
+    let mut current = table;
+    for segment in parent {
+        current = current.get(segment)?.as_table()?;
+    }
+    current.get(key)
+}
+
+fn lookup_table<'a>(table: &'a mut toml::Table, path: Vec<&str>) -> Option<&'a mut toml::Table> {
+    let mut current = table;
+    for segment in path {
+        current = current.get_mut(segment)?.as_table_mut()?;
+    }
+    Some(current)
+}
+
+/// v0 accepted any network string; v1 only accepts the lowercase presets
+fn normalize_network_name(table: &mut toml::Table) -> Vec<String> {
+    let Some(toml::Value::String(name)) = table
+        .get_mut("network")
+        .and_then(|network| network.get_mut("network"))
+    else {
+        return Vec::new();
+    };
+
+    let normalized = match name.parse::<Network>() {
+        Ok(network) => network,
+        Err(_) => Network::Custom,
+    };
+    if name == normalized.as_str() {
+        return Vec::new();
+    }
+
+    let note = format!("network.network {name:?} is now {:?}", normalized.as_str());
+    *name = normalized.as_str().to_string();
+    vec![note]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_unversioned_config() -> Result<()> {
+        let mut table: toml::Table = toml::from_str("[network]\nnetwork = \"Mainnet\"\n")?;
+
+        let notes = migrate(&mut table, "old.toml")?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(table["network"]["network"].as_str(), Some("mainnet"));
+        assert_eq!(
+            table["version"].as_integer(),
+            Some(i64::from(CONFIG_VERSION))
+        );
+
+        assert!(migrate(&mut table, "old.toml")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_version_rejected() -> Result<()> {
+        let mut table: toml::Table = toml::from_str("version = 99\n")?;
+        let err = migrate(&mut table, "new.toml").unwrap_err().to_string();
+        assert!(err.contains("new.toml") && err.contains("99"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_key() -> Result<()> {
+        let mut table: toml::Table = toml::from_str("[events]\npoll_interval = 500\n")?;
+
+        assert!(move_key(
+            &mut table,
+            "events.poll_interval",
+            "runtime.poll_interval"
+        ));
+        assert_eq!(table["runtime"]["poll_interval"].as_integer(), Some(500));
+        assert!(table["events"].get("poll_interval").is_none());
+        assert!(!move_key(&mut table, "events.missing", "runtime.missing"));
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fmt, str::FromStr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::ConfigError;
+
+/// Sui network the indexer connects to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Testnet,
+    Devnet,
+    Localnet,
+    /// Any other network; requires an explicit `grpc_url`
+    Custom,
+}
+
+/// Addresses of the framework packages published at genesis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameworkPackages {
+    /// Move standard library
+    pub move_stdlib: &'static str,
+    /// Sui framework
+    pub sui_framework: &'static str,
+    /// Sui system package
+    pub sui_system: &'static str,
+    /// DeepBook v1
+    pub deepbook: &'static str,
+}
+
+/// Framework package addresses shared by all public networks
+const FRAMEWORK_PACKAGES: FrameworkPackages = FrameworkPackages {
+    move_stdlib: "0x1",
+    sui_framework: "0x2",
+    sui_system: "0x3",
+    deepbook: "0xdee9",
+};
+
+impl Network {
+    /// All known networks
+    pub const ALL: [Network; 5] = [
+        Network::Mainnet,
+        Network::Testnet,
+        Network::Devnet,
+        Network::Localnet,
+        Network::Custom,
+    ];
+
+    /// Network name as used in configuration files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+            Network::Localnet => "localnet",
+            Network::Custom => "custom",
+        }
+    }
+
+    /// Default public gRPC endpoint for this network
+    pub fn default_grpc_url(&self) -> Option<Url> {
+        let url = match self {
+            Network::Mainnet => "https://fullnode.mainnet.sui.io:443",
+            Network::Testnet => "https://fullnode.testnet.sui.io:443",
+            Network::Devnet => "https://fullnode.devnet.sui.io:443",
+            Network::Localnet => "http://127.0.0.1:9000",
+            Network::Custom => return None,
+        };
+        Some(url.parse().expect("Preset gRPC URL should be valid"))
+    }
+
+    /// Known chain identifier (first four bytes of the genesis checkpoint digest)
+    ///
+    /// Devnet and localnet are regenerated on every reset, so they have none.
+    pub fn chain_id(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("35834a8a"),
+            Network::Testnet => Some("4c78adac"),
+            Network::Devnet | Network::Localnet | Network::Custom => None,
+        }
+    }
+
+    /// Framework package addresses on this network
+    pub fn framework_packages(&self) -> FrameworkPackages {
+        FRAMEWORK_PACKAGES
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Network {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Network::ALL
+            .into_iter()
+            .find(|network| network.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ConfigError::UnknownNetwork(s.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_presets() {
+        assert_eq!(
+            Network::Mainnet.default_grpc_url().unwrap().as_str(),
+            "https://fullnode.mainnet.sui.io/"
+        );
+        assert_eq!(Network::Custom.default_grpc_url(), None);
+        assert_eq!(Network::Testnet.chain_id(), Some("4c78adac"));
+        assert_eq!(Network::Devnet.chain_id(), None);
+        assert_eq!(Network::Mainnet.framework_packages().sui_framework, "0x2");
+    }
+
+    #[test]
+    fn test_network_parsing() {
+        assert_eq!("Mainnet".parse::<Network>().unwrap(), Network::Mainnet);
+        assert!("moonnet".parse::<Network>().is_err());
+        assert_eq!(Network::Localnet.to_string(), "localnet");
+    }
+}
@@ -0,0 +1,226 @@
+use url::Url;
+
+use crate::{IndexerConfig, SinkConfig};
+
+/// Placeholder written in place of a secret value
+pub const REDACTED: &str = "REDACTED";
+
+/// Property names of sink settings that hold credentials
+const SECRET_PROPERTY_WORDS: &[&str] = &["password", "secret", "token", "key", "credential"];
+
+impl IndexerConfig {
+    /// Copy of the configuration with passwords, tokens and credentials replaced
+    ///
+    /// Secret references such as `password_secret` stay visible, as they
+    /// only name where a secret is read from.
+    pub fn redacted(&self) -> IndexerConfig {
+        let mut config = self.clone();
+
+        let network = &mut config.network;
+        for url in network
+            .grpc_url
+            .iter_mut()
+            .chain(&mut network.fallback_grpc_urls)
+        {
+            redact_url(url);
+        }
+
+        let database = &mut config.database;
+        database.url = database.url.as_deref().map(redact_url_str);
+        if database.password.is_some() {
+            database.password = Some(REDACTED.to_string());
+        }
+
+        if let Some(url) = &mut config.observability.error_reporting.url {
+            redact_url(url);
+        }
+
+        let alerts = &mut config.alerts;
+        for url in &mut alerts.webhook_urls {
+            redact_url(url);
+        }
+        if let Some(pagerduty) = &mut alerts.pagerduty {
+            pagerduty.routing_key = REDACTED.to_string();
+        }
+
+        for sink in config.sinks.values_mut() {
+            redact_sink(sink);
+        }
+
+        for context in config.networks.values_mut() {
+            for url in context
+                .grpc_url
+                .iter_mut()
+                .chain(&mut context.fallback_grpc_urls)
+            {
+                redact_url(url);
+            }
+            for sink in context.sinks.values_mut() {
+                redact_sink(sink);
+            }
+        }
+
+        config
+    }
+}
+
+/// Replace the credentials a sink's settings may hold
+fn redact_sink(sink: &mut SinkConfig) {
+    match sink {
+        SinkConfig::Kafka(kafka) => {
+            for (name, value) in &mut kafka.properties {
+                let name = name.to_ascii_lowercase();
+                if SECRET_PROPERTY_WORDS.iter().any(|word| name.contains(word)) {
+                    *value = REDACTED.to_string();
+                }
+            }
+            if let Some(registry) = &mut kafka.schema_registry {
+                redact_url(&mut registry.url);
+            }
+        }
+        SinkConfig::Nats(nats) => {
+            nats.url = redact_url_str(&nats.url);
+            if let Some(registry) = &mut nats.schema_registry {
+                redact_url(&mut registry.url);
+            }
+        }
+        SinkConfig::Redis(redis) => {
+            redis.url = redact_url_str(&redis.url);
+            if let Some(registry) = &mut redis.schema_registry {
+                redact_url(&mut registry.url);
+            }
+        }
+        SinkConfig::PubSub(pubsub) => {
+            if let Some(endpoint) = &mut pubsub.endpoint {
+                redact_url(endpoint);
+            }
+        }
+        SinkConfig::Sns(sns) => {
+            if let Some(endpoint) = &mut sns.endpoint {
+                redact_url(endpoint);
+            }
+        }
+        SinkConfig::Sqs(sqs) => {
+            if let Some(endpoint) = &mut sqs.endpoint {
+                redact_url(endpoint);
+            }
+        }
+        SinkConfig::Webhook(webhook) => {
+            redact_url(&mut webhook.url);
+            // Headers usually carry authorization
+            for value in webhook.headers.values_mut() {
+                *value = REDACTED.to_string();
+            }
+        }
+        SinkConfig::Notification(notification) => {
+            if let Some(discord) = &mut notification.discord {
+                // The token is part of the path
+                discord.webhook_url.set_path(REDACTED);
+            }
+        }
+        SinkConfig::S3Parquet(s3) => {
+            if let Some(endpoint) = &mut s3.endpoint {
+                redact_url(endpoint);
+            }
+        }
+        SinkConfig::BigQuery(bigquery) => {
+            if let Some(endpoint) = &mut bigquery.endpoint {
+                redact_url(endpoint);
+            }
+        }
+        SinkConfig::Snowflake(snowflake) => {
+            if let Some(endpoint) = &mut snowflake.endpoint {
+                redact_url(endpoint);
+            }
+        }
+    }
+}
+
+/// Replace the password and query of a URL, which may hold credentials
+fn redact_url(url: &mut Url) {
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    if url.query().is_some() {
+        url.set_query(Some(REDACTED));
+    }
+}
+
+/// Like [`redact_url`], hiding the whole value if it does not parse
+fn redact_url_str(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut url) => {
+            redact_url(&mut url);
+            url.to_string()
+        }
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{DiscordConfig, NotificationSinkConfig, SlackConfig, WebhookSinkConfig};
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let mut config = IndexerConfig::default();
+        config.database.url = Some("postgresql://indexer:hunter2@db:5432/sui".to_string());
+        config.database.password = Some("hunter2".to_string());
+        config.alerts.slack = Some(SlackConfig {
+            token: crate::SecretRef::Env("SLACK_TOKEN".to_string()),
+            channel: "#alerts".to_string(),
+        });
+        config.sinks.insert(
+            "hook".to_string(),
+            SinkConfig::Webhook(WebhookSinkConfig {
+                enabled: true,
+                filters: vec![],
+                format: crate::EventFormat::Json,
+                url: "https://hooks.example.com/in?key=abc".parse().unwrap(),
+                headers: BTreeMap::from([("Authorization".to_string(), "Bearer abc".to_string())]),
+                batch_size: 100,
+                timeout: 30,
+                secret: None,
+                max_attempts: 5,
+                retry_delay: 1,
+                max_retry_delay: 60,
+                concurrency: 4,
+            }),
+        );
+        config.sinks.insert(
+            "liquidations".to_string(),
+            SinkConfig::Notification(NotificationSinkConfig {
+                enabled: true,
+                filters: vec![],
+                template: "{event_type}".to_string(),
+                threshold: None,
+                max_per_minute: 20,
+                discord: Some(DiscordConfig {
+                    webhook_url: "https://discord.com/api/webhooks/123/tok-456"
+                        .parse()
+                        .unwrap(),
+                }),
+                slack: None,
+                telegram: None,
+            }),
+        );
+
+        let redacted = config.redacted();
+        let rendered = serde_json::to_string(&redacted).unwrap();
+        for secret in ["hunter2", "key=abc", "Bearer abc", "tok-456"] {
+            assert!(!rendered.contains(secret), "{secret} leaked: {rendered}");
+        }
+        assert_eq!(
+            redacted.database.url.as_deref(),
+            Some("postgresql://indexer:REDACTED@db:5432/sui")
+        );
+        // A secret reference only names where the token is read from
+        assert_eq!(
+            redacted.alerts.slack.unwrap().token,
+            crate::SecretRef::Env("SLACK_TOKEN".to_string())
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeSet;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::IndexerConfig;
+
+/// Configuration fields that can be applied to a running indexer without a restart
+///
+/// A `*` segment stands for any key, e.g. the name of a watchlist.
+pub const RELOADABLE_FIELDS: &[&str] = &[
+    "events.filters",
+    "events.batch_size",
+    "events.max_concurrent_batches",
+    "observability.log_level",
+    "observability.slow_checkpoint_ms",
+    "observability.slow_event_ms",
+    "watchlists.*.enabled",
+    "watchlists.*.addresses",
+    "watchlists.*.file",
+    "watchlists.*.database",
+    "watchlists.*.tag",
+    "watchlists.*.capture",
+    "watchlists.*.false_positive_rate",
+];
+
+/// Outcome of applying a reloaded configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// Fields that changed and were applied at runtime
+    pub applied: Vec<String>,
+    /// Fields that changed but only take effect after a restart
+    pub requires_restart: Vec<String>,
+}
+
+impl ReloadReport {
+    /// Check if the reloaded configuration was identical to the current one
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.requires_restart.is_empty()
+    }
+}
+
+/// Check if a changed field path can be applied at runtime
+pub fn is_reloadable(field: &str) -> bool {
+    RELOADABLE_FIELDS.iter().any(|reloadable| {
+        let mut segments = field.split('.');
+        // The field is the reloadable one or lies within it
+        reloadable.split('.').all(|expected| {
+            segments
+                .next()
+                .is_some_and(|s| expected == "*" || s == expected)
+        })
+    })
+}
+
+impl IndexerConfig {
+    /// List the dotted paths of all fields that differ between two configurations
+    pub fn changed_fields(&self, other: &IndexerConfig) -> Result<Vec<String>> {
+        let current = toml::Value::try_from(self)?;
+        let updated = toml::Value::try_from(other)?;
+
+        let mut changed = Vec::new();
+        diff_values("", &current, &updated, &mut changed);
+        Ok(changed)
+    }
+
+    /// Apply the reloadable sections of `updated` and report what changed
+    ///
+    /// Fields that require a restart are reported but left untouched.
+    pub fn apply_reload(&mut self, updated: &IndexerConfig) -> Result<ReloadReport> {
+        let mut report = ReloadReport::default();
+
+        for field in self.changed_fields(updated)? {
+            if is_reloadable(&field) {
+                report.applied.push(field);
+            } else {
+                report.requires_restart.push(field);
+            }
+        }
+
+        if !report.applied.is_empty() {
+            self.events.filters = updated.events.filters.clone();
+            self.events.batch_size = updated.events.batch_size;
+            self.events.max_concurrent_batches = updated.events.max_concurrent_batches;
+            self.observability.log_level = updated.observability.log_level.clone();
+            self.observability.slow_checkpoint_ms = updated.observability.slow_checkpoint_ms;
+            self.observability.slow_event_ms = updated.observability.slow_event_ms;
+            // Watchlists are only added, removed and rerouted on restart
+            for (name, watchlist) in &mut self.watchlists {
+                let Some(updated) = updated.watchlists.get(name) else {
+                    continue;
+                };
+                *watchlist = crate::WatchlistConfig {
+                    refresh_interval: watchlist.refresh_interval,
+                    sinks: watchlist.sinks.clone(),
+                    ..updated.clone()
+                };
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Recursively collect the paths of differing leaves between two TOML trees
+fn diff_values(path: &str, current: &toml::Value, updated: &toml::Value, out: &mut Vec<String>) {
+    match (current, updated) {
+        (toml::Value::Table(current), toml::Value::Table(updated)) => {
+            let keys: BTreeSet<&String> = current.keys().chain(updated.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match (current.get(key), updated.get(key)) {
+                    (Some(current), Some(updated)) => diff_values(&child, current, updated, out),
+                    _ => out.push(child),
+                }
+            }
+        }
+        _ if current != updated => out.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventFilter, FilterPriority};
+
+    #[test]
+    fn test_reloadable_field_matching() {
+        assert!(is_reloadable("events.filters"));
+        assert!(is_reloadable("events.batch_size"));
+        assert!(!is_reloadable("events.batch_size_extra"));
+        assert!(is_reloadable("observability.slow_checkpoint_ms"));
+        assert!(!is_reloadable("network.grpc_url"));
+        assert!(is_reloadable("watchlists.sanctions.addresses"));
+        assert!(!is_reloadable("watchlists.sanctions.sinks"));
+        assert!(!is_reloadable("watchlists.sanctions"));
+    }
+
+    #[test]
+    fn test_apply_reload_splits_fields() -> Result<()> {
+        let mut current = IndexerConfig::default();
+        let mut updated = IndexerConfig::default();
+        updated.events.batch_size = 500;
+        updated.events.filters.push(EventFilter {
+            name: None,
+            package: Some("0x2".to_string()),
+            module: None,
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        });
+        updated.database.url = Some("postgresql://other/sui_indexer".to_string());
+
+        let report = current.apply_reload(&updated)?;
+
+        assert_eq!(report.applied, vec!["events.batch_size", "events.filters"]);
+        assert_eq!(report.requires_restart, vec!["database.url"]);
+        assert_eq!(current.events.batch_size, 500);
+        assert_eq!(current.events.filters.len(), 1);
+        assert_eq!(current.database.url, IndexerConfig::default().database.url);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_reload_without_changes() -> Result<()> {
+        let mut current = IndexerConfig::default();
+        let report = current.apply_reload(&IndexerConfig::default())?;
+        assert!(report.is_empty());
+        Ok(())
+    }
+}
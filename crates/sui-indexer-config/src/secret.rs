@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use eyre::{Result, WrapErr};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Reference to a secret kept outside the configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretRef {
+    /// Read from an environment variable, e.g. `{ env = "DB_PASSWORD" }`
+    Env(String),
+    /// Read from a file such as a mounted secret, e.g. `{ file = "/run/secrets/db" }`
+    File(PathBuf),
+}
+
+impl SecretRef {
+    /// Read the secret value, trimming a trailing newline from files
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            SecretRef::Env(name) => std::env::var(name).wrap_err_with(|| {
+                format!("Failed to read secret from environment variable {name}")
+            }),
+            SecretRef::File(path) => std::fs::read_to_string(path)
+                .map(|secret| secret.trim_end_matches(['\r', '\n']).to_string())
+                .wrap_err_with(|| format!("Failed to read secret from {}", path.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_file_secret() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("password");
+        std::fs::write(&path, "p@ss:w/rd\n")?;
+
+        assert_eq!(SecretRef::File(path).resolve()?, "p@ss:w/rd");
+        assert!(SecretRef::Env("SUI_INDEXER_TEST_MISSING_SECRET".into())
+            .resolve()
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,866 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{
+    IndexerConfig, PubSubSinkConfig, RedisMode, SinkConfig, SnsSinkConfig, SqsSinkConfig,
+    WebhookSinkConfig,
+};
+
+/// A problem found by [`IndexerConfig::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Dotted path of the offending setting, e.g. `events.filters[1].name`
+    pub path: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl ValidationError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// 1-based line of the setting in a TOML source, falling back to the
+    /// closest enclosing key or table that is present
+    pub fn line_in(&self, source: &str) -> Option<usize> {
+        let lines = scan_lines(source);
+        let mut path = self.path.as_str();
+
+        loop {
+            let (table, key) = path.rsplit_once('.').unwrap_or(("", path));
+            let key = key.split('[').next().unwrap_or(key);
+
+            let found = lines
+                .iter()
+                .find(|line| line.section == table && line.key.as_deref() == Some(key))
+                .or_else(|| {
+                    lines
+                        .iter()
+                        .find(|line| line.key.is_none() && line.section == path)
+                });
+            if let Some(line) = found {
+                return Some(line.number);
+            }
+            if table.is_empty() {
+                return None;
+            }
+            path = table;
+        }
+    }
+}
+
+/// A table header or key assignment in a TOML source
+struct SourceLine {
+    number: usize,
+    /// Table the line belongs to, with array-of-tables entries indexed as `name[n]`
+    section: String,
+    /// Assigned key, or `None` for table headers
+    key: Option<String>,
+}
+
+fn scan_lines(source: &str) -> Vec<SourceLine> {
+    let unquote = |name: &str| -> String {
+        name.split('.')
+            .map(|part| part.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+
+    let mut section = String::new();
+    let mut array_counts: HashMap<String, usize> = HashMap::new();
+    let mut lines = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let key = if let Some((name, _)) = line
+            .strip_prefix("[[")
+            .and_then(|rest| rest.split_once("]]"))
+        {
+            let name = unquote(name);
+            let count = array_counts.entry(name.clone()).or_default();
+            section = format!("{name}[{count}]");
+            *count += 1;
+            None
+        } else if let Some((name, _)) = line.strip_prefix('[').and_then(|rest| rest.split_once(']'))
+        {
+            section = unquote(name);
+            None
+        } else if let Some((key, _)) = line.split_once('=').filter(|_| !line.starts_with('#')) {
+            Some(unquote(key))
+        } else {
+            continue;
+        };
+
+        lines.push(SourceLine {
+            number: index + 1,
+            section: section.clone(),
+            key,
+        });
+    }
+    lines
+}
+
+impl IndexerConfig {
+    /// Check the configuration for values that parse but cannot work,
+    /// returning every problem found
+    pub fn validate(&self) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, path: &str, message: &str| {
+            if !ok {
+                errors.push(ValidationError::new(path, message));
+            }
+        };
+
+        let network = &self.network;
+        let retry = &network.retry;
+        check(
+            network.pool.max_connections > 0,
+            "network.pool.max_connections",
+            "must be at least 1",
+        );
+        check(
+            retry.max_attempts > 0,
+            "network.retry.max_attempts",
+            "must be at least 1",
+        );
+        check(
+            retry.initial_delay <= retry.max_delay,
+            "network.retry.initial_delay",
+            "must not exceed `max_delay`",
+        );
+        check(
+            retry.backoff_multiplier >= 1.0,
+            "network.retry.backoff_multiplier",
+            "must be at least 1.0",
+        );
+
+        let database = &self.database;
+        check(
+            database.max_connections > 0,
+            "database.max_connections",
+            "must be at least 1",
+        );
+        check(
+            database.min_connections <= database.max_connections,
+            "database.min_connections",
+            "must not exceed `max_connections`",
+        );
+        check(
+            !(database.password.is_some() && database.password_secret.is_some()),
+            "database.password_secret",
+            "cannot be combined with `password`",
+        );
+        check(
+            database.schema.as_deref().is_none_or(is_schema_name),
+            "database.schema",
+            "must be a lowercase identifier of at most 63 characters",
+        );
+
+        let events = &self.events;
+        check(
+            events.batch_size > 0,
+            "events.batch_size",
+            "must be at least 1",
+        );
+        check(
+            events.max_concurrent_batches > 0,
+            "events.max_concurrent_batches",
+            "must be at least 1",
+        );
+        check(
+            events.priority_batch_size > 0,
+            "events.priority_batch_size",
+            "must be at least 1",
+        );
+        for (index, id) in self.packages.original_ids.iter().enumerate() {
+            check(
+                is_object_id(id),
+                &format!("packages.original_ids[{index}]"),
+                "must be a 0x-prefixed hex object ID",
+            );
+        }
+        for (index, filter) in self.move_calls.filters.iter().enumerate() {
+            let path = format!("move_calls.filters[{index}]");
+            check(
+                filter.package.as_deref().is_none_or(is_object_id),
+                &format!("{path}.package"),
+                "must be a 0x-prefixed hex object ID",
+            );
+            check(
+                filter.function.is_none() || filter.module.is_some(),
+                &format!("{path}.function"),
+                "requires a module",
+            );
+            check(
+                filter.sender.as_deref().is_none_or(is_object_id),
+                &format!("{path}.sender"),
+                "must be a 0x-prefixed hex address",
+            );
+        }
+        for (index, id) in self.objects.ids.iter().enumerate() {
+            check(
+                is_object_id(id),
+                &format!("objects.ids[{index}]"),
+                "must be a 0x-prefixed hex object ID",
+            );
+        }
+        for (name, watchlist) in &self.watchlists {
+            let path = format!("watchlists.{name}");
+            for (index, address) in watchlist.addresses.iter().enumerate() {
+                check(
+                    is_object_id(address),
+                    &format!("{path}.addresses[{index}]"),
+                    "must be a 0x-prefixed hex address",
+                );
+            }
+            check(
+                watchlist.refresh_interval > 0 || (watchlist.file.is_none() && !watchlist.database),
+                &format!("{path}.refresh_interval"),
+                "must be at least 1 second",
+            );
+            check(
+                watchlist.false_positive_rate > 0.0 && watchlist.false_positive_rate < 1.0,
+                &format!("{path}.false_positive_rate"),
+                "must be between 0 and 1, exclusive",
+            );
+            check(
+                watchlist
+                    .tag
+                    .as_deref()
+                    .is_none_or(|tag| !tag.trim().is_empty()),
+                &format!("{path}.tag"),
+                "must not be empty",
+            );
+            for (index, sink) in watchlist.sinks.iter().enumerate() {
+                check(
+                    self.sinks.contains_key(sink),
+                    &format!("{path}.sinks[{index}]"),
+                    "must name a configured sink",
+                );
+            }
+        }
+
+        let runtime = &self.runtime;
+        for (value, field) in [
+            (runtime.fetch_concurrency, "fetch_concurrency"),
+            (runtime.writer_concurrency, "writer_concurrency"),
+            (runtime.process_concurrency, "process_concurrency"),
+            (
+                runtime.checkpoint_channel_capacity,
+                "checkpoint_channel_capacity",
+            ),
+        ] {
+            check(value > 0, &format!("runtime.{field}"), "must be at least 1");
+        }
+        check(
+            runtime.checkpoint_cache_dir.is_none() || runtime.checkpoint_cache_limit > 0,
+            "runtime.checkpoint_cache_limit",
+            "must be positive when `checkpoint_cache_dir` is set",
+        );
+        let scheduler = &runtime.scheduler;
+        if scheduler.enabled {
+            check(
+                scheduler.node_requests > 0,
+                "runtime.scheduler.node_requests",
+                "must be at least 1",
+            );
+            check(
+                scheduler.storage_writes > 0,
+                "runtime.scheduler.storage_writes",
+                "must be at least 1",
+            );
+        }
+        for (name, weight) in &scheduler.weights {
+            check(
+                *weight > 0,
+                &format!("runtime.scheduler.weights.{name}"),
+                "must be at least 1",
+            );
+        }
+        let autotune = &runtime.autotune;
+        if autotune.enabled {
+            check(
+                autotune.interval > 0,
+                "runtime.autotune.interval",
+                "must be at least 1 second",
+            );
+            check(
+                autotune.store_p95_ms > 0,
+                "runtime.autotune.store_p95_ms",
+                "must be at least 1 millisecond",
+            );
+            check(
+                autotune.rpc_error_rate > 0.0 && autotune.rpc_error_rate <= 1.0,
+                "runtime.autotune.rpc_error_rate",
+                "must be above 0 and at most 1",
+            );
+            check(
+                autotune.max_writer_concurrency >= runtime.writer_concurrency,
+                "runtime.autotune.max_writer_concurrency",
+                "must be at least runtime.writer_concurrency",
+            );
+            check(
+                autotune.max_fetch_concurrency >= runtime.fetch_concurrency,
+                "runtime.autotune.max_fetch_concurrency",
+                "must be at least runtime.fetch_concurrency",
+            );
+        }
+        check(
+            runtime.poll_interval > 0,
+            "runtime.poll_interval",
+            "must be at least 1 millisecond",
+        );
+
+        let outbox = &self.outbox;
+        check(
+            outbox.poll_interval > 0,
+            "outbox.poll_interval",
+            "must be at least 1 millisecond",
+        );
+        check(
+            outbox.batch_size > 0,
+            "outbox.batch_size",
+            "must be at least 1",
+        );
+        check(
+            outbox.max_attempts > 0,
+            "outbox.max_attempts",
+            "must be at least 1",
+        );
+        check(
+            self.flight.batch_size > 0,
+            "flight.batch_size",
+            "must be at least 1",
+        );
+
+        let alerts = &self.alerts;
+        check(
+            alerts.check_interval > 0,
+            "alerts.check_interval",
+            "must be at least 1 second",
+        );
+        check(
+            !alerts.any_rule_enabled() || alerts.any_channel_configured(),
+            "alerts",
+            "rules are enabled but no `webhook_urls`, `slack`, `telegram` or `pagerduty` channel is set",
+        );
+
+        check(
+            !alerts.stall.enabled || self.watchdog.enabled,
+            "alerts.stall.enabled",
+            "requires `watchdog.enabled`, which detects the stall",
+        );
+        check(
+            self.watchdog.stall_timeout > 0,
+            "watchdog.stall_timeout",
+            "must be at least 1 second",
+        );
+
+        for (name, slo) in &self.observability.slos {
+            let path = format!("observability.slos.{name}");
+            check(
+                slo.threshold_secs > 0.0,
+                &format!("{path}.threshold_secs"),
+                "must be greater than 0",
+            );
+            check(
+                slo.objective > 0.0 && slo.objective < 1.0,
+                &format!("{path}.objective"),
+                "must be between 0 and 1, exclusive",
+            );
+            check(
+                slo.window > 0,
+                &format!("{path}.window"),
+                "must be at least 1 second",
+            );
+        }
+
+        for (name, key) in &self.auth.keys {
+            check(
+                key.rate_limit
+                    .is_none_or(|limit| limit.is_finite() && limit > 0.0),
+                &format!("auth.keys.{name}.rate_limit"),
+                "must be greater than 0",
+            );
+        }
+        check(
+            !self.auth.database_keys || self.auth.refresh_interval > 0,
+            "auth.refresh_interval",
+            "must be at least 1 second",
+        );
+
+        if let Err(e) = network.grpc_url() {
+            errors.push(ValidationError::new("network.grpc_url", e.to_string()));
+        }
+
+        let mut filter_names = HashMap::new();
+        for (index, filter) in events.filters.iter().enumerate() {
+            let Some(name) = &filter.name else {
+                continue;
+            };
+            match filter_names.get(name) {
+                Some(first) => errors.push(ValidationError::new(
+                    format!("events.filters[{index}].name"),
+                    format!("duplicates the name of `events.filters[{first}]`"),
+                )),
+                None => {
+                    filter_names.insert(name, index);
+                }
+            }
+        }
+
+        for (name, sink) in &self.sinks {
+            let path = format!("sinks.{name}");
+            if let Err(e) = events.named_filters(sink.filters()) {
+                errors.push(ValidationError::new(
+                    format!("{path}.filters"),
+                    e.to_string(),
+                ));
+            }
+
+            let problem = match sink {
+                SinkConfig::Kafka(kafka) if kafka.brokers.trim().is_empty() => {
+                    Some(("brokers", "must list at least one broker"))
+                }
+                SinkConfig::Kafka(kafka)
+                    if kafka.format.uses_schema_registry() && kafka.schema_registry.is_none() =>
+                {
+                    Some((
+                        "schema_registry",
+                        "is required for Avro and protobuf formats",
+                    ))
+                }
+                SinkConfig::Nats(nats)
+                    if nats.format.uses_schema_registry() && nats.schema_registry.is_none() =>
+                {
+                    Some((
+                        "schema_registry",
+                        "is required for Avro and protobuf formats",
+                    ))
+                }
+                SinkConfig::Redis(redis)
+                    if redis.format.uses_schema_registry() && redis.schema_registry.is_none() =>
+                {
+                    Some((
+                        "schema_registry",
+                        "is required for Avro and protobuf formats",
+                    ))
+                }
+                // Text messages and JSON batches cannot carry binary payloads
+                SinkConfig::Webhook(WebhookSinkConfig { format, .. })
+                | SinkConfig::PubSub(PubSubSinkConfig { format, .. })
+                | SinkConfig::Sns(SnsSinkConfig { format, .. })
+                | SinkConfig::Sqs(SqsSinkConfig { format, .. })
+                    if format.uses_schema_registry() =>
+                {
+                    Some((
+                        "format",
+                        "must be `json` or `cloudevents` for this sink type",
+                    ))
+                }
+                SinkConfig::Webhook(webhook) if webhook.batch_size == 0 => {
+                    Some(("batch_size", "must be at least 1"))
+                }
+                SinkConfig::Webhook(webhook) if webhook.max_attempts == 0 => {
+                    Some(("max_attempts", "must be at least 1"))
+                }
+                SinkConfig::Webhook(webhook) if webhook.concurrency == 0 => {
+                    Some(("concurrency", "must be at least 1"))
+                }
+                SinkConfig::S3Parquet(s3) if s3.bucket.is_empty() => {
+                    Some(("bucket", "must not be empty"))
+                }
+                SinkConfig::Redis(redis) if redis.key.is_empty() => {
+                    Some(("key", "must not be empty"))
+                }
+                SinkConfig::Redis(redis) if redis.max_len == Some(0) => {
+                    Some(("max_len", "must be at least 1"))
+                }
+                SinkConfig::Redis(redis)
+                    if redis.mode == RedisMode::PubSub && redis.max_len.is_some() =>
+                {
+                    Some(("max_len", "only applies to `mode = \"stream\"`"))
+                }
+                SinkConfig::PubSub(pubsub) if !(1..=1000).contains(&pubsub.batch_size) => {
+                    Some(("batch_size", "must be between 1 and 1000"))
+                }
+                SinkConfig::Sns(sns)
+                    if sns.topic_arn.ends_with(".fifo") && sns.ordering_key.is_none() =>
+                {
+                    Some(("ordering_key", "is required for FIFO topics"))
+                }
+                SinkConfig::Sqs(sqs)
+                    if sqs.queue_url.path().ends_with(".fifo") && sqs.ordering_key.is_none() =>
+                {
+                    Some(("ordering_key", "is required for FIFO queues"))
+                }
+                SinkConfig::Notification(notification)
+                    if notification.template.trim().is_empty() =>
+                {
+                    Some(("template", "must not be empty"))
+                }
+                SinkConfig::Notification(notification) if notification.max_per_minute == 0 => {
+                    Some(("max_per_minute", "must be at least 1"))
+                }
+                SinkConfig::Notification(notification)
+                    if notification.discord.is_none()
+                        && notification.slack.is_none()
+                        && notification.telegram.is_none() =>
+                {
+                    Some(("discord", "or `slack` or `telegram` must be set"))
+                }
+                SinkConfig::BigQuery(bigquery) if bigquery.table.is_empty() => {
+                    Some(("table", "must not be empty"))
+                }
+                SinkConfig::BigQuery(bigquery) if bigquery.max_rows == 0 => {
+                    Some(("max_rows", "must be at least 1"))
+                }
+                SinkConfig::Snowflake(snowflake) if snowflake.table.is_empty() => {
+                    Some(("table", "must not be empty"))
+                }
+                SinkConfig::Snowflake(snowflake) if snowflake.max_rows == 0 => {
+                    Some(("max_rows", "must be at least 1"))
+                }
+                _ => None,
+            };
+            if let Some((field, message)) = problem {
+                errors.push(ValidationError::new(format!("{path}.{field}"), message));
+            }
+
+            let tables = match sink {
+                SinkConfig::BigQuery(bigquery) => Some(&bigquery.tables),
+                SinkConfig::Snowflake(snowflake) => Some(&snowflake.tables),
+                _ => None,
+            };
+            for filter in tables.into_iter().flat_map(BTreeMap::keys) {
+                let selected = sink.filters().is_empty() || sink.filters().contains(filter);
+                if !selected || events.named_filters(std::slice::from_ref(filter)).is_err() {
+                    errors.push(ValidationError::new(
+                        format!("{path}.tables.{filter}"),
+                        "must name an event filter selected by the sink",
+                    ));
+                }
+            }
+        }
+
+        for (name, rule) in &self.rules {
+            let path = format!("rules.{name}");
+            if let Err(e) = events.named_filters(&rule.filters) {
+                errors.push(ValidationError::new(
+                    format!("{path}.filters"),
+                    e.to_string(),
+                ));
+            }
+            for (index, condition) in rule.conditions.iter().enumerate() {
+                let path = format!("{path}.conditions[{index}]");
+                let problem = if condition.field.trim().is_empty() {
+                    Some(("field", "must not be empty"))
+                } else if condition.min.is_none()
+                    && condition.max.is_none()
+                    && condition.one_of.is_empty()
+                {
+                    Some(("min", "or `max` or `one_of` must be set"))
+                } else if condition
+                    .min
+                    .zip(condition.max)
+                    .is_some_and(|(min, max)| min > max)
+                {
+                    Some(("max", "must not be below `min`"))
+                } else {
+                    None
+                };
+                if let Some((field, message)) = problem {
+                    errors.push(ValidationError::new(format!("{path}.{field}"), message));
+                }
+            }
+            let problem = if rule.template.trim().is_empty() {
+                Some(("template", "must not be empty"))
+            } else if rule.tag.as_deref().is_some_and(|tag| tag.trim().is_empty()) {
+                Some(("tag", "must not be empty"))
+            } else if rule.max_per_minute == 0 {
+                Some(("max_per_minute", "must be at least 1"))
+            } else if rule.tag.is_none() && rule.webhooks.is_empty() && !rule.alert {
+                Some(("tag", "or `webhooks` or `alert` must be set"))
+            } else if rule.alert && !self.alerts.any_channel_configured() {
+                Some(("alert", "requires a notification channel under `[alerts]`"))
+            } else {
+                None
+            };
+            if let Some((field, message)) = problem {
+                errors.push(ValidationError::new(format!("{path}.{field}"), message));
+            }
+        }
+
+        // A context shares everything else with the top level, checked above
+        let mut schemas = HashMap::new();
+        if let Some(schema) = &self.database.schema {
+            schemas.insert(schema.clone(), "database.schema".to_string());
+        }
+        for (name, config) in self.network_contexts() {
+            let path = format!("networks.{name}");
+            if name == self.context_name() {
+                errors.push(ValidationError::new(
+                    &path,
+                    "must not be named after the top-level network",
+                ));
+            }
+            let schema = config.database.schema.clone().unwrap_or_default();
+            if let Some(owner) = schemas.insert(schema, format!("{path}.schema")) {
+                errors.push(ValidationError::new(
+                    format!("{path}.schema"),
+                    format!("is already used by `{owner}`"),
+                ));
+            }
+            // Problems of the inherited settings were reported for the top level
+            let own_filters = self.networks[&name].filters.is_some();
+            for error in config.validate().err().into_iter().flatten() {
+                let field = [
+                    ("network.grpc_url", "grpc_url"),
+                    ("database.schema", "schema"),
+                    ("events.filters", if own_filters { "filters" } else { "" }),
+                    ("sinks.", "sinks."),
+                ]
+                .into_iter()
+                .filter(|(_, field)| !field.is_empty())
+                .find_map(|(prefix, field)| {
+                    let rest = error.path.strip_prefix(prefix)?;
+                    Some(format!("{field}{rest}"))
+                });
+                if let Some(field) = field {
+                    errors.push(ValidationError::new(
+                        format!("{path}.{field}"),
+                        error.message,
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Check if `id` is a hex object ID such as `0x2`
+fn is_object_id(id: &str) -> bool {
+    id.strip_prefix("0x").is_some_and(|hex| {
+        (1..=64).contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Check if `name` can be used unquoted as a PostgreSQL schema name
+fn is_schema_name(name: &str) -> bool {
+    name.len() <= 63
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventFilter, FilterPriority};
+
+    #[test]
+    fn test_validate_collects_all_problems() {
+        let filter = EventFilter {
+            name: Some("swaps".to_string()),
+            package: None,
+            module: Some("pool".to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        };
+        let mut config = IndexerConfig::default();
+        config.events.batch_size = 0;
+        config.events.priority_batch_size = 0;
+        config.events.filters = vec![filter.clone(), filter];
+        config.database.min_connections = 50;
+        config.outbox.batch_size = 0;
+        config.outbox.max_attempts = 0;
+        config.packages.original_ids = vec!["0x2".to_string(), "navi".to_string()];
+        config.objects.ids = vec!["0x5".to_string(), "pool".to_string()];
+        config.watchlists.insert(
+            "sanctions".to_string(),
+            crate::WatchlistConfig {
+                addresses: vec!["0xa11ce".to_string(), "alice".to_string()],
+                sinks: vec!["missing".to_string()],
+                ..crate::WatchlistConfig::default()
+            },
+        );
+        config.rules.insert(
+            "liquidations".to_string(),
+            crate::RuleConfig {
+                filters: vec!["missing".to_string()],
+                conditions: vec![crate::RuleCondition {
+                    field: "fields.parsed_json.amount".to_string(),
+                    min: Some(100.0),
+                    max: Some(10.0),
+                    one_of: Vec::new(),
+                }],
+                alert: true,
+                ..crate::RuleConfig::default()
+            },
+        );
+        config.move_calls.filters = vec![crate::MoveCallFilter {
+            package: Some("0x2".to_string()),
+            module: None,
+            function: Some("transfer".to_string()),
+            sender: None,
+        }];
+        config.runtime.autotune.enabled = true;
+        config.runtime.autotune.max_writer_concurrency = 2;
+        config.observability.slos.insert(
+            "availability".to_string(),
+            crate::SloConfig {
+                threshold_secs: 30.0,
+                objective: 99.0,
+                window: 3600,
+            },
+        );
+        config.auth.keys.insert(
+            "dashboard".to_string(),
+            crate::ApiKeyConfig {
+                key: crate::SecretRef::Env("DASHBOARD_KEY".to_string()),
+                scope: crate::ApiScope::Read,
+                rate_limit: Some(0.0),
+            },
+        );
+        config.sinks.insert(
+            "orders".to_string(),
+            SinkConfig::Sqs(crate::SqsSinkConfig {
+                enabled: true,
+                filters: vec![],
+                format: crate::EventFormat::Json,
+                queue_url: "https://sqs.us-east-1.amazonaws.com/123456789012/orders.fifo"
+                    .parse()
+                    .unwrap(),
+                ordering_key: None,
+                region: None,
+                profile: None,
+                endpoint: None,
+            }),
+        );
+        config.sinks.insert(
+            "stream".to_string(),
+            SinkConfig::Kafka(crate::KafkaSinkConfig {
+                enabled: true,
+                filters: vec![],
+                format: crate::EventFormat::Avro,
+                schema_registry: None,
+                brokers: "localhost:9092".to_string(),
+                topic: "sui-events".to_string(),
+                properties: BTreeMap::new(),
+            }),
+        );
+        config.sinks.insert(
+            "warehouse".to_string(),
+            SinkConfig::BigQuery(crate::BigQuerySinkConfig {
+                enabled: true,
+                filters: vec!["swaps".to_string()],
+                project: "analytics".to_string(),
+                dataset: "sui".to_string(),
+                table: "events".to_string(),
+                tables: BTreeMap::from([
+                    ("swaps".to_string(), "swaps".to_string()),
+                    ("liquidations".to_string(), "liquidations".to_string()),
+                ]),
+                credentials_file: None,
+                max_rows: 500,
+                flush_interval: 5,
+                endpoint: None,
+            }),
+        );
+
+        config.database.schema = Some("sui".to_string());
+        config.networks.insert(
+            "devnet".to_string(),
+            crate::NetworkContextConfig {
+                network: crate::Network::Custom,
+                schema: Some("sui".to_string()),
+                ..crate::NetworkContextConfig::default()
+            },
+        );
+        config.networks.insert(
+            "testnet".to_string(),
+            crate::NetworkContextConfig {
+                schema: Some("Testnet".to_string()),
+                ..crate::NetworkContextConfig::default()
+            },
+        );
+
+        let paths: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "database.min_connections",
+                "events.batch_size",
+                "events.priority_batch_size",
+                "packages.original_ids[1]",
+                "move_calls.filters[0].function",
+                "objects.ids[1]",
+                "watchlists.sanctions.addresses[1]",
+                "watchlists.sanctions.sinks[0]",
+                "runtime.autotune.max_writer_concurrency",
+                "outbox.batch_size",
+                "outbox.max_attempts",
+                "observability.slos.availability.objective",
+                "auth.keys.dashboard.rate_limit",
+                "events.filters[1].name",
+                "sinks.orders.ordering_key",
+                "sinks.stream.schema_registry",
+                "sinks.warehouse.tables.liquidations",
+                "rules.liquidations.filters",
+                "rules.liquidations.conditions[0].max",
+                "rules.liquidations.alert",
+                "networks.devnet.schema",
+                "networks.devnet.grpc_url",
+                "networks.testnet",
+                "networks.testnet.schema",
+            ]
+        );
+        assert!(IndexerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_line_in_source() {
+        let source = r#"
+[database]
+url = "postgresql://localhost/sui"
+min_connections = 50
+
+[[events.filters]]
+module = "coin"
+
+[[events.filters]]
+name = "swaps"
+"#;
+        let line = |path: &str| ValidationError::new(path, "").line_in(source);
+
+        assert_eq!(line("database.min_connections"), Some(4));
+        assert_eq!(line("events.filters[1].name"), Some(10));
+        // Settings that are not written fall back to their table
+        assert_eq!(line("database.max_connections"), Some(2));
+        assert_eq!(line("events.filters[0].name"), Some(6));
+        assert_eq!(line("alerts.check_interval"), None);
+    }
+}
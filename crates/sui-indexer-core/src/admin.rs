@@ -0,0 +1,796 @@
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{request::Parts, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use sui_indexer_config::{ErrorKind, EventFilter, IndexerConfig, ReloadReport, WithKind};
+use sui_indexer_events::BreakdownStats;
+use sui_indexer_storage::{AuditEntry, StorageManager};
+use tokio::{net::TcpListener, sync::watch};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::{
+    audit::{self, SOURCE_ADMIN_API},
+    auth::{self, ApiAuth, Caller},
+    backfill::Backfill,
+    control::{PipelineControl, QueueDepths},
+    error::IndexerError,
+    error_history::PipelineError,
+    metrics::Metrics,
+    recent_errors::{RecentError, RecentErrors},
+    reconcile::ReconciliationReport,
+    reload::ConfigReloader,
+    resources::ResourceUsage,
+    startup::StartupReport,
+    sui::{CheckpointRange, EndpointHealth, SuiClient},
+};
+
+/// Snapshot of a running indexer reported by the control endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerStatus {
+    /// Indexer version
+    pub version: String,
+    /// Network being indexed
+    pub network: String,
+    /// Seconds since the pipeline started
+    pub uptime_secs: u64,
+    /// Last committed checkpoint, if any
+    pub latest_processed_checkpoint: Option<u64>,
+    /// Latest checkpoint reported by the node, if known
+    pub latest_network_checkpoint: Option<u64>,
+    /// Checkpoints committed since startup
+    pub checkpoints_processed: u64,
+    /// Events stored since startup
+    pub events_processed: u64,
+    /// Failed checkpoint fetches since startup
+    pub fetch_errors: u64,
+    /// Failed storage commits since startup
+    pub storage_errors: u64,
+    /// Events matched per filter since startup
+    #[serde(default)]
+    pub filter_matches: BTreeMap<String, u64>,
+    /// Matches, stored events and errors per filter since startup
+    #[serde(default)]
+    pub filters: BTreeMap<String, BreakdownStats>,
+    /// Matches, stored events and errors per pipeline since startup
+    #[serde(default)]
+    pub pipelines: BTreeMap<String, BreakdownStats>,
+    /// Memory, connections and tasks held by the process
+    #[serde(default)]
+    pub resources: ResourceUsage,
+    /// Whether checkpoint processing is paused
+    #[serde(default)]
+    pub paused: bool,
+    /// Latest failed fetch, processing and storage attempts, oldest first
+    #[serde(default)]
+    pub recent_errors: Vec<PipelineError>,
+}
+
+/// A configured filter with what it matched since startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveFilter {
+    /// Filter name, its position for unnamed filters
+    pub name: String,
+    pub filter: EventFilter,
+    pub stats: BreakdownStats,
+}
+
+/// Body of a backfill request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillRequest {
+    /// First checkpoint to index
+    pub from: u64,
+    /// Last checkpoint to index (inclusive)
+    pub to: u64,
+    /// Event filter names to apply (all configured filters if empty)
+    #[serde(default)]
+    pub filters: Vec<String>,
+}
+
+/// State of a backfill started through the control endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillState {
+    Running,
+    Completed,
+    Failed,
+    /// Stopped by an indexer shutdown
+    Interrupted,
+}
+
+/// A backfill started through the control endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillJob {
+    /// Identifier, increasing from 1 within a run of the indexer
+    pub id: u64,
+    pub from: u64,
+    pub to: u64,
+    pub filters: Vec<String>,
+    pub state: BackfillState,
+    /// Checkpoints completed so far
+    pub completed: u64,
+    pub started_at: DateTime<Utc>,
+    /// Why the backfill failed
+    pub error: Option<String>,
+}
+
+type BackfillFactory = dyn Fn(CheckpointRange) -> Backfill + Send + Sync;
+
+/// HTTP control endpoint letting the CLI stop, reload and inspect a running indexer
+///
+/// Besides status, stop and reload it reports the redacted configuration, startup report,
+/// active filters, cursors, queue depths, node endpoint health and recent
+/// errors, and can pause
+/// and resume the pipeline, reload the event filters and start backfills.
+/// With a token or API keys set, every request must carry one as a bearer token.
+pub struct AdminServer {
+    state: AdminState,
+}
+
+#[derive(Clone)]
+struct AdminState {
+    config: Arc<watch::Sender<IndexerConfig>>,
+    config_path: Option<PathBuf>,
+    storage: Option<StorageManager>,
+    sui: Option<SuiClient>,
+    metrics: Arc<Metrics>,
+    shutdown: CancellationToken,
+    started: Instant,
+    token: Option<Arc<str>>,
+    control: PipelineControl,
+    errors: RecentErrors,
+    backfill: Option<Arc<BackfillFactory>>,
+    backfills: Arc<Mutex<Vec<BackfillJob>>>,
+    startup: Option<Arc<StartupReport>>,
+    auth: ApiAuth,
+}
+
+impl AdminServer {
+    /// Create a control endpoint; `stop` requests cancel `shutdown`
+    pub fn new(
+        config: Arc<watch::Sender<IndexerConfig>>,
+        metrics: Arc<Metrics>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            state: AdminState {
+                config,
+                config_path: None,
+                storage: None,
+                sui: None,
+                metrics,
+                shutdown,
+                started: Instant::now(),
+                token: None,
+                control: PipelineControl::default(),
+                errors: RecentErrors::default(),
+                backfill: None,
+                backfills: Arc::default(),
+                startup: None,
+                auth: ApiAuth::default(),
+            },
+        }
+    }
+
+    /// Require this bearer token on every request
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.state.token = token.map(Arc::from);
+        self
+    }
+
+    /// Also accept the API keys of `auth`, limited to their scopes and rates
+    pub fn with_api_auth(mut self, auth: ApiAuth) -> Self {
+        self.state.auth = auth;
+        self
+    }
+
+    /// Pause, resume and report the queues of the pipeline behind `control`
+    pub fn with_control(mut self, control: PipelineControl) -> Self {
+        self.state.control = control;
+        self
+    }
+
+    /// Report the warnings and errors recorded in `errors`
+    pub fn with_recent_errors(mut self, errors: RecentErrors) -> Self {
+        self.state.errors = errors;
+        self
+    }
+
+    /// Accept backfill requests, preparing each with `backfill`
+    pub fn with_backfill(
+        mut self,
+        backfill: impl Fn(CheckpointRange) -> Backfill + Send + Sync + 'static,
+    ) -> Self {
+        self.state.backfill = Some(Arc::new(backfill));
+        self
+    }
+
+    /// Serve `report` as the startup report of this run
+    pub fn with_startup_report(mut self, report: Arc<StartupReport>) -> Self {
+        self.state.startup = Some(report);
+        self
+    }
+
+    /// Re-read this file on `reload` requests
+    pub fn with_config_path(mut self, path: Option<PathBuf>) -> Self {
+        self.state.config_path = path;
+        self
+    }
+
+    /// Report the connection pool of this storage in the status
+    pub fn with_storage(mut self, storage: StorageManager) -> Self {
+        self.state.storage = Some(storage);
+        self
+    }
+
+    /// Report the health of the node endpoints used by `client`
+    pub fn with_sui_client(mut self, client: SuiClient) -> Self {
+        self.state.sui = Some(client);
+        self
+    }
+
+    /// Build the router for the control endpoints
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/admin/status", get(status_handler))
+            .route("/admin/stop", post(stop_handler))
+            .route("/admin/reload", post(reload_handler))
+            .route("/admin/config", get(config_handler))
+            .route("/admin/startup", get(startup_handler))
+            .route("/admin/filters", get(filters_handler))
+            .route("/admin/filters/reload", post(reload_filters_handler))
+            .route("/admin/cursors", get(cursors_handler))
+            .route("/admin/queues", get(queues_handler))
+            .route("/admin/endpoints", get(endpoints_handler))
+            .route("/admin/reconciliation", get(reconciliation_handler))
+            .route("/admin/errors", get(errors_handler))
+            .route("/admin/pause", post(pause_handler))
+            .route("/admin/resume", post(resume_handler))
+            .route(
+                "/admin/backfills",
+                get(backfills_handler).post(start_backfill_handler),
+            )
+            .layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                authenticate,
+            ))
+            .with_state(self.state.clone())
+    }
+
+    /// Serve the control endpoints on `addr` until shutdown
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        if !addr.ip().is_loopback() && self.state.token.is_none() && !self.state.auth.is_enabled() {
+            warn!(%addr, "Control endpoint is not on a loopback address and has no token; anyone who can reach it can stop the indexer");
+        }
+        let listener = TcpListener::bind(addr)
+            .await
+            .wrap_err_with(|| format!("Failed to bind control endpoint on {addr}"))?;
+        info!(%addr, "Serving control endpoint");
+
+        let shutdown = self.state.shutdown.clone();
+        axum::serve(
+            listener,
+            self.router()
+                .into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await?;
+        Ok(())
+    }
+}
+
+async fn status_handler(State(state): State<AdminState>) -> Json<IndexerStatus> {
+    let metrics = &state.metrics;
+    // Gauges stay at zero until the first fetch and commit
+    let known = |value: i64| (value > 0).then_some(value as u64);
+
+    Json(IndexerStatus {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        network: state.config.borrow().network.network.to_string(),
+        uptime_secs: state.started.elapsed().as_secs(),
+        latest_processed_checkpoint: known(metrics.latest_processed_checkpoint.get()),
+        latest_network_checkpoint: known(metrics.latest_network_checkpoint.get()),
+        checkpoints_processed: metrics.checkpoints_processed.get(),
+        events_processed: metrics.events_processed.get(),
+        fetch_errors: metrics.fetch_errors.get(),
+        storage_errors: metrics.storage_errors.get(),
+        filter_matches: metrics.filter_match_counts(),
+        filters: metrics.filter_stats(),
+        pipelines: metrics.pipeline_stats(),
+        resources: ResourceUsage::sample(state.storage.as_ref()),
+        paused: state.control.is_paused(),
+        recent_errors: metrics.error_history.snapshot(),
+    })
+}
+
+/// Operator behind a request: the caller [`authenticate`] let through, at the peer address
+struct Actor(String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let named = parts
+            .extensions
+            .get::<Caller>()
+            .map(|caller| caller.name.as_str());
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+        Ok(Actor(match (named, peer) {
+            (Some(named), Some(peer)) => format!("{named}@{peer}"),
+            (Some(named), None) => named.to_string(),
+            (None, Some(peer)) => peer,
+            (None, None) => "unknown".to_string(),
+        }))
+    }
+}
+
+impl AdminState {
+    /// Record an action taken through the control endpoint, if storage is attached
+    async fn audit(&self, actor: Actor, action: &str, details: serde_json::Value) {
+        if let Some(storage) = &self.storage {
+            let entry = AuditEntry {
+                actor: actor.0,
+                source: SOURCE_ADMIN_API.to_string(),
+                action: action.to_string(),
+                details,
+            };
+            audit::record(storage, entry).await;
+        }
+    }
+}
+
+/// Reject requests without the token or an API key with the scope they need, and log them
+///
+/// `GET` requests need the `read` scope and all others `admin`.
+async fn authenticate(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    auth::authenticate_http(&state.auth, state.token.as_deref(), "admin", request, next).await
+}
+
+async fn config_handler(State(state): State<AdminState>) -> Json<IndexerConfig> {
+    Json(state.config.borrow().redacted())
+}
+
+async fn filters_handler(State(state): State<AdminState>) -> Json<Vec<ActiveFilter>> {
+    let mut stats = state.metrics.filter_stats();
+    let filters = state.config.borrow().events.filters.clone();
+    Json(
+        filters
+            .into_iter()
+            .enumerate()
+            .map(|(index, filter)| {
+                let name = crate::pipeline::filter_label(index, &filter);
+                ActiveFilter {
+                    stats: stats.remove(&name).unwrap_or_default(),
+                    name,
+                    filter,
+                }
+            })
+            .collect(),
+    )
+}
+
+async fn reload_filters_handler(State(state): State<AdminState>, actor: Actor) -> Response {
+    let Some(path) = state.config_path.clone() else {
+        return (
+            StatusCode::CONFLICT,
+            "Indexer was started without a configuration file",
+        )
+            .into_response();
+    };
+
+    info!("Filter reload requested through the control endpoint");
+    match ConfigReloader::new(path, state.config.clone()).reload_filters() {
+        Ok(report) => {
+            crate::reload::log_report(&report);
+            let details = serde_json::to_value(&report).unwrap_or_default();
+            state.audit(actor, "filters_reload", details).await;
+            Json(report).into_response()
+        }
+        Err(e) => (StatusCode::UNPROCESSABLE_ENTITY, format!("{e:#}")).into_response(),
+    }
+}
+
+async fn cursors_handler(State(state): State<AdminState>) -> Response {
+    let Some(storage) = &state.storage else {
+        return (StatusCode::CONFLICT, "No storage attached").into_response();
+    };
+    match storage.list_cursors().await {
+        Ok(cursors) => Json(cursors).into_response(),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, format!("{e:#}")).into_response(),
+    }
+}
+
+async fn queues_handler(State(state): State<AdminState>) -> Json<QueueDepths> {
+    Json(state.control.queue_depths())
+}
+
+async fn endpoints_handler(State(state): State<AdminState>) -> Response {
+    match &state.sui {
+        Some(client) => Json::<Vec<EndpointHealth>>(client.endpoint_health()).into_response(),
+        None => (StatusCode::CONFLICT, "No Sui client attached").into_response(),
+    }
+}
+
+async fn startup_handler(State(state): State<AdminState>) -> Response {
+    match &state.startup {
+        Some(report) => Json(&**report).into_response(),
+        None => (StatusCode::CONFLICT, "No startup report attached").into_response(),
+    }
+}
+
+async fn reconciliation_handler(State(state): State<AdminState>) -> Json<ReconciliationReport> {
+    Json(ReconciliationReport::from_metrics(&state.metrics))
+}
+
+async fn errors_handler(State(state): State<AdminState>) -> Json<Vec<RecentError>> {
+    Json(state.errors.snapshot())
+}
+
+async fn pause_handler(State(state): State<AdminState>, actor: Actor) -> Json<serde_json::Value> {
+    let changed = state.control.pause();
+    if changed {
+        state.audit(actor, "pause", serde_json::json!({})).await;
+    }
+    Json(serde_json::json!({ "paused": true, "changed": changed }))
+}
+
+async fn resume_handler(State(state): State<AdminState>, actor: Actor) -> Json<serde_json::Value> {
+    let changed = state.control.resume();
+    if changed {
+        state.audit(actor, "resume", serde_json::json!({})).await;
+    }
+    Json(serde_json::json!({ "paused": false, "changed": changed }))
+}
+
+async fn backfills_handler(State(state): State<AdminState>) -> Json<Vec<BackfillJob>> {
+    Json(
+        state
+            .backfills
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone(),
+    )
+}
+
+async fn start_backfill_handler(
+    State(state): State<AdminState>,
+    actor: Actor,
+    Json(request): Json<BackfillRequest>,
+) -> Response {
+    let Some(prepare) = state.backfill.clone() else {
+        return (StatusCode::CONFLICT, "Backfills are not available").into_response();
+    };
+    let range = match CheckpointRange::new(request.from, request.to) {
+        Ok(range) => range,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, format!("{e:#}")).into_response(),
+    };
+    let filters = {
+        let config = state.config.borrow();
+        if request.filters.is_empty() {
+            Ok(config.events.filters.clone())
+        } else {
+            config.events.named_filters(&request.filters)
+        }
+    };
+    let filters = match filters {
+        Ok(filters) => filters,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, format!("{e:#}")).into_response(),
+    };
+
+    let backfill = prepare(range).with_filters(filters);
+    let job = {
+        let mut jobs = state
+            .backfills
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let job = BackfillJob {
+            id: jobs.len() as u64 + 1,
+            from: request.from,
+            to: request.to,
+            filters: request.filters,
+            state: BackfillState::Running,
+            completed: 0,
+            started_at: Utc::now(),
+            error: None,
+        };
+        jobs.push(job.clone());
+        job
+    };
+    info!(
+        id = job.id,
+        from = job.from,
+        to = job.to,
+        "Backfill requested through the control endpoint"
+    );
+    let details = serde_json::to_value(&job).unwrap_or_default();
+    state.audit(actor, "backfill_start", details).await;
+    tokio::spawn(run_backfill(
+        backfill,
+        job.id,
+        state.backfills.clone(),
+        state.shutdown.child_token(),
+    ));
+    (StatusCode::ACCEPTED, Json(job)).into_response()
+}
+
+/// Run a requested backfill, recording its progress and outcome in `jobs`
+async fn run_backfill(
+    backfill: Backfill,
+    id: u64,
+    jobs: Arc<Mutex<Vec<BackfillJob>>>,
+    shutdown: CancellationToken,
+) {
+    let update = |update: &dyn Fn(&mut BackfillJob)| {
+        if let Some(job) = jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter_mut()
+            .find(|job| job.id == id)
+        {
+            update(job);
+        }
+    };
+
+    let mut progress = backfill.progress();
+    let run = backfill.run(shutdown.clone());
+    tokio::pin!(run);
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            Ok(()) = progress.changed() => {
+                let completed = progress.borrow_and_update().total_processed;
+                update(&|job| job.completed = completed);
+            }
+        }
+    };
+
+    match result {
+        Ok(stats) => {
+            let state = if shutdown.is_cancelled() {
+                BackfillState::Interrupted
+            } else {
+                BackfillState::Completed
+            };
+            info!(
+                id,
+                completed = stats.total_processed,
+                ?state,
+                "Backfill finished"
+            );
+            update(&|job| {
+                job.completed = stats.total_processed;
+                job.state = state;
+            });
+        }
+        Err(e) => {
+            error!(id, error = %e, "Backfill failed");
+            let error = format!("{e:#}");
+            update(&|job| {
+                job.state = BackfillState::Failed;
+                job.error = Some(error.clone());
+            });
+        }
+    }
+}
+
+async fn stop_handler(State(state): State<AdminState>, actor: Actor) -> StatusCode {
+    info!("Stop requested through the control endpoint");
+    state.audit(actor, "stop", serde_json::json!({})).await;
+    state.shutdown.cancel();
+    StatusCode::ACCEPTED
+}
+
+async fn reload_handler(State(state): State<AdminState>, actor: Actor) -> Response {
+    let Some(path) = state.config_path.clone() else {
+        return (
+            StatusCode::CONFLICT,
+            "Indexer was started without a configuration file",
+        )
+            .into_response();
+    };
+
+    info!("Reload requested through the control endpoint");
+    match ConfigReloader::new(path, state.config.clone()).reload() {
+        Ok(report) => {
+            crate::reload::log_report(&report);
+            let details = serde_json::to_value(&report).unwrap_or_default();
+            state.audit(actor, "config_reload", details).await;
+            Json(report).into_response()
+        }
+        Err(e) => (StatusCode::UNPROCESSABLE_ENTITY, format!("{e:#}")).into_response(),
+    }
+}
+
+/// Client for the control endpoint of a running indexer
+pub struct AdminClient {
+    client: reqwest::Client,
+    base: Url,
+    token: Option<String>,
+}
+
+impl AdminClient {
+    /// Create a client for the control endpoint at `addr`
+    pub fn new(addr: SocketAddr) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()?,
+            base: Url::parse(&format!("http://{addr}/admin/"))?,
+            token: None,
+        })
+    }
+
+    /// Authenticate requests with this bearer token
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Fetch the status of the running indexer
+    pub async fn status(&self) -> Result<IndexerStatus> {
+        let response = self
+            .send(self.client.get(self.base.join("status")?))
+            .await?;
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Fetch the event accounting of the running indexer
+    pub async fn reconciliation(&self) -> Result<ReconciliationReport> {
+        let response = self
+            .send(self.client.get(self.base.join("reconciliation")?))
+            .await?;
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Ask the running indexer to shut down gracefully
+    pub async fn stop(&self) -> Result<()> {
+        let response = self.send(self.client.post(self.base.join("stop")?)).await?;
+        error_for_status(response).await?;
+        Ok(())
+    }
+
+    /// Ask the running indexer to re-read its configuration file
+    pub async fn reload(&self) -> Result<ReloadReport> {
+        let response = self
+            .send(self.client.post(self.base.join("reload")?))
+            .await?;
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Pause checkpoint processing; `false` if it was already paused
+    pub async fn pause(&self) -> Result<bool> {
+        self.toggle("pause").await
+    }
+
+    /// Resume checkpoint processing; `false` if it was not paused
+    pub async fn resume(&self) -> Result<bool> {
+        self.toggle("resume").await
+    }
+
+    async fn toggle(&self, action: &str) -> Result<bool> {
+        let response = self.send(self.client.post(self.base.join(action)?)).await?;
+        let body: serde_json::Value = error_for_status(response).await?.json().await?;
+        Ok(body["changed"].as_bool().unwrap_or(false))
+    }
+
+    async fn send(&self, mut request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .await
+            .wrap_err("Failed to reach the control endpoint")
+            .kind(ErrorKind::Connectivity)
+    }
+}
+
+/// Turn error responses into errors carrying the response body
+async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(IndexerError::ControlEndpoint {
+        status: status.to_string(),
+        body,
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+    use crate::{auth::TOKEN_CALLER, error_history::PipelineStage};
+
+    #[tokio::test]
+    async fn test_status_and_stop() -> Result<()> {
+        let shutdown = CancellationToken::new();
+        let metrics = Arc::new(Metrics::new()?);
+        metrics.latest_processed_checkpoint.set(42);
+        metrics.error_history.record(
+            "default",
+            PipelineStage::Store,
+            Some(43),
+            &eyre::eyre!("pool timed out"),
+        );
+        let config = Arc::new(watch::channel(IndexerConfig::default()).0);
+        let control = PipelineControl::default();
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let server = AdminServer::new(config, metrics, shutdown.clone())
+            .with_token(Some("s3cret".to_string()))
+            .with_control(control.clone())
+            .with_storage(storage.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let serve_shutdown = shutdown.clone();
+        let serving = tokio::spawn(async move {
+            axum::serve(listener, server.router())
+                .with_graceful_shutdown(async move { serve_shutdown.cancelled().await })
+                .await
+        });
+
+        let err = AdminClient::new(addr)?.status().await.unwrap_err();
+        assert!(err.to_string().contains("401"));
+
+        let client = AdminClient::new(addr)?.with_token(Some("s3cret".to_string()));
+        let status = client.status().await?;
+        assert_eq!(status.latest_processed_checkpoint, Some(42));
+        assert_eq!(status.latest_network_checkpoint, None);
+        assert_eq!(status.recent_errors.len(), 1);
+        assert_eq!(status.recent_errors[0].checkpoint, Some(43));
+
+        assert!(client.pause().await?);
+        assert!(control.is_paused());
+        assert!(client.status().await?.paused);
+        assert!(client.resume().await?);
+        assert!(!client.resume().await?);
+
+        // Actions are audited under the authenticated caller, not a name the client claims
+        reqwest::Client::new()
+            .post(format!("http://{addr}/admin/pause"))
+            .bearer_auth("s3cret")
+            .header("x-actor", "mallory")
+            .send()
+            .await?
+            .error_for_status()?;
+        let audited = storage.audit_log(10).await?;
+        let actions: Vec<_> = audited.iter().map(|entry| entry.action.as_str()).collect();
+        assert_eq!(actions, ["pause", "resume", "pause"]);
+        assert!(audited.iter().all(|entry| entry.actor == TOKEN_CALLER));
+        assert!(client.resume().await?);
+
+        let err = client.reload().await.unwrap_err();
+        assert!(err.to_string().contains("409"));
+
+        client.stop().await?;
+        assert!(shutdown.is_cancelled());
+        serving.await??;
+        Ok(())
+    }
+}
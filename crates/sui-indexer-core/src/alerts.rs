@@ -0,0 +1,559 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::Serialize;
+use serde_json::json;
+use sui_indexer_config::{AlertsConfig, PagerDutyConfig};
+use sui_indexer_sinks::{chat::ChatChannel, DeadLetters};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::metrics::Metrics;
+
+/// Timeout for a single notification request
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// PagerDuty Events API v2 endpoint
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Condition watched by an alert rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The indexer is too far behind the network tip
+    Lag,
+    /// Fetch and storage errors exceed the configured rate
+    ErrorRate,
+    /// Sinks drop events faster than the configured rate
+    DeadLetters,
+    /// The watchdog found ingestion stalled
+    Stall,
+    /// A stored event met a rule under `[rules]`
+    Rule,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AlertKind::Lag => "lag",
+            AlertKind::ErrorRate => "error_rate",
+            AlertKind::DeadLetters => "dead_letters",
+            AlertKind::Stall => "stall",
+            AlertKind::Rule => "rule",
+        })
+    }
+}
+
+/// Notification that an alert started firing, is still firing or resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    /// Rule that produced the notification
+    pub kind: AlertKind,
+    /// Identifies the alert across its firing, repeated and resolved notifications
+    pub dedup_key: String,
+    /// Whether the condition is present; `false` marks a resolved alert
+    pub firing: bool,
+    /// Human-readable description of the condition
+    pub message: String,
+    /// When the rule was evaluated
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Alert {
+    /// Single-line text used for chat notifications
+    pub fn text(&self) -> String {
+        let state = if self.firing { "FIRING" } else { "RESOLVED" };
+        format!("[{state}] sui-indexer {}: {}", self.kind, self.message)
+    }
+}
+
+/// Metric values the alert rules are evaluated against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlertSample {
+    /// Checkpoints between the network tip and the last committed one, if known
+    pub lag: Option<u64>,
+    /// Fetch and storage errors per minute since the previous sample, if known
+    pub errors_per_minute: Option<f64>,
+    /// Events dropped by sinks per minute since the previous sample, if known
+    pub dead_letters_per_minute: Option<f64>,
+    /// Seconds since the last committed checkpoint while stalled, 0 while progressing
+    pub stalled_secs: Option<u64>,
+}
+
+/// Evaluates alert rules and tracks which ones are firing
+///
+/// A firing alert is notified once and then at most every repeat interval.
+/// Once resolved, the same rule stays quiet for the cooldown even if its
+/// threshold is crossed again.
+struct AlertRules {
+    config: AlertsConfig,
+    last_notified: HashMap<AlertKind, Instant>,
+    resolved_at: HashMap<AlertKind, Instant>,
+}
+
+impl AlertRules {
+    fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            last_notified: HashMap::new(),
+            resolved_at: HashMap::new(),
+        }
+    }
+
+    /// Evaluate all enabled rules and return the notifications to send
+    fn evaluate(&mut self, sample: AlertSample, now: Instant) -> Vec<Alert> {
+        let lag = &self.config.lag;
+        let error_rate = &self.config.error_rate;
+        let dead_letters = &self.config.dead_letters;
+        let stall = &self.config.stall;
+        let checks = [
+            (
+                AlertKind::Lag,
+                lag.enabled,
+                sample.lag.map(|value| {
+                    (
+                        value > lag.max_checkpoints,
+                        format!(
+                            "{value} checkpoints behind the network (threshold {})",
+                            lag.max_checkpoints
+                        ),
+                    )
+                }),
+            ),
+            (
+                AlertKind::ErrorRate,
+                error_rate.enabled,
+                sample.errors_per_minute.map(|value| {
+                    (
+                        value > error_rate.max_errors_per_minute,
+                        format!(
+                            "{value:.1} errors per minute (threshold {})",
+                            error_rate.max_errors_per_minute
+                        ),
+                    )
+                }),
+            ),
+            (
+                AlertKind::DeadLetters,
+                dead_letters.enabled,
+                sample.dead_letters_per_minute.map(|value| {
+                    (
+                        value > dead_letters.max_events_per_minute,
+                        format!(
+                            "sinks dropped {value:.1} events per minute (threshold {})",
+                            dead_letters.max_events_per_minute
+                        ),
+                    )
+                }),
+            ),
+            (
+                AlertKind::Stall,
+                stall.enabled,
+                sample.stalled_secs.map(|value| {
+                    let message = if value > 0 {
+                        format!("no checkpoint committed for {value}s while the network advanced")
+                    } else {
+                        "checkpoints are being committed again".to_string()
+                    };
+                    (value > 0, message)
+                }),
+            ),
+        ];
+
+        let repeat_interval = Duration::from_secs(self.config.repeat_interval);
+        let cooldown = Duration::from_secs(self.config.cooldown);
+        let mut alerts = Vec::new();
+        for (kind, enabled, state) in checks {
+            let Some((breached, message)) = state.filter(|_| enabled) else {
+                continue;
+            };
+
+            let firing = match (breached, self.last_notified.get(&kind)) {
+                (true, Some(last)) if now.duration_since(*last) < repeat_interval => continue,
+                (true, None)
+                    if self
+                        .resolved_at
+                        .get(&kind)
+                        .is_some_and(|resolved| now.duration_since(*resolved) < cooldown) =>
+                {
+                    continue
+                }
+                (true, _) => {
+                    self.last_notified.insert(kind, now);
+                    true
+                }
+                (false, Some(_)) => {
+                    self.last_notified.remove(&kind);
+                    self.resolved_at.insert(kind, now);
+                    false
+                }
+                (false, None) => continue,
+            };
+
+            alerts.push(Alert {
+                kind,
+                dedup_key: format!("sui-indexer-{kind}"),
+                firing,
+                message,
+                timestamp: Utc::now(),
+            });
+        }
+        alerts
+    }
+}
+
+/// Periodically checks indexer metrics against the alert rules and delivers
+/// notifications to every notifier
+pub struct AlertManager {
+    rules: AlertRules,
+    notifiers: Vec<Box<dyn Notifier>>,
+    metrics: Arc<Metrics>,
+    dead_letters: Option<DeadLetters>,
+}
+
+impl AlertManager {
+    /// Create an alert manager notifying the channels configured in `config`
+    pub fn new(config: AlertsConfig, metrics: Arc<Metrics>) -> Result<Self> {
+        Ok(Self {
+            notifiers: notifiers_from_config(&config)?,
+            rules: AlertRules::new(config),
+            metrics,
+            dead_letters: None,
+        })
+    }
+
+    /// Also deliver notifications to a custom notifier
+    pub fn with_notifier(mut self, notifier: Box<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Watch the events dropped by sinks for the dead-letter rule
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetters) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    /// Check if any alert rule is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.rules.config.any_rule_enabled()
+    }
+
+    /// Evaluate the rules every check interval until `shutdown` is cancelled
+    pub async fn run(mut self, shutdown: CancellationToken) {
+        let check_interval = Duration::from_secs(self.rules.config.check_interval.max(1));
+        let mut interval = tokio::time::interval(check_interval);
+        let mut previous_errors: Option<(u64, Instant)> = None;
+        let mut previous_dead_letters: Option<(u64, Instant)> = None;
+
+        if self.notifiers.is_empty() {
+            warn!("Alert rules are enabled but no notification channels are configured");
+        }
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+
+            let now = Instant::now();
+            let errors = self.metrics.fetch_errors.get() + self.metrics.storage_errors.get();
+            let errors_per_minute = per_minute(previous_errors.replace((errors, now)), errors, now);
+            let dead_letters_per_minute = self.dead_letters.as_ref().and_then(|dead_letters| {
+                let dropped = dead_letters.count();
+                per_minute(previous_dead_letters.replace((dropped, now)), dropped, now)
+            });
+
+            // Both gauges stay at zero until the first fetch and commit
+            let processed = self.metrics.latest_processed_checkpoint.get();
+            let network = self.metrics.latest_network_checkpoint.get();
+            let lag = (processed > 0 && network > 0)
+                .then(|| network.saturating_sub(processed).max(0) as u64);
+
+            let sample = AlertSample {
+                lag,
+                errors_per_minute,
+                dead_letters_per_minute,
+                stalled_secs: Some(self.metrics.ingestion_stalled_seconds.get().max(0) as u64),
+            };
+            for alert in self.rules.evaluate(sample, now) {
+                if alert.firing {
+                    warn!(alert = %alert.kind, message = %alert.message, "Alert firing");
+                } else {
+                    info!(alert = %alert.kind, message = %alert.message, "Alert resolved");
+                }
+                self.notify(&alert).await;
+            }
+        }
+    }
+
+    /// Send an alert to every notifier, logging delivery failures
+    async fn notify(&self, alert: &Alert) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(alert).await {
+                warn!(
+                    notifier = notifier.name(),
+                    alert = %alert.kind,
+                    error = %e,
+                    "Failed to deliver alert"
+                );
+            }
+        }
+    }
+}
+
+/// Growth per minute of a counter since the `previous` sample
+fn per_minute(previous: Option<(u64, Instant)>, count: u64, now: Instant) -> Option<f64> {
+    previous.and_then(|(previous, at)| {
+        let minutes = now.duration_since(at).as_secs_f64() / 60.0;
+        (minutes > 0.0).then(|| count.saturating_sub(previous) as f64 / minutes)
+    })
+}
+
+/// Channel an alert notification is delivered through
+///
+/// Implement this to route alerts somewhere other than the built-in
+/// channels and register it with [`AlertManager::with_notifier`].
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Name used when logging delivery failures
+    fn name(&self) -> &str;
+
+    /// Deliver a firing or resolved alert
+    async fn notify(&self, alert: &Alert) -> Result<()>;
+}
+
+/// Create a notifier for every channel configured in `config`
+pub fn notifiers_from_config(config: &AlertsConfig) -> Result<Vec<Box<dyn Notifier>>> {
+    let client = reqwest::Client::builder().timeout(NOTIFY_TIMEOUT).build()?;
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    for url in &config.webhook_urls {
+        notifiers.push(Box::new(WebhookNotifier {
+            client: client.clone(),
+            url: url.clone(),
+        }));
+    }
+    if let Some(slack) = &config.slack {
+        notifiers.push(Box::new(ChatNotifier {
+            channel: ChatChannel::slack(client.clone(), slack)?,
+        }));
+    }
+    if let Some(telegram) = &config.telegram {
+        notifiers.push(Box::new(ChatNotifier {
+            channel: ChatChannel::telegram(client.clone(), telegram)?,
+        }));
+    }
+    if let Some(pagerduty) = &config.pagerduty {
+        notifiers.push(Box::new(PagerDutyNotifier {
+            client,
+            config: pagerduty.clone(),
+        }));
+    }
+    Ok(notifiers)
+}
+
+/// Posts the alert as a JSON payload
+struct WebhookNotifier {
+    client: reqwest::Client,
+    url: Url,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        self.url.as_str()
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Posts the alert text to a Slack channel or Telegram chat
+struct ChatNotifier {
+    channel: ChatChannel,
+}
+
+#[async_trait]
+impl Notifier for ChatNotifier {
+    fn name(&self) -> &str {
+        self.channel.name()
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.channel.post(&alert.text()).await
+    }
+}
+
+/// Triggers and resolves PagerDuty incidents keyed by the alert's dedup key
+struct PagerDutyNotifier {
+    client: reqwest::Client,
+    config: PagerDutyConfig,
+}
+
+impl PagerDutyNotifier {
+    fn event(&self, alert: &Alert) -> serde_json::Value {
+        if !alert.firing {
+            return json!({
+                "routing_key": self.config.routing_key,
+                "event_action": "resolve",
+                "dedup_key": alert.dedup_key,
+            });
+        }
+        json!({
+            "routing_key": self.config.routing_key,
+            "event_action": "trigger",
+            "dedup_key": alert.dedup_key,
+            "payload": {
+                "summary": alert.text(),
+                "source": "sui-indexer",
+                "severity": self.config.severity,
+                "timestamp": alert.timestamp.to_rfc3339(),
+                "class": alert.kind,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for PagerDutyNotifier {
+    fn name(&self) -> &str {
+        "pagerduty"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(PAGERDUTY_EVENTS_URL)
+            .json(&self.event(alert))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> AlertRules {
+        let mut config = AlertsConfig {
+            repeat_interval: 60,
+            cooldown: 120,
+            ..AlertsConfig::default()
+        };
+        config.lag.enabled = true;
+        config.lag.max_checkpoints = 100;
+        AlertRules::new(config)
+    }
+
+    fn lag(value: u64) -> AlertSample {
+        AlertSample {
+            lag: Some(value),
+            errors_per_minute: Some(1000.0),
+            dead_letters_per_minute: Some(1000.0),
+            stalled_secs: Some(600),
+        }
+    }
+
+    #[test]
+    fn test_lag_alert_fires_repeats_and_resolves() {
+        let mut rules = rules();
+        let start = Instant::now();
+
+        assert!(rules.evaluate(lag(10), start).is_empty());
+
+        let fired = rules.evaluate(lag(500), start);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].kind, AlertKind::Lag);
+        assert!(fired[0].firing);
+
+        assert!(rules
+            .evaluate(lag(500), start + Duration::from_secs(30))
+            .is_empty());
+        assert_eq!(
+            rules
+                .evaluate(lag(500), start + Duration::from_secs(61))
+                .len(),
+            1
+        );
+
+        let resolved = rules.evaluate(lag(10), start + Duration::from_secs(90));
+        assert_eq!(resolved.len(), 1);
+        assert!(!resolved[0].firing);
+        assert!(resolved[0].text().starts_with("[RESOLVED]"));
+    }
+
+    #[test]
+    fn test_unknown_values_are_skipped() {
+        let mut rules = rules();
+        let start = Instant::now();
+
+        assert_eq!(rules.evaluate(lag(500), start).len(), 1);
+        // An unknown lag neither repeats nor resolves the alert
+        assert!(rules
+            .evaluate(AlertSample::default(), start + Duration::from_secs(120))
+            .is_empty());
+        assert!(rules.last_notified.contains_key(&AlertKind::Lag));
+    }
+
+    #[test]
+    fn test_resolved_alert_waits_for_cooldown() {
+        let mut rules = rules();
+        let start = Instant::now();
+
+        assert_eq!(rules.evaluate(lag(500), start).len(), 1);
+        let resolved = rules.evaluate(lag(10), start + Duration::from_secs(30));
+        assert_eq!(resolved[0].dedup_key, "sui-indexer-lag");
+
+        // Crossing the threshold again within the cooldown stays quiet
+        assert!(rules
+            .evaluate(lag(500), start + Duration::from_secs(60))
+            .is_empty());
+        let fired = rules.evaluate(lag(500), start + Duration::from_secs(151));
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].firing);
+    }
+
+    #[test]
+    fn test_pagerduty_event_resolves_by_dedup_key() {
+        let notifier = PagerDutyNotifier {
+            client: reqwest::Client::new(),
+            config: PagerDutyConfig {
+                routing_key: "key".to_string(),
+                severity: Default::default(),
+            },
+        };
+        let mut alert = Alert {
+            kind: AlertKind::DeadLetters,
+            dedup_key: "sui-indexer-dead_letters".to_string(),
+            firing: true,
+            message: "sinks dropped 5.0 events per minute (threshold 0)".to_string(),
+            timestamp: Utc::now(),
+        };
+
+        let trigger = notifier.event(&alert);
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["payload"]["severity"], "error");
+
+        alert.firing = false;
+        let resolve = notifier.event(&alert);
+        assert_eq!(resolve["event_action"], "resolve");
+        assert_eq!(resolve["dedup_key"], trigger["dedup_key"]);
+    }
+}
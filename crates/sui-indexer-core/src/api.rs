@@ -0,0 +1,305 @@
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, Request, State,
+    },
+    http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use eyre::{Result, WrapErr};
+use futures::stream;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_storage::StorageManager;
+use tokio::{net::TcpListener, sync::watch};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    auth::{self, ApiAuth},
+    live::{EventMatcher, FeedError, LiveEvents, LiveFeed, LiveMessage, Subscription},
+};
+
+/// HTTP API streaming matched events to clients as their checkpoints are committed
+///
+/// Events are streamed over a WebSocket on `/events/live` and as server-sent
+/// events on `/events/stream`, replaying stored ones first when asked to.
+/// With a token or API keys set, every request must carry one as a bearer
+/// token; the streams need the `read` scope.
+pub struct ApiServer {
+    state: ApiState,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    config: Arc<watch::Sender<IndexerConfig>>,
+    storage: StorageManager,
+    live: LiveEvents,
+    shutdown: CancellationToken,
+    token: Option<Arc<str>>,
+    auth: ApiAuth,
+}
+
+impl ApiServer {
+    /// Stream the events published on `live`, replaying `storage`, until `shutdown` is cancelled
+    pub fn new(
+        config: Arc<watch::Sender<IndexerConfig>>,
+        storage: StorageManager,
+        live: LiveEvents,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            state: ApiState {
+                config,
+                storage,
+                live,
+                shutdown,
+                token: None,
+                auth: ApiAuth::default(),
+            },
+        }
+    }
+
+    /// Require this bearer token on every request
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.state.token = token.map(Arc::from);
+        self
+    }
+
+    /// Also accept the API keys of `auth`, limited to their scopes and rates
+    pub fn with_api_auth(mut self, auth: ApiAuth) -> Self {
+        self.state.auth = auth;
+        self
+    }
+
+    /// Build the router for the API endpoints
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/events/live", get(live_events_handler))
+            .route("/events/stream", get(event_stream_handler))
+            .layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                authenticate,
+            ))
+            .with_state(self.state.clone())
+    }
+
+    /// Serve the API on `addr` until shutdown
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        if !addr.ip().is_loopback() && self.state.token.is_none() && !self.state.auth.is_enabled() {
+            warn!(%addr, "HTTP API is not on a loopback address and has no token; anyone who can reach it can read indexed data");
+        }
+        let listener = TcpListener::bind(addr)
+            .await
+            .wrap_err_with(|| format!("Failed to bind HTTP API on {addr}"))?;
+        info!(%addr, "Serving HTTP API");
+
+        let shutdown = self.state.shutdown.clone();
+        axum::serve(listener, self.router())
+            .with_graceful_shutdown(async move { shutdown.cancelled().await })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Reject requests without the token or an API key with the `read` scope, and log them
+async fn authenticate(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    auth::authenticate_http(&state.auth, state.token.as_deref(), "api", request, next).await
+}
+
+async fn live_events_handler(
+    State(state): State<ApiState>,
+    Query(subscription): Query<Subscription>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let feed = match open_feed(&state, &subscription).await {
+        Ok(feed) => feed,
+        Err(response) => return response,
+    };
+    let shutdown = state.shutdown.clone();
+    upgrade.on_upgrade(move |socket| stream_live_events(socket, feed, shutdown))
+}
+
+/// Same feed as [`live_events_handler`], as server-sent events
+///
+/// Every message is an event named after its `type`. Batches and the
+/// `caught_up` marker carry their checkpoint as the event id, so a
+/// reconnecting `EventSource` resumes after it through `Last-Event-ID`
+/// unless `from_checkpoint` is given.
+async fn event_stream_handler(
+    State(state): State<ApiState>,
+    Query(mut subscription): Query<Subscription>,
+    headers: HeaderMap,
+) -> Response {
+    if subscription.from_checkpoint.is_none() {
+        subscription.from_checkpoint = headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(|checkpoint| checkpoint + 1);
+    }
+    let feed = match open_feed(&state, &subscription).await {
+        Ok(feed) => feed,
+        Err(response) => return response,
+    };
+    let shutdown = state.shutdown.clone();
+    let events = stream::unfold(Some(feed), move |feed| {
+        let shutdown = shutdown.clone();
+        async move {
+            let mut feed = feed?;
+            let next = tokio::select! {
+                next = feed.next() => next,
+                () = shutdown.cancelled() => return None,
+            };
+            let message = feed_message(next, feed.cursor())?;
+            let ended = matches!(message, LiveMessage::Error { .. });
+            let event = sse_event(&message)?;
+            Some((Ok::<_, Infallible>(event), (!ended).then_some(feed)))
+        }
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Feed of the events `subscription` asks for, or the response refusing it
+async fn open_feed(
+    state: &ApiState,
+    subscription: &Subscription,
+) -> std::result::Result<LiveFeed, Response> {
+    let matcher = EventMatcher::new(subscription, &state.config.borrow())
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")).into_response())?;
+    LiveFeed::new(
+        &state.live,
+        matcher,
+        Some(state.storage.clone()),
+        subscription.from_checkpoint,
+    )
+    .await
+    .map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")).into_response())
+}
+
+/// Message for what a feed handed out, turning its failure into an `error` message
+fn feed_message(
+    next: std::result::Result<Option<LiveMessage>, FeedError>,
+    cursor: Option<u64>,
+) -> Option<LiveMessage> {
+    let message = match next {
+        Ok(message) => return message,
+        Err(FeedError::Lagged) => "Subscriber fell behind".to_string(),
+        Err(FeedError::Replay(e)) => format!("Failed to replay stored events: {e:#}"),
+    };
+    Some(LiveMessage::Error {
+        message,
+        from_checkpoint: cursor,
+    })
+}
+
+/// Server-sent event of `message`, with the checkpoint to resume after as its id
+fn sse_event(message: &LiveMessage) -> Option<Event> {
+    let (name, id) = match message {
+        LiveMessage::Events(batch) => ("events", Some(batch.checkpoint)),
+        LiveMessage::CaughtUp { checkpoint } => ("caught_up", *checkpoint),
+        LiveMessage::Error { .. } => ("error", None),
+        LiveMessage::Paused { .. } => ("paused", None),
+        LiveMessage::Resumed => ("resumed", None),
+    };
+    let event = Event::default().event(name).json_data(message).ok()?;
+    Some(match id {
+        Some(id) => event.id(id.to_string()),
+        None => event,
+    })
+}
+
+/// Send the batches of `feed` until the subscriber leaves, lags or the indexer stops
+async fn stream_live_events(
+    mut socket: WebSocket,
+    mut feed: LiveFeed,
+    shutdown: CancellationToken,
+) {
+    loop {
+        // Every branch is cancel safe: the feed only advances once a batch is handed out
+        let next = tokio::select! {
+            next = feed.next() => next,
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            () = shutdown.cancelled() => break,
+        };
+        let Some(message) = feed_message(next, feed.cursor()) else {
+            break;
+        };
+        let ended = matches!(message, LiveMessage::Error { .. });
+        let Ok(text) = serde_json::to_string(&message) else {
+            break;
+        };
+        if socket.send(Message::Text(text)).await.is_err() || ended {
+            break;
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_stream_needs_a_token() -> Result<()> {
+        let shutdown = CancellationToken::new();
+        let config = Arc::new(watch::channel(IndexerConfig::default()).0);
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let live = LiveEvents::default();
+        let server = ApiServer::new(config, storage, live.clone(), shutdown.clone())
+            .with_token(Some("s3cret".to_string()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let serve_shutdown = shutdown.clone();
+        let serving = tokio::spawn(async move {
+            axum::serve(listener, server.router())
+                .with_graceful_shutdown(async move { serve_shutdown.cancelled().await })
+                .await
+        });
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}/events/stream?module=pool");
+        let response = client.get(&url).send().await?;
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let mut response = client
+            .get(&url)
+            .bearer_auth("s3cret")
+            .send()
+            .await?
+            .error_for_status()?;
+        while live.subscribers() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let events = DefaultEventProcessor::new()
+            .process_events(vec![TestEvent::new("0x2::pool::Event").tx("tx1").build()])
+            .await?;
+        live.publish(10, &events);
+        let chunk = response.chunk().await?.unwrap_or_default();
+        let message = String::from_utf8_lossy(&chunk);
+        assert!(message.contains("event: events"), "{message}");
+        assert!(message.contains("id: 10"), "{message}");
+
+        drop(response);
+        shutdown.cancel();
+        serving.await??;
+        Ok(())
+    }
+}
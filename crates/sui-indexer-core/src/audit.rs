@@ -0,0 +1,85 @@
+use sui_indexer_storage::{AuditEntry, StorageManager};
+use tracing::{info, warn};
+
+/// Source of actions taken through CLI commands
+pub const SOURCE_CLI: &str = "cli";
+/// Source of actions taken through the control endpoint
+pub const SOURCE_ADMIN_API: &str = "admin_api";
+/// Source of decisions the indexer takes on its own, like skipping pruned checkpoints
+pub const SOURCE_INDEXER: &str = "indexer";
+
+/// OS user running this process, as recorded for CLI actions
+pub fn current_user() -> String {
+    ["USER", "USERNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Audit entry for an action taken by the current user through the CLI
+pub fn cli_entry(action: &str, details: serde_json::Value) -> AuditEntry {
+    AuditEntry {
+        actor: current_user(),
+        source: SOURCE_CLI.to_string(),
+        action: action.to_string(),
+        details,
+    }
+}
+
+/// Audit entry for a decision the indexer took on its own, under the user running it
+pub fn indexer_entry(action: &str, details: serde_json::Value) -> AuditEntry {
+    AuditEntry {
+        actor: current_user(),
+        source: SOURCE_INDEXER.to_string(),
+        action: action.to_string(),
+        details,
+    }
+}
+
+/// Append `entry` to the audit log
+///
+/// The action has already happened when it is recorded, so a failed write
+/// is logged rather than returned.
+pub async fn record(storage: &StorageManager, entry: AuditEntry) {
+    info!(
+        actor = %entry.actor,
+        source = %entry.source,
+        action = %entry.action,
+        details = %entry.details,
+        "Operator action"
+    );
+    if let Err(e) = storage.record_audit(&entry).await {
+        warn!(action = %entry.action, error = %e, "Failed to write audit log entry");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_entries_are_recorded_under_their_source() -> eyre::Result<()> {
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        record(
+            &storage,
+            cli_entry("cursor_set", serde_json::json!({ "checkpoint": 7 })),
+        )
+        .await;
+        record(
+            &storage,
+            indexer_entry("skip_pruned", serde_json::json!({ "to": 9 })),
+        )
+        .await;
+
+        let log = storage.audit_log(10).await?;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].source, SOURCE_INDEXER);
+        assert_eq!(log[0].action, "skip_pruned");
+        assert_eq!(log[1].source, SOURCE_CLI);
+        assert_eq!(log[1].details["checkpoint"], 7);
+        assert!(log.iter().all(|entry| entry.actor == current_user()));
+        Ok(())
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::Request,
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use eyre::{Result, WrapErr};
+use sha2::{Digest, Sha256};
+use sui_indexer_config::{ApiScope, AuthConfig};
+use sui_indexer_storage::StorageManager;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use tonic::{metadata::MetadataMap, Code, Status};
+use tracing::{info, warn};
+
+/// Caller name of requests presenting the endpoint's own token
+pub const TOKEN_CALLER: &str = "token";
+
+/// Caller name of requests to an endpoint without authentication
+pub const ANONYMOUS_CALLER: &str = "anonymous";
+
+/// Who made an API request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    /// Key name, or [`TOKEN_CALLER`] or [`ANONYMOUS_CALLER`]
+    pub name: String,
+    pub scope: ApiScope,
+}
+
+/// Why an API request was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum Denied {
+    /// No key, or one that is unknown or revoked
+    Unauthenticated,
+    /// The key of `caller` lacks the scope the request needs
+    Forbidden { caller: String, needs: ApiScope },
+    /// The key of `caller` exceeded its rate limit
+    RateLimited {
+        caller: String,
+        retry_after: Duration,
+    },
+}
+
+impl Denied {
+    /// Name of the key that was refused, if it was valid
+    pub fn caller(&self) -> Option<&str> {
+        match self {
+            Self::Unauthenticated => None,
+            Self::Forbidden { caller, .. } | Self::RateLimited { caller, .. } => Some(caller),
+        }
+    }
+
+    /// Whole seconds to wait before trying again, if the key was rate limited
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited { retry_after, .. } => Some(retry_after.as_secs_f64().ceil() as u64),
+            _ => None,
+        }
+    }
+
+    /// gRPC status refusing the call, telling rate limited callers when to retry
+    pub(crate) fn to_status(&self) -> Status {
+        let message = self.to_string();
+        match self {
+            Self::Unauthenticated => Status::unauthenticated(message),
+            Self::Forbidden { .. } => Status::permission_denied(message),
+            Self::RateLimited { .. } => {
+                let mut metadata = MetadataMap::new();
+                if let Some(secs) = self.retry_after_secs() {
+                    metadata.insert("retry-after", secs.into());
+                }
+                Status::with_metadata(Code::ResourceExhausted, message, metadata)
+            }
+        }
+    }
+
+    /// HTTP response refusing the request, telling rate limited callers when to retry
+    pub(crate) fn to_response(&self) -> Response {
+        match self {
+            Self::Unauthenticated => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                self.to_string(),
+            )
+                .into_response(),
+            Self::Forbidden { .. } => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
+            Self::RateLimited { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(
+                    header::RETRY_AFTER,
+                    self.retry_after_secs().unwrap_or_default().to_string(),
+                )],
+                self.to_string(),
+            )
+                .into_response(),
+        }
+    }
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthenticated => f.write_str("Missing or invalid API key"),
+            Self::Forbidden { caller, needs } => {
+                write!(f, "API key `{caller}` lacks the `{needs}` scope")
+            }
+            Self::RateLimited { caller, .. } => {
+                write!(f, "API key `{caller}` exceeded its rate limit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Denied {}
+
+struct ApiKey {
+    name: String,
+    scope: ApiScope,
+    /// Requests per second
+    rate_limit: Option<f64>,
+}
+
+/// Requests a key may still make, refilled continuously at its rate limit
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Requests a key limited to `rate` per second may make at once
+    fn capacity(rate: f64) -> f64 {
+        rate.ceil().max(1.0)
+    }
+
+    /// Spend one request, or return how long until one is available
+    fn take(&mut self, rate: f64, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(Self::capacity(rate));
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+/// API keys from `auth.keys` and the `api_keys` table, with their scopes and rate limits
+///
+/// Shared by the control endpoint and the gRPC API, so a key's rate limit
+/// covers its requests to both.
+#[derive(Clone, Default)]
+pub struct ApiAuth {
+    enabled: bool,
+    /// Configured keys by hash
+    configured: Arc<HashMap<String, ApiKey>>,
+    /// Active stored keys by hash, replaced on every refresh
+    stored: Arc<RwLock<HashMap<String, ApiKey>>>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl ApiAuth {
+    /// Resolve the keys configured in `config`
+    pub fn from_config(config: &AuthConfig) -> Result<Self> {
+        let mut configured = HashMap::new();
+        for (name, key) in &config.keys {
+            let value = key
+                .key
+                .resolve()
+                .wrap_err_with(|| format!("Failed to read API key {name}"))?;
+            configured.insert(
+                hash_key(&value),
+                ApiKey {
+                    name: name.clone(),
+                    scope: key.scope,
+                    rate_limit: key.rate_limit,
+                },
+            );
+        }
+        Ok(Self {
+            enabled: config.is_enabled(),
+            configured: Arc::new(configured),
+            ..Self::default()
+        })
+    }
+
+    /// Whether requests must present a key or an endpoint token
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Replace the stored keys with the active ones in `storage`
+    pub async fn refresh(&self, storage: &StorageManager) -> Result<()> {
+        let mut keys = HashMap::new();
+        for record in storage.api_keys().await? {
+            if record.revoked_at.is_some() {
+                continue;
+            }
+            match record.scope.parse() {
+                Ok(scope) => {
+                    keys.insert(
+                        record.key_hash,
+                        ApiKey {
+                            name: record.name,
+                            scope,
+                            rate_limit: record.rate_limit,
+                        },
+                    );
+                }
+                Err(e) => warn!(key = %record.name, error = %e, "Ignoring stored API key"),
+            }
+        }
+        info!(keys = keys.len(), "Loaded stored API keys");
+        *self.stored.write().unwrap_or_else(PoisonError::into_inner) = keys;
+        Ok(())
+    }
+
+    /// Reload the stored keys every `interval` until `shutdown` is cancelled
+    pub async fn run(
+        self,
+        storage: StorageManager,
+        interval: Duration,
+        shutdown: CancellationToken,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        // The first tick completes at once; the keys were loaded on startup
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                () = shutdown.cancelled() => return,
+            }
+            if let Err(e) = self.refresh(&storage).await {
+                warn!(error = %e, "Failed to reload stored API keys");
+            }
+        }
+    }
+
+    /// Check a request presenting `presented` that needs `scope`
+    ///
+    /// `token` is the endpoint's own token, which grants every scope without a
+    /// rate limit. Without keys or a token every request is let through.
+    pub fn authorize(
+        &self,
+        presented: Option<&str>,
+        token: Option<&str>,
+        scope: ApiScope,
+    ) -> Result<Caller, Denied> {
+        self.authorize_at(presented, token, scope, Instant::now())
+    }
+
+    fn authorize_at(
+        &self,
+        presented: Option<&str>,
+        token: Option<&str>,
+        scope: ApiScope,
+        now: Instant,
+    ) -> Result<Caller, Denied> {
+        if !self.enabled && token.is_none() {
+            return Ok(Caller {
+                name: ANONYMOUS_CALLER.to_string(),
+                scope: ApiScope::Admin,
+            });
+        }
+        let presented = presented.ok_or(Denied::Unauthenticated)?;
+        if token.is_some_and(|token| constant_time_eq(presented, token)) {
+            return Ok(Caller {
+                name: TOKEN_CALLER.to_string(),
+                scope: ApiScope::Admin,
+            });
+        }
+
+        // Keys are looked up by hash, so lookups reveal nothing about key values
+        let hash = hash_key(presented);
+        let stored = self.stored.read().unwrap_or_else(PoisonError::into_inner);
+        let key = self
+            .configured
+            .get(&hash)
+            .or_else(|| stored.get(&hash))
+            .ok_or(Denied::Unauthenticated)?;
+        if key.scope < scope {
+            return Err(Denied::Forbidden {
+                caller: key.name.clone(),
+                needs: scope,
+            });
+        }
+        if let Some(rate) = key.rate_limit {
+            let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+            let bucket = buckets.entry(key.name.clone()).or_insert(Bucket {
+                tokens: Bucket::capacity(rate),
+                updated: now,
+            });
+            bucket
+                .take(rate, now)
+                .map_err(|retry_after| Denied::RateLimited {
+                    caller: key.name.clone(),
+                    retry_after,
+                })?;
+        }
+        Ok(Caller {
+            name: key.name.clone(),
+            scope: key.scope,
+        })
+    }
+}
+
+/// Hex-encoded SHA-256 of an API key, as stored in the `api_keys` table
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// A new random API key
+pub fn generate_key() -> String {
+    format!(
+        "sk_{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Log a finished API request; `request` names the route or method called
+pub(crate) fn log_request(
+    surface: &str,
+    caller: Option<&str>,
+    request: &str,
+    status: impl fmt::Display,
+    elapsed: Duration,
+) {
+    info!(
+        surface,
+        caller = caller.unwrap_or("-"),
+        request,
+        %status,
+        duration_ms = elapsed.as_millis() as u64,
+        "API request"
+    );
+}
+
+/// Pass on HTTP requests carrying `token` or an API key with the scope they need, and log them
+///
+/// `GET` requests need the `read` scope and all others `admin`. The
+/// [`Caller`] let through is added to the request extensions.
+pub(crate) async fn authenticate_http(
+    auth: &ApiAuth,
+    token: Option<&str>,
+    surface: &str,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let route = format!("{} {}", request.method(), request.uri().path());
+    let scope = if request.method() == Method::GET {
+        ApiScope::Read
+    } else {
+        ApiScope::Admin
+    };
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let (caller, response) = match auth.authorize(presented, token, scope) {
+        Ok(caller) => {
+            let name = caller.name.clone();
+            request.extensions_mut().insert(caller);
+            (Some(name), next.run(request).await)
+        }
+        Err(denied) => (denied.caller().map(String::from), denied.to_response()),
+    };
+    log_request(
+        surface,
+        caller.as_deref(),
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Compare secrets without returning early on the first differing byte
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use sui_indexer_config::{ApiKeyConfig, SecretRef};
+
+    use super::*;
+
+    #[test]
+    fn test_scopes_and_rate_limits() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut keys = BTreeMap::new();
+        for (name, scope, rate_limit) in [
+            ("dashboard", ApiScope::Read, Some(2.0)),
+            ("ops", ApiScope::Admin, None),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("{name}-key\n"))?;
+            keys.insert(
+                name.to_string(),
+                ApiKeyConfig {
+                    key: SecretRef::File(path),
+                    scope,
+                    rate_limit,
+                },
+            );
+        }
+        let auth = ApiAuth::from_config(&AuthConfig {
+            keys,
+            ..AuthConfig::default()
+        })?;
+
+        let now = Instant::now();
+        let read = |key, now| auth.authorize_at(key, None, ApiScope::Read, now);
+        assert_eq!(read(Some("dashboard-key"), now)?.name, "dashboard");
+        assert_eq!(read(Some("dashboard-key"), now)?.name, "dashboard");
+        let Err(Denied::RateLimited { retry_after, .. }) = read(Some("dashboard-key"), now) else {
+            panic!("expected the rate limit to apply");
+        };
+        assert_eq!(retry_after, Duration::from_millis(500));
+        assert!(read(Some("dashboard-key"), now + Duration::from_millis(500)).is_ok());
+
+        assert_eq!(
+            auth.authorize_at(Some("dashboard-key"), None, ApiScope::Admin, now),
+            Err(Denied::Forbidden {
+                caller: "dashboard".to_string(),
+                needs: ApiScope::Admin,
+            })
+        );
+        assert_eq!(
+            auth.authorize_at(Some("ops-key"), None, ApiScope::Admin, now)?
+                .scope,
+            ApiScope::Admin
+        );
+        assert_eq!(read(Some("unknown"), now), Err(Denied::Unauthenticated));
+        assert_eq!(read(None, now), Err(Denied::Unauthenticated));
+        assert_eq!(
+            auth.authorize_at(Some("secret"), Some("secret"), ApiScope::Admin, now)?
+                .name,
+            TOKEN_CALLER
+        );
+
+        let open = ApiAuth::default();
+        assert_eq!(
+            open.authorize(None, None, ApiScope::Admin)?.name,
+            ANONYMOUS_CALLER
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,377 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use sui_indexer_config::{AutotuneConfig, RuntimeConfig};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::metrics::Metrics;
+
+/// Storage writes and node fetches one pipeline may have in flight
+///
+/// Without tuning the limits stay at the configured `writer_concurrency`
+/// and `fetch_concurrency`. With `runtime.autotune` enabled, [`Concurrency::run`]
+/// adjusts them every interval from the storage write latencies and node
+/// request outcomes recorded since the last adjustment: a limit grows by one
+/// while its signal stays healthy and halves when it does not.
+#[derive(Clone)]
+pub struct Concurrency {
+    pipeline: &'static str,
+    writers: Arc<Limit>,
+    fetches: Arc<Limit>,
+    tuning: Option<Arc<Tuning>>,
+    metrics: Arc<Metrics>,
+}
+
+struct Tuning {
+    config: AutotuneConfig,
+    window: Mutex<Window>,
+}
+
+impl Tuning {
+    fn window(&self) -> MutexGuard<'_, Window> {
+        self.window.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Observations since the last adjustment
+#[derive(Default)]
+struct Window {
+    write_latencies: Vec<Duration>,
+    requests: u64,
+    failed_requests: u64,
+}
+
+impl Concurrency {
+    /// Limits of `pipeline` starting at the configured concurrency
+    pub fn new(pipeline: &'static str, runtime: &RuntimeConfig, metrics: Arc<Metrics>) -> Self {
+        let tuning = runtime.autotune.enabled.then(|| {
+            Arc::new(Tuning {
+                config: runtime.autotune.clone(),
+                window: Mutex::new(Window::default()),
+            })
+        });
+        let concurrency = Self {
+            pipeline,
+            writers: Limit::new(runtime.writer_concurrency),
+            fetches: Limit::new(runtime.fetch_concurrency),
+            tuning,
+            metrics,
+        };
+        concurrency.publish();
+        concurrency
+    }
+
+    /// Current limit of concurrent storage writes per checkpoint
+    pub fn writers(&self) -> usize {
+        self.writers.get()
+    }
+
+    /// Current limit of checkpoints fetched at once
+    pub fn fetches(&self) -> usize {
+        self.fetches.get()
+    }
+
+    /// Most storage writes the limit can grow to, for sizing write streams
+    pub(crate) fn writer_bound(&self) -> usize {
+        match &self.tuning {
+            Some(tuning) => tuning.config.max_writer_concurrency.max(1),
+            None => self.writers(),
+        }
+    }
+
+    /// Most fetches the limit can grow to, for sizing fetch streams
+    pub(crate) fn fetch_bound(&self) -> usize {
+        match &self.tuning {
+            Some(tuning) => tuning.config.max_fetch_concurrency.max(1),
+            None => self.fetches(),
+        }
+    }
+
+    /// Wait for room for one more storage write
+    pub(crate) async fn write(&self) -> Slot {
+        self.writers.acquire().await
+    }
+
+    /// Wait for room for one more fetch
+    pub(crate) async fn fetch(&self) -> Slot {
+        self.fetches.acquire().await
+    }
+
+    /// Note how long a storage write took
+    pub(crate) fn record_write(&self, latency: Duration) {
+        if let Some(tuning) = &self.tuning {
+            tuning.window().write_latencies.push(latency);
+        }
+    }
+
+    /// Note whether a node request succeeded
+    pub(crate) fn record_request(&self, succeeded: bool) {
+        if let Some(tuning) = &self.tuning {
+            let mut window = tuning.window();
+            window.requests += 1;
+            window.failed_requests += u64::from(!succeeded);
+        }
+    }
+
+    /// Adjust the limits every interval until `shutdown` is cancelled, if tuning is enabled
+    pub(crate) async fn run(self, shutdown: CancellationToken) {
+        let Some(tuning) = self.tuning.clone() else {
+            return;
+        };
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(tuning.config.interval.max(1)));
+        // The first tick completes at once, before anything was observed
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => self.adjust(),
+            }
+        }
+    }
+
+    /// Take one additive-increase, multiplicative-decrease step from the observations so far
+    ///
+    /// A limit with nothing observed since the last step stays where it is.
+    pub(crate) fn adjust(&self) {
+        let Some(tuning) = &self.tuning else {
+            return;
+        };
+        let window = std::mem::take(&mut *tuning.window());
+        let config = &tuning.config;
+
+        if let Some(p95) = percentile(window.write_latencies, 0.95) {
+            let healthy = p95 <= Duration::from_millis(config.store_p95_ms);
+            let writers = step(self.writers(), healthy, config.max_writer_concurrency);
+            if writers != self.writers() {
+                debug!(
+                    pipeline = self.pipeline,
+                    p95_ms = p95.as_millis() as u64,
+                    writers,
+                    "Adjusted writer concurrency"
+                );
+                self.writers.set(writers);
+            }
+        }
+        if window.requests > 0 {
+            let error_rate = window.failed_requests as f64 / window.requests as f64;
+            let healthy = error_rate <= config.rpc_error_rate;
+            let fetches = step(self.fetches(), healthy, config.max_fetch_concurrency);
+            if fetches != self.fetches() {
+                debug!(
+                    pipeline = self.pipeline,
+                    error_rate, fetches, "Adjusted fetch concurrency"
+                );
+                self.fetches.set(fetches);
+            }
+        }
+        self.publish();
+    }
+
+    fn publish(&self) {
+        self.metrics
+            .pipeline_writer_concurrency
+            .with_label_values(&[self.pipeline])
+            .set(self.writers() as i64);
+        self.metrics
+            .pipeline_fetch_concurrency
+            .with_label_values(&[self.pipeline])
+            .set(self.fetches() as i64);
+    }
+}
+
+/// Grow `current` by one up to `max` if healthy, otherwise halve it down to 1
+fn step(current: usize, healthy: bool, max: usize) -> usize {
+    if healthy {
+        (current + 1).min(max.max(1))
+    } else {
+        (current / 2).max(1)
+    }
+}
+
+/// The `quantile` of `samples`, or `None` if there are none
+fn percentile(mut samples: Vec<Duration>, quantile: f64) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = (quantile * samples.len() as f64).ceil() as usize;
+    Some(samples[rank.clamp(1, samples.len()) - 1])
+}
+
+/// Counting limit whose size can change while permits are held
+///
+/// Shrinking never revokes a held slot; new ones wait until enough are returned.
+struct Limit {
+    state: Mutex<LimitState>,
+    released: Notify,
+}
+
+struct LimitState {
+    limit: usize,
+    in_flight: usize,
+}
+
+impl Limit {
+    fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(LimitState {
+                limit: limit.max(1),
+                in_flight: 0,
+            }),
+            released: Notify::new(),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, LimitState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self) -> usize {
+        self.state().limit
+    }
+
+    fn set(&self, limit: usize) {
+        self.state().limit = limit.max(1);
+        self.released.notify_waiters();
+    }
+
+    async fn acquire(self: &Arc<Self>) -> Slot {
+        loop {
+            // Registered before checking, so a release in between still wakes us
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            {
+                let mut state = self.state();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    return Slot {
+                        limit: self.clone(),
+                    };
+                }
+            }
+            released.await;
+        }
+    }
+}
+
+/// Room for one write or fetch, returned when dropped
+#[must_use]
+pub(crate) struct Slot {
+    limit: Arc<Limit>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.limit.state().in_flight -= 1;
+        self.limit.released.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuned(writers: usize, fetches: usize) -> Concurrency {
+        let mut runtime = RuntimeConfig {
+            writer_concurrency: writers,
+            fetch_concurrency: fetches,
+            ..RuntimeConfig::default()
+        };
+        runtime.autotune.enabled = true;
+        runtime.autotune.max_writer_concurrency = 6;
+        Concurrency::new("default", &runtime, Arc::new(Metrics::new().unwrap()))
+    }
+
+    #[test]
+    fn test_limits_grow_additively_and_halve() {
+        let concurrency = tuned(4, 8);
+        for _ in 0..20 {
+            concurrency.record_write(Duration::from_millis(10));
+        }
+        concurrency.record_request(true);
+        concurrency.adjust();
+        assert_eq!((concurrency.writers(), concurrency.fetches()), (5, 9));
+
+        // One slow write in twenty stays within the 95th percentile
+        for _ in 0..19 {
+            concurrency.record_write(Duration::from_millis(10));
+        }
+        concurrency.record_write(Duration::from_secs(5));
+        concurrency.adjust();
+        assert_eq!(concurrency.writers(), 6);
+        // Healthy again, but already at the maximum; fetches saw nothing and stay
+        concurrency.record_write(Duration::from_millis(10));
+        concurrency.adjust();
+        assert_eq!((concurrency.writers(), concurrency.fetches()), (6, 9));
+
+        for _ in 0..2 {
+            concurrency.record_write(Duration::from_secs(1));
+        }
+        concurrency.record_request(false);
+        concurrency.record_request(true);
+        concurrency.adjust();
+        assert_eq!((concurrency.writers(), concurrency.fetches()), (3, 4));
+        let metrics = &concurrency.metrics;
+        assert_eq!(
+            metrics
+                .pipeline_writer_concurrency
+                .with_label_values(&["default"])
+                .get(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_follow_the_tuned_limits() {
+        // The pipeline and its fetcher hold clones of the tuned concurrency
+        let concurrency = tuned(1, 1);
+        let running = concurrency.clone();
+        let held = running.write().await;
+        for _ in 0..20 {
+            concurrency.record_write(Duration::from_millis(10));
+        }
+        concurrency.adjust();
+        assert_eq!(running.writers(), 2);
+        let _second = tokio::time::timeout(Duration::from_secs(1), running.write())
+            .await
+            .expect("the grown limit admits a second write");
+        drop(held);
+    }
+
+    #[test]
+    fn test_fixed_concurrency_never_moves() {
+        let runtime = RuntimeConfig::default();
+        let concurrency = Concurrency::new("default", &runtime, Arc::new(Metrics::new().unwrap()));
+        concurrency.record_write(Duration::from_secs(60));
+        concurrency.record_request(false);
+        concurrency.adjust();
+        assert_eq!(concurrency.writers(), runtime.writer_concurrency);
+        assert_eq!(concurrency.writer_bound(), runtime.writer_concurrency);
+        assert_eq!(concurrency.fetch_bound(), runtime.fetch_concurrency);
+    }
+
+    #[tokio::test]
+    async fn test_shrinking_waits_for_held_slots() {
+        let limit = Limit::new(2);
+        let first = limit.acquire().await;
+        let second = limit.acquire().await;
+        limit.set(1);
+
+        let waiting = tokio::spawn({
+            let limit = limit.clone();
+            async move { limit.acquire().await }
+        });
+        drop(first);
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        drop(second);
+        let _third = waiting.await.unwrap();
+        assert_eq!(limit.state().in_flight, 1);
+    }
+}
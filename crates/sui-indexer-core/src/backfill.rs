@@ -0,0 +1,507 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use eyre::{Result, WrapErr};
+use futures::{stream, StreamExt};
+use sui_indexer_config::{EventFilter, RetryConfig};
+use sui_indexer_events::{EventFilterProcessor, EventProcessor};
+use sui_indexer_storage::StorageManager;
+use sui_json_rpc_types::SuiEvent;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::{
+    audit,
+    error::is_retryable,
+    scheduler::{Resource, Scheduler},
+    spill::{CheckpointBatch, SpillOptions, SpillQueue},
+    sui::{CheckpointProcessor, CheckpointPruned, CheckpointRange, CheckpointStats, SuiClient},
+};
+
+/// Prefix of the cursors that track filter re-indexing
+pub const FILTER_CURSOR_PREFIX: &str = "filter:";
+
+/// Cursor recording how far the filter `name` has been re-indexed
+pub fn filter_cursor(name: &str) -> String {
+    format!("{FILTER_CURSOR_PREFIX}{name}")
+}
+
+/// Cursor recording the first checkpoint the live pipeline applied the filter `name` to
+///
+/// A re-index of the filter ends right before it, so every checkpoint is
+/// covered by one of the two.
+pub fn filter_handoff_cursor(name: &str) -> String {
+    format!("{FILTER_CURSOR_PREFIX}{name}@live")
+}
+
+/// Re-indexes a fixed checkpoint range
+///
+/// Checkpoints are fetched and processed concurrently in any order, then
+/// stored one at a time. When storage is slower than fetching, processed
+/// checkpoints beyond a memory budget are spilled to disk, so a long backfill
+/// keeps flat memory use; fetching pauses once the disk budget is used up.
+/// The live checkpoint cursor is left untouched, so a backfill can run next
+/// to a live indexer without moving its resume point. With a cursor of its
+/// own, checkpoints complete in order and the cursor advances after each, so
+/// an interrupted backfill can resume where it stopped. Checkpoints the node
+/// has pruned are skipped with a warning and an audit log entry, rather than
+/// retried.
+pub struct Backfill {
+    client: SuiClient,
+    storage: StorageManager,
+    processor: Arc<dyn EventProcessor>,
+    range: CheckpointRange,
+    filters: Vec<EventFilter>,
+    excluded: Vec<EventFilter>,
+    cursor: Option<String>,
+    concurrency: usize,
+    batch_size: usize,
+    retry: RetryConfig,
+    spill: SpillOptions,
+    scheduler: Scheduler,
+    progress: watch::Sender<CheckpointStats>,
+}
+
+impl Backfill {
+    /// Create a backfill of `range` that stores every event of those checkpoints
+    pub fn new(
+        client: SuiClient,
+        storage: StorageManager,
+        processor: Arc<dyn EventProcessor>,
+        range: CheckpointRange,
+    ) -> Self {
+        let tracker = progress_tracker(&range, 0);
+        let stats = CheckpointStats::calculate(&tracker, 0, Instant::now());
+
+        Self {
+            client,
+            storage,
+            processor,
+            range,
+            filters: Vec::new(),
+            excluded: Vec::new(),
+            cursor: None,
+            concurrency: 8,
+            batch_size: 100,
+            retry: RetryConfig::default(),
+            spill: SpillOptions::default(),
+            scheduler: Scheduler::default(),
+            progress: watch::channel(stats).0,
+        }
+    }
+
+    /// Only store events matching these filters
+    pub fn with_filters(mut self, filters: Vec<EventFilter>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Skip events that also match one of these filters, as they are already stored
+    pub fn with_excluded_filters(mut self, filters: Vec<EventFilter>) -> Self {
+        self.excluded = filters;
+        self
+    }
+
+    /// Complete checkpoints in order, recording each in this named cursor
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Set how many checkpoints are processed at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how many events are stored per write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set how failed checkpoints are retried before the backfill aborts
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set how many processed events wait in memory for storage before spilling to disk
+    pub fn with_spill(mut self, spill: SpillOptions) -> Self {
+        self.spill = spill;
+        self
+    }
+
+    /// Share node requests and storage writes with the live pipeline through `scheduler`
+    pub fn with_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Checkpoints this backfill covers
+    pub fn range(&self) -> &CheckpointRange {
+        &self.range
+    }
+
+    /// Subscribe to progress updates, published after every completed checkpoint
+    pub fn progress(&self) -> watch::Receiver<CheckpointStats> {
+        self.progress.subscribe()
+    }
+
+    /// Process the whole range, returning early if `shutdown` is cancelled
+    pub async fn run(mut self, shutdown: CancellationToken) -> Result<CheckpointStats> {
+        if !self.skip_pruned().await? {
+            return Ok(self.progress.borrow().clone());
+        }
+        let started = Instant::now();
+        let filter = Selector {
+            included: EventFilterProcessor::new(self.filters.clone()),
+            // An empty filter list matches everything, so nothing is excluded without filters
+            excluded: (!self.excluded.is_empty())
+                .then(|| EventFilterProcessor::new(self.excluded.clone())),
+        };
+
+        info!(
+            from = self.range.start,
+            to = self.range.end,
+            checkpoints = self.range.len(),
+            concurrency = self.concurrency,
+            filters = self.filters.len(),
+            cursor = self.cursor.as_deref(),
+            "Starting backfill"
+        );
+
+        // Fetching runs ahead of storage, with the backlog in between spilled to disk
+        let queue = SpillQueue::new(self.spill.clone());
+        let completed = AtomicU64::new(0);
+
+        let produce = async {
+            let checkpoints = stream::iter(self.range.iter()).map(|sequence_number| {
+                let span = info_span!(
+                    "checkpoint",
+                    pipeline = self.lane(),
+                    checkpoint = sequence_number,
+                );
+                self.fetch_checkpoint(sequence_number, &filter)
+                    .instrument(span)
+            });
+            let mut batches = if self.cursor.is_some() {
+                checkpoints.buffered(self.concurrency).left_stream()
+            } else {
+                checkpoints
+                    .buffer_unordered(self.concurrency)
+                    .right_stream()
+            };
+            while let Some(batch) = batches.next().await {
+                queue.push(batch?).await?;
+            }
+            queue.close();
+            Ok::<_, eyre::Report>(())
+        };
+
+        let write = async {
+            while let Some(batch) = queue.pop().await? {
+                let sequence_number = batch.checkpoint;
+                self.store_checkpoint(&batch)
+                    .instrument(info_span!("checkpoint", checkpoint = sequence_number))
+                    .await?;
+                if let Some(cursor) = &self.cursor {
+                    self.storage.set_cursor(cursor, sequence_number).await?;
+                }
+
+                let completed = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                let tracker = progress_tracker(&self.range, completed);
+                self.progress
+                    .send_replace(CheckpointStats::calculate(&tracker, completed, started));
+            }
+            Ok::<_, eyre::Report>(())
+        };
+
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                warn!(
+                    completed = completed.load(Ordering::Relaxed),
+                    spilled_bytes = queue.spilled_bytes(),
+                    "Backfill interrupted"
+                );
+            }
+            result = async { tokio::try_join!(produce, write) } => {
+                result?;
+            }
+        }
+
+        Ok(self.progress.borrow().clone())
+    }
+
+    /// Move the start of the range past checkpoints the node has pruned, false if none are left
+    ///
+    /// Nodes prune from the oldest checkpoint up, so probing the first one
+    /// tells whether any are gone. The skipped range is recorded in the audit
+    /// log and, with a cursor, the cursor moves past it so a resumed run
+    /// starts at the first available checkpoint.
+    async fn skip_pruned(&mut self) -> Result<bool> {
+        let start = self.range.start;
+        let probe = {
+            let _turn = self.scheduler.acquire(self.lane(), Resource::Node).await;
+            self.client.get_checkpoint(start).await
+        };
+        // Other failures are retried when the checkpoint is fetched for real
+        let Err(e) = probe else {
+            return Ok(true);
+        };
+        let earliest =
+            match CheckpointPruned::find(&e) {
+                None => return Ok(true),
+                Some(CheckpointPruned {
+                    earliest: Some(earliest),
+                    ..
+                }) if earliest > start => earliest,
+                Some(_) => return Err(e).wrap_err(
+                    "The node pruned the backfill range without saying which checkpoints it keeps",
+                ),
+            };
+
+        let skipped = CheckpointRange::new(start, (earliest - 1).min(self.range.end))?;
+        warn!(
+            from = skipped.start,
+            to = skipped.end,
+            earliest,
+            cursor = self.cursor.as_deref(),
+            "The node pruned part of the backfill range, skipping those checkpoints"
+        );
+        audit::record(
+            &self.storage,
+            audit::indexer_entry(
+                "checkpoints_pruned",
+                serde_json::json!({
+                    "from": skipped.start,
+                    "to": skipped.end,
+                    "earliest": earliest,
+                    "cursor": self.cursor,
+                }),
+            ),
+        )
+        .await;
+        if let Some(cursor) = &self.cursor {
+            self.storage.set_cursor(cursor, skipped.end).await?;
+        }
+
+        if earliest > self.range.end {
+            return Ok(false);
+        }
+        self.range = CheckpointRange::new(earliest, self.range.end)?;
+        let tracker = progress_tracker(&self.range, 0);
+        self.progress
+            .send_replace(CheckpointStats::calculate(&tracker, 0, Instant::now()));
+        Ok(true)
+    }
+
+    /// Fetch, filter and process one checkpoint, retrying on failure
+    async fn fetch_checkpoint(
+        &self,
+        sequence_number: u64,
+        filter: &Selector,
+    ) -> Result<CheckpointBatch> {
+        self.retrying(sequence_number, "fetch", || async move {
+            let checkpoint = {
+                let _turn = self.scheduler.acquire(self.lane(), Resource::Node).await;
+                self.client.get_checkpoint(sequence_number).await?
+            };
+            let matched: Vec<_> = checkpoint
+                .events
+                .into_iter()
+                .filter(|event| filter.selects(event))
+                .collect();
+
+            let mut events = self.processor.process_events(matched).await?;
+            for event in &mut events {
+                event.checkpoint_sequence = sequence_number;
+            }
+            Ok(CheckpointBatch {
+                checkpoint: sequence_number,
+                events,
+            })
+        })
+        .await
+    }
+
+    /// Store the events of one checkpoint in batches, retrying on failure
+    async fn store_checkpoint(&self, batch: &CheckpointBatch) -> Result<()> {
+        self.retrying(batch.checkpoint, "store", || async move {
+            for chunk in batch.events.chunks(self.batch_size) {
+                let _turn = self.scheduler.acquire(self.lane(), Resource::Storage).await;
+                self.storage.store_events(chunk).await?;
+            }
+            Ok(())
+        })
+        .await?;
+        debug!(
+            checkpoint = batch.checkpoint,
+            events = batch.events.len(),
+            "Checkpoint backfilled"
+        );
+        Ok(())
+    }
+
+    /// Name the backfill is scheduled and traced under
+    fn lane(&self) -> &str {
+        self.cursor.as_deref().unwrap_or("backfill")
+    }
+
+    /// Run `step` for a checkpoint until it succeeds or the retry policy gives up
+    ///
+    /// Errors that fail again when retried, such as a checkpoint pruned by
+    /// the node or a write the database refused, fail at once.
+    async fn retrying<T, F, Fut>(&self, sequence_number: u64, action: &str, step: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut delay = Duration::from_millis(self.retry.initial_delay);
+        let mut attempt = 1;
+
+        loop {
+            match step().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.retry.max_attempts && is_retryable(&e) => {
+                    warn!(
+                        checkpoint = sequence_number,
+                        attempt,
+                        error = %e,
+                        "Failed to {action} checkpoint, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = Duration::from_millis(
+                        ((delay.as_millis() as f64 * self.retry.backoff_multiplier) as u64)
+                            .min(self.retry.max_delay),
+                    );
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e).wrap_err_with(|| {
+                        format!("Failed to {action} checkpoint {sequence_number}")
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// Events a backfill stores: those matching its filters but none of the excluded ones
+struct Selector {
+    included: EventFilterProcessor,
+    excluded: Option<EventFilterProcessor>,
+}
+
+impl Selector {
+    fn selects(&self, event: &SuiEvent) -> bool {
+        self.included.should_process_event(event)
+            && !self
+                .excluded
+                .as_ref()
+                .is_some_and(|excluded| excluded.should_process_event(event))
+    }
+}
+
+/// Position tracker for `completed` finished checkpoints of `range`
+///
+/// Checkpoints finish out of order, so the position is the count of finished
+/// checkpoints from the start of the range rather than the highest one.
+fn progress_tracker(range: &CheckpointRange, completed: u64) -> CheckpointProcessor {
+    let current = (range.start + completed).checked_sub(1);
+    let mut tracker = CheckpointProcessor::new(current.filter(|_| completed > 0));
+    tracker.set_target_checkpoint(range.end);
+    tracker
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::FilterPriority;
+    use sui_indexer_events::DefaultEventProcessor;
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+    use crate::sui::MockSuiClient;
+
+    #[test]
+    fn test_progress_tracker() -> Result<()> {
+        let range = CheckpointRange::new(100, 199)?;
+
+        assert_eq!(progress_tracker(&range, 0).checkpoints_remaining(), None);
+        assert_eq!(
+            progress_tracker(&range, 40).checkpoints_remaining(),
+            Some(60)
+        );
+        assert!(progress_tracker(&range, 100).is_caught_up());
+        Ok(())
+    }
+
+    #[test]
+    fn test_selector_skips_excluded_events() {
+        let filter = |module: &str| EventFilter {
+            name: None,
+            package: Some("0x2".to_string()),
+            module: Some(module.to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        };
+        let event = |module: &str| {
+            TestEvent::new(&format!("0x2::{module}::Event"))
+                .tx("tx1")
+                .build()
+        };
+
+        let selector = Selector {
+            included: EventFilterProcessor::new(vec![filter("pool"), filter("coin")]),
+            excluded: Some(EventFilterProcessor::new(vec![filter("coin")])),
+        };
+        assert!(selector.selects(&event("pool")));
+        assert!(!selector.selects(&event("coin")));
+        assert!(!selector.selects(&event("nft")));
+    }
+
+    #[tokio::test]
+    async fn test_skips_pruned_checkpoints() -> Result<()> {
+        let node = MockSuiClient::synthetic(20, 2)?;
+        node.prune_before(12);
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let backfill = |start, end, cursor| -> Result<Backfill> {
+            Ok(Backfill::new(
+                SuiClient::mock(node.clone()),
+                storage.clone(),
+                Arc::new(DefaultEventProcessor::new()),
+                CheckpointRange::new(start, end)?,
+            )
+            .with_cursor(cursor))
+        };
+
+        let stats = backfill(5, 19, "recent")?
+            .run(CancellationToken::new())
+            .await?;
+        assert_eq!(stats.total_processed, 8);
+        let events = storage.get_events_by_checkpoint_range(0, 19).await?;
+        assert_eq!(events.len(), 16);
+        assert!(events.iter().all(|event| event.checkpoint_sequence >= 12));
+        assert_eq!(storage.get_cursor("recent").await?, Some(19));
+
+        let gap = &storage.audit_log(1).await?[0];
+        assert_eq!(gap.action, "checkpoints_pruned");
+        assert_eq!(gap.details["from"], 5);
+        assert_eq!(gap.details["to"], 11);
+
+        // A range pruned altogether only moves the cursor past it
+        let stats = backfill(0, 4, "old")?.run(CancellationToken::new()).await?;
+        assert_eq!(stats.total_processed, 0);
+        assert_eq!(storage.get_cursor("old").await?, Some(4));
+        Ok(())
+    }
+}
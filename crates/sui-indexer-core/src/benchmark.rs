@@ -0,0 +1,310 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use eyre::{Result, WrapErr};
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use sui_indexer_config::EventFilter;
+use sui_indexer_events::{EventFilterProcessor, EventProcessor, ProcessedEvent};
+use sui_indexer_storage::StorageManager;
+use sui_json_rpc_types::SuiEvent;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::sui::{CheckpointRange, SuiClient};
+
+/// Table written by the store stage, so benchmark rows never mix with indexed data
+pub const DEFAULT_BENCHMARK_TABLE: &str = "benchmark_events";
+
+/// Throughput of one benchmark stage
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct StageStats {
+    /// Wall-clock time spent in the stage
+    pub elapsed: Duration,
+    /// Checkpoints handled by the stage
+    pub checkpoints: u64,
+    /// Events handled by the stage
+    pub events: u64,
+}
+
+impl StageStats {
+    /// Checkpoints handled per second
+    pub fn checkpoints_per_second(&self) -> f64 {
+        per_second(self.checkpoints, self.elapsed)
+    }
+
+    /// Events handled per second
+    pub fn events_per_second(&self) -> f64 {
+        per_second(self.events, self.elapsed)
+    }
+}
+
+/// Outcome of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    /// Checkpoints benchmarked
+    pub range: CheckpointRange,
+    /// Fetching checkpoints from the node
+    pub fetch: StageStats,
+    /// Filtering and processing the fetched events
+    pub process: StageStats,
+    /// Writing the processed events, unless storing was skipped
+    pub store: Option<StageStats>,
+}
+
+/// Measures fetch, process and store throughput over a range of checkpoints
+///
+/// The stages run one after another over the whole range, so each figure is
+/// the throughput of that stage alone: fetching uses the configured
+/// concurrency, processing runs on one task and storing writes batches into
+/// a separate table whose rows for the range are replaced on every run. The
+/// live events table and checkpoint cursor are never touched.
+pub struct Benchmark {
+    client: SuiClient,
+    storage: Option<StorageManager>,
+    processor: Arc<dyn EventProcessor>,
+    checkpoints: u64,
+    start: Option<u64>,
+    filters: Vec<EventFilter>,
+    concurrency: usize,
+    batch_size: usize,
+    table: String,
+}
+
+impl Benchmark {
+    /// Create a benchmark of the latest `checkpoints` checkpoints, storing into `storage` if set
+    pub fn new(
+        client: SuiClient,
+        storage: Option<StorageManager>,
+        processor: Arc<dyn EventProcessor>,
+        checkpoints: u64,
+    ) -> Self {
+        Self {
+            client,
+            storage,
+            processor,
+            checkpoints: checkpoints.max(1),
+            start: None,
+            filters: Vec::new(),
+            concurrency: 8,
+            batch_size: 100,
+            table: DEFAULT_BENCHMARK_TABLE.to_string(),
+        }
+    }
+
+    /// Start at this checkpoint instead of ending at the network tip
+    pub fn with_start(mut self, start: Option<u64>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Only process events matching these filters
+    pub fn with_filters(mut self, filters: Vec<EventFilter>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Set how many checkpoints are fetched at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how many events are stored per write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Store into this table instead of [`DEFAULT_BENCHMARK_TABLE`]
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Run every stage, returning early if `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) -> Result<BenchmarkReport> {
+        let range = self.range().await?;
+        info!(
+            from = range.start,
+            to = range.end,
+            concurrency = self.concurrency,
+            store = self.storage.is_some(),
+            "Starting benchmark"
+        );
+
+        let (checkpoints, fetch) = self.fetch(&range, &shutdown).await?;
+        info!(
+            checkpoints = fetch.checkpoints,
+            per_second = fetch.checkpoints_per_second(),
+            "Fetch stage finished"
+        );
+
+        let (events, process) = self.process(checkpoints, &shutdown).await?;
+        info!(
+            events = process.events,
+            per_second = process.events_per_second(),
+            "Process stage finished"
+        );
+
+        let store = match &self.storage {
+            Some(storage) => {
+                let store = self.store(storage, &range, events, &shutdown).await?;
+                info!(
+                    events = store.events,
+                    per_second = store.events_per_second(),
+                    "Store stage finished"
+                );
+                Some(store)
+            }
+            None => None,
+        };
+
+        Ok(BenchmarkReport {
+            range,
+            fetch,
+            process,
+            store,
+        })
+    }
+
+    async fn range(&self) -> Result<CheckpointRange> {
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                let latest = self.client.get_latest_checkpoint().await?;
+                (latest + 1).saturating_sub(self.checkpoints)
+            }
+        };
+        CheckpointRange::new(start, start + self.checkpoints - 1)
+    }
+
+    /// Fetch every checkpoint of the range, keeping their events by sequence number
+    async fn fetch(
+        &self,
+        range: &CheckpointRange,
+        shutdown: &CancellationToken,
+    ) -> Result<(Vec<(u64, Vec<SuiEvent>)>, StageStats)> {
+        let started = Instant::now();
+        let mut stats = StageStats::default();
+        let mut fetched = Vec::with_capacity(range.len() as usize);
+
+        let mut results = stream::iter(range.iter())
+            .map(|sequence_number| async move {
+                let checkpoint = self
+                    .client
+                    .get_checkpoint(sequence_number)
+                    .await
+                    .wrap_err_with(|| format!("Failed to fetch checkpoint {sequence_number}"))?;
+                Ok::<_, eyre::Report>((sequence_number, checkpoint.events))
+            })
+            .buffer_unordered(self.concurrency);
+
+        loop {
+            let result = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    warn!(fetched = stats.checkpoints, "Benchmark interrupted");
+                    break;
+                }
+                result = results.next() => result,
+            };
+            let Some(result) = result else {
+                break;
+            };
+            let (sequence_number, events) = result?;
+
+            stats.checkpoints += 1;
+            stats.events += events.len() as u64;
+            fetched.push((sequence_number, events));
+        }
+
+        stats.elapsed = started.elapsed();
+        Ok((fetched, stats))
+    }
+
+    async fn process(
+        &self,
+        checkpoints: Vec<(u64, Vec<SuiEvent>)>,
+        shutdown: &CancellationToken,
+    ) -> Result<(Vec<ProcessedEvent>, StageStats)> {
+        let started = Instant::now();
+        let filter = EventFilterProcessor::new(self.filters.clone());
+        let mut stats = StageStats::default();
+        let mut processed = Vec::new();
+
+        for (sequence_number, events) in checkpoints {
+            if shutdown.is_cancelled() {
+                break;
+            }
+            let matched: Vec<_> = events
+                .into_iter()
+                .filter(|event| filter.should_process_event(event))
+                .collect();
+            stats.events += matched.len() as u64;
+
+            let mut events = self.processor.process_events(matched).await?;
+            for event in &mut events {
+                event.checkpoint_sequence = sequence_number;
+            }
+            stats.checkpoints += 1;
+            processed.extend(events);
+        }
+
+        stats.elapsed = started.elapsed();
+        Ok((processed, stats))
+    }
+
+    async fn store(
+        &self,
+        storage: &StorageManager,
+        range: &CheckpointRange,
+        events: Vec<ProcessedEvent>,
+        shutdown: &CancellationToken,
+    ) -> Result<StageStats> {
+        storage
+            .reset_event_table(&self.table, range.start, range.end)
+            .await?;
+
+        let started = Instant::now();
+        let mut stats = StageStats {
+            checkpoints: range.len(),
+            ..StageStats::default()
+        };
+        for chunk in events.chunks(self.batch_size) {
+            if shutdown.is_cancelled() {
+                break;
+            }
+            storage.store_events_in(&self.table, chunk).await?;
+            stats.events += chunk.len() as u64;
+        }
+
+        stats.elapsed = started.elapsed();
+        Ok(stats)
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    count as f64 / elapsed.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_rates() {
+        let stats = StageStats {
+            elapsed: Duration::from_millis(500),
+            checkpoints: 100,
+            events: 250,
+        };
+        assert_eq!(stats.checkpoints_per_second(), 200.0);
+        assert_eq!(stats.events_per_second(), 500.0);
+        assert_eq!(StageStats::default().events_per_second(), 0.0);
+    }
+}
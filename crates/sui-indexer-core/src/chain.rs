@@ -0,0 +1,101 @@
+use eyre::Result;
+use sui_indexer_config::{ErrorKind, NetworkConfig, WithKind};
+use sui_indexer_storage::StorageManager;
+use tracing::{info, warn};
+
+use crate::{error::IndexerError, sui::SuiClient};
+
+/// Check that the node, the configured network and the database belong to one chain
+///
+/// The node's chain identifier must match `network.chain_id` (or the preset
+/// of the network) when one is known, and the identifier stored with the
+/// database when it was first indexed. A database without one is stamped
+/// with the node's. Returns the node's chain identifier, `None` if the node
+/// pruned its genesis checkpoint and the chain cannot be told.
+pub async fn verify_chain(
+    client: &SuiClient,
+    storage: &StorageManager,
+    network: &NetworkConfig,
+) -> Result<Option<String>> {
+    let Some(chain_id) = client.get_chain_identifier().await? else {
+        warn!(
+            stored = storage.get_chain_id().await?,
+            "The node pruned its genesis checkpoint, so its chain cannot be verified"
+        );
+        return Ok(None);
+    };
+
+    if let Some(expected) = network.chain_id() {
+        if expected != chain_id {
+            return Err(eyre::Report::from(IndexerError::NetworkMismatch {
+                node: chain_id,
+                network: network.network.to_string(),
+                expected: expected.to_string(),
+            }))
+            .kind(ErrorKind::Config);
+        }
+    }
+
+    match storage.get_chain_id().await? {
+        Some(stored) if stored != chain_id => {
+            Err(eyre::Report::from(IndexerError::DatabaseMismatch {
+                stored,
+                node: chain_id,
+            }))
+            .kind(ErrorKind::Config)
+        }
+        Some(_) => Ok(Some(chain_id)),
+        None => {
+            storage.set_chain_id(&chain_id).await?;
+            info!(%chain_id, "Recorded chain identifier in the database");
+            Ok(Some(chain_id))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::{error_kind, Network};
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+    use crate::sui::mock::MockSuiClient;
+
+    #[tokio::test]
+    async fn test_verify_chain() -> Result<()> {
+        let mock = MockSuiClient::synthetic(2, 0)?;
+        let client = SuiClient::mock(mock.clone());
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let network = NetworkConfig::for_network(Network::Localnet);
+
+        let chain_id = verify_chain(&client, &storage, &network).await?.unwrap();
+        assert_eq!(chain_id.len(), 8);
+        assert_eq!(storage.get_chain_id().await?, Some(chain_id.clone()));
+        // A second run against the same node passes
+        assert_eq!(
+            verify_chain(&client, &storage, &network).await?,
+            Some(chain_id.clone())
+        );
+
+        // A network preset of another chain is refused
+        let mainnet = NetworkConfig::for_network(Network::Mainnet);
+        let err = verify_chain(&client, &storage, &mainnet).await.unwrap_err();
+        assert_eq!(error_kind(&err), Some(ErrorKind::Config));
+
+        // So is a database indexed from another chain
+        let other = StorageManager::from_backend(MemoryStorage::new());
+        other.set_chain_id("35834a8a").await?;
+        let err = verify_chain(&client, &other, &network).await.unwrap_err();
+        assert_eq!(error_kind(&err), Some(ErrorKind::Config));
+        assert!(matches!(
+            IndexerError::find(&err),
+            Some(IndexerError::DatabaseMismatch { stored, .. }) if stored == "35834a8a"
+        ));
+
+        // A node without its genesis checkpoint cannot tell its chain, which is not an error
+        let pruned = SuiClient::mock(MockSuiClient::recorded());
+        assert_eq!(verify_chain(&pruned, &other, &mainnet).await?, None);
+        assert_eq!(other.get_chain_id().await?, Some("35834a8a".to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+use sui_indexer_sinks::SinkQueues;
+use tokio::sync::{mpsc, watch};
+use tracing::info;
+
+use crate::pipeline::FetchedCheckpoint;
+
+/// Items waiting in a bounded queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueDepth {
+    /// Items currently queued
+    pub queued: usize,
+    /// Items the queue holds before it applies back-pressure
+    pub capacity: usize,
+}
+
+/// Queue depths of a running pipeline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueDepths {
+    /// Fetched checkpoints waiting to be processed, if the pipeline runs
+    pub checkpoints: Option<QueueDepth>,
+    /// Event batches waiting for delivery, by sink name
+    pub sinks: BTreeMap<String, QueueDepth>,
+}
+
+/// Pause switch and queue introspection shared by a pipeline and the control endpoint
+///
+/// A paused pipeline finishes the checkpoint in hand and then waits; fetching
+/// continues until the checkpoint queue is full. Clones share the same state.
+#[derive(Clone)]
+pub struct PipelineControl {
+    paused: Arc<watch::Sender<bool>>,
+    checkpoints: Arc<Mutex<Option<mpsc::WeakSender<FetchedCheckpoint>>>>,
+    sinks: Arc<Mutex<SinkQueues>>,
+}
+
+impl PipelineControl {
+    /// Pause processing; returns `false` if it was already paused
+    pub fn pause(&self) -> bool {
+        let changed = self
+            .paused
+            .send_if_modified(|paused| !std::mem::replace(paused, true));
+        if changed {
+            info!("Pipeline paused");
+        }
+        changed
+    }
+
+    /// Resume processing; returns `false` if it was not paused
+    pub fn resume(&self) -> bool {
+        let changed = self
+            .paused
+            .send_if_modified(|paused| std::mem::replace(paused, false));
+        if changed {
+            info!("Pipeline resumed");
+        }
+        changed
+    }
+
+    /// Whether processing is paused
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Current depth of the checkpoint queue and every sink queue
+    pub fn queue_depths(&self) -> QueueDepths {
+        let checkpoints = self
+            .checkpoints
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .and_then(|tx| tx.upgrade())
+            .map(|tx| QueueDepth {
+                queued: tx.max_capacity() - tx.capacity(),
+                capacity: tx.max_capacity(),
+            });
+        let sinks = self.sinks.lock().unwrap_or_else(PoisonError::into_inner);
+        let capacity = sinks.capacity();
+        QueueDepths {
+            checkpoints,
+            sinks: sinks
+                .depths()
+                .into_iter()
+                .map(|(name, queued)| (name, QueueDepth { queued, capacity }))
+                .collect(),
+        }
+    }
+
+    /// Wait until processing is not paused
+    pub(crate) async fn wait_while_paused(&self) {
+        let mut paused = self.paused.subscribe();
+        // The sender lives in `self`, so the channel cannot close while waiting
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+
+    pub(crate) fn watch_checkpoints(&self, tx: &mpsc::Sender<FetchedCheckpoint>) {
+        *self
+            .checkpoints
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(tx.downgrade());
+    }
+
+    pub(crate) fn watch_sinks(&self, queues: SinkQueues) {
+        *self.sinks.lock().unwrap_or_else(PoisonError::into_inner) = queues;
+    }
+}
+
+impl Default for PipelineControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(watch::channel(false).0),
+            checkpoints: Arc::default(),
+            sinks: Arc::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let control = PipelineControl::default();
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert!(control.is_paused());
+
+        let waiting = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait_while_paused().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        assert!(control.resume());
+        assert!(!control.resume());
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("resume wakes the pipeline")
+            .unwrap();
+    }
+}
@@ -0,0 +1,405 @@
+use serde_json::{json, Value};
+
+use crate::metrics::NAMESPACE;
+
+/// Grafana schema version the dashboard is written for
+const SCHEMA_VERSION: u32 = 39;
+
+/// Grid columns a panel spans, two panels per row
+const PANEL_WIDTH: u32 = 12;
+const PANEL_HEIGHT: u32 = 8;
+
+/// Label selecting the scraped indexer instances
+const INSTANCE: &str = r#"instance=~"$instance""#;
+
+/// A time series panel; `queries` pair a PromQL expression with its legend
+struct Panel {
+    title: &'static str,
+    unit: &'static str,
+    queries: Vec<(String, &'static str)>,
+}
+
+/// Full name of `metric` with the instance selector and any extra `labels`
+fn series(metric: &str, labels: &str) -> String {
+    if labels.is_empty() {
+        format!("{NAMESPACE}_{metric}{{{INSTANCE}}}")
+    } else {
+        format!("{NAMESPACE}_{metric}{{{INSTANCE},{labels}}}")
+    }
+}
+
+fn rate(metric: &str) -> String {
+    format!("rate({}[$__rate_interval])", series(metric, ""))
+}
+
+fn rate_by(by: &str, metric: &str) -> String {
+    format!("sum by ({by}) ({})", rate(metric))
+}
+
+fn quantile(quantile: f64, by: &str, histogram: &str) -> String {
+    let by = if by.is_empty() {
+        "le".to_string()
+    } else {
+        format!("le, {by}")
+    };
+    format!(
+        "histogram_quantile({quantile}, sum by ({by}) ({}))",
+        rate(&format!("{histogram}_bucket"))
+    )
+}
+
+fn panel(title: &'static str, unit: &'static str, queries: Vec<(String, &'static str)>) -> Panel {
+    Panel {
+        title,
+        unit,
+        queries,
+    }
+}
+
+/// Rows of the dashboard with their panels
+fn rows() -> Vec<(&'static str, Vec<Panel>)> {
+    vec![
+        (
+            "Ingestion",
+            vec![
+                panel(
+                    "Checkpoint lag",
+                    "short",
+                    vec![(
+                        format!(
+                            "{} - {}",
+                            series("latest_network_checkpoint", ""),
+                            series("latest_processed_checkpoint", "")
+                        ),
+                        "{{instance}}",
+                    )],
+                ),
+                panel(
+                    "Checkpoints committed",
+                    "cps",
+                    vec![(rate("checkpoints_processed_total"), "{{instance}}")],
+                ),
+                panel(
+                    "Events stored per pipeline",
+                    "eps",
+                    vec![(
+                        rate_by("pipeline", "pipeline_events_stored_total"),
+                        "{{pipeline}}",
+                    )],
+                ),
+                panel(
+                    "Checkpoint duration",
+                    "s",
+                    vec![
+                        (quantile(0.5, "", "checkpoint_duration_seconds"), "p50"),
+                        (quantile(0.99, "", "checkpoint_duration_seconds"), "p99"),
+                    ],
+                ),
+                panel(
+                    "Checkpoint availability",
+                    "s",
+                    vec![
+                        (quantile(0.5, "", "checkpoint_availability_seconds"), "p50"),
+                        (quantile(0.99, "", "checkpoint_availability_seconds"), "p99"),
+                    ],
+                ),
+                panel(
+                    "Ingestion stalled",
+                    "s",
+                    vec![
+                        (series("ingestion_stalled_seconds", ""), "stalled"),
+                        (
+                            format!(
+                                "increase({}[$__range])",
+                                series("watchdog_restarts_total", "")
+                            ),
+                            "watchdog restarts",
+                        ),
+                    ],
+                ),
+            ],
+        ),
+        (
+            "Filters",
+            vec![
+                panel(
+                    "Events matched per filter",
+                    "eps",
+                    vec![(
+                        rate_by("filter", "filter_events_matched_total"),
+                        "{{filter}}",
+                    )],
+                ),
+                panel(
+                    "Events stored per filter",
+                    "eps",
+                    vec![(
+                        rate_by("filter", "filter_events_stored_total"),
+                        "{{filter}}",
+                    )],
+                ),
+                panel(
+                    "Errors per filter",
+                    "short",
+                    vec![(rate_by("filter", "filter_errors_total"), "{{filter}}")],
+                ),
+                panel(
+                    "Events abandoned per pipeline",
+                    "short",
+                    vec![(
+                        rate_by("pipeline", "pipeline_events_abandoned_total"),
+                        "{{pipeline}}",
+                    )],
+                ),
+            ],
+        ),
+        (
+            "Errors",
+            vec![
+                panel(
+                    "Fetch and storage errors",
+                    "short",
+                    vec![
+                        (rate("fetch_errors_total"), "fetch"),
+                        (rate("storage_errors_total"), "storage"),
+                    ],
+                ),
+                panel(
+                    "Errors per pipeline",
+                    "short",
+                    vec![(rate_by("pipeline", "pipeline_errors_total"), "{{pipeline}}")],
+                ),
+            ],
+        ),
+        (
+            "Node",
+            vec![
+                panel(
+                    "gRPC requests",
+                    "reqps",
+                    vec![(
+                        rate_by("endpoint, outcome", "rpc_requests_total"),
+                        "{{endpoint}} {{outcome}}",
+                    )],
+                ),
+                panel(
+                    "gRPC latency p99",
+                    "s",
+                    vec![(
+                        quantile(0.99, "endpoint", "rpc_request_duration_seconds"),
+                        "{{endpoint}}",
+                    )],
+                ),
+                panel(
+                    "Active endpoint",
+                    "short",
+                    vec![(series("rpc_endpoint_active", ""), "{{endpoint}}")],
+                ),
+            ],
+        ),
+        (
+            "Resources",
+            vec![
+                panel(
+                    "Resident memory",
+                    "bytes",
+                    vec![(series("resident_memory_bytes", ""), "{{instance}}")],
+                ),
+                panel(
+                    "Database connections",
+                    "short",
+                    vec![
+                        (series("db_in_use_connections", ""), "in use"),
+                        (series("db_idle_connections", ""), "idle"),
+                        (series("db_max_connections", ""), "max"),
+                    ],
+                ),
+                panel(
+                    "Database acquire wait p99",
+                    "s",
+                    vec![(quantile(0.99, "", "db_acquire_wait_seconds"), "p99")],
+                ),
+                panel(
+                    "Tokio runtime",
+                    "short",
+                    vec![
+                        (series("tokio_tasks", ""), "tasks"),
+                        (series("tokio_workers", ""), "workers"),
+                        (series("tokio_global_queue_depth", ""), "queue depth"),
+                    ],
+                ),
+                panel(
+                    "Tokio busy ratio",
+                    "percentunit",
+                    vec![(series("tokio_busy_ratio", ""), "{{instance}}")],
+                ),
+                panel(
+                    "Tokio scheduling delay p99",
+                    "s",
+                    vec![(quantile(0.99, "", "tokio_scheduling_delay_seconds"), "p99")],
+                ),
+            ],
+        ),
+        (
+            "SLOs",
+            vec![
+                panel(
+                    "SLO compliance",
+                    "percentunit",
+                    vec![
+                        (series("slo_compliance_ratio", ""), "{{slo}}"),
+                        (series("slo_objective_ratio", ""), "{{slo}} objective"),
+                    ],
+                ),
+                panel(
+                    "SLO burn rate",
+                    "short",
+                    vec![(series("slo_burn_rate", ""), "{{slo}}")],
+                ),
+                panel(
+                    "Checkpoints missing SLOs",
+                    "short",
+                    vec![(
+                        format!(
+                            "sum by (slo) (rate({}[$__rate_interval]))",
+                            series("slo_checkpoints_total", r#"outcome="missed""#)
+                        ),
+                        "{{slo}}",
+                    )],
+                ),
+            ],
+        ),
+    ]
+}
+
+/// Grafana dashboard JSON charting the exported metrics
+///
+/// Panels query the Prometheus data source picked in the `datasource`
+/// variable and the indexers picked in the `instance` variable.
+pub fn grafana_dashboard(title: &str) -> Value {
+    let datasource = json!({ "type": "prometheus", "uid": "${datasource}" });
+    let mut panels = Vec::new();
+    let mut y = 0;
+    let mut id = 1;
+
+    for (row, row_panels) in rows() {
+        panels.push(json!({
+            "id": id,
+            "type": "row",
+            "title": row,
+            "collapsed": false,
+            "gridPos": { "x": 0, "y": y, "w": 2 * PANEL_WIDTH, "h": 1 },
+            "panels": [],
+        }));
+        id += 1;
+        y += 1;
+
+        let count = row_panels.len();
+        for (index, panel) in row_panels.into_iter().enumerate() {
+            let column = index as u32 % 2;
+            let targets: Vec<Value> = panel
+                .queries
+                .into_iter()
+                .zip('A'..)
+                .map(|((expr, legend), ref_id)| {
+                    json!({
+                        "datasource": datasource,
+                        "expr": expr,
+                        "legendFormat": legend,
+                        "refId": ref_id.to_string(),
+                    })
+                })
+                .collect();
+            panels.push(json!({
+                "id": id,
+                "type": "timeseries",
+                "title": panel.title,
+                "datasource": datasource,
+                "gridPos": {
+                    "x": column * PANEL_WIDTH,
+                    "y": y,
+                    "w": PANEL_WIDTH,
+                    "h": PANEL_HEIGHT,
+                },
+                "fieldConfig": { "defaults": { "unit": panel.unit }, "overrides": [] },
+                "targets": targets,
+            }));
+            id += 1;
+            if column == 1 {
+                y += PANEL_HEIGHT;
+            }
+        }
+        if count % 2 == 1 {
+            y += PANEL_HEIGHT;
+        }
+    }
+
+    json!({
+        "title": title,
+        "uid": "sui-indexer",
+        "tags": ["sui-indexer"],
+        "schemaVersion": SCHEMA_VERSION,
+        "editable": true,
+        "refresh": "30s",
+        "time": { "from": "now-6h", "to": "now" },
+        "templating": {
+            "list": [
+                {
+                    "name": "datasource",
+                    "label": "Data source",
+                    "type": "datasource",
+                    "query": "prometheus",
+                },
+                {
+                    "name": "instance",
+                    "label": "Instance",
+                    "type": "query",
+                    "datasource": datasource,
+                    "query": format!("label_values({NAMESPACE}_latest_processed_checkpoint, instance)"),
+                    "refresh": 2,
+                    "multi": true,
+                    "includeAll": true,
+                },
+            ],
+        },
+        "panels": panels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Metrics;
+
+    #[test]
+    fn test_dashboard_queries_exported_metrics() -> eyre::Result<()> {
+        let metrics = Metrics::new()?;
+        let dashboard = grafana_dashboard("Sui Indexer");
+
+        let mut queried = 0;
+        for panel in dashboard["panels"].as_array().unwrap() {
+            for target in panel["targets"].as_array().into_iter().flatten() {
+                let expr = target["expr"].as_str().unwrap();
+                for (start, _) in expr.match_indices(NAMESPACE) {
+                    let name: String = expr[start..]
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                        .collect();
+                    let name = name.strip_suffix("_bucket").unwrap_or(&name);
+                    assert!(metrics.names().contains(name), "{name} is not exported");
+                    queried += 1;
+                }
+            }
+        }
+        assert!(queried > 20);
+
+        let ids: Vec<_> = dashboard["panels"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|panel| panel["id"].as_u64().unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        Ok(())
+    }
+}
@@ -0,0 +1,421 @@
+use std::{
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_events::common_filters;
+use sui_indexer_storage::{PostgresStorage, Storage};
+
+use crate::{
+    backfill::FILTER_CURSOR_PREFIX,
+    sui::{ChainTip, SuiClient},
+};
+
+/// Node round trips slower than this are reported as degraded
+const SLOW_NODE: Duration = Duration::from_secs(1);
+
+/// A latest checkpoint older than this means the node is falling behind
+const STALE_TIP: Duration = Duration::from_secs(60);
+
+/// Tables whose size is reported
+const TABLES: &[&str] = &["processed_events", "processed_transactions"];
+
+/// Outcome of one diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Green,
+    Yellow,
+    Red,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Green => "🟢",
+            Self::Yellow => "🟡",
+            Self::Red => "🔴",
+        })
+    }
+}
+
+/// One diagnostic check with what it found
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Short name of the checked component
+    pub name: &'static str,
+    /// How healthy the component is
+    pub status: Status,
+    /// What was measured or went wrong
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Results of every diagnostic check, in the order they ran
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// The worst status of any check
+    pub fn status(&self) -> Status {
+        self.checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(Status::Green)
+    }
+}
+
+/// Diagnoses a configuration's node, database and filters
+///
+/// Every check runs even when an earlier one fails, so one run shows all
+/// problems; checks that need an unreachable component are reported as
+/// skipped.
+pub struct Doctor {
+    config: IndexerConfig,
+}
+
+impl Doctor {
+    /// Create a diagnosis of `config`
+    pub fn new(config: IndexerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Run every check
+    pub async fn run(self) -> DoctorReport {
+        let mut report = DoctorReport::default();
+
+        let tip = self.check_node(&mut report).await;
+        self.check_filters(&mut report);
+
+        match PostgresStorage::new(self.config.database.clone(), self.config.storage.clone()).await
+        {
+            Ok(storage) if storage.health_check().await.unwrap_or(false) => {
+                report
+                    .checks
+                    .push(Check::new("database", Status::Green, "connected"));
+                self.check_migrations(&storage, &mut report).await;
+                check_sizes(&storage, &mut report).await;
+                self.check_cursors(&storage, tip, &mut report).await;
+            }
+            Ok(_) => {
+                report
+                    .checks
+                    .push(Check::new("database", Status::Red, "health query failed"));
+                skip_database_checks(&mut report);
+            }
+            Err(err) => {
+                report
+                    .checks
+                    .push(Check::new("database", Status::Red, err.to_string()));
+                skip_database_checks(&mut report);
+            }
+        }
+
+        report
+    }
+
+    /// Node reachability and round trip, then the state of the chain it serves
+    async fn check_node(&self, report: &mut DoctorReport) -> Option<ChainTip> {
+        let endpoint = self
+            .config
+            .network
+            .grpc_url()
+            .map_or_else(|_| "node".to_string(), |url| url.to_string());
+        let started = Instant::now();
+        let client = match SuiClient::new(self.config.network.clone()).await {
+            Ok(client) => client,
+            Err(err) => {
+                report
+                    .checks
+                    .push(Check::new("node", Status::Red, err.to_string()));
+                report.checks.push(Check::new(
+                    "chain",
+                    Status::Yellow,
+                    "skipped: node unreachable",
+                ));
+                return None;
+            }
+        };
+
+        match client.get_chain_tip().await {
+            Ok(tip) => {
+                let latency = started.elapsed();
+                let status = if latency > SLOW_NODE {
+                    Status::Yellow
+                } else {
+                    Status::Green
+                };
+                report.checks.push(Check::new(
+                    "node",
+                    status,
+                    format!("{endpoint} answered in {}ms", latency.as_millis()),
+                ));
+
+                let age = tip_age(&tip);
+                let status = if age > STALE_TIP {
+                    Status::Yellow
+                } else {
+                    Status::Green
+                };
+                // The node API exposes the epoch but not its protocol version
+                report.checks.push(Check::new(
+                    "chain",
+                    status,
+                    format!(
+                        "epoch {}, latest checkpoint {} from {}s ago",
+                        tip.epoch,
+                        tip.sequence_number,
+                        age.as_secs()
+                    ),
+                ));
+                Some(tip)
+            }
+            Err(err) => {
+                report
+                    .checks
+                    .push(Check::new("node", Status::Red, err.to_string()));
+                report.checks.push(Check::new(
+                    "chain",
+                    Status::Yellow,
+                    "skipped: node unreachable",
+                ));
+                None
+            }
+        }
+    }
+
+    /// Every configured filter's package, module and sender parse
+    fn check_filters(&self, report: &mut DoctorReport) {
+        let filters = &self.config.events.filters;
+        let invalid: Vec<String> = filters
+            .iter()
+            .enumerate()
+            .filter_map(|(index, filter)| {
+                common_filters::validate(filter).err().map(|err| {
+                    let name = filter
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("filters[{index}]"));
+                    format!("{name}: {err}")
+                })
+            })
+            .collect();
+
+        let check = if !invalid.is_empty() {
+            Check::new("filters", Status::Red, invalid.join("; "))
+        } else if filters.is_empty() {
+            Check::new(
+                "filters",
+                Status::Yellow,
+                "no filters configured, every event is indexed",
+            )
+        } else {
+            Check::new(
+                "filters",
+                Status::Green,
+                format!("{} filters parse", filters.len()),
+            )
+        };
+        report.checks.push(check);
+    }
+
+    /// Pending or failed migrations
+    async fn check_migrations(&self, storage: &PostgresStorage, report: &mut DoctorReport) {
+        let check = match storage.migration_status().await {
+            Ok(migrations) => {
+                let pending = migrations.iter().filter(|m| m.applied_at.is_none()).count();
+                if let Some(dirty) = migrations.iter().find(|m| m.dirty) {
+                    Check::new(
+                        "migrations",
+                        Status::Red,
+                        format!("migration {} failed part way", dirty.version),
+                    )
+                } else if pending == 0 {
+                    Check::new(
+                        "migrations",
+                        Status::Green,
+                        format!("{} applied, none pending", migrations.len()),
+                    )
+                } else if self.config.database.auto_migrate {
+                    Check::new(
+                        "migrations",
+                        Status::Yellow,
+                        format!("{pending} pending, applied on next start"),
+                    )
+                } else {
+                    Check::new(
+                        "migrations",
+                        Status::Red,
+                        format!("{pending} pending and auto_migrate is off; run `migrate up`"),
+                    )
+                }
+            }
+            Err(err) => Check::new("migrations", Status::Red, err.to_string()),
+        };
+        report.checks.push(check);
+    }
+
+    /// How far each stored cursor is behind the network tip
+    async fn check_cursors(
+        &self,
+        storage: &PostgresStorage,
+        tip: Option<ChainTip>,
+        report: &mut DoctorReport,
+    ) {
+        // Filter re-index cursors stop at the live cursor once they finish
+        let cursors = match storage.list_cursors().await {
+            Ok(cursors) => cursors
+                .into_iter()
+                .filter(|cursor| !cursor.pipeline.starts_with(FILTER_CURSOR_PREFIX))
+                .collect::<Vec<_>>(),
+            Err(err) => {
+                report
+                    .checks
+                    .push(Check::new("cursor lag", Status::Red, err.to_string()));
+                return;
+            }
+        };
+        let Some(tip) = tip else {
+            report.checks.push(Check::new(
+                "cursor lag",
+                Status::Yellow,
+                "skipped: node unreachable",
+            ));
+            return;
+        };
+        if cursors.is_empty() {
+            report.checks.push(Check::new(
+                "cursor lag",
+                Status::Yellow,
+                "no cursors stored, nothing indexed yet",
+            ));
+            return;
+        }
+
+        let max_lag = self.config.alerts.lag.max_checkpoints;
+        for cursor in cursors {
+            let lag = tip
+                .sequence_number
+                .saturating_sub(cursor.checkpoint_sequence as u64);
+            report.checks.push(Check::new(
+                "cursor lag",
+                lag_status(lag, max_lag),
+                format!("`{}` is {lag} checkpoints behind", cursor.pipeline),
+            ));
+        }
+    }
+}
+
+/// On-disk size of the database and the indexer tables
+async fn check_sizes(storage: &PostgresStorage, report: &mut DoctorReport) {
+    let mut sizes = Vec::new();
+    let database = match storage.database_size().await {
+        Ok(size) => size,
+        Err(err) => {
+            report
+                .checks
+                .push(Check::new("disk", Status::Red, err.to_string()));
+            return;
+        }
+    };
+    for table in TABLES {
+        match storage.table_size(table).await {
+            Ok(size) => sizes.push(format!("{table} {}", format_bytes(size))),
+            Err(err) => {
+                report
+                    .checks
+                    .push(Check::new("disk", Status::Red, err.to_string()));
+                return;
+            }
+        }
+    }
+
+    report.checks.push(Check::new(
+        "disk",
+        Status::Green,
+        format!("database {} ({})", format_bytes(database), sizes.join(", ")),
+    ));
+}
+
+fn skip_database_checks(report: &mut DoctorReport) {
+    for name in ["migrations", "disk", "cursor lag"] {
+        report.checks.push(Check::new(
+            name,
+            Status::Yellow,
+            "skipped: database unreachable",
+        ));
+    }
+}
+
+/// Time since the tip checkpoint was produced
+fn tip_age(tip: &ChainTip) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now.saturating_sub(Duration::from_millis(tip.timestamp_ms))
+}
+
+/// Green within the lag alert threshold, red beyond ten times it
+fn lag_status(lag: u64, max_lag: u64) -> Status {
+    if lag <= max_lag {
+        Status::Green
+    } else if lag <= max_lag.saturating_mul(10) {
+        Status::Yellow
+    } else {
+        Status::Red
+    }
+}
+
+/// Render a byte count with a binary unit
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_thresholds() {
+        assert_eq!(lag_status(0, 100), Status::Green);
+        assert_eq!(lag_status(100, 100), Status::Green);
+        assert_eq!(lag_status(101, 100), Status::Yellow);
+        assert_eq!(lag_status(1001, 100), Status::Red);
+
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+
+        let report = DoctorReport {
+            checks: vec![
+                Check::new("node", Status::Green, ""),
+                Check::new("filters", Status::Yellow, ""),
+            ],
+        };
+        assert_eq!(report.status(), Status::Yellow);
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use eyre::Result;
+use futures::Stream;
+use sui_indexer_events::ProcessedEvent;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+/// Events of an indexer running in the background, as returned by
+/// [`IndexerCore::start_with_stream`](crate::IndexerCore::start_with_stream)
+///
+/// The stream ends when the indexer stops; [`EventStream::shutdown`] stops it
+/// and reports why it stopped, so errors are not lost with the task.
+pub struct EventStream {
+    events: mpsc::Receiver<ProcessedEvent>,
+    shutdown: CancellationToken,
+    task: JoinHandle<Result<()>>,
+}
+
+impl EventStream {
+    pub(crate) fn new(
+        events: mpsc::Receiver<ProcessedEvent>,
+        shutdown: CancellationToken,
+        task: JoinHandle<Result<()>>,
+    ) -> Self {
+        Self {
+            events,
+            shutdown,
+            task,
+        }
+    }
+
+    /// Next event, or `None` once the indexer has stopped and every event was received
+    pub async fn recv(&mut self) -> Option<ProcessedEvent> {
+        self.events.recv().await
+    }
+
+    /// Token that stops the indexer when cancelled
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Stop the indexer and wait for it, returning the error it stopped with if any
+    ///
+    /// Events not received yet are dropped.
+    pub async fn shutdown(self) -> Result<()> {
+        self.shutdown.cancel();
+        // Unblocks the stream sink if it waits for room in the channel
+        drop(self.events);
+        self.task.await?
+    }
+}
+
+impl Stream for EventStream {
+    type Item = ProcessedEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
@@ -0,0 +1,136 @@
+use eyre::Report;
+use sui_indexer_config::ConfigError;
+use sui_indexer_events::EventError;
+use sui_indexer_storage::StorageError;
+
+use crate::sui::{CheckpointPruned, ClientError};
+
+/// Failure of the indexer itself, as opposed to its node, storage or event processing
+///
+/// Like the errors of the other crates ([`ClientError`], [`StorageError`],
+/// [`EventError`]) it travels in an [`eyre::Report`] with any added context
+/// and is read back by type; [`is_retryable`] weighs them all.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IndexerError {
+    /// The node is not on the chain the configured network expects
+    #[error(
+        "The node serves chain {node}, but network {network} expects chain {expected}; check network.grpc_url and network.chain_id"
+    )]
+    NetworkMismatch {
+        node: String,
+        network: String,
+        expected: String,
+    },
+    /// The database was indexed from another chain than the node's
+    #[error(
+        "The database was indexed from chain {stored}, but the node serves chain {node}; use a database of that network"
+    )]
+    DatabaseMismatch { stored: String, node: String },
+    /// A part of the indexer whose support was not compiled in
+    #[error("{what} requires building with the `{feature}` feature")]
+    FeatureDisabled {
+        what: &'static str,
+        feature: &'static str,
+    },
+    /// A checkpoint range that ends before it starts
+    #[error("Start checkpoint {start} cannot be greater than end checkpoint {end}")]
+    InvalidRange { start: u64, end: u64 },
+    /// An operation that starts from the indexed tip before anything was indexed
+    #[error("No checkpoint has been indexed yet")]
+    NothingIndexed,
+    /// Re-indexing a filter the live pipeline has not taken over yet
+    #[error(
+        "The live indexer has not applied filter `{0}` yet; start it or reload its configuration with the filter first"
+    )]
+    FilterNotLive(String),
+    /// Resuming a live stream from a checkpoint without storage to replay it from
+    #[error("Resuming needs storage to replay from")]
+    ReplayWithoutStorage,
+    /// A watchlist address that does not parse
+    #[error("Invalid address `{0}`")]
+    InvalidAddress(String),
+    /// An `error_reporting.sentry_dsn` without a project id or public key
+    #[error("Invalid Sentry DSN: {0}")]
+    InvalidSentryDsn(&'static str),
+    /// The control endpoint of a running indexer refused a command
+    #[error("Control endpoint returned {status}: {body}")]
+    ControlEndpoint { status: String, body: String },
+    /// A simulation run that broke invariants
+    #[error("{} invariant violations:\n{}", .0.len(), .0.join("\n"))]
+    InvariantViolations(Vec<String>),
+}
+
+impl IndexerError {
+    /// The indexer error behind `err`, if that is what failed
+    pub fn find(err: &Report) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+}
+
+/// Whether the operation that failed with `err` can succeed when tried again
+///
+/// Typed errors decide: indexer, configuration and event errors never go
+/// away, a pruned checkpoint does not come back, and client and storage
+/// errors say for themselves. Errors without a typed cause are retried as before, since
+/// most of them are transient node or network failures.
+pub fn is_retryable(err: &Report) -> bool {
+    if IndexerError::find(err).is_some()
+        || ConfigError::find(err).is_some()
+        || EventError::find(err).is_some()
+        || CheckpointPruned::find(err).is_some()
+    {
+        return false;
+    }
+    ClientError::find(err).is_none_or(ClientError::is_retryable)
+        && StorageError::find(err).is_none_or(|storage| storage.is_retryable())
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::WrapErr;
+
+    use super::*;
+
+    #[test]
+    fn test_typed_errors_decide_retries() {
+        let wrapped = |err: Report| {
+            Err::<(), _>(err)
+                .wrap_err("Failed to store checkpoint 7")
+                .unwrap_err()
+        };
+
+        assert!(is_retryable(&eyre::eyre!("connection reset")));
+        assert!(is_retryable(&wrapped(ClientError::NotFound(7).into())));
+        let unavailable = tonic::Status::unavailable("connection refused");
+        assert!(is_retryable(&wrapped(
+            ClientError::from_status("get checkpoint 7", &unavailable).into()
+        )));
+        let denied = tonic::Status::permission_denied("missing token");
+        assert!(!is_retryable(&wrapped(
+            ClientError::from_status("get checkpoint 7", &denied).into()
+        )));
+        assert!(!is_retryable(&wrapped(
+            ClientError::Unsupported("Fetching transaction data over gRPC").into()
+        )));
+        assert!(is_retryable(&wrapped(StorageError::Conflict.into())));
+        assert!(!is_retryable(&wrapped(StorageError::Rejected.into())));
+        assert!(!is_retryable(&wrapped(
+            CheckpointPruned {
+                checkpoint: 7,
+                earliest: Some(9)
+            }
+            .into()
+        )));
+        assert!(!is_retryable(&wrapped(ConfigError::MissingGrpcUrl.into())));
+        assert!(!is_retryable(&wrapped(
+            EventError::UnknownProtocol("navi2".to_string()).into()
+        )));
+        assert!(!is_retryable(&wrapped(
+            IndexerError::DatabaseMismatch {
+                stored: "35834a8a".to_string(),
+                node: "4c78adac".to_string(),
+            }
+            .into()
+        )));
+    }
+}
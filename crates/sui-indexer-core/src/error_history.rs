@@ -0,0 +1,97 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::recent_errors::RecentErrors;
+
+/// Pipeline errors kept unless `observability.error_history` says otherwise
+pub(crate) const DEFAULT_CAPACITY: usize = 50;
+
+/// Pipeline stage an error occurred in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    /// Getting the network tip or a checkpoint from the node
+    Fetch,
+    /// Filtering and processing a checkpoint's events
+    Transform,
+    /// Storing events and advancing the cursor
+    Store,
+}
+
+impl fmt::Display for PipelineStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fetch => "fetch",
+            Self::Transform => "transform",
+            Self::Store => "store",
+        })
+    }
+}
+
+/// A failed attempt of one pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineError {
+    pub timestamp: DateTime<Utc>,
+    pub pipeline: String,
+    pub stage: PipelineStage,
+    /// Checkpoint being handled, unknown when getting the network tip failed
+    pub checkpoint: Option<u64>,
+    pub message: String,
+}
+
+/// The most recent pipeline errors, behind the error counters of `status`
+pub type ErrorHistory = RecentErrors<PipelineError>;
+
+impl ErrorHistory {
+    /// Record a failure of `stage` while handling `checkpoint`
+    pub fn record(
+        &self,
+        pipeline: &str,
+        stage: PipelineStage,
+        checkpoint: Option<u64>,
+        error: &eyre::Report,
+    ) {
+        self.push(PipelineError {
+            timestamp: Utc::now(),
+            pipeline: pipeline.to_string(),
+            stage,
+            checkpoint,
+            message: format!("{error:#}"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_latest_errors() {
+        let history = ErrorHistory::default();
+        history.set_capacity(3);
+        for checkpoint in 0..5 {
+            let error = eyre::eyre!("connection reset").wrap_err("Failed to fetch checkpoint");
+            history.record("default", PipelineStage::Fetch, Some(checkpoint), &error);
+        }
+        history.record(
+            "default",
+            PipelineStage::Store,
+            Some(5),
+            &eyre::eyre!("pool timed out"),
+        );
+
+        let errors = history.snapshot();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].checkpoint, Some(3));
+        assert_eq!(
+            errors[0].message,
+            "Failed to fetch checkpoint: connection reset"
+        );
+        assert_eq!(errors[2].stage, PipelineStage::Store);
+
+        history.set_capacity(0);
+        assert!(history.snapshot().is_empty());
+    }
+}
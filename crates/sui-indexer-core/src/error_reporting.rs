@@ -0,0 +1,419 @@
+use std::{
+    panic::PanicHookInfo,
+    sync::{Arc, Mutex, Once, PoisonError},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sentry::protocol::{Event, Exception, Level};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sui_indexer_config::ErrorReportingConfig;
+use tokio::{runtime::Handle, task::JoinSet};
+use tracing::warn;
+use url::Url;
+
+use crate::{error::IndexerError, error_history::PipelineStage};
+
+/// Timeout for delivering one report
+const REPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reporting the panic hook forwards to, replaced by every [`ErrorReporting::install_panic_hook`]
+static PANIC_REPORTING: Mutex<Option<ErrorReporting>> = Mutex::new(None);
+
+/// What failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorReportKind {
+    /// A thread or task panicked
+    Panic,
+    /// A pipeline stage failed in a way that stops the pipeline
+    Pipeline,
+}
+
+/// A panic or pipeline error forwarded to the error reporters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Unique identifier, 32 hex digits as Sentry expects
+    pub event_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: ErrorReportKind,
+    pub message: String,
+    pub pipeline: Option<String>,
+    pub stage: Option<PipelineStage>,
+    pub checkpoint: Option<u64>,
+    /// Filters that matched events of the failed checkpoint
+    pub filters: Vec<String>,
+    /// Source location of a panic
+    pub location: Option<String>,
+    /// Indexer version
+    pub release: String,
+    pub environment: Option<String>,
+}
+
+impl ErrorReport {
+    fn new(kind: ErrorReportKind, message: String) -> Self {
+        Self {
+            event_id: uuid::Uuid::new_v4().simple().to_string(),
+            timestamp: Utc::now(),
+            kind,
+            message,
+            pipeline: None,
+            stage: None,
+            checkpoint: None,
+            filters: Vec::new(),
+            location: None,
+            release: env!("CARGO_PKG_VERSION").to_string(),
+            environment: None,
+        }
+    }
+
+    /// Report of a pipeline error while handling `checkpoint`
+    pub fn pipeline(
+        pipeline: &str,
+        stage: PipelineStage,
+        checkpoint: u64,
+        filters: Vec<String>,
+        error: &eyre::Report,
+    ) -> Self {
+        Self {
+            pipeline: Some(pipeline.to_string()),
+            stage: Some(stage),
+            checkpoint: Some(checkpoint),
+            filters,
+            ..Self::new(ErrorReportKind::Pipeline, format!("{error:#}"))
+        }
+    }
+
+    /// Report of a panic
+    pub fn panic(info: &PanicHookInfo<'_>) -> Self {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let thread = std::thread::current();
+        Self {
+            location: info.location().map(ToString::to_string),
+            ..Self::new(
+                ErrorReportKind::Panic,
+                format!(
+                    "thread '{}' panicked: {message}",
+                    thread.name().unwrap_or("<unnamed>")
+                ),
+            )
+        }
+    }
+}
+
+/// Destination error reports are delivered to
+///
+/// Implement this to forward reports to another service and register it
+/// with [`ErrorReporting::with_reporter`].
+#[async_trait]
+pub trait ErrorReporter: Send + Sync {
+    /// Name used when logging delivery failures
+    fn name(&self) -> &str;
+
+    /// Deliver one report
+    async fn report(&self, report: &ErrorReport) -> Result<()>;
+
+    /// Wait up to `timeout` for reports this reporter queued to be sent
+    async fn flush(&self, _timeout: Duration) {}
+}
+
+/// Forwards panics and pipeline errors to the configured reporters
+///
+/// Reports are delivered in the background so the failing code is not held
+/// up; [`ErrorReporting::flush`] waits for deliveries still in flight.
+#[derive(Clone, Default)]
+pub struct ErrorReporting {
+    reporters: Vec<Arc<dyn ErrorReporter>>,
+    environment: Option<String>,
+    pending: Arc<Mutex<JoinSet<()>>>,
+    runtime: Option<Handle>,
+}
+
+impl ErrorReporting {
+    /// Create reporting to the destinations in `config` on the current runtime
+    pub fn from_config(config: &ErrorReportingConfig) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REPORT_TIMEOUT).build()?;
+        let mut reporting = Self {
+            environment: config.environment.clone(),
+            runtime: Handle::try_current().ok(),
+            ..Self::default()
+        };
+        if let Some(dsn) = &config.sentry_dsn {
+            let dsn = dsn.resolve()?;
+            reporting = reporting.with_reporter(Arc::new(SentryReporter::new(&dsn)?));
+        }
+        if let Some(url) = &config.url {
+            reporting = reporting.with_reporter(Arc::new(HttpReporter {
+                client,
+                url: url.clone(),
+            }));
+        }
+        Ok(reporting)
+    }
+
+    /// Also deliver reports to `reporter`
+    pub fn with_reporter(mut self, reporter: Arc<dyn ErrorReporter>) -> Self {
+        self.reporters.push(reporter);
+        self.runtime = self.runtime.or_else(|| Handle::try_current().ok());
+        self
+    }
+
+    /// Whether any reporter is registered
+    pub fn is_enabled(&self) -> bool {
+        !self.reporters.is_empty()
+    }
+
+    /// Deliver `report` to every reporter in the background
+    pub fn report(&self, mut report: ErrorReport) {
+        let Some(runtime) = &self.runtime else {
+            return;
+        };
+        report.environment = report.environment.or_else(|| self.environment.clone());
+        let report = Arc::new(report);
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        for reporter in &self.reporters {
+            let reporter = reporter.clone();
+            let report = report.clone();
+            pending.spawn_on(
+                async move {
+                    if let Err(e) = reporter.report(&report).await {
+                        warn!(reporter = reporter.name(), error = %e, "Failed to deliver error report");
+                    }
+                },
+                runtime,
+            );
+        }
+    }
+
+    /// Report panics through this reporting, keeping the previous panic hook
+    pub fn install_panic_hook(&self) {
+        static INSTALL: Once = Once::new();
+
+        *PANIC_REPORTING
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(self.clone());
+        INSTALL.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                previous(info);
+                // A panic while the lock is held must not deadlock the hook
+                if let Ok(reporting) = PANIC_REPORTING.try_lock() {
+                    if let Some(reporting) = reporting.as_ref() {
+                        reporting.report(ErrorReport::panic(info));
+                    }
+                }
+            }));
+        });
+    }
+
+    /// Wait up to `timeout` for reports still being delivered
+    pub async fn flush(&self, timeout: Duration) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut pending =
+            std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+        let drained = tokio::time::timeout_at(deadline, async {
+            while pending.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            warn!(
+                reports = pending.len(),
+                "Timed out delivering error reports"
+            );
+        }
+        for reporter in &self.reporters {
+            reporter
+                .flush(deadline.saturating_duration_since(tokio::time::Instant::now()))
+                .await;
+        }
+    }
+}
+
+/// Posts each report as JSON
+struct HttpReporter {
+    client: reqwest::Client,
+    url: Url,
+}
+
+#[async_trait]
+impl ErrorReporter for HttpReporter {
+    fn name(&self) -> &str {
+        self.url.as_str()
+    }
+
+    async fn report(&self, report: &ErrorReport) -> Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(report)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Sends reports to a Sentry project with the Sentry client
+///
+/// The client queues events and sends them from a thread of its own, so
+/// [`ErrorReporter::report`] returns once the event is queued and
+/// [`ErrorReporter::flush`] waits for the queue to drain.
+struct SentryReporter {
+    client: Arc<sentry::Client>,
+}
+
+impl SentryReporter {
+    /// Reporter for a DSN of the form `https://<key>@<host>/<project>`
+    fn new(dsn: &str) -> Result<Self> {
+        let dsn: sentry::types::Dsn = dsn.parse().map_err(|_| {
+            IndexerError::InvalidSentryDsn("expected https://<key>@<host>/<project>")
+        })?;
+        let options = sentry::ClientOptions {
+            dsn: Some(dsn),
+            release: Some(format!("sui-indexer@{}", env!("CARGO_PKG_VERSION")).into()),
+            // Panics are reported through the indexer's own hook
+            default_integrations: false,
+            ..Default::default()
+        };
+        Ok(Self {
+            client: Arc::new(sentry::Client::from_config(sentry::apply_defaults(options))),
+        })
+    }
+
+    /// Sentry event for `report`, with the context as tags and extra data
+    fn event(report: &ErrorReport) -> Event<'static> {
+        let (level, kind) = match report.kind {
+            ErrorReportKind::Panic => (Level::Fatal, "Panic"),
+            ErrorReportKind::Pipeline => (Level::Error, "PipelineError"),
+        };
+        let mut event = Event {
+            event_id: report
+                .event_id
+                .parse()
+                .unwrap_or_else(|_| uuid::Uuid::new_v4()),
+            timestamp: report.timestamp.into(),
+            level,
+            logger: Some("sui-indexer".to_string()),
+            environment: report.environment.clone().map(Into::into),
+            exception: vec![Exception {
+                ty: kind.to_string(),
+                value: Some(report.message.clone()),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        };
+        if let Some(pipeline) = &report.pipeline {
+            event.tags.insert("pipeline".to_string(), pipeline.clone());
+        }
+        if let Some(stage) = report.stage {
+            event.tags.insert("stage".to_string(), stage.to_string());
+        }
+        event
+            .extra
+            .insert("checkpoint".to_string(), json!(report.checkpoint));
+        event
+            .extra
+            .insert("filters".to_string(), json!(report.filters));
+        event
+            .extra
+            .insert("location".to_string(), json!(report.location));
+        event
+    }
+}
+
+#[async_trait]
+impl ErrorReporter for SentryReporter {
+    fn name(&self) -> &str {
+        "sentry"
+    }
+
+    async fn report(&self, report: &ErrorReport) -> Result<()> {
+        self.client.capture_event(Self::event(report), None);
+        Ok(())
+    }
+
+    async fn flush(&self, timeout: Duration) {
+        let client = self.client.clone();
+        // The client waits for its sending thread by blocking
+        let _ = tokio::task::spawn_blocking(move || client.flush(Some(timeout))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Collect(Mutex<Vec<ErrorReport>>);
+
+    #[async_trait]
+    impl ErrorReporter for Collect {
+        fn name(&self) -> &str {
+            "collect"
+        }
+
+        async fn report(&self, report: &ErrorReport) -> Result<()> {
+            self.0.lock().unwrap().push(report.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sentry_events_carry_the_report() -> Result<()> {
+        SentryReporter::new("https://abc123@o1.ingest.sentry.io/42")?;
+        assert!(SentryReporter::new("https://o1.ingest.sentry.io/42").is_err());
+        assert!(SentryReporter::new("not a dsn").is_err());
+
+        let report = ErrorReport::pipeline(
+            "default",
+            PipelineStage::Store,
+            7,
+            vec!["transfers".to_string()],
+            &eyre::eyre!("database rejected the statement"),
+        );
+        let event = SentryReporter::event(&report);
+        assert_eq!(event.event_id.simple().to_string(), report.event_id);
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.tags["stage"], "store");
+        assert_eq!(event.extra["checkpoint"], 7);
+        assert_eq!(
+            event.exception.values[0].value.as_deref(),
+            Some("database rejected the statement")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reports_reach_custom_reporter() {
+        let collect = Arc::new(Collect(Mutex::default()));
+        let reporting = ErrorReporting::from_config(&ErrorReportingConfig {
+            environment: Some("staging".to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+        .with_reporter(collect.clone());
+
+        reporting.report(ErrorReport::pipeline(
+            "default",
+            PipelineStage::Transform,
+            7,
+            vec!["transfers".to_string()],
+            &eyre::eyre!("processor failed"),
+        ));
+        reporting.flush(Duration::from_secs(1)).await;
+
+        let reports = collect.0.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].checkpoint, Some(7));
+        assert_eq!(reports[0].filters, ["transfers"]);
+        assert_eq!(reports[0].environment.as_deref(), Some("staging"));
+        assert_eq!(reports[0].event_id.len(), 32);
+    }
+}
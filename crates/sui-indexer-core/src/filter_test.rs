@@ -0,0 +1,275 @@
+use eyre::{Result, WrapErr};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use sui_indexer_config::EventFilter;
+use sui_indexer_events::{common_filters, EventFilterProcessor};
+use sui_indexer_storage::StorageManager;
+use sui_json_rpc_types::SuiEvent;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    error::IndexerError,
+    sui::{CheckpointRange, SuiClient},
+};
+
+/// Where a filter test reads events from
+pub enum EventSource {
+    /// Fetch recent checkpoints from the node
+    Node(SuiClient),
+    /// Read stored raw events, which only covers what the indexer already matched
+    Database(StorageManager),
+}
+
+/// An event matched by a tested filter
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterExample {
+    /// Checkpoint the event was emitted in
+    pub checkpoint: u64,
+    /// The matched event
+    pub event: SuiEvent,
+}
+
+/// Matches of one tested filter
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterMatches {
+    /// Filter name, or its position in the file when unnamed
+    pub filter: String,
+    /// Events matching the filter
+    pub matches: u64,
+    /// The first matched events, oldest first
+    pub examples: Vec<FilterExample>,
+}
+
+/// Outcome of a filter test
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterTestReport {
+    /// Checkpoints scanned
+    pub range: CheckpointRange,
+    /// Events evaluated against the filters
+    pub events_scanned: u64,
+    /// Stored events skipped because they have no raw event data
+    pub events_without_raw: u64,
+    /// Per-filter matches
+    pub filters: Vec<FilterMatches>,
+}
+
+impl FilterTestReport {
+    fn new(range: CheckpointRange, filters: &[EventFilter]) -> Self {
+        Self {
+            range,
+            events_scanned: 0,
+            events_without_raw: 0,
+            filters: filters
+                .iter()
+                .enumerate()
+                .map(|(index, filter)| FilterMatches {
+                    filter: filter
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("filters[{index}]")),
+                    matches: 0,
+                    examples: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Evaluate every filter against the events of one checkpoint
+    fn tally(
+        &mut self,
+        selectors: &[EventFilterProcessor],
+        checkpoint: u64,
+        events: &[SuiEvent],
+        examples: usize,
+    ) {
+        self.events_scanned += events.len() as u64;
+        for (matches, selector) in self.filters.iter_mut().zip(selectors) {
+            for event in events.iter().filter(|e| selector.should_process_event(e)) {
+                matches.matches += 1;
+                if matches.examples.len() < examples {
+                    matches.examples.push(FilterExample {
+                        checkpoint,
+                        event: event.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Evaluates candidate filters over the most recent checkpoints without storing anything
+pub struct FilterTest {
+    source: EventSource,
+    filters: Vec<EventFilter>,
+    last: u64,
+    examples: usize,
+    concurrency: usize,
+}
+
+impl FilterTest {
+    /// Create a test of `filters` over the last `last` checkpoints of `source`
+    pub fn new(source: EventSource, filters: Vec<EventFilter>, last: u64) -> Self {
+        Self {
+            source,
+            filters,
+            last: last.max(1),
+            examples: 3,
+            concurrency: 8,
+        }
+    }
+
+    /// Keep up to this many example events per filter
+    pub fn with_examples(mut self, examples: usize) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    /// Set how many checkpoints are fetched from the node at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Scan the checkpoints, returning early if `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) -> Result<FilterTestReport> {
+        for filter in &self.filters {
+            common_filters::validate(filter).wrap_err_with(|| {
+                format!(
+                    "Filter {} would never match",
+                    filter.name.as_deref().unwrap_or("without a name")
+                )
+            })?;
+        }
+
+        // Separate selectors keep the counts of overlapping filters independent
+        let selectors: Vec<_> = self
+            .filters
+            .iter()
+            .map(|filter| EventFilterProcessor::new(vec![filter.clone()]))
+            .collect();
+
+        match &self.source {
+            EventSource::Node(client) => {
+                let latest = client.get_latest_checkpoint().await?;
+                let range = self.range(latest)?;
+                info!(
+                    from = range.start,
+                    to = range.end,
+                    "Testing filters against the node"
+                );
+                let mut report = FilterTestReport::new(range.clone(), &self.filters);
+
+                // Fetched in order so the examples are the oldest matches
+                let mut checkpoints = stream::iter(range.iter())
+                    .map(|sequence_number| async move {
+                        client
+                            .get_checkpoint(sequence_number)
+                            .await
+                            .wrap_err_with(|| {
+                                format!("Failed to fetch checkpoint {sequence_number}")
+                            })
+                    })
+                    .buffered(self.concurrency);
+                loop {
+                    let checkpoint = tokio::select! {
+                        _ = shutdown.cancelled() => {
+                            warn!("Filter test interrupted");
+                            break;
+                        }
+                        checkpoint = checkpoints.try_next() => checkpoint?,
+                    };
+                    let Some(checkpoint) = checkpoint else {
+                        break;
+                    };
+                    report.tally(
+                        &selectors,
+                        checkpoint.sequence_number,
+                        &checkpoint.events,
+                        self.examples,
+                    );
+                }
+                Ok(report)
+            }
+            EventSource::Database(storage) => {
+                let latest = storage
+                    .get_latest_checkpoint()
+                    .await?
+                    .ok_or(IndexerError::NothingIndexed)?;
+                let range = self.range(latest)?;
+                info!(
+                    from = range.start,
+                    to = range.end,
+                    "Testing filters against stored events"
+                );
+                let mut report = FilterTestReport::new(range.clone(), &self.filters);
+
+                let mut stored = storage.export_raw_events(range.start, range.end);
+                while let Some(record) = stored.try_next().await? {
+                    if shutdown.is_cancelled() {
+                        warn!("Filter test interrupted");
+                        break;
+                    }
+                    let Some(event_data) = record.event_data else {
+                        report.events_without_raw += 1;
+                        continue;
+                    };
+                    let checkpoint = record.checkpoint_sequence as u64;
+                    let event: SuiEvent =
+                        serde_json::from_value(event_data).wrap_err_with(|| {
+                            format!("Failed to decode stored event of checkpoint {checkpoint}")
+                        })?;
+                    report.tally(&selectors, checkpoint, &[event], self.examples);
+                }
+                Ok(report)
+            }
+        }
+    }
+
+    /// The last `self.last` checkpoints up to and including `latest`
+    fn range(&self, latest: u64) -> Result<CheckpointRange> {
+        CheckpointRange::new((latest + 1).saturating_sub(self.last), latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::FilterPriority;
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    fn event(module: &str) -> SuiEvent {
+        TestEvent::new(&format!("0x2::{module}::Event"))
+            .tx("tx1")
+            .build()
+    }
+
+    #[test]
+    fn test_tally_counts_each_filter() -> Result<()> {
+        let filter = |name: &str, module: Option<&str>| EventFilter {
+            name: Some(name.to_string()),
+            package: Some("0x2".to_string()),
+            module: module.map(str::to_string),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        };
+        let filters = vec![filter("all", None), filter("pools", Some("pool"))];
+        let selectors: Vec<_> = filters
+            .iter()
+            .map(|f| EventFilterProcessor::new(vec![f.clone()]))
+            .collect();
+
+        let mut report = FilterTestReport::new(CheckpointRange::new(1, 2)?, &filters);
+        report.tally(&selectors, 1, &[event("pool"), event("coin")], 1);
+        report.tally(&selectors, 2, &[event("pool")], 1);
+
+        assert_eq!(report.events_scanned, 3);
+        assert_eq!(report.filters[0].matches, 3);
+        assert_eq!(report.filters[1].matches, 2);
+        assert_eq!(report.filters[1].examples.len(), 1);
+        assert_eq!(report.filters[1].examples[0].checkpoint, 1);
+        Ok(())
+    }
+}
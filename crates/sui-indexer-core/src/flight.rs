@@ -0,0 +1,596 @@
+use std::{net::SocketAddr, sync::Arc, time::Instant};
+
+use arrow_flight::{
+    encode::FlightDataEncoderBuilder,
+    error::FlightError,
+    flight_descriptor::DescriptorType,
+    flight_service_server::{FlightService, FlightServiceServer},
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaResult, Ticket,
+};
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sui_indexer_config::ApiScope;
+use sui_indexer_storage::{
+    export::{self, ExportRecord},
+    EventQuery, EventRecord, StorageManager, TransactionQuery, TransactionRecord,
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{info, warn};
+
+use crate::auth::{self, ApiAuth};
+
+/// Record batches encoded ahead of a slow client
+const BATCH_BUFFER: usize = 4;
+
+/// Arrow Flight endpoint streaming stored events and transactions as record batches
+///
+/// Analysts read indexed data straight into pandas, Polars, DuckDB or
+/// DataFusion with any Flight client. A ticket is the JSON of a
+/// [`FlightQuery`]; with a token or API keys set every call needs one as a
+/// bearer token with the `read` scope.
+pub struct FlightServer {
+    service: FlightEndpointService,
+}
+
+#[derive(Clone)]
+struct FlightEndpointService {
+    storage: StorageManager,
+    batch_size: usize,
+    token: Option<Arc<str>>,
+    auth: ApiAuth,
+    shutdown: CancellationToken,
+}
+
+/// Rows selected by a Flight ticket or command descriptor
+///
+/// Checkpoints default to everything up to the live cursor; the other
+/// criteria match like those of the query APIs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FlightQuery {
+    Events {
+        from_checkpoint: Option<u64>,
+        to_checkpoint: Option<u64>,
+        event_type: Option<String>,
+        sender: Option<String>,
+        package: Option<String>,
+        module: Option<String>,
+        since_ms: Option<i64>,
+        until_ms: Option<i64>,
+    },
+    Transactions {
+        from_checkpoint: Option<u64>,
+        to_checkpoint: Option<u64>,
+        digest: Option<String>,
+        success: Option<bool>,
+        since_ms: Option<i64>,
+        until_ms: Option<i64>,
+    },
+}
+
+impl FlightQuery {
+    /// Every stored row of a table, `events` or `transactions`
+    fn table(name: &str) -> Option<Self> {
+        let query = match name {
+            "events" => Self::Events {
+                from_checkpoint: None,
+                to_checkpoint: None,
+                event_type: None,
+                sender: None,
+                package: None,
+                module: None,
+                since_ms: None,
+                until_ms: None,
+            },
+            "transactions" => Self::Transactions {
+                from_checkpoint: None,
+                to_checkpoint: None,
+                digest: None,
+                success: None,
+                since_ms: None,
+                until_ms: None,
+            },
+            _ => return None,
+        };
+        Some(query)
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, Status> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| Status::invalid_argument(format!("Invalid Flight query: {e}")))
+    }
+
+    /// Query named by a descriptor: a path of one table name, or a command with query JSON
+    fn from_descriptor(descriptor: &FlightDescriptor) -> Result<Self, Status> {
+        match descriptor.r#type() {
+            DescriptorType::Path => match descriptor.path.as_slice() {
+                [table] => Self::table(table).ok_or_else(|| {
+                    Status::not_found(format!(
+                        "Unknown table `{table}`, expected `events` or `transactions`"
+                    ))
+                }),
+                _ => Err(Status::invalid_argument(
+                    "Descriptor paths name one table, `events` or `transactions`",
+                )),
+            },
+            DescriptorType::Cmd => Self::parse(&descriptor.cmd),
+            DescriptorType::Unknown => Err(Status::invalid_argument("Unknown descriptor type")),
+        }
+    }
+
+    /// Flight listing the query's schema and a ticket to fetch its rows
+    fn flight_info(&self, descriptor: FlightDescriptor) -> Result<FlightInfo, Status> {
+        let ticket = serde_json::to_vec(self).map_err(|e| Status::internal(e.to_string()))?;
+        let info = match self {
+            Self::Events { .. } => {
+                FlightInfo::new().try_with_schema(&export::schema::<EventRecord>())
+            }
+            Self::Transactions { .. } => {
+                FlightInfo::new().try_with_schema(&export::schema::<TransactionRecord>())
+            }
+        }
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(info
+            .with_descriptor(descriptor)
+            .with_endpoint(FlightEndpoint::new().with_ticket(Ticket::new(ticket))))
+    }
+}
+
+/// An authorized call, logged once it finishes
+struct Call {
+    method: &'static str,
+    caller: String,
+    started: Instant,
+}
+
+impl Call {
+    /// Log the outcome of the call and pass it on
+    fn finish<T>(self, result: Result<T, Status>) -> Result<Response<T>, Status> {
+        let code = result
+            .as_ref()
+            .map_or_else(Status::code, |_| tonic::Code::Ok);
+        auth::log_request(
+            "flight",
+            Some(&self.caller),
+            self.method,
+            format!("{code:?}"),
+            self.started.elapsed(),
+        );
+        result.map(Response::new)
+    }
+}
+
+impl FlightServer {
+    /// Serve the events and transactions in `storage` until `shutdown` is cancelled
+    pub fn new(storage: StorageManager, batch_size: usize, shutdown: CancellationToken) -> Self {
+        Self {
+            service: FlightEndpointService {
+                storage,
+                batch_size: batch_size.max(1),
+                token: None,
+                auth: ApiAuth::default(),
+                shutdown,
+            },
+        }
+    }
+
+    /// Require this bearer token on every call
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.service.token = token.map(Arc::from);
+        self
+    }
+
+    /// Also accept the API keys of `auth`, limited to their rates
+    pub fn with_api_auth(mut self, auth: ApiAuth) -> Self {
+        self.service.auth = auth;
+        self
+    }
+
+    /// Serve the endpoint on `addr` until shutdown
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let service = &self.service;
+        if !addr.ip().is_loopback() && service.token.is_none() && !service.auth.is_enabled() {
+            warn!(%addr, "Arrow Flight endpoint is not on a loopback address and has no token; anyone who can reach it can read indexed data");
+        }
+        info!(%addr, "Serving Arrow Flight endpoint");
+
+        let shutdown = self.service.shutdown.clone();
+        Server::builder()
+            .add_service(FlightServiceServer::new(self.service))
+            .serve_with_shutdown(addr, async move { shutdown.cancelled().await })
+            .await
+            .wrap_err_with(|| format!("Failed to serve Arrow Flight endpoint on {addr}"))
+    }
+}
+
+impl FlightEndpointService {
+    /// Authorize a call to `method`, logging it if it is refused
+    fn call<T>(&self, method: &'static str, request: &Request<T>) -> Result<Call, Status> {
+        let started = Instant::now();
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match self
+            .auth
+            .authorize(presented, self.token.as_deref(), ApiScope::Read)
+        {
+            Ok(caller) => Ok(Call {
+                method,
+                caller: caller.name,
+                started,
+            }),
+            Err(denied) => {
+                let status = denied.to_status();
+                auth::log_request(
+                    "flight",
+                    denied.caller(),
+                    method,
+                    format!("{:?}", status.code()),
+                    started.elapsed(),
+                );
+                Err(status)
+            }
+        }
+    }
+
+    /// Stream the rows selected by `query` as Flight data
+    async fn rows(&self, query: FlightQuery) -> Result<FlightDataStream, Status> {
+        let latest = self
+            .storage
+            .get_latest_checkpoint()
+            .await
+            .map_err(|e| Status::unavailable(format!("{e:#}")))?;
+        match query {
+            FlightQuery::Events {
+                from_checkpoint,
+                to_checkpoint,
+                event_type,
+                sender,
+                package,
+                module,
+                since_ms,
+                until_ms,
+            } => {
+                let Some((from, to)) = range(from_checkpoint, to_checkpoint, latest) else {
+                    return Ok(self.batches(None, StorageManager::export_matching_events));
+                };
+                let query = EventQuery {
+                    event_type,
+                    sender,
+                    package,
+                    module,
+                    since: timestamp(since_ms)?,
+                    until: timestamp(until_ms)?,
+                    from_checkpoint: Some(from),
+                    to_checkpoint: Some(to),
+                    ..EventQuery::default()
+                };
+                Ok(self.batches(Some(query), StorageManager::export_matching_events))
+            }
+            FlightQuery::Transactions {
+                from_checkpoint,
+                to_checkpoint,
+                digest,
+                success,
+                since_ms,
+                until_ms,
+            } => {
+                let Some((from, to)) = range(from_checkpoint, to_checkpoint, latest) else {
+                    return Ok(self.batches(None, StorageManager::export_matching_transactions));
+                };
+                let query = TransactionQuery {
+                    digest,
+                    success,
+                    since: timestamp(since_ms)?,
+                    until: timestamp(until_ms)?,
+                    from_checkpoint: Some(from),
+                    to_checkpoint: Some(to),
+                    ..TransactionQuery::default()
+                };
+                Ok(self.batches(Some(query), StorageManager::export_matching_transactions))
+            }
+        }
+    }
+
+    /// Encode the rows `export` selects for `query` in checkpoint order, none without a query
+    ///
+    /// The criteria are applied by the storage, and rows are read by a task
+    /// of their own, so a slow client holds back only its export.
+    fn batches<Q, R>(
+        &self,
+        query: Option<Q>,
+        export: for<'s> fn(&'s StorageManager, &Q) -> BoxStream<'s, Result<R>>,
+    ) -> FlightDataStream
+    where
+        Q: Send + 'static,
+        R: ExportRecord + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(BATCH_BUFFER);
+        if let Some(query) = query {
+            let storage = self.storage.clone();
+            let batch_size = self.batch_size;
+            let shutdown = self.shutdown.clone();
+            tokio::spawn(async move {
+                let mut chunks = export(&storage, &query).try_chunks(batch_size);
+                loop {
+                    let chunk = tokio::select! {
+                        chunk = chunks.next() => chunk,
+                        () = shutdown.cancelled() => break,
+                    };
+                    let Some(chunk) = chunk else {
+                        break;
+                    };
+                    let batch = chunk
+                        .map_err(|e| e.1)
+                        .and_then(|rows| export::record_batch(&rows))
+                        .map_err(|e| FlightError::ExternalError(e.into()));
+                    if tx.send(batch).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let batches = futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|batch| (batch, rx))
+        });
+        FlightDataEncoderBuilder::new()
+            .with_schema(export::schema::<R>())
+            .build(batches)
+            .map_err(Status::from)
+            .boxed()
+    }
+}
+
+type FlightDataStream = BoxStream<'static, Result<FlightData, Status>>;
+
+#[tonic::async_trait]
+impl FlightService for FlightEndpointService {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented(
+            "No handshake needed, send a bearer token with every call",
+        ))
+    }
+
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+
+    async fn list_flights(
+        &self,
+        request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        let call = self.call("ListFlights", &request)?;
+        let flights = ["events", "transactions"].map(|table| {
+            FlightQuery::table(table)
+                .expect("table is known")
+                .flight_info(FlightDescriptor::new_path(vec![table.to_string()]))
+        });
+        call.finish(Ok(futures::stream::iter(flights).boxed()))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let call = self.call("GetFlightInfo", &request)?;
+        let descriptor = request.into_inner();
+        call.finish(
+            FlightQuery::from_descriptor(&descriptor)
+                .and_then(|query| query.flight_info(descriptor)),
+        )
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented(
+            "Flights are ready at once, use GetFlightInfo",
+        ))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let call = self.call("GetSchema", &request)?;
+        let descriptor = request.into_inner();
+        call.finish(
+            FlightQuery::from_descriptor(&descriptor)
+                .and_then(|query| query.flight_info(descriptor))
+                .map(|info| SchemaResult {
+                    schema: info.schema,
+                }),
+        )
+    }
+
+    type DoGetStream = FlightDataStream;
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let call = self.call("DoGet", &request)?;
+        let result = match FlightQuery::parse(&request.into_inner().ticket) {
+            Ok(query) => self.rows(query).await,
+            Err(status) => Err(status),
+        };
+        call.finish(result)
+    }
+
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("The Flight endpoint is read-only"))
+    }
+
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("No actions are supported"))
+    }
+
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(futures::stream::empty().boxed()))
+    }
+
+    type DoExchangeStream = FlightDataStream;
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("The Flight endpoint is read-only"))
+    }
+}
+
+/// Checkpoints `from..=to` to export, `to` defaulting to the live cursor
+fn range(from: Option<u64>, to: Option<u64>, latest: Option<u64>) -> Option<(u64, u64)> {
+    let end = to.or(latest)?;
+    let start = from.unwrap_or(0);
+    (start <= end).then_some((start, end))
+}
+
+fn timestamp(ms: Option<i64>) -> Result<Option<DateTime<Utc>>, Status> {
+    ms.map(|ms| {
+        DateTime::from_timestamp_millis(ms)
+            .ok_or_else(|| Status::invalid_argument(format!("Timestamp {ms} is out of range")))
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_flight::decode::FlightRecordBatchStream;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor, ProcessedEvent};
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    async fn events(checkpoint: u64, modules: &[&str]) -> Result<Vec<ProcessedEvent>> {
+        let events = modules
+            .iter()
+            .enumerate()
+            .map(|(seq, module)| {
+                TestEvent::new(&format!("0x2::{module}::Event"))
+                    .tx(&format!("tx{checkpoint}"))
+                    .seq(seq as u64)
+                    .build()
+            })
+            .collect();
+        let mut events = DefaultEventProcessor::new().process_events(events).await?;
+        for event in &mut events {
+            event.checkpoint_sequence = checkpoint;
+        }
+        Ok(events)
+    }
+
+    async fn count_rows(service: &FlightEndpointService, query: FlightQuery) -> Result<usize> {
+        let data = service.rows(query).await?.map_err(FlightError::from);
+        let batches: Vec<_> = FlightRecordBatchStream::new_from_flight_data(data)
+            .try_collect()
+            .await?;
+        Ok(batches.iter().map(|batch| batch.num_rows()).sum())
+    }
+
+    #[tokio::test]
+    async fn test_do_get_streams_the_rows_a_ticket_selects() -> Result<()> {
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        for checkpoint in 1..=4 {
+            storage
+                .store_events(&events(checkpoint, &["pool", "coin"]).await?)
+                .await?;
+        }
+        storage.update_checkpoint_progress(3).await?;
+        let service = FlightServer::new(storage, 2, CancellationToken::new()).service;
+
+        let ticket = |json: serde_json::Value| FlightQuery::parse(json.to_string().as_bytes());
+        // Up to the live cursor by default, so checkpoint 4 is left out
+        let pool = ticket(serde_json::json!({"table": "events", "module": "pool"}))?;
+        assert_eq!(count_rows(&service, pool).await?, 3);
+        let later = ticket(serde_json::json!({
+            "table": "events",
+            "module": "pool",
+            "from_checkpoint": 2,
+            "to_checkpoint": 4
+        }))?;
+        assert_eq!(count_rows(&service, later).await?, 3);
+        let all = FlightQuery::table("events").expect("table is known");
+        assert_eq!(count_rows(&service, all).await?, 6);
+        let empty = ticket(serde_json::json!({"table": "events", "from_checkpoint": 5}))?;
+        assert_eq!(count_rows(&service, empty).await?, 0);
+        let transactions = FlightQuery::table("transactions").expect("table is known");
+        assert_eq!(count_rows(&service, transactions).await?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_descriptors_name_a_table_or_carry_a_query() {
+        let path = FlightDescriptor::new_path(vec!["transactions".to_string()]);
+        assert!(matches!(
+            FlightQuery::from_descriptor(&path),
+            Ok(FlightQuery::Transactions { digest: None, .. })
+        ));
+        let unknown = FlightDescriptor::new_path(vec!["blocks".to_string()]);
+        assert_eq!(
+            FlightQuery::from_descriptor(&unknown).unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+        let cmd = FlightDescriptor::new_cmd(r#"{"table":"events","sender":"0x1"}"#);
+        assert!(matches!(
+            FlightQuery::from_descriptor(&cmd),
+            Ok(FlightQuery::Events {
+                sender: Some(_),
+                ..
+            })
+        ));
+        let typo = FlightDescriptor::new_cmd(r#"{"table":"events","senders":"0x1"}"#);
+        assert_eq!(
+            FlightQuery::from_descriptor(&typo).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_calls_need_the_token() {
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let service = FlightServer::new(storage, 1, CancellationToken::new())
+            .with_token(Some("s3cret".to_string()))
+            .service;
+
+        let err = service
+            .call("ListFlights", &Request::new(Criteria::default()))
+            .err()
+            .expect("a call without the token is refused");
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(Criteria::default());
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer s3cret".parse().unwrap());
+        assert!(service.call("ListFlights", &request).is_ok());
+    }
+}
@@ -0,0 +1,623 @@
+use std::{net::SocketAddr, pin::Pin, sync::Arc, time::Instant};
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::{stream, Stream};
+use sui_indexer_config::{ApiScope, IndexerConfig};
+use sui_indexer_events::ProcessedEvent;
+use sui_indexer_storage::{
+    EventQuery, EventRecord, PageCursor, StorageManager, TransactionQuery, TransactionRecord,
+};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tonic::{metadata::MetadataMap, transport::Server, Code, Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::{
+    auth::{self, ApiAuth},
+    control::PipelineControl,
+    live::{EventMatcher, FeedError, LiveBatch, LiveEvents, LiveFeed, LiveMessage, Subscription},
+    metrics::Metrics,
+};
+
+/// Types and service generated in `sui-indexer-client`, so both ends share one definition
+pub use sui_indexer_client::proto;
+
+use proto::{
+    indexer_server::{Indexer, IndexerServer},
+    Event, EventBatch, GetStatusRequest, GetStatusResponse, QueryEventsRequest,
+    QueryEventsResponse, QueryTransactionsRequest, QueryTransactionsResponse,
+    SubscribeEventsRequest, Transaction,
+};
+
+/// Rows returned by a query that sets no limit
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Largest limit a query may ask for
+const MAX_LIMIT: u32 = 1000;
+
+/// Trailer carrying the checkpoint a lagging subscriber resumes from
+const RESUME_TRAILER: &str = "resume-from-checkpoint";
+
+/// gRPC API serving stored events and transactions, live events and status
+///
+/// Other services get typed access to indexed data without reading the
+/// database. With a token or API keys set, every call must carry one as a
+/// bearer token; all methods need the `read` scope.
+pub struct GrpcServer {
+    service: IndexerService,
+}
+
+#[derive(Clone)]
+struct IndexerService {
+    config: Arc<watch::Sender<IndexerConfig>>,
+    metrics: Arc<Metrics>,
+    storage: StorageManager,
+    live: LiveEvents,
+    control: PipelineControl,
+    token: Option<Arc<str>>,
+    auth: ApiAuth,
+    shutdown: CancellationToken,
+    started: Instant,
+}
+
+impl GrpcServer {
+    /// Serve `storage` and the events published on `live` until `shutdown` is cancelled
+    pub fn new(
+        config: Arc<watch::Sender<IndexerConfig>>,
+        metrics: Arc<Metrics>,
+        storage: StorageManager,
+        live: LiveEvents,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            service: IndexerService {
+                config,
+                metrics,
+                storage,
+                live,
+                control: PipelineControl::default(),
+                token: None,
+                auth: ApiAuth::default(),
+                shutdown,
+                started: Instant::now(),
+            },
+        }
+    }
+
+    /// Require this bearer token on every call
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.service.token = token.map(Arc::from);
+        self
+    }
+
+    /// Also accept the API keys of `auth`, limited to their rates
+    pub fn with_api_auth(mut self, auth: ApiAuth) -> Self {
+        self.service.auth = auth;
+        self
+    }
+
+    /// Report whether the pipeline behind `control` is paused
+    pub fn with_control(mut self, control: PipelineControl) -> Self {
+        self.service.control = control;
+        self
+    }
+
+    /// Serve the API on `addr` until shutdown
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let service = &self.service;
+        if !addr.ip().is_loopback() && service.token.is_none() && !service.auth.is_enabled() {
+            warn!(%addr, "gRPC API is not on a loopback address and has no token; anyone who can reach it can read indexed data");
+        }
+        info!(%addr, "Serving gRPC API");
+
+        let shutdown = self.service.shutdown.clone();
+        Server::builder()
+            .add_service(IndexerServer::new(self.service))
+            .serve_with_shutdown(addr, async move { shutdown.cancelled().await })
+            .await
+            .wrap_err_with(|| format!("Failed to serve gRPC API on {addr}"))
+    }
+}
+
+/// An authorized call, logged once it finishes
+struct Call {
+    method: &'static str,
+    caller: String,
+    started: Instant,
+}
+
+impl Call {
+    /// Log the outcome of the call and pass it on
+    fn finish<T>(self, result: Result<T, Status>) -> Result<Response<T>, Status> {
+        let code = result.as_ref().map_or_else(Status::code, |_| Code::Ok);
+        auth::log_request(
+            "grpc",
+            Some(&self.caller),
+            self.method,
+            format!("{code:?}"),
+            self.started.elapsed(),
+        );
+        result.map(Response::new)
+    }
+}
+
+impl IndexerService {
+    /// Authorize a call to `method`, logging it if it is refused
+    fn call<T>(&self, method: &'static str, request: &Request<T>) -> Result<Call, Status> {
+        let started = Instant::now();
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match self
+            .auth
+            .authorize(presented, self.token.as_deref(), ApiScope::Read)
+        {
+            Ok(caller) => Ok(Call {
+                method,
+                caller: caller.name,
+                started,
+            }),
+            Err(denied) => {
+                let status = denied.to_status();
+                auth::log_request(
+                    "grpc",
+                    denied.caller(),
+                    method,
+                    format!("{:?}", status.code()),
+                    started.elapsed(),
+                );
+                Err(status)
+            }
+        }
+    }
+
+    async fn events(&self, request: QueryEventsRequest) -> Result<QueryEventsResponse, Status> {
+        let limit = limit(request.limit)?;
+        let query = EventQuery {
+            event_type: request.event_type,
+            sender: request.sender,
+            package: request.package,
+            module: request.module,
+            since: timestamp(request.since_ms)?,
+            until: timestamp(request.until_ms)?,
+            from_checkpoint: request.from_checkpoint,
+            to_checkpoint: request.to_checkpoint,
+            limit: Some(limit),
+            after: cursor(request.cursor.as_deref())?,
+        };
+        let events = self
+            .storage
+            .query_events(&query)
+            .await
+            .map_err(unavailable)?;
+        let next = PageCursor::next_page(&events, limit, EventRecord::cursor);
+        Ok(QueryEventsResponse {
+            events: events.into_iter().map(Event::from).collect(),
+            next_cursor: next.map(|next| next.encode()),
+        })
+    }
+
+    async fn transactions(
+        &self,
+        request: QueryTransactionsRequest,
+    ) -> Result<QueryTransactionsResponse, Status> {
+        let limit = limit(request.limit)?;
+        let query = TransactionQuery {
+            digest: request.digest,
+            success: request.success,
+            since: timestamp(request.since_ms)?,
+            until: timestamp(request.until_ms)?,
+            from_checkpoint: request.from_checkpoint,
+            to_checkpoint: request.to_checkpoint,
+            limit: Some(limit),
+            after: cursor(request.cursor.as_deref())?,
+        };
+        let transactions = self
+            .storage
+            .query_transactions(&query)
+            .await
+            .map_err(unavailable)?;
+        let next = PageCursor::next_page(&transactions, limit, TransactionRecord::cursor);
+        Ok(QueryTransactionsResponse {
+            transactions: transactions.into_iter().map(Transaction::from).collect(),
+            next_cursor: next.map(|next| next.encode()),
+        })
+    }
+
+    async fn subscribe(&self, request: SubscribeEventsRequest) -> Result<EventStream, Status> {
+        let subscription = Subscription {
+            filter: (!request.filters.is_empty()).then(|| request.filters.join(",")),
+            package: request.package,
+            module: request.module,
+            event_type: request.event_type,
+            sender: request.sender,
+            from_checkpoint: request.from_checkpoint,
+        };
+        let matcher = EventMatcher::new(&subscription, &self.config.borrow())
+            .map_err(|e| Status::invalid_argument(format!("{e:#}")))?;
+        let feed = LiveFeed::new(
+            &self.live,
+            matcher,
+            Some(self.storage.clone()),
+            subscription.from_checkpoint,
+        )
+        .await
+        .map_err(unavailable)?;
+
+        let shutdown = self.shutdown.clone();
+        let batches = stream::unfold(Some(feed), move |feed| {
+            let shutdown = shutdown.clone();
+            async move {
+                let mut feed = feed?;
+                loop {
+                    let next = tokio::select! {
+                        next = feed.next() => next,
+                        () = shutdown.cancelled() => return None,
+                    };
+                    return match next {
+                        Ok(Some(LiveMessage::Events(batch))) => {
+                            Some((Ok(EventBatch::from(batch)), Some(feed)))
+                        }
+                        Ok(Some(LiveMessage::CaughtUp { checkpoint })) => {
+                            let marker = EventBatch {
+                                checkpoint: checkpoint.unwrap_or_default(),
+                                caught_up: true,
+                                ..EventBatch::default()
+                            };
+                            Some((Ok(marker), Some(feed)))
+                        }
+                        // Pauses have no message in the protocol; the stream just goes quiet
+                        Ok(Some(_)) => continue,
+                        Ok(None) => None,
+                        Err(e) => Some((Err(feed_status(e, feed.cursor())), None)),
+                    };
+                }
+            }
+        });
+        Ok(Box::pin(batches))
+    }
+
+    fn status(&self) -> GetStatusResponse {
+        let metrics = &self.metrics;
+        // Gauges stay at zero until the first fetch and commit
+        let known = |value: i64| (value > 0).then_some(value as u64);
+
+        GetStatusResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            network: self.config.borrow().network.network.to_string(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            latest_processed_checkpoint: known(metrics.latest_processed_checkpoint.get()),
+            latest_network_checkpoint: known(metrics.latest_network_checkpoint.get()),
+            checkpoints_processed: metrics.checkpoints_processed.get(),
+            events_processed: metrics.events_processed.get(),
+            paused: self.control.is_paused(),
+            api_version: sui_indexer_client::API_VERSION,
+        }
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<EventBatch, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Indexer for IndexerService {
+    async fn query_events(
+        &self,
+        request: Request<QueryEventsRequest>,
+    ) -> Result<Response<QueryEventsResponse>, Status> {
+        let call = self.call("QueryEvents", &request)?;
+        call.finish(self.events(request.into_inner()).await)
+    }
+
+    async fn query_transactions(
+        &self,
+        request: Request<QueryTransactionsRequest>,
+    ) -> Result<Response<QueryTransactionsResponse>, Status> {
+        let call = self.call("QueryTransactions", &request)?;
+        call.finish(self.transactions(request.into_inner()).await)
+    }
+
+    type SubscribeEventsStream = EventStream;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let call = self.call("SubscribeEvents", &request)?;
+        call.finish(self.subscribe(request.into_inner()).await)
+    }
+
+    async fn get_status(
+        &self,
+        request: Request<GetStatusRequest>,
+    ) -> Result<Response<GetStatusResponse>, Status> {
+        let call = self.call("GetStatus", &request)?;
+        call.finish(Ok(self.status()))
+    }
+}
+
+fn unavailable(e: eyre::Report) -> Status {
+    Status::unavailable(format!("{e:#}"))
+}
+
+fn timestamp(ms: Option<i64>) -> Result<Option<DateTime<Utc>>, Status> {
+    ms.map(|ms| {
+        DateTime::from_timestamp_millis(ms)
+            .ok_or_else(|| Status::invalid_argument(format!("Timestamp {ms} is out of range")))
+    })
+    .transpose()
+}
+
+fn limit(limit: Option<u32>) -> Result<u32, Status> {
+    match limit.unwrap_or(DEFAULT_LIMIT) {
+        0 => Err(Status::invalid_argument("Limit must be positive")),
+        limit if limit > MAX_LIMIT => Err(Status::invalid_argument(format!(
+            "Limit must be at most {MAX_LIMIT}"
+        ))),
+        limit => Ok(limit),
+    }
+}
+
+fn cursor(token: Option<&str>) -> Result<Option<PageCursor>, Status> {
+    token
+        .map(|token| PageCursor::decode(token).map_err(|e| Status::invalid_argument(e.to_string())))
+        .transpose()
+}
+
+/// Why a subscription ended, with the cursor to resume from in the trailers
+fn feed_status(error: FeedError, cursor: Option<u64>) -> Status {
+    let mut metadata = MetadataMap::new();
+    if let Some(cursor) = cursor {
+        metadata.insert(RESUME_TRAILER, cursor.into());
+    }
+    match error {
+        FeedError::Lagged => {
+            Status::with_metadata(Code::ResourceExhausted, "Subscriber fell behind", metadata)
+        }
+        FeedError::Replay(e) => Status::with_metadata(
+            Code::Unavailable,
+            format!("Failed to replay stored events: {e:#}"),
+            metadata,
+        ),
+    }
+}
+
+impl From<EventRecord> for Event {
+    fn from(record: EventRecord) -> Self {
+        Self {
+            id: record.id.to_string(),
+            checkpoint: record.checkpoint_sequence as u64,
+            transaction_digest: record.transaction_digest,
+            timestamp_ms: record.timestamp.timestamp_millis(),
+            package_id: record.package_id,
+            module: record.module_name,
+            event_type: record.event_type,
+            sender: record.sender,
+            fields_json: record.fields.to_string(),
+        }
+    }
+}
+
+impl From<&ProcessedEvent> for Event {
+    fn from(event: &ProcessedEvent) -> Self {
+        Self {
+            id: event.id.to_string(),
+            checkpoint: event.checkpoint_sequence,
+            transaction_digest: event.transaction_digest.to_string(),
+            timestamp_ms: event.timestamp.timestamp_millis(),
+            package_id: event.package_id.to_string(),
+            module: event.module_name.clone(),
+            event_type: event.event_type.clone(),
+            sender: event.sender.clone(),
+            fields_json: event.fields.to_string(),
+        }
+    }
+}
+
+impl From<LiveBatch> for EventBatch {
+    fn from(batch: LiveBatch) -> Self {
+        Self {
+            checkpoint: batch.checkpoint,
+            events: batch.events.iter().map(Event::from).collect(),
+            caught_up: false,
+        }
+    }
+}
+
+impl From<TransactionRecord> for Transaction {
+    fn from(record: TransactionRecord) -> Self {
+        Self {
+            id: record.id.to_string(),
+            checkpoint: record.checkpoint_sequence as u64,
+            digest: record.digest,
+            timestamp_ms: record.timestamp.timestamp_millis(),
+            sender: record.sender,
+            gas_used: record.gas_used.max(0) as u64,
+            // Stored as the text of the success flag
+            success: record.status == "true",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    /// Service over an empty in-memory store, requiring `token` if one is given
+    fn service(token: Option<&str>) -> Result<IndexerService> {
+        let server = GrpcServer::new(
+            Arc::new(watch::channel(IndexerConfig::default()).0),
+            Arc::new(Metrics::new()?),
+            StorageManager::from_backend(MemoryStorage::new()),
+            LiveEvents::default(),
+            CancellationToken::new(),
+        )
+        .with_token(token.map(str::to_string));
+        Ok(server.service)
+    }
+
+    /// Request carrying `token` as a bearer token
+    fn authorized<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        let value = format!("Bearer {token}").parse().expect("valid header");
+        request.metadata_mut().insert("authorization", value);
+        request
+    }
+
+    async fn events(checkpoint: u64, modules: &[&str]) -> Result<Vec<ProcessedEvent>> {
+        let events = modules
+            .iter()
+            .map(|module| {
+                TestEvent::new(&format!("0x2::{module}::Event"))
+                    .tx(&format!("tx{checkpoint}"))
+                    .build()
+            })
+            .collect();
+        let mut events = DefaultEventProcessor::new().process_events(events).await?;
+        for event in &mut events {
+            event.checkpoint_sequence = checkpoint;
+        }
+        Ok(events)
+    }
+
+    #[tokio::test]
+    async fn test_calls_without_the_token_are_refused() -> Result<()> {
+        let service = service(Some("secret"))?;
+
+        let Err(status) = service
+            .get_status(Request::new(GetStatusRequest::default()))
+            .await
+        else {
+            panic!("a call without a token was accepted");
+        };
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let Err(status) = service
+            .query_events(authorized(QueryEventsRequest::default(), "guess"))
+            .await
+        else {
+            panic!("a call with the wrong token was accepted");
+        };
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let Err(status) = service
+            .subscribe_events(Request::new(SubscribeEventsRequest::default()))
+            .await
+        else {
+            panic!("a subscription without a token was accepted");
+        };
+        assert_eq!(status.code(), Code::Unauthenticated);
+        assert_eq!(service.live.subscribers(), 0);
+
+        service
+            .get_status(authorized(GetStatusRequest::default(), "secret"))
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pages_hold_every_event_once_while_new_ones_arrive() -> Result<()> {
+        let service = service(None)?;
+        for checkpoint in 1..=5 {
+            service
+                .storage
+                .store_events(&events(checkpoint, &["pool"]).await?)
+                .await?;
+        }
+
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let request = QueryEventsRequest {
+                limit: Some(2),
+                cursor,
+                ..QueryEventsRequest::default()
+            };
+            let page = service
+                .query_events(Request::new(request))
+                .await?
+                .into_inner();
+            if pages.is_empty() {
+                // Newer than the cursor, so later pages are unaffected
+                service
+                    .storage
+                    .store_events(&events(6, &["pool"]).await?)
+                    .await?;
+            }
+            pages.push(
+                page.events
+                    .iter()
+                    .map(|event| event.checkpoint)
+                    .collect::<Vec<_>>(),
+            );
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, [vec![5, 4], vec![3, 2], vec![1]]);
+
+        for request in [
+            QueryEventsRequest {
+                limit: Some(0),
+                ..QueryEventsRequest::default()
+            },
+            QueryEventsRequest {
+                limit: Some(MAX_LIMIT + 1),
+                ..QueryEventsRequest::default()
+            },
+            QueryEventsRequest {
+                cursor: Some("not a cursor".to_string()),
+                ..QueryEventsRequest::default()
+            },
+        ] {
+            let Err(status) = service.query_events(Request::new(request)).await else {
+                panic!("an invalid query was accepted");
+            };
+            assert_eq!(status.code(), Code::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_stream_only_matching_events() -> Result<()> {
+        let service = service(None)?;
+        let request = SubscribeEventsRequest {
+            module: Some("pool".to_string()),
+            ..SubscribeEventsRequest::default()
+        };
+        let mut stream = service
+            .subscribe_events(Request::new(request))
+            .await?
+            .into_inner();
+
+        service.live.publish(10, &events(10, &["coin"]).await?);
+        service
+            .live
+            .publish(11, &events(11, &["pool", "coin"]).await?);
+        let batch = stream.next().await.expect("a batch is streamed")?;
+        assert_eq!(batch.checkpoint, 11);
+        assert!(!batch.caught_up);
+        let modules: Vec<_> = batch
+            .events
+            .iter()
+            .map(|event| event.module.as_str())
+            .collect();
+        assert_eq!(modules, ["pool"]);
+
+        let request = SubscribeEventsRequest {
+            filters: vec!["missing".to_string()],
+            ..SubscribeEventsRequest::default()
+        };
+        let Err(status) = service.subscribe_events(Request::new(request)).await else {
+            panic!("a subscription to an unknown filter was accepted");
+        };
+        assert_eq!(status.code(), Code::InvalidArgument);
+        Ok(())
+    }
+}
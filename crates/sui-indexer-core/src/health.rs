@@ -0,0 +1,201 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use sui_indexer_storage::StorageManager;
+
+use crate::{
+    metrics::Metrics,
+    readiness::Readiness,
+    sui::{HealthStatus, SuiClient},
+};
+
+/// Longest a dependency check may take before it counts as failed
+///
+/// Kept below typical probe timeouts so a hung dependency reports as
+/// not ready instead of timing out the probe itself.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Health of the storage backend
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageHealth {
+    pub healthy: bool,
+    pub latency: Option<Duration>,
+    pub error: Option<String>,
+}
+
+/// Outcome of a readiness check
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    /// Whether the indexer should receive traffic
+    pub ready: bool,
+    /// Whether the cold start finished: migrations ran and a checkpoint was fetched
+    pub started: bool,
+    /// Whether the watchdog found ingestion stalled
+    pub stalled: bool,
+    /// Checkpoints behind the network tip, if both ends are known
+    pub lag: Option<u64>,
+    /// Largest lag still reported as ready
+    pub max_lag: u64,
+    /// Node connection
+    pub sui: HealthStatus,
+    /// Storage backend
+    pub storage: StorageHealth,
+}
+
+/// Checks behind the liveness and readiness endpoints of a running indexer
+#[derive(Clone)]
+pub struct HealthProbe {
+    readiness: Readiness,
+    client: SuiClient,
+    storage: StorageManager,
+    metrics: Arc<Metrics>,
+    max_lag: u64,
+}
+
+impl HealthProbe {
+    /// Create a probe reporting ready while lag stays within `max_lag` checkpoints
+    pub fn new(
+        readiness: Readiness,
+        client: SuiClient,
+        storage: StorageManager,
+        metrics: Arc<Metrics>,
+        max_lag: u64,
+    ) -> Self {
+        Self {
+            readiness,
+            client,
+            storage,
+            metrics,
+            max_lag,
+        }
+    }
+
+    /// Whether the cold start finished
+    pub fn is_started(&self) -> bool {
+        self.readiness.is_ready()
+    }
+
+    /// Check the node, the database and the lag between them
+    pub async fn check(&self) -> ReadinessReport {
+        let (sui, storage) = tokio::join!(self.check_sui(), self.check_storage());
+        let started = self.is_started();
+        let stalled = self.readiness.is_stalled();
+
+        // The processed gauge is only meaningful once a checkpoint was processed
+        let processed = self.metrics.latest_processed_checkpoint.get();
+        let lag = sui
+            .latest_checkpoint
+            .filter(|_| started && processed > 0)
+            .map(|network| network.saturating_sub(processed as u64));
+
+        ReadinessReport {
+            ready: started
+                && !stalled
+                && sui.healthy
+                && storage.healthy
+                && lag.is_some_and(|lag| lag <= self.max_lag),
+            started,
+            stalled,
+            lag,
+            max_lag: self.max_lag,
+            sui,
+            storage,
+        }
+    }
+
+    async fn check_sui(&self) -> HealthStatus {
+        let started = Instant::now();
+        let error = match tokio::time::timeout(CHECK_TIMEOUT, self.client.health_check()).await {
+            Ok(Ok(status)) => return status,
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("Timed out after {CHECK_TIMEOUT:?}"),
+        };
+        HealthStatus {
+            healthy: false,
+            latest_checkpoint: None,
+            latency: Some(started.elapsed()),
+            error: Some(error),
+        }
+    }
+
+    async fn check_storage(&self) -> StorageHealth {
+        let started = Instant::now();
+        let result = tokio::time::timeout(CHECK_TIMEOUT, self.storage.health_check()).await;
+        let latency = Some(started.elapsed());
+        match result {
+            Ok(Ok(healthy)) => StorageHealth {
+                healthy,
+                latency,
+                error: (!healthy).then(|| "Storage backend reported unhealthy".to_string()),
+            },
+            Ok(Err(e)) => StorageHealth {
+                healthy: false,
+                latency,
+                error: Some(e.to_string()),
+            },
+            Err(_) => StorageHealth {
+                healthy: false,
+                latency,
+                error: Some(format!("Timed out after {CHECK_TIMEOUT:?}")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+    use crate::sui::MockSuiClient;
+
+    /// Probe of a node at checkpoint 99, allowing a lag of 10
+    fn probe(node: MockSuiClient) -> eyre::Result<(HealthProbe, Readiness, Arc<Metrics>)> {
+        let readiness = Readiness::default();
+        let metrics = Arc::new(Metrics::new()?);
+        let probe = HealthProbe::new(
+            readiness.clone(),
+            SuiClient::mock(node),
+            StorageManager::from_backend(MemoryStorage::new()),
+            metrics.clone(),
+            10,
+        );
+        Ok((probe, readiness, metrics))
+    }
+
+    #[tokio::test]
+    async fn test_ready_only_when_started_caught_up_and_connected() -> eyre::Result<()> {
+        let node = MockSuiClient::synthetic(100, 1)?;
+        let (probe, readiness, metrics) = probe(node.clone())?;
+
+        let report = probe.check().await;
+        assert!(!report.started);
+        assert!(!report.ready);
+        assert_eq!(report.lag, None);
+
+        readiness.set_ready();
+        metrics.latest_processed_checkpoint.set(50);
+        let report = probe.check().await;
+        assert!(report.sui.healthy && report.storage.healthy);
+        assert_eq!(report.lag, Some(49));
+        assert!(!report.ready, "a lag above max_lag is not ready");
+
+        metrics.latest_processed_checkpoint.set(89);
+        let report = probe.check().await;
+        assert_eq!(report.lag, Some(10));
+        assert!(report.ready);
+
+        readiness.set_stalled(true);
+        assert!(!probe.check().await.ready);
+        readiness.set_stalled(false);
+
+        node.fail_requests(1);
+        let report = probe.check().await;
+        assert!(!report.sui.healthy);
+        assert!(!report.ready);
+        Ok(())
+    }
+}
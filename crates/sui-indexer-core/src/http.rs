@@ -0,0 +1,150 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use eyre::Result;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    health::{HealthProbe, ReadinessReport},
+    metrics::Metrics,
+};
+
+/// Shared state of the HTTP server
+#[derive(Clone)]
+struct HttpState {
+    metrics: Arc<Metrics>,
+}
+
+/// Build the router for the indexer's HTTP endpoints
+pub fn router(metrics: Arc<Metrics>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(HttpState { metrics })
+}
+
+/// Serve the metrics and health probes on `addr` until `shutdown` is cancelled
+pub async fn serve(
+    addr: SocketAddr,
+    metrics: Arc<Metrics>,
+    probe: HealthProbe,
+    shutdown: CancellationToken,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "Serving metrics");
+
+    axum::serve(listener, router(metrics).merge(health_router(probe)))
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await?;
+    Ok(())
+}
+
+async fn metrics_handler(State(state): State<HttpState>) -> Response {
+    match state.metrics.encode() {
+        Ok(body) => ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Build the router for the liveness and readiness probes
+///
+/// `/healthz` (also `/livez`) answers as long as the process runs;
+/// `/readyz` answers 200 once the indexer started and the node, the database
+/// and the lag are healthy, and 503 otherwise, with a [`ReadinessReport`] body.
+pub fn health_router(probe: HealthProbe) -> Router {
+    Router::new()
+        .route("/healthz", get(liveness_handler))
+        .route("/livez", get(liveness_handler))
+        .route("/readyz", get(readiness_handler))
+        .with_state(probe)
+}
+
+/// Serve the health probes on `addr` until the process exits
+pub async fn serve_health(addr: SocketAddr, probe: HealthProbe) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(%addr, "Serving health probes");
+
+    axum::serve(listener, health_router(probe)).await?;
+    Ok(())
+}
+
+async fn liveness_handler(State(probe): State<HealthProbe>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "alive": true, "started": probe.is_started() }))
+}
+
+async fn readiness_handler(
+    State(probe): State<HealthProbe>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    let report = probe.check().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_storage::{MemoryStorage, StorageManager};
+
+    use super::*;
+    use crate::{
+        readiness::Readiness,
+        sui::{MockSuiClient, SuiClient},
+    };
+
+    #[tokio::test]
+    async fn test_readyz_answers_503_while_lagging() -> Result<()> {
+        let readiness = Readiness::default();
+        readiness.set_ready();
+        let metrics = Arc::new(Metrics::new()?);
+        metrics.latest_processed_checkpoint.set(50);
+        let probe = HealthProbe::new(
+            readiness,
+            SuiClient::mock(MockSuiClient::synthetic(100, 1)?),
+            StorageManager::from_backend(MemoryStorage::new()),
+            metrics.clone(),
+            10,
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let shutdown = CancellationToken::new();
+        let serve_shutdown = shutdown.clone();
+        let serving = tokio::spawn(async move {
+            axum::serve(listener, health_router(probe))
+                .with_graceful_shutdown(async move { serve_shutdown.cancelled().await })
+                .await
+        });
+
+        let client = reqwest::Client::new();
+        let response = client.get(format!("http://{addr}/readyz")).send().await?;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let report: serde_json::Value = response.json().await?;
+        assert_eq!(report["ready"], false);
+        assert_eq!(report["lag"], 49);
+
+        // Liveness does not depend on the lag
+        let response = client.get(format!("http://{addr}/healthz")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        metrics.latest_processed_checkpoint.set(95);
+        let response = client.get(format!("http://{addr}/readyz")).send().await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let report: serde_json::Value = response.json().await?;
+        assert_eq!(report["ready"], true);
+        assert_eq!(report["lag"], 4);
+
+        shutdown.cancel();
+        serving.await??;
+        Ok(())
+    }
+}
@@ -1,22 +1,158 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
-use eyre::Result;
-use sui_indexer_config::IndexerConfig;
-use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
-use sui_indexer_storage::StorageManager;
+use eyre::{Result, WrapErr};
+use serde::Serialize;
+use sui_indexer_config::{ConfigError, ErrorKind, IndexerConfig, StartMode, WithKind};
+use sui_indexer_events::{
+    system_clock, DefaultEventProcessor, EventFilterProcessor, EventProcessor, ProcessedEvent,
+    ProtocolRegistry, SharedClock, TypedEventRegistry,
+};
+use sui_indexer_sinks::{ChannelSink, SinkManager};
+use sui_indexer_storage::{MemoryStorage, StorageError, StorageManager};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
+pub mod admin;
+pub mod alerts;
+pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod autotune;
+pub mod backfill;
+pub mod benchmark;
+pub mod chain;
+pub mod control;
+pub mod dashboard;
+pub mod doctor;
+pub mod embedded;
+pub mod error;
+pub mod error_history;
+pub mod error_reporting;
+pub mod filter_test;
+#[cfg(feature = "flight")]
+pub mod flight;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod http;
+pub mod json_log;
+pub mod live;
+pub mod maintenance;
+pub mod metrics;
+pub mod move_calls;
+pub mod objects;
+pub mod otlp;
+pub mod packages;
+pub mod pipeline;
+pub mod readiness;
+pub mod recent_errors;
+pub mod reconcile;
+pub mod reload;
+pub mod replay;
+pub mod resources;
+pub mod rules;
+pub mod scheduler;
+#[cfg(any(test, feature = "testing"))]
+pub mod simulation;
+pub mod slo;
+pub mod spill;
+pub mod start;
+pub mod startup;
+pub mod tail;
+pub mod verify;
+pub mod watchdog;
+pub mod watchlists;
 // Local Sui client module
 pub mod sui;
-pub use sui::SuiClient;
+pub use admin::{AdminClient, AdminServer, IndexerStatus};
+pub use alerts::{Alert, AlertKind, AlertManager, Notifier};
+pub use api::ApiServer;
+pub use auth::{ApiAuth, Caller, Denied};
+pub use backfill::{filter_cursor, filter_handoff_cursor, Backfill};
+pub use benchmark::{Benchmark, BenchmarkReport};
+pub use control::{PipelineControl, QueueDepths};
+pub use dashboard::grafana_dashboard;
+pub use doctor::{Doctor, DoctorReport};
+pub use embedded::EventStream;
+pub use error::{is_retryable, IndexerError};
+pub use error_history::{ErrorHistory, PipelineError, PipelineStage};
+pub use error_reporting::{ErrorReport, ErrorReporter, ErrorReporting};
+pub use filter_test::{FilterTest, FilterTestReport};
+#[cfg(feature = "flight")]
+pub use flight::{FlightQuery, FlightServer};
+#[cfg(feature = "grpc")]
+pub use grpc::GrpcServer;
+pub use health::{HealthProbe, ReadinessReport, StorageHealth};
+pub use live::{LiveBatch, LiveEvents, LiveMessage, Subscription};
+pub use maintenance::StorageMaintenance;
+pub use metrics::Metrics;
+pub use move_calls::MoveCallIndexer;
+pub use objects::ObjectTracker;
+pub use packages::PackageTracker;
+pub use pipeline::Pipeline;
+pub use readiness::Readiness;
+pub use recent_errors::RecentErrors;
+pub use reconcile::{Reconciliation, ReconciliationReport};
+pub use reload::ConfigReloader;
+pub use replay::{ReplayEngine, ReplayReport};
+pub use resources::{ResourceMonitor, ResourceUsage};
+pub use rules::{RuleEngine, RuleNotifier};
+pub use scheduler::Scheduler;
+#[cfg(any(test, feature = "testing"))]
+pub use simulation::{Simulation, SimulationReport, Step};
+pub use slo::SloTracker;
+pub use spill::SpillOptions;
+pub use startup::StartupReport;
+#[cfg(any(test, feature = "testing"))]
+pub use sui::MockSuiClient;
+pub use sui::{
+    CheckpointCache, CheckpointPruned, CheckpointRange, CheckpointStats, EndpointHealth,
+    HealthStatus, MoveCall, ObjectChange, ObjectChangeKind, PublishedPackage, SuiClient,
+};
+pub use tail::Tail;
+pub use verify::{Verify, VerifyReport};
+pub use watchdog::Watchdog;
+pub use watchlists::WatchlistLoader;
+
+/// Time allowed on shutdown for error reports still being delivered
+const REPORT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events buffered for an [`EventStream`] before the pipeline waits for the reader
+const EVENT_STREAM_CAPACITY: usize = 1024;
+
+/// Health of each component checked by [`IndexerCore::health`]
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Whether every component is healthy
+    pub healthy: bool,
+    /// Node connection
+    pub sui: HealthStatus,
+    /// Whether the storage backend answers
+    pub storage: bool,
+}
 
 /// Core indexer service
 #[derive(Clone)]
 pub struct IndexerCore {
-    config: IndexerConfig,
+    config: Arc<watch::Sender<IndexerConfig>>,
     sui_client: SuiClient,
     storage: StorageManager,
-    _event_processor: Arc<dyn EventProcessor>, // TODO: Integrate with gRPC event processing
+    event_processor: Arc<dyn EventProcessor>,
+    metrics: Arc<Metrics>,
+    config_path: Option<PathBuf>,
+    readiness: Readiness,
+    control: PipelineControl,
+    recent_errors: RecentErrors,
+    error_reporters: Vec<Arc<dyn ErrorReporter>>,
+    live: LiveEvents,
+    scheduler: Scheduler,
+    clock: SharedClock,
+    /// Cores of the other networks in `networks`, run along with this one
+    networks: Vec<(String, IndexerCore)>,
 }
 
 impl IndexerCore {
@@ -24,16 +160,13 @@ impl IndexerCore {
     pub async fn new(config: IndexerConfig) -> Result<Self> {
         info!("Initializing Sui Indexer Core");
 
-        let sui_client = SuiClient::new_grpc_only(config.network.clone()).await?;
-        let storage = StorageManager::new_postgres(config.database.clone()).await?;
-        let event_processor = Arc::new(DefaultEventProcessor::new());
-
-        Ok(Self {
-            config,
-            sui_client,
-            storage,
-            _event_processor: event_processor,
-        })
+        let storage =
+            StorageManager::new_postgres(config.database.clone(), config.storage.clone()).await?;
+        let event_processor = Self::default_event_processor(&config)?;
+        Self::with_storage(config, storage, event_processor.clone())
+            .await?
+            .with_network_contexts(event_processor)
+            .await
     }
 
     /// Create indexer with custom event processor
@@ -43,19 +176,320 @@ impl IndexerCore {
     ) -> Result<Self> {
         info!("Initializing Sui Indexer Core with custom event processor");
 
-        let sui_client = SuiClient::new_grpc_only(config.network.clone()).await?;
-        let storage = StorageManager::new_postgres(config.database.clone()).await?;
+        let storage =
+            StorageManager::new_postgres(config.database.clone(), config.storage.clone()).await?;
+        Self::with_storage(config, storage, event_processor.clone())
+            .await?
+            .with_network_contexts(event_processor)
+            .await
+    }
+
+    /// Create an indexer to embed in an application, without a database
+    ///
+    /// Progress is kept in memory only, so every run starts according to
+    /// `events.start_mode`. Read the events with [`IndexerCore::start_with_stream`].
+    /// No control endpoint is opened in the host process unless `admin.addr`
+    /// is set.
+    pub async fn embedded(config: IndexerConfig) -> Result<Self> {
+        info!("Initializing embedded Sui Indexer Core");
+
+        let event_processor = Self::default_event_processor(&config)?;
+        let storage = StorageManager::from_backend(MemoryStorage::cursors_only());
+        Self::with_storage(config, storage, event_processor).await
+    }
+
+    /// Create indexer with the given storage and event processor
+    ///
+    /// The other networks in `networks` are not indexed, as they need storage
+    /// of their own; [`IndexerCore::new`] creates it.
+    pub async fn with_storage(
+        config: IndexerConfig,
+        storage: StorageManager,
+        event_processor: Arc<dyn EventProcessor>,
+    ) -> Result<Self> {
+        let metrics = if config.networks.is_empty() {
+            Metrics::new()?
+        } else {
+            Metrics::for_network(config.context_name())?
+        };
+        Self::assemble(config, storage, event_processor, Arc::new(metrics)).await
+    }
+
+    async fn assemble(
+        config: IndexerConfig,
+        storage: StorageManager,
+        event_processor: Arc<dyn EventProcessor>,
+        metrics: Arc<Metrics>,
+    ) -> Result<Self> {
+        let mut sui_client = SuiClient::new_grpc_only(config.network.clone())
+            .await?
+            .with_metrics(metrics.clone());
+        if let Some(dir) = &config.runtime.checkpoint_cache_dir {
+            let dir = sui::chain_dir(dir, &sui_client, &config.network).await?;
+            let cache = CheckpointCache::open(dir, config.runtime.checkpoint_cache_limit)
+                .await
+                .kind(ErrorKind::Config)?;
+            sui_client = sui_client.with_checkpoint_cache(cache);
+        }
 
         Ok(Self {
-            config,
+            scheduler: Scheduler::from_config(&config.runtime.scheduler),
+            config: Arc::new(watch::channel(config).0),
             sui_client,
             storage,
-            _event_processor: event_processor,
+            event_processor,
+            metrics,
+            config_path: None,
+            readiness: Readiness::default(),
+            control: PipelineControl::default(),
+            recent_errors: RecentErrors::default(),
+            error_reporters: Vec::new(),
+            live: LiveEvents::default(),
+            clock: system_clock(),
+            networks: Vec::new(),
+        })
+    }
+
+    /// Add a core for every network in `networks`, each with its own client and storage
+    ///
+    /// Their metrics are exported with this core's, labelled by `network`.
+    async fn with_network_contexts(
+        mut self,
+        event_processor: Arc<dyn EventProcessor>,
+    ) -> Result<Self> {
+        for (name, config) in self.config().network_contexts() {
+            info!(network = %name, schema = ?config.database.schema, "Initializing network context");
+            let storage =
+                StorageManager::new_postgres(config.database.clone(), config.storage.clone())
+                    .await
+                    .wrap_err_with(|| format!("Failed to open the storage of network `{name}`"))?;
+            let metrics = Arc::new(Metrics::for_network(&name)?);
+            self.metrics.link(&metrics);
+            let core = Self::assemble(config, storage, event_processor.clone(), metrics).await?;
+            self.networks.push((name, core));
+        }
+        Ok(self)
+    }
+
+    /// Event processor decoding the configured protocols
+    fn default_event_processor(config: &IndexerConfig) -> Result<Arc<dyn EventProcessor>> {
+        let protocols = ProtocolRegistry::from_config(&config.protocols)?;
+        Ok(Arc::new(DefaultEventProcessor::with_protocols(Arc::new(
+            protocols,
+        ))))
+    }
+
+    /// Record the file the configuration was loaded from, re-read on control endpoint reloads
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Time the live pipeline with `clock` instead of the system clock
+    ///
+    /// Event processors keep their own clock, e.g. the one given to
+    /// [`DefaultEventProcessor::with_clock`].
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        for (_, core) in &mut self.networks {
+            core.clock = clock.clone();
+            core.sui_client = core.sui_client.clone().with_clock(clock.clone());
+        }
+        self.sui_client = self.sui_client.clone().with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// Store the events of `registry`'s typed events in tables of their own as well
+    ///
+    /// Call before [`IndexerCore::initialize`], which creates and migrates the tables.
+    pub fn with_typed_events(mut self, registry: TypedEventRegistry) -> Self {
+        self.networks = std::mem::take(&mut self.networks)
+            .into_iter()
+            .map(|(name, core)| (name, core.with_typed_events(registry.clone())))
+            .collect();
+        self.storage = self.storage.with_typed_events(registry);
+        self
+    }
+
+    /// Signal readiness by creating `path`, which is removed again on shutdown
+    pub fn with_readiness_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.readiness = Readiness::with_file(path);
+        self
+    }
+
+    /// Whether the indexer has finished its cold start
+    pub fn readiness(&self) -> Readiness {
+        self.readiness.clone()
+    }
+
+    /// Report the warnings and errors recorded in `errors` on the control endpoint
+    pub fn with_recent_errors(mut self, errors: RecentErrors) -> Self {
+        self.recent_errors = errors;
+        self
+    }
+
+    /// Forward panics and errors that stop the pipeline to `reporter`, besides `observability.error_reporting`
+    pub fn with_error_reporter(mut self, reporter: Arc<dyn ErrorReporter>) -> Self {
+        self.error_reporters.push(reporter);
+        self
+    }
+
+    /// Pause switch and queue depths of the live pipeline
+    pub fn control(&self) -> PipelineControl {
+        self.control.clone()
+    }
+
+    /// Cores of the other networks in `networks`, by context name
+    pub fn network_contexts(&self) -> impl Iterator<Item = (&str, &IndexerCore)> {
+        self.networks
+            .iter()
+            .map(|(name, core)| (name.as_str(), core))
+    }
+
+    /// Committed events as they are stored, for live subscribers
+    pub fn live_events(&self) -> LiveEvents {
+        self.live.clone()
+    }
+
+    /// Checks behind the `/healthz` and `/readyz` endpoints
+    pub fn health_probe(&self) -> HealthProbe {
+        HealthProbe::new(
+            self.readiness.clone(),
+            self.sui_client.clone(),
+            self.storage.clone(),
+            self.metrics.clone(),
+            self.config.borrow().observability.ready_max_lag,
+        )
+    }
+
+    /// Get a snapshot of the current configuration
+    pub fn config(&self) -> IndexerConfig {
+        self.config.borrow().clone()
+    }
+
+    /// Metrics recorded by the indexer
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Storage the indexer writes to
+    pub fn storage(&self) -> StorageManager {
+        self.storage.clone()
+    }
+
+    /// Subscribe to configuration updates applied by hot-reload
+    pub fn subscribe_config(&self) -> watch::Receiver<IndexerConfig> {
+        self.config.subscribe()
+    }
+
+    /// Reload the configuration file on SIGHUP or when it changes on disk
+    pub fn watch_config_file(&self, path: impl Into<PathBuf>) -> JoinHandle<()> {
+        ConfigReloader::new(path, self.config.clone()).spawn()
+    }
+
+    /// Prepare a backfill of `range` using the configured client, storage and processor
+    pub fn backfill(&self, range: CheckpointRange) -> Backfill {
+        let config = self.config.borrow();
+        Backfill::new(
+            self.sui_client.clone(),
+            self.storage.clone(),
+            self.event_processor.clone(),
+            range,
+        )
+        .with_concurrency(config.runtime.fetch_concurrency)
+        .with_batch_size(config.events.batch_size)
+        .with_retry(config.network.retry.clone())
+        .with_scheduler(self.scheduler.clone())
+        .with_spill(SpillOptions {
+            dir: config
+                .runtime
+                .backfill_spill_dir
+                .clone()
+                .unwrap_or_else(std::env::temp_dir),
+            memory_events: config.runtime.backfill_memory_events,
+            disk_bytes: config.runtime.backfill_spill_limit,
         })
     }
 
-    /// Initialize the indexer (run migrations, etc.)
+    /// Prepare a re-index of the configured filter `name` from `from` up to where the live pipeline took it over
+    ///
+    /// The live pipeline records the first checkpoint it applied the filter to,
+    /// when it starts with the filter or reloads a configuration adding or
+    /// changing it; the re-index ends right before that checkpoint. Progress is
+    /// kept in the filter's own cursor, so a later run resumes after the last
+    /// re-indexed checkpoint; `None` means there is nothing left to do. Events
+    /// that another configured filter also matches are skipped, since the live
+    /// pipeline already stored them.
+    pub async fn reindex_filter(&self, name: &str, from: u64) -> Result<Option<Backfill>> {
+        let filters = self.config.borrow().events.filters.clone();
+        let (filter, others): (Vec<_>, Vec<_>) = filters
+            .into_iter()
+            .partition(|filter| filter.name.as_deref() == Some(name));
+        if filter.is_empty() {
+            return Err(ConfigError::UnknownFilter(name.to_string()).into());
+        }
+
+        let handoff = self
+            .storage
+            .get_cursor(&filter_handoff_cursor(name))
+            .await?
+            .ok_or_else(|| IndexerError::FilterNotLive(name.to_string()))?;
+        let cursor = filter_cursor(name);
+        let start = match self.storage.get_cursor(&cursor).await? {
+            Some(reindexed) => from.max(reindexed + 1),
+            None => from,
+        };
+        let Some(end) = handoff.checked_sub(1).filter(|end| start <= *end) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            self.backfill(CheckpointRange::new(start, end)?)
+                .with_filters(filter)
+                .with_excluded_filters(others)
+                .with_cursor(cursor),
+        ))
+    }
+
+    /// Prepare a benchmark of the latest `checkpoints` checkpoints, storing events unless `store` is off
+    pub fn benchmark(&self, checkpoints: u64, store: bool) -> Benchmark {
+        let config = self.config.borrow();
+        Benchmark::new(
+            self.sui_client.clone(),
+            store.then(|| self.storage.clone()),
+            self.event_processor.clone(),
+            checkpoints,
+        )
+        .with_filters(config.events.filters.clone())
+        .with_concurrency(config.runtime.fetch_concurrency)
+        .with_batch_size(config.events.batch_size)
+    }
+
+    /// Prepare a verification of `range` against the configured storage
+    pub fn verify(&self, range: CheckpointRange) -> Verify {
+        let config = self.config.borrow();
+        Verify::new(
+            self.sui_client.clone(),
+            self.storage.clone(),
+            self.event_processor.clone(),
+            range,
+        )
+        .with_filters(config.events.filters.clone())
+        .with_concurrency(config.runtime.fetch_concurrency)
+    }
+
+    /// Initialize the indexer (run migrations, etc.), and those of the other networks
     pub async fn initialize(&self) -> Result<()> {
+        self.initialize_storage().await?;
+        for (name, core) in &self.networks {
+            core.initialize_storage()
+                .await
+                .wrap_err_with(|| format!("Failed to initialize network `{name}`"))?;
+        }
+        Ok(())
+    }
+
+    async fn initialize_storage(&self) -> Result<()> {
         info!("Initializing storage backend");
 
         // Try to initialize with timeout
@@ -76,144 +510,526 @@ impl IndexerCore {
             }
             Err(_) => {
                 error!("Storage initialization timed out after 30 seconds");
-                Err(eyre::eyre!("Storage initialization timeout"))
+                Err(eyre::Report::from(StorageError::Unavailable)
+                    .wrap_err("Storage initialization timed out after 30 seconds"))
             }
         }
     }
 
     /// Start the indexer service
     pub async fn start(&self) -> Result<()> {
-        info!("✅ Sui Indexer started successfully!");
-        info!("🌐 Network: {} (using gRPC)", self.config.network.network);
-        info!("🔗 gRPC URL: {}", self.config.network.grpc_url);
-        info!("💾 Database: PostgreSQL (connected and migrated)");
-        info!("📊 Event batch size: {}", self.config.events.batch_size);
-        info!(
-            "🔄 Max concurrent batches: {}",
-            self.config.events.max_concurrent_batches
-        );
+        let shutdown = CancellationToken::new();
+        let signal_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                info!("Received Ctrl+C, shutting down");
+                signal_shutdown.cancel();
+            }
+        });
 
-        // Display the configured event filters
+        self.run(shutdown).await?;
+
+        info!("Indexer shutdown complete");
+        Ok(())
+    }
+
+    /// Run the indexer in the background, handing every committed event to the returned stream
+    ///
+    /// The stream is fed like the configured sinks, once events are committed;
+    /// a reader that falls behind holds back the pipeline rather than missing events.
+    pub async fn start_with_stream(&self) -> Result<EventStream> {
+        self.initialize().await?;
+
+        let (tx, rx) = mpsc::channel(EVENT_STREAM_CAPACITY);
+        let shutdown = CancellationToken::new();
+        let core = self.clone();
+        let token = shutdown.clone();
+        let task = tokio::spawn(async move { core.run_networks(token, Some(tx)).await });
+        Ok(EventStream::new(rx, shutdown, task))
+    }
+
+    /// Run the checkpoint pipeline, and those of the other networks, until `shutdown` is cancelled
+    pub async fn run(&self, shutdown: CancellationToken) -> Result<()> {
+        self.run_networks(shutdown, None).await
+    }
+
+    /// Run this pipeline with `stream` and every other network's next to it
+    ///
+    /// A network that fails stops the others, so the process exits and is
+    /// restarted as a whole rather than indexing some networks only.
+    async fn run_networks(
+        &self,
+        shutdown: CancellationToken,
+        stream: Option<mpsc::Sender<ProcessedEvent>>,
+    ) -> Result<()> {
+        if self.networks.is_empty() {
+            return self.run_pipeline(shutdown, stream).await;
+        }
+
+        let stop = shutdown.child_token();
+        let run = |name: &str, core: &Self, stream| {
+            let stop = stop.clone();
+            let name = name.to_string();
+            let core = core.clone();
+            tokio::spawn(async move {
+                let result = core.run_pipeline(stop.clone(), stream).await;
+                if let Err(e) = &result {
+                    error!(network = %name, error = %e, "Network stopped, stopping the others");
+                    stop.cancel();
+                }
+                result.wrap_err_with(|| format!("Network `{name}` failed"))
+            })
+        };
+        let mut tasks = vec![run(self.config.borrow().context_name(), self, stream)];
+        for (name, core) in &self.networks {
+            tasks.push(run(name, core, None));
+        }
+
+        let mut result = Ok(());
+        for task in tasks {
+            let outcome = task
+                .await
+                .map_err(eyre::Report::from)
+                .and_then(|outcome| outcome);
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+        result
+    }
+
+    /// Run the checkpoint pipeline, also sending committed events to `stream` if given
+    async fn run_pipeline(
+        &self,
+        shutdown: CancellationToken,
+        stream: Option<mpsc::Sender<ProcessedEvent>>,
+    ) -> Result<()> {
+        // A readiness file left behind by a crashed run must not claim readiness
+        self.readiness.set_not_ready();
+        self.verify_chain().await?;
+        let start_checkpoint = self.resolve_start_checkpoint().await?;
+        let report = Arc::new(self.startup_report(start_checkpoint));
         info!(
-            "📋 Configured {} event filter(s):",
-            self.config.events.filters.len()
+            report = %serde_json::to_string(&*report)?,
+            "Startup report"
         );
-        for (i, filter) in self.config.events.filters.iter().enumerate() {
-            info!(
-                "   {}. Package: {}, Module: {}, Event: {}",
-                i + 1,
-                filter.package.as_deref().unwrap_or("*"),
-                filter.module.as_deref().unwrap_or("*"),
-                filter.event_type.as_deref().unwrap_or("*")
-            );
+        self.metrics
+            .error_history
+            .set_capacity(self.config.borrow().observability.error_history);
+        self.metrics
+            .slos
+            .configure(&self.config.borrow().observability.slos);
+        let mut reporting =
+            ErrorReporting::from_config(&self.config.borrow().observability.error_reporting)
+                .kind(ErrorKind::Config)?;
+        for reporter in &self.error_reporters {
+            reporting = reporting.with_reporter(reporter.clone());
+        }
+        if reporting.is_enabled() {
+            reporting.install_panic_hook();
         }
 
-        info!("");
-        info!("🎉 Sui event indexing is now active!");
-        info!("💡 This is a generic Sui blockchain event indexer");
-        info!("⚡ Ready to capture events in real-time based on your configuration");
-        info!("");
+        let maintenance =
+            StorageMaintenance::new(self.storage.clone(), self.config.borrow().storage.clone());
+        tokio::spawn(maintenance.run(shutdown.clone()));
+
+        let mut sinks = {
+            let config = self.config();
+            SinkManager::from_config(
+                &config.sinks,
+                &config.events,
+                &config.outbox,
+                Some(&self.storage),
+            )
+            .await?
+        };
+        if let Some(stream) = stream {
+            sinks.add(
+                "stream".to_string(),
+                Box::new(ChannelSink::new(stream)),
+                EventFilterProcessor::default(),
+            );
+        }
+        for (name, watchlist) in &self.config.borrow().watchlists {
+            sinks.route(&watchlist.tag_for(name), &watchlist.sinks);
+        }
 
-        // Start the event monitoring loop
-        let mut shutdown_signal = Box::pin(tokio::signal::ctrl_c());
-        let mut event_monitor_interval = tokio::time::interval(std::time::Duration::from_secs(10));
+        let alerts = AlertManager::new(self.config.borrow().alerts.clone(), self.metrics.clone())?
+            .with_dead_letters(sinks.dead_letters());
+        if alerts.is_enabled() {
+            tokio::spawn(alerts.run(shutdown.clone()));
+        }
 
-        info!("🔍 Starting event monitoring loop...");
-        info!("📡 Polling for events every 10 seconds");
+        let resources = ResourceMonitor::new(self.storage.clone(), self.metrics.clone());
+        tokio::spawn(resources.run(shutdown.clone()));
 
-        loop {
-            tokio::select! {
-                _ = &mut shutdown_signal => {
-                    info!("✋ Received shutdown signal (Ctrl+C)");
-                    info!("🛑 Stopping Sui indexer...");
-                    break;
+        let auth = self.api_auth(&shutdown).await?;
+        let admin = self.config.borrow().admin.clone();
+        if let Some(addr) = admin.addr {
+            let token = admin
+                .token
+                .as_ref()
+                .map(|token| token.resolve())
+                .transpose()
+                .kind(ErrorKind::Config)?;
+            let core = self.clone();
+            let admin =
+                AdminServer::new(self.config.clone(), self.metrics.clone(), shutdown.clone())
+                    .with_config_path(self.config_path.clone())
+                    .with_storage(self.storage.clone())
+                    .with_sui_client(self.sui_client.clone())
+                    .with_token(token)
+                    .with_api_auth(auth.clone())
+                    .with_control(self.control.clone())
+                    .with_recent_errors(self.recent_errors.clone())
+                    .with_startup_report(report.clone())
+                    .with_backfill(move |range| core.backfill(range));
+            tokio::spawn(async move {
+                if let Err(e) = admin.serve(addr).await {
+                    error!(%addr, error = %e, "Control endpoint failed");
                 }
-                _ = event_monitor_interval.tick() => {
-                    if let Err(e) = self.poll_and_process_events().await {
-                        error!("❌ Error during event polling: {}", e);
-                    }
+            });
+        }
+
+        self.serve_api(auth.clone(), shutdown.clone())?;
+        self.serve_grpc(auth.clone(), shutdown.clone())?;
+        self.serve_flight(auth, shutdown.clone())?;
+
+        if let Some(addr) = self.config.borrow().observability.metrics_addr {
+            let metrics = self.metrics.clone();
+            let probe = self.health_probe();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                if let Err(e) = http::serve(addr, metrics, probe, shutdown).await {
+                    error!(%addr, error = %e, "Metrics server failed");
                 }
+            });
+        }
+
+        let mut pipeline = Pipeline::new(
+            self.sui_client.clone(),
+            self.storage.clone(),
+            self.event_processor.clone(),
+            self.subscribe_config(),
+            self.metrics.clone(),
+        )
+        .with_sinks(sinks)
+        .with_readiness(self.readiness.clone())
+        .with_control(self.control.clone())
+        .with_error_reporting(reporting.clone())
+        .with_live(self.live.clone())
+        .with_scheduler(self.scheduler.clone())
+        .with_clock(self.clock.clone());
+        let (packages, move_calls, objects) = {
+            let config = self.config.borrow();
+            (
+                PackageTracker::from_config(&config.packages),
+                MoveCallIndexer::from_config(&config.move_calls),
+                ObjectTracker::from_config(&config.objects),
+            )
+        };
+        let lineage = packages.as_ref().map(PackageTracker::lineage);
+        if let Some(packages) = packages {
+            packages.load(&self.storage).await?;
+            pipeline = pipeline.with_packages(packages);
+        }
+        if let Some(mut move_calls) = move_calls {
+            if let Some(lineage) = lineage {
+                move_calls = move_calls.with_lineage(lineage);
             }
+            pipeline = pipeline.with_move_calls(move_calls);
+        }
+        if let Some(objects) = objects {
+            pipeline = pipeline.with_objects(objects);
+        }
+        if !self.config.borrow().watchlists.is_empty() {
+            let loader = WatchlistLoader::new(
+                self.subscribe_config(),
+                self.storage.clone(),
+                self.metrics.clone(),
+            );
+            loader.load().await.kind(ErrorKind::Config)?;
+            pipeline = pipeline.with_watchlists(loader.watchlists());
+            tokio::spawn(loader.run(shutdown.clone()));
+        }
+        let rules = RuleEngine::from_config(&self.config(), self.metrics.clone())
+            .kind(ErrorKind::Config)?;
+        if let Some((rules, notifier)) = rules {
+            pipeline = pipeline.with_rules(rules);
+            tokio::spawn(
+                notifier
+                    .with_clock(self.clock.clone())
+                    .run(shutdown.clone()),
+            );
         }
 
-        info!("💤 Indexer shutdown complete. Goodbye!");
+        let watchdog = self.config.borrow().watchdog.clone();
+        if watchdog.enabled {
+            let watchdog = Watchdog::new(
+                watchdog,
+                self.metrics.clone(),
+                self.readiness.clone(),
+                self.control.clone(),
+            );
+            pipeline = pipeline.with_restarts(watchdog.restarts());
+            tokio::spawn(watchdog.run(shutdown.clone()));
+        }
+        let result = pipeline.run(start_checkpoint, shutdown).await;
+        self.readiness.set_not_ready();
+        reporting.flush(REPORT_FLUSH_TIMEOUT).await;
+        result
+    }
+
+    /// API keys of `auth`, reloading the stored ones until `shutdown` if `auth.database_keys` is on
+    async fn api_auth(&self, shutdown: &CancellationToken) -> Result<ApiAuth> {
+        let config = self.config.borrow().auth.clone();
+        let auth = ApiAuth::from_config(&config).kind(ErrorKind::Config)?;
+        if config.database_keys {
+            auth.refresh(&self.storage).await?;
+            tokio::spawn(auth.clone().run(
+                self.storage.clone(),
+                Duration::from_secs(config.refresh_interval),
+                shutdown.clone(),
+            ));
+        }
+        Ok(auth)
+    }
+
+    /// Start the HTTP API if `api.addr` is set
+    fn serve_api(&self, auth: ApiAuth, shutdown: CancellationToken) -> Result<()> {
+        let api = self.config.borrow().api.clone();
+        let Some(addr) = api.addr else {
+            return Ok(());
+        };
+        let token = api
+            .token
+            .as_ref()
+            .map(|token| token.resolve())
+            .transpose()
+            .kind(ErrorKind::Config)?;
+        let server = ApiServer::new(
+            self.config.clone(),
+            self.storage.clone(),
+            self.live.clone(),
+            shutdown,
+        )
+        .with_token(token)
+        .with_api_auth(auth);
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(addr).await {
+                error!(%addr, error = %e, "HTTP API failed");
+            }
+        });
+        Ok(())
+    }
+
+    /// Start the gRPC API if `grpc.addr` is set
+    #[cfg(feature = "grpc")]
+    fn serve_grpc(&self, auth: ApiAuth, shutdown: CancellationToken) -> Result<()> {
+        let grpc = self.config.borrow().grpc.clone();
+        let Some(addr) = grpc.addr else {
+            return Ok(());
+        };
+        let token = grpc
+            .token
+            .as_ref()
+            .map(|token| token.resolve())
+            .transpose()
+            .kind(ErrorKind::Config)?;
+        let server = GrpcServer::new(
+            self.config.clone(),
+            self.metrics.clone(),
+            self.storage.clone(),
+            self.live.clone(),
+            shutdown,
+        )
+        .with_token(token)
+        .with_api_auth(auth)
+        .with_control(self.control.clone());
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(addr).await {
+                error!(%addr, error = %e, "gRPC API failed");
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    fn serve_grpc(&self, _auth: ApiAuth, _shutdown: CancellationToken) -> Result<()> {
+        if self.config.borrow().grpc.addr.is_some() {
+            return Err(IndexerError::FeatureDisabled {
+                what: "The gRPC API",
+                feature: "grpc",
+            }
+            .into())
+            .kind(ErrorKind::Config);
+        }
         Ok(())
     }
 
-    /// Poll for new events and process them
-    async fn poll_and_process_events(&self) -> Result<()> {
-        info!("🔍 Polling for new events...");
+    /// Start the Arrow Flight endpoint if `flight.addr` is set
+    #[cfg(feature = "flight")]
+    fn serve_flight(&self, auth: ApiAuth, shutdown: CancellationToken) -> Result<()> {
+        let flight = self.config.borrow().flight.clone();
+        let Some(addr) = flight.addr else {
+            return Ok(());
+        };
+        let token = flight
+            .token
+            .as_ref()
+            .map(|token| token.resolve())
+            .transpose()
+            .kind(ErrorKind::Config)?;
+        let server = FlightServer::new(self.storage.clone(), flight.batch_size, shutdown)
+            .with_token(token)
+            .with_api_auth(auth);
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(addr).await {
+                error!(%addr, error = %e, "Arrow Flight endpoint failed");
+            }
+        });
+        Ok(())
+    }
 
-        // Get latest checkpoint
-        match self.sui_client.get_latest_checkpoint().await {
-            Ok(latest_checkpoint) => {
-                info!("📊 Latest checkpoint: {}", latest_checkpoint);
+    #[cfg(not(feature = "flight"))]
+    fn serve_flight(&self, _auth: ApiAuth, _shutdown: CancellationToken) -> Result<()> {
+        if self.config.borrow().flight.addr.is_some() {
+            return Err(IndexerError::FeatureDisabled {
+                what: "The Arrow Flight endpoint",
+                feature: "flight",
+            }
+            .into())
+            .kind(ErrorKind::Config);
+        }
+        Ok(())
+    }
 
-                // Try to query events for each configured filter
-                for (i, filter) in self.config.events.filters.iter().enumerate() {
+    /// Resolved configuration, features and backends of a run starting at `start_checkpoint`
+    pub fn startup_report(&self, start_checkpoint: u64) -> StartupReport {
+        let endpoints = self
+            .sui_client
+            .endpoint_health()
+            .into_iter()
+            .map(|endpoint| endpoint.endpoint)
+            .collect();
+        StartupReport::new(
+            &self.config.borrow(),
+            self.storage.backend_name(),
+            endpoints,
+            start_checkpoint,
+        )
+    }
+
+    /// Refuse to index if the node, the configured network and the database are of different chains
+    pub async fn verify_chain(&self) -> Result<Option<String>> {
+        let network = self.config.borrow().network.clone();
+        chain::verify_chain(&self.sui_client, &self.storage, &network).await
+    }
+
+    /// Determine the first checkpoint to index according to `events.start_mode`
+    ///
+    /// Only `resume` continues after the stored cursor; the other modes always
+    /// start from the checkpoint they select.
+    async fn resolve_start_checkpoint(&self) -> Result<u64> {
+        let (start_mode, start_checkpoint) = {
+            let config = self.config.borrow();
+            (config.events.start_mode, config.events.start_checkpoint)
+        };
+        let stored = self.storage.get_latest_checkpoint().await?;
+
+        if let Some(stored) = stored.filter(|_| start_mode != StartMode::Resume) {
+            info!(
+                stored,
+                ?start_mode,
+                "Ignoring stored checkpoint because start mode is not resume"
+            );
+        }
+
+        match start_mode {
+            StartMode::Genesis => {
+                info!(
+                    start_mode = "genesis",
+                    checkpoint = 0,
+                    "Resolved start checkpoint"
+                );
+                Ok(0)
+            }
+            StartMode::Latest => {
+                let latest = self.sui_client.get_latest_checkpoint().await?;
+                info!(
+                    start_mode = "latest",
+                    checkpoint = latest,
+                    "Resolved start checkpoint"
+                );
+                Ok(latest)
+            }
+            StartMode::Checkpoint(checkpoint) => {
+                info!(
+                    start_mode = "checkpoint",
+                    checkpoint, "Resolved start checkpoint"
+                );
+                Ok(checkpoint)
+            }
+            StartMode::Timestamp(at) => {
+                let latest = self.sui_client.get_latest_checkpoint().await?;
+                let checkpoint = start::checkpoint_at_time(
+                    &self.sui_client,
+                    latest,
+                    at.timestamp_millis().max(0) as u64,
+                )
+                .await?;
+                info!(
+                    start_mode = "timestamp",
+                    %at,
+                    checkpoint,
+                    "Resolved start checkpoint"
+                );
+                Ok(checkpoint)
+            }
+            StartMode::Resume => {
+                if let Some(last_processed) = stored {
                     info!(
-                        "🔎 Checking filter {}: Package={:?}, Module={:?}, Event={:?}",
-                        i + 1,
-                        filter.package,
-                        filter.module,
-                        filter.event_type
+                        start_mode = "resume",
+                        stored = last_processed,
+                        checkpoint = last_processed + 1,
+                        "Resolved start checkpoint, continuing after the stored one"
                     );
-
-                    match self
-                        .sui_client
-                        .query_events(
-                            None,                   // transaction_digest
-                            None,                   // sender
-                            filter.package.clone(), // package_id
-                            None,                   // cursor
-                            Some(50),               // limit
-                            false,                  // descending_order
-                        )
-                        .await
-                    {
-                        Ok(events) => {
-                            if events.data.is_empty() {
-                                info!("📭 No events found for filter {}", i + 1);
-                            } else {
-                                info!("📬 Found {} events for filter {}", events.data.len(), i + 1);
-
-                                // For now, just log the events since we need to convert types
-                                // TODO: Convert sui_indexer_sui::Event to SuiEvent for processing
-                                for (j, event) in events.data.iter().enumerate() {
-                                    info!(
-                                        "  📄 Event {}: Type={:?}, Package={:?}",
-                                        j + 1,
-                                        event.event_type,
-                                        event.package_id
-                                    );
-                                }
-
-                                info!(
-                                    "✅ Listed {} events (processing integration pending)",
-                                    events.data.len()
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            error!("❌ Failed to query events for filter {}: {}", i + 1, e);
-                        }
-                    }
+                    return Ok(last_processed + 1);
                 }
-            }
-            Err(e) => {
-                error!("❌ Failed to get latest checkpoint: {}", e);
+                if let Some(start_checkpoint) = start_checkpoint {
+                    info!(
+                        start_mode = "resume",
+                        checkpoint = start_checkpoint,
+                        "Resolved start checkpoint, nothing stored so using the configured one"
+                    );
+                    return Ok(start_checkpoint);
+                }
+                let latest = self.sui_client.get_latest_checkpoint().await?;
+                info!(
+                    start_mode = "resume",
+                    checkpoint = latest,
+                    "Resolved start checkpoint, nothing stored so using the latest one"
+                );
+                Ok(latest)
             }
         }
-
-        Ok(())
     }
 
     /// Health check
     pub async fn health_check(&self) -> Result<bool> {
-        let sui_healthy = self.sui_client.health_check().await?.healthy;
-        let storage_healthy = self.storage.health_check().await?;
+        Ok(self.health().await?.healthy)
+    }
+
+    /// Health of the node connection and storage backend
+    pub async fn health(&self) -> Result<HealthReport> {
+        let sui = self.sui_client.health_check().await?;
+        let storage = self.storage.health_check().await?;
 
-        Ok(sui_healthy && storage_healthy)
+        Ok(HealthReport {
+            healthy: sui.healthy && storage,
+            sui,
+            storage,
+        })
     }
 }
 
@@ -0,0 +1,450 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use sui_indexer_config::{EventFilter, FilterPriority, IndexerConfig};
+use sui_indexer_events::{EventFilterProcessor, ProcessedEvent};
+use sui_indexer_storage::StorageManager;
+use tokio::sync::{broadcast, watch};
+
+use crate::error::IndexerError;
+
+/// Committed checkpoints buffered per subscriber before it counts as lagging
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Stored checkpoints read per query while replaying from a cursor
+const REPLAY_CHUNK: u64 = 100;
+
+/// Matched events of one committed checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveBatch {
+    pub checkpoint: u64,
+    pub events: Vec<ProcessedEvent>,
+}
+
+/// Message sent to a live subscriber
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveMessage {
+    /// Events of a checkpoint that match the subscription
+    Events(LiveBatch),
+    /// Every stored event from `from_checkpoint` on was sent; live events follow
+    CaughtUp {
+        /// Last stored checkpoint replayed, `None` if nothing was stored yet
+        checkpoint: Option<u64>,
+    },
+    /// The subscription ended; reconnect with `from_checkpoint` to continue
+    Error {
+        message: String,
+        from_checkpoint: Option<u64>,
+    },
+    /// Storage is failing, so no events follow until `resumed`; none are skipped
+    Paused {
+        reason: String,
+        /// Last checkpoint committed before the pause
+        last_checkpoint: Option<u64>,
+    },
+    /// Storage recovered and events flow again
+    Resumed,
+}
+
+/// Why live events are held back
+#[derive(Debug, Clone)]
+struct Pause {
+    reason: String,
+    last_checkpoint: Option<u64>,
+}
+
+/// Fans committed events out to live subscribers
+#[derive(Clone)]
+pub struct LiveEvents {
+    tx: broadcast::Sender<Arc<LiveBatch>>,
+    paused: Arc<watch::Sender<Option<Pause>>>,
+}
+
+impl Default for LiveEvents {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(CHANNEL_CAPACITY).0,
+            paused: Arc::new(watch::channel(None).0),
+        }
+    }
+}
+
+impl LiveEvents {
+    /// Hand the events of a committed checkpoint to every subscriber
+    pub fn publish(&self, checkpoint: u64, events: &[ProcessedEvent]) {
+        if events.is_empty() || self.tx.receiver_count() == 0 {
+            return;
+        }
+        let _ = self.tx.send(Arc::new(LiveBatch {
+            checkpoint,
+            events: events.to_vec(),
+        }));
+    }
+
+    /// Checkpoints committed from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveBatch>> {
+        self.tx.subscribe()
+    }
+
+    /// Current number of subscribers
+    pub fn subscribers(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Tell subscribers no events follow until [`LiveEvents::resume`], returning whether they were flowing
+    pub fn pause(&self, reason: &str, last_checkpoint: Option<u64>) -> bool {
+        self.paused.send_if_modified(|paused| {
+            let was_flowing = paused.is_none();
+            if was_flowing {
+                *paused = Some(Pause {
+                    reason: reason.to_string(),
+                    last_checkpoint,
+                });
+            }
+            was_flowing
+        })
+    }
+
+    /// Tell subscribers events flow again, returning whether they were held back
+    pub fn resume(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| paused.take().is_some())
+    }
+
+    /// Check if subscribers were told events are held back
+    pub fn is_paused(&self) -> bool {
+        self.paused.borrow().is_some()
+    }
+}
+
+/// What a live subscriber asks for, as query parameters of the WebSocket request
+///
+/// Events must match one of the named `filter`s (comma-separated, any event
+/// if unset) and every field given here. With `from_checkpoint`, stored events
+/// from that checkpoint on are sent before live ones, followed by a
+/// [`LiveMessage::CaughtUp`] marker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Subscription {
+    pub filter: Option<String>,
+    pub package: Option<String>,
+    pub module: Option<String>,
+    pub event_type: Option<String>,
+    pub sender: Option<String>,
+    pub from_checkpoint: Option<u64>,
+}
+
+/// Selects the events of a [`Subscription`]
+pub(crate) struct EventMatcher {
+    named: EventFilterProcessor,
+    fields: EventFilterProcessor,
+}
+
+impl EventMatcher {
+    /// Resolve the named filters of `subscription` against `config`
+    pub(crate) fn new(subscription: &Subscription, config: &IndexerConfig) -> Result<Self> {
+        let names: Vec<String> = subscription
+            .filter
+            .iter()
+            .flat_map(|names| names.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        let fields = EventFilter {
+            name: None,
+            package: subscription.package.clone(),
+            module: subscription.module.clone(),
+            event_type: subscription.event_type.clone(),
+            sender: subscription.sender.clone(),
+            priority: FilterPriority::Normal,
+        };
+        let any_field = fields.package.is_some()
+            || fields.module.is_some()
+            || fields.event_type.is_some()
+            || fields.sender.is_some();
+
+        Ok(Self {
+            named: EventFilterProcessor::new(config.events.named_filters(&names)?),
+            fields: EventFilterProcessor::new(if any_field { vec![fields] } else { vec![] }),
+        })
+    }
+
+    fn matches(&self, event: &ProcessedEvent) -> bool {
+        self.named.should_process_event(&event.event)
+            && self.fields.should_process_event(&event.event)
+    }
+
+    /// The matching events of `events`, or `None` if there are none
+    fn select(&self, checkpoint: u64, events: &[ProcessedEvent]) -> Option<LiveBatch> {
+        let events: Vec<_> = events
+            .iter()
+            .filter(|event| self.matches(event))
+            .cloned()
+            .collect();
+        (!events.is_empty()).then_some(LiveBatch { checkpoint, events })
+    }
+}
+
+/// Why a live feed ended
+#[derive(Debug)]
+pub(crate) enum FeedError {
+    /// The subscriber fell behind; it can continue from the feed cursor
+    Lagged,
+    /// Reading stored events failed
+    Replay(eyre::Report),
+}
+
+/// Stored events from a cursor, then committed ones as they arrive
+pub(crate) struct LiveFeed {
+    rx: broadcast::Receiver<Arc<LiveBatch>>,
+    /// Pauses and resumptions not yet announced to the subscriber
+    status: watch::Receiver<Option<Pause>>,
+    matcher: EventMatcher,
+    storage: Option<StorageManager>,
+    /// Next stored checkpoint to read and the last one to replay
+    replay: Option<(u64, u64)>,
+    /// Replay still to be announced as done, with the last stored checkpoint
+    catching_up: Option<Option<u64>>,
+    pending: VecDeque<LiveBatch>,
+    /// First checkpoint not yet delivered or replayed
+    next: Option<u64>,
+}
+
+impl LiveFeed {
+    /// Follow `live`, first replaying `storage` from `from_checkpoint` up to the stored cursor
+    pub(crate) async fn new(
+        live: &LiveEvents,
+        matcher: EventMatcher,
+        storage: Option<StorageManager>,
+        from_checkpoint: Option<u64>,
+    ) -> Result<Self> {
+        // Subscribe before reading the cursor so no commit falls in between
+        let rx = live.subscribe();
+        let mut status = live.paused.subscribe();
+        // A subscriber joining during a pause learns of it first
+        if status.borrow().is_some() {
+            status.mark_changed();
+        }
+        let latest = match (from_checkpoint, &storage) {
+            (Some(_), Some(storage)) => storage.get_latest_checkpoint().await?,
+            (Some(_), None) => return Err(IndexerError::ReplayWithoutStorage.into()),
+            (None, _) => None,
+        };
+        let replay = from_checkpoint.zip(latest);
+        Ok(Self {
+            rx,
+            status,
+            matcher,
+            storage,
+            replay,
+            catching_up: from_checkpoint.map(|_| latest),
+            pending: VecDeque::new(),
+            next: from_checkpoint,
+        })
+    }
+
+    /// Cursor to resume from after the batches delivered so far
+    pub(crate) fn cursor(&self) -> Option<u64> {
+        self.next
+    }
+
+    /// Next batch of matching events, marker or pause announcement, or `None` once publishing stops
+    ///
+    /// Cancel safe: the feed only advances once a message is handed out.
+    pub(crate) async fn next(&mut self) -> std::result::Result<Option<LiveMessage>, FeedError> {
+        loop {
+            if let Some(batch) = self.pending.pop_front() {
+                return Ok(Some(LiveMessage::Events(batch)));
+            }
+
+            if let Some((from, last)) = self.replay {
+                if from > last {
+                    self.replay = None;
+                    continue;
+                }
+                let to = last.min(from + REPLAY_CHUNK - 1);
+                let storage = self.storage.as_ref().expect("replay needs storage");
+                let events = storage
+                    .get_events_by_checkpoint_range(from, to)
+                    .await
+                    .map_err(FeedError::Replay)?;
+                for events in events.chunk_by(|a, b| a.checkpoint_sequence == b.checkpoint_sequence)
+                {
+                    let checkpoint = events[0].checkpoint_sequence;
+                    self.pending.extend(self.matcher.select(checkpoint, events));
+                }
+                self.replay = Some((to + 1, last));
+                self.next = Some(to + 1);
+                continue;
+            }
+            if let Some(checkpoint) = self.catching_up.take() {
+                return Ok(Some(LiveMessage::CaughtUp { checkpoint }));
+            }
+
+            let received = tokio::select! {
+                received = self.rx.recv() => received,
+                Ok(()) = self.status.changed() => {
+                    let message = match &*self.status.borrow_and_update() {
+                        Some(pause) => LiveMessage::Paused {
+                            reason: pause.reason.clone(),
+                            last_checkpoint: pause.last_checkpoint,
+                        },
+                        None => LiveMessage::Resumed,
+                    };
+                    return Ok(Some(message));
+                }
+            };
+            let batch = match received {
+                Ok(batch) => batch,
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    return Err(FeedError::Lagged);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(None),
+            };
+            // Already replayed from storage
+            if self.next.is_some_and(|next| batch.checkpoint < next) {
+                continue;
+            }
+            self.next = Some(batch.checkpoint + 1);
+            if let Some(batch) = self.matcher.select(batch.checkpoint, &batch.events) {
+                return Ok(Some(LiveMessage::Events(batch)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    async fn events(checkpoint: u64, modules: &[&str]) -> Result<Vec<ProcessedEvent>> {
+        let events = modules
+            .iter()
+            .map(|module| {
+                TestEvent::new(&format!("0x2::{module}::Event"))
+                    .tx("tx1")
+                    .build()
+            })
+            .collect();
+        let mut events = DefaultEventProcessor::new().process_events(events).await?;
+        for event in &mut events {
+            event.checkpoint_sequence = checkpoint;
+        }
+        Ok(events)
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_filters_and_reports_lag() -> Result<()> {
+        let live = LiveEvents::default();
+        let subscription = Subscription {
+            module: Some("pool".to_string()),
+            ..Subscription::default()
+        };
+        let matcher = EventMatcher::new(&subscription, &IndexerConfig::default())?;
+        let mut feed = LiveFeed::new(&live, matcher, None, None).await?;
+        assert_eq!(live.subscribers(), 1);
+
+        live.publish(10, &events(10, &["coin"]).await?);
+        live.publish(11, &events(11, &["pool", "coin"]).await?);
+        let Ok(Some(LiveMessage::Events(batch))) = feed.next().await else {
+            panic!("expected a batch");
+        };
+        assert_eq!(batch.checkpoint, 11);
+        assert_eq!(batch.events.len(), 1);
+        assert_eq!(feed.cursor(), Some(12));
+
+        let pool = events(12, &["pool"]).await?;
+        for checkpoint in 12..=12 + CHANNEL_CAPACITY as u64 {
+            live.publish(checkpoint, &pool);
+        }
+        assert!(matches!(feed.next().await, Err(FeedError::Lagged)));
+        assert_eq!(feed.cursor(), Some(12));
+
+        let unknown = Subscription {
+            filter: Some("missing".to_string()),
+            ..Subscription::default()
+        };
+        assert!(EventMatcher::new(&unknown, &IndexerConfig::default()).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_replays_then_marks_catching_up() -> Result<()> {
+        let live = LiveEvents::default();
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        for checkpoint in 4..=6 {
+            storage
+                .store_events(&events(checkpoint, &["pool"]).await?)
+                .await?;
+        }
+        storage.update_checkpoint_progress(6).await?;
+        let matcher = || EventMatcher::new(&Subscription::default(), &IndexerConfig::default());
+        let mut feed = LiveFeed::new(&live, matcher()?, Some(storage.clone()), Some(5)).await?;
+
+        // Committed while replaying, and not sent twice
+        live.publish(6, &events(6, &["pool"]).await?);
+        live.publish(7, &events(7, &["pool"]).await?);
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(feed.next().await.map_err(|e| eyre::eyre!("{e:?}"))?);
+        }
+        assert!(matches!(
+            &received[..],
+            [
+                Some(LiveMessage::Events(LiveBatch { checkpoint: 5, .. })),
+                Some(LiveMessage::Events(LiveBatch { checkpoint: 6, .. })),
+                Some(LiveMessage::CaughtUp {
+                    checkpoint: Some(6)
+                }),
+                Some(LiveMessage::Events(LiveBatch { checkpoint: 7, .. })),
+            ]
+        ));
+
+        // Nothing stored yet still ends the replay with a marker
+        let empty = StorageManager::from_backend(MemoryStorage::new());
+        let mut feed = LiveFeed::new(&live, matcher()?, Some(empty), Some(0)).await?;
+        assert!(matches!(
+            feed.next().await,
+            Ok(Some(LiveMessage::CaughtUp { checkpoint: None }))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_live_feed_announces_pauses() -> Result<()> {
+        let live = LiveEvents::default();
+        let matcher = || EventMatcher::new(&Subscription::default(), &IndexerConfig::default());
+        let mut feed = LiveFeed::new(&live, matcher()?, None, None).await?;
+
+        assert!(live.pause("Storage is failing", Some(9)));
+        assert!(!live.pause("Storage is failing", Some(10)));
+        assert!(matches!(
+            feed.next().await,
+            Ok(Some(LiveMessage::Paused {
+                last_checkpoint: Some(9),
+                ..
+            }))
+        ));
+        // A subscriber joining during the pause hears of it first
+        let mut late = LiveFeed::new(&live, matcher()?, None, None).await?;
+        assert!(matches!(
+            late.next().await,
+            Ok(Some(LiveMessage::Paused { .. }))
+        ));
+
+        assert!(live.resume());
+        assert!(matches!(feed.next().await, Ok(Some(LiveMessage::Resumed))));
+        live.publish(10, &events(10, &["pool"]).await?);
+        assert!(matches!(
+            feed.next().await,
+            Ok(Some(LiveMessage::Events(LiveBatch { checkpoint: 10, .. })))
+        ));
+        Ok(())
+    }
+}
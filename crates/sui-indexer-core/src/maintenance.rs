@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use sui_indexer_config::StorageConfig;
+use sui_indexer_storage::StorageManager;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Interval between storage housekeeping runs
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically creates upcoming partitions and prunes data past the retention window
+pub struct StorageMaintenance {
+    storage: StorageManager,
+    config: StorageConfig,
+}
+
+impl StorageMaintenance {
+    /// Create a maintenance task for the given storage and settings
+    pub fn new(storage: StorageManager, config: StorageConfig) -> Self {
+        Self { storage, config }
+    }
+
+    /// Run housekeeping until `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) {
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.storage.maintain().await {
+                warn!(error = %e, "Storage maintenance failed");
+            }
+
+            let Some(retention_days) = self.config.retention_days else {
+                continue;
+            };
+            let cutoff = Utc::now() - chrono::Duration::days(retention_days.into());
+            match self.storage.prune_before(cutoff).await {
+                Ok(report) => info!(
+                    partitions = report.partitions.len(),
+                    events = report.events,
+                    transactions = report.transactions,
+                    retention_days,
+                    "Applied storage retention"
+                ),
+                Err(e) => warn!(error = %e, "Failed to apply storage retention"),
+            }
+        }
+    }
+}
@@ -0,0 +1,790 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use eyre::Result;
+use prometheus::{
+    core::Collector, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sui_indexer_events::BreakdownStats;
+
+use crate::{
+    error_history::{self, ErrorHistory},
+    resources::ResourceUsage,
+    slo::SloTracker,
+};
+
+/// Prefix of every exported metric name
+pub const NAMESPACE: &str = "sui_indexer";
+
+/// Histogram buckets in seconds for short waits such as connection checkouts
+const WAIT_BUCKETS: [f64; 12] = [
+    0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5, 1.0, 5.0,
+];
+
+/// Histogram buckets in seconds for the delay between a checkpoint and its storage
+const AVAILABILITY_BUCKETS: [f64; 13] = [
+    0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0,
+];
+
+/// Prometheus metrics exported by the indexer
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    /// Checkpoints committed to storage
+    pub checkpoints_processed: IntCounter,
+    /// Events that matched the configured filters
+    pub events_processed: IntCounter,
+    /// Events matched per filter, counted under the first filter that matches
+    pub filter_matches: IntCounterVec,
+    /// Events stored per filter, counted under the first filter that matches
+    pub filter_stored: IntCounterVec,
+    /// Failed processing and storage attempts per filter with events in the checkpoint
+    pub filter_errors: IntCounterVec,
+    /// Events that matched the filters per pipeline
+    pub pipeline_matches: IntCounterVec,
+    /// Events stored per pipeline
+    pub pipeline_stored: IntCounterVec,
+    /// Failed fetch, processing and storage attempts per pipeline
+    pub pipeline_errors: IntCounterVec,
+    /// Events in checkpoints run through the filters per pipeline
+    pub pipeline_seen: IntCounterVec,
+    /// Events that matched no filter per pipeline
+    pub pipeline_filtered: IntCounterVec,
+    /// Matched events of checkpoints given up before their commit per pipeline
+    pub pipeline_abandoned: IntCounterVec,
+    /// Matched events of checkpoints not yet committed per pipeline
+    pub pipeline_in_flight: IntGaugeVec,
+    /// Concurrent storage writes per checkpoint allowed per pipeline
+    pub pipeline_writer_concurrency: IntGaugeVec,
+    /// Checkpoints fetched at once allowed per pipeline
+    pub pipeline_fetch_concurrency: IntGaugeVec,
+    /// Matched events of checkpoints given up before their commit per filter
+    pub filter_abandoned: IntCounterVec,
+    /// Matched events of checkpoints not yet committed per filter
+    pub filter_in_flight: IntGaugeVec,
+    /// Processed events involving an address of each watchlist
+    pub watchlist_events: IntCounterVec,
+    /// Addresses watched per watchlist
+    pub watchlist_addresses: IntGaugeVec,
+    /// Processed events meeting each rule
+    pub rule_events_matched: IntCounterVec,
+    /// Rule messages by rule and outcome (`sent`, `failed`, `rate_limited`, `dropped`)
+    pub rule_notifications: IntCounterVec,
+    /// Sequence number of the last committed checkpoint
+    pub latest_processed_checkpoint: IntGauge,
+    /// Latest checkpoint reported by the node
+    pub latest_network_checkpoint: IntGauge,
+    /// Failed checkpoint fetches
+    pub fetch_errors: IntCounter,
+    /// Failed storage commits
+    pub storage_errors: IntCounter,
+    /// 1 while repeated commit failures hold back sinks and live subscribers
+    pub storage_circuit_open: IntGauge,
+    /// Time spent processing and committing a checkpoint
+    pub checkpoint_duration_seconds: Histogram,
+    /// Resident set size of the indexer process
+    pub resident_memory_bytes: IntGauge,
+    /// Open database connections
+    pub db_connections: IntGauge,
+    /// Open database connections not in use
+    pub db_idle_connections: IntGauge,
+    /// Open database connections in use
+    pub db_in_use_connections: IntGauge,
+    /// Connections the database pool may open
+    pub db_max_connections: IntGauge,
+    /// Time taken to check a connection out of the database pool
+    pub db_acquire_wait_seconds: Histogram,
+    /// Tasks alive on the tokio runtime
+    pub tokio_tasks: IntGauge,
+    /// Worker threads of the tokio runtime
+    pub tokio_workers: IntGauge,
+    /// Tasks waiting in the tokio runtime's global queue
+    pub tokio_global_queue_depth: IntGauge,
+    /// Share of worker time spent polling tasks between two samples
+    pub tokio_busy_ratio: Gauge,
+    /// Time from spawning a task until it is first polled
+    pub tokio_scheduling_delay_seconds: Histogram,
+    /// Seconds since the last committed checkpoint while the watchdog reports a stall, 0 otherwise
+    pub ingestion_stalled_seconds: IntGauge,
+    /// Ingestion restarts triggered by the watchdog
+    pub watchdog_restarts: IntCounter,
+    /// Sui gRPC requests per endpoint and outcome
+    pub rpc_requests: IntCounterVec,
+    /// Latency of Sui gRPC requests per endpoint
+    pub rpc_request_duration_seconds: HistogramVec,
+    /// 1 for the endpoint requests currently go to, 0 for the others
+    pub rpc_endpoint_active: IntGaugeVec,
+    /// Checkpoint fetches answered from the disk cache (`hit`) or the node (`miss`)
+    pub checkpoint_cache_lookups: IntCounterVec,
+    /// Bytes of checkpoints in the disk cache
+    pub checkpoint_cache_bytes: IntGauge,
+    /// Transactions whose full data was fetched from the node
+    pub transactions_fetched: IntCounter,
+    /// Events of registered typed events that did not decode and were left out of their tables
+    pub typed_events_skipped: IntGauge,
+    /// Events of priority filters delivered ahead of their checkpoint or left to its commit
+    pub priority_events: IntCounterVec,
+    /// Time from a checkpoint's timestamp until its events are stored
+    pub checkpoint_availability_seconds: Histogram,
+    /// Checkpoints per SLO that met or missed its threshold
+    pub slo_checkpoints: IntCounterVec,
+    /// Share of checkpoints meeting each SLO over its window
+    pub slo_compliance: GaugeVec,
+    /// Rate each SLO spends its error budget at over its window
+    pub slo_burn_rate: GaugeVec,
+    /// Target share of checkpoints of each SLO
+    pub slo_objective: GaugeVec,
+    /// Rolling windows behind the SLO gauges
+    pub slos: SloTracker,
+    /// Full names of the registered metrics
+    names: Arc<BTreeSet<String>>,
+    /// Latest failed fetch, processing and storage attempts behind the error counters
+    pub error_history: ErrorHistory,
+    /// Held while updating the event accounting counters together, so
+    /// reconciliation never sees a half-applied checkpoint
+    pub(crate) accounting: Arc<Mutex<()>>,
+    /// Registries of other network contexts exported along with this one
+    linked: Arc<Mutex<Vec<Registry>>>,
+}
+
+impl Metrics {
+    /// Create the indexer metrics on a fresh registry
+    pub fn new() -> Result<Self> {
+        Self::with_registry(Registry::new_custom(Some(NAMESPACE.to_string()), None)?)
+    }
+
+    /// Create the metrics of one network context, every series labelled with its `network`
+    pub fn for_network(network: &str) -> Result<Self> {
+        let labels = HashMap::from([("network".to_string(), network.to_string())]);
+        Self::with_registry(Registry::new_custom(
+            Some(NAMESPACE.to_string()),
+            Some(labels),
+        )?)
+    }
+
+    fn with_registry(registry: Registry) -> Result<Self> {
+        let checkpoints_processed = IntCounter::new(
+            "checkpoints_processed_total",
+            "Checkpoints committed to storage",
+        )?;
+        let events_processed = IntCounter::new(
+            "events_processed_total",
+            "Events that matched the configured filters",
+        )?;
+        let filter_matches = IntCounterVec::new(
+            Opts::new(
+                "filter_events_matched_total",
+                "Events matched per filter, counted under the first filter that matches",
+            ),
+            &["filter"],
+        )?;
+        let filter_stored = IntCounterVec::new(
+            Opts::new(
+                "filter_events_stored_total",
+                "Events stored per filter, counted under the first filter that matches",
+            ),
+            &["filter"],
+        )?;
+        let filter_errors = IntCounterVec::new(
+            Opts::new(
+                "filter_errors_total",
+                "Failed processing and storage attempts per filter with events in the checkpoint",
+            ),
+            &["filter"],
+        )?;
+        let pipeline_matches = IntCounterVec::new(
+            Opts::new(
+                "pipeline_events_matched_total",
+                "Events that matched the filters per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_stored = IntCounterVec::new(
+            Opts::new("pipeline_events_stored_total", "Events stored per pipeline"),
+            &["pipeline"],
+        )?;
+        let pipeline_errors = IntCounterVec::new(
+            Opts::new(
+                "pipeline_errors_total",
+                "Failed fetch, processing and storage attempts per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_seen = IntCounterVec::new(
+            Opts::new(
+                "pipeline_events_seen_total",
+                "Events in checkpoints run through the filters per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_filtered = IntCounterVec::new(
+            Opts::new(
+                "pipeline_events_filtered_total",
+                "Events that matched no filter per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_abandoned = IntCounterVec::new(
+            Opts::new(
+                "pipeline_events_abandoned_total",
+                "Matched events of checkpoints given up before their commit per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "pipeline_events_in_flight",
+                "Matched events of checkpoints not yet committed per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_writer_concurrency = IntGaugeVec::new(
+            Opts::new(
+                "pipeline_writer_concurrency",
+                "Concurrent storage writes per checkpoint allowed per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let pipeline_fetch_concurrency = IntGaugeVec::new(
+            Opts::new(
+                "pipeline_fetch_concurrency",
+                "Checkpoints fetched at once allowed per pipeline",
+            ),
+            &["pipeline"],
+        )?;
+        let watchlist_events = IntCounterVec::new(
+            Opts::new(
+                "watchlist_events_total",
+                "Processed events involving an address of each watchlist",
+            ),
+            &["watchlist"],
+        )?;
+        let watchlist_addresses = IntGaugeVec::new(
+            Opts::new("watchlist_addresses", "Addresses watched per watchlist"),
+            &["watchlist"],
+        )?;
+        let rule_events_matched = IntCounterVec::new(
+            Opts::new(
+                "rule_events_matched_total",
+                "Processed events meeting each rule",
+            ),
+            &["rule"],
+        )?;
+        let rule_notifications = IntCounterVec::new(
+            Opts::new(
+                "rule_notifications_total",
+                "Rule messages by rule and outcome",
+            ),
+            &["rule", "outcome"],
+        )?;
+        let filter_abandoned = IntCounterVec::new(
+            Opts::new(
+                "filter_events_abandoned_total",
+                "Matched events of checkpoints given up before their commit per filter",
+            ),
+            &["filter"],
+        )?;
+        let filter_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "filter_events_in_flight",
+                "Matched events of checkpoints not yet committed per filter",
+            ),
+            &["filter"],
+        )?;
+        let latest_processed_checkpoint = IntGauge::new(
+            "latest_processed_checkpoint",
+            "Sequence number of the last committed checkpoint",
+        )?;
+        let latest_network_checkpoint = IntGauge::new(
+            "latest_network_checkpoint",
+            "Latest checkpoint reported by the node",
+        )?;
+        let fetch_errors = IntCounter::new("fetch_errors_total", "Failed checkpoint fetches")?;
+        let storage_errors = IntCounter::new("storage_errors_total", "Failed storage commits")?;
+        let storage_circuit_open = IntGauge::new(
+            "storage_circuit_open",
+            "1 while repeated commit failures hold back sinks and live subscribers",
+        )?;
+        let checkpoint_duration_seconds = Histogram::with_opts(HistogramOpts::new(
+            "checkpoint_duration_seconds",
+            "Time spent processing and committing a checkpoint",
+        ))?;
+        let resident_memory_bytes = IntGauge::new(
+            "resident_memory_bytes",
+            "Resident set size of the indexer process",
+        )?;
+        let db_connections = IntGauge::new("db_connections", "Open database connections")?;
+        let db_idle_connections = IntGauge::new(
+            "db_idle_connections",
+            "Open database connections not in use",
+        )?;
+        let db_in_use_connections =
+            IntGauge::new("db_in_use_connections", "Open database connections in use")?;
+        let db_max_connections = IntGauge::new(
+            "db_max_connections",
+            "Connections the database pool may open",
+        )?;
+        let db_acquire_wait_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "db_acquire_wait_seconds",
+                "Time taken to check a connection out of the database pool",
+            )
+            .buckets(WAIT_BUCKETS.to_vec()),
+        )?;
+        let tokio_tasks = IntGauge::new("tokio_tasks", "Tasks alive on the tokio runtime")?;
+        let tokio_workers = IntGauge::new("tokio_workers", "Worker threads of the tokio runtime")?;
+        let tokio_global_queue_depth = IntGauge::new(
+            "tokio_global_queue_depth",
+            "Tasks waiting in the tokio runtime's global queue",
+        )?;
+        let tokio_busy_ratio = Gauge::new(
+            "tokio_busy_ratio",
+            "Share of worker time spent polling tasks between two samples",
+        )?;
+        let tokio_scheduling_delay_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "tokio_scheduling_delay_seconds",
+                "Time from spawning a task until it is first polled",
+            )
+            .buckets(WAIT_BUCKETS.to_vec()),
+        )?;
+        let ingestion_stalled_seconds = IntGauge::new(
+            "ingestion_stalled_seconds",
+            "Seconds since the last committed checkpoint while ingestion is stalled",
+        )?;
+        let watchdog_restarts = IntCounter::new(
+            "watchdog_restarts_total",
+            "Ingestion restarts triggered by the watchdog",
+        )?;
+        let rpc_requests = IntCounterVec::new(
+            Opts::new(
+                "rpc_requests_total",
+                "Sui gRPC requests per endpoint and outcome",
+            ),
+            &["endpoint", "outcome"],
+        )?;
+        let rpc_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "rpc_request_duration_seconds",
+                "Latency of Sui gRPC requests per endpoint",
+            ),
+            &["endpoint"],
+        )?;
+        let rpc_endpoint_active = IntGaugeVec::new(
+            Opts::new(
+                "rpc_endpoint_active",
+                "1 for the endpoint requests currently go to, 0 for the others",
+            ),
+            &["endpoint"],
+        )?;
+        let checkpoint_cache_lookups = IntCounterVec::new(
+            Opts::new(
+                "checkpoint_cache_lookups_total",
+                "Checkpoint fetches answered from the disk cache or the node",
+            ),
+            &["outcome"],
+        )?;
+        let checkpoint_cache_bytes = IntGauge::new(
+            "checkpoint_cache_bytes",
+            "Bytes of checkpoints in the disk cache",
+        )?;
+        let transactions_fetched = IntCounter::new(
+            "transactions_fetched_total",
+            "Transactions whose full data was fetched from the node",
+        )?;
+        let typed_events_skipped = IntGauge::new(
+            "typed_events_skipped",
+            "Events of registered typed events that did not decode and were left out of their tables",
+        )?;
+        let priority_events = IntCounterVec::new(
+            Opts::new(
+                "priority_events_total",
+                "Events of priority filters delivered ahead of their checkpoint or left to its commit",
+            ),
+            &["outcome"],
+        )?;
+        let checkpoint_availability_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "checkpoint_availability_seconds",
+                "Time from a checkpoint's timestamp until its events are stored",
+            )
+            .buckets(AVAILABILITY_BUCKETS.to_vec()),
+        )?;
+        let slo_checkpoints = IntCounterVec::new(
+            Opts::new(
+                "slo_checkpoints_total",
+                "Checkpoints per SLO that met or missed its threshold",
+            ),
+            &["slo", "outcome"],
+        )?;
+        let slo_compliance = GaugeVec::new(
+            Opts::new(
+                "slo_compliance_ratio",
+                "Share of checkpoints meeting each SLO over its window",
+            ),
+            &["slo"],
+        )?;
+        let slo_burn_rate = GaugeVec::new(
+            Opts::new(
+                "slo_burn_rate",
+                "Rate each SLO spends its error budget at over its window, 1 spending it exactly",
+            ),
+            &["slo"],
+        )?;
+        let slo_objective = GaugeVec::new(
+            Opts::new(
+                "slo_objective_ratio",
+                "Target share of checkpoints of each SLO",
+            ),
+            &["slo"],
+        )?;
+
+        let mut names = BTreeSet::new();
+        let mut register = |collector: Box<dyn Collector>| -> Result<()> {
+            names.extend(
+                collector
+                    .desc()
+                    .iter()
+                    .map(|desc| format!("{NAMESPACE}_{}", desc.fq_name)),
+            );
+            registry.register(collector)?;
+            Ok(())
+        };
+        register(Box::new(checkpoints_processed.clone()))?;
+        register(Box::new(events_processed.clone()))?;
+        register(Box::new(filter_matches.clone()))?;
+        register(Box::new(filter_stored.clone()))?;
+        register(Box::new(filter_errors.clone()))?;
+        register(Box::new(pipeline_matches.clone()))?;
+        register(Box::new(pipeline_stored.clone()))?;
+        register(Box::new(pipeline_errors.clone()))?;
+        register(Box::new(pipeline_seen.clone()))?;
+        register(Box::new(pipeline_filtered.clone()))?;
+        register(Box::new(pipeline_abandoned.clone()))?;
+        register(Box::new(pipeline_in_flight.clone()))?;
+        register(Box::new(pipeline_writer_concurrency.clone()))?;
+        register(Box::new(pipeline_fetch_concurrency.clone()))?;
+        register(Box::new(filter_abandoned.clone()))?;
+        register(Box::new(filter_in_flight.clone()))?;
+        register(Box::new(watchlist_events.clone()))?;
+        register(Box::new(watchlist_addresses.clone()))?;
+        register(Box::new(rule_events_matched.clone()))?;
+        register(Box::new(rule_notifications.clone()))?;
+        register(Box::new(latest_processed_checkpoint.clone()))?;
+        register(Box::new(latest_network_checkpoint.clone()))?;
+        register(Box::new(fetch_errors.clone()))?;
+        register(Box::new(storage_errors.clone()))?;
+        register(Box::new(storage_circuit_open.clone()))?;
+        register(Box::new(checkpoint_duration_seconds.clone()))?;
+        register(Box::new(resident_memory_bytes.clone()))?;
+        register(Box::new(db_connections.clone()))?;
+        register(Box::new(db_idle_connections.clone()))?;
+        register(Box::new(db_in_use_connections.clone()))?;
+        register(Box::new(db_max_connections.clone()))?;
+        register(Box::new(db_acquire_wait_seconds.clone()))?;
+        register(Box::new(tokio_tasks.clone()))?;
+        register(Box::new(tokio_workers.clone()))?;
+        register(Box::new(tokio_global_queue_depth.clone()))?;
+        register(Box::new(tokio_busy_ratio.clone()))?;
+        register(Box::new(tokio_scheduling_delay_seconds.clone()))?;
+        register(Box::new(ingestion_stalled_seconds.clone()))?;
+        register(Box::new(watchdog_restarts.clone()))?;
+        register(Box::new(rpc_requests.clone()))?;
+        register(Box::new(rpc_request_duration_seconds.clone()))?;
+        register(Box::new(rpc_endpoint_active.clone()))?;
+        register(Box::new(checkpoint_cache_lookups.clone()))?;
+        register(Box::new(checkpoint_cache_bytes.clone()))?;
+        register(Box::new(transactions_fetched.clone()))?;
+        register(Box::new(typed_events_skipped.clone()))?;
+        register(Box::new(priority_events.clone()))?;
+        register(Box::new(checkpoint_availability_seconds.clone()))?;
+        register(Box::new(slo_checkpoints.clone()))?;
+        register(Box::new(slo_compliance.clone()))?;
+        register(Box::new(slo_burn_rate.clone()))?;
+        register(Box::new(slo_objective.clone()))?;
+
+        Ok(Self {
+            registry,
+            checkpoints_processed,
+            events_processed,
+            filter_matches,
+            filter_stored,
+            filter_errors,
+            pipeline_matches,
+            pipeline_stored,
+            pipeline_errors,
+            pipeline_seen,
+            pipeline_filtered,
+            pipeline_abandoned,
+            pipeline_in_flight,
+            pipeline_writer_concurrency,
+            pipeline_fetch_concurrency,
+            filter_abandoned,
+            filter_in_flight,
+            watchlist_events,
+            watchlist_addresses,
+            rule_events_matched,
+            rule_notifications,
+            latest_processed_checkpoint,
+            latest_network_checkpoint,
+            fetch_errors,
+            storage_errors,
+            storage_circuit_open,
+            checkpoint_duration_seconds,
+            resident_memory_bytes,
+            db_connections,
+            db_idle_connections,
+            db_in_use_connections,
+            db_max_connections,
+            db_acquire_wait_seconds,
+            tokio_tasks,
+            tokio_workers,
+            tokio_global_queue_depth,
+            tokio_busy_ratio,
+            tokio_scheduling_delay_seconds,
+            ingestion_stalled_seconds,
+            watchdog_restarts,
+            rpc_requests,
+            rpc_request_duration_seconds,
+            rpc_endpoint_active,
+            checkpoint_cache_lookups,
+            checkpoint_cache_bytes,
+            transactions_fetched,
+            typed_events_skipped,
+            priority_events,
+            checkpoint_availability_seconds,
+            slo_checkpoints,
+            slo_compliance,
+            slo_burn_rate,
+            slo_objective,
+            slos: SloTracker::default(),
+            names: Arc::new(names),
+            error_history: ErrorHistory::with_capacity(error_history::DEFAULT_CAPACITY),
+            accounting: Arc::default(),
+            linked: Arc::default(),
+        })
+    }
+
+    /// Registry holding all indexer metrics, for registering custom collectors
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Current value of every per-filter match counter, by filter name
+    pub fn filter_match_counts(&self) -> BTreeMap<String, u64> {
+        label_counts(&self.filter_matches)
+    }
+
+    /// Matches, stored events and errors by filter name
+    pub fn filter_stats(&self) -> BTreeMap<String, BreakdownStats> {
+        breakdown(
+            &self.filter_matches,
+            &self.filter_stored,
+            &self.filter_errors,
+        )
+    }
+
+    /// Matches, stored events and errors by pipeline name
+    pub fn pipeline_stats(&self) -> BTreeMap<String, BreakdownStats> {
+        breakdown(
+            &self.pipeline_matches,
+            &self.pipeline_stored,
+            &self.pipeline_errors,
+        )
+    }
+
+    /// Set the resource gauges from a sample, leaving unreported ones unchanged
+    pub fn record_resources(&self, usage: &ResourceUsage) {
+        if let Some(rss) = usage.rss_bytes {
+            self.resident_memory_bytes.set(rss as i64);
+        }
+        if let Some(connections) = usage.db_connections {
+            self.db_connections.set(connections.open.into());
+            self.db_idle_connections.set(connections.idle.into());
+            self.db_in_use_connections.set(connections.in_use().into());
+            self.db_max_connections.set(connections.max.into());
+        }
+        if let Some(tasks) = usage.tokio_tasks {
+            self.tokio_tasks.set(tasks as i64);
+        }
+        if let Some(workers) = usage.tokio_workers {
+            self.tokio_workers.set(workers as i64);
+        }
+        if let Some(depth) = usage.tokio_queue_depth {
+            self.tokio_global_queue_depth.set(depth as i64);
+        }
+    }
+
+    /// Full name of every exported metric, e.g. `sui_indexer_fetch_errors_total`
+    pub fn names(&self) -> &BTreeSet<String> {
+        &self.names
+    }
+
+    /// Record that a checkpoint was stored `latency` after its timestamp
+    pub fn record_availability(&self, latency: Duration) {
+        self.checkpoint_availability_seconds
+            .observe(latency.as_secs_f64());
+        for (met, status) in self.slos.observe(latency, Instant::now()) {
+            let outcome = if met { "met" } else { "missed" };
+            self.slo_checkpoints
+                .with_label_values(&[&status.name, outcome])
+                .inc();
+            self.slo_compliance
+                .with_label_values(&[&status.name])
+                .set(status.compliance);
+            self.slo_burn_rate
+                .with_label_values(&[&status.name])
+                .set(status.burn_rate);
+            self.slo_objective
+                .with_label_values(&[&status.name])
+                .set(status.objective);
+        }
+    }
+
+    /// Export the metrics of another network context with these
+    pub fn link(&self, other: &Metrics) {
+        self.linked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(other.registry.clone());
+    }
+
+    /// Encode all metrics, including linked ones, in the Prometheus text exposition format
+    pub fn encode(&self) -> Result<String> {
+        let mut families = self.registry.gather();
+        for registry in self
+            .linked
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            for mut family in registry.gather() {
+                // Series of the same name must be exposed as one family
+                match families
+                    .iter_mut()
+                    .find(|known| known.get_name() == family.get_name())
+                {
+                    Some(known) => {
+                        for metric in family.take_metric() {
+                            known.mut_metric().push(metric);
+                        }
+                    }
+                    None => families.push(family),
+                }
+            }
+        }
+        families.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&families, &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// Current value of every counter in a single-label vector, by label value
+pub(crate) fn label_counts(counters: &IntCounterVec) -> BTreeMap<String, u64> {
+    counters
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .filter_map(|metric| {
+            let label = metric.get_label().first()?.value().to_string();
+            Some((label, metric.get_counter().value() as u64))
+        })
+        .collect()
+}
+
+/// Current value of every gauge in a single-label vector, by label value
+pub(crate) fn gauge_values(gauges: &IntGaugeVec) -> BTreeMap<String, u64> {
+    gauges
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .filter_map(|metric| {
+            let label = metric.get_label().first()?.value().to_string();
+            Some((label, metric.get_gauge().value().max(0.0) as u64))
+        })
+        .collect()
+}
+
+fn breakdown(
+    matched: &IntCounterVec,
+    stored: &IntCounterVec,
+    errors: &IntCounterVec,
+) -> BTreeMap<String, BreakdownStats> {
+    let mut stats: BTreeMap<String, BreakdownStats> = BTreeMap::new();
+    for (label, count) in label_counts(matched) {
+        stats.entry(label).or_default().matched = count;
+    }
+    for (label, count) in label_counts(stored) {
+        stats.entry(label).or_default().stored = count;
+    }
+    for (label, count) in label_counts(errors) {
+        stats.entry(label).or_default().errors = count;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_encoding() -> Result<()> {
+        let metrics = Metrics::new()?;
+        metrics.checkpoints_processed.inc();
+        metrics.latest_processed_checkpoint.set(42);
+
+        let encoded = metrics.encode()?;
+        assert!(encoded.contains("sui_indexer_checkpoints_processed_total 1"));
+        assert!(encoded.contains("sui_indexer_latest_processed_checkpoint 42"));
+
+        metrics
+            .filter_matches
+            .with_label_values(&["swaps"])
+            .inc_by(3);
+        assert_eq!(metrics.filter_match_counts().get("swaps"), Some(&3));
+
+        metrics
+            .filter_stored
+            .with_label_values(&["swaps"])
+            .inc_by(2);
+        metrics.filter_errors.with_label_values(&["pools"]).inc();
+        let filters = metrics.filter_stats();
+        assert_eq!(
+            filters["swaps"],
+            BreakdownStats {
+                matched: 3,
+                stored: 2,
+                errors: 0,
+            }
+        );
+        assert_eq!(filters["pools"].errors, 1);
+        assert!(metrics.pipeline_stats().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_linked_networks_share_families() -> Result<()> {
+        let mainnet = Metrics::for_network("mainnet")?;
+        let testnet = Metrics::for_network("testnet")?;
+        mainnet.link(&testnet);
+        mainnet.latest_processed_checkpoint.set(42);
+        testnet.latest_processed_checkpoint.set(7);
+
+        let encoded = mainnet.encode()?;
+        assert!(encoded.contains("sui_indexer_latest_processed_checkpoint{network=\"mainnet\"} 42"));
+        assert!(encoded.contains("sui_indexer_latest_processed_checkpoint{network=\"testnet\"} 7"));
+        assert_eq!(
+            encoded
+                .matches("# TYPE sui_indexer_latest_processed_checkpoint gauge")
+                .count(),
+            1
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use sui_indexer_config::{MoveCallFilter, MoveCallsConfig};
+use sui_indexer_events::{move_call_id, PackageLineage};
+use sui_indexer_storage::MoveCallRecord;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::sui::{CheckpointData, MoveCall};
+
+/// Selects the Move calls of each checkpoint that the live pipeline stores
+///
+/// Calls are matched on their target and sender, so a contract can be
+/// indexed by who calls it even when the called functions emit no events.
+#[derive(Debug, Clone)]
+pub struct MoveCallIndexer {
+    /// Targets to store; every call if empty
+    filters: Vec<CallFilter>,
+    lineage: Option<PackageLineage>,
+}
+
+/// [`MoveCallFilter`] with its IDs parsed
+#[derive(Debug, Clone)]
+struct CallFilter {
+    package: Option<ObjectID>,
+    module: Option<String>,
+    function: Option<String>,
+    sender: Option<SuiAddress>,
+}
+
+impl MoveCallIndexer {
+    /// Indexer storing the calls `config` selects, or `None` if call indexing is disabled
+    ///
+    /// Filters whose package or sender does not parse match no call; `validate`
+    /// rejects them before the indexer starts.
+    pub fn from_config(config: &MoveCallsConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            filters: config
+                .filters
+                .iter()
+                .filter_map(CallFilter::parse)
+                .collect(),
+            lineage: None,
+        })
+    }
+
+    /// Match a filter's `package` against every version of that package in `lineage`
+    pub fn with_lineage(mut self, lineage: PackageLineage) -> Self {
+        self.lineage = Some(lineage);
+        self
+    }
+
+    /// The calls in `checkpoint` that match a filter, as rows to store
+    pub fn extract(&self, checkpoint: &CheckpointData) -> Vec<MoveCallRecord> {
+        let timestamp = DateTime::<Utc>::from_timestamp_millis(checkpoint.timestamp_ms as i64)
+            .unwrap_or_default();
+        checkpoint
+            .move_calls
+            .iter()
+            .filter(|call| self.matches(call))
+            .map(|call| MoveCallRecord {
+                id: move_call_id(&call.tx_digest, call.command_index),
+                checkpoint_sequence: checkpoint.sequence_number as i64,
+                transaction_digest: call.tx_digest.to_string(),
+                command_index: call.command_index as i32,
+                timestamp,
+                package_id: call.package.to_string(),
+                module_name: call.module.clone(),
+                function_name: call.function.clone(),
+                type_arguments: call.type_arguments.clone(),
+                sender: call.sender.to_string(),
+            })
+            .collect()
+    }
+
+    fn matches(&self, call: &MoveCall) -> bool {
+        self.filters.is_empty()
+            || self.filters.iter().any(|filter| {
+                filter.package.is_none_or(|package| match &self.lineage {
+                    Some(lineage) => lineage.same_package(&call.package, &package),
+                    None => call.package == package,
+                }) && filter.module.as_ref().is_none_or(|m| *m == call.module)
+                    && filter.function.as_ref().is_none_or(|f| *f == call.function)
+                    && filter.sender.is_none_or(|sender| sender == call.sender)
+            })
+    }
+}
+
+impl CallFilter {
+    fn parse(filter: &MoveCallFilter) -> Option<Self> {
+        Some(Self {
+            package: filter.package.as_deref().map(str::parse).transpose().ok()?,
+            module: filter.module.clone(),
+            function: filter.function.clone(),
+            sender: filter.sender.as_deref().map(str::parse).transpose().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::Result;
+    use sui_types::base_types::TransactionDigest;
+
+    use super::*;
+    use crate::sui::mock::synthetic_checkpoint;
+
+    fn call(package: u8, module: &str, function: &str, command_index: u32) -> MoveCall {
+        MoveCall {
+            tx_digest: TransactionDigest::default(),
+            command_index,
+            package: ObjectID::from_single_byte(package),
+            module: module.to_string(),
+            function: function.to_string(),
+            type_arguments: vec!["0x2::sui::SUI".to_string()],
+            sender: SuiAddress::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_calls_are_selected_by_target() -> Result<()> {
+        let config = MoveCallsConfig {
+            enabled: true,
+            filters: vec![MoveCallFilter {
+                package: Some(ObjectID::from_single_byte(1).to_string()),
+                module: Some("pool".to_string()),
+                function: Some("swap".to_string()),
+                sender: None,
+            }],
+        };
+        let mut checkpoint = synthetic_checkpoint(7, 0)?;
+        checkpoint.move_calls = vec![
+            call(1, "pool", "swap", 0),
+            call(1, "pool", "add_liquidity", 1),
+            call(2, "pool", "swap", 2),
+        ];
+
+        let lineage = PackageLineage::new();
+        let indexer = MoveCallIndexer::from_config(&config)
+            .unwrap()
+            .with_lineage(lineage.clone());
+        let records = indexer.extract(&checkpoint);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].command_index, 0);
+        assert_eq!(records[0].checkpoint_sequence, 7);
+        assert_eq!(records[0].type_arguments, ["0x2::sui::SUI"]);
+
+        // Calls into an upgrade of the package match once the upgrade is known
+        lineage.record(ObjectID::from_single_byte(2), ObjectID::from_single_byte(1));
+        assert_eq!(indexer.extract(&checkpoint).len(), 2);
+
+        let everything = MoveCallsConfig {
+            enabled: true,
+            filters: Vec::new(),
+        };
+        let records = MoveCallIndexer::from_config(&everything)
+            .unwrap()
+            .extract(&checkpoint);
+        assert_eq!(records.len(), 3);
+        assert!(MoveCallIndexer::from_config(&MoveCallsConfig::default()).is_none());
+        Ok(())
+    }
+}
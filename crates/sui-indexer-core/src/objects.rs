@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use sui_indexer_config::ObjectsConfig;
+use sui_indexer_events::object_version_id;
+use sui_indexer_storage::ObjectVersionRecord;
+use sui_types::base_types::ObjectID;
+
+use crate::sui::CheckpointData;
+
+/// Selects the versions of tracked objects written by each checkpoint
+///
+/// The live pipeline stores them next to the checkpoint's events, so the
+/// history of an object such as a DEX pool can be queried over time.
+#[derive(Debug, Clone)]
+pub struct ObjectTracker {
+    ids: HashSet<ObjectID>,
+}
+
+impl ObjectTracker {
+    /// Tracker of the objects `config` lists, or `None` if it lists none
+    ///
+    /// IDs that do not parse match no object; `validate` rejects them before
+    /// the indexer starts.
+    pub fn from_config(config: &ObjectsConfig) -> Option<Self> {
+        let ids: HashSet<_> = config.ids.iter().filter_map(|id| id.parse().ok()).collect();
+        (!ids.is_empty()).then_some(Self { ids })
+    }
+
+    /// The versions of tracked objects in `checkpoint`, as rows to store
+    pub fn extract(&self, checkpoint: &CheckpointData) -> Vec<ObjectVersionRecord> {
+        let timestamp = DateTime::<Utc>::from_timestamp_millis(checkpoint.timestamp_ms as i64)
+            .unwrap_or_default();
+        checkpoint
+            .object_changes
+            .iter()
+            .filter(|change| self.ids.contains(&change.object_id))
+            .map(|change| ObjectVersionRecord {
+                id: object_version_id(&change.object_id, change.version),
+                object_id: change.object_id.to_string(),
+                version: change.version as i64,
+                checkpoint_sequence: checkpoint.sequence_number as i64,
+                transaction_digest: change.tx_digest.to_string(),
+                timestamp,
+                change_kind: change.kind.as_str().to_string(),
+                digest: change.digest.clone(),
+                object_type: change.object_type.clone(),
+                owner: change.owner.clone(),
+                contents: change.contents.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::Result;
+    use serde_json::json;
+    use sui_types::base_types::TransactionDigest;
+
+    use super::*;
+    use crate::sui::{mock::synthetic_checkpoint, ObjectChange, ObjectChangeKind};
+
+    fn change(object: u8, version: u64, kind: ObjectChangeKind) -> ObjectChange {
+        ObjectChange {
+            tx_digest: TransactionDigest::default(),
+            object_id: ObjectID::from_single_byte(object),
+            version,
+            kind,
+            digest: (kind != ObjectChangeKind::Deleted).then(|| "digest".to_string()),
+            object_type: Some("0xa1::pool::Pool".to_string()),
+            owner: Some("shared".to_string()),
+            contents: Some(json!({"reserve_a": "1500"})),
+        }
+    }
+
+    #[test]
+    fn test_only_tracked_objects_are_recorded() -> Result<()> {
+        let config = ObjectsConfig {
+            ids: vec![ObjectID::from_single_byte(5).to_string()],
+        };
+        let tracker = ObjectTracker::from_config(&config).expect("an object is listed");
+
+        let mut checkpoint = synthetic_checkpoint(7, 0)?;
+        checkpoint.object_changes = vec![
+            change(5, 3, ObjectChangeKind::Mutated),
+            change(6, 3, ObjectChangeKind::Mutated),
+            change(5, 4, ObjectChangeKind::Deleted),
+        ];
+        let versions = tracker.extract(&checkpoint);
+        assert_eq!(versions.len(), 2);
+        assert!(versions
+            .iter()
+            .all(|version| version.object_id == config.ids[0] && version.checkpoint_sequence == 7));
+        assert_eq!(versions[0].contents, Some(json!({"reserve_a": "1500"})));
+        assert_eq!(versions[1].change_kind, "deleted");
+        assert_eq!(versions[1].digest, None);
+
+        assert!(ObjectTracker::from_config(&ObjectsConfig::default()).is_none());
+        Ok(())
+    }
+}
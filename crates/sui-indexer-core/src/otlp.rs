@@ -0,0 +1,302 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use eyre::Result;
+use opentelemetry::{
+    trace::{SpanId, Status, TraceId, TracerProvider as _},
+    Context, KeyValue,
+};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    error::OTelSdkResult,
+    trace::{
+        BatchConfigBuilder, BatchSpanProcessor, SdkTracerProvider, Span, SpanData, SpanProcessor,
+    },
+    Resource,
+};
+use tracing::{warn, Subscriber};
+use tracing_subscriber::{filter::filter_fn, registry::LookupSpan, Layer};
+use url::Url;
+
+/// Spans exported per request
+const MAX_BATCH: usize = 512;
+
+/// Longest a finished span waits before it is exported
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Finished spans buffered for export; spans beyond this are dropped
+const QUEUE_CAPACITY: usize = 8192;
+
+/// Spans of an unsampled trace held until it ends; later ones are dropped
+const MAX_HELD_SPANS: usize = 256;
+
+/// Crates whose spans stay local, so exporting never traces itself
+const INTERNAL_TARGETS: &[&str] = &["hyper", "reqwest", "h2", "rustls", "tower"];
+
+/// Create a layer exporting spans to the OTLP/HTTP collector at `endpoint`
+///
+/// Spans are sent with `opentelemetry-otlp` to `<endpoint>/v1/traces` in
+/// batches from a background thread. One in `sample_every` traces is
+/// exported, plus every trace in which a span logged a warning or error
+/// (0 keeps only the latter). The returned [`TraceExporter`] flushes the
+/// spans still buffered before the process exits.
+pub fn layer<S>(
+    endpoint: &Url,
+    service_name: &str,
+    sample_every: u64,
+) -> Result<(impl Layer<S>, TraceExporter)>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_url(endpoint).as_str())
+        .with_timeout(Duration::from_secs(10))
+        .build()?;
+    let batches = BatchSpanProcessor::builder(exporter)
+        .with_batch_config(
+            BatchConfigBuilder::default()
+                .with_max_queue_size(QUEUE_CAPACITY)
+                .with_max_export_batch_size(MAX_BATCH)
+                .with_scheduled_delay(EXPORT_INTERVAL)
+                .build(),
+        )
+        .build();
+    let provider = SdkTracerProvider::builder()
+        .with_span_processor(TailSampler::new(batches, sample_every))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(service_name.to_string())
+                .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+                .build(),
+        )
+        .build();
+
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+        .with_filter(filter_fn(|metadata| !is_internal(metadata.target())));
+    Ok((layer, TraceExporter { provider }))
+}
+
+/// Handle on the span export of a trace [`layer`]
+#[derive(Clone)]
+pub struct TraceExporter {
+    provider: SdkTracerProvider,
+}
+
+impl TraceExporter {
+    /// Export every finished span still buffered, blocking until the collector answers
+    pub fn flush(&self) {
+        if let Err(e) = self.provider.force_flush() {
+            warn!(error = %e, "Failed to export traces");
+        }
+    }
+}
+
+/// Passes on one in `sample_every` traces, plus those in which a span logged a warning or error
+///
+/// The spans of other traces are held until their root span ends, so a
+/// problem late in the trace still exports its earlier spans.
+#[derive(Debug)]
+struct TailSampler<P> {
+    inner: P,
+    sample_every: u64,
+    held: Mutex<HashMap<TraceId, HeldTrace>>,
+}
+
+#[derive(Debug, Default)]
+struct HeldTrace {
+    /// Whether a span of the trace logged a warning or error
+    problem: bool,
+    spans: Vec<SpanData>,
+}
+
+impl<P> TailSampler<P> {
+    fn new(inner: P, sample_every: u64) -> Self {
+        Self {
+            inner,
+            sample_every,
+            held: Mutex::default(),
+        }
+    }
+
+    fn held(&self) -> MutexGuard<'_, HashMap<TraceId, HeldTrace>> {
+        self.held.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether every span of the trace is exported; trace ids are random
+    fn sampled(&self, trace_id: TraceId) -> bool {
+        self.sample_every > 0
+            && u128::from_be_bytes(trace_id.to_bytes()) % u128::from(self.sample_every) == 0
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for TailSampler<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        let trace_id = span.span_context.trace_id();
+        if self.sampled(trace_id) {
+            self.inner.on_end(span);
+            return;
+        }
+
+        let mut held = self.held();
+        let mut trace = held.remove(&trace_id).unwrap_or_default();
+        trace.problem |= is_problem(&span);
+        if span.parent_span_id != SpanId::INVALID {
+            if trace.spans.len() < MAX_HELD_SPANS {
+                trace.spans.push(span);
+            }
+            held.insert(trace_id, trace);
+            return;
+        }
+        drop(held);
+        if trace.problem {
+            for span in trace.spans.into_iter().chain([span]) {
+                self.inner.on_end(span);
+            }
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// Whether `span` failed or logged a warning or error, recorded as the `level` of its events
+fn is_problem(span: &SpanData) -> bool {
+    matches!(span.status, Status::Error { .. })
+        || span.events.iter().any(|event| {
+            event.attributes.iter().any(|attribute| {
+                attribute.key.as_str() == "level"
+                    && matches!(attribute.value.as_str().as_ref(), "WARN" | "ERROR")
+            })
+        })
+}
+
+/// The collector's trace endpoint, `<endpoint>/v1/traces` unless already given
+fn traces_url(endpoint: &Url) -> Url {
+    let mut url = endpoint.clone();
+    if !endpoint.path().ends_with("/v1/traces") {
+        url.set_path(&format!(
+            "{}/v1/traces",
+            endpoint.path().trim_end_matches('/')
+        ));
+    }
+    url
+}
+
+fn is_internal(target: &str) -> bool {
+    INTERNAL_TARGETS.iter().any(|internal| {
+        target
+            .strip_prefix(internal)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SimpleSpanProcessor};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    /// Spans exported under `sample_every`, by way of an in-memory exporter
+    fn exported(sample_every: u64, trace: impl FnOnce()) -> Result<Vec<SpanData>> {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_span_processor(TailSampler::new(
+                SimpleSpanProcessor::new(exporter.clone()),
+                sample_every,
+            ))
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, trace);
+        Ok(exporter.get_finished_spans()?)
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Option<Value> {
+        span.attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == key)
+            .map(|attribute| attribute.value.clone())
+    }
+
+    #[test]
+    fn test_nested_spans_share_trace() -> Result<()> {
+        let spans = exported(1, || {
+            let checkpoint = tracing::info_span!("checkpoint", checkpoint = 42u64);
+            let _guard = checkpoint.enter();
+            tracing::info_span!("store").in_scope(|| tracing::error!(attempt = 1, "Write failed"));
+        })?;
+
+        let [store, checkpoint] = &spans[..] else {
+            panic!("expected two spans, got {spans:?}");
+        };
+        assert_eq!(store.name, "store");
+        assert_eq!(
+            store.span_context.trace_id(),
+            checkpoint.span_context.trace_id()
+        );
+        assert_eq!(store.parent_span_id, checkpoint.span_context.span_id());
+        assert!(is_problem(store));
+        assert_eq!(store.events[0].name, "Write failed");
+        assert_eq!(attribute(checkpoint, "checkpoint"), Some(Value::I64(42)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampling_keeps_problem_traces() -> Result<()> {
+        let spans = exported(0, || {
+            for checkpoint in 0..3u64 {
+                let span = tracing::info_span!("checkpoint", checkpoint);
+                tracing::info_span!(parent: &span, "store").in_scope(|| {
+                    if checkpoint == 1 {
+                        tracing::warn!("Slow checkpoint");
+                    }
+                });
+            }
+        })?;
+
+        let names: Vec<_> = spans.iter().map(|span| span.name.as_ref()).collect();
+        assert_eq!(names, ["store", "checkpoint"]);
+        assert_eq!(attribute(&spans[1], "checkpoint"), Some(Value::I64(1)));
+        assert_eq!(spans[0].parent_span_id, spans[1].span_context.span_id());
+        Ok(())
+    }
+
+    #[test]
+    fn test_traces_url() -> Result<()> {
+        let url = |endpoint: &str| Url::parse(endpoint).map(|e| String::from(traces_url(&e)));
+        assert_eq!(
+            url("http://localhost:4318")?,
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            url("http://otel:4318/prefix/")?,
+            "http://otel:4318/prefix/v1/traces"
+        );
+        assert_eq!(
+            url("http://otel:4318/v1/traces")?,
+            "http://otel:4318/v1/traces"
+        );
+        assert!(is_internal("hyper::client") && !is_internal("hyperlane"));
+        Ok(())
+    }
+}
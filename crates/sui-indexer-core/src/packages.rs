@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sui_indexer_config::PackagesConfig;
+use sui_indexer_events::PackageLineage;
+use sui_indexer_storage::{PackageRecord, StorageManager};
+use sui_types::base_types::ObjectID;
+use tracing::info;
+
+use crate::sui::{CheckpointData, PublishedPackage};
+
+/// Package publishes and upgrades followed by the live pipeline
+///
+/// Tracked versions are stored with their checkpoint and recorded in a
+/// [`PackageLineage`] that the pipeline's event filters consult, so a filter
+/// on a package matches its upgrades from the checkpoint they are published in.
+#[derive(Debug, Clone)]
+pub struct PackageTracker {
+    /// Original IDs of the tracked packages; all if empty
+    original_ids: HashSet<ObjectID>,
+    lineage: PackageLineage,
+}
+
+impl PackageTracker {
+    /// Tracker following the packages of `config`, or `None` if tracking is disabled
+    pub fn from_config(config: &PackagesConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            original_ids: config
+                .original_ids
+                .iter()
+                .filter_map(|id| id.parse().ok())
+                .collect(),
+            lineage: PackageLineage::new(),
+        })
+    }
+
+    /// Lineage of the package versions seen so far
+    pub fn lineage(&self) -> PackageLineage {
+        self.lineage.clone()
+    }
+
+    /// Fill the lineage with the package versions stored by earlier runs
+    pub async fn load(&self, storage: &StorageManager) -> Result<()> {
+        for package in storage.packages(None).await? {
+            if let (Ok(package_id), Ok(original_id)) =
+                (package.package_id.parse(), package.original_id.parse())
+            {
+                self.lineage.record(package_id, original_id);
+            }
+        }
+        info!(versions = self.lineage.len(), "Loaded package lineage");
+        Ok(())
+    }
+
+    /// Record the tracked packages published in `checkpoint`, returning them to be stored
+    pub fn observe(&self, checkpoint: &CheckpointData) -> Vec<PackageRecord> {
+        let published_at = DateTime::<Utc>::from_timestamp_millis(checkpoint.timestamp_ms as i64)
+            .unwrap_or_default();
+        checkpoint
+            .packages
+            .iter()
+            .filter(|package| self.tracks(package))
+            .map(|package| {
+                self.lineage.record(package.package_id, package.original_id);
+                PackageRecord {
+                    package_id: package.package_id.to_string(),
+                    original_id: package.original_id.to_string(),
+                    version: package.version as i64,
+                    modules: package.modules.clone(),
+                    tx_digest: package.tx_digest.to_string(),
+                    sender: package.sender.to_string(),
+                    checkpoint_sequence: checkpoint.sequence_number as i64,
+                    published_at,
+                }
+            })
+            .collect()
+    }
+
+    fn tracks(&self, package: &PublishedPackage) -> bool {
+        self.original_ids.is_empty() || self.original_ids.contains(&package.original_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::base_types::{SuiAddress, TransactionDigest};
+
+    use super::*;
+    use crate::sui::mock::synthetic_checkpoint;
+
+    fn package(package_id: u8, original_id: u8, version: u64) -> PublishedPackage {
+        PublishedPackage {
+            package_id: ObjectID::from_single_byte(package_id),
+            original_id: ObjectID::from_single_byte(original_id),
+            version,
+            modules: vec!["pool".to_string()],
+            tx_digest: TransactionDigest::default(),
+            sender: SuiAddress::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_only_listed_lineages_are_tracked() -> Result<()> {
+        let config = PackagesConfig {
+            enabled: true,
+            original_ids: vec![ObjectID::from_single_byte(1).to_string()],
+        };
+        let tracker = PackageTracker::from_config(&config).unwrap();
+        let mut checkpoint = synthetic_checkpoint(7, 0)?;
+        checkpoint.packages = vec![package(2, 1, 2), package(9, 9, 1)];
+
+        let records = tracker.observe(&checkpoint);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].version, 2);
+        assert_eq!(records[0].checkpoint_sequence, 7);
+        assert_eq!(
+            records[0].original_id,
+            ObjectID::from_single_byte(1).to_string()
+        );
+        let lineage = tracker.lineage();
+        assert!(lineage.same_package(
+            &ObjectID::from_single_byte(2),
+            &ObjectID::from_single_byte(1)
+        ));
+        assert_eq!(lineage.len(), 1);
+
+        assert!(PackageTracker::from_config(&PackagesConfig::default()).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,2084 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::{stream, StreamExt, TryStreamExt};
+use sui_indexer_config::{EventFilter, IndexerConfig, RuntimeConfig, TransactionData};
+use sui_indexer_events::{
+    system_clock, watchlist, Clock, EventFilterProcessor, EventProcessor, ProcessedEvent,
+    ProcessedTransaction, SharedClock, Watchlist, Watchlists,
+};
+use sui_indexer_sinks::SinkManager;
+use sui_indexer_storage::{
+    CheckpointReport, MoveCallRecord, ObjectVersionRecord, PackageRecord, StorageManager,
+    DEFAULT_PIPELINE,
+};
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::TransactionDigest;
+use tokio::sync::{mpsc, watch, Notify};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, field, info, info_span, warn, Instrument, Span};
+
+use crate::{
+    autotune::Concurrency,
+    backfill::filter_handoff_cursor,
+    control::PipelineControl,
+    error::is_retryable,
+    error_history::PipelineStage,
+    error_reporting::{ErrorReport, ErrorReporting},
+    live::LiveEvents,
+    metrics::Metrics,
+    move_calls::MoveCallIndexer,
+    objects::ObjectTracker,
+    packages::PackageTracker,
+    readiness::Readiness,
+    rules::{RuleEngine, RuleMatch},
+    scheduler::{Resource, Scheduler},
+    sui::{CheckpointData, ClientError, SuiClient},
+};
+
+/// Checkpoint ingestion pipeline: fetch → filter → process → store → sinks
+///
+/// Checkpoints are fetched and processed concurrently but committed strictly
+/// in sequence, so the stored checkpoint cursor never skips ahead of data.
+/// Events reach the sinks only after their checkpoint is committed, with
+/// the outbox as part of the same transaction. Events of priority filters
+/// are the exception: they are stored and pushed to sinks without the
+/// outbox as soon as they are processed. Each
+/// checkpoint is traced by a `checkpoint` span covering its `fetch`,
+/// `transform`, `store` and `deliver` stages.
+pub struct Pipeline {
+    /// Name recorded on spans and per-pipeline metrics
+    name: &'static str,
+    client: SuiClient,
+    storage: StorageManager,
+    processor: Arc<dyn EventProcessor>,
+    config: watch::Receiver<IndexerConfig>,
+    metrics: Arc<Metrics>,
+    sinks: SinkManager,
+    readiness: Readiness,
+    control: PipelineControl,
+    reporting: ErrorReporting,
+    restarts: Option<Arc<Notify>>,
+    live: LiveEvents,
+    scheduler: Scheduler,
+    clock: SharedClock,
+    concurrency: Concurrency,
+    packages: Option<PackageTracker>,
+    move_calls: Option<MoveCallIndexer>,
+    objects: Option<ObjectTracker>,
+    watchlists: Option<Watchlists>,
+    rules: Option<RuleEngine>,
+    /// This pipeline's share of the `filter_in_flight` gauges
+    filters_in_flight: Mutex<BTreeMap<String, u64>>,
+}
+
+impl Pipeline {
+    /// Create a new pipeline over the given client, storage and processor
+    pub fn new(
+        client: SuiClient,
+        storage: StorageManager,
+        processor: Arc<dyn EventProcessor>,
+        config: watch::Receiver<IndexerConfig>,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        let concurrency =
+            Concurrency::new(DEFAULT_PIPELINE, &config.borrow().runtime, metrics.clone());
+        Self {
+            name: DEFAULT_PIPELINE,
+            client,
+            storage,
+            processor,
+            config,
+            metrics,
+            sinks: SinkManager::default(),
+            readiness: Readiness::default(),
+            control: PipelineControl::default(),
+            reporting: ErrorReporting::default(),
+            restarts: None,
+            live: LiveEvents::default(),
+            scheduler: Scheduler::default(),
+            clock: system_clock(),
+            concurrency,
+            packages: None,
+            move_calls: None,
+            objects: None,
+            watchlists: None,
+            rules: None,
+            filters_in_flight: Mutex::default(),
+        }
+    }
+
+    /// Deliver committed events to the given sinks
+    pub fn with_sinks(mut self, sinks: SinkManager) -> Self {
+        self.sinks = sinks;
+        self
+    }
+
+    /// Mark `readiness` once the first checkpoint has been fetched
+    pub fn with_readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
+    /// Pause, resume and inspect the queues of the pipeline through `control`
+    pub fn with_control(mut self, control: PipelineControl) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Report errors that stop the pipeline through `reporting`
+    pub fn with_error_reporting(mut self, reporting: ErrorReporting) -> Self {
+        self.reporting = reporting;
+        self
+    }
+
+    /// Restart fetching from the last committed checkpoint whenever `restarts` is notified
+    pub fn with_restarts(mut self, restarts: Arc<Notify>) -> Self {
+        self.restarts = Some(restarts);
+        self
+    }
+
+    /// Publish committed events to the subscribers of `live`
+    pub fn with_live(mut self, live: LiveEvents) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Share node requests and storage writes with other pipelines through `scheduler`
+    pub fn with_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Time stages and checkpoint availability with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Store the package versions `packages` tracks and match filters against their lineage
+    pub fn with_packages(mut self, packages: PackageTracker) -> Self {
+        self.packages = Some(packages);
+        self
+    }
+
+    /// Store the Move calls `move_calls` selects, following package upgrades if they are tracked
+    pub fn with_move_calls(mut self, move_calls: MoveCallIndexer) -> Self {
+        self.move_calls = Some(move_calls);
+        self
+    }
+
+    /// Store the versions of the objects `objects` tracks
+    pub fn with_objects(mut self, objects: ObjectTracker) -> Self {
+        self.objects = Some(objects);
+        self
+    }
+
+    /// Tag events involving `watchlists`, keeping those a capturing watchlist involves
+    pub fn with_watchlists(mut self, watchlists: Watchlists) -> Self {
+        self.watchlists = Some(watchlists);
+        self
+    }
+
+    /// Tag events meeting `rules` and post their messages once the events are committed
+    pub fn with_rules(mut self, rules: RuleEngine) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Event filter over `filters`, following package upgrades if they are tracked
+    fn event_filter(&self, filters: Vec<EventFilter>) -> Arc<EventFilterProcessor> {
+        let filter = EventFilterProcessor::new(filters);
+        Arc::new(match &self.packages {
+            Some(packages) => filter.with_lineage(packages.lineage()),
+            None => filter,
+        })
+    }
+
+    /// Run the pipeline from `start_checkpoint` until `shutdown` is cancelled
+    pub async fn run(mut self, start_checkpoint: u64, shutdown: CancellationToken) -> Result<()> {
+        let runtime = self.config.borrow().runtime.clone();
+        let control = self.control.clone();
+        control.watch_sinks(self.sinks.queues());
+        let restarts = self.restarts.take().unwrap_or_default();
+
+        info!(
+            start_checkpoint,
+            fetch_concurrency = runtime.fetch_concurrency,
+            writer_concurrency = runtime.writer_concurrency,
+            autotune = runtime.autotune.enabled,
+            "Starting checkpoint pipeline"
+        );
+        // Limits follow the runtime settings the pipeline runs with, not those it was built with
+        self.concurrency = Concurrency::new(self.name, &runtime, self.metrics.clone());
+        let tuning = tokio::spawn(self.concurrency.clone().run(shutdown.clone()));
+
+        let mut next = start_checkpoint;
+        loop {
+            let (tx, rx) = mpsc::channel(runtime.checkpoint_channel_capacity.max(1));
+            control.watch_checkpoints(&tx);
+            let attempt = shutdown.child_token();
+            let fetcher = Fetcher {
+                pipeline: self.name,
+                client: self.client.clone(),
+                runtime: runtime.clone(),
+                metrics: self.metrics.clone(),
+                scheduler: self.scheduler.clone(),
+                clock: self.clock.clone(),
+                concurrency: self.concurrency.clone(),
+                readiness: self.readiness.clone(),
+                max_lag: self.config.borrow().observability.ready_max_lag,
+            };
+            let mut fetch_task = tokio::spawn(fetcher.run(next, tx, attempt.clone()));
+
+            let restart = {
+                let process = self.process(rx, &runtime, &mut next);
+                tokio::pin!(process);
+                let finished = tokio::select! {
+                    result = &mut process => Some(result),
+                    _ = restarts.notified() => None,
+                    _ = shutdown.cancelled() => None,
+                };
+                match finished {
+                    Some(result) => result.map(|()| false),
+                    None => {
+                        let restart = !shutdown.is_cancelled();
+                        if !restart {
+                            // A paused pipeline still drains on shutdown
+                            control.resume();
+                        }
+                        // The fetcher stops on cancellation and drops its sender, which
+                        // lets the processor commit the checkpoints it already holds
+                        // instead of being dropped part way through a commit.
+                        attempt.cancel();
+                        (&mut fetch_task).await?;
+                        let drain_timeout = Duration::from_secs(runtime.shutdown_timeout);
+                        match tokio::time::timeout(drain_timeout, &mut process).await {
+                            Ok(result) => result.map(|()| restart),
+                            Err(_) => {
+                                warn!(
+                                    timeout_secs = runtime.shutdown_timeout,
+                                    restart, "Timed out draining checkpoints"
+                                );
+                                Ok(restart)
+                            }
+                        }
+                    }
+                }
+            };
+            self.abandon_in_flight();
+            if !restart? {
+                break;
+            }
+            info!(checkpoint = next, "Restarting checkpoint pipeline");
+        }
+
+        tuning.abort();
+        self.sinks.shutdown().await;
+        Ok(())
+    }
+
+    /// Filter and process checkpoints concurrently, then store them in order, advancing `next` past each commit
+    ///
+    /// Up to `process_concurrency` checkpoints are transformed at once and
+    /// held until every earlier one is committed, so the cursor only ever
+    /// moves past checkpoints whose events are all stored.
+    async fn process(
+        &self,
+        rx: mpsc::Receiver<FetchedCheckpoint>,
+        runtime: &RuntimeConfig,
+        next: &mut u64,
+    ) -> Result<()> {
+        let filters = self.config.borrow().events.filters.clone();
+        // Filters configured before this run keep the hand-off of the run that started them
+        self.hand_off_filters(&filters, *next, false).await?;
+        let filter = self.event_filter(filters.clone());
+
+        let received = stream::unfold(
+            (rx, self.config.clone(), filter, filters),
+            move |(mut rx, mut config, mut filter, mut filters)| async move {
+                let fetched = rx.recv().await?;
+                self.readiness.set_ready();
+                if self.control.is_paused() {
+                    self.control.wait_while_paused().await;
+                }
+                // Filters change between checkpoints in arrival order, never within one
+                let mut handed_off = Vec::new();
+                if config.has_changed().unwrap_or(false) {
+                    let reloaded = config.borrow_and_update().events.filters.clone();
+                    debug!(filters = reloaded.len(), "Rebuilding event filters");
+                    handed_off = reloaded
+                        .iter()
+                        .filter(|filter| !filters.contains(filter))
+                        .cloned()
+                        .collect();
+                    filter = self.event_filter(reloaded.clone());
+                    filters = reloaded;
+                }
+                // Recorded in arrival order, before any later checkpoint is filtered
+                let packages = match &self.packages {
+                    Some(packages) => packages.observe(&fetched.checkpoint),
+                    None => Vec::new(),
+                };
+                let slow = {
+                    let observability = &config.borrow().observability;
+                    SlowThresholds {
+                        checkpoint_ms: observability.slow_checkpoint_ms,
+                        event_ms: observability.slow_event_ms,
+                    }
+                };
+                Some((
+                    (fetched, filter.clone(), slow, packages, handed_off),
+                    (rx, config, filter, filters),
+                ))
+            },
+        );
+        let mut transformed = received
+            .map(move |(fetched, filter, slow, packages, handed_off)| {
+                self.transform(fetched, filter, slow, packages, handed_off)
+            })
+            .buffered(runtime.process_concurrency.max(1));
+
+        while let Some(Transformed {
+            sequence_number,
+            timestamp_ms,
+            transactions,
+            events,
+            expedited,
+            rows,
+            rule_matches,
+            by_filter,
+            filtered,
+            handed_off,
+            span,
+            fetch_duration,
+            started,
+            transform_duration,
+            slow_checkpoint_ms,
+        }) = transformed.try_next().await?
+        {
+            // Recorded ahead of the commit, so a crash in between resumes with the new filters
+            self.hand_off_filters(&handed_off, sequence_number, true)
+                .await?;
+            let event_count = events.len() as u64;
+            let store_started = self.clock.instant();
+            self.commit(sequence_number, &events, &rows, &by_filter, runtime)
+                .instrument(info_span!(parent: &span, "store", events = event_count))
+                .await?;
+            *next = sequence_number + 1;
+            let store_duration = self.clock.elapsed(store_started);
+            let now_ms = self.clock.now().timestamp_millis().max(0) as u64;
+            self.metrics
+                .record_availability(Duration::from_millis(now_ms.saturating_sub(timestamp_ms)));
+            self.account_stored(event_count, &by_filter, &filtered);
+            self.live.publish(sequence_number, &events);
+            let deliver_started = self.clock.instant();
+            self.sinks
+                .deliver(&events[expedited..])
+                .instrument(info_span!(parent: &span, "deliver"))
+                .await;
+            if let Some(rules) = &self.rules {
+                rules.notify(rule_matches);
+            }
+            let deliver_duration = self.clock.elapsed(deliver_started);
+
+            let busy = transform_duration + store_duration;
+            if let Some(threshold_ms) =
+                slow_checkpoint_ms.filter(|threshold| busy.as_millis() > u128::from(*threshold))
+            {
+                warn!(
+                    parent: &span,
+                    checkpoint = sequence_number,
+                    transactions,
+                    events = event_count,
+                    threshold_ms,
+                    total_ms = millis(fetch_duration + self.clock.elapsed(started)),
+                    fetch_ms = millis(fetch_duration),
+                    transform_ms = millis(transform_duration),
+                    store_ms = millis(store_duration),
+                    deliver_ms = millis(deliver_duration),
+                    "Slow checkpoint"
+                );
+            }
+
+            let report = CheckpointReport {
+                pipeline: self.name.to_string(),
+                checkpoint_sequence: sequence_number as i64,
+                transactions: transactions as i64,
+                events_seen: filtered.seen as i64,
+                events_matched: filtered.matched as i64,
+                events_skipped: (filtered.seen - filtered.matched) as i64,
+                events_stored: event_count as i64,
+                matched_by_filter: serde_json::json!(filtered.by_filter),
+                fetch_ms: millis(fetch_duration) as i64,
+                transform_ms: millis(transform_duration) as i64,
+                store_ms: millis(store_duration) as i64,
+                deliver_ms: millis(deliver_duration) as i64,
+                processed_at: self.clock.now(),
+            };
+            // The report is an audit record; losing one never holds up indexing
+            if let Err(e) = self.storage.record_checkpoint_report(&report).await {
+                warn!(
+                    checkpoint = sequence_number,
+                    error = %e,
+                    "Failed to record checkpoint report"
+                );
+            }
+
+            self.metrics.checkpoints_processed.inc();
+            self.metrics.events_processed.inc_by(event_count);
+            self.metrics
+                .latest_processed_checkpoint
+                .set(sequence_number as i64);
+            self.readiness.observe_lag(
+                &self.metrics,
+                self.config.borrow().observability.ready_max_lag,
+            );
+            self.metrics
+                .checkpoint_duration_seconds
+                .observe(self.clock.elapsed(started).as_secs_f64());
+        }
+
+        Ok(())
+    }
+
+    /// Record `checkpoint` as the hand-off of the named `filters` to the live pipeline
+    ///
+    /// Unless `replace` is set, a filter keeps the hand-off recorded for it earlier.
+    async fn hand_off_filters(
+        &self,
+        filters: &[EventFilter],
+        checkpoint: u64,
+        replace: bool,
+    ) -> Result<()> {
+        for name in filters.iter().filter_map(|filter| filter.name.as_deref()) {
+            let cursor = filter_handoff_cursor(name);
+            if replace || self.storage.get_cursor(&cursor).await?.is_none() {
+                debug!(filter = name, checkpoint, "Live pipeline took over filter");
+                self.storage.set_cursor(&cursor, checkpoint).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Filter and process one fetched checkpoint, ready to be committed
+    async fn transform(
+        &self,
+        fetched: FetchedCheckpoint,
+        filter: Arc<EventFilterProcessor>,
+        slow: SlowThresholds,
+        packages: Vec<PackageRecord>,
+        handed_off: Vec<EventFilter>,
+    ) -> Result<Transformed> {
+        let FetchedCheckpoint {
+            checkpoint,
+            span,
+            fetch_duration,
+        } = fetched;
+        let started = self.clock.instant();
+        let sequence_number = checkpoint.sequence_number;
+        let timestamp_ms = checkpoint.timestamp_ms;
+        let transactions = checkpoint.transactions.len();
+        let move_calls = match &self.move_calls {
+            Some(move_calls) => move_calls.extract(&checkpoint),
+            None => Vec::new(),
+        };
+        let object_versions = match &self.objects {
+            Some(objects) => objects.extract(&checkpoint),
+            None => Vec::new(),
+        };
+        let transaction_data = {
+            let events = &self.config.borrow().events;
+            events
+                .index_transactions
+                .then_some(events.include_transaction_data)
+        };
+        let mut digests = match transaction_data {
+            Some(TransactionData::All) => checkpoint.transactions.clone(),
+            _ => Vec::new(),
+        };
+        let Processed {
+            events,
+            filtered,
+            rule_matches,
+            expedited,
+        } = self
+            .process_checkpoint(checkpoint, &filter, slow.event_ms)
+            .instrument(info_span!(parent: &span, "transform"))
+            .await?;
+        if transaction_data == Some(TransactionData::Matched) {
+            let mut seen = HashSet::new();
+            digests = events
+                .iter()
+                .map(|event| event.transaction_digest)
+                .filter(|digest| seen.insert(*digest))
+                .collect();
+        }
+        let transaction_data = self
+            .fetch_transactions(sequence_number, timestamp_ms, &digests, &events, &span)
+            .await?;
+        span.record("events", events.len() as u64);
+        let by_filter = count_by_filter(&filter, &events);
+
+        Ok(Transformed {
+            sequence_number,
+            timestamp_ms,
+            transactions,
+            events,
+            expedited,
+            rows: CheckpointRows {
+                packages,
+                move_calls,
+                object_versions,
+                transactions: transaction_data,
+            },
+            rule_matches,
+            by_filter,
+            filtered,
+            handed_off,
+            span,
+            fetch_duration,
+            started,
+            transform_duration: self.clock.elapsed(started),
+            slow_checkpoint_ms: slow.checkpoint_ms,
+        })
+    }
+
+    /// Full data of the transactions `digests` of a checkpoint, with their matched events
+    ///
+    /// Fetched in batched requests and retried until the node answers, as
+    /// the checkpoint cannot be committed without them. Errors that cannot
+    /// clear up, such as a client that cannot fetch transactions, fail the
+    /// checkpoint instead.
+    async fn fetch_transactions(
+        &self,
+        sequence_number: u64,
+        timestamp_ms: u64,
+        digests: &[TransactionDigest],
+        events: &[ProcessedEvent],
+        span: &Span,
+    ) -> Result<Vec<ProcessedTransaction>> {
+        if digests.is_empty() {
+            return Ok(Vec::new());
+        }
+        let retry_delay = Duration::from_millis(self.config.borrow().runtime.poll_interval);
+        let fetched = loop {
+            let result = {
+                let _turn = self.scheduler.acquire(self.name, Resource::Node).await;
+                self.client
+                    .get_transactions(digests)
+                    .instrument(info_span!(
+                        parent: span,
+                        "fetch_transactions",
+                        transactions = digests.len()
+                    ))
+                    .await
+            };
+            match result {
+                Ok(fetched) => break fetched,
+                Err(e) if !crate::is_retryable(&e) => {
+                    return Err(e).wrap_err_with(|| {
+                        format!("Failed to fetch the transactions of checkpoint {sequence_number}")
+                    })
+                }
+                Err(e) => {
+                    warn!(
+                        parent: span,
+                        checkpoint = sequence_number,
+                        error = %e,
+                        "Failed to fetch transaction data, retrying"
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                }
+            }
+        };
+        self.metrics
+            .transactions_fetched
+            .inc_by(fetched.len() as u64);
+
+        let now = self.clock.now();
+        let checkpoint_time = DateTime::<Utc>::from_timestamp_millis(timestamp_ms as i64);
+        Ok(fetched
+            .into_iter()
+            .map(|response| {
+                let stamped = response.timestamp_ms.is_some();
+                let mut transaction = ProcessedTransaction::from_response(response, now);
+                transaction.checkpoint_sequence = sequence_number;
+                if !stamped {
+                    transaction.timestamp = checkpoint_time.unwrap_or(now);
+                }
+                transaction.events = events
+                    .iter()
+                    .filter(|event| event.transaction_digest == transaction.transaction.digest)
+                    .cloned()
+                    .collect();
+                transaction
+            })
+            .collect())
+    }
+
+    /// Run matching events of a checkpoint through the event processor and the rules
+    ///
+    /// Events of `priority = "high"` filters are processed first, in batches of
+    /// `priority_batch_size`, and [expedited](Self::expedite) before the rest
+    /// of the checkpoint is processed; they lead the returned events.
+    async fn process_checkpoint(
+        &self,
+        checkpoint: CheckpointData,
+        filter: &EventFilterProcessor,
+        slow_event_ms: Option<u64>,
+    ) -> Result<Processed> {
+        let sequence_number = checkpoint.sequence_number;
+        let (batch_size, max_concurrent_batches, priority_batch_size, priority_timeout) = {
+            let config = self.config.borrow();
+            (
+                config.events.batch_size.max(1),
+                config.events.max_concurrent_batches.max(1),
+                config.events.priority_batch_size.max(1),
+                Duration::from_millis(config.events.priority_timeout_ms),
+            )
+        };
+
+        // One snapshot for the whole checkpoint, even if the watchlists are reloaded meanwhile
+        let watchlists = self
+            .watchlists
+            .as_ref()
+            .map(Watchlists::current)
+            .unwrap_or_default();
+
+        let seen = checkpoint.events.len() as u64;
+        let mut matched = Vec::new();
+        let mut matched_by_filter = BTreeMap::new();
+        for event in checkpoint.events {
+            if !filter.has_filters() {
+                matched.push(event);
+                continue;
+            }
+            if let Some((index, matching)) = filter.matching_filter(&event) {
+                let name = filter_label(index, matching);
+                debug!(
+                    filter = %name,
+                    tx_digest = %event.id.tx_digest,
+                    event_type = %event.type_.name,
+                    "Event matched filter"
+                );
+                *matched_by_filter.entry(name).or_default() += 1;
+                matched.push(event);
+            } else if watchlist::involving(&watchlists, &event)
+                .iter()
+                .any(|list| list.capture)
+            {
+                debug!(
+                    tx_digest = %event.id.tx_digest,
+                    event_type = %event.type_.name,
+                    "Event captured by watchlist"
+                );
+                matched.push(event);
+            }
+        }
+        let filtered = Filtered {
+            seen,
+            matched: matched.len() as u64,
+            by_filter: matched_by_filter,
+        };
+        self.account_matched(filtered.seen, filtered.matched, &filtered.by_filter);
+        let matched_filters: BTreeSet<_> = filtered.by_filter.keys().cloned().collect();
+        let (priority, bulk): (Vec<_>, Vec<_>) = matched
+            .into_iter()
+            .partition(|event| filter.is_priority(event));
+
+        let mut events = Vec::new();
+        let mut rule_matches = Vec::new();
+        let mut expedited = 0;
+        if !priority.is_empty() {
+            events = self
+                .process_batches(
+                    priority,
+                    priority_batch_size,
+                    max_concurrent_batches,
+                    slow_event_ms,
+                )
+                .await
+                .map_err(|e| self.transform_failed(sequence_number, e, &matched_filters))?;
+            self.tag_events(sequence_number, &watchlists, &mut events);
+            rule_matches = self.evaluate_rules(&mut events);
+            if self
+                .expedite(sequence_number, &events, priority_timeout)
+                .await
+            {
+                expedited = events.len();
+                if let Some(rules) = &self.rules {
+                    rules.notify(std::mem::take(&mut rule_matches));
+                }
+            }
+        }
+
+        let mut processed = self
+            .process_batches(bulk, batch_size, max_concurrent_batches, slow_event_ms)
+            .await
+            .map_err(|e| self.transform_failed(sequence_number, e, &matched_filters))?;
+        self.tag_events(sequence_number, &watchlists, &mut processed);
+        rule_matches.extend(self.evaluate_rules(&mut processed));
+        events.extend(processed);
+
+        Ok(Processed {
+            events,
+            filtered,
+            rule_matches,
+            expedited,
+        })
+    }
+
+    /// Run events through the event processor in concurrent batches of `batch_size`
+    async fn process_batches(
+        &self,
+        events: Vec<SuiEvent>,
+        batch_size: usize,
+        max_concurrent_batches: usize,
+        slow_event_ms: Option<u64>,
+    ) -> Result<Vec<ProcessedEvent>> {
+        let batches: Vec<_> = into_batches(events, batch_size)
+            .into_iter()
+            .map(|chunk| {
+                let processor = self.processor.clone();
+                let clock = self.clock.clone();
+                let transactions: BTreeSet<_> = chunk
+                    .iter()
+                    .map(|event| event.id.tx_digest.to_string())
+                    .collect();
+                let span = info_span!(
+                    "transform_batch",
+                    events = chunk.len(),
+                    tx_digests = %transactions.into_iter().collect::<Vec<_>>().join(","),
+                );
+                let batch = async move {
+                    match slow_event_ms {
+                        Some(threshold_ms) => {
+                            process_timed(processor.as_ref(), chunk, threshold_ms, clock.as_ref())
+                                .await
+                        }
+                        None => processor.process_events(chunk).await,
+                    }
+                }
+                .instrument(span);
+                // Spawned so batches of concurrent checkpoints run on all worker threads
+                async move { tokio::spawn(batch).await? }
+            })
+            .collect();
+        let batches: Vec<Vec<ProcessedEvent>> = stream::iter(batches)
+            .buffered(max_concurrent_batches)
+            .try_collect()
+            .await?;
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    /// Record and report an event processing error, which ends the pipeline
+    fn transform_failed(
+        &self,
+        sequence_number: u64,
+        error: eyre::Report,
+        matched_filters: &BTreeSet<String>,
+    ) -> eyre::Report {
+        self.record_errors(
+            PipelineStage::Transform,
+            sequence_number,
+            &error,
+            matched_filters,
+        );
+        // Processing errors are not retried, so they end the pipeline
+        self.reporting.report(ErrorReport::pipeline(
+            self.name,
+            PipelineStage::Transform,
+            sequence_number,
+            matched_filters.iter().cloned().collect(),
+            &error,
+        ));
+        error
+    }
+
+    /// Stamp processed events with their checkpoint and the watchlists involving them
+    fn tag_events(
+        &self,
+        sequence_number: u64,
+        watchlists: &[Watchlist],
+        events: &mut [ProcessedEvent],
+    ) {
+        for event in events {
+            event.checkpoint_sequence = sequence_number;
+            for list in watchlist::tag_event(watchlists, event) {
+                self.metrics
+                    .watchlist_events
+                    .with_label_values(&[&list.name])
+                    .inc();
+            }
+        }
+    }
+
+    /// Tag events meeting the rules, returning the messages to post
+    fn evaluate_rules(&self, events: &mut [ProcessedEvent]) -> Vec<RuleMatch> {
+        match &self.rules {
+            Some(rules) => events
+                .iter_mut()
+                .flat_map(|event| rules.evaluate(event))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Store events of priority filters and push them to the sinks ahead of their checkpoint
+    ///
+    /// Returns whether the events were stored within `timeout`; if not, they
+    /// are delivered with the rest of the checkpoint once it is committed.
+    /// Storing them again with the checkpoint skips them, and sinks fed from
+    /// the outbox still only read them after the commit.
+    async fn expedite(
+        &self,
+        sequence_number: u64,
+        events: &[ProcessedEvent],
+        timeout: Duration,
+    ) -> bool {
+        let stored = tokio::time::timeout(timeout, self.storage.store_events(events)).await;
+        let outcome = match stored {
+            Ok(Ok(())) => {
+                self.sinks.deliver_now(events).await;
+                debug!(
+                    checkpoint = sequence_number,
+                    events = events.len(),
+                    "Priority events delivered"
+                );
+                "expedited"
+            }
+            Ok(Err(e)) => {
+                warn!(
+                    checkpoint = sequence_number,
+                    error = %e,
+                    "Failed to store priority events, leaving them to the checkpoint"
+                );
+                "deferred"
+            }
+            Err(_) => {
+                warn!(
+                    checkpoint = sequence_number,
+                    timeout_ms = millis(timeout),
+                    "Timed out storing priority events, leaving them to the checkpoint"
+                );
+                "deferred"
+            }
+        };
+        self.metrics
+            .priority_events
+            .with_label_values(&[outcome])
+            .inc_by(events.len() as u64);
+        outcome == "expedited"
+    }
+
+    /// Store a checkpoint's rows and events and advance the cursor, retrying on failure
+    ///
+    /// Packages, Move calls, object versions and transactions are stored first
+    /// and kept on a retry, so the cursor never moves past a checkpoint whose
+    /// rows are missing.
+    async fn commit(
+        &self,
+        sequence_number: u64,
+        events: &[ProcessedEvent],
+        rows: &CheckpointRows,
+        by_filter: &BTreeMap<String, u64>,
+        runtime: &RuntimeConfig,
+    ) -> Result<()> {
+        let batch_size = self.config.borrow().events.batch_size.max(1);
+        let retry_delay = Duration::from_millis(runtime.poll_interval);
+        let mut failures = 0;
+
+        loop {
+            let result = if let Err(e) = rows.store(&self.storage).await {
+                Err(e)
+            } else if self.sinks.uses_outbox() {
+                let _slot = self.concurrency.write().await;
+                let _turn = self.scheduler.acquire(self.name, Resource::Storage).await;
+                let started = self.clock.instant();
+                let result = self
+                    .storage
+                    .commit_with_outbox(sequence_number, events, self.sinks.outbox_entries(events))
+                    .await;
+                self.concurrency.record_write(self.clock.elapsed(started));
+                result
+            } else {
+                self.store(sequence_number, events, batch_size, runtime)
+                    .await
+            };
+
+            match result {
+                Ok(()) => {
+                    debug!(
+                        checkpoint = sequence_number,
+                        events = events.len(),
+                        "Checkpoint committed"
+                    );
+                    self.close_storage_circuit(sequence_number);
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.metrics.storage_errors.inc();
+                    failures += 1;
+                    if failures == runtime.storage_breaker_failures {
+                        self.open_storage_circuit(sequence_number, failures);
+                    }
+                    self.record_errors(PipelineStage::Store, sequence_number, &e, by_filter.keys());
+                    // A write the database refused fails again, so stop rather than spin
+                    if !is_retryable(&e) {
+                        let e = e.wrap_err(format!("Failed to store checkpoint {sequence_number}"));
+                        self.reporting.report(ErrorReport::pipeline(
+                            self.name,
+                            PipelineStage::Store,
+                            sequence_number,
+                            by_filter.keys().cloned().collect(),
+                            &e,
+                        ));
+                        return Err(e);
+                    }
+                    error!(
+                        checkpoint = sequence_number,
+                        error = %e,
+                        "Failed to store checkpoint, retrying"
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                }
+            }
+        }
+    }
+
+    /// Hold back sinks and live subscribers after repeated commit failures
+    ///
+    /// Nothing past the stored cursor is ever delivered, but without storage
+    /// outbox acknowledgements fail and subscribers would wait unaware.
+    fn open_storage_circuit(&self, sequence_number: u64, failures: u32) {
+        self.sinks.pause_switch().pause();
+        let last_checkpoint = sequence_number.checked_sub(1);
+        if self.live.pause(
+            "Storage is failing; events resume once it recovers",
+            last_checkpoint,
+        ) {
+            warn!(
+                checkpoint = sequence_number,
+                failures, "Storage circuit open, pausing sinks and live subscribers"
+            );
+        }
+        self.metrics.storage_circuit_open.set(1);
+    }
+
+    /// Release sinks and live subscribers once a commit succeeds again
+    fn close_storage_circuit(&self, sequence_number: u64) {
+        let sinks = self.sinks.pause_switch().resume();
+        if self.live.resume() || sinks {
+            info!(
+                checkpoint = sequence_number,
+                "Storage recovered, resuming sinks and live subscribers"
+            );
+            self.metrics.storage_circuit_open.set(0);
+        }
+    }
+
+    /// Store events in concurrent batches, then advance the checkpoint progress
+    async fn store(
+        &self,
+        sequence_number: u64,
+        events: &[ProcessedEvent],
+        batch_size: usize,
+        runtime: &RuntimeConfig,
+    ) -> Result<()> {
+        let storage = &self.storage;
+        let writes: Vec<_> = events
+            .chunks(batch_size)
+            .map(move |chunk| async move {
+                let _slot = self.concurrency.write().await;
+                let _turn = self.scheduler.acquire(self.name, Resource::Storage).await;
+                let started = self.clock.instant();
+                let stored = storage.store_events(chunk).await;
+                self.concurrency.record_write(self.clock.elapsed(started));
+                stored
+            })
+            .collect();
+        stream::iter(writes)
+            .buffer_unordered(self.concurrency.writer_bound())
+            .try_collect::<Vec<()>>()
+            .await?;
+        self.storage
+            .update_checkpoint_progress(sequence_number)
+            .await
+    }
+
+    /// Count the events of a checkpoint run through the filters, holding the matched ones in flight
+    fn account_matched(&self, seen: u64, matched: u64, by_filter: &BTreeMap<String, u64>) {
+        let metrics = &self.metrics;
+        let _accounting = metrics
+            .accounting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        metrics
+            .pipeline_seen
+            .with_label_values(&[self.name])
+            .inc_by(seen);
+        metrics
+            .pipeline_filtered
+            .with_label_values(&[self.name])
+            .inc_by(seen - matched);
+        metrics
+            .pipeline_matches
+            .with_label_values(&[self.name])
+            .inc_by(matched);
+        metrics
+            .pipeline_in_flight
+            .with_label_values(&[self.name])
+            .add(matched as i64);
+        for (name, count) in by_filter {
+            metrics
+                .filter_matches
+                .with_label_values(&[name])
+                .inc_by(*count);
+        }
+        self.hold_in_flight(by_filter);
+    }
+
+    /// Count the events of a committed checkpoint as stored, settling the ones it `filtered` in flight
+    ///
+    /// Other checkpoints matched meanwhile stay in flight until their own commit.
+    fn account_stored(&self, stored: u64, by_filter: &BTreeMap<String, u64>, filtered: &Filtered) {
+        let metrics = &self.metrics;
+        let _accounting = metrics
+            .accounting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        metrics
+            .pipeline_stored
+            .with_label_values(&[self.name])
+            .inc_by(stored);
+        for (name, stored) in by_filter {
+            metrics
+                .filter_stored
+                .with_label_values(&[name])
+                .inc_by(*stored);
+        }
+        metrics
+            .pipeline_in_flight
+            .with_label_values(&[self.name])
+            .sub(filtered.matched as i64);
+        self.release_in_flight(&filtered.by_filter);
+    }
+
+    /// Count events still in flight as abandoned, once their checkpoint will not be committed
+    fn abandon_in_flight(&self) {
+        let metrics = &self.metrics;
+        let _accounting = metrics
+            .accounting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let in_flight = metrics.pipeline_in_flight.with_label_values(&[self.name]);
+        metrics
+            .pipeline_abandoned
+            .with_label_values(&[self.name])
+            .inc_by(in_flight.get().max(0) as u64);
+        in_flight.set(0);
+        for (name, count) in self.take_in_flight() {
+            metrics
+                .filter_abandoned
+                .with_label_values(&[&name])
+                .inc_by(count);
+        }
+    }
+
+    /// Add the matched events of a checkpoint to those this pipeline holds in flight per filter
+    fn hold_in_flight(&self, by_filter: &BTreeMap<String, u64>) {
+        let mut held = self.held_in_flight();
+        for (name, count) in by_filter {
+            *held.entry(name.clone()).or_default() += count;
+            self.metrics
+                .filter_in_flight
+                .with_label_values(&[name])
+                .add(*count as i64);
+        }
+    }
+
+    /// Take the matched events of a committed checkpoint off those held in flight
+    fn release_in_flight(&self, by_filter: &BTreeMap<String, u64>) {
+        let mut held = self.held_in_flight();
+        for (name, count) in by_filter {
+            let Some(current) = held.get_mut(name) else {
+                continue;
+            };
+            let released = (*count).min(*current);
+            *current -= released;
+            if *current == 0 {
+                held.remove(name);
+            }
+            self.metrics
+                .filter_in_flight
+                .with_label_values(&[name])
+                .sub(released as i64);
+        }
+    }
+
+    /// Take every event this pipeline holds in flight, returning them per filter
+    ///
+    /// Filters can be shared by pipelines, so only this pipeline's share
+    /// is taken off the gauges; the counts of other pipelines stay put.
+    fn take_in_flight(&self) -> BTreeMap<String, u64> {
+        let held = std::mem::take(&mut *self.held_in_flight());
+        for (name, count) in &held {
+            self.metrics
+                .filter_in_flight
+                .with_label_values(&[name])
+                .sub(*count as i64);
+        }
+        held
+    }
+
+    fn held_in_flight(&self) -> MutexGuard<'_, BTreeMap<String, u64>> {
+        self.filters_in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Count a failed attempt against this pipeline and the given filters
+    fn record_errors<'a>(
+        &self,
+        stage: PipelineStage,
+        checkpoint: u64,
+        error: &eyre::Report,
+        filters: impl IntoIterator<Item = &'a String>,
+    ) {
+        self.metrics
+            .error_history
+            .record(self.name, stage, Some(checkpoint), error);
+        self.metrics
+            .pipeline_errors
+            .with_label_values(&[self.name])
+            .inc();
+        for name in filters {
+            self.metrics.filter_errors.with_label_values(&[name]).inc();
+        }
+    }
+}
+
+/// Name a filter is reported under, its position for unnamed filters
+pub(crate) fn filter_label(index: usize, filter: &EventFilter) -> String {
+    filter
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("filters[{index}]"))
+}
+
+/// Process events one call each, warning about calls slower than `threshold_ms`
+async fn process_timed(
+    processor: &dyn EventProcessor,
+    events: Vec<SuiEvent>,
+    threshold_ms: u64,
+    clock: &dyn Clock,
+) -> Result<Vec<ProcessedEvent>> {
+    let mut processed = Vec::with_capacity(events.len());
+    for event in events {
+        let id = event.id;
+        let event_type = event.type_.clone();
+        let started = clock.instant();
+        processed.push(processor.process_event(event).await?);
+
+        let elapsed = clock.elapsed(started);
+        if elapsed.as_millis() > u128::from(threshold_ms) {
+            warn!(
+                tx_digest = %id.tx_digest,
+                event_seq = id.event_seq,
+                event_type = %event_type,
+                elapsed_ms = millis(elapsed),
+                threshold_ms,
+                "Slow event processor call"
+            );
+        }
+    }
+    Ok(processed)
+}
+
+/// Split owned items into batches of at most `size` without copying them
+fn into_batches<T>(items: Vec<T>, size: usize) -> Vec<Vec<T>> {
+    let mut batches = Vec::with_capacity(items.len().div_ceil(size));
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        batches.push(items.by_ref().take(size).collect());
+    }
+    batches
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Processed events by the name of the first filter they match
+fn count_by_filter(
+    filter: &EventFilterProcessor,
+    events: &[ProcessedEvent],
+) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    if !filter.has_filters() {
+        return counts;
+    }
+    for event in events {
+        if let Some((index, matching)) = filter.matching_filter(&event.event) {
+            *counts.entry(filter_label(index, matching)).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Durations above which a checkpoint or event processor call is logged as slow
+#[derive(Clone, Copy)]
+struct SlowThresholds {
+    checkpoint_ms: Option<u64>,
+    event_ms: Option<u64>,
+}
+
+/// Events of a checkpoint run through the filters
+struct Filtered {
+    seen: u64,
+    matched: u64,
+    /// Matched events by filter name, empty without filters
+    by_filter: BTreeMap<String, u64>,
+}
+
+/// Matched events of a checkpoint run through the event processor and the rules
+struct Processed {
+    events: Vec<ProcessedEvent>,
+    filtered: Filtered,
+    /// Rule messages not posted yet
+    rule_matches: Vec<RuleMatch>,
+    /// Leading events already stored and delivered ahead of the checkpoint
+    expedited: usize,
+}
+
+/// A processed checkpoint waiting for the earlier ones to be committed
+struct Transformed {
+    sequence_number: u64,
+    timestamp_ms: u64,
+    transactions: usize,
+    events: Vec<ProcessedEvent>,
+    /// Leading events already delivered to the sinks
+    expedited: usize,
+    rows: CheckpointRows,
+    /// Rule messages to post once the checkpoint is committed
+    rule_matches: Vec<RuleMatch>,
+    by_filter: BTreeMap<String, u64>,
+    filtered: Filtered,
+    /// Filters added or changed from this checkpoint on
+    handed_off: Vec<EventFilter>,
+    span: Span,
+    fetch_duration: Duration,
+    /// When transforming started
+    started: Instant,
+    transform_duration: Duration,
+    slow_checkpoint_ms: Option<u64>,
+}
+
+/// Rows of a checkpoint stored ahead of its events
+struct CheckpointRows {
+    /// Tracked package versions published in the checkpoint
+    packages: Vec<PackageRecord>,
+    /// Selected Move calls made in the checkpoint
+    move_calls: Vec<MoveCallRecord>,
+    /// Versions of tracked objects written in the checkpoint
+    object_versions: Vec<ObjectVersionRecord>,
+    /// Fetched transactions, with the matched events they emitted
+    transactions: Vec<ProcessedTransaction>,
+}
+
+impl CheckpointRows {
+    async fn store(&self, storage: &StorageManager) -> Result<()> {
+        storage.store_packages(&self.packages).await?;
+        storage.store_move_calls(&self.move_calls).await?;
+        storage.store_object_versions(&self.object_versions).await?;
+        if !self.transactions.is_empty() {
+            storage
+                .store_transactions(self.transactions.clone())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// A fetched checkpoint with the span tracing it through the pipeline
+pub(crate) struct FetchedCheckpoint {
+    pub(crate) checkpoint: CheckpointData,
+    pub(crate) span: Span,
+    /// Time the node took to return the checkpoint
+    pub(crate) fetch_duration: Duration,
+}
+
+/// Fetches checkpoints from the node in order with bounded fan-out
+pub(crate) struct Fetcher {
+    /// Name recorded on checkpoint spans, like the cursor of the pipeline
+    pub(crate) pipeline: &'static str,
+    pub(crate) client: SuiClient,
+    pub(crate) runtime: RuntimeConfig,
+    pub(crate) metrics: Arc<Metrics>,
+    /// Turns of node requests shared with the other pipelines
+    pub(crate) scheduler: Scheduler,
+    pub(crate) clock: SharedClock,
+    /// Fan-out of checkpoint fetches, tuned from the node's error rate
+    pub(crate) concurrency: Concurrency,
+    /// Readiness kept in line with the lag behind each network tip seen
+    pub(crate) readiness: Readiness,
+    pub(crate) max_lag: u64,
+}
+
+impl Fetcher {
+    /// Send checkpoints from `next` onwards to `tx`, following the network tip
+    pub(crate) async fn run(
+        self,
+        mut next: u64,
+        tx: mpsc::Sender<FetchedCheckpoint>,
+        shutdown: CancellationToken,
+    ) {
+        let request_timeout = Duration::from_secs(self.runtime.request_timeout);
+        let mut poll = tokio::time::interval(Duration::from_millis(self.runtime.poll_interval));
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = poll.tick() => {}
+            }
+
+            let latest =
+                match tokio::time::timeout(request_timeout, self.client.get_latest_checkpoint())
+                    .await
+                {
+                    Ok(Ok(latest)) => {
+                        self.concurrency.record_request(true);
+                        latest
+                    }
+                    Ok(Err(e)) => {
+                        self.concurrency.record_request(false);
+                        self.record_fetch_error(None, &e);
+                        warn!(error = %e, "Failed to get latest checkpoint");
+                        continue;
+                    }
+                    Err(_) => {
+                        self.concurrency.record_request(false);
+                        self.record_fetch_error(
+                            None,
+                            &eyre::Report::from(ClientError::Timeout(
+                                "getting latest checkpoint".to_string(),
+                            )),
+                        );
+                        warn!("Timed out getting latest checkpoint");
+                        continue;
+                    }
+                };
+
+            self.metrics.latest_network_checkpoint.set(latest as i64);
+            self.readiness.observe_lag(&self.metrics, self.max_lag);
+            if next > latest {
+                continue;
+            }
+
+            let client = &self.client;
+            let scheduler = &self.scheduler;
+            let clock = &self.clock;
+            let concurrency = &self.concurrency;
+            let pipeline = self.pipeline;
+            let mut checkpoints = stream::iter(next..=latest)
+                .map(|sequence_number| {
+                    let span = info_span!(
+                        "checkpoint",
+                        pipeline = self.pipeline,
+                        checkpoint = sequence_number,
+                        transactions = field::Empty,
+                        events = field::Empty,
+                    );
+                    let fetch = info_span!(parent: &span, "fetch", checkpoint = sequence_number);
+                    async move {
+                        let slot = concurrency.fetch().await;
+                        let _turn = scheduler.acquire(pipeline, Resource::Node).await;
+                        let started = clock.instant();
+                        let checkpoint = tokio::time::timeout(
+                            request_timeout,
+                            client.get_checkpoint(sequence_number),
+                        )
+                        .instrument(fetch)
+                        .await
+                        .map_err(|_| {
+                            eyre::Report::from(ClientError::Timeout(format!(
+                                "fetching checkpoint {sequence_number}"
+                            )))
+                        })
+                        .and_then(|result| result);
+                        drop(slot);
+                        concurrency.record_request(checkpoint.is_ok());
+                        let checkpoint = checkpoint
+                            .and_then(|checkpoint| {
+                                // Committing another checkpoint would move the cursor to it
+                                if checkpoint.sequence_number != sequence_number {
+                                    return Err(ClientError::WrongCheckpoint {
+                                        requested: sequence_number,
+                                        served: checkpoint.sequence_number,
+                                    }
+                                    .into());
+                                }
+                                Ok(checkpoint)
+                            })
+                            .inspect_err(|e| {
+                                // Inside the checkpoint span so sampled tracing keeps the trace
+                                warn!(
+                                    parent: &span,
+                                    checkpoint = sequence_number,
+                                    error = %e,
+                                    "Failed to fetch checkpoint"
+                                );
+                            })?;
+                        span.record("transactions", checkpoint.transactions.len());
+                        Ok::<_, eyre::Report>(FetchedCheckpoint {
+                            checkpoint,
+                            span,
+                            fetch_duration: clock.elapsed(started),
+                        })
+                    }
+                })
+                .buffered(self.concurrency.fetch_bound());
+
+            loop {
+                let checkpoint = tokio::select! {
+                    _ = shutdown.cancelled() => return,
+                    checkpoint = checkpoints.next() => checkpoint,
+                };
+
+                let fetched = match checkpoint {
+                    Some(Ok(fetched)) => fetched,
+                    Some(Err(e)) => {
+                        self.record_fetch_error(Some(next), &e);
+                        break;
+                    }
+                    None => break,
+                };
+
+                next = fetched.checkpoint.sequence_number + 1;
+                tokio::select! {
+                    _ = shutdown.cancelled() => return,
+                    sent = tx.send(fetched) => {
+                        if sent.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_fetch_error(&self, checkpoint: Option<u64>, error: &eyre::Report) {
+        self.metrics.fetch_errors.inc();
+        self.metrics
+            .error_history
+            .record(self.pipeline, PipelineStage::Fetch, checkpoint, error);
+        self.metrics
+            .pipeline_errors
+            .with_label_values(&[self.pipeline])
+            .inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::FilterPriority;
+    use sui_indexer_events::{DefaultEventProcessor, ManualClock};
+    use sui_indexer_sinks::ChannelSink;
+    use sui_indexer_storage::{MemoryStorage, TransactionQuery};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::{reconcile::ReconciliationReport, sui::MockSuiClient, RecentErrors};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_synthetic_throughput() -> Result<()> {
+        const CHECKPOINTS: u64 = 200;
+        const EVENTS: usize = 50;
+
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        let (_config, config_rx) = watch::channel(config);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, EVENTS)?),
+            storage.clone(),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        );
+
+        let shutdown = CancellationToken::new();
+        let started = Instant::now();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(60),
+                "pipeline stalled"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let elapsed = started.elapsed();
+        shutdown.cancel();
+        run.await??;
+
+        let events = storage
+            .get_events_by_checkpoint_range(0, CHECKPOINTS - 1)
+            .await?;
+        assert_eq!(events.len(), CHECKPOINTS as usize * EVENTS);
+
+        // Wall-clock rates depend on the machine and its load, so the floor
+        // is only checked where one is set for a known runner
+        let min_rate = std::env::var("SUI_INDEXER_MIN_EVENTS_PER_SEC")
+            .ok()
+            .and_then(|rate| rate.parse::<f64>().ok());
+        if let Some(min_rate) = min_rate {
+            let rate = events.len() as f64 / elapsed.as_secs_f64();
+            assert!(
+                rate >= min_rate,
+                "throughput regressed to {rate:.0} events/s over {elapsed:?}, below {min_rate:.0}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restart_commits_held_checkpoints_before_refetching() -> Result<()> {
+        const CHECKPOINTS: u64 = 50;
+        const EVENTS: usize = 4;
+
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        let (_config, config_rx) = watch::channel(config);
+        let restarts = Arc::new(Notify::new());
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, EVENTS)?),
+            storage.clone(),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        )
+        .with_restarts(restarts.clone());
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        while storage.get_latest_checkpoint().await?.is_none() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        restarts.notify_one();
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        let events = storage
+            .get_events_by_checkpoint_range(0, CHECKPOINTS - 1)
+            .await?;
+        assert_eq!(events.len(), CHECKPOINTS as usize * EVENTS);
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_flight_is_settled_per_pipeline() -> Result<()> {
+        let metrics = Arc::new(Metrics::new()?);
+        let pipeline = || {
+            Pipeline::new(
+                SuiClient::mock(MockSuiClient::synthetic(1, 1).unwrap()),
+                StorageManager::from_backend(MemoryStorage::new()),
+                Arc::new(DefaultEventProcessor::new()),
+                watch::channel(IndexerConfig::default()).1,
+                metrics.clone(),
+            )
+        };
+        let (first, second) = (pipeline(), pipeline());
+        let counts = |filters: &[(&str, u64)]| {
+            filters
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let in_flight = |name| metrics.filter_in_flight.with_label_values(&[name]).get();
+
+        let filtered = |filters: &[(&str, u64)]| Filtered {
+            seen: filters.iter().map(|(_, count)| count).sum(),
+            matched: filters.iter().map(|(_, count)| count).sum(),
+            by_filter: counts(filters),
+        };
+
+        // Two checkpoints of the first pipeline are matched before either commits
+        first.account_matched(2, 2, &counts(&[("pool", 2)]));
+        first.account_matched(1, 1, &counts(&[("pool", 1)]));
+        second.account_matched(4, 4, &counts(&[("pool", 3), ("swap", 1)]));
+        first.account_stored(2, &counts(&[("pool", 2)]), &filtered(&[("pool", 2)]));
+        assert_eq!(in_flight("pool"), 4);
+        assert_eq!(in_flight("swap"), 1);
+        assert!(!ReconciliationReport::from_metrics(&metrics).has_unexplained());
+        first.account_stored(1, &counts(&[("pool", 1)]), &filtered(&[("pool", 1)]));
+        assert_eq!(in_flight("pool"), 3);
+
+        second.abandon_in_flight();
+        assert_eq!((in_flight("pool"), in_flight("swap")), (0, 0));
+        assert_eq!(
+            metrics.filter_abandoned.with_label_values(&["pool"]).get(),
+            3
+        );
+        Ok(())
+    }
+
+    /// Processor holding back earlier checkpoints of every window of `WINDOW`, so later ones finish first
+    ///
+    /// The checkpoint of every processed event is recorded in the order processing finished.
+    struct Staggered(DefaultEventProcessor, Arc<Mutex<Vec<u64>>>);
+
+    impl Staggered {
+        const WINDOW: u64 = 4;
+    }
+
+    #[async_trait::async_trait]
+    impl EventProcessor for Staggered {
+        async fn process_event(&self, event: SuiEvent) -> Result<ProcessedEvent> {
+            // Synthetic events carry their checkpoint in `amount_in`
+            let checkpoint = event.parsed_json["amount_in"]
+                .as_str()
+                .and_then(|amount| amount.parse::<u64>().ok())
+                .unwrap_or_default()
+                / 1000;
+            let delay = Staggered::WINDOW - 1 - checkpoint % Staggered::WINDOW;
+            tokio::time::sleep(Duration::from_millis(delay * 5)).await;
+            let processed = self.0.process_event(event).await;
+            self.1.lock().unwrap().push(checkpoint);
+            processed
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_reconciliation_holds_while_checkpoints_are_processed_concurrently() -> Result<()>
+    {
+        const CHECKPOINTS: u64 = 24;
+        const EVENTS: usize = 2;
+
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        config.runtime.process_concurrency = Staggered::WINDOW as usize;
+        let (_config, config_rx) = watch::channel(config);
+        let metrics = Arc::new(Metrics::new()?);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, EVENTS)?),
+            storage.clone(),
+            Arc::new(Staggered(DefaultEventProcessor::new(), Arc::default())),
+            config_rx,
+            metrics.clone(),
+        );
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        let mut held = 0;
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            let report = ReconciliationReport::from_metrics(&metrics);
+            assert!(!report.has_unexplained(), "{report:?}");
+            held = held.max(report.pipelines[0].in_flight);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        // Later checkpoints were matched while an earlier one was still being processed
+        assert!(held > EVENTS as u64, "at most {held} events were in flight");
+        let report = ReconciliationReport::from_metrics(&metrics);
+        assert!(!report.has_unexplained(), "{report:?}");
+        assert_eq!(report.pipelines[0].in_flight, 0);
+        assert_eq!(report.pipelines[0].stored, CHECKPOINTS * EVENTS as u64);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_commits_follow_checkpoint_order_when_transforms_finish_out_of_order() -> Result<()>
+    {
+        const CHECKPOINTS: u64 = 16;
+
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        config.runtime.process_concurrency = Staggered::WINDOW as usize;
+        let (_config, config_rx) = watch::channel(config);
+        let live = LiveEvents::default();
+        let mut committed = live.subscribe();
+        let finished = Arc::<Mutex<Vec<u64>>>::default();
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, 1)?),
+            storage.clone(),
+            Arc::new(Staggered(DefaultEventProcessor::new(), finished.clone())),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        )
+        .with_live(live);
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        let mut cursor = None;
+        while cursor != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            let latest = storage.get_latest_checkpoint().await?;
+            assert!(
+                latest >= cursor,
+                "cursor moved back from {cursor:?} to {latest:?}"
+            );
+            cursor = latest;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        let in_order: Vec<u64> = (0..CHECKPOINTS).collect();
+        let finished = finished.lock().unwrap().clone();
+        assert_eq!(finished.len(), CHECKPOINTS as usize);
+        assert_ne!(
+            finished, in_order,
+            "transforms finished in checkpoint order"
+        );
+        // Published right after each commit
+        let mut commits = Vec::new();
+        while let Ok(batch) = committed.try_recv() {
+            commits.push(batch.checkpoint);
+        }
+        assert_eq!(commits, in_order);
+        Ok(())
+    }
+
+    /// Takes `slow` to process the second event of checkpoint 2 on `clock`, 10ms for the others
+    #[derive(Clone)]
+    struct Sluggish {
+        clock: ManualClock,
+        slow: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl EventProcessor for Sluggish {
+        async fn process_event(&self, event: SuiEvent) -> Result<ProcessedEvent> {
+            // Synthetic events carry their checkpoint and index in `amount_in`
+            let amount = event.parsed_json["amount_in"]
+                .as_str()
+                .and_then(|amount| amount.parse::<u64>().ok())
+                .unwrap_or_default();
+            self.clock.advance(if amount == 2001 {
+                self.slow
+            } else {
+                Duration::from_millis(10)
+            });
+            DefaultEventProcessor::new().process_event(event).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_checkpoints_and_event_calls_are_logged_with_their_timings() -> Result<()> {
+        const CHECKPOINTS: u64 = 4;
+
+        let warnings: RecentErrors = RecentErrors::default();
+        let _logging =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(warnings.layer()));
+        let clock = ManualClock::new(Utc::now());
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        config.runtime.process_concurrency = 1;
+        config.observability.slow_checkpoint_ms = Some(100);
+        config.observability.slow_event_ms = Some(40);
+        let (_config, config_rx) = watch::channel(config);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, 2)?),
+            storage.clone(),
+            Arc::new(Sluggish {
+                clock: clock.clone(),
+                slow: Duration::from_millis(150),
+            }),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        )
+        .with_clock(Arc::new(clock));
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        let logged = |message: &str| -> Vec<_> {
+            warnings
+                .snapshot()
+                .into_iter()
+                .filter(|warning| warning.message == message)
+                .map(|warning| warning.fields)
+                .collect()
+        };
+        let calls = logged("Slow event processor call");
+        assert_eq!(calls.len(), 1, "{calls:?}");
+        assert_eq!(calls[0]["elapsed_ms"], 150);
+        assert_eq!(calls[0]["threshold_ms"], 40);
+        assert!(calls[0]["event_type"]
+            .as_str()
+            .is_some_and(|event_type| event_type.ends_with("::pool::SwapEvent")));
+
+        // Only checkpoint 2 took longer than the threshold, all of it transforming
+        let checkpoints = logged("Slow checkpoint");
+        assert_eq!(checkpoints.len(), 1, "{checkpoints:?}");
+        assert_eq!(checkpoints[0]["checkpoint"], 2);
+        assert_eq!(checkpoints[0]["events"], 2);
+        assert_eq!(checkpoints[0]["threshold_ms"], 100);
+        assert_eq!(checkpoints[0]["transform_ms"], 160);
+        assert_eq!(checkpoints[0]["store_ms"], 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_handoff_is_recorded_where_the_filter_starts() -> Result<()> {
+        let filter = |name: &str| EventFilter {
+            name: Some(name.to_string()),
+            package: None,
+            module: Some(name.to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        };
+        let client = MockSuiClient::synthetic(2, 4)?;
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        config.events.filters = vec![filter("pool")];
+        let (config_tx, config_rx) = watch::channel(config.clone());
+        let pipeline = Pipeline::new(
+            SuiClient::mock(client.clone()),
+            storage.clone(),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        );
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        let wait_for = |checkpoint: u64| {
+            let storage = storage.clone();
+            async move {
+                while storage.get_latest_checkpoint().await? != Some(checkpoint) {
+                    assert!(
+                        started.elapsed() < Duration::from_secs(30),
+                        "pipeline stalled"
+                    );
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                Ok::<_, eyre::Report>(())
+            }
+        };
+        wait_for(1).await?;
+        config.events.filters.push(filter("coin"));
+        config_tx.send(config)?;
+        for sequence_number in 2..4 {
+            client.push_checkpoint(crate::sui::mock::synthetic_checkpoint(sequence_number, 4)?);
+        }
+        wait_for(3).await?;
+        shutdown.cancel();
+        run.await??;
+
+        assert_eq!(
+            storage.get_cursor(&filter_handoff_cursor("pool")).await?,
+            Some(0)
+        );
+        assert_eq!(
+            storage.get_cursor(&filter_handoff_cursor("coin")).await?,
+            Some(2)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_follows_the_runtime_it_runs_with() -> Result<()> {
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        let (config_tx, config_rx) = watch::channel(config);
+        let metrics = Arc::new(Metrics::new()?);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(1, 1)?),
+            StorageManager::from_backend(MemoryStorage::new()),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            metrics.clone(),
+        );
+        config_tx.send_modify(|config| {
+            config.runtime.writer_concurrency = 7;
+            config.runtime.autotune.enabled = true;
+        });
+
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        pipeline.run(0, shutdown).await?;
+        assert_eq!(
+            metrics
+                .pipeline_writer_concurrency
+                .with_label_values(&[DEFAULT_PIPELINE])
+                .get(),
+            7
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_matched_transactions_are_fetched() -> Result<()> {
+        const CHECKPOINTS: u64 = 4;
+
+        let client = MockSuiClient::synthetic(CHECKPOINTS, 16)?;
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        // One sender of 16 per checkpoint, so one transaction each
+        config.events.filters = vec![EventFilter {
+            name: None,
+            package: None,
+            module: None,
+            event_type: None,
+            sender: Some(format!("{:#066x}", 3)),
+            priority: FilterPriority::Normal,
+        }];
+        let (_config, config_rx) = watch::channel(config);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(client.clone()),
+            storage.clone(),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            Arc::new(Metrics::new()?),
+        );
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        assert_eq!(client.transactions_served(), CHECKPOINTS as usize);
+        let transactions = storage
+            .query_transactions(&TransactionQuery::default())
+            .await?;
+        assert_eq!(transactions.len(), CHECKPOINTS as usize);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_priority_events_are_delivered_ahead_of_their_checkpoint() -> Result<()> {
+        const CHECKPOINTS: u64 = 3;
+        const EVENTS: usize = 16;
+
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 10;
+        let alerted = format!("{:#066x}", 3);
+        config.events.filters = vec![
+            EventFilter {
+                name: Some("swaps".to_string()),
+                package: None,
+                module: Some("pool".to_string()),
+                event_type: None,
+                sender: None,
+                priority: FilterPriority::Normal,
+            },
+            EventFilter {
+                name: Some("alerts".to_string()),
+                package: None,
+                module: None,
+                event_type: None,
+                sender: Some(alerted.clone()),
+                priority: FilterPriority::High,
+            },
+        ];
+        let (_config, config_rx) = watch::channel(config);
+        let (tx, mut rx) = mpsc::channel(1024);
+        let mut sinks = SinkManager::default();
+        sinks.add(
+            "stream".to_string(),
+            Box::new(ChannelSink::new(tx)),
+            EventFilterProcessor::default(),
+        );
+        let metrics = Arc::new(Metrics::new()?);
+        let pipeline = Pipeline::new(
+            SuiClient::mock(MockSuiClient::synthetic(CHECKPOINTS, EVENTS)?),
+            storage.clone(),
+            Arc::new(DefaultEventProcessor::new()),
+            config_rx,
+            metrics.clone(),
+        )
+        .with_sinks(sinks);
+
+        let shutdown = CancellationToken::new();
+        let run = tokio::spawn(pipeline.run(0, shutdown.clone()));
+        let started = Instant::now();
+        while storage.get_latest_checkpoint().await? != Some(CHECKPOINTS - 1) {
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "pipeline stalled"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.cancel();
+        run.await??;
+
+        let mut delivered = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            delivered.push(event);
+        }
+        assert_eq!(delivered.len(), CHECKPOINTS as usize * EVENTS);
+        for checkpoint in 0..CHECKPOINTS {
+            let first = delivered
+                .iter()
+                .find(|event| event.checkpoint_sequence == checkpoint)
+                .expect("checkpoint events are delivered");
+            assert_eq!(first.sender, alerted);
+        }
+        assert_eq!(
+            metrics
+                .priority_events
+                .with_label_values(&["expedited"])
+                .get(),
+            CHECKPOINTS
+        );
+        // Stored once, although the checkpoint commit stores them again
+        let events = storage
+            .get_events_by_checkpoint_range(0, CHECKPOINTS - 1)
+            .await?;
+        assert_eq!(events.len(), CHECKPOINTS as usize * EVENTS);
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::metrics::Metrics;
+
+/// Whether the indexer has finished its cold start
+///
+/// The indexer is ready once storage is migrated, the node answered and the
+/// first checkpoint was fetched. Readiness is reported on the health endpoint
+/// and, if configured, by the presence of a readiness file. The watchdog
+/// marks a started indexer stalled, which the readiness probe also reports.
+/// Like the probe, the file is only kept while the indexer is not stalled
+/// and keeps up with the network tip as the pipeline last saw it.
+#[derive(Clone, Default)]
+pub struct Readiness {
+    ready: Arc<AtomicBool>,
+    stalled: Arc<AtomicBool>,
+    lagging: Arc<AtomicBool>,
+    /// Whether the readiness file was last written rather than removed
+    written: Arc<AtomicBool>,
+    file: Option<Arc<PathBuf>>,
+}
+
+impl Readiness {
+    /// Readiness that also creates `path` when ready and removes it when not
+    pub fn with_file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file: Some(Arc::new(path.into())),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the indexer is ready
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Relaxed)
+    }
+
+    /// The readiness file, if one is configured
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref().map(PathBuf::as_path)
+    }
+
+    /// Mark the indexer ready, writing the readiness file the first time
+    pub fn set_ready(&self) {
+        if self.ready.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        info!("Indexer is ready");
+        self.sync_file();
+    }
+
+    /// Whether the watchdog found ingestion stalled
+    pub fn is_stalled(&self) -> bool {
+        self.stalled.load(Ordering::Relaxed)
+    }
+
+    /// Record whether ingestion is stalled
+    pub fn set_stalled(&self, stalled: bool) {
+        self.stalled.store(stalled, Ordering::Relaxed);
+        self.sync_file();
+    }
+
+    /// Whether processing trailed the network tip by more than the allowed lag when last observed
+    pub fn is_lagging(&self) -> bool {
+        self.lagging.load(Ordering::Relaxed)
+    }
+
+    /// Compare the processed checkpoint with the network tip, both as currently recorded in `metrics`
+    pub(crate) fn observe_lag(&self, metrics: &Metrics, max_lag: u64) {
+        let processed = metrics.latest_processed_checkpoint.get();
+        let network = metrics.latest_network_checkpoint.get();
+        // Unknown until a checkpoint was processed
+        if processed <= 0 || network <= 0 {
+            return;
+        }
+        let lag = network.saturating_sub(processed).max(0) as u64;
+        self.lagging.store(lag > max_lag, Ordering::Relaxed);
+        self.sync_file();
+    }
+
+    /// Create or remove the readiness file to match the current state
+    fn sync_file(&self) {
+        let Some(path) = self.file() else {
+            return;
+        };
+        let ready = self.is_ready() && !self.is_stalled() && !self.is_lagging();
+        if self.written.swap(ready, Ordering::Relaxed) == ready {
+            return;
+        }
+        if ready {
+            if let Err(e) = std::fs::write(path, format!("{}\n", Utc::now().to_rfc3339())) {
+                warn!(path = %path.display(), error = %e, "Failed to write readiness file");
+            }
+        } else {
+            remove_file(path);
+        }
+    }
+
+    /// Mark the indexer not ready, removing any readiness file
+    pub fn set_not_ready(&self) {
+        self.ready.store(false, Ordering::Relaxed);
+        self.written.store(false, Ordering::Relaxed);
+        // Also one left behind by an earlier run
+        if let Some(path) = self.file() {
+            remove_file(path);
+        }
+    }
+}
+
+fn remove_file(path: &Path) {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            warn!(path = %path.display(), error = %e, "Failed to remove readiness file");
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_file() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("ready");
+        std::fs::write(&path, "stale")?;
+
+        let readiness = Readiness::with_file(&path);
+        readiness.set_not_ready();
+        assert!(!readiness.is_ready());
+        assert!(!path.exists());
+
+        readiness.clone().set_ready();
+        assert!(readiness.is_ready());
+        assert!(path.exists());
+
+        // The file follows the current lag and stalls, not only the cold start
+        let metrics = Metrics::new()?;
+        metrics.latest_network_checkpoint.set(100);
+        metrics.latest_processed_checkpoint.set(90);
+        readiness.observe_lag(&metrics, 5);
+        assert!(readiness.is_lagging() && !path.exists());
+        metrics.latest_processed_checkpoint.set(98);
+        readiness.observe_lag(&metrics, 5);
+        assert!(path.exists());
+        readiness.set_stalled(true);
+        assert!(!path.exists());
+        readiness.set_stalled(false);
+        assert!(path.exists());
+
+        readiness.set_not_ready();
+        assert!(!path.exists());
+        Ok(())
+    }
+}
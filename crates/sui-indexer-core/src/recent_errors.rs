@@ -0,0 +1,150 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{layer::Context, Layer};
+
+use crate::json_log::Fields;
+
+/// Entries kept unless a capacity is given
+const CAPACITY: usize = 100;
+
+/// A warning or error logged by the indexer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentError {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Other fields of the log line, such as `checkpoint` or `error`
+    pub fields: Map<String, Value>,
+}
+
+/// The most recent warnings and errors, newest last
+///
+/// Logged warnings and errors are fed by [`RecentErrors::layer`] and
+/// reported on the control endpoint; failed pipeline stages are kept the
+/// same way in an [`ErrorHistory`](crate::ErrorHistory). Clones share the
+/// same buffer.
+#[derive(Clone)]
+pub struct RecentErrors<T = RecentError> {
+    errors: Arc<Mutex<VecDeque<T>>>,
+    capacity: Arc<AtomicUsize>,
+}
+
+impl<T: Clone> RecentErrors<T> {
+    /// Keep at most `capacity` entries, none if it is 0
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            errors: Arc::default(),
+            capacity: Arc::new(AtomicUsize::new(capacity)),
+        }
+    }
+
+    /// Keep at most `capacity` entries from now on, dropping the oldest beyond that
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut errors = self.errors();
+        while errors.len() > capacity {
+            errors.pop_front();
+        }
+    }
+
+    /// Recorded entries, oldest first
+    pub fn snapshot(&self) -> Vec<T> {
+        self.errors().iter().cloned().collect()
+    }
+
+    pub(crate) fn push(&self, error: T) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        if capacity == 0 {
+            return;
+        }
+        let mut errors = self.errors();
+        while errors.len() >= capacity {
+            errors.pop_front();
+        }
+        errors.push_back(error);
+    }
+
+    fn errors(&self) -> MutexGuard<'_, VecDeque<T>> {
+        self.errors.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Clone> Default for RecentErrors<T> {
+    fn default() -> Self {
+        Self::with_capacity(CAPACITY)
+    }
+}
+
+impl RecentErrors {
+    /// Tracing layer recording `WARN` and `ERROR` events into this buffer
+    pub fn layer(&self) -> RecentErrorsLayer {
+        RecentErrorsLayer(self.clone())
+    }
+}
+
+/// Layer created by [`RecentErrors::layer`]
+pub struct RecentErrorsLayer(RecentErrors);
+
+impl<S: Subscriber> Layer<S> for RecentErrorsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+
+        let mut fields = Map::new();
+        event.record(&mut Fields(&mut fields));
+        let message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        self.0.push(RecentError {
+            timestamp: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message,
+            fields,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn test_keeps_latest_warnings_and_errors() {
+        let errors: RecentErrors = RecentErrors::default();
+        let subscriber = tracing_subscriber::registry().with(errors.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Checkpoint committed");
+            for checkpoint in 0..CAPACITY as u64 + 5 {
+                tracing::warn!(checkpoint, "Failed to fetch checkpoint");
+            }
+            tracing::error!(error = "pool timed out", "Failed to store checkpoint");
+        });
+
+        let recorded = errors.snapshot();
+        assert_eq!(recorded.len(), CAPACITY);
+        assert_eq!(recorded[0].fields["checkpoint"], 6);
+        let last = recorded.last().unwrap();
+        assert_eq!(last.level, "ERROR");
+        assert_eq!(last.message, "Failed to store checkpoint");
+        assert_eq!(last.fields["error"], "pool timed out");
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::PoisonError,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{gauge_values, label_counts, Metrics};
+
+/// Event counts of one pipeline or filter since startup
+///
+/// Matched events are stored, abandoned with a checkpoint that was not
+/// committed (and fetched again), or still in flight. Anything else is
+/// reported as `unexplained`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reconciliation {
+    /// Pipeline or filter name
+    pub name: String,
+    /// Events in processed checkpoints, only counted per pipeline
+    pub seen: Option<u64>,
+    /// Events that matched no filter, only counted per pipeline
+    pub filtered: Option<u64>,
+    pub matched: u64,
+    pub stored: u64,
+    /// Matched events of checkpoints given up before their commit
+    pub abandoned: u64,
+    /// Matched events of checkpoints transformed but not yet committed
+    pub in_flight: u64,
+    /// Events neither stored, abandoned nor in flight (negative if stored more than once)
+    pub unexplained: i64,
+}
+
+/// Seen, filtered and stored events per pipeline and filter, with any delta they leave
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub pipelines: Vec<Reconciliation>,
+    pub filters: Vec<Reconciliation>,
+}
+
+impl ReconciliationReport {
+    /// Reconcile the event counters of `metrics`
+    pub fn from_metrics(metrics: &Metrics) -> Self {
+        let _accounting = metrics
+            .accounting
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let seen = label_counts(&metrics.pipeline_seen);
+        let filtered = label_counts(&metrics.pipeline_filtered);
+        let mut pipelines = reconcile(
+            label_counts(&metrics.pipeline_matches),
+            label_counts(&metrics.pipeline_stored),
+            label_counts(&metrics.pipeline_abandoned),
+            gauge_values(&metrics.pipeline_in_flight),
+        );
+        for name in seen.keys().chain(filtered.keys()) {
+            if !pipelines.iter().any(|pipeline| &pipeline.name == name) {
+                pipelines.push(Reconciliation {
+                    name: name.clone(),
+                    ..Reconciliation::default()
+                });
+            }
+        }
+        for pipeline in &mut pipelines {
+            let seen = seen.get(&pipeline.name).copied().unwrap_or_default();
+            let filtered = filtered.get(&pipeline.name).copied().unwrap_or_default();
+            // Every seen event is either filtered out or matched
+            pipeline.unexplained += seen as i64 - filtered as i64 - pipeline.matched as i64;
+            pipeline.seen = Some(seen);
+            pipeline.filtered = Some(filtered);
+        }
+
+        Self {
+            pipelines,
+            filters: reconcile(
+                label_counts(&metrics.filter_matches),
+                label_counts(&metrics.filter_stored),
+                label_counts(&metrics.filter_abandoned),
+                gauge_values(&metrics.filter_in_flight),
+            ),
+        }
+    }
+
+    /// Whether any pipeline or filter lost or duplicated events
+    pub fn has_unexplained(&self) -> bool {
+        self.pipelines
+            .iter()
+            .chain(&self.filters)
+            .any(|reconciliation| reconciliation.unexplained != 0)
+    }
+}
+
+/// Account for the matched events of every label
+fn reconcile(
+    matched: BTreeMap<String, u64>,
+    stored: BTreeMap<String, u64>,
+    abandoned: BTreeMap<String, u64>,
+    in_flight: BTreeMap<String, u64>,
+) -> Vec<Reconciliation> {
+    let names: BTreeSet<_> = [&matched, &stored, &abandoned, &in_flight]
+        .into_iter()
+        .flat_map(BTreeMap::keys)
+        .cloned()
+        .collect();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let count =
+                |counts: &BTreeMap<String, u64>| counts.get(&name).copied().unwrap_or_default();
+            let (matched, stored, abandoned, in_flight) = (
+                count(&matched),
+                count(&stored),
+                count(&abandoned),
+                count(&in_flight),
+            );
+            Reconciliation {
+                unexplained: matched as i64 - stored as i64 - abandoned as i64 - in_flight as i64,
+                name,
+                seen: None,
+                filtered: None,
+                matched,
+                stored,
+                abandoned,
+                in_flight,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconciliation() -> eyre::Result<()> {
+        let metrics = Metrics::new()?;
+        metrics
+            .pipeline_seen
+            .with_label_values(&["default"])
+            .inc_by(100);
+        metrics
+            .pipeline_filtered
+            .with_label_values(&["default"])
+            .inc_by(88);
+        metrics
+            .pipeline_matches
+            .with_label_values(&["default"])
+            .inc_by(12);
+        metrics
+            .pipeline_stored
+            .with_label_values(&["default"])
+            .inc_by(8);
+        metrics
+            .pipeline_abandoned
+            .with_label_values(&["default"])
+            .inc_by(2);
+        metrics
+            .pipeline_in_flight
+            .with_label_values(&["default"])
+            .set(2);
+        metrics
+            .filter_matches
+            .with_label_values(&["swaps"])
+            .inc_by(12);
+        metrics
+            .filter_stored
+            .with_label_values(&["swaps"])
+            .inc_by(8);
+        metrics
+            .filter_abandoned
+            .with_label_values(&["swaps"])
+            .inc_by(2);
+        metrics
+            .filter_in_flight
+            .with_label_values(&["swaps"])
+            .set(2);
+
+        let report = ReconciliationReport::from_metrics(&metrics);
+        assert!(!report.has_unexplained());
+        assert_eq!(report.pipelines[0].seen, Some(100));
+
+        // The processor returned fewer events than it was given
+        metrics
+            .filter_matches
+            .with_label_values(&["swaps"])
+            .inc_by(3);
+        metrics
+            .pipeline_seen
+            .with_label_values(&["default"])
+            .inc_by(3);
+        metrics
+            .pipeline_matches
+            .with_label_values(&["default"])
+            .inc_by(3);
+        let report = ReconciliationReport::from_metrics(&metrics);
+        assert!(report.has_unexplained());
+        assert_eq!(report.pipelines[0].unexplained, 3);
+        assert_eq!(report.filters[0].name, "swaps");
+        assert_eq!(report.filters[0].unexplained, 3);
+        Ok(())
+    }
+}
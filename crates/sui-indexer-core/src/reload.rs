@@ -0,0 +1,182 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use eyre::Result;
+use sui_indexer_config::{ConfigLoader, IndexerConfig, ReloadReport};
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+
+/// Default interval between configuration file modification checks
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Re-reads the configuration file on SIGHUP or file change and applies
+/// the reloadable sections to the running indexer
+pub struct ConfigReloader {
+    path: PathBuf,
+    config: Arc<watch::Sender<IndexerConfig>>,
+    poll_interval: Duration,
+}
+
+impl ConfigReloader {
+    /// Create a reloader for the given file and shared configuration
+    pub fn new(path: impl Into<PathBuf>, config: Arc<watch::Sender<IndexerConfig>>) -> Self {
+        Self {
+            path: path.into(),
+            config,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Set how often the file modification time is checked
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Re-read the configuration file and apply reloadable changes
+    pub fn reload(&self) -> Result<ReloadReport> {
+        let updated = ConfigLoader::from_file(&self.path)?;
+
+        let mut result = Ok(ReloadReport::default());
+        self.config.send_if_modified(|current| {
+            result = current.apply_reload(&updated);
+            result
+                .as_ref()
+                .is_ok_and(|report| !report.applied.is_empty())
+        });
+        result
+    }
+
+    /// Re-read the configuration file and apply only its event filters
+    pub fn reload_filters(&self) -> Result<ReloadReport> {
+        let updated = ConfigLoader::from_file(&self.path)?;
+
+        let mut result = Ok(ReloadReport::default());
+        self.config.send_if_modified(|current| {
+            let changed = match current.changed_fields(&updated) {
+                Ok(changed) => changed
+                    .iter()
+                    .any(|field| field.starts_with("events.filters")),
+                Err(e) => {
+                    result = Err(e);
+                    return false;
+                }
+            };
+            if changed {
+                current.events.filters = updated.events.filters.clone();
+                result = Ok(ReloadReport {
+                    applied: vec!["events.filters".to_string()],
+                    requires_restart: vec![],
+                });
+            }
+            changed
+        });
+        result
+    }
+
+    /// Spawn the reload loop as a background task
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(self) {
+        let mut hangup = HangupSignal::new();
+        let mut poll = tokio::time::interval(self.poll_interval);
+        let mut last_modified = self.modified_time();
+
+        info!(path = %self.path.display(), "Watching configuration file for changes");
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => {
+                    info!("Received SIGHUP, reloading configuration");
+                }
+                _ = poll.tick() => {
+                    let modified = self.modified_time();
+                    if modified == last_modified {
+                        continue;
+                    }
+                    last_modified = modified;
+                    info!("Configuration file changed, reloading");
+                }
+            }
+
+            match self.reload() {
+                Ok(report) => log_report(&report),
+                Err(e) => error!(
+                    path = %self.path.display(),
+                    error = %e,
+                    "Failed to reload configuration, keeping current settings"
+                ),
+            }
+        }
+    }
+
+    /// Latest modification time across the file and the base files it extends
+    fn modified_time(&self) -> Option<SystemTime> {
+        let sources =
+            ConfigLoader::source_files(&self.path).unwrap_or_else(|_| vec![self.path.clone()]);
+        sources.iter().filter_map(|path| modified_time(path)).max()
+    }
+}
+
+/// Log what a reload applied and what needs a restart
+pub(crate) fn log_report(report: &ReloadReport) {
+    if report.is_empty() {
+        info!("Configuration unchanged");
+        return;
+    }
+    if !report.applied.is_empty() {
+        info!(fields = ?report.applied, "Applied configuration changes");
+    }
+    if !report.requires_restart.is_empty() {
+        warn!(
+            fields = ?report.requires_restart,
+            "Configuration changes require a restart to take effect"
+        );
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// SIGHUP listener that never fires on platforms without Unix signals
+struct HangupSignal {
+    #[cfg(unix)]
+    inner: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let inner = signal(SignalKind::hangup())
+                .map_err(|e| warn!(error = %e, "Failed to install SIGHUP handler"))
+                .ok();
+            Self { inner }
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self {}
+        }
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.inner.as_mut() {
+            if signal.recv().await.is_some() {
+                return;
+            }
+            self.inner = None;
+        }
+
+        std::future::pending::<()>().await
+    }
+}
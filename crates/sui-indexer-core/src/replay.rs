@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::TryStreamExt;
+use serde::Serialize;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_events::{DefaultEventProcessor, EventProcessor, ProtocolRegistry};
+use sui_indexer_storage::StorageManager;
+use sui_json_rpc_types::SuiEvent;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::sui::CheckpointRange;
+
+/// Outcome of a replay run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Stored events read from the range
+    pub events_read: u64,
+    /// Processed events written to the output table
+    pub events_written: u64,
+    /// Events skipped because they were stored without raw event data
+    pub events_without_raw: u64,
+}
+
+/// Name of the processor that decodes every configured protocol, as `start` does
+pub const DEFAULT_PROCESSOR: &str = "default";
+
+/// Look up a built-in processor by name: [`DEFAULT_PROCESSOR`] or a single protocol decoder
+pub fn named_processor(config: &IndexerConfig, name: &str) -> Result<Arc<dyn EventProcessor>> {
+    let protocols = if name == DEFAULT_PROCESSOR {
+        ProtocolRegistry::from_config(&config.protocols)?
+    } else {
+        ProtocolRegistry::only(&config.protocols, name)?
+    };
+    Ok(Arc::new(DefaultEventProcessor::with_protocols(Arc::new(
+        protocols,
+    ))))
+}
+
+/// Re-runs an event processor over stored events instead of the chain
+///
+/// Events are rebuilt from the raw event JSON kept by `storage.store_raw_event`
+/// and written, with their original checkpoint and timestamp, to a separate
+/// output table whose rows for the range are replaced on every run. The live
+/// events table and checkpoint cursor are never touched.
+pub struct ReplayEngine {
+    storage: StorageManager,
+    processor: Arc<dyn EventProcessor>,
+    range: CheckpointRange,
+    output_table: String,
+    batch_size: usize,
+}
+
+impl ReplayEngine {
+    /// Create a replay of `range` through `processor` into `output_table`
+    pub fn new(
+        storage: StorageManager,
+        processor: Arc<dyn EventProcessor>,
+        range: CheckpointRange,
+        output_table: impl Into<String>,
+    ) -> Self {
+        Self {
+            storage,
+            processor,
+            range,
+            output_table: output_table.into(),
+            batch_size: 100,
+        }
+    }
+
+    /// Set how many events are processed and stored per write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Replay the whole range, returning early if `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) -> Result<ReplayReport> {
+        info!(
+            from = self.range.start,
+            to = self.range.end,
+            table = %self.output_table,
+            "Starting replay"
+        );
+        self.storage
+            .reset_event_table(&self.output_table, self.range.start, self.range.end)
+            .await?;
+
+        let mut report = ReplayReport::default();
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut stored = self
+            .storage
+            .export_raw_events(self.range.start, self.range.end);
+
+        while let Some(record) = stored.try_next().await? {
+            report.events_read += 1;
+            let Some(event_data) = record.event_data else {
+                report.events_without_raw += 1;
+                continue;
+            };
+            let checkpoint = record.checkpoint_sequence as u64;
+            let event: SuiEvent = serde_json::from_value(event_data).wrap_err_with(|| {
+                format!("Failed to decode stored event of checkpoint {checkpoint}")
+            })?;
+            batch.push((event, checkpoint, record.timestamp));
+
+            if batch.len() >= self.batch_size {
+                report.events_written += self.flush(&mut batch).await?;
+                if shutdown.is_cancelled() {
+                    warn!(events = report.events_read, "Replay interrupted");
+                    return Ok(report);
+                }
+            }
+        }
+        report.events_written += self.flush(&mut batch).await?;
+
+        if report.events_without_raw > 0 {
+            warn!(
+                skipped = report.events_without_raw,
+                "Skipped events stored without raw event data; enable storage.store_raw_event to replay them"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Process and store a batch, keeping each event's original checkpoint and timestamp
+    async fn flush(&self, batch: &mut Vec<(SuiEvent, u64, DateTime<Utc>)>) -> Result<u64> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let (events, origins): (Vec<_>, Vec<_>) = batch
+            .drain(..)
+            .map(|(event, checkpoint, timestamp)| (event, (checkpoint, timestamp)))
+            .unzip();
+        let mut processed = self.processor.process_events(events).await?;
+        for (event, (checkpoint, timestamp)) in processed.iter_mut().zip(origins) {
+            event.checkpoint_sequence = checkpoint;
+            event.timestamp = timestamp;
+        }
+
+        let count = processed.len() as u64;
+        self.storage
+            .store_events_in(&self.output_table, &processed)
+            .await?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sui_indexer_storage::{ConnectionStats, StorageManager};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{doctor::format_bytes, metrics::Metrics};
+
+/// Interval between resource samples recorded as metrics
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resources held by the indexer process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Resident set size in bytes, if the platform reports it
+    pub rss_bytes: Option<u64>,
+    /// Database connections, if the storage backend pools them
+    pub db_connections: Option<ConnectionStats>,
+    /// Tasks alive on the tokio runtime
+    pub tokio_tasks: Option<usize>,
+    /// Worker threads of the tokio runtime
+    #[serde(default)]
+    pub tokio_workers: Option<usize>,
+    /// Tasks waiting in the runtime's global queue
+    #[serde(default)]
+    pub tokio_queue_depth: Option<usize>,
+    /// Time all workers spent polling tasks since the runtime started, in milliseconds
+    #[serde(default)]
+    pub tokio_busy_ms: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// Sample the current process and the pool of `storage`, if given
+    pub fn sample(storage: Option<&StorageManager>) -> Self {
+        let runtime = tokio::runtime::Handle::try_current()
+            .ok()
+            .map(|runtime| runtime.metrics());
+        Self {
+            rss_bytes: resident_memory(),
+            db_connections: storage.and_then(StorageManager::connection_stats),
+            tokio_tasks: runtime.as_ref().map(|metrics| metrics.num_alive_tasks()),
+            tokio_workers: runtime.as_ref().map(|metrics| metrics.num_workers()),
+            tokio_queue_depth: runtime.as_ref().map(|metrics| metrics.global_queue_depth()),
+            tokio_busy_ms: runtime.as_ref().map(|metrics| {
+                let busy: Duration = (0..metrics.num_workers())
+                    .map(|worker| metrics.worker_total_busy_duration(worker))
+                    .sum();
+                busy.as_millis() as u64
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rss_bytes {
+            Some(rss) => write!(f, "{} RSS", format_bytes(rss))?,
+            None => f.write_str("RSS unavailable")?,
+        }
+        if let Some(connections) = self.db_connections {
+            f.write_str(", ")?;
+            if connections.max > 0 {
+                write!(f, "{} of {}", connections.open, connections.max)?;
+            } else {
+                write!(f, "{}", connections.open)?;
+            }
+            write!(f, " DB connections ({} idle)", connections.idle)?;
+        }
+        if let Some(tasks) = self.tokio_tasks {
+            write!(f, ", {tasks} tasks")?;
+        }
+        Ok(())
+    }
+}
+
+/// Periodically records [`ResourceUsage`] in the resource gauges
+///
+/// Each sample also times checking a connection out of the database pool
+/// and how long a freshly spawned task waits before it is first polled,
+/// which grow when the pool is exhausted or tasks block the runtime.
+pub struct ResourceMonitor {
+    storage: StorageManager,
+    metrics: Arc<Metrics>,
+}
+
+impl ResourceMonitor {
+    /// Create a monitor sampling `storage` into `metrics`
+    pub fn new(storage: StorageManager, metrics: Arc<Metrics>) -> Self {
+        Self { storage, metrics }
+    }
+
+    /// Sample until `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        let mut previous: Option<(Instant, u64)> = None;
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+            let usage = ResourceUsage::sample(Some(&self.storage));
+            self.metrics.record_resources(&usage);
+            self.metrics
+                .typed_events_skipped
+                .set(self.storage.typed_events_skipped() as i64);
+
+            if let (Some(busy_ms), Some(workers)) = (usage.tokio_busy_ms, usage.tokio_workers) {
+                let now = Instant::now();
+                if let Some((at, previous_ms)) = previous {
+                    let available = now.duration_since(at).as_secs_f64() * workers.max(1) as f64;
+                    let busy = busy_ms.saturating_sub(previous_ms) as f64 / 1000.0;
+                    self.metrics
+                        .tokio_busy_ratio
+                        .set((busy / available).min(1.0));
+                }
+                previous = Some((now, busy_ms));
+            }
+
+            let spawned = Instant::now();
+            if let Ok(delay) = tokio::spawn(async move { spawned.elapsed() }).await {
+                self.metrics
+                    .tokio_scheduling_delay_seconds
+                    .observe(delay.as_secs_f64());
+            }
+
+            self.sample_acquire_wait(usage.db_connections).await;
+        }
+    }
+
+    async fn sample_acquire_wait(&self, connections: Option<ConnectionStats>) {
+        let waited = match tokio::time::timeout(SAMPLE_INTERVAL, self.storage.acquire_wait()).await
+        {
+            Ok(Ok(Some(waited))) => waited,
+            Ok(Ok(None)) => return,
+            Ok(Err(e)) => {
+                debug!(error = %e, "Failed to check out a database connection");
+                return;
+            }
+            Err(_) => {
+                let connections = connections.unwrap_or_default();
+                warn!(
+                    waited_secs = SAMPLE_INTERVAL.as_secs(),
+                    in_use = connections.in_use(),
+                    max = connections.max,
+                    "Timed out waiting for a database connection, the pool may be exhausted"
+                );
+                SAMPLE_INTERVAL
+            }
+        };
+        self.metrics
+            .db_acquire_wait_seconds
+            .observe(waited.as_secs_f64());
+    }
+}
+
+/// Resident set size of the current process in bytes
+#[cfg(target_os = "linux")]
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_rss(&status)
+}
+
+/// Resident set size of the current process in bytes
+#[cfg(target_os = "macos")]
+pub fn resident_memory() -> Option<u64> {
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    // SAFETY: proc_taskinfo is plain data and proc_pidinfo writes at most
+    // `size` bytes into it
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let written = unsafe {
+        libc::proc_pidinfo(
+            std::process::id() as libc::c_int,
+            libc::PROC_PIDTASKINFO,
+            0,
+            (&mut info as *mut libc::proc_taskinfo).cast(),
+            size,
+        )
+    };
+    (written == size).then_some(info.pti_resident_size)
+}
+
+/// Resident set size of the current process in bytes
+#[cfg(windows)]
+pub fn resident_memory() -> Option<u64> {
+    use windows_sys::Win32::System::{
+        ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+        Threading::GetCurrentProcess,
+    };
+
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    // SAFETY: the counters are plain data, and the pseudo handle of the current
+    // process needs no closing
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+    (ok != 0).then_some(counters.WorkingSetSize as u64)
+}
+
+/// Resident set size of the current process in bytes
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn resident_memory() -> Option<u64> {
+    None
+}
+
+/// The `VmRSS` line of `/proc/<pid>/status`, which is given in KiB
+#[cfg(target_os = "linux")]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_resident_memory() {
+        assert_eq!(
+            parse_vm_rss("Name:\tsui-indexer\nVmRSS:\t  2048 kB\nThreads:\t4\n"),
+            Some(2 * 1024 * 1024)
+        );
+        assert!(resident_memory().is_some_and(|rss| rss > 0));
+    }
+
+    #[test]
+    fn test_usage_display() {
+        let usage = ResourceUsage {
+            rss_bytes: Some(3 * 1024 * 1024),
+            db_connections: Some(ConnectionStats {
+                open: 4,
+                idle: 3,
+                max: 0,
+            }),
+            tokio_tasks: Some(12),
+            ..ResourceUsage::default()
+        };
+        assert_eq!(
+            usage.to_string(),
+            "3.0 MiB RSS, 4 DB connections (3 idle), 12 tasks"
+        );
+        assert_eq!(ResourceUsage::default().to_string(), "RSS unavailable");
+
+        let bounded = ResourceUsage {
+            db_connections: Some(ConnectionStats {
+                open: 10,
+                idle: 0,
+                max: 10,
+            }),
+            ..usage
+        };
+        assert_eq!(
+            bounded.to_string(),
+            "3.0 MiB RSS, 10 of 10 DB connections (0 idle), 12 tasks"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_sample_reads_the_runtime() {
+        let usage = ResourceUsage::sample(None);
+        assert_eq!(usage.tokio_workers, Some(2));
+        assert!(usage.tokio_tasks.is_some());
+        assert!(usage.tokio_queue_depth.is_some());
+        assert!(usage.tokio_busy_ms.is_some());
+        assert_eq!(usage.db_connections, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_monitor_records_samples_in_the_gauges() -> eyre::Result<()> {
+        let metrics = Arc::new(Metrics::new()?);
+        let monitor = ResourceMonitor::new(
+            StorageManager::from_backend(MemoryStorage::new()),
+            metrics.clone(),
+        );
+        let shutdown = CancellationToken::new();
+        let running = tokio::spawn(monitor.run(shutdown.clone()));
+
+        // The first sample is taken right away
+        let started = Instant::now();
+        while metrics.tokio_scheduling_delay_seconds.get_sample_count() == 0 {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "no sample taken"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.cancel();
+        running.await?;
+        assert_eq!(metrics.tokio_workers.get(), 2);
+        assert!(metrics.tokio_tasks.get() > 0);
+        // Backends without a pool have no checkout to time
+        assert_eq!(metrics.db_acquire_wait_seconds.get_sample_count(), 0);
+
+        metrics.record_resources(&ResourceUsage {
+            db_connections: Some(ConnectionStats {
+                open: 10,
+                idle: 3,
+                max: 10,
+            }),
+            ..ResourceUsage::default()
+        });
+        assert_eq!(metrics.db_connections.get(), 10);
+        assert_eq!(metrics.db_in_use_connections.get(), 7);
+        assert_eq!(metrics.db_max_connections.get(), 10);
+        let encoded = metrics.encode()?;
+        assert!(encoded.contains("sui_indexer_db_in_use_connections 7"));
+        Ok(())
+    }
+}
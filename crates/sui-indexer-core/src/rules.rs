@@ -0,0 +1,461 @@
+use std::{sync::Arc, time::Duration};
+
+use eyre::{Result, WrapErr};
+use serde_json::{json, Value};
+use sui_indexer_config::{IndexerConfig, RuleCondition, RuleConfig};
+use sui_indexer_events::{
+    system_clock, EventFilterProcessor, Placeholder, ProcessedEvent, SharedClock, Template,
+};
+use sui_indexer_sinks::notification::RateLimit;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+use url::Url;
+
+use crate::{
+    alerts::{notifiers_from_config, Alert, AlertKind, Notifier},
+    metrics::Metrics,
+};
+
+/// Matches waiting for the notifier; further matches are dropped and counted
+const MATCH_QUEUE_CAPACITY: usize = 1024;
+
+/// Timeout of a single rule webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Evaluates the configured rules against processed events
+///
+/// Tags are attached while a checkpoint is transformed, so they are stored
+/// and seen by the sinks. Messages are handed to the [`RuleNotifier`] only
+/// once the checkpoint is committed, and a slow channel never holds up the
+/// pipeline: matches beyond its queue are dropped.
+#[derive(Clone)]
+pub struct RuleEngine {
+    rules: Arc<[Rule]>,
+    matches: mpsc::Sender<RuleMatch>,
+    metrics: Arc<Metrics>,
+}
+
+/// A rule met by an event, with its rendered message
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    /// Index of the rule in the engine
+    rule: usize,
+    text: String,
+    event: ProcessedEvent,
+}
+
+struct Rule {
+    name: String,
+    selector: EventFilterProcessor,
+    conditions: Vec<Condition>,
+    template: Template,
+    tag: Option<String>,
+    /// Whether matches are posted anywhere
+    notifies: bool,
+}
+
+struct Condition {
+    value: Placeholder,
+    min: Option<f64>,
+    max: Option<f64>,
+    /// Allowed values, addresses in full form
+    one_of: Vec<String>,
+}
+
+impl RuleEngine {
+    /// Engine over the enabled rules of `config` with the notifier posting their messages,
+    /// or `None` if no rule is enabled
+    pub fn from_config(
+        config: &IndexerConfig,
+        metrics: Arc<Metrics>,
+    ) -> Result<Option<(Self, RuleNotifier)>> {
+        let enabled: Vec<_> = config
+            .rules
+            .iter()
+            .filter(|(_, rule)| rule.enabled)
+            .collect();
+        if enabled.is_empty() {
+            return Ok(None);
+        }
+
+        let mut rules = Vec::new();
+        let mut actions = Vec::new();
+        for (name, rule) in &enabled {
+            rules.push(
+                Rule::new(name, rule, config).wrap_err_with(|| format!("Invalid rule `{name}`"))?,
+            );
+            actions.push(Actions {
+                name: name.to_string(),
+                webhooks: rule.webhooks.clone(),
+                alert: rule.alert,
+                limit: RateLimit::per_minute(rule.max_per_minute.max(1)),
+                skipped: 0,
+            });
+        }
+        let notifiers = if enabled.iter().any(|(_, rule)| rule.alert) {
+            notifiers_from_config(&config.alerts)?
+        } else {
+            Vec::new()
+        };
+
+        let (tx, rx) = mpsc::channel(MATCH_QUEUE_CAPACITY);
+        let notifier = RuleNotifier {
+            client: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()?,
+            actions,
+            notifiers,
+            matches: rx,
+            metrics: metrics.clone(),
+            clock: system_clock(),
+        };
+        let engine = Self {
+            rules: rules.into(),
+            matches: tx,
+            metrics,
+        };
+        Ok(Some((engine, notifier)))
+    }
+
+    /// Tag `event` for every rule it meets, returning the matches to notify once it is stored
+    pub fn evaluate(&self, event: &mut ProcessedEvent) -> Vec<RuleMatch> {
+        let mut matches = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.matches(event) {
+                continue;
+            }
+            self.metrics
+                .rule_events_matched
+                .with_label_values(&[&rule.name])
+                .inc();
+            if let Some(tag) = &rule.tag {
+                if !event.metadata.tags.contains(tag) {
+                    event.metadata.tags.push(tag.clone());
+                }
+            }
+            if rule.notifies {
+                matches.push(RuleMatch {
+                    rule: index,
+                    text: rule.template.render(event),
+                    event: event.clone(),
+                });
+            }
+        }
+        matches
+    }
+
+    /// Queue the messages of committed matches, dropping those the notifier has no room for
+    pub fn notify(&self, matches: Vec<RuleMatch>) {
+        let mut dropped = 0;
+        for rule_match in matches {
+            if let Err(mpsc::error::TrySendError::Full(rule_match)) =
+                self.matches.try_send(rule_match)
+            {
+                self.metrics
+                    .rule_notifications
+                    .with_label_values(&[&self.rules[rule_match.rule].name, "dropped"])
+                    .inc();
+                dropped += 1;
+            }
+        }
+        if dropped > 0 {
+            warn!(
+                dropped,
+                "Rule notifications are backed up, dropping messages"
+            );
+        }
+    }
+}
+
+impl Rule {
+    fn new(name: &str, config: &RuleConfig, indexer: &IndexerConfig) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            selector: EventFilterProcessor::new(indexer.events.named_filters(&config.filters)?),
+            conditions: config
+                .conditions
+                .iter()
+                .map(Condition::new)
+                .collect::<Result<_>>()?,
+            template: Template::parse_with(&config.template, &[("rule", name)])?,
+            tag: config.tag.clone(),
+            notifies: !config.webhooks.is_empty() || config.alert,
+        })
+    }
+
+    fn matches(&self, event: &ProcessedEvent) -> bool {
+        self.selector.should_process_event(&event.event)
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(event))
+    }
+}
+
+impl Condition {
+    fn new(config: &RuleCondition) -> Result<Self> {
+        Ok(Self {
+            value: Placeholder::parse(&config.field)?,
+            min: config.min,
+            max: config.max,
+            one_of: config.one_of.iter().map(|value| normalize(value)).collect(),
+        })
+    }
+
+    fn holds(&self, event: &ProcessedEvent) -> bool {
+        if self.min.is_some() || self.max.is_some() {
+            let Some(number) = self.value.number(event) else {
+                return false;
+            };
+            if self.min.is_some_and(|min| number < min) || self.max.is_some_and(|max| number > max)
+            {
+                return false;
+            }
+        }
+        if !self.one_of.is_empty() {
+            let text = match self.value.value(event) {
+                Some(Value::String(text)) => text,
+                Some(Value::Null) | None => return false,
+                Some(value) => value.to_string(),
+            };
+            return self.one_of.contains(&normalize(&text));
+        }
+        true
+    }
+}
+
+/// Full lowercase form of a hex address, so `0x2` matches `0x0…02`; other values are unchanged
+fn normalize(value: &str) -> String {
+    match value.strip_prefix("0x") {
+        Some(hex)
+            if (1..=64).contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            format!("0x{:0>64}", hex.to_ascii_lowercase())
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Posts rule messages to their webhooks and alert channels
+///
+/// Messages over a rule's `max_per_minute` are skipped; the next message that
+/// goes out says how many were. Failed posts are logged and not retried.
+pub struct RuleNotifier {
+    client: reqwest::Client,
+    /// Actions by rule index
+    actions: Vec<Actions>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    matches: mpsc::Receiver<RuleMatch>,
+    metrics: Arc<Metrics>,
+    clock: SharedClock,
+}
+
+struct Actions {
+    name: String,
+    webhooks: Vec<Url>,
+    alert: bool,
+    limit: RateLimit,
+    /// Matches skipped by the rate limit since the last message
+    skipped: u64,
+}
+
+impl RuleNotifier {
+    /// Rate limit and timestamp alerts with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Post the messages of committed matches until `shutdown` is cancelled
+    pub async fn run(mut self, shutdown: CancellationToken) {
+        loop {
+            let rule_match = tokio::select! {
+                () = shutdown.cancelled() => return,
+                rule_match = self.matches.recv() => match rule_match {
+                    Some(rule_match) => rule_match,
+                    None => return,
+                },
+            };
+            let rule = rule_match.rule;
+            let outcome = self.post(rule_match).await;
+            self.metrics
+                .rule_notifications
+                .with_label_values(&[&self.actions[rule].name, outcome])
+                .inc();
+        }
+    }
+
+    /// Post one message, returning its outcome
+    async fn post(&mut self, rule_match: RuleMatch) -> &'static str {
+        let actions = &mut self.actions[rule_match.rule];
+        if !actions.limit.try_take(self.clock.instant()) {
+            if actions.skipped == 0 {
+                warn!(rule = %actions.name, "Rule rate limit reached, skipping messages");
+            }
+            actions.skipped += 1;
+            return "rate_limited";
+        }
+
+        let mut text = rule_match.text;
+        if actions.skipped > 0 {
+            text.push_str(&format!(
+                "\n({} more matches were skipped by the rate limit)",
+                actions.skipped
+            ));
+            actions.skipped = 0;
+        }
+
+        let mut failed = false;
+        let payload = json!({
+            "rule": actions.name,
+            "message": text,
+            "event": rule_match.event,
+        });
+        for url in &actions.webhooks {
+            let posted = self
+                .client
+                .post(url.clone())
+                .json(&payload)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            // Webhook URLs often carry a token, so only the host is logged
+            if let Err(e) = posted {
+                warn!(
+                    rule = %actions.name,
+                    host = url.host_str().unwrap_or_default(),
+                    error = %e.without_url(),
+                    "Failed to post rule webhook"
+                );
+                failed = true;
+            }
+        }
+        if actions.alert {
+            let event = &rule_match.event;
+            let alert = Alert {
+                kind: AlertKind::Rule,
+                dedup_key: format!(
+                    "sui-indexer-rule-{}-{}-{}",
+                    actions.name, event.transaction_digest, event.metadata.event_index
+                ),
+                firing: true,
+                message: text,
+                timestamp: self.clock.now(),
+            };
+            for notifier in &self.notifiers {
+                if let Err(e) = notifier.notify(&alert).await {
+                    warn!(
+                        rule = %actions.name,
+                        notifier = notifier.name(),
+                        error = %e,
+                        "Failed to deliver rule alert"
+                    );
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            "failed"
+        } else {
+            "sent"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::{EventFilter, FilterPriority};
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    async fn event(module: &str, amount: &str) -> Result<ProcessedEvent> {
+        let event = TestEvent::new(&format!("0x2::{module}::Liquidation"))
+            .fields(json!({"amount": amount}))
+            .build();
+        let mut events = DefaultEventProcessor::new()
+            .process_events(vec![event])
+            .await?;
+        Ok(events.remove(0))
+    }
+
+    #[tokio::test]
+    async fn test_rules_tag_and_notify_matching_events() -> Result<()> {
+        let mut config = IndexerConfig::default();
+        config.events.filters = vec![EventFilter {
+            name: Some("lending".to_string()),
+            package: None,
+            module: Some("lending".to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        }];
+        config.rules.insert(
+            "big_liquidations".to_string(),
+            RuleConfig {
+                filters: vec!["lending".to_string()],
+                conditions: vec![
+                    RuleCondition {
+                        field: "fields.parsed_json.amount".to_string(),
+                        min: Some(1000.0),
+                        max: None,
+                        one_of: Vec::new(),
+                    },
+                    RuleCondition {
+                        field: "sender".to_string(),
+                        min: None,
+                        max: None,
+                        one_of: vec!["0x123".to_string(), "0x456".to_string()],
+                    },
+                ],
+                template: "{rule}: {fields.parsed_json.amount} liquidated".to_string(),
+                tag: Some("big-liquidation".to_string()),
+                webhooks: vec!["http://localhost:9/rules".parse()?],
+                ..RuleConfig::default()
+            },
+        );
+        config.rules.insert(
+            "disabled".to_string(),
+            RuleConfig {
+                enabled: false,
+                tag: Some("never".to_string()),
+                ..RuleConfig::default()
+            },
+        );
+
+        let metrics = Arc::new(Metrics::new()?);
+        let (engine, _notifier) = RuleEngine::from_config(&config, metrics.clone())?.unwrap();
+
+        let mut big = event("lending", "1500").await?;
+        let matches = engine.evaluate(&mut big);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].text, "big_liquidations: 1500 liquidated");
+        assert_eq!(big.metadata.tags, ["big-liquidation"]);
+
+        // Below the threshold, or outside the preselecting filter
+        for (module, amount) in [("lending", "999"), ("pool", "1500")] {
+            let mut other = event(module, amount).await?;
+            assert!(engine.evaluate(&mut other).is_empty());
+            assert!(other.metadata.tags.is_empty());
+        }
+        assert_eq!(
+            metrics
+                .rule_events_matched
+                .with_label_values(&["big_liquidations"])
+                .get(),
+            1
+        );
+        assert!(RuleEngine::from_config(&IndexerConfig::default(), metrics)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_pads_addresses_only() {
+        assert_eq!(normalize("0x2"), format!("0x{:0>64}", "2"));
+        assert_eq!(normalize("0xAB"), format!("0x{:0>64}", "ab"));
+        assert_eq!(normalize("0x2::sui::SUI"), "0x2::sui::SUI");
+        assert_eq!(normalize("1500"), "1500");
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use sui_indexer_config::SchedulerConfig;
+use tokio::sync::oneshot;
+
+/// Resource shared between pipelines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// A request to the Sui node
+    Node,
+    /// A write to storage
+    Storage,
+}
+
+/// Weighted round-robin scheduler of node requests and storage writes across pipelines
+///
+/// Each resource has a fixed number of permits. A pipeline takes one per
+/// request or write and returns it when the permit is dropped; a returned
+/// permit goes to the next waiting pipeline by smooth weighted round-robin,
+/// so pipelines waiting at the same time are served in proportion to their
+/// weights however many requests each has queued. The default scheduler
+/// hands out permits immediately.
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    pools: Option<Arc<Pools>>,
+}
+
+struct Pools {
+    node: Arc<Pool>,
+    storage: Arc<Pool>,
+}
+
+impl Scheduler {
+    /// Scheduler sharing the configured capacity, or one that never waits when disabled
+    pub fn from_config(config: &SchedulerConfig) -> Self {
+        if !config.enabled {
+            return Self::default();
+        }
+        let pool = |capacity: usize| {
+            Arc::new(Pool {
+                weights: config.weights.clone(),
+                state: Mutex::new(PoolState {
+                    available: capacity.max(1),
+                    lanes: BTreeMap::new(),
+                }),
+            })
+        };
+        Self {
+            pools: Some(Arc::new(Pools {
+                node: pool(config.node_requests),
+                storage: pool(config.storage_writes),
+            })),
+        }
+    }
+
+    /// Wait for the turn of `pipeline` to use `resource`
+    pub async fn acquire(&self, pipeline: &str, resource: Resource) -> Permit {
+        let Some(pools) = &self.pools else {
+            return Permit { pool: None };
+        };
+        let pool = match resource {
+            Resource::Node => &pools.node,
+            Resource::Storage => &pools.storage,
+        };
+
+        let granted = {
+            let mut state = pool.state();
+            if state.available > 0 && state.lanes.values().all(|lane| lane.waiters.is_empty()) {
+                state.available -= 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                let weight = pool.weight(pipeline);
+                state
+                    .lanes
+                    .entry(pipeline.to_string())
+                    .or_insert_with(|| Lane::new(weight))
+                    .waiters
+                    .push_back(tx);
+                Some(rx)
+            }
+        };
+        match granted {
+            None => Permit {
+                pool: Some(pool.clone()),
+            },
+            // The sender is only dropped with the scheduler, which outlives its permits
+            Some(rx) => rx.await.unwrap_or(Permit { pool: None }),
+        }
+    }
+
+    /// Pipelines waiting for `resource` with the number of queued requests of each
+    pub fn waiting(&self, resource: Resource) -> BTreeMap<String, usize> {
+        let Some(pools) = &self.pools else {
+            return BTreeMap::new();
+        };
+        let pool = match resource {
+            Resource::Node => &pools.node,
+            Resource::Storage => &pools.storage,
+        };
+        let state = pool.state();
+        state
+            .lanes
+            .iter()
+            .filter(|(_, lane)| !lane.waiters.is_empty())
+            .map(|(name, lane)| (name.clone(), lane.waiters.len()))
+            .collect()
+    }
+}
+
+struct Pool {
+    weights: BTreeMap<String, u32>,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    /// Permits not held by anyone
+    available: usize,
+    lanes: BTreeMap<String, Lane>,
+}
+
+struct Lane {
+    weight: i64,
+    /// Credit of the smooth weighted round-robin
+    current: i64,
+    waiters: VecDeque<oneshot::Sender<Permit>>,
+}
+
+impl Lane {
+    fn new(weight: i64) -> Self {
+        Self {
+            weight,
+            current: 0,
+            waiters: VecDeque::new(),
+        }
+    }
+}
+
+impl Pool {
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn weight(&self, pipeline: &str) -> i64 {
+        i64::from(self.weights.get(pipeline).copied().unwrap_or(1).max(1))
+    }
+
+    /// Hand a returned permit to the next waiter, or put it back
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state();
+        loop {
+            let mut total = 0;
+            let mut next: Option<&mut Lane> = None;
+            for lane in state
+                .lanes
+                .values_mut()
+                .filter(|lane| !lane.waiters.is_empty())
+            {
+                lane.current += lane.weight;
+                total += lane.weight;
+                if next.as_ref().is_none_or(|next| lane.current > next.current) {
+                    next = Some(lane);
+                }
+            }
+            let Some(lane) = next else {
+                state.available += 1;
+                return;
+            };
+            lane.current -= total;
+
+            let Some(waiter) = lane.waiters.pop_front() else {
+                continue;
+            };
+            let permit = Permit {
+                pool: Some(self.clone()),
+            };
+            match waiter.send(permit) {
+                Ok(()) => return,
+                // The waiter gave up; pass the permit on without releasing it again
+                Err(mut permit) => permit.pool = None,
+            }
+        }
+    }
+}
+
+/// Turn to use a resource, passed on to the next waiting pipeline when dropped
+#[must_use]
+pub struct Permit {
+    pool: Option<Arc<Pool>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(weights: &[(&str, u32)]) -> Scheduler {
+        Scheduler::from_config(&SchedulerConfig {
+            enabled: true,
+            node_requests: 1,
+            storage_writes: 1,
+            weights: weights
+                .iter()
+                .map(|(name, weight)| (name.to_string(), *weight))
+                .collect(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_waiters_served_by_weight() {
+        let scheduler = scheduler(&[("live", 3), ("backfill", 1)]);
+        let held = scheduler.acquire("backfill", Resource::Node).await;
+
+        // A backfill queues far more requests than the live pipeline
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut tasks = Vec::new();
+        for (pipeline, requests) in [("backfill", 12), ("live", 6)] {
+            for _ in 0..requests {
+                let scheduler = scheduler.clone();
+                let tx = tx.clone();
+                tasks.push(tokio::spawn(async move {
+                    let permit = scheduler.acquire(pipeline, Resource::Node).await;
+                    tx.send(pipeline).unwrap();
+                    // Hold the turn until the test has recorded it
+                    tokio::task::yield_now().await;
+                    drop(permit);
+                }));
+                tokio::task::yield_now().await;
+            }
+        }
+        assert_eq!(
+            scheduler.waiting(Resource::Node),
+            BTreeMap::from([("backfill".to_string(), 12), ("live".to_string(), 6)])
+        );
+        assert!(scheduler.waiting(Resource::Storage).is_empty());
+
+        drop(held);
+        let mut order = Vec::new();
+        for _ in 0..8 {
+            order.push(rx.recv().await.unwrap());
+        }
+        // Three live turns for every backfill turn while both wait
+        assert_eq!(
+            order.iter().filter(|pipeline| **pipeline == "live").count(),
+            6
+        );
+        for task in tasks {
+            task.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_scheduler_never_waits() {
+        let scheduler = Scheduler::default();
+        let _first = scheduler.acquire("default", Resource::Storage).await;
+        let _second = scheduler.acquire("default", Resource::Storage).await;
+        assert!(scheduler.waiting(Resource::Storage).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_passes_turn_on() {
+        let scheduler = scheduler(&[]);
+        let held = scheduler.acquire("default", Resource::Storage).await;
+
+        let cancelled = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            scheduler.acquire("backfill", Resource::Storage),
+        )
+        .await;
+        assert!(cancelled.is_err());
+        drop(held);
+
+        // The abandoned turn did not leak the only permit
+        let _permit = scheduler.acquire("default", Resource::Storage).await;
+    }
+}
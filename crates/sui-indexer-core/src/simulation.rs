@@ -0,0 +1,614 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::Serialize;
+use sui_indexer_config::IndexerConfig;
+use sui_indexer_events::{DefaultEventProcessor, EventProcessor, ManualClock, SharedClock};
+use sui_indexer_storage::{MemoryStorage, StorageManager, DEFAULT_PIPELINE};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    error::IndexerError,
+    live::{LiveBatch, LiveEvents},
+    metrics::Metrics,
+    pipeline::Pipeline,
+    sui::{mock::synthetic_checkpoint, CheckpointData, MockSuiClient, SuiClient},
+};
+
+/// One scripted action of a [`Simulation`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum Step {
+    /// Add the next `checkpoints` checkpoints to the chain, `events` events each
+    Produce { checkpoints: u64, events: usize },
+    /// Hold back the next checkpoint, so the tip moves past a gap until it is released
+    Hold { events: usize },
+    /// Add every held back checkpoint to the chain
+    Release,
+    /// Fail the next `requests` node requests
+    NodeErrors { requests: usize },
+    /// Answer the next request for `checkpoint` with the earlier checkpoint `served`
+    Duplicate { checkpoint: u64, served: u64 },
+    /// Let the indexer run for this long
+    Run { millis: u64 },
+    /// Abort the indexer wherever it is and start it again from the stored cursor
+    Crash,
+}
+
+/// Outcome of a simulation with every invariant violation found
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    /// Checkpoints the chain ended with
+    pub checkpoints: u64,
+    /// Events in those checkpoints
+    pub events: u64,
+    /// Events found in storage
+    pub stored: u64,
+    /// Events written to storage, counting ones written again and skipped as duplicates
+    pub written: u64,
+    /// Times the indexer was aborted and started again
+    pub crashes: u64,
+    /// Stored cursor at the end
+    pub cursor: Option<u64>,
+    /// Broken invariants, empty if the run was correct
+    pub violations: Vec<String>,
+}
+
+impl SimulationReport {
+    /// Fail with every violation if any invariant was broken
+    pub fn check(&self) -> Result<()> {
+        if !self.violations.is_empty() {
+            return Err(IndexerError::InvariantViolations(self.violations.clone()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic run of the pipeline over a scripted chain and [`MemoryStorage`]
+///
+/// The steps grow a [`MockSuiClient`] chain, inject node faults and crash
+/// the indexer, which is restarted from its stored cursor like a real
+/// deployment. Once the script is done, held back checkpoints are released
+/// and the indexer is given time to catch up, then the run is checked: every
+/// event is written exactly once under its checkpoint, although storage would
+/// skip a second copy, commits happen in increasing checkpoint order and the
+/// cursor never moves back and ends at the tip. Run it on a current-thread runtime with paused time, as in
+/// `#[tokio::test(start_paused = true)]`, so timers advance instantly and the
+/// same script always interleaves the same way.
+pub struct Simulation {
+    steps: Vec<Step>,
+    config: IndexerConfig,
+    /// Processor given by the script, or the default one on the simulated clock
+    processor: Option<Arc<dyn EventProcessor>>,
+    catch_up: Duration,
+    clock: ManualClock,
+}
+
+impl Simulation {
+    /// Create a simulation of the given steps
+    pub fn new(steps: Vec<Step>) -> Self {
+        let mut config = IndexerConfig::default();
+        config.runtime.poll_interval = 100;
+        Self {
+            steps,
+            config,
+            processor: None,
+            catch_up: Duration::from_secs(600),
+            clock: ManualClock::new(simulation_start()),
+        }
+    }
+
+    /// Create a simulation of `steps` steps drawn from `seed`, the same for the same seed
+    pub fn random(seed: u64, steps: usize) -> Self {
+        let mut rng = XorShift::new(seed);
+        let mut next = 0;
+        let mut script = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let step = match rng.below(100) {
+                0..40 => Step::Produce {
+                    checkpoints: 1 + rng.below(5),
+                    events: rng.below(9) as usize,
+                },
+                40..65 => Step::Run {
+                    millis: 50 + rng.below(2000),
+                },
+                65..75 => Step::NodeErrors {
+                    requests: 1 + rng.below(4) as usize,
+                },
+                75..83 => Step::Hold {
+                    events: rng.below(9) as usize,
+                },
+                83..88 => Step::Release,
+                88..93 if next > 0 => Step::Duplicate {
+                    checkpoint: next,
+                    served: rng.below(next),
+                },
+                _ => Step::Crash,
+            };
+            match &step {
+                Step::Produce { checkpoints, .. } => next += checkpoints,
+                Step::Hold { .. } => next += 1,
+                _ => {}
+            }
+            script.push(step);
+        }
+        Self::new(script)
+    }
+
+    /// Run the pipeline with this configuration instead of the defaults with a 100ms poll interval
+    pub fn with_config(mut self, config: IndexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Process events with `processor` instead of the default processor
+    pub fn with_processor(mut self, processor: Arc<dyn EventProcessor>) -> Self {
+        self.processor = Some(processor);
+        self
+    }
+
+    /// Read the time from `clock`, which the simulation advances along with paused time
+    ///
+    /// Keep a clone to read the simulated time after the run.
+    pub fn with_clock(mut self, clock: ManualClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Give the indexer this long to reach the tip after the last step
+    pub fn with_catch_up(mut self, catch_up: Duration) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// Run the script and check the invariants
+    pub async fn run(self) -> Result<SimulationReport> {
+        let node = MockSuiClient::new();
+        let memory = MemoryStorage::new();
+        let storage = StorageManager::from_backend(memory.clone());
+        let live = LiveEvents::default();
+        let monitor = tokio::spawn(monitor(live.subscribe()));
+        let (_config, config) = watch::channel(self.config.clone());
+        let clock: SharedClock = Arc::new(self.clock.clone());
+        let processor = self
+            .processor
+            .clone()
+            .unwrap_or_else(|| Arc::new(DefaultEventProcessor::new().with_clock(clock.clone())));
+        let mut harness = Harness {
+            node: node.clone(),
+            client: SuiClient::mock(node).with_clock(clock.clone()),
+            storage,
+            memory,
+            processor,
+            clock,
+            started_at: self.clock.now(),
+            config,
+            metrics: Arc::new(Metrics::new()?),
+            live,
+            expected: BTreeMap::new(),
+            next: 0,
+            held: VecDeque::new(),
+            cursor: None,
+            crashes: 0,
+            violations: Vec::new(),
+        };
+
+        let mut indexer = harness.start().await?;
+        for step in &self.steps {
+            match step {
+                Step::Produce {
+                    checkpoints,
+                    events,
+                } => {
+                    for _ in 0..*checkpoints {
+                        harness.produce(*events)?;
+                    }
+                }
+                Step::Hold { events } => {
+                    let checkpoint = harness.checkpoint(*events)?;
+                    harness.held.push_back(checkpoint);
+                }
+                Step::Release => harness.release(),
+                Step::NodeErrors { requests } => harness.node.fail_requests(*requests),
+                Step::Duplicate { checkpoint, served } => {
+                    harness.node.substitute(*checkpoint, *served)
+                }
+                Step::Run { millis } => {
+                    tokio::time::sleep(Duration::from_millis(*millis)).await;
+                    self.clock.advance(Duration::from_millis(*millis));
+                }
+                Step::Crash => {
+                    indexer.crash().await;
+                    harness.crashes += 1;
+                    indexer = harness.start().await?;
+                }
+            }
+            harness.observe_cursor().await?;
+        }
+
+        harness.release();
+        let tip = harness.next.checked_sub(1);
+        let deadline = Instant::now() + self.catch_up;
+        while harness.cursor != tip && Instant::now() < deadline && !indexer.handle.is_finished() {
+            let poll = Duration::from_millis(self.config.runtime.poll_interval.max(1));
+            tokio::time::sleep(poll).await;
+            self.clock.advance(poll);
+            harness.observe_cursor().await?;
+        }
+        if harness.cursor != tip {
+            harness.violations.push(format!(
+                "Cursor stopped at {:?} short of the tip {tip:?}",
+                harness.cursor
+            ));
+        }
+        indexer.shutdown.cancel();
+        match indexer.handle.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => harness.violations.push(format!("Pipeline failed: {e}")),
+            Err(e) => harness.violations.push(format!("Pipeline panicked: {e}")),
+        }
+        harness.observe_cursor().await?;
+        // Every sender is gone with the pipelines, which ends the monitor
+        drop(std::mem::take(&mut harness.live));
+        let commits = monitor.await?;
+        harness.violations.extend(commits);
+
+        harness.report().await
+    }
+}
+
+/// Scripted chain, storage and what the run has observed so far
+struct Harness {
+    node: MockSuiClient,
+    client: SuiClient,
+    storage: StorageManager,
+    /// Backend of `storage`, which deduplicates stored events and counts every write
+    memory: MemoryStorage,
+    processor: Arc<dyn EventProcessor>,
+    clock: SharedClock,
+    /// Simulated time the run started at
+    started_at: DateTime<Utc>,
+    config: watch::Receiver<IndexerConfig>,
+    metrics: Arc<Metrics>,
+    live: LiveEvents,
+    /// Checkpoint of every event on the chain, by transaction digest and event sequence
+    expected: BTreeMap<(String, u64), u64>,
+    /// Sequence number of the next checkpoint to create
+    next: u64,
+    held: VecDeque<CheckpointData>,
+    cursor: Option<u64>,
+    crashes: u64,
+    violations: Vec<String>,
+}
+
+/// A running indexer
+struct Indexer {
+    handle: JoinHandle<Result<()>>,
+    shutdown: CancellationToken,
+}
+
+impl Indexer {
+    /// Abort the pipeline at its current await point, without draining
+    async fn crash(self) {
+        self.handle.abort();
+        let _ = self.handle.await;
+        // The fetcher is a task of its own and stops once it sees the crash
+        self.shutdown.cancel();
+    }
+}
+
+impl Harness {
+    /// Start a pipeline after the stored cursor
+    async fn start(&self) -> Result<Indexer> {
+        let start = self
+            .storage
+            .get_latest_checkpoint()
+            .await?
+            .map_or(0, |cursor| cursor + 1);
+        info!(start, crashes = self.crashes, "Starting simulated indexer");
+        let pipeline = Pipeline::new(
+            self.client.clone(),
+            self.storage.clone(),
+            self.processor.clone(),
+            self.config.clone(),
+            self.metrics.clone(),
+        )
+        .with_live(self.live.clone())
+        .with_clock(self.clock.clone());
+        let shutdown = CancellationToken::new();
+        Ok(Indexer {
+            handle: tokio::spawn(pipeline.run(start, shutdown.clone())),
+            shutdown,
+        })
+    }
+
+    /// Create the next checkpoint and note its events as expected
+    fn checkpoint(&mut self, events: usize) -> Result<CheckpointData> {
+        let checkpoint = synthetic_checkpoint(self.next, events)?;
+        self.next += 1;
+        for event in &checkpoint.events {
+            self.expected.insert(
+                (event.id.tx_digest.to_string(), event.id.event_seq),
+                checkpoint.sequence_number,
+            );
+        }
+        Ok(checkpoint)
+    }
+
+    fn produce(&mut self, events: usize) -> Result<()> {
+        let checkpoint = self.checkpoint(events)?;
+        self.node.push_checkpoint(checkpoint);
+        Ok(())
+    }
+
+    fn release(&mut self) {
+        for checkpoint in self.held.drain(..) {
+            self.node.push_checkpoint(checkpoint);
+        }
+    }
+
+    /// Read the stored cursor, noting it if it moved back
+    async fn observe_cursor(&mut self) -> Result<()> {
+        let cursor = self.storage.get_latest_checkpoint().await?;
+        if cursor < self.cursor {
+            self.violations.push(format!(
+                "Cursor moved back from {:?} to {cursor:?}",
+                self.cursor
+            ));
+        }
+        self.cursor = cursor;
+        Ok(())
+    }
+
+    /// Compare storage with the chain
+    async fn report(mut self) -> Result<SimulationReport> {
+        let stored = match self.next.checked_sub(1) {
+            Some(tip) => self.storage.get_events_by_checkpoint_range(0, tip).await?,
+            None => Vec::new(),
+        };
+        let mut found: BTreeMap<(String, u64), u64> = BTreeMap::new();
+        for event in &stored {
+            let key = (
+                event.event.id.tx_digest.to_string(),
+                event.event.id.event_seq,
+            );
+            match self.expected.get(&key) {
+                None => self.violations.push(format!(
+                    "Stored event {}:{} is not on the chain",
+                    key.0, key.1
+                )),
+                Some(checkpoint) if *checkpoint != event.checkpoint_sequence => {
+                    self.violations.push(format!(
+                        "Event {}:{} of checkpoint {checkpoint} stored under checkpoint {}",
+                        key.0, key.1, event.checkpoint_sequence
+                    ))
+                }
+                Some(_) => {}
+            }
+            *found.entry(key).or_default() += 1;
+            let processed_at = event.metadata.processed_at;
+            if processed_at < self.started_at || processed_at > self.clock.now() {
+                self.violations.push(format!(
+                    "Event {}:{} processed at {processed_at}, outside the simulated run",
+                    key.0, key.1
+                ));
+            }
+        }
+        for (key, checkpoint) in &self.expected {
+            match found.get(key).copied().unwrap_or(0) {
+                0 => self.violations.push(format!(
+                    "Event {}:{} of checkpoint {checkpoint} was never stored",
+                    key.0, key.1
+                )),
+                1 => {}
+                copies => self.violations.push(format!(
+                    "Event {}:{} of checkpoint {checkpoint} was stored {copies} times",
+                    key.0, key.1
+                )),
+            }
+        }
+
+        // Storage skips events it already holds, so a checkpoint delivered twice only shows in the writes
+        let written = self.memory.events_written();
+        if written > self.expected.len() as u64 {
+            self.violations.push(format!(
+                "{written} event writes for the {} events on the chain, so some were delivered twice",
+                self.expected.len()
+            ));
+        }
+
+        // A crash between a commit and its report loses the report, so only recorded ones are checked
+        if let Some(cursor) = self.cursor {
+            let mut on_chain: BTreeMap<u64, i64> = BTreeMap::new();
+            for checkpoint in self.expected.values() {
+                *on_chain.entry(*checkpoint).or_default() += 1;
+            }
+            for report in self
+                .storage
+                .checkpoint_reports(DEFAULT_PIPELINE, 0, cursor)
+                .await?
+            {
+                let checkpoint = report.checkpoint_sequence as u64;
+                let events = on_chain.get(&checkpoint).copied().unwrap_or(0);
+                if report.events_seen != events || report.events_stored != events {
+                    self.violations.push(format!(
+                        "Report of checkpoint {checkpoint} counts {} seen and {} stored events of {events}",
+                        report.events_seen, report.events_stored
+                    ));
+                }
+            }
+        }
+
+        Ok(SimulationReport {
+            checkpoints: self.next,
+            events: self.expected.len() as u64,
+            stored: stored.len() as u64,
+            written,
+            crashes: self.crashes,
+            cursor: self.cursor,
+            violations: self.violations,
+        })
+    }
+}
+
+/// Check that committed checkpoints arrive in increasing order, each with only its own events
+async fn monitor(mut commits: broadcast::Receiver<Arc<LiveBatch>>) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut last: Option<u64> = None;
+    loop {
+        match commits.recv().await {
+            Ok(batch) => {
+                if last.is_some_and(|last| batch.checkpoint <= last) {
+                    violations.push(format!(
+                        "Checkpoint {} committed after checkpoint {last:?}",
+                        batch.checkpoint
+                    ));
+                }
+                if let Some(event) = batch
+                    .events
+                    .iter()
+                    .find(|event| event.checkpoint_sequence != batch.checkpoint)
+                {
+                    violations.push(format!(
+                        "Commit of checkpoint {} holds an event of checkpoint {}",
+                        batch.checkpoint, event.checkpoint_sequence
+                    ));
+                }
+                last = Some(batch.checkpoint);
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                violations.push(format!("Monitor missed {missed} commits"));
+            }
+            Err(broadcast::error::RecvError::Closed) => return violations,
+        }
+    }
+}
+
+/// Simulated time every run starts at unless given a clock
+fn simulation_start() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_735_689_600, 0).expect("2025-01-01 is a valid timestamp")
+}
+
+/// Small xorshift generator, so schedules are reproducible from a seed alone
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The state must never be zero
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Next number below `bound`
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scripted_faults_keep_invariants() -> Result<()> {
+        let report = Simulation::new(vec![
+            Step::Produce {
+                checkpoints: 5,
+                events: 3,
+            },
+            Step::Run { millis: 1000 },
+            Step::Hold { events: 2 },
+            Step::Produce {
+                checkpoints: 2,
+                events: 1,
+            },
+            Step::NodeErrors { requests: 3 },
+            Step::Run { millis: 1000 },
+            Step::Release,
+            Step::Duplicate {
+                checkpoint: 8,
+                served: 4,
+            },
+            Step::Produce {
+                checkpoints: 3,
+                events: 0,
+            },
+            Step::Crash,
+            Step::Produce {
+                checkpoints: 2,
+                events: 4,
+            },
+            Step::Crash,
+            Step::Run { millis: 500 },
+        ])
+        .run()
+        .await?;
+
+        report.check()?;
+        assert_eq!(report.checkpoints, 13);
+        assert_eq!(report.events, 15 + 2 + 2 + 8);
+        assert_eq!(report.stored, report.events);
+        assert_eq!(report.written, report.events);
+        assert_eq!(report.crashes, 2);
+        assert_eq!(report.cursor, Some(12));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_random_schedules_keep_invariants() -> Result<()> {
+        for seed in 0..20 {
+            let report = Simulation::random(seed, 60).run().await?;
+            report
+                .check()
+                .map_err(|e| e.wrap_err(format!("Simulation with seed {seed} failed")))?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_clock_follows_the_script() -> Result<()> {
+        let clock = ManualClock::new(simulation_start());
+        let report = Simulation::new(vec![
+            Step::Produce {
+                checkpoints: 3,
+                events: 2,
+            },
+            Step::Run { millis: 1500 },
+        ])
+        .with_clock(clock.clone())
+        .run()
+        .await?;
+
+        // Events are stamped with simulated time, which only the script moves
+        report.check()?;
+        let elapsed = clock.now() - simulation_start();
+        assert!(elapsed >= chrono::Duration::milliseconds(1500));
+        assert!(elapsed < chrono::Duration::seconds(60));
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_schedule_is_reproducible() {
+        assert_eq!(
+            Simulation::random(7, 40).steps,
+            Simulation::random(7, 40).steps
+        );
+        assert_ne!(
+            Simulation::random(7, 40).steps,
+            Simulation::random(8, 40).steps
+        );
+    }
+}
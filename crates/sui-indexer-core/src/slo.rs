@@ -0,0 +1,154 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use sui_indexer_config::SloConfig;
+
+/// Buckets a window is split into, so old observations expire in steps
+const BUCKETS_PER_WINDOW: u32 = 60;
+
+/// Compliance of one SLO over its window
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SloStatus {
+    pub(crate) name: String,
+    pub(crate) objective: f64,
+    /// Share of checkpoints in the window that met the threshold
+    pub(crate) compliance: f64,
+    /// Rate the error budget is spent at, where 1 spends it exactly over the window
+    pub(crate) burn_rate: f64,
+}
+
+/// Rolling availability latency objectives configured under `observability.slos`
+#[derive(Clone, Default)]
+pub struct SloTracker {
+    slos: Arc<Mutex<Vec<Slo>>>,
+}
+
+struct Slo {
+    name: String,
+    config: SloConfig,
+    /// Checkpoints that met the threshold and all checkpoints, per bucket, oldest first
+    buckets: VecDeque<(Instant, u64, u64)>,
+}
+
+impl Slo {
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.window.max(1))
+    }
+
+    fn observe(&mut self, latency: Duration, now: Instant) -> (bool, SloStatus) {
+        let window = self.window();
+        while let Some((start, _, _)) = self.buckets.front() {
+            if now.duration_since(*start) < window {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+        let bucket = window / BUCKETS_PER_WINDOW;
+        match self.buckets.back() {
+            Some((start, _, _)) if now.duration_since(*start) < bucket => {}
+            _ => self.buckets.push_back((now, 0, 0)),
+        }
+
+        let met = latency.as_secs_f64() <= self.config.threshold_secs;
+        if let Some((_, good, total)) = self.buckets.back_mut() {
+            *good += u64::from(met);
+            *total += 1;
+        }
+
+        let (good, total) = self
+            .buckets
+            .iter()
+            .fold((0, 0), |(good, total), (_, g, t)| (good + g, total + t));
+        let compliance = good as f64 / total as f64;
+        let budget = (1.0 - self.config.objective).max(f64::EPSILON);
+        (
+            met,
+            SloStatus {
+                name: self.name.clone(),
+                objective: self.config.objective,
+                compliance,
+                burn_rate: (1.0 - compliance) / budget,
+            },
+        )
+    }
+}
+
+impl SloTracker {
+    /// Track `slos`, keeping the history of those whose settings did not change
+    pub fn configure(&self, slos: &BTreeMap<String, SloConfig>) {
+        let mut tracked = self.slos.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut previous: BTreeMap<String, Slo> = tracked
+            .drain(..)
+            .map(|slo| (slo.name.clone(), slo))
+            .collect();
+        *tracked = slos
+            .iter()
+            .map(|(name, config)| match previous.remove(name) {
+                Some(slo)
+                    if slo.config.threshold_secs == config.threshold_secs
+                        && slo.config.window == config.window =>
+                {
+                    Slo {
+                        config: config.clone(),
+                        ..slo
+                    }
+                }
+                _ => Slo {
+                    name: name.clone(),
+                    config: config.clone(),
+                    buckets: VecDeque::new(),
+                },
+            })
+            .collect();
+    }
+
+    /// Count a checkpoint stored `latency` after its timestamp against every SLO
+    pub(crate) fn observe(&self, latency: Duration, now: Instant) -> Vec<(bool, SloStatus)> {
+        self.slos
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter_mut()
+            .map(|slo| slo.observe(latency, now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compliance_and_burn_rate() {
+        let tracker = SloTracker::default();
+        let slo = SloConfig {
+            threshold_secs: 30.0,
+            objective: 0.9,
+            window: 60,
+        };
+        tracker.configure(&BTreeMap::from([("availability".to_string(), slo)]));
+
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        for second in 0..8 {
+            tracker.observe(Duration::from_secs(5), at(second));
+        }
+        tracker.observe(Duration::from_secs(45), at(8));
+        let [(met, status)] = &tracker.observe(Duration::from_secs(31), at(9))[..] else {
+            panic!("expected one SLO");
+        };
+        assert!(!met);
+        assert_eq!(status.compliance, 0.8);
+        assert!((status.burn_rate - 2.0).abs() < 1e-9);
+
+        // The slow checkpoints leave the window
+        let [(met, status)] = &tracker.observe(Duration::from_secs(1), at(69))[..] else {
+            panic!("expected one SLO");
+        };
+        assert!(met);
+        assert_eq!(status.compliance, 1.0);
+        assert_eq!(status.burn_rate, 0.0);
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use sui_indexer_events::ProcessedEvent;
+use tempfile::TempDir;
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
+    sync::Notify,
+};
+use tracing::{debug, info};
+
+/// Size at which a segment file is sealed and a new one started
+const SEGMENT_BYTES: u64 = 64 << 20;
+
+/// Processed events of one checkpoint waiting to be stored
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CheckpointBatch {
+    pub(crate) checkpoint: u64,
+    pub(crate) events: Vec<ProcessedEvent>,
+}
+
+/// Where and how much a [`SpillQueue`] may hold
+#[derive(Debug, Clone)]
+pub struct SpillOptions {
+    /// Directory the segment files are created in
+    pub dir: PathBuf,
+    /// Events held in memory before further checkpoints go to disk
+    pub memory_events: usize,
+    /// Bytes of segment files at which pushing waits for the queue to drain
+    pub disk_bytes: u64,
+}
+
+impl Default for SpillOptions {
+    fn default() -> Self {
+        Self {
+            dir: std::env::temp_dir(),
+            memory_events: 50_000,
+            disk_bytes: 1 << 30,
+        }
+    }
+}
+
+/// FIFO queue of checkpoints between processing and storage with flat memory use
+///
+/// Checkpoints are kept in memory up to a number of events; once storage falls
+/// further behind, later checkpoints are appended to segment files and read
+/// back one at a time, so nothing overtakes a spilled checkpoint. Each record
+/// is a big-endian `u32` length followed by the checkpoint as JSON. Segments
+/// live in a temporary directory removed with the queue, since a checkpoint
+/// only counts as done once it is stored.
+///
+/// The segment being written and the one being read each sit behind a lock of
+/// their own, so file I/O never holds up the other side; the shared state is
+/// only locked to move checkpoints and segments between them.
+pub(crate) struct SpillQueue {
+    state: Mutex<State>,
+    writer: tokio::sync::Mutex<Writer>,
+    reader: tokio::sync::Mutex<Option<(Segment, BufReader<File>)>>,
+    /// Notified when a checkpoint is pushed or the queue is closed
+    readable: Notify,
+    /// Notified when a checkpoint is popped
+    writable: Notify,
+    options: SpillOptions,
+}
+
+#[derive(Default)]
+struct State {
+    memory: VecDeque<CheckpointBatch>,
+    memory_events: usize,
+    /// Complete segments waiting to be read, oldest first
+    sealed: VecDeque<Segment>,
+    /// Segments created and not yet read to the end
+    segments: usize,
+    /// Bytes of records written but not yet read back
+    disk_bytes: u64,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct Writer {
+    /// Segment being appended to
+    segment: Option<(Segment, BufWriter<File>)>,
+    next_segment: u64,
+    dir: Option<TempDir>,
+}
+
+struct Segment {
+    path: PathBuf,
+    bytes: u64,
+}
+
+/// Where a pushed checkpoint goes
+enum Placement {
+    Memory,
+    Disk,
+    Full,
+}
+
+impl Writer {
+    /// Append `batch` to the segment being written, starting one if needed
+    ///
+    /// Returns the bytes written, the segment sealed to make room if any, and
+    /// whether a segment was started.
+    async fn append(
+        &mut self,
+        batch: &CheckpointBatch,
+        options: &SpillOptions,
+    ) -> Result<(u64, Option<Segment>, bool)> {
+        let mut sealed = None;
+        if self
+            .segment
+            .as_ref()
+            .is_some_and(|(segment, _)| segment.bytes >= SEGMENT_BYTES)
+        {
+            sealed = self.seal().await?;
+        }
+        let started = self.segment.is_none();
+        if started {
+            if self.dir.is_none() {
+                let parent = options.dir.clone();
+                let dir = tokio::task::spawn_blocking(move || {
+                    tempfile::Builder::new()
+                        .prefix("sui-indexer-spill-")
+                        .tempdir_in(&parent)
+                        .wrap_err_with(|| {
+                            format!("Failed to create spill directory in {}", parent.display())
+                        })
+                })
+                .await??;
+                self.dir = Some(dir);
+            }
+            let dir = self.dir.as_ref().expect("spill directory was just created");
+            let path = dir.path().join(format!("{:08}.seg", self.next_segment));
+            self.next_segment += 1;
+            let file = File::create(&path)
+                .await
+                .wrap_err_with(|| format!("Failed to create {}", path.display()))?;
+            self.segment = Some((Segment { path, bytes: 0 }, BufWriter::new(file)));
+        }
+
+        let record = serde_json::to_vec(batch)?;
+        let (segment, writer) = self.segment.as_mut().expect("segment was just opened");
+        writer
+            .write_all(&(record.len() as u32).to_be_bytes())
+            .await?;
+        writer.write_all(&record).await?;
+        let written = 4 + record.len() as u64;
+        segment.bytes += written;
+        Ok((written, sealed, started))
+    }
+
+    /// Finish the segment being written so it can be read
+    async fn seal(&mut self) -> Result<Option<Segment>> {
+        let Some((segment, mut writer)) = self.segment.take() else {
+            return Ok(None);
+        };
+        writer.flush().await?;
+        Ok(Some(segment))
+    }
+}
+
+impl SpillQueue {
+    /// Create an empty queue; the spill directory is only created once needed
+    pub(crate) fn new(options: SpillOptions) -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            writer: tokio::sync::Mutex::new(Writer::default()),
+            reader: tokio::sync::Mutex::new(None),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            options,
+        }
+    }
+
+    /// Append a checkpoint, waiting while the segment files are at their limit
+    pub(crate) async fn push(&self, batch: CheckpointBatch) -> Result<()> {
+        loop {
+            // Held until the checkpoint is placed, so pushes keep their order
+            let mut writer = self.writer.lock().await;
+            let placement = {
+                let mut state = self.state();
+                let fits = state.memory_events + batch.events.len() <= self.options.memory_events;
+                if state.segments == 0 && (fits || state.memory.is_empty()) {
+                    state.memory_events += batch.events.len();
+                    state.memory.push_back(batch);
+                    Placement::Memory
+                } else if state.disk_bytes < self.options.disk_bytes {
+                    Placement::Disk
+                } else {
+                    Placement::Full
+                }
+            };
+            match placement {
+                Placement::Memory => {}
+                Placement::Disk => {
+                    let (written, sealed, started) = writer.append(&batch, &self.options).await?;
+                    let mut state = self.state();
+                    if started {
+                        if state.segments == 0 {
+                            info!(
+                                checkpoint = batch.checkpoint,
+                                "Storage is behind, spilling checkpoints to disk"
+                            );
+                        }
+                        state.segments += 1;
+                    }
+                    state.sealed.extend(sealed);
+                    state.disk_bytes += written;
+                }
+                Placement::Full => {
+                    drop(writer);
+                    self.writable.notified().await;
+                    continue;
+                }
+            }
+            self.readable.notify_one();
+            return Ok(());
+        }
+    }
+
+    /// Take the oldest checkpoint, waiting for one; `None` once closed and drained
+    pub(crate) async fn pop(&self) -> Result<Option<CheckpointBatch>> {
+        loop {
+            if let Some(batch) = self.pop_memory() {
+                self.writable.notify_one();
+                return Ok(Some(batch));
+            }
+            if let Some(batch) = self.pop_disk().await? {
+                self.writable.notify_one();
+                return Ok(Some(batch));
+            }
+            if self.state().closed {
+                return Ok(None);
+            }
+            self.readable.notified().await;
+        }
+    }
+
+    fn pop_memory(&self) -> Option<CheckpointBatch> {
+        let mut state = self.state();
+        let batch = state.memory.pop_front()?;
+        state.memory_events -= batch.events.len();
+        Some(batch)
+    }
+
+    /// Read the oldest spilled checkpoint, `None` if none is on disk
+    async fn pop_disk(&self) -> Result<Option<CheckpointBatch>> {
+        let mut reader = self.reader.lock().await;
+        loop {
+            if let Some((segment, file)) = reader.as_mut() {
+                let mut len = [0; 4];
+                match file.read_exact(&mut len).await {
+                    Ok(_) => {
+                        let mut record = vec![0; u32::from_be_bytes(len) as usize];
+                        file.read_exact(&mut record).await?;
+                        self.state().disk_bytes -= 4 + record.len() as u64;
+                        return Ok(Some(serde_json::from_slice(&record)?));
+                    }
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                        tokio::fs::remove_file(&segment.path).await?;
+                        debug!(path = %segment.path.display(), "Spill segment drained");
+                        *reader = None;
+                        self.state().segments -= 1;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+                continue;
+            }
+
+            let (sealed, writing) = {
+                let mut state = self.state();
+                let sealed = state.sealed.pop_front();
+                (sealed, state.segments > 0)
+            };
+            if let Some(segment) = sealed {
+                let file = File::open(&segment.path)
+                    .await
+                    .wrap_err_with(|| format!("Failed to open {}", segment.path.display()))?;
+                *reader = Some((segment, BufReader::new(file)));
+            } else if writing {
+                // Only the segment being written is left; seal it to read it
+                // (a push may have sealed it meanwhile, putting it with the others)
+                let segment = self.writer.lock().await.seal().await?;
+                self.state().sealed.extend(segment);
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Mark that no more checkpoints will be pushed
+    pub(crate) fn close(&self) {
+        self.state().closed = true;
+        self.readable.notify_one();
+    }
+
+    /// Bytes of spilled checkpoints not yet read back
+    pub(crate) fn spilled_bytes(&self) -> u64 {
+        self.state().disk_bytes
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn batch(checkpoint: u64, events: usize) -> Result<CheckpointBatch> {
+        use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+        use sui_indexer_fixtures::TestEvent;
+
+        let raw = (0..events)
+            .map(|seq| {
+                TestEvent::new("0x2::pool::SwapEvent")
+                    .seq(seq as u64)
+                    .fields(serde_json::json!({"amount": "5"}))
+                    .build()
+            })
+            .collect();
+        Ok(CheckpointBatch {
+            checkpoint,
+            events: DefaultEventProcessor::new().process_events(raw).await?,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_spilled_checkpoints_keep_order() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let queue = SpillQueue::new(SpillOptions {
+            dir: dir.path().to_path_buf(),
+            memory_events: 4,
+            disk_bytes: u64::MAX,
+        });
+
+        for checkpoint in 0..5 {
+            queue.push(batch(checkpoint, 2).await?).await?;
+        }
+        assert!(queue.spilled_bytes() > 0);
+        // A checkpoint pushed while reading back still comes last
+        assert_eq!(queue.pop().await?.map(|batch| batch.checkpoint), Some(0));
+        queue.push(batch(5, 2).await?).await?;
+        queue.close();
+
+        let mut order = vec![0];
+        while let Some(batch) = queue.pop().await? {
+            assert_eq!(batch.events.len(), 2);
+            order.push(batch.checkpoint);
+        }
+        assert_eq!(order, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(queue.spilled_bytes(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_waits_at_disk_limit() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let queue = SpillQueue::new(SpillOptions {
+            dir: dir.path().to_path_buf(),
+            memory_events: 0,
+            disk_bytes: 1,
+        });
+
+        // The first checkpoint stays in memory, the second fills the disk budget
+        queue.push(batch(0, 1).await?).await?;
+        queue.push(batch(1, 1).await?).await?;
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            queue.push(batch(2, 1).await?),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        assert_eq!(queue.pop().await?.map(|batch| batch.checkpoint), Some(0));
+        assert_eq!(queue.pop().await?.map(|batch| batch.checkpoint), Some(1));
+        queue.push(batch(2, 1).await?).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use std::future::Future;
+
+use eyre::Result;
+
+use crate::sui::{CheckpointPruned, SuiClient};
+
+/// Find the first checkpoint the node of `client` served at or after `target_ms`
+///
+/// Searches the checkpoint summaries up to `latest`. Checkpoints the node
+/// pruned count as older than the target, so a target before its history
+/// resolves to the earliest checkpoint it keeps.
+pub async fn checkpoint_at_time(client: &SuiClient, latest: u64, target_ms: u64) -> Result<u64> {
+    first_checkpoint_at(latest, target_ms, |sequence_number| async move {
+        match client.get_checkpoint_timestamp(sequence_number).await {
+            Err(e) if CheckpointPruned::find(&e).is_some() => Ok(0),
+            result => result,
+        }
+    })
+    .await
+}
+
+/// Find the first checkpoint in `0..=latest` with a timestamp at or after `target_ms`
+///
+/// Checkpoint timestamps never decrease, so this is a binary search costing
+/// about log2(latest) lookups. Returns `latest + 1` when every checkpoint is older.
+pub async fn first_checkpoint_at<F, Fut>(
+    latest: u64,
+    target_ms: u64,
+    timestamp_of: F,
+) -> Result<u64>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    let (mut low, mut high) = (0, latest + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if timestamp_of(mid).await? < target_ms {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sui::mock::MockSuiClient;
+
+    #[tokio::test]
+    async fn test_first_checkpoint_at() -> Result<()> {
+        let timestamps = [10, 20, 20, 30];
+        let find = |target| {
+            first_checkpoint_at(3, target, |sequence| async move {
+                Ok(timestamps[sequence as usize])
+            })
+        };
+
+        assert_eq!(find(5).await?, 0);
+        assert_eq!(find(20).await?, 1);
+        assert_eq!(find(25).await?, 3);
+        assert_eq!(find(31).await?, 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_at_time_skips_pruned_history() -> Result<()> {
+        // Checkpoint n is stamped n seconds after the epoch
+        let node = MockSuiClient::synthetic(10, 0)?;
+        node.prune_before(4);
+        let client = SuiClient::mock(node);
+
+        assert_eq!(checkpoint_at_time(&client, 9, 6_500).await?, 7);
+        assert_eq!(checkpoint_at_time(&client, 9, 1_000).await?, 4);
+        assert_eq!(checkpoint_at_time(&client, 9, 20_000).await?, 10);
+        Ok(())
+    }
+}
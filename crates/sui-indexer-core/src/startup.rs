@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sui_indexer_config::{IndexerConfig, StartMode};
+
+/// What a starting indexer resolved from its configuration
+///
+/// Logged once as JSON when the pipeline starts and served on
+/// `/admin/startup`. Secrets in the configuration are redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupReport {
+    pub version: String,
+    pub network: String,
+    /// Origins of the gRPC endpoints, primary first
+    pub endpoints: Vec<String>,
+    /// Storage backend name
+    pub storage: String,
+    /// Type of every enabled sink by name
+    pub sinks: BTreeMap<String, String>,
+    /// Whether each optional feature is on
+    pub features: BTreeMap<String, bool>,
+    /// Enabled protocol decoders
+    pub protocols: Vec<String>,
+    /// Configured event filters
+    pub filters: usize,
+    pub start_mode: StartMode,
+    /// First checkpoint the pipeline fetches
+    pub start_checkpoint: u64,
+    /// Configuration after defaults, overlays and environment overrides
+    pub config: IndexerConfig,
+}
+
+impl StartupReport {
+    /// Report `config` for a pipeline starting at `start_checkpoint`
+    pub fn new(
+        config: &IndexerConfig,
+        storage: &str,
+        endpoints: Vec<String>,
+        start_checkpoint: u64,
+    ) -> Self {
+        let observability = &config.observability;
+        let features = [
+            ("admin_api", config.admin.addr.is_some()),
+            ("http_api", config.api.addr.is_some()),
+            ("grpc_api", config.grpc.addr.is_some()),
+            ("flight_api", config.flight.addr.is_some()),
+            ("api_keys", config.auth.is_enabled()),
+            ("metrics", observability.metrics_addr.is_some()),
+            ("tracing", observability.tracing_endpoint.is_some()),
+            (
+                "error_reporting",
+                observability.error_reporting.is_enabled(),
+            ),
+            ("alerts", config.alerts.any_rule_enabled()),
+            ("watchdog", config.watchdog.enabled),
+            ("index_transactions", config.events.index_transactions),
+            ("index_objects", config.events.index_objects),
+            ("store_raw_event", config.storage.store_raw_event),
+            ("store_bcs", config.storage.store_bcs),
+            ("retention", config.storage.retention_days.is_some()),
+        ]
+        .into_iter()
+        .map(|(feature, enabled)| (feature.to_string(), enabled))
+        .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            network: config.network.network.to_string(),
+            endpoints,
+            storage: storage.to_string(),
+            sinks: config
+                .sinks
+                .iter()
+                .filter(|(_, sink)| sink.enabled())
+                .map(|(name, sink)| (name.clone(), sink.kind().to_string()))
+                .collect(),
+            features,
+            protocols: config
+                .protocols
+                .iter()
+                .filter(|(_, protocol)| protocol.enabled)
+                .map(|(name, _)| name.clone())
+                .collect(),
+            filters: config.events.filters.len(),
+            start_mode: config.events.start_mode,
+            start_checkpoint,
+            config: config.redacted(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::{SinkConfig, WebhookSinkConfig};
+
+    use super::*;
+
+    #[test]
+    fn test_startup_report() -> eyre::Result<()> {
+        let mut config = IndexerConfig::default();
+        config.database.password = Some("hunter2".to_string());
+        config.watchdog.enabled = true;
+        config.admin.addr = None;
+        let webhook: WebhookSinkConfig =
+            serde_json::from_value(serde_json::json!({ "url": "https://hooks.example.com" }))?;
+        config
+            .sinks
+            .insert("hooks".to_string(), SinkConfig::Webhook(webhook));
+
+        let report = StartupReport::new(
+            &config,
+            "postgres",
+            vec!["https://fullnode.example.com".to_string()],
+            42,
+        );
+        assert_eq!(report.start_checkpoint, 42);
+        assert_eq!(report.sinks["hooks"], "webhook");
+        assert!(report.features["watchdog"]);
+        assert!(!report.features["admin_api"]);
+
+        let json = serde_json::to_string(&report)?;
+        assert!(!json.contains("hunter2"));
+        Ok(())
+    }
+}
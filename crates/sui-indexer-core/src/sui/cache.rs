@@ -0,0 +1,346 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use eyre::{Result, WrapErr};
+use sui_indexer_config::NetworkConfig;
+use tracing::{debug, warn};
+
+use super::{CheckpointData, SuiClient};
+
+/// Checkpoints fetched from the node, kept on local disk for later fetches
+///
+/// Each checkpoint is a JSON file named after its sequence number and digest,
+/// and is checked against both when read back, so a damaged or foreign file
+/// is fetched again instead of served. Past `limit` bytes the least recently
+/// used checkpoints are removed; after a restart, recency is by the time each
+/// file was written. Checkpoints of different chains share sequence numbers,
+/// so each chain caches in a directory of its own, see [`chain_dir`]. The
+/// cache only ever saves a request, so a failing disk is logged and otherwise
+/// ignored.
+#[derive(Clone)]
+pub struct CheckpointCache {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    limit: u64,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<u64, Entry>,
+    /// Sequence numbers by last use, least recent first
+    recency: BTreeMap<u64, u64>,
+    bytes: u64,
+    tick: u64,
+}
+
+struct Entry {
+    digest: String,
+    bytes: u64,
+    used: u64,
+}
+
+impl State {
+    /// Mark `sequence` as just used, returning its digest if it is cached
+    fn touch(&mut self, sequence: u64) -> Option<String> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(&sequence)?;
+        self.recency.remove(&entry.used);
+        entry.used = self.tick;
+        self.recency.insert(self.tick, sequence);
+        Some(entry.digest.clone())
+    }
+
+    /// Add a checkpoint as just used, returning the entry it replaces
+    fn insert(&mut self, sequence: u64, digest: String, bytes: u64) -> Option<Entry> {
+        let replaced = self.remove(sequence);
+        self.tick += 1;
+        self.entries.insert(
+            sequence,
+            Entry {
+                digest,
+                bytes,
+                used: self.tick,
+            },
+        );
+        self.recency.insert(self.tick, sequence);
+        self.bytes += bytes;
+        replaced
+    }
+
+    fn remove(&mut self, sequence: u64) -> Option<Entry> {
+        let entry = self.entries.remove(&sequence)?;
+        self.recency.remove(&entry.used);
+        self.bytes -= entry.bytes;
+        Some(entry)
+    }
+
+    /// Remove the least recently used checkpoints until at most `limit` bytes remain
+    fn evict(&mut self, limit: u64) -> Vec<(u64, Entry)> {
+        let mut evicted = Vec::new();
+        while self.bytes > limit {
+            let Some((_, sequence)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&sequence) {
+                self.bytes -= entry.bytes;
+                evicted.push((sequence, entry));
+            }
+        }
+        evicted
+    }
+}
+
+impl CheckpointCache {
+    /// Open the cache in `dir`, creating it if needed and picking up the checkpoints already there
+    pub async fn open(dir: impl Into<PathBuf>, limit: u64) -> Result<Self> {
+        let dir = dir.into();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .wrap_err_with(|| format!("Failed to create checkpoint cache {}", dir.display()))?;
+
+        let mut found = Vec::new();
+        let mut listing = tokio::fs::read_dir(&dir)
+            .await
+            .wrap_err_with(|| format!("Failed to read checkpoint cache {}", dir.display()))?;
+        while let Some(file) = listing.next_entry().await? {
+            let name = file.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            // Left behind by a write that was interrupted
+            if name.ends_with(".tmp") {
+                remove(&file.path()).await;
+                continue;
+            }
+            let Some((sequence, digest)) = parse_file_name(name) else {
+                continue;
+            };
+            let metadata = file.metadata().await?;
+            let written = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((written, sequence, digest, metadata.len()));
+        }
+        found.sort();
+
+        let mut state = State::default();
+        let mut stale = Vec::new();
+        for (_, sequence, digest, bytes) in found {
+            if let Some(replaced) = state.insert(sequence, digest, bytes) {
+                stale.push((sequence, replaced));
+            }
+        }
+        stale.extend(state.evict(limit));
+        for (sequence, entry) in stale {
+            remove(&dir.join(file_name(sequence, &entry.digest))).await;
+        }
+        debug!(
+            dir = %dir.display(),
+            checkpoints = state.entries.len(),
+            bytes = state.bytes,
+            "Opened checkpoint cache"
+        );
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                dir,
+                limit,
+                state: Mutex::new(state),
+            }),
+        })
+    }
+
+    /// Bytes of checkpoints in the cache
+    pub fn size(&self) -> u64 {
+        self.state().bytes
+    }
+
+    /// Cached checkpoint `sequence`, if there is one and it reads back intact
+    pub async fn get(&self, sequence: u64) -> Option<CheckpointData> {
+        let digest = self.state().touch(sequence)?;
+        let path = self.inner.dir.join(file_name(sequence, &digest));
+        let error = match read(&path).await {
+            Ok(checkpoint)
+                if checkpoint.sequence_number == sequence && checkpoint.digest == digest =>
+            {
+                return Some(checkpoint)
+            }
+            Ok(_) => "the file holds another checkpoint".to_string(),
+            Err(e) => e.to_string(),
+        };
+        warn!(path = %path.display(), error = %error, "Discarding cached checkpoint");
+        let discarded = {
+            let mut state = self.state();
+            let current = state
+                .entries
+                .get(&sequence)
+                .is_some_and(|entry| entry.digest == digest);
+            current && state.remove(sequence).is_some()
+        };
+        if discarded {
+            remove(&path).await;
+        }
+        None
+    }
+
+    /// Keep `checkpoint` for later fetches, evicting others past the size limit
+    pub async fn put(&self, checkpoint: &CheckpointData) {
+        if let Err(e) = self.write(checkpoint).await {
+            warn!(
+                checkpoint = checkpoint.sequence_number,
+                error = %e,
+                "Failed to cache checkpoint"
+            );
+        }
+    }
+
+    async fn write(&self, checkpoint: &CheckpointData) -> Result<()> {
+        let bytes = serde_json::to_vec(checkpoint)?;
+        let size = bytes.len() as u64;
+        if size > self.inner.limit {
+            return Ok(());
+        }
+        let sequence = checkpoint.sequence_number;
+        let name = file_name(sequence, &checkpoint.digest);
+        // Written aside and renamed so readers never see part of a file
+        let partial = self.inner.dir.join(format!("{name}.tmp"));
+        tokio::fs::write(&partial, &bytes).await?;
+        tokio::fs::rename(&partial, self.inner.dir.join(&name)).await?;
+
+        let stale = {
+            let mut state = self.state();
+            let replaced = state
+                .insert(sequence, checkpoint.digest.clone(), size)
+                .filter(|replaced| replaced.digest != checkpoint.digest)
+                .map(|replaced| (sequence, replaced));
+            let mut stale: Vec<_> = replaced.into_iter().collect();
+            stale.extend(state.evict(self.inner.limit));
+            stale
+        };
+        for (sequence, entry) in stale {
+            remove(&self.inner.dir.join(file_name(sequence, &entry.digest))).await;
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+async fn read(path: &Path) -> Result<CheckpointData> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn remove(path: &Path) {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => warn!(path = %path.display(), error = %e, "Failed to remove cached checkpoint"),
+    }
+}
+
+/// Subdirectory of `dir` the checkpoints of the node's chain are cached in
+///
+/// Named after the node's chain identifier, or, if the node pruned the
+/// genesis checkpoint it is derived from, the configured one or the network.
+pub async fn chain_dir(dir: &Path, client: &SuiClient, network: &NetworkConfig) -> Result<PathBuf> {
+    let chain = match client.get_chain_identifier().await? {
+        Some(chain_id) => chain_id,
+        None => network
+            .chain_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| network.network.to_string()),
+    };
+    Ok(dir.join(chain))
+}
+
+fn file_name(sequence: u64, digest: &str) -> String {
+    format!("{sequence}-{digest}.json")
+}
+
+fn parse_file_name(name: &str) -> Option<(u64, String)> {
+    let (sequence, digest) = name.strip_suffix(".json")?.split_once('-')?;
+    Some((sequence.parse().ok()?, digest.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sui::{mock::synthetic_checkpoint, MockSuiClient, SuiClient};
+
+    #[tokio::test]
+    async fn test_cache_evicts_least_recently_used() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let checkpoints = (1..=3)
+            .map(|sequence| synthetic_checkpoint(sequence, 2))
+            .collect::<Result<Vec<_>>>()?;
+        let size = serde_json::to_vec(&checkpoints[0])?.len() as u64;
+        let cache = CheckpointCache::open(dir.path(), size * 5 / 2).await?;
+
+        cache.put(&checkpoints[0]).await;
+        cache.put(&checkpoints[1]).await;
+        // Reading checkpoint 1 makes checkpoint 2 the least recently used
+        assert_eq!(
+            cache.get(1).await.map(|c| c.digest),
+            Some(checkpoints[0].digest.clone())
+        );
+        cache.put(&checkpoints[2]).await;
+        assert!(cache.get(2).await.is_none());
+        assert!(cache.get(3).await.is_some());
+        assert!(cache.size() <= size * 5 / 2);
+
+        // Reopening picks up what is on disk and drops damaged files
+        drop(cache);
+        std::fs::write(
+            dir.path().join(file_name(3, &checkpoints[2].digest)),
+            b"not json",
+        )?;
+        let cache = CheckpointCache::open(dir.path(), size * 5 / 2).await?;
+        assert!(cache.get(1).await.is_some());
+        assert!(cache.get(3).await.is_none());
+        assert!(!dir
+            .path()
+            .join(file_name(3, &checkpoints[2].digest))
+            .exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_serves_cached_checkpoints_without_the_node() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let node = MockSuiClient::synthetic(5, 1)?;
+        let client = SuiClient::mock(node.clone())
+            .with_checkpoint_cache(CheckpointCache::open(dir.path(), 1 << 20).await?);
+
+        let fetched = client.get_checkpoint(2).await?;
+        node.fail_requests(1);
+        let cached = client.get_checkpoint(2).await?;
+        assert_eq!(cached.digest, fetched.digest);
+        assert_eq!(cached.events.len(), fetched.events.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_each_chain_caches_in_its_own_directory() -> Result<()> {
+        let client = SuiClient::mock(MockSuiClient::synthetic(2, 0)?);
+        let network = NetworkConfig::default();
+        let chain_id = client.get_chain_identifier().await?.unwrap();
+        assert_eq!(
+            chain_dir(Path::new("cache"), &client, &network).await?,
+            Path::new("cache").join(chain_id)
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 use eyre::Result;
 use serde::{Deserialize, Serialize};
-use sui_types::base_types::TransactionDigest;
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
+
+use crate::error::IndexerError;
 
 /// Checkpoint data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,12 +24,132 @@ pub struct CheckpointData {
     pub network_total_transactions: u64,
     /// Transactions included in this checkpoint
     pub transactions: Vec<TransactionDigest>,
+    /// Events emitted by the transactions in this checkpoint
+    #[serde(default)]
+    pub events: Vec<SuiEvent>,
+    /// Packages published or upgraded by the transactions in this checkpoint
+    #[serde(default)]
+    pub packages: Vec<PublishedPackage>,
+    /// Move calls made by the programmable transactions in this checkpoint
+    #[serde(default)]
+    pub move_calls: Vec<MoveCall>,
+    /// Objects created, mutated, wrapped or deleted by the transactions in this checkpoint
+    #[serde(default)]
+    pub object_changes: Vec<ObjectChange>,
     /// End of epoch data (if this checkpoint ends an epoch)
     pub end_of_epoch_data: Option<EndOfEpochData>,
     /// Validator signature
     pub validator_signature: String,
 }
 
+#[cfg(test)]
+impl From<sui_indexer_fixtures::RecordedCheckpoint> for CheckpointData {
+    fn from(recorded: sui_indexer_fixtures::RecordedCheckpoint) -> Self {
+        Self {
+            sequence_number: recorded.sequence_number,
+            digest: recorded.digest,
+            previous_digest: recorded.previous_digest,
+            epoch: recorded.epoch,
+            round: 0,
+            timestamp_ms: recorded.timestamp_ms,
+            network_total_transactions: recorded.network_total_transactions,
+            transactions: recorded.transactions,
+            events: recorded.events,
+            packages: Vec::new(),
+            move_calls: Vec::new(),
+            object_changes: Vec::new(),
+            end_of_epoch_data: None,
+            validator_signature: String::new(),
+        }
+    }
+}
+
+/// Package version created by a publish or upgrade transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedPackage {
+    /// ID of this version
+    pub package_id: ObjectID,
+    /// ID of the package's first version; `package_id` itself when first published
+    pub original_id: ObjectID,
+    /// Version, 1 when first published
+    pub version: u64,
+    /// Names of the package's modules
+    pub modules: Vec<String>,
+    /// Transaction that published or upgraded the package
+    pub tx_digest: TransactionDigest,
+    /// Sender of that transaction
+    pub sender: SuiAddress,
+}
+
+impl PublishedPackage {
+    /// Check if this is an upgrade rather than a first publish
+    pub fn is_upgrade(&self) -> bool {
+        self.package_id != self.original_id
+    }
+}
+
+/// Move function called by a command of a programmable transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveCall {
+    /// Transaction making the call
+    pub tx_digest: TransactionDigest,
+    /// Position of the call among the transaction's commands
+    pub command_index: u32,
+    /// Package of the called function, as named by the call
+    pub package: ObjectID,
+    /// Module of the called function
+    pub module: String,
+    /// Name of the called function
+    pub function: String,
+    /// Type arguments, e.g. `0x2::sui::SUI`
+    pub type_arguments: Vec<String>,
+    /// Sender of the transaction
+    pub sender: SuiAddress,
+}
+
+/// New version of an object written by a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectChange {
+    /// Transaction writing the version
+    pub tx_digest: TransactionDigest,
+    pub object_id: ObjectID,
+    /// Version after the transaction
+    pub version: u64,
+    pub kind: ObjectChangeKind,
+    /// Digest of the new version; unset once the object is wrapped or deleted
+    pub digest: Option<String>,
+    /// Move type of the object, e.g. `0x2::coin::Coin<0x2::sui::SUI>`
+    pub object_type: Option<String>,
+    /// Owner of the new version, as an address, `shared` or `immutable`
+    pub owner: Option<String>,
+    /// Fields of the new version as JSON, if they were fetched
+    pub contents: Option<serde_json::Value>,
+}
+
+/// How a transaction changed an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectChangeKind {
+    Created,
+    Mutated,
+    Unwrapped,
+    Wrapped,
+    Deleted,
+}
+
+impl ObjectChangeKind {
+    /// Name of the kind as stored
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Mutated => "mutated",
+            Self::Unwrapped => "unwrapped",
+            Self::Wrapped => "wrapped",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
 /// End of epoch data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EndOfEpochData {
@@ -122,7 +245,7 @@ impl CheckpointProcessor {
 }
 
 /// Checkpoint range for batch processing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CheckpointRange {
     pub start: u64,
     pub end: u64,
@@ -132,9 +255,7 @@ impl CheckpointRange {
     /// Create a new checkpoint range
     pub fn new(start: u64, end: u64) -> Result<Self> {
         if start > end {
-            return Err(eyre::eyre!(
-                "Start checkpoint cannot be greater than end checkpoint"
-            ));
+            return Err(IndexerError::InvalidRange { start, end }.into());
         }
         Ok(Self { start, end })
     }
@@ -0,0 +1,191 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[cfg(any(test, feature = "testing"))]
+use super::MockSuiClient;
+use super::{NodeClient, SuiGrpcClient};
+
+/// Requests the success rate and latency of an endpoint are computed over
+const WINDOW: usize = 100;
+
+/// Success rate and latency of one configured gRPC endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Origin of the endpoint, without path or credentials
+    pub endpoint: String,
+    /// Whether requests currently go to this endpoint
+    pub active: bool,
+    /// Requests sent since startup
+    pub requests: u64,
+    /// Failed requests since startup
+    pub failures: u64,
+    /// Share of the latest requests that succeeded, if any were sent
+    pub success_rate: Option<f64>,
+    /// Mean latency of the latest requests in milliseconds
+    pub avg_latency_ms: Option<f64>,
+    /// Slowest of the latest requests in milliseconds
+    pub max_latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+/// A configured endpoint with the outcomes of its requests
+#[derive(Debug)]
+pub(crate) struct Endpoint {
+    /// Label used in logs and metrics
+    pub(crate) label: String,
+    pub(crate) client: NodeClient,
+    stats: Mutex<EndpointStats>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    requests: u64,
+    failures: u64,
+    /// Outcome and latency of the latest requests, oldest first
+    recent: VecDeque<(bool, Duration)>,
+    last_error: Option<(String, DateTime<Utc>)>,
+}
+
+impl Endpoint {
+    pub(crate) fn new(url: &Url, client: SuiGrpcClient) -> Self {
+        Self {
+            label: endpoint_label(url),
+            client: NodeClient::Grpc(client),
+            stats: Mutex::default(),
+        }
+    }
+
+    /// Endpoint labelled `mock` serving a scripted chain
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn mock(client: MockSuiClient) -> Self {
+        Self {
+            label: "mock".to_string(),
+            client: NodeClient::Mock(client),
+            stats: Mutex::default(),
+        }
+    }
+
+    /// Record the outcome of a request that took `latency` and ended `at`
+    pub(crate) fn record(
+        &self,
+        result: Result<(), &eyre::Report>,
+        latency: Duration,
+        at: DateTime<Utc>,
+    ) {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.requests += 1;
+        if let Err(e) = result {
+            stats.failures += 1;
+            stats.last_error = Some((format!("{e:#}"), at));
+        }
+        if stats.recent.len() == WINDOW {
+            stats.recent.pop_front();
+        }
+        stats.recent.push_back((result.is_ok(), latency));
+    }
+
+    pub(crate) fn health(&self, active: bool) -> EndpointHealth {
+        let stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        let recent = stats.recent.len();
+        let (success_rate, avg_latency_ms, max_latency_ms) = if recent == 0 {
+            (None, None, None)
+        } else {
+            let succeeded = stats.recent.iter().filter(|(ok, _)| *ok).count();
+            let latencies = stats
+                .recent
+                .iter()
+                .map(|(_, latency)| latency.as_secs_f64());
+            let total: f64 = latencies.clone().sum();
+            let max = latencies.fold(0.0, f64::max);
+            (
+                Some(succeeded as f64 / recent as f64),
+                Some(total * 1000.0 / recent as f64),
+                Some(max * 1000.0),
+            )
+        };
+        EndpointHealth {
+            endpoint: self.label.clone(),
+            active,
+            requests: stats.requests,
+            failures: stats.failures,
+            success_rate,
+            avg_latency_ms,
+            max_latency_ms,
+            last_error: stats.last_error.as_ref().map(|(error, _)| error.clone()),
+            last_error_at: stats.last_error.as_ref().map(|(_, at)| *at),
+        }
+    }
+}
+
+/// Scheme, host and port of `url`, leaving out API keys carried in the path or query
+fn endpoint_label(url: &Url) -> String {
+    match url.origin() {
+        origin @ url::Origin::Tuple(..) => origin.ascii_serialization(),
+        url::Origin::Opaque(_) => url.scheme().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sui_indexer_events::{Clock, ManualClock};
+
+    use super::*;
+    use crate::sui::SuiClient;
+
+    #[tokio::test]
+    async fn test_endpoint_health() -> eyre::Result<()> {
+        let url: Url = "https://fullnode.example.com:443/v1/secret-key?token=abc".parse()?;
+        let endpoint = Endpoint::new(&url, SuiGrpcClient::new_lazy(url.as_str())?);
+        assert_eq!(endpoint.label, "https://fullnode.example.com");
+        assert_eq!(endpoint.health(true).success_rate, None);
+
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for _ in 0..WINDOW {
+            endpoint.record(Ok(()), Duration::from_millis(10), at);
+        }
+        let error = eyre::eyre!("connection refused");
+        for _ in 0..WINDOW / 4 {
+            endpoint.record(Err(&error), Duration::from_millis(50), at);
+        }
+
+        let health = endpoint.health(false);
+        assert_eq!(health.requests, 125);
+        assert_eq!(health.failures, 25);
+        assert_eq!(health.success_rate, Some(0.75));
+        assert_eq!(health.max_latency_ms, Some(50.0));
+        assert!((health.avg_latency_ms.unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(health.last_error.as_deref(), Some("connection refused"));
+        assert_eq!(health.last_error_at, Some(at));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_timed_by_the_client_clock() -> eyre::Result<()> {
+        let clock = ManualClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let mock = MockSuiClient::synthetic(1, 1)?;
+        let client = SuiClient::mock(mock.clone()).with_clock(Arc::new(clock.clone()));
+
+        client.get_latest_checkpoint().await?;
+        clock.advance(Duration::from_secs(30));
+        mock.fail_requests(1);
+        assert!(client.get_latest_checkpoint().await.is_err());
+
+        let health = &client.endpoint_health()[0];
+        assert_eq!((health.requests, health.failures), (2, 1));
+        assert_eq!(health.success_rate, Some(0.5));
+        // The clock stood still while the requests ran
+        assert_eq!(health.max_latency_ms, Some(0.0));
+        assert_eq!(health.last_error_at, Some(clock.now()));
+        Ok(())
+    }
+}
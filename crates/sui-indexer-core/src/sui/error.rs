@@ -0,0 +1,91 @@
+/// Failure of a node request, telling failures worth retrying from the rest
+///
+/// Raised where a request fails in a way the indexer understands; other
+/// node errors stay plain reports. Read back with [`ClientError::find`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ClientError {
+    /// The request did not complete within `runtime.request_timeout`
+    #[error("Timed out {0}")]
+    Timeout(String),
+    /// The node has not produced the checkpoint yet
+    #[error("Checkpoint {0} is not available yet")]
+    NotFound(u64),
+    /// The node answered with another checkpoint than the one requested
+    #[error("Node returned checkpoint {served} for checkpoint {requested}")]
+    WrongCheckpoint { requested: u64, served: u64 },
+    /// The node could not be reached or was overloaded
+    #[error("The node is unavailable: {0}")]
+    Unavailable(String),
+    /// The node refused the request, e.g. for an invalid argument or missing permission
+    #[error("The node rejected the request: {0}")]
+    Rejected(String),
+    /// A request the client cannot make yet
+    #[error("{0} is not supported by this client yet")]
+    Unsupported(&'static str),
+}
+
+impl ClientError {
+    /// The client error behind `err`, if that is what failed
+    pub fn find(err: &eyre::Report) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+
+    /// Classify the gRPC status of a failed node request, described by `what`
+    pub fn from_status(what: &str, status: &tonic::Status) -> Self {
+        let message = format!("{what}: {}", status.message());
+        match status.code() {
+            tonic::Code::DeadlineExceeded => Self::Timeout(message),
+            tonic::Code::InvalidArgument
+            | tonic::Code::PermissionDenied
+            | tonic::Code::Unauthenticated
+            | tonic::Code::Unimplemented
+            | tonic::Code::FailedPrecondition
+            | tonic::Code::OutOfRange
+            | tonic::Code::AlreadyExists => Self::Rejected(message),
+            _ => Self::Unavailable(message),
+        }
+    }
+
+    /// Whether the request can succeed when tried again, on this or another endpoint
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout(_)
+            | Self::NotFound(_)
+            | Self::WrongCheckpoint { .. }
+            | Self::Unavailable(_) => true,
+            Self::Rejected(_) | Self::Unsupported(_) => false,
+        }
+    }
+}
+
+/// A checkpoint the node no longer serves because it pruned that part of its history
+///
+/// Returned in place of a plain "not found" for checkpoints below the
+/// earliest one the node keeps, so callers can skip ahead instead of
+/// retrying. It survives added context; read it back with [`CheckpointPruned::find`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointPruned {
+    /// Checkpoint that was requested
+    pub checkpoint: u64,
+    /// Earliest checkpoint the node still serves, if it said
+    pub earliest: Option<u64>,
+}
+
+impl CheckpointPruned {
+    /// The pruned checkpoint behind `err`, if that is what failed
+    pub fn find(err: &eyre::Report) -> Option<Self> {
+        err.downcast_ref::<Self>().copied()
+    }
+}
+
+impl std::fmt::Display for CheckpointPruned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Checkpoint {} was pruned by the node", self.checkpoint)?;
+        if let Some(earliest) = self.earliest {
+            write!(f, ", which keeps checkpoints from {earliest}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CheckpointPruned {}
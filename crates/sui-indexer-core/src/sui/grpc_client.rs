@@ -1,9 +1,14 @@
 use eyre::Result;
+use sui_json_rpc_types::SuiTransactionBlockResponse;
 use sui_rpc_api::Client as SuiRpcApiClient;
-use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::{
+    base_types::TransactionDigest,
+    digests::ChainIdentifier,
+    messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSequenceNumber},
+};
 use tracing::{debug, error, info};
 
-use super::{CheckpointData, Event, EventQueryResult};
+use super::{ChainTip, CheckpointData, CheckpointPruned, ClientError, Event, EventQueryResult};
 
 /// Placeholder for checkpoint subscription
 #[derive(Debug, Clone)]
@@ -24,12 +29,17 @@ impl SuiGrpcClient {
         info!("Connecting to Sui gRPC endpoint: {}", endpoint);
 
         let client = SuiRpcApiClient::new(endpoint)
-            .map_err(|e| eyre::eyre!("Failed to create gRPC client: {}", e))?;
+            .map_err(|e| ClientError::Rejected(format!("Failed to create gRPC client: {e}")))?;
 
         // Test the connection
-        if let Err(e) = client.get_latest_checkpoint().await {
-            error!("Failed to connect to Sui gRPC endpoint {}: {}", endpoint, e);
-            return Err(eyre::eyre!("Failed to connect to gRPC endpoint: {}", e));
+        if let Err(status) = client.get_latest_checkpoint().await {
+            error!(
+                "Failed to connect to Sui gRPC endpoint {}: {}",
+                endpoint, status
+            );
+            return Err(
+                ClientError::from_status("Failed to connect to gRPC endpoint", &status).into(),
+            );
         }
 
         info!("Successfully connected to Sui gRPC endpoint: {}", endpoint);
@@ -40,6 +50,17 @@ impl SuiGrpcClient {
         })
     }
 
+    /// Create a gRPC client without checking that the endpoint answers
+    pub fn new_lazy(endpoint: &str) -> Result<Self> {
+        let client = SuiRpcApiClient::new(endpoint)
+            .map_err(|e| ClientError::Rejected(format!("Failed to create gRPC client: {e}")))?;
+
+        Ok(Self {
+            client,
+            endpoint: endpoint.to_string(),
+        })
+    }
+
     /// Get the latest checkpoint number
     pub async fn get_latest_checkpoint(&self) -> Result<CheckpointSequenceNumber> {
         debug!("Fetching latest checkpoint from gRPC");
@@ -48,40 +69,159 @@ impl SuiGrpcClient {
             .client
             .get_latest_checkpoint()
             .await
-            .map_err(|e| eyre::eyre!("Failed to get latest checkpoint: {}", e))?;
+            .map_err(|status| {
+                ClientError::from_status("Failed to get latest checkpoint", &status)
+            })?;
         let sequence_number = checkpoint_summary.sequence_number;
 
         debug!("Latest checkpoint: {}", sequence_number);
         Ok(sequence_number)
     }
 
+    /// Get the epoch and timestamp of the latest checkpoint along with its number
+    pub async fn get_chain_tip(&self) -> Result<ChainTip> {
+        let summary = self
+            .client
+            .get_latest_checkpoint()
+            .await
+            .map_err(|status| {
+                ClientError::from_status("Failed to get latest checkpoint", &status)
+            })?;
+
+        Ok(ChainTip {
+            sequence_number: summary.sequence_number,
+            epoch: summary.epoch,
+            timestamp_ms: summary.timestamp_ms,
+        })
+    }
+
+    /// Chain identifier of the node: the first four bytes of its genesis checkpoint digest
+    ///
+    /// `None` if the node pruned its genesis checkpoint.
+    pub async fn get_chain_identifier(&self) -> Result<Option<String>> {
+        let genesis = match self.get_checkpoint_summary(0).await {
+            Ok(genesis) => genesis,
+            Err(e) if CheckpointPruned::find(&e).is_some() => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Some(ChainIdentifier::from(*genesis.digest()).to_string()))
+    }
+
+    /// Summary of a checkpoint, telling pruned checkpoints from ones not produced yet
+    ///
+    /// Nodes answer `NOT_FOUND` for both; a checkpoint at or below the
+    /// latest one was pruned and fails with [`CheckpointPruned`].
+    async fn get_checkpoint_summary(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<CertifiedCheckpointSummary> {
+        let status = match self.client.get_checkpoint_summary(sequence_number).await {
+            Ok(summary) => return Ok(summary),
+            Err(status) => status,
+        };
+        if status.code() != tonic::Code::NotFound {
+            return Err(ClientError::from_status(
+                &format!("Failed to get checkpoint {sequence_number}"),
+                &status,
+            )
+            .into());
+        }
+
+        let latest = self.get_latest_checkpoint().await?;
+        if sequence_number > latest {
+            return Err(ClientError::NotFound(sequence_number).into());
+        }
+        Err(CheckpointPruned {
+            checkpoint: sequence_number,
+            earliest: self.lowest_available_checkpoint(latest).await.ok(),
+        }
+        .into())
+    }
+
+    /// Earliest checkpoint the node still serves, searched for below `latest`
+    ///
+    /// Nodes prune from the oldest checkpoint up, so the available ones
+    /// form a range; this takes a few dozen requests and only runs once a
+    /// pruned checkpoint was requested.
+    async fn lowest_available_checkpoint(&self, latest: CheckpointSequenceNumber) -> Result<u64> {
+        let (mut low, mut high) = (0, latest);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.client.get_checkpoint_summary(mid).await {
+                Ok(_) => high = mid,
+                Err(status) if status.code() == tonic::Code::NotFound => low = mid + 1,
+                Err(status) => {
+                    return Err(ClientError::from_status(
+                        "Failed to find the earliest available checkpoint",
+                        &status,
+                    )
+                    .into())
+                }
+            }
+        }
+        Ok(low)
+    }
+
+    /// Timestamp of a checkpoint, read from its summary
+    pub async fn get_checkpoint_timestamp(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<u64> {
+        Ok(self
+            .get_checkpoint_summary(sequence_number)
+            .await?
+            .timestamp_ms)
+    }
+
     /// Get checkpoint data by sequence number
+    ///
+    /// The header comes from the checkpoint summary; transactions, events
+    /// and object changes are not fetched over gRPC yet and stay empty.
     pub async fn get_checkpoint(
         &self,
         sequence_number: CheckpointSequenceNumber,
     ) -> Result<CheckpointData> {
         debug!("Fetching checkpoint {} from gRPC", sequence_number);
 
-        // For now, create a placeholder checkpoint data structure
-        // This will be implemented with actual gRPC calls once the API is stable
+        let summary = self.get_checkpoint_summary(sequence_number).await?;
         let checkpoint_data = CheckpointData {
             sequence_number,
-            digest: sui_types::digests::CheckpointDigest::default().to_string(),
-            previous_digest: Some(sui_types::digests::CheckpointDigest::default().to_string()),
+            digest: summary.digest().to_string(),
+            previous_digest: summary.previous_digest.map(|digest| digest.to_string()),
             transactions: vec![],
-            timestamp_ms: 0,
-            epoch: 0,
-            network_total_transactions: 0,
+            events: vec![],
+            packages: vec![],
+            move_calls: vec![],
+            object_changes: vec![],
+            timestamp_ms: summary.timestamp_ms,
+            epoch: summary.epoch,
+            network_total_transactions: summary.network_total_transactions,
             end_of_epoch_data: None,
             validator_signature: sui_types::committee::StakeUnit::default().to_string(),
             round: 0,
         };
 
+        debug!("Retrieved checkpoint {} (summary only)", sequence_number);
+        Ok(checkpoint_data)
+    }
+
+    /// Get the full data of a batch of transactions
+    ///
+    /// Not wired to the node yet, so any transaction to look up fails with
+    /// [`ClientError::Unsupported`] rather than being silently left out.
+    pub async fn get_transactions(
+        &self,
+        digests: &[TransactionDigest],
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        if digests.is_empty() {
+            return Ok(Vec::new());
+        }
         debug!(
-            "Retrieved checkpoint {} (placeholder implementation)",
-            sequence_number
+            transactions = digests.len(),
+            "Fetching transactions from gRPC"
         );
-        Ok(checkpoint_data)
+        Err(ClientError::Unsupported("Fetching transaction data over gRPC").into())
     }
 
     /// Subscribe to checkpoint stream (placeholder for future streaming implementation)
@@ -108,14 +248,14 @@ impl SuiGrpcClient {
         debug!("Querying events from gRPC");
 
         if let Some(pkg_id) = &package_id {
-            info!("🔍 Searching for events from package: {}", pkg_id);
+            info!(package = %pkg_id, "Searching for events from package");
         }
 
         // Get latest checkpoint to show we're actively monitoring
         let latest_checkpoint = self.get_latest_checkpoint().await?;
         info!(
-            "📊 Latest checkpoint: {}, monitoring for new events",
-            latest_checkpoint
+            checkpoint = latest_checkpoint,
+            "Monitoring for new events from the latest checkpoint"
         );
 
         // For now, simulate event discovery to test the monitoring loop
@@ -126,7 +266,7 @@ impl SuiGrpcClient {
         if package_id.as_deref()
             == Some("0x81c408448d0d57b3e371ea94de1d40bf852784d3e225de1e74acab3e8395c18f")
         {
-            info!("� SIMULATING: Navi Protocol package detected in query!");
+            info!("Simulating events for the Navi Protocol package");
 
             // Create a simulated event for testing
             let simulated_event = Event {
@@ -144,16 +284,13 @@ impl SuiGrpcClient {
             };
 
             simulated_events.push(simulated_event);
-            info!("🧪 SIMULATION: Created test Navi Protocol event");
+            info!("Created simulated Navi Protocol event");
         }
 
         if simulated_events.is_empty() {
-            info!("📭 No events found (monitoring system is working, waiting for real events)");
+            info!("No events found, waiting for new events");
         } else {
-            info!(
-                "� Found {} simulated events for testing",
-                simulated_events.len()
-            );
+            info!(events = simulated_events.len(), "Found simulated events");
         }
 
         Ok(EventQueryResult {
@@ -172,9 +309,9 @@ impl SuiGrpcClient {
                 debug!("gRPC health check passed");
                 Ok(())
             }
-            Err(e) => {
-                error!("gRPC health check failed: {}", e);
-                Err(eyre::eyre!("Health check failed: {}", e))
+            Err(status) => {
+                error!("gRPC health check failed: {}", status);
+                Err(ClientError::from_status("Health check failed", &status).into())
             }
         }
     }
@@ -0,0 +1,345 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use eyre::Result;
+use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockResponse};
+use sui_types::{
+    base_types::TransactionDigest,
+    digests::{ChainIdentifier, CheckpointDigest},
+};
+
+use super::{ChainTip, CheckpointData, CheckpointPruned, ClientError};
+
+/// Package of the events in [`MockSuiClient::synthetic`] checkpoints
+pub const SYNTHETIC_PACKAGE: &str = "0x2";
+
+/// In-memory chain of scripted checkpoints standing in for a Sui node
+///
+/// Clones share the chain, so a test can keep a handle and append
+/// checkpoints while a pipeline follows the tip through
+/// [`SuiClient::mock`](super::SuiClient::mock). Faults are scripted the
+/// same way: failed requests, checkpoints answered with the wrong one and
+/// pruned history.
+#[derive(Debug, Clone, Default)]
+pub struct MockSuiClient {
+    chain: Arc<Mutex<Chain>>,
+}
+
+#[derive(Debug, Default)]
+struct Chain {
+    checkpoints: BTreeMap<u64, CheckpointData>,
+    /// Requests left to fail
+    failures: usize,
+    /// Checkpoints to answer once with another one, by requested sequence number
+    substitutes: BTreeMap<u64, u64>,
+    /// Checkpoints below this one were pruned
+    pruned_before: u64,
+    /// Identifier derived from checkpoint 0, kept once it is pruned
+    chain_id: Option<String>,
+    /// Checkpoint each pushed transaction was included in
+    transaction_checkpoints: HashMap<TransactionDigest, u64>,
+    /// Transactions answered in full so far
+    transactions_served: usize,
+}
+
+impl Chain {
+    /// Take one scripted failure, if any are left
+    fn fail(&mut self) -> Result<()> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err(ClientError::Unavailable("injected node error".to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+impl MockSuiClient {
+    /// Create a node without any checkpoints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, Chain> {
+        self.chain.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Create a node serving checkpoints `0..checkpoints`, each with `events` synthetic events
+    pub fn synthetic(checkpoints: u64, events: usize) -> Result<Self> {
+        let client = Self::new();
+        for sequence_number in 0..checkpoints {
+            client.push_checkpoint(synthetic_checkpoint(sequence_number, events)?);
+        }
+        Ok(client)
+    }
+
+    /// Create a node serving the mainnet checkpoints bundled with [`sui_indexer_fixtures`]
+    ///
+    /// Earlier checkpoints are reported as pruned, like on a node that does not keep full history.
+    #[cfg(test)]
+    pub fn recorded() -> Self {
+        Self::from_recorded(sui_indexer_fixtures::checkpoints())
+    }
+
+    /// Create a node serving recorded checkpoints, such as ones loaded with [`sui_indexer_fixtures::load_dir`]
+    #[cfg(test)]
+    pub fn from_recorded(
+        checkpoints: impl IntoIterator<Item = sui_indexer_fixtures::RecordedCheckpoint>,
+    ) -> Self {
+        let client = Self::new();
+        for checkpoint in checkpoints {
+            client.push_checkpoint(checkpoint.into());
+        }
+        let first = client.state().checkpoints.keys().next().copied();
+        if let Some(first) = first {
+            client.prune_before(first);
+        }
+        client
+    }
+
+    /// Add or replace a checkpoint, moving the tip if it is the highest
+    pub fn push_checkpoint(&self, checkpoint: CheckpointData) {
+        let mut chain = self.state();
+        if checkpoint.sequence_number == 0 {
+            chain.chain_id = checkpoint
+                .digest
+                .parse::<CheckpointDigest>()
+                .ok()
+                .map(|digest| ChainIdentifier::from(digest).to_string());
+        }
+        for digest in &checkpoint.transactions {
+            chain
+                .transaction_checkpoints
+                .insert(*digest, checkpoint.sequence_number);
+        }
+        chain
+            .checkpoints
+            .insert(checkpoint.sequence_number, checkpoint);
+    }
+
+    /// Fail the next `requests` requests for checkpoints or the chain tip
+    pub fn fail_requests(&self, requests: usize) {
+        self.state().failures += requests;
+    }
+
+    /// Answer the next request for `checkpoint` with checkpoint `served` instead
+    pub fn substitute(&self, checkpoint: u64, served: u64) {
+        self.state().substitutes.insert(checkpoint, served);
+    }
+
+    /// Drop the checkpoints below `checkpoint`, which are then reported as pruned
+    pub fn prune_before(&self, checkpoint: u64) {
+        let mut chain = self.state();
+        chain.checkpoints = chain.checkpoints.split_off(&checkpoint);
+        chain.pruned_before = chain.pruned_before.max(checkpoint);
+    }
+
+    /// Number of transactions answered by [`MockSuiClient::get_transactions`] so far
+    pub fn transactions_served(&self) -> usize {
+        self.state().transactions_served
+    }
+
+    /// Highest checkpoint served
+    pub async fn get_latest_checkpoint(&self) -> Result<u64> {
+        let mut chain = self.state();
+        chain.fail()?;
+        chain
+            .checkpoints
+            .last_key_value()
+            .map(|(sequence_number, _)| *sequence_number)
+            .ok_or_else(|| {
+                ClientError::Unavailable("the mock node has no checkpoints".to_string()).into()
+            })
+    }
+
+    /// Highest checkpoint served with its epoch and timestamp
+    pub async fn get_chain_tip(&self) -> Result<ChainTip> {
+        let mut chain = self.state();
+        chain.fail()?;
+        let (_, checkpoint) = chain.checkpoints.last_key_value().ok_or_else(|| {
+            ClientError::Unavailable("the mock node has no checkpoints".to_string())
+        })?;
+        Ok(ChainTip {
+            sequence_number: checkpoint.sequence_number,
+            epoch: checkpoint.epoch,
+            timestamp_ms: checkpoint.timestamp_ms,
+        })
+    }
+
+    /// Chain identifier derived like a node's, from the digest of checkpoint 0
+    ///
+    /// `None` if checkpoint 0 was never pushed, like a node that pruned it.
+    pub async fn get_chain_identifier(&self) -> Result<Option<String>> {
+        let mut chain = self.state();
+        chain.fail()?;
+        Ok(chain.chain_id.clone())
+    }
+
+    /// Checkpoint by sequence number, failing for one that was never pushed
+    pub async fn get_checkpoint(&self, sequence_number: u64) -> Result<CheckpointData> {
+        let mut chain = self.state();
+        chain.fail()?;
+        let served = chain
+            .substitutes
+            .remove(&sequence_number)
+            .unwrap_or(sequence_number);
+        if served < chain.pruned_before {
+            return Err(CheckpointPruned {
+                checkpoint: served,
+                earliest: chain.checkpoints.first_key_value().map(|(first, _)| *first),
+            }
+            .into());
+        }
+        chain
+            .checkpoints
+            .get(&served)
+            .cloned()
+            .ok_or_else(|| ClientError::NotFound(served).into())
+    }
+
+    /// Timestamp of a checkpoint, failing like [`MockSuiClient::get_checkpoint`]
+    pub async fn get_checkpoint_timestamp(&self, sequence_number: u64) -> Result<u64> {
+        Ok(self.get_checkpoint(sequence_number).await?.timestamp_ms)
+    }
+
+    /// Transactions of the served checkpoints, skipping unknown digests
+    ///
+    /// Responses carry only the digest, checkpoint and timestamp.
+    pub async fn get_transactions(
+        &self,
+        digests: &[TransactionDigest],
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        let mut chain = self.state();
+        let transactions: Vec<_> = digests
+            .iter()
+            .filter_map(|digest| {
+                let checkpoint = chain
+                    .checkpoints
+                    .get(chain.transaction_checkpoints.get(digest)?)
+                    .filter(|checkpoint| checkpoint.transactions.contains(digest))?;
+                let mut transaction = SuiTransactionBlockResponse::new(*digest);
+                transaction.checkpoint = Some(checkpoint.sequence_number);
+                transaction.timestamp_ms = Some(checkpoint.timestamp_ms);
+                Some(transaction)
+            })
+            .collect();
+        chain.transactions_served += transactions.len();
+        Ok(transactions)
+    }
+}
+
+/// Checkpoint with `events` swap events of [`SYNTHETIC_PACKAGE`], one transaction each
+///
+/// Timestamps advance by a second per checkpoint and amounts vary per event,
+/// so the data is realistic enough for filters and transformers.
+pub fn synthetic_checkpoint(sequence_number: u64, events: usize) -> Result<CheckpointData> {
+    let events = (0..events)
+        .map(|index| {
+            let event = serde_json::json!({
+                "id": {
+                    "eventSeq": "0",
+                    "txDigest": synthetic_digest(sequence_number, index),
+                },
+                "packageId": SYNTHETIC_PACKAGE,
+                "transactionModule": "pool",
+                "sender": format!("{:#066x}", index % 16),
+                "type": format!("{SYNTHETIC_PACKAGE}::pool::SwapEvent"),
+                "parsedJson": {
+                    "pool": format!("{:#066x}", index % 4),
+                    "amount_in": (sequence_number * 1000 + index as u64).to_string(),
+                    "amount_out": (index as u64 * 7).to_string(),
+                    "a_to_b": index % 2 == 0,
+                },
+                "bcs": "",
+                "timestampMs": (sequence_number * 1000).to_string(),
+            });
+            Ok(serde_json::from_value::<SuiEvent>(event)?)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let transactions = events
+        .iter()
+        .map(|event: &SuiEvent| event.id.tx_digest)
+        .collect();
+
+    Ok(CheckpointData {
+        sequence_number,
+        digest: synthetic_digest(sequence_number, usize::MAX),
+        previous_digest: sequence_number
+            .checked_sub(1)
+            .map(|previous| synthetic_digest(previous, usize::MAX)),
+        epoch: sequence_number / 10_000,
+        round: 0,
+        timestamp_ms: sequence_number * 1000,
+        network_total_transactions: 0,
+        transactions,
+        events,
+        packages: Vec::new(),
+        move_calls: Vec::new(),
+        object_changes: Vec::new(),
+        end_of_epoch_data: None,
+        validator_signature: String::new(),
+    })
+}
+
+/// Base58 digest unique to a checkpoint and index
+fn synthetic_digest(sequence_number: u64, index: usize) -> String {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&sequence_number.to_be_bytes());
+    bytes[8..16].copy_from_slice(&(index as u64).to_be_bytes());
+    sui_types::digests::TransactionDigest::new(bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_follows_pushed_checkpoints() -> Result<()> {
+        let client = MockSuiClient::synthetic(3, 2)?;
+        assert_eq!(client.get_latest_checkpoint().await?, 2);
+
+        let checkpoint = client.get_checkpoint(1).await?;
+        assert_eq!(checkpoint.events.len(), 2);
+        assert_eq!(checkpoint.transactions.len(), 2);
+        assert_ne!(
+            checkpoint.events[0].id.tx_digest,
+            checkpoint.events[1].id.tx_digest
+        );
+        let err = client.get_checkpoint(3).await.unwrap_err();
+        assert_eq!(ClientError::find(&err), Some(&ClientError::NotFound(3)));
+
+        client.push_checkpoint(synthetic_checkpoint(3, 0)?);
+        assert_eq!(client.get_chain_tip().await?.sequence_number, 3);
+
+        client.fail_requests(1);
+        assert!(client.get_latest_checkpoint().await.is_err());
+        client.substitute(2, 1);
+        assert_eq!(client.get_checkpoint(2).await?.sequence_number, 1);
+        assert_eq!(client.get_checkpoint(2).await?.sequence_number, 2);
+
+        client.prune_before(2);
+        let err = client.get_checkpoint(1).await.unwrap_err();
+        assert_eq!(
+            CheckpointPruned::find(&err),
+            Some(CheckpointPruned {
+                checkpoint: 1,
+                earliest: Some(2)
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_serves_recorded_checkpoints() -> Result<()> {
+        let client = MockSuiClient::recorded();
+        let last = *sui_indexer_fixtures::CHECKPOINTS.end();
+        assert_eq!(client.get_latest_checkpoint().await?, last);
+
+        let checkpoint = client.get_checkpoint(last - 2).await?;
+        assert!(!checkpoint.events.is_empty());
+        let err = client.get_checkpoint(0).await.unwrap_err();
+        assert!(CheckpointPruned::find(&err).is_some());
+        Ok(())
+    }
+}
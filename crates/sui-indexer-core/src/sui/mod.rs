@@ -1,6 +1,20 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
 use eyre::Result;
-use sui_indexer_config::NetworkConfig;
+use sui_indexer_config::{ErrorKind, NetworkConfig, WithKind};
+use sui_indexer_events::{system_clock, SharedClock};
+use sui_json_rpc_types::SuiTransactionBlockResponse;
+use sui_types::base_types::TransactionDigest;
 use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::metrics::Metrics;
 
 /// gRPC Event type (pure gRPC)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -14,12 +28,29 @@ pub struct Event {
     pub bcs: Option<Vec<u8>>,
 }
 
+pub mod cache;
 pub mod checkpoint;
+pub mod endpoints;
+pub mod error;
 pub mod grpc_client;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
 
 // Re-export the main types from checkpoint module
-pub use checkpoint::{CheckpointData, CheckpointProcessor, CheckpointRange, CheckpointStats};
+pub use cache::{chain_dir, CheckpointCache};
+pub use checkpoint::{
+    CheckpointData, CheckpointProcessor, CheckpointRange, CheckpointStats, MoveCall, ObjectChange,
+    ObjectChangeKind, PublishedPackage,
+};
+use endpoints::Endpoint;
+pub use endpoints::EndpointHealth;
+pub use error::{CheckpointPruned, ClientError};
 pub use grpc_client::{CheckpointSubscription, SuiGrpcClient};
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockSuiClient;
+
+/// Transactions fetched per request by [`SuiClient::get_transactions`]
+pub const TRANSACTIONS_PER_REQUEST: usize = 50;
 
 /// Event query result using pure gRPC types
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -38,22 +69,295 @@ pub struct HealthStatus {
     pub error: Option<String>,
 }
 
-/// Sui client wrapper for pure gRPC communication
+/// Latest checkpoint known to the node
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct ChainTip {
+    pub sequence_number: u64,
+    pub epoch: u64,
+    pub timestamp_ms: u64,
+}
+
+/// Connection of one endpoint to a node, or to a scripted chain in tests
 #[derive(Debug, Clone)]
+pub(crate) enum NodeClient {
+    Grpc(SuiGrpcClient),
+    #[cfg(any(test, feature = "testing"))]
+    Mock(MockSuiClient),
+}
+
+impl NodeClient {
+    async fn get_latest_checkpoint(&self) -> Result<u64> {
+        match self {
+            Self::Grpc(client) => client.get_latest_checkpoint().await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_latest_checkpoint().await,
+        }
+    }
+
+    async fn get_chain_tip(&self) -> Result<ChainTip> {
+        match self {
+            Self::Grpc(client) => client.get_chain_tip().await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_chain_tip().await,
+        }
+    }
+
+    async fn get_chain_identifier(&self) -> Result<Option<String>> {
+        match self {
+            Self::Grpc(client) => client.get_chain_identifier().await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_chain_identifier().await,
+        }
+    }
+
+    async fn get_checkpoint(&self, sequence_number: u64) -> Result<CheckpointData> {
+        match self {
+            Self::Grpc(client) => client.get_checkpoint(sequence_number).await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_checkpoint(sequence_number).await,
+        }
+    }
+
+    async fn get_checkpoint_timestamp(&self, sequence_number: u64) -> Result<u64> {
+        match self {
+            Self::Grpc(client) => client.get_checkpoint_timestamp(sequence_number).await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_checkpoint_timestamp(sequence_number).await,
+        }
+    }
+
+    async fn get_transactions(
+        &self,
+        digests: &[TransactionDigest],
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        match self {
+            Self::Grpc(client) => client.get_transactions(digests).await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(client) => client.get_transactions(digests).await,
+        }
+    }
+
+    async fn subscribe_checkpoints(
+        &self,
+        start_sequence: Option<u64>,
+    ) -> Result<CheckpointSubscription> {
+        match self {
+            Self::Grpc(client) => client.subscribe_checkpoints(start_sequence).await,
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(_) => Ok(CheckpointSubscription { start_sequence }),
+        }
+    }
+
+    async fn query_events(
+        &self,
+        transaction_digest: Option<String>,
+        sender: Option<String>,
+        package_id: Option<String>,
+        cursor: Option<String>,
+        limit: Option<usize>,
+        descending_order: bool,
+    ) -> Result<EventQueryResult> {
+        match self {
+            Self::Grpc(client) => {
+                client
+                    .query_events(
+                        transaction_digest,
+                        sender,
+                        package_id,
+                        cursor,
+                        limit,
+                        descending_order,
+                    )
+                    .await
+            }
+            #[cfg(any(test, feature = "testing"))]
+            Self::Mock(_) => Ok(EventQueryResult {
+                data: Vec::new(),
+                next_cursor: None,
+                has_next_page: false,
+            }),
+        }
+    }
+}
+
+/// Sui client wrapper for pure gRPC communication
+///
+/// With fallback endpoints configured, a failed request is retried on the
+/// next endpoint, which then stays active until it fails in turn. Success
+/// rate and latency are tracked per endpoint.
+#[derive(Clone)]
 pub struct SuiClient {
-    grpc_client: SuiGrpcClient,
-    config: NetworkConfig,
+    endpoints: Arc<[Endpoint]>,
+    /// Index of the endpoint requests go to first
+    active: Arc<AtomicUsize>,
+    config: Arc<NetworkConfig>,
+    metrics: Option<Arc<Metrics>>,
+    cache: Option<CheckpointCache>,
+    /// Times requests and stamps endpoint errors
+    clock: SharedClock,
+}
+
+impl std::fmt::Debug for SuiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SuiClient")
+            .field("endpoints", &self.endpoints)
+            .field("active", &self.active)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
 }
 
 impl SuiClient {
     /// Create a new Sui client with gRPC-only mode
     pub async fn new_grpc_only(config: NetworkConfig) -> Result<Self> {
-        let grpc_client = SuiGrpcClient::new(config.grpc_url.as_str()).await?;
+        let urls = config.grpc_urls().kind(ErrorKind::Config)?;
+        let endpoints = urls
+            .iter()
+            .map(|url| Ok(Endpoint::new(url, SuiGrpcClient::new_lazy(url.as_str())?)))
+            .collect::<Result<Vec<_>>>()
+            .kind(ErrorKind::Config)?;
 
-        Ok(SuiClient {
-            grpc_client,
-            config,
-        })
+        let client = SuiClient {
+            endpoints: endpoints.into(),
+            active: Arc::default(),
+            config: Arc::new(config),
+            metrics: None,
+            cache: None,
+            clock: system_clock(),
+        };
+        // Fail early when no endpoint answers
+        client.get_latest_checkpoint().await?;
+        info!(
+            endpoint = %client.active_endpoint().label,
+            endpoints = client.endpoints.len(),
+            "Connected to Sui gRPC endpoint"
+        );
+        Ok(client)
+    }
+
+    /// Create a client served by an in-memory [`MockSuiClient`] instead of a node
+    #[cfg(any(test, feature = "testing"))]
+    pub fn mock(mock: MockSuiClient) -> Self {
+        SuiClient {
+            endpoints: vec![Endpoint::mock(mock)].into(),
+            active: Arc::default(),
+            config: Arc::new(NetworkConfig::default()),
+            metrics: None,
+            cache: None,
+            clock: system_clock(),
+        }
+    }
+
+    /// Export the request outcomes and latency of every endpoint to `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        let active = self.active.load(Ordering::Relaxed);
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            metrics
+                .rpc_endpoint_active
+                .with_label_values(&[&endpoint.label])
+                .set(i64::from(index == active));
+        }
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Answer checkpoint fetches from `cache` when it has them, and keep fetched ones in it
+    pub fn with_checkpoint_cache(mut self, cache: CheckpointCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Time requests and stamp endpoint errors with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Success rate and latency of every configured endpoint, the primary one first
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        let active = self.active.load(Ordering::Relaxed);
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| endpoint.health(index == active))
+            .collect()
+    }
+
+    fn active_endpoint(&self) -> &Endpoint {
+        &self.endpoints[self.active.load(Ordering::Relaxed)]
+    }
+
+    /// Send a request to the active endpoint, failing over to the others in order
+    async fn call<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn(NodeClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let active = self.active.load(Ordering::Relaxed);
+        let count = self.endpoints.len();
+        let mut last_error = None;
+        for offset in 0..count {
+            let index = (active + offset) % count;
+            let endpoint = &self.endpoints[index];
+            let started = self.clock.instant();
+            let result = request(endpoint.client.clone()).await;
+            let latency = self.clock.elapsed(started);
+            endpoint.record(result.as_ref().map(|_| ()), latency, self.clock.now());
+            if let Some(metrics) = &self.metrics {
+                let outcome = if result.is_ok() { "success" } else { "failure" };
+                metrics
+                    .rpc_requests
+                    .with_label_values(&[&endpoint.label, outcome])
+                    .inc();
+                metrics
+                    .rpc_request_duration_seconds
+                    .with_label_values(&[&endpoint.label])
+                    .observe(latency.as_secs_f64());
+            }
+
+            match result {
+                Ok(value) => {
+                    if index != active {
+                        self.fail_over(active, index);
+                    }
+                    return Ok(value);
+                }
+                Err(e) => {
+                    if offset + 1 < count {
+                        warn!(
+                            endpoint = %endpoint.label,
+                            error = %e,
+                            "Sui gRPC request failed, trying the next endpoint"
+                        );
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("at least one endpoint is configured"))
+    }
+
+    fn fail_over(&self, from: usize, to: usize) {
+        if self
+            .active
+            .compare_exchange(from, to, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        warn!(
+            from = %self.endpoints[from].label,
+            to = %self.endpoints[to].label,
+            "Failed over to another Sui gRPC endpoint"
+        );
+        if let Some(metrics) = &self.metrics {
+            for (index, value) in [(from, 0), (to, 1)] {
+                metrics
+                    .rpc_endpoint_active
+                    .with_label_values(&[&self.endpoints[index].label])
+                    .set(value);
+            }
+        }
     }
 
     /// Create a new Sui client (alias for new_grpc_only)
@@ -63,12 +367,81 @@ impl SuiClient {
 
     /// Get the latest checkpoint sequence number
     pub async fn get_latest_checkpoint(&self) -> Result<u64> {
-        self.grpc_client.get_latest_checkpoint().await
+        self.call(|client| async move { client.get_latest_checkpoint().await })
+            .await
+            .kind(ErrorKind::Connectivity)
+    }
+
+    /// Get the latest checkpoint with its epoch and timestamp
+    pub async fn get_chain_tip(&self) -> Result<ChainTip> {
+        self.call(|client| async move { client.get_chain_tip().await })
+            .await
+            .kind(ErrorKind::Connectivity)
     }
 
-    /// Get checkpoint data by sequence number
+    /// Get the chain identifier of the node, which tells networks apart
+    ///
+    /// `None` if the node pruned the genesis checkpoint it is derived from.
+    pub async fn get_chain_identifier(&self) -> Result<Option<String>> {
+        self.call(|client| async move { client.get_chain_identifier().await })
+            .await
+            .kind(ErrorKind::Connectivity)
+    }
+
+    /// Get checkpoint data by sequence number, from the checkpoint cache if it has it
     pub async fn get_checkpoint(&self, sequence_number: u64) -> Result<CheckpointData> {
-        self.grpc_client.get_checkpoint(sequence_number).await
+        let Some(cache) = &self.cache else {
+            return self.fetch_checkpoint(sequence_number).await;
+        };
+        let cached = cache.get(sequence_number).await;
+        if let Some(metrics) = &self.metrics {
+            let outcome = if cached.is_some() { "hit" } else { "miss" };
+            metrics
+                .checkpoint_cache_lookups
+                .with_label_values(&[outcome])
+                .inc();
+        }
+        if let Some(checkpoint) = cached {
+            return Ok(checkpoint);
+        }
+
+        let checkpoint = self.fetch_checkpoint(sequence_number).await?;
+        cache.put(&checkpoint).await;
+        if let Some(metrics) = &self.metrics {
+            metrics.checkpoint_cache_bytes.set(cache.size() as i64);
+        }
+        Ok(checkpoint)
+    }
+
+    /// Timestamp of a checkpoint, without fetching its contents
+    pub async fn get_checkpoint_timestamp(&self, sequence_number: u64) -> Result<u64> {
+        self.call(|client| async move { client.get_checkpoint_timestamp(sequence_number).await })
+            .await
+            .kind(ErrorKind::Connectivity)
+    }
+
+    async fn fetch_checkpoint(&self, sequence_number: u64) -> Result<CheckpointData> {
+        self.call(|client| async move { client.get_checkpoint(sequence_number).await })
+            .await
+            .kind(ErrorKind::Connectivity)
+    }
+
+    /// Full data of the transactions `digests`, in requests of at most [`TRANSACTIONS_PER_REQUEST`]
+    ///
+    /// Transactions the node does not know are left out.
+    pub async fn get_transactions(
+        &self,
+        digests: &[TransactionDigest],
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        let mut transactions = Vec::with_capacity(digests.len());
+        for chunk in digests.chunks(TRANSACTIONS_PER_REQUEST) {
+            let fetched = self
+                .call(|client| async move { client.get_transactions(chunk).await })
+                .await
+                .kind(ErrorKind::Connectivity)?;
+            transactions.extend(fetched);
+        }
+        Ok(transactions)
     }
 
     /// Subscribe to checkpoint updates (pure gRPC streaming)
@@ -76,7 +449,10 @@ impl SuiClient {
         &self,
         start_sequence: Option<u64>,
     ) -> Result<CheckpointSubscription> {
-        self.grpc_client.subscribe_checkpoints(start_sequence).await
+        self.active_endpoint()
+            .client
+            .subscribe_checkpoints(start_sequence)
+            .await
     }
 
     /// Query events with filter using pure gRPC
@@ -89,16 +465,28 @@ impl SuiClient {
         limit: Option<usize>,
         descending_order: bool,
     ) -> Result<EventQueryResult> {
-        self.grpc_client
-            .query_events(
-                transaction_digest,
-                sender,
-                package_id,
-                cursor,
-                limit,
-                descending_order,
-            )
-            .await
+        self.call(|client| {
+            let (transaction_digest, sender, package_id, cursor) = (
+                transaction_digest.clone(),
+                sender.clone(),
+                package_id.clone(),
+                cursor.clone(),
+            );
+            async move {
+                client
+                    .query_events(
+                        transaction_digest,
+                        sender,
+                        package_id,
+                        cursor,
+                        limit,
+                        descending_order,
+                    )
+                    .await
+            }
+        })
+        .await
+        .kind(ErrorKind::Connectivity)
     }
 
     /// Get the network configuration
@@ -110,24 +498,13 @@ impl SuiClient {
     pub async fn health_check(&self) -> Result<HealthStatus> {
         let start = std::time::Instant::now();
 
-        match self.grpc_client.health_check().await {
-            Ok(_) => {
-                if let Ok(checkpoint) = self.grpc_client.get_latest_checkpoint().await {
-                    Ok(HealthStatus {
-                        healthy: true,
-                        latest_checkpoint: Some(checkpoint),
-                        latency: Some(start.elapsed()),
-                        error: None,
-                    })
-                } else {
-                    Ok(HealthStatus {
-                        healthy: true,
-                        latest_checkpoint: None,
-                        latency: Some(start.elapsed()),
-                        error: None,
-                    })
-                }
-            }
+        match self.get_latest_checkpoint().await {
+            Ok(checkpoint) => Ok(HealthStatus {
+                healthy: true,
+                latest_checkpoint: Some(checkpoint),
+                latency: Some(start.elapsed()),
+                error: None,
+            }),
             Err(e) => Ok(HealthStatus {
                 healthy: false,
                 latest_checkpoint: None,
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use sui_indexer_config::{EventFilter, IndexerConfig, RuntimeConfig};
+use sui_indexer_events::{
+    system_clock, DefaultEventProcessor, EventFilterProcessor, EventProcessor, ProcessedEvent,
+    ProtocolRegistry,
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::{
+    autotune::Concurrency,
+    metrics::Metrics,
+    pipeline::{FetchedCheckpoint, Fetcher},
+    readiness::Readiness,
+    scheduler::Scheduler,
+    sui::SuiClient,
+};
+
+/// Follows the network tip and emits matched events without storing them
+///
+/// This is a storage-free pipeline for watching what a set of filters would
+/// index; it starts at the latest checkpoint and never touches the cursor.
+pub struct Tail {
+    client: SuiClient,
+    processor: Arc<dyn EventProcessor>,
+    runtime: RuntimeConfig,
+    filters: Vec<EventFilter>,
+}
+
+impl Tail {
+    /// Create a tail over the configured network, protocols and filters
+    pub async fn from_config(config: &IndexerConfig) -> Result<Self> {
+        let client = SuiClient::new_grpc_only(config.network.clone()).await?;
+        let protocols = ProtocolRegistry::from_config(&config.protocols)?;
+
+        Ok(Self {
+            client,
+            processor: Arc::new(DefaultEventProcessor::with_protocols(Arc::new(protocols))),
+            runtime: config.runtime.clone(),
+            filters: config.events.filters.clone(),
+        })
+    }
+
+    /// Only emit events matching these filters
+    pub fn with_filters(mut self, filters: Vec<EventFilter>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Send matched events to `output` until `shutdown` is cancelled or the
+    /// receiver is dropped
+    pub async fn run(
+        self,
+        output: mpsc::Sender<ProcessedEvent>,
+        shutdown: CancellationToken,
+    ) -> Result<()> {
+        let start_checkpoint = self.client.get_latest_checkpoint().await?;
+        let filter = EventFilterProcessor::new(self.filters.clone());
+        info!(
+            start_checkpoint,
+            filters = self.filters.len(),
+            "Tailing checkpoints"
+        );
+
+        let (tx, mut rx) = mpsc::channel(self.runtime.checkpoint_channel_capacity.max(1));
+        let metrics = Arc::new(Metrics::new()?);
+        let fetcher = Fetcher {
+            pipeline: "tail",
+            client: self.client.clone(),
+            runtime: self.runtime.clone(),
+            metrics: metrics.clone(),
+            scheduler: Scheduler::default(),
+            clock: system_clock(),
+            concurrency: Concurrency::new("tail", &self.runtime, metrics),
+            readiness: Readiness::default(),
+            max_lag: 0,
+        };
+        let fetch_task = tokio::spawn(fetcher.run(start_checkpoint, tx, shutdown.clone()));
+
+        'checkpoints: while let Some(FetchedCheckpoint { checkpoint, .. }) = rx.recv().await {
+            let sequence_number = checkpoint.sequence_number;
+            let matched: Vec<_> = checkpoint
+                .events
+                .into_iter()
+                .filter(|event| filter.should_process_event(event))
+                .collect();
+            if matched.is_empty() {
+                continue;
+            }
+
+            for mut event in self.processor.process_events(matched).await? {
+                event.checkpoint_sequence = sequence_number;
+                if output.send(event).await.is_err() {
+                    shutdown.cancel();
+                    break 'checkpoints;
+                }
+            }
+        }
+
+        fetch_task.await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sui_indexer_config::FilterPriority;
+
+    use super::*;
+    use crate::sui::{mock::synthetic_checkpoint, MockSuiClient};
+
+    #[tokio::test]
+    async fn test_tail_emits_matched_events_from_the_tip_on() -> Result<()> {
+        let node = MockSuiClient::synthetic(3, 4)?;
+        let sender = format!("{:#066x}", 1);
+        let mut runtime = RuntimeConfig::default();
+        runtime.poll_interval = 10;
+        let tail = Tail {
+            client: SuiClient::mock(node.clone()),
+            processor: Arc::new(DefaultEventProcessor::new()),
+            runtime,
+            filters: vec![EventFilter {
+                name: Some("one".to_string()),
+                package: None,
+                module: None,
+                event_type: None,
+                sender: Some(sender.clone()),
+                priority: FilterPriority::Normal,
+            }],
+        };
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let running = tokio::spawn(tail.run(tx, CancellationToken::new()));
+
+        // Earlier checkpoints are skipped; the tip and later ones are followed
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await?
+            .expect("an event at the tip");
+        assert_eq!(event.checkpoint_sequence, 2);
+        assert_eq!(event.sender, sender);
+        node.push_checkpoint(synthetic_checkpoint(3, 4)?);
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await?
+            .expect("an event of the new checkpoint");
+        assert_eq!(event.checkpoint_sequence, 3);
+        assert_eq!(event.sender, sender);
+
+        // Dropping the receiver ends the tail
+        drop(rx);
+        node.push_checkpoint(synthetic_checkpoint(4, 4)?);
+        tokio::time::timeout(Duration::from_secs(5), running).await???;
+        Ok(())
+    }
+}
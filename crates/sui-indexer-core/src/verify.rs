@@ -0,0 +1,464 @@
+use std::{collections::HashMap, sync::Arc};
+
+use eyre::{Result, WrapErr};
+use futures::{stream, StreamExt};
+use serde::Serialize;
+use sui_indexer_config::EventFilter;
+use sui_indexer_events::{EventFilterProcessor, EventProcessor};
+use sui_indexer_storage::{EventQuery, EventRecord, StorageManager, DEFAULT_PIPELINE};
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::ObjectID;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::sui::{CheckpointRange, SuiClient};
+
+/// Expected and stored event counts of one filter over the verified range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FilterCounts {
+    /// Filter name, or its position in `events.filters` when unnamed
+    pub filter: String,
+    /// Events on chain matching the filter
+    pub expected: u64,
+    /// Matching events found in storage
+    pub stored: u64,
+}
+
+/// A checkpoint whose stored events differ from the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Discrepancy {
+    /// Checkpoint sequence number
+    pub checkpoint: u64,
+    /// Matched events on chain
+    pub expected: u64,
+    /// Events in storage
+    pub stored: u64,
+    /// Matched events missing from storage
+    pub missing: u64,
+    /// Stored events that match no filter or are duplicates
+    pub unexpected: u64,
+    /// Events the live pipeline reported storing when it committed the checkpoint, if its report is kept
+    pub reported: Option<u64>,
+    /// Whether the checkpoint was re-indexed
+    pub repaired: bool,
+}
+
+/// Outcome of a verification run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// Checkpoints compared
+    pub checkpoints: u64,
+    /// Per-filter totals
+    pub filters: Vec<FilterCounts>,
+    /// Checkpoints whose stored events differ from the chain, in order
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl VerifyReport {
+    /// Check if any discrepancy was left unrepaired
+    pub fn has_unrepaired(&self) -> bool {
+        self.discrepancies.iter().any(|d| !d.repaired)
+    }
+}
+
+/// Compares stored events with the chain over a checkpoint range
+///
+/// Events are identified by transaction digest and event type, so a
+/// checkpoint is consistent when storage holds exactly the matched events of
+/// every transaction. Repairing replaces a checkpoint's stored events of the
+/// filters with a freshly processed copy; the checkpoint cursor is never moved.
+pub struct Verify {
+    client: SuiClient,
+    storage: StorageManager,
+    processor: Arc<dyn EventProcessor>,
+    range: CheckpointRange,
+    filters: Vec<EventFilter>,
+    concurrency: usize,
+    repair: bool,
+}
+
+impl Verify {
+    /// Create a verification of `range` against every event of those checkpoints
+    pub fn new(
+        client: SuiClient,
+        storage: StorageManager,
+        processor: Arc<dyn EventProcessor>,
+        range: CheckpointRange,
+    ) -> Self {
+        Self {
+            client,
+            storage,
+            processor,
+            range,
+            filters: Vec::new(),
+            concurrency: 8,
+            repair: false,
+        }
+    }
+
+    /// Expect the events matching these filters
+    pub fn with_filters(mut self, filters: Vec<EventFilter>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Set how many checkpoints are compared at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Re-index checkpoints that differ from the chain
+    pub fn with_repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    /// Compare the whole range, returning early if `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) -> Result<VerifyReport> {
+        info!(
+            from = self.range.start,
+            to = self.range.end,
+            filters = self.filters.len(),
+            repair = self.repair,
+            "Starting verification"
+        );
+
+        let mut report = VerifyReport {
+            filters: self
+                .filters
+                .iter()
+                .enumerate()
+                .map(|(index, filter)| FilterCounts {
+                    filter: filter
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("filters[{index}]")),
+                    expected: 0,
+                    stored: 0,
+                })
+                .collect(),
+            ..VerifyReport::default()
+        };
+
+        let mut results = stream::iter(self.range.iter())
+            .map(|sequence_number| self.verify_checkpoint(sequence_number))
+            .buffer_unordered(self.concurrency);
+
+        loop {
+            let result = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    warn!(checkpoints = report.checkpoints, "Verification interrupted");
+                    break;
+                }
+                result = results.next() => result,
+            };
+            let Some(result) = result else {
+                break;
+            };
+            let (comparison, discrepancy) = result?;
+
+            report.checkpoints += 1;
+            for (totals, (expected, stored)) in report.filters.iter_mut().zip(comparison.filters) {
+                totals.expected += expected;
+                totals.stored += stored;
+            }
+            report.discrepancies.extend(discrepancy);
+        }
+
+        report.discrepancies.sort_by_key(|d| d.checkpoint);
+        Ok(report)
+    }
+
+    async fn verify_checkpoint(
+        &self,
+        sequence_number: u64,
+    ) -> Result<(Comparison, Option<Discrepancy>)> {
+        let checkpoint = self
+            .client
+            .get_checkpoint(sequence_number)
+            .await
+            .wrap_err_with(|| format!("Failed to fetch checkpoint {sequence_number}"))?;
+        let stored = self
+            .storage
+            .query_events(&EventQuery {
+                from_checkpoint: Some(sequence_number),
+                to_checkpoint: Some(sequence_number),
+                ..EventQuery::default()
+            })
+            .await?;
+
+        let comparison = compare(&checkpoint.events, &stored, &self.filters);
+        if comparison.missing == 0 && comparison.unexpected == 0 {
+            return Ok((comparison, None));
+        }
+
+        // The report tells events lost after the commit from ones never indexed
+        let reported = self
+            .storage
+            .checkpoint_reports(DEFAULT_PIPELINE, sequence_number, sequence_number)
+            .await?
+            .first()
+            .map(|report| report.events_stored.max(0) as u64);
+        let mut discrepancy = Discrepancy {
+            checkpoint: sequence_number,
+            expected: comparison.expected,
+            stored: stored.len() as u64,
+            missing: comparison.missing,
+            unexpected: comparison.unexpected,
+            reported,
+            repaired: false,
+        };
+        warn!(
+            checkpoint = sequence_number,
+            expected = discrepancy.expected,
+            stored = discrepancy.stored,
+            reported = ?discrepancy.reported,
+            "Stored events differ from the chain"
+        );
+
+        if self.repair && checkpoint.events.is_empty() {
+            // A node that returns no events would have every stored event deleted
+            warn!(
+                checkpoint = sequence_number,
+                "The node returned no events for the checkpoint, not repairing it"
+            );
+        } else if self.repair {
+            let filter = EventFilterProcessor::new(self.filters.clone());
+            let matched: Vec<_> = checkpoint
+                .events
+                .into_iter()
+                .filter(|event| filter.should_process_event(event))
+                .collect();
+            let mut events = self.processor.process_events(matched).await?;
+            for event in &mut events {
+                event.checkpoint_sequence = sequence_number;
+            }
+
+            // Only the events of the repaired filters are replaced
+            let replaced: Vec<_> = stored
+                .iter()
+                .filter(|record| {
+                    self.filters.is_empty()
+                        || self.filters.iter().any(|f| record_matches(record, f))
+                })
+                .map(|record| record.id)
+                .collect();
+            self.storage.replace_events(&replaced, &events).await?;
+            discrepancy.repaired = true;
+            info!(checkpoint = sequence_number, "Checkpoint repaired");
+        }
+
+        Ok((comparison, Some(discrepancy)))
+    }
+}
+
+/// Check if a stored event is selected by `filter`
+fn record_matches(record: &EventRecord, filter: &EventFilter) -> bool {
+    let same_id = |stored: &str, expected: &str| match (
+        stored.parse::<ObjectID>(),
+        expected.parse::<ObjectID>(),
+    ) {
+        (Ok(stored), Ok(expected)) => stored == expected,
+        _ => false,
+    };
+    filter
+        .package
+        .as_ref()
+        .is_none_or(|package| same_id(&record.package_id, package))
+        && filter
+            .module
+            .as_ref()
+            .is_none_or(|module| record.module_name == *module)
+        && filter
+            .event_type
+            .as_ref()
+            .is_none_or(|event_type| record.event_type == *event_type)
+        && filter
+            .sender
+            .as_ref()
+            .is_none_or(|sender| same_id(&record.sender, sender))
+}
+
+/// Event counts of one checkpoint
+#[derive(Debug, PartialEq, Eq)]
+struct Comparison {
+    /// Expected and stored counts per filter
+    filters: Vec<(u64, u64)>,
+    /// Events matching any filter
+    expected: u64,
+    /// Expected events absent from storage
+    missing: u64,
+    /// Stored events beyond the expected ones
+    unexpected: u64,
+}
+
+/// Compare the events of a checkpoint with what storage holds for it
+fn compare(events: &[SuiEvent], stored: &[EventRecord], filters: &[EventFilter]) -> Comparison {
+    type Key = (String, String);
+    let key = |event: &SuiEvent| -> Key {
+        (event.id.tx_digest.to_string(), event.type_.name.to_string())
+    };
+    let count = |selector: &EventFilterProcessor| {
+        let mut counts: HashMap<Key, u64> = HashMap::new();
+        for event in events.iter().filter(|e| selector.should_process_event(e)) {
+            *counts.entry(key(event)).or_default() += 1;
+        }
+        counts
+    };
+    let overlap = |expected: &HashMap<Key, u64>, stored: &HashMap<Key, u64>| -> u64 {
+        expected
+            .iter()
+            .map(|(key, count)| (*count).min(stored.get(key).copied().unwrap_or(0)))
+            .sum()
+    };
+
+    let mut in_storage: HashMap<Key, u64> = HashMap::new();
+    for record in stored {
+        let key = (record.transaction_digest.clone(), record.event_type.clone());
+        *in_storage.entry(key).or_default() += 1;
+    }
+
+    let expected = count(&EventFilterProcessor::new(filters.to_vec()));
+    let expected_total: u64 = expected.values().sum();
+    let found = overlap(&expected, &in_storage);
+
+    Comparison {
+        filters: filters
+            .iter()
+            .map(|filter| {
+                let per_filter = count(&EventFilterProcessor::new(vec![filter.clone()]));
+                (per_filter.values().sum(), overlap(&per_filter, &in_storage))
+            })
+            .collect(),
+        expected: expected_total,
+        missing: expected_total - found,
+        unexpected: stored.len() as u64 - found,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::FilterPriority;
+    use sui_indexer_events::DefaultEventProcessor;
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+    use crate::sui::{mock::synthetic_checkpoint, MockSuiClient};
+
+    fn event(tx: &str, module: &str, name: &str) -> SuiEvent {
+        TestEvent::new(&format!("0x2::{module}::{name}"))
+            .tx(tx)
+            .build()
+    }
+
+    fn record(tx: &str, name: &str) -> EventRecord {
+        EventRecord {
+            id: uuid::Uuid::new_v4(),
+            checkpoint_sequence: 1,
+            transaction_digest: tx.to_string(),
+            timestamp: chrono::Utc::now(),
+            package_id: "0x2".to_string(),
+            module_name: "pool".to_string(),
+            event_type: name.to_string(),
+            sender: "0x123".to_string(),
+            fields: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_compare_counts_missing_and_unexpected() {
+        let swap = event("tx1", "pool", "SwapEvent");
+        let tx1 = swap.id.tx_digest.to_string();
+        let events = vec![swap.clone(), swap, event("tx1", "coin", "Burn")];
+        let filters = vec![EventFilter {
+            name: Some("swaps".to_string()),
+            package: None,
+            module: Some("pool".to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        }];
+
+        // One of two swaps stored, plus an event no filter selects
+        let stored = vec![record(&tx1, "SwapEvent"), record(&tx1, "Burn")];
+        let comparison = compare(&events, &stored, &filters);
+        assert_eq!(
+            comparison,
+            Comparison {
+                filters: vec![(2, 1)],
+                expected: 2,
+                missing: 1,
+                unexpected: 1,
+            }
+        );
+
+        let stored = vec![record(&tx1, "SwapEvent"), record(&tx1, "SwapEvent")];
+        let comparison = compare(&events, &stored, &filters);
+        assert_eq!((comparison.missing, comparison.unexpected), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_repair_replaces_only_events_of_the_filters() -> Result<()> {
+        let node = MockSuiClient::synthetic(2, 2)?;
+        node.push_checkpoint(synthetic_checkpoint(2, 0)?);
+        let processor: Arc<dyn EventProcessor> = Arc::new(DefaultEventProcessor::new());
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+
+        // One of the two swaps of checkpoint 1 is stored, next to an event of
+        // another filter; checkpoint 2 has a swap the node does not return
+        let swaps = node.get_checkpoint(1).await?.events;
+        let mut stored = processor
+            .process_events(vec![swaps[0].clone(), event("tx1", "coin", "Burn")])
+            .await?;
+        let mut empty = processor.process_events(vec![swaps[1].clone()]).await?;
+        stored.iter_mut().for_each(|e| e.checkpoint_sequence = 1);
+        empty.iter_mut().for_each(|e| e.checkpoint_sequence = 2);
+        storage.store_events(&stored).await?;
+        storage.store_events(&empty).await?;
+
+        let filters = vec![EventFilter {
+            name: Some("swaps".to_string()),
+            package: None,
+            module: Some("pool".to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        }];
+        let report = Verify::new(
+            SuiClient::mock(node),
+            storage.clone(),
+            processor,
+            CheckpointRange::new(1, 2)?,
+        )
+        .with_filters(filters)
+        .with_repair(true)
+        .run(CancellationToken::new())
+        .await?;
+
+        let repaired: Vec<_> = report
+            .discrepancies
+            .iter()
+            .map(|d| (d.checkpoint, d.repaired))
+            .collect();
+        assert_eq!(repaired, [(1, true), (2, false)]);
+        let mut types: Vec<_> = storage
+            .query_events(&EventQuery::default())
+            .await?
+            .into_iter()
+            .map(|record| (record.checkpoint_sequence, record.event_type))
+            .collect();
+        types.sort();
+        assert_eq!(
+            types,
+            [
+                (1, "Burn".to_string()),
+                (1, "SwapEvent".to_string()),
+                (1, "SwapEvent".to_string()),
+                (2, "SwapEvent".to_string()),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use sui_indexer_config::WatchdogConfig;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{control::PipelineControl, metrics::Metrics, readiness::Readiness};
+
+/// Longest pause between two progress checks
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What a progress check found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Check {
+    /// Ingestion keeps up or has nothing to do
+    Healthy,
+    /// Ingestion stalled, or stayed stalled for another timeout
+    Stalled(Duration),
+    /// Stalled, and already acted on within the timeout
+    StillStalled(Duration),
+    /// A checkpoint was committed after a stall
+    Recovered,
+}
+
+/// Tracks the committed watermark against the network tip
+struct StallDetector {
+    timeout: Duration,
+    /// Watermark and tip at the last progress, and when it was made
+    progress: Option<(i64, i64, Instant)>,
+    /// When the stall was last reported, while stalled
+    reported: Option<Instant>,
+}
+
+impl StallDetector {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            progress: None,
+            reported: None,
+        }
+    }
+
+    /// Compare the committed and network checkpoints with the last progress
+    fn check(&mut self, processed: i64, network: i64, paused: bool, now: Instant) -> Check {
+        let progressed = match self.progress {
+            Some((watermark, _, _)) => processed != watermark,
+            None => true,
+        };
+        // A paused pipeline holds the watermark on purpose
+        if progressed || paused {
+            self.progress = Some((processed, network, now));
+            return match self.reported.take() {
+                Some(_) => Check::Recovered,
+                None => Check::Healthy,
+            };
+        }
+
+        let Some((_, tip, since)) = self.progress else {
+            return Check::Healthy;
+        };
+        let stalled_for = now.duration_since(since);
+        if network <= tip || stalled_for < self.timeout {
+            return Check::Healthy;
+        }
+        match self.reported {
+            Some(at) if now.duration_since(at) < self.timeout => Check::StillStalled(stalled_for),
+            _ => {
+                self.reported = Some(now);
+                Check::Stalled(stalled_for)
+            }
+        }
+    }
+}
+
+/// Notices when committed checkpoints stop advancing although the network tip moves
+///
+/// A stall marks the indexer not ready, feeds the `stall` alert rule through
+/// the `ingestion_stalled_seconds` gauge and, if configured, asks the
+/// pipeline to restart fetching and processing.
+pub struct Watchdog {
+    config: WatchdogConfig,
+    metrics: Arc<Metrics>,
+    readiness: Readiness,
+    control: PipelineControl,
+    restarts: Arc<Notify>,
+}
+
+impl Watchdog {
+    /// Create a watchdog over the pipeline reporting to `metrics`
+    pub fn new(
+        config: WatchdogConfig,
+        metrics: Arc<Metrics>,
+        readiness: Readiness,
+        control: PipelineControl,
+    ) -> Self {
+        Self {
+            config,
+            metrics,
+            readiness,
+            control,
+            restarts: Arc::default(),
+        }
+    }
+
+    /// Notified whenever the watchdog wants ingestion restarted
+    pub fn restarts(&self) -> Arc<Notify> {
+        self.restarts.clone()
+    }
+
+    /// Check progress until `shutdown` is cancelled
+    pub async fn run(self, shutdown: CancellationToken) {
+        let timeout = Duration::from_secs(self.config.stall_timeout.max(1));
+        let mut detector = StallDetector::new(timeout);
+        let mut interval = tokio::time::interval((timeout / 4).min(MAX_CHECK_INTERVAL));
+        info!(
+            stall_timeout_secs = timeout.as_secs(),
+            restart = self.config.restart,
+            "Starting watchdog"
+        );
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+
+            let processed = self.metrics.latest_processed_checkpoint.get();
+            let network = self.metrics.latest_network_checkpoint.get();
+            match detector.check(processed, network, self.control.is_paused(), Instant::now()) {
+                Check::Healthy => {}
+                Check::Stalled(stalled_for) => {
+                    warn!(
+                        checkpoint = processed,
+                        network_checkpoint = network,
+                        stalled_secs = stalled_for.as_secs(),
+                        "Ingestion stalled: no checkpoint committed while the network advanced"
+                    );
+                    self.set_stalled(Some(stalled_for));
+                    if self.config.restart {
+                        warn!("Restarting ingestion");
+                        self.metrics.watchdog_restarts.inc();
+                        self.restarts.notify_one();
+                    }
+                }
+                Check::StillStalled(stalled_for) => self.set_stalled(Some(stalled_for)),
+                Check::Recovered => {
+                    info!(checkpoint = processed, "Ingestion recovered");
+                    self.set_stalled(None);
+                }
+            }
+        }
+    }
+
+    fn set_stalled(&self, stalled_for: Option<Duration>) {
+        self.readiness.set_stalled(stalled_for.is_some());
+        self.metrics
+            .ingestion_stalled_seconds
+            .set(stalled_for.map_or(0, |stalled_for| stalled_for.as_secs().max(1) as i64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_detection() {
+        let timeout = Duration::from_secs(60);
+        let mut detector = StallDetector::new(timeout);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(detector.check(100, 110, false, at(0)), Check::Healthy);
+        // The network tip does not move: nothing to ingest
+        assert_eq!(detector.check(100, 110, false, at(90)), Check::Healthy);
+        // The tip moves on but the watermark stays
+        assert_eq!(
+            detector.check(100, 150, false, at(90)),
+            Check::Stalled(Duration::from_secs(90))
+        );
+        assert_eq!(
+            detector.check(100, 160, false, at(120)),
+            Check::StillStalled(Duration::from_secs(120))
+        );
+        assert_eq!(
+            detector.check(100, 170, false, at(150)),
+            Check::Stalled(Duration::from_secs(150))
+        );
+        assert_eq!(detector.check(101, 170, false, at(160)), Check::Recovered);
+
+        // Pausing holds the watermark without counting as a stall
+        assert_eq!(detector.check(101, 250, true, at(400)), Check::Healthy);
+        assert_eq!(detector.check(101, 260, false, at(420)), Check::Healthy);
+    }
+}
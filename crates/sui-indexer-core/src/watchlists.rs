@@ -0,0 +1,218 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use eyre::{Result, WrapErr};
+use sui_indexer_config::{IndexerConfig, WatchlistConfig};
+use sui_indexer_events::{AddressSet, Watchlist, Watchlists};
+use sui_indexer_storage::StorageManager;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use tokio::{sync::watch, time::MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{error::IndexerError, metrics::Metrics};
+
+/// Loads the configured watchlists and keeps them current
+///
+/// Watchlists are rebuilt when the configuration is reloaded, and every
+/// `refresh_interval` while any of them reads a file or the database, so
+/// addresses added there take effect without a restart.
+#[derive(Clone)]
+pub struct WatchlistLoader {
+    config: watch::Receiver<IndexerConfig>,
+    storage: StorageManager,
+    watchlists: Watchlists,
+    metrics: Arc<Metrics>,
+}
+
+impl WatchlistLoader {
+    /// Loader of the watchlists in `config`, reading stored addresses from `storage`
+    pub fn new(
+        config: watch::Receiver<IndexerConfig>,
+        storage: StorageManager,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self {
+            config,
+            storage,
+            watchlists: Watchlists::new(),
+            metrics,
+        }
+    }
+
+    /// Watchlists in effect, updated by [`WatchlistLoader::load`]
+    pub fn watchlists(&self) -> Watchlists {
+        self.watchlists.clone()
+    }
+
+    /// Read every enabled watchlist and put them in effect together
+    ///
+    /// On error the previous watchlists stay in effect.
+    pub async fn load(&self) -> Result<()> {
+        let configs = self.config.borrow().watchlists.clone();
+        let mut lists = Vec::new();
+        for (name, config) in configs.iter().filter(|(_, config)| config.enabled) {
+            let addresses = self
+                .addresses(name, config)
+                .await
+                .wrap_err_with(|| format!("Failed to load watchlist `{name}`"))?;
+            lists.push(Watchlist {
+                name: name.clone(),
+                tag: config.tag_for(name),
+                capture: config.capture,
+                addresses: AddressSet::new(addresses, config.false_positive_rate),
+            });
+        }
+
+        for (name, config) in &configs {
+            let watched = lists
+                .iter()
+                .find(|list| list.name == *name)
+                .map_or(0, |list| list.addresses.len());
+            self.metrics
+                .watchlist_addresses
+                .with_label_values(&[name])
+                .set(watched as i64);
+            if config.enabled {
+                info!(watchlist = %name, addresses = watched, "Loaded watchlist");
+            }
+        }
+        self.watchlists.replace(lists);
+        Ok(())
+    }
+
+    /// Reload the watchlists on config changes and every refresh interval until `shutdown`
+    pub async fn run(mut self, shutdown: CancellationToken) {
+        let interval = refresh_interval(&self.config.borrow());
+        let mut ticker = tokio::time::interval(interval.unwrap_or(Duration::MAX));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        // The first tick completes at once; the watchlists were loaded on startup
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                _ = ticker.tick(), if interval.is_some() => {}
+                changed = self.config.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                () = shutdown.cancelled() => return,
+            }
+            if let Err(e) = self.load().await {
+                warn!(error = %e, "Failed to reload watchlists, keeping the previous ones");
+            }
+        }
+    }
+
+    /// Addresses of one watchlist, from its configuration, file and stored entries
+    async fn addresses(&self, name: &str, config: &WatchlistConfig) -> Result<HashSet<SuiAddress>> {
+        let mut addresses = config
+            .addresses
+            .iter()
+            .map(|address| parse_address(address))
+            .collect::<Result<HashSet<_>>>()?;
+
+        if let Some(file) = &config.file {
+            let content = tokio::fs::read_to_string(file)
+                .await
+                .wrap_err_with(|| format!("Failed to read {}", file.display()))?;
+            for (index, line) in content.lines().enumerate() {
+                let address = line.split('#').next().unwrap_or_default().trim();
+                if address.is_empty() {
+                    continue;
+                }
+                let address = parse_address(address)
+                    .wrap_err_with(|| format!("{}:{}", file.display(), index + 1))?;
+                addresses.insert(address);
+            }
+        }
+
+        if config.database {
+            for address in self.storage.watchlist_addresses(name).await? {
+                match parse_address(&address) {
+                    Ok(address) => {
+                        addresses.insert(address);
+                    }
+                    Err(e) => warn!(watchlist = %name, error = %e, "Ignoring stored address"),
+                }
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+/// Shortest refresh interval of the watchlists that read a file or the database
+fn refresh_interval(config: &IndexerConfig) -> Option<Duration> {
+    config
+        .watchlists
+        .values()
+        .filter(|watchlist| watchlist.enabled && (watchlist.file.is_some() || watchlist.database))
+        .map(|watchlist| Duration::from_secs(watchlist.refresh_interval.max(1)))
+        .min()
+}
+
+/// Parse a full or short (`0x2`) hex address
+fn parse_address(address: &str) -> Result<SuiAddress> {
+    let id: ObjectID = address
+        .parse()
+        .map_err(|_| IndexerError::InvalidAddress(address.to_string()))?;
+    Ok(SuiAddress::from(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use sui_indexer_storage::MemoryStorage;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_addresses_come_from_config_file_and_database() -> Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "# sanctioned\n0xb0b  # added 2025-01-01\n\n0xc4a")?;
+
+        let mut config = IndexerConfig::default();
+        config.watchlists.insert(
+            "sanctions".to_string(),
+            WatchlistConfig {
+                addresses: vec!["0xa11ce".to_string()],
+                file: Some(file.path().to_path_buf()),
+                database: true,
+                ..WatchlistConfig::default()
+            },
+        );
+        config.watchlists.insert(
+            "paused".to_string(),
+            WatchlistConfig {
+                enabled: false,
+                addresses: vec!["0x1".to_string()],
+                ..WatchlistConfig::default()
+            },
+        );
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        storage
+            .add_watchlist_addresses("sanctions", &["0xd0d".to_string()])
+            .await?;
+
+        let (_tx, rx) = watch::channel(config);
+        let metrics = Arc::new(Metrics::new()?);
+        let loader = WatchlistLoader::new(rx, storage, metrics.clone());
+        loader.load().await?;
+
+        let lists = loader.watchlists().current();
+        assert_eq!(lists.len(), 1);
+        assert_eq!(lists[0].tag, "watchlist:sanctions");
+        for address in ["0xa11ce", "0xb0b", "0xc4a", "0xd0d"] {
+            assert!(lists[0].addresses.contains(&parse_address(address)?));
+        }
+        assert_eq!(lists[0].addresses.len(), 4);
+        assert_eq!(
+            metrics
+                .watchlist_addresses
+                .with_label_values(&["paused"])
+                .get(),
+            0
+        );
+        Ok(())
+    }
+}
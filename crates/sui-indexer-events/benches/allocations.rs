@@ -0,0 +1,145 @@
+//! Heap allocations of the per-event work of the pipeline
+//!
+//! Run with `cargo bench -p sui-indexer-events --bench allocations`. A
+//! counting global allocator measures each stage and prints allocations and
+//! bytes per event, next to the cost of copying the raw events, which is what
+//! the pipeline did at every stage before sharing them behind an `Arc`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use sui_indexer_config::{EventFilter, FilterPriority};
+use sui_indexer_events::{BatchProcessor, EventFilterProcessor, EventTransformer};
+use sui_indexer_fixtures::TestEvent;
+use sui_json_rpc_types::SuiEvent;
+use tokio::runtime::Runtime;
+
+const EVENTS: usize = 1_000;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// System allocator counting allocations and the bytes they request
+struct Counting;
+
+// Every call is passed on to the system allocator unchanged
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Allocations made while running one stage over every event
+struct Allocations {
+    count: usize,
+    bytes: usize,
+}
+
+impl Allocations {
+    fn print(&self, stage: &str) {
+        println!(
+            "{stage:<24} {:>10.1} {:>12.1}",
+            self.count as f64 / EVENTS as f64,
+            self.bytes as f64 / EVENTS as f64,
+        );
+    }
+}
+
+/// Count the allocations of `stage`, leaving those of dropping its output out
+fn measure<T>(stage: impl FnOnce() -> T) -> (Allocations, T) {
+    let (count, bytes) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        BYTES.load(Ordering::Relaxed),
+    );
+    let output = black_box(stage());
+    let allocations = Allocations {
+        count: ALLOCATIONS.load(Ordering::Relaxed) - count,
+        bytes: BYTES.load(Ordering::Relaxed) - bytes,
+    };
+    (allocations, output)
+}
+
+/// Swap events spread over a few modules and senders, as in the throughput benchmarks
+fn events() -> Vec<SuiEvent> {
+    (0..EVENTS)
+        .map(|index| {
+            let module = ["pool", "router", "position"][index % 3];
+            TestEvent::new(&format!("0x2::{module}::SwapEvent"))
+                .tx("11111111111111111111111111111111")
+                .seq(index as u64)
+                .sender(&format!("{:#066x}", index % 16))
+                .fields(serde_json::json!({
+                    "pool": format!("{:#066x}", index % 4),
+                    "amount_in": (index * 1000).to_string(),
+                    "amount_out": (index * 7).to_string(),
+                    "a_to_b": index % 2 == 0,
+                }))
+                .build()
+        })
+        .collect()
+}
+
+fn filters() -> Vec<EventFilter> {
+    vec![EventFilter {
+        name: Some("any".to_string()),
+        package: Some("0x2".to_string()),
+        module: None,
+        event_type: None,
+        sender: None,
+        priority: FilterPriority::Normal,
+    }]
+}
+
+fn main() {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let events = events();
+    let transformer = EventTransformer::default();
+    let processor = BatchProcessor::with_components(
+        EventTransformer::default(),
+        EventFilterProcessor::new(filters()),
+        100,
+        Duration::from_secs(5),
+    );
+
+    println!("{:<24} {:>10} {:>12}", "per event", "allocs", "bytes");
+    let (copied, _) = measure(|| events.clone());
+    copied.print("copy raw event");
+
+    let input = events.clone();
+    let (transformed, processed) = measure(|| {
+        runtime
+            .block_on(transformer.transform_events(input))
+            .expect("transformed")
+    });
+    transformed.print("transform_events");
+
+    let (cloned, _) = measure(|| processed.clone());
+    cloned.print("clone processed event");
+
+    let input = events.clone();
+    let (batched, _) = measure(|| {
+        runtime
+            .block_on(processor.process_event_batch(input))
+            .expect("processed")
+    });
+    batched.print("process_event_batch");
+}
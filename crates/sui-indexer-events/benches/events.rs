@@ -0,0 +1,135 @@
+//! Throughput of the per-event work of the pipeline
+//!
+//! Run with `cargo bench -p sui-indexer-events`; criterion compares each run
+//! against the previous one and reports regressions.
+
+use std::{hint::black_box, time::Duration};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use sui_indexer_config::{EventFilter, FilterPriority};
+use sui_indexer_events::{BatchProcessor, EventFilterProcessor, EventTransformer};
+use sui_indexer_fixtures::TestEvent;
+use sui_json_rpc_types::SuiEvent;
+use tokio::runtime::Runtime;
+
+const EVENTS: usize = 1_000;
+
+/// Swap and liquidity events spread over a few modules and senders
+fn events() -> Vec<SuiEvent> {
+    (0..EVENTS)
+        .map(|index| {
+            let module = ["pool", "router", "position"][index % 3];
+            TestEvent::new(&format!("0x2::{module}::SwapEvent"))
+                .tx("11111111111111111111111111111111")
+                .seq(index as u64)
+                .sender(&format!("{:#066x}", index % 16))
+                .fields(serde_json::json!({
+                    "pool": format!("{:#066x}", index % 4),
+                    "amount_in": (index * 1000).to_string(),
+                    "amount_out": (index * 7).to_string(),
+                    "a_to_b": index % 2 == 0,
+                }))
+                .build()
+        })
+        .collect()
+}
+
+/// Filters where most events only match the last one, the worst case for matching
+fn filters() -> Vec<EventFilter> {
+    let filter = |module: &str, sender: Option<&str>| EventFilter {
+        name: Some(module.to_string()),
+        package: Some("0x2".to_string()),
+        module: Some(module.to_string()),
+        event_type: None,
+        sender: sender.map(str::to_string),
+        priority: FilterPriority::Normal,
+    };
+    vec![
+        filter("coin", None),
+        filter("router", Some("0x1")),
+        filter("position", Some("0x2")),
+        filter("pool", None),
+        EventFilter {
+            name: Some("any".to_string()),
+            package: Some("0x2".to_string()),
+            module: None,
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        },
+    ]
+}
+
+fn bench_filter_matching(c: &mut Criterion) {
+    let events = events();
+    let filter = EventFilterProcessor::new(filters());
+
+    let mut group = c.benchmark_group("filter");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    group.bench_function("matching_filter", |b| {
+        b.iter(|| {
+            events
+                .iter()
+                .filter(|event| filter.matching_filter(black_box(event)).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn bench_transform(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let events = events();
+    let transformer = EventTransformer::default();
+
+    let mut group = c.benchmark_group("transform");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    group.bench_function("transform_events", |b| {
+        b.to_async(&runtime).iter_batched(
+            || events.clone(),
+            |events| async {
+                transformer
+                    .transform_events(events)
+                    .await
+                    .expect("transformed")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_batch_building(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let events = events();
+    let processor = BatchProcessor::with_components(
+        EventTransformer::default(),
+        EventFilterProcessor::new(filters()),
+        100,
+        Duration::from_secs(5),
+    );
+
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    group.bench_function("process_event_batch", |b| {
+        b.to_async(&runtime).iter_batched(
+            || events.clone(),
+            |events| async {
+                processor
+                    .process_event_batch(events)
+                    .await
+                    .expect("processed")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_filter_matching,
+    bench_transform,
+    bench_batch_building
+);
+criterion_main!(benches);
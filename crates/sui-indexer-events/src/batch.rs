@@ -1,13 +1,12 @@
 use std::time::Duration;
 
 use eyre::Result;
-use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse};
-use tokio::time::Instant;
+use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockResponse};
 use tracing::{debug, info, warn};
 
 use crate::{
-    filter::EventFilterProcessor, EventTransformer, ProcessedEvent, ProcessedTransaction,
-    TransactionMetadata,
+    filter::EventFilterProcessor, system_clock, EventTransformer, ProcessedEvent,
+    ProcessedTransaction, SharedClock,
 };
 
 /// Batch processor for handling multiple events efficiently
@@ -16,6 +15,7 @@ pub struct BatchProcessor {
     filter_processor: EventFilterProcessor,
     batch_size: usize,
     batch_timeout: Duration,
+    clock: SharedClock,
 }
 
 impl BatchProcessor {
@@ -26,6 +26,7 @@ impl BatchProcessor {
             filter_processor: EventFilterProcessor::default(),
             batch_size,
             batch_timeout: Duration::from_secs(5),
+            clock: system_clock(),
         }
     }
 
@@ -41,9 +42,18 @@ impl BatchProcessor {
             filter_processor,
             batch_size,
             batch_timeout,
+            clock: system_clock(),
         }
     }
 
+    /// Stamp and time transactions with `clock` instead of the system clock
+    ///
+    /// Events are stamped by the transformer, which takes its own clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get the configured batch size
     pub fn batch_size(&self) -> usize {
         self.batch_size
@@ -56,7 +66,7 @@ impl BatchProcessor {
 
     /// Process a batch of events with filtering and transformation
     pub async fn process_event_batch(&self, events: Vec<SuiEvent>) -> Result<Vec<ProcessedEvent>> {
-        let start_time = Instant::now();
+        let start_time = self.clock.instant();
 
         debug!(
             event_count = events.len(),
@@ -78,7 +88,7 @@ impl BatchProcessor {
         // Transform filtered events
         let processed_events = self.transformer.transform_events(filtered_events).await?;
 
-        let processing_time = start_time.elapsed();
+        let processing_time = self.clock.elapsed(start_time);
         info!(
             processed_count = processed_events.len(),
             processing_time_ms = processing_time.as_millis(),
@@ -93,7 +103,7 @@ impl BatchProcessor {
         &self,
         transactions: Vec<SuiTransactionBlockResponse>,
     ) -> Result<Vec<ProcessedTransaction>> {
-        let start_time = Instant::now();
+        let start_time = self.clock.instant();
 
         debug!(
             transaction_count = transactions.len(),
@@ -113,7 +123,7 @@ impl BatchProcessor {
             }
         }
 
-        let processing_time = start_time.elapsed();
+        let processing_time = self.clock.elapsed(start_time);
         info!(
             processed_count = processed_transactions.len(),
             processing_time_ms = processing_time.as_millis(),
@@ -128,58 +138,14 @@ impl BatchProcessor {
         &self,
         transaction: SuiTransactionBlockResponse,
     ) -> Result<ProcessedTransaction> {
-        use chrono::Utc;
-        use uuid::Uuid;
-
-        // Extract basic transaction information
-        let transaction_digest = transaction.digest;
-        let checkpoint_sequence = transaction.checkpoint.unwrap_or(0);
-        let timestamp = transaction
-            .timestamp_ms
-            .map(|ts| chrono::DateTime::from_timestamp_millis(ts as i64).unwrap_or(Utc::now()))
-            .unwrap_or(Utc::now());
-
-        // Extract transaction status
-        let success = transaction
-            .effects
-            .as_ref()
-            .map(|effects| effects.status().is_ok())
-            .unwrap_or(false);
-
-        // Extract gas information
-        let gas_used = transaction.effects.as_ref().map(|effects| {
-            let summary = effects.gas_cost_summary();
-            summary.computation_cost + summary.storage_cost
-        });
-
-        // Count events in the transaction
-        let event_count = transaction
-            .events
-            .as_ref()
-            .map(|events| events.data.len())
-            .unwrap_or(0);
-
-        // Create processed transaction with correct structure
-        let processed_transaction = ProcessedTransaction {
-            id: Uuid::new_v4(),
-            transaction: transaction.clone(),
-            checkpoint_sequence,
-            timestamp,
-            events: vec![], // Will be populated with processed events
-            metadata: TransactionMetadata {
-                processed_at: Utc::now(),
-                processing_duration_ms: 0, // Will be updated later
-                event_count,
-                gas_used,
-                success,
-            },
-        };
+        let processed_transaction =
+            ProcessedTransaction::from_response(transaction, self.clock.now());
 
         debug!(
-            transaction_digest = %transaction_digest,
-            success = success,
-            gas_used = gas_used,
-            event_count = event_count,
+            transaction_digest = %processed_transaction.transaction.digest,
+            success = processed_transaction.metadata.success,
+            gas_used = processed_transaction.metadata.gas_used,
+            event_count = processed_transaction.metadata.event_count,
             "Transaction processed"
         );
 
@@ -0,0 +1,128 @@
+//! Source of the current time for timestamps, stats and durations
+//!
+//! Processors, stats and the pipeline read the time through a [`Clock`]
+//! rather than `Utc::now()` and `Instant::now()`, so tests can hold it still
+//! with a [`ManualClock`] and assert exact timestamps and durations.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+/// Clock shared by the components of one indexer
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Wall-clock and monotonic time
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current wall-clock time, stamped on processed events and stats
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current monotonic time, to measure durations from
+    fn instant(&self) -> Instant;
+
+    /// Time passed since `earlier`, an instant of this clock
+    fn elapsed(&self, earlier: Instant) -> Duration {
+        self.instant().saturating_duration_since(earlier)
+    }
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The system clock, the default of every component
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Clock that only moves when told to
+///
+/// Clones share the time, so a test can keep one and advance the clock of
+/// the components it handed the others to.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    state: Arc<Mutex<ManualState>>,
+}
+
+#[derive(Debug)]
+struct ManualState {
+    now: DateTime<Utc>,
+    /// Instant standing for the start, and how far the clock moved since
+    base: Instant,
+    offset: Duration,
+}
+
+impl ManualClock {
+    /// Create a clock stopped at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ManualState {
+                now,
+                base: Instant::now(),
+                offset: Duration::ZERO,
+            })),
+        }
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state();
+        state.offset += duration;
+        state.now += chrono::Duration::from_std(duration).unwrap_or(chrono::Duration::MAX);
+    }
+
+    /// Set the wall-clock time, leaving the monotonic time where it is
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.state().now = now;
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ManualState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state().now
+    }
+
+    fn instant(&self) -> Instant {
+        let state = self.state();
+        state.base + state.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_when_told() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let earlier = clock.instant();
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.elapsed(earlier), Duration::ZERO);
+
+        clock.clone().advance(Duration::from_millis(1500));
+        assert_eq!(clock.elapsed(earlier), Duration::from_millis(1500));
+        assert_eq!(clock.now() - start, chrono::Duration::milliseconds(1500));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.elapsed(earlier), Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::common_filters::FilterError;
+
+/// Failure setting up or running event processing
+///
+/// Processing is deterministic, so none of these go away when retried.
+/// They travel in [`eyre::Report`]s like any other error and are read back
+/// with [`EventError::find`].
+#[derive(Debug, thiserror::Error)]
+pub enum EventError {
+    /// A `[protocols]` entry names no built-in decoder
+    #[error("Unknown protocol decoder in [protocols]: {0}")]
+    UnknownProtocol(String),
+    /// The requested decoder does not exist or is disabled
+    #[error("Protocol decoder {0} is unknown or disabled")]
+    ProtocolUnavailable(String),
+    /// A package ID configured for a protocol does not parse
+    #[error("Invalid package ID {id} for protocol {protocol}")]
+    InvalidPackageId { id: String, protocol: &'static str },
+    /// A typed event table name that cannot be used or is taken
+    #[error(
+        "Invalid typed event table `{0}`: use a lowercase name starting with `typed_` that no other typed event uses"
+    )]
+    InvalidTypedTable(String),
+    /// A typed event column name that cannot be used
+    #[error("Invalid column `{column}` of typed event table `{table}`: use a lowercase name, once, other than id, checkpoint_sequence, transaction_digest and timestamp")]
+    InvalidTypedColumn { table: String, column: String },
+    /// An event of a registered typed event that does not fit the type
+    #[error("Failed to decode {event_type} into its typed event")]
+    TypedDecode {
+        event_type: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A message or key template that does not parse
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+    /// A filter could not be built
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+}
+
+impl EventError {
+    /// The event error behind `err`, if that is what failed
+    pub fn find(err: &eyre::Report) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+}
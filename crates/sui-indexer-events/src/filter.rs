@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use sui_indexer_config::EventFilter;
+use sui_indexer_config::{EventFilter, FilterPriority};
 use sui_json_rpc_types::SuiEvent;
 use sui_types::{
     base_types::{ObjectID, SuiAddress},
@@ -8,6 +8,8 @@ use sui_types::{
 };
 use tracing::debug;
 
+use crate::PackageLineage;
+
 /// Event filtering logic for processing incoming events
 pub struct EventFilterProcessor {
     filters: Vec<EventFilter>,
@@ -15,6 +17,7 @@ pub struct EventFilterProcessor {
     module_filters: HashSet<(ObjectID, Identifier)>,
     event_type_filters: HashSet<String>,
     sender_filters: HashSet<SuiAddress>,
+    lineage: Option<PackageLineage>,
 }
 
 impl EventFilterProcessor {
@@ -26,6 +29,7 @@ impl EventFilterProcessor {
             module_filters: HashSet::new(),
             event_type_filters: HashSet::new(),
             sender_filters: HashSet::new(),
+            lineage: None,
         };
 
         // Pre-process filters for efficient matching
@@ -33,6 +37,12 @@ impl EventFilterProcessor {
         processor
     }
 
+    /// Match a filter's `package` against every version of that package in `lineage`
+    pub fn with_lineage(mut self, lineage: PackageLineage) -> Self {
+        self.lineage = Some(lineage);
+        self
+    }
+
     /// Pre-process filters for efficient matching
     fn preprocess_filters(&mut self, filters: &[EventFilter]) {
         for filter in filters {
@@ -85,12 +95,31 @@ impl EventFilterProcessor {
         false
     }
 
+    /// The first configured filter matching an event, with its position in the filter list
+    pub fn matching_filter(&self, event: &SuiEvent) -> Option<(usize, &EventFilter)> {
+        self.filters
+            .iter()
+            .enumerate()
+            .find(|(_, filter)| self.event_matches_filter(event, filter))
+    }
+
+    /// Check if an event matches any filter of `priority = "high"`
+    pub fn is_priority(&self, event: &SuiEvent) -> bool {
+        self.filters.iter().any(|filter| {
+            filter.priority == FilterPriority::High && self.event_matches_filter(event, filter)
+        })
+    }
+
     /// Check if an event matches a specific filter
     fn event_matches_filter(&self, event: &SuiEvent, filter: &EventFilter) -> bool {
         // Package filter
         if let Some(expected_package) = &filter.package {
             if let Ok(expected_id) = expected_package.parse::<ObjectID>() {
-                if event.package_id != expected_id {
+                let same = match &self.lineage {
+                    Some(lineage) => lineage.same_package(&event.package_id, &expected_id),
+                    None => event.package_id == expected_id,
+                };
+                if !same {
                     return false;
                 }
             } else {
@@ -186,10 +215,12 @@ pub mod common_filters {
             .parse::<ObjectID>()
             .map_err(|_| FilterError::InvalidPackageId(package_id.to_string()))?;
         Ok(EventFilter {
+            name: None,
             package: Some(package_id.to_string()),
             module: None,
             event_type: None,
             sender: None,
+            priority: FilterPriority::Normal,
         })
     }
 
@@ -204,10 +235,12 @@ pub mod common_filters {
             .map_err(|_| FilterError::InvalidModuleName(module_name.to_string()))?;
 
         Ok(EventFilter {
+            name: None,
             package: Some(package_id.to_string()),
             module: Some(module_name.to_string()),
             event_type: None,
             sender: None,
+            priority: FilterPriority::Normal,
         })
     }
 
@@ -220,10 +253,12 @@ pub mod common_filters {
         let event_type = format!("{package_id}::{module_name}::{event_name}");
 
         Ok(EventFilter {
+            name: None,
             package: Some(package_id.to_string()),
             module: Some(module_name.to_string()),
             event_type: Some(event_type),
             sender: None,
+            priority: FilterPriority::Normal,
         })
     }
 
@@ -235,13 +270,38 @@ pub mod common_filters {
             .map_err(|_| FilterError::InvalidSenderAddress(sender.to_string()))?;
 
         Ok(EventFilter {
+            name: None,
             package: None,
             module: None,
             event_type: None,
             sender: Some(sender.to_string()),
+            priority: FilterPriority::Normal,
         })
     }
 
+    /// Check that a configured filter's package, module and sender parse
+    ///
+    /// A filter with an unparseable value is accepted by [`EventFilterProcessor`]
+    /// but never matches that criterion.
+    pub fn validate(filter: &EventFilter) -> Result<(), FilterError> {
+        if let Some(package) = &filter.package {
+            package
+                .parse::<ObjectID>()
+                .map_err(|_| FilterError::InvalidPackageId(package.clone()))?;
+        }
+        if let Some(module) = &filter.module {
+            module
+                .parse::<Identifier>()
+                .map_err(|_| FilterError::InvalidModuleName(module.clone()))?;
+        }
+        if let Some(sender) = &filter.sender {
+            sender
+                .parse::<SuiAddress>()
+                .map_err(|_| FilterError::InvalidSenderAddress(sender.clone()))?;
+        }
+        Ok(())
+    }
+
     /// Create filter for Navi Protocol lending events
     pub fn navi_lending_events(package_id: &str) -> Result<Vec<EventFilter>, FilterError> {
         Ok(vec![
@@ -265,6 +325,8 @@ pub mod common_filters {
 
 #[cfg(test)]
 mod tests {
+    use sui_indexer_fixtures::TestEvent;
+
     use super::{common_filters::*, *};
 
     #[test]
@@ -277,10 +339,12 @@ mod tests {
     #[test]
     fn test_filter_processor_with_filters() {
         let filters = vec![EventFilter {
+            name: None,
             package: Some("0x2".to_string()),
             module: None,
             event_type: None,
             sender: None,
+            priority: FilterPriority::Normal,
         }];
 
         let processor = EventFilterProcessor::new(filters);
@@ -297,6 +361,44 @@ mod tests {
         let filter = module_events("0x2", "coin").unwrap();
         assert_eq!(filter.package, Some("0x2".to_string()));
         assert_eq!(filter.module, Some("coin".to_string()));
+        assert!(validate(&filter).is_ok());
+
+        let mut filter = filter;
+        filter.sender = Some("alice".to_string());
+        assert!(matches!(
+            validate(&filter),
+            Err(FilterError::InvalidSenderAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_package_filters_follow_upgrades() {
+        let event = TestEvent::new("0xb1::pool::Swap").package("0xb2").build();
+        let filters = vec![package_events("0xb1").unwrap()];
+        assert!(!EventFilterProcessor::new(filters.clone()).should_process_event(&event));
+
+        let lineage = PackageLineage::new();
+        let processor = EventFilterProcessor::new(filters).with_lineage(lineage.clone());
+        assert!(!processor.should_process_event(&event));
+        // Recorded later, as the upgrade is indexed
+        lineage.record("0xb2".parse().unwrap(), "0xb1".parse().unwrap());
+        assert!(processor.should_process_event(&event));
+    }
+
+    #[test]
+    fn test_priority_follows_any_high_filter() {
+        let event = TestEvent::new("0xb1::pool::Swap").build();
+        let bulk = module_events("0xb1", "pool").unwrap();
+        let mut critical = package_events("0xb1").unwrap();
+        critical.sender = Some("0x456".to_string());
+        critical.priority = FilterPriority::High;
+
+        let processor = EventFilterProcessor::new(vec![bulk.clone(), critical.clone()]);
+        assert!(!processor.is_priority(&event));
+
+        critical.sender = Some("0x123".to_string());
+        let processor = EventFilterProcessor::new(vec![bulk, critical]);
+        assert!(processor.is_priority(&event));
     }
 
     #[test]
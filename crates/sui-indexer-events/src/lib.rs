@@ -1,29 +1,68 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sui_indexer_config::EventFilter;
-use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockResponse};
-use sui_types::base_types::{ObjectID, TransactionDigest};
+use sui_json_rpc_types::{SuiEvent, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse};
+use sui_types::{
+    base_types::{ObjectID, TransactionDigest},
+    event::EventID,
+};
 use uuid::Uuid;
 
 pub mod batch;
+pub mod clock;
+pub mod error;
 pub mod filter;
+pub mod lineage;
 pub mod processor;
+pub mod protocols;
+pub mod template;
 pub mod transformer;
+pub mod typed;
+pub mod watchlist;
+
+#[cfg(test)]
+mod proptests;
 
 pub use batch::*;
+pub use clock::{system_clock, Clock, ManualClock, SharedClock, SystemClock};
+pub use error::EventError;
 pub use filter::*;
+pub use lineage::PackageLineage;
 pub use processor::*;
+pub use protocols::*;
+pub use template::{Placeholder, Template};
 pub use transformer::*;
+pub use typed::{
+    is_typed_table_name, Column, ColumnType, TypedEvent, TypedEventRegistry, TypedRow, TypedTable,
+    TYPED_EVENT_COLUMNS, TYPED_TABLE_PREFIX,
+};
+pub use watchlist::{AddressSet, Watchlist, Watchlists};
+
+/// Namespace of the UUIDv5 ids derived by [`ProcessedEvent::id_for`]
+pub const EVENT_ID_NAMESPACE: Uuid = Uuid::from_u128(0x6b3f_2a1e_95c4_4d0b_8e7a_1c52_f0d9_3b84);
+
+/// Namespace of the UUIDv5 ids derived by [`ProcessedTransaction::id_for`]
+pub const TRANSACTION_ID_NAMESPACE: Uuid =
+    Uuid::from_u128(0xc87e_3074_a7ea_426e_895f_f31c_c162_e0dc);
+
+/// Namespace of the UUIDv5 ids derived by [`move_call_id`]
+pub const MOVE_CALL_ID_NAMESPACE: Uuid = Uuid::from_u128(0xc2a3_70a7_32fc_47fc_81b7_91bd_276f_9414);
+
+/// Namespace of the UUIDv5 ids derived by [`object_version_id`]
+pub const OBJECT_VERSION_ID_NAMESPACE: Uuid =
+    Uuid::from_u128(0x118d_f246_66e0_4189_862e_fbbe_b16f_2ba1);
 
 /// Processed event with additional metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedEvent {
-    /// Unique identifier for this processed event
+    /// Identifier derived from the event's transaction digest and sequence number
+    ///
+    /// See [`ProcessedEvent::id_for`]; processing the same event again yields the same id.
     pub id: Uuid,
-    /// Original Sui event
-    pub event: SuiEvent,
+    /// Original Sui event, shared so cloning a processed event does not copy it
+    pub event: Arc<SuiEvent>,
     /// Transaction digest that emitted this event
     pub transaction_digest: TransactionDigest,
     /// Checkpoint sequence number
@@ -44,6 +83,19 @@ pub struct ProcessedEvent {
     pub metadata: EventMetadata,
 }
 
+impl ProcessedEvent {
+    /// Deterministic id of an event: the UUIDv5 of its transaction digest and sequence number
+    ///
+    /// Replays, backfills and retries after a crash process an event into the
+    /// same id, so storage and sinks can drop duplicates by id.
+    pub fn id_for(id: &EventID) -> Uuid {
+        let mut name = [0u8; 40];
+        name[..32].copy_from_slice(id.tx_digest.inner());
+        name[32..].copy_from_slice(&id.event_seq.to_be_bytes());
+        Uuid::new_v5(&EVENT_ID_NAMESPACE, &name)
+    }
+}
+
 /// Event processing metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMetadata {
@@ -62,7 +114,7 @@ pub struct EventMetadata {
 /// Transaction processing result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedTransaction {
-    /// Unique identifier for this processed transaction
+    /// Identifier derived from the transaction digest, see [`ProcessedTransaction::id_for`]
     pub id: Uuid,
     /// Original Sui transaction
     pub transaction: SuiTransactionBlockResponse,
@@ -76,6 +128,69 @@ pub struct ProcessedTransaction {
     pub metadata: TransactionMetadata,
 }
 
+impl ProcessedTransaction {
+    /// Deterministic id of a transaction: the UUIDv5 of its digest
+    pub fn id_for(digest: &TransactionDigest) -> Uuid {
+        Uuid::new_v5(&TRANSACTION_ID_NAMESPACE, digest.inner())
+    }
+
+    /// Process a transaction fetched from the node, stamped as processed at `now`
+    ///
+    /// The transaction takes its checkpoint and timestamp from the response,
+    /// falling back to 0 and `now`; `events` starts out empty.
+    pub fn from_response(transaction: SuiTransactionBlockResponse, now: DateTime<Utc>) -> Self {
+        let timestamp = transaction
+            .timestamp_ms
+            .and_then(|ts| DateTime::from_timestamp_millis(ts as i64))
+            .unwrap_or(now);
+        let success = transaction
+            .effects
+            .as_ref()
+            .map(|effects| effects.status().is_ok())
+            .unwrap_or(false);
+        let gas_used = transaction.effects.as_ref().map(|effects| {
+            let summary = effects.gas_cost_summary();
+            summary.computation_cost + summary.storage_cost
+        });
+        let event_count = transaction
+            .events
+            .as_ref()
+            .map(|events| events.data.len())
+            .unwrap_or(0);
+
+        Self {
+            id: Self::id_for(&transaction.digest),
+            checkpoint_sequence: transaction.checkpoint.unwrap_or(0),
+            timestamp,
+            transaction,
+            events: vec![],
+            metadata: TransactionMetadata {
+                processed_at: now,
+                processing_duration_ms: 0,
+                event_count,
+                gas_used,
+                success,
+            },
+        }
+    }
+}
+
+/// Deterministic id of a Move call: the UUIDv5 of its transaction digest and command index
+pub fn move_call_id(digest: &TransactionDigest, command_index: u32) -> Uuid {
+    let mut name = [0u8; 36];
+    name[..32].copy_from_slice(digest.inner());
+    name[32..].copy_from_slice(&command_index.to_be_bytes());
+    Uuid::new_v5(&MOVE_CALL_ID_NAMESPACE, &name)
+}
+
+/// Deterministic id of an object version: the UUIDv5 of the object ID and version
+pub fn object_version_id(object_id: &ObjectID, version: u64) -> Uuid {
+    let mut name = [0u8; 40];
+    name[..32].copy_from_slice(&object_id.into_bytes());
+    name[32..].copy_from_slice(&version.to_be_bytes());
+    Uuid::new_v5(&OBJECT_VERSION_ID_NAMESPACE, &name)
+}
+
 /// Transaction processing metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionMetadata {
@@ -116,12 +231,31 @@ pub struct ProcessingStats {
     pub events_by_type: HashMap<String, u64>,
     /// Errors encountered
     pub error_count: u64,
+    /// Clock the rates and times are taken from
+    #[serde(skip, default = "system_clock")]
+    clock: SharedClock,
+}
+
+/// Counts attributed to one filter or one pipeline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakdownStats {
+    /// Events that matched
+    pub matched: u64,
+    /// Events stored
+    pub stored: u64,
+    /// Failed processing, storage or fetch attempts
+    pub errors: u64,
 }
 
 impl ProcessingStats {
     /// Create new processing statistics
     pub fn new() -> Self {
-        let now = Utc::now();
+        Self::with_clock(system_clock())
+    }
+
+    /// Create processing statistics that read the time from `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        let now = clock.now();
         Self {
             total_events: 0,
             total_transactions: 0,
@@ -134,6 +268,7 @@ impl ProcessingStats {
             current_checkpoint: None,
             events_by_type: HashMap::new(),
             error_count: 0,
+            clock,
         }
     }
 
@@ -147,7 +282,7 @@ impl ProcessingStats {
         current_checkpoint: Option<u64>,
         events_by_type: HashMap<String, u64>,
     ) {
-        let now = Utc::now();
+        let now = self.clock.now();
         let elapsed = (now - self.start_time).num_seconds() as f64;
 
         self.total_events += events_processed;
@@ -189,12 +324,12 @@ impl ProcessingStats {
 
     /// Get processing uptime
     pub fn uptime(&self) -> chrono::Duration {
-        Utc::now() - self.start_time
+        self.clock.now() - self.start_time
     }
 
     /// Get time since last update
     pub fn time_since_last_update(&self) -> chrono::Duration {
-        Utc::now() - self.last_update
+        self.clock.now() - self.last_update
     }
 }
 
@@ -267,6 +402,8 @@ impl Default for EventProcessingConfig {
 
 #[cfg(test)]
 mod tests {
+    use sui_indexer_fixtures::TestEvent;
+
     use super::*;
 
     #[test]
@@ -291,6 +428,43 @@ mod tests {
         assert_eq!(stats.events_by_type.get("test_event"), Some(&5));
     }
 
+    #[test]
+    fn test_processing_stats_follow_their_clock() {
+        let clock = ManualClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let mut stats = ProcessingStats::with_clock(Arc::new(clock.clone()));
+
+        clock.advance(std::time::Duration::from_secs(10));
+        stats.update(50, 5, 0, 0, Some(7), HashMap::new());
+        assert_eq!(stats.events_per_second, 5.0);
+        assert_eq!(stats.uptime(), chrono::Duration::seconds(10));
+
+        clock.advance(std::time::Duration::from_secs(3));
+        assert_eq!(stats.time_since_last_update(), chrono::Duration::seconds(3));
+    }
+
+    #[tokio::test]
+    async fn test_processed_events_are_stamped_by_the_clock() -> eyre::Result<()> {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock: SharedClock = Arc::new(ManualClock::new(now));
+        // Without a timestamp of its own the event is stamped with the clock's time
+        let raw = || TestEvent::new("0x2::test::Event").build();
+        let processed = DefaultEventProcessor::new()
+            .with_clock(clock.clone())
+            .process_event(raw())
+            .await?;
+        assert_eq!(processed.timestamp, now);
+        assert_eq!(processed.metadata.processed_at, now);
+        assert_eq!(processed.metadata.processing_duration_ms, 0);
+
+        let transformed = EventTransformer::new()
+            .with_clock(clock)
+            .transform_event(raw())
+            .await?;
+        assert_eq!(transformed.timestamp, now);
+        assert_eq!(transformed.metadata.processed_at, now);
+        Ok(())
+    }
+
     #[test]
     fn test_processing_stats_error_increment() {
         let mut stats = ProcessingStats::new();
@@ -312,9 +486,15 @@ mod tests {
     fn test_processed_event_uuid() {
         use sui_types::base_types::ObjectID;
 
+        let raw = TestEvent::new("test::Event")
+            .package("0x2")
+            .module("test")
+            .seq(1)
+            .timestamp_ms(1000)
+            .build();
         let event = ProcessedEvent {
-            id: Uuid::new_v4(),
-            event: serde_json::from_str(r#"{"id":{"eventSeq":"1","txDigest":"test"},"packageId":"0x2","transactionModule":"test","sender":"0x123","type":"test::Event","parsedJson":{},"bcs":"","timestampMs":"1000"}"#).unwrap(),
+            id: ProcessedEvent::id_for(&raw.id),
+            event: Arc::new(raw),
             transaction_digest: TransactionDigest::default(),
             checkpoint_sequence: 123,
             timestamp: Utc::now(),
@@ -334,5 +514,57 @@ mod tests {
 
         // UUID should be valid
         assert_ne!(event.id, Uuid::nil());
+        assert_eq!(event.id.get_version_num(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_processed_event_ids_are_deterministic() -> eyre::Result<()> {
+        let raw = |seq: u64| TestEvent::new("0x2::test::Event").seq(seq).build();
+        let processor = DefaultEventProcessor::new();
+        let first = processor.process_event(raw(0)).await?;
+        let again = processor.process_event(raw(0)).await?;
+        let transformed = EventTransformer::new().transform_event(raw(0)).await?;
+        assert_eq!(first.id, again.id);
+        assert_eq!(first.id, transformed.id);
+        assert_ne!(first.id, processor.process_event(raw(1)).await?.id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_derived_ids_differ_by_kind() {
+        let digest = TransactionDigest::default();
+        let event = ProcessedEvent::id_for(&EventID {
+            tx_digest: digest,
+            event_seq: 0,
+        });
+        let call = move_call_id(&digest, 0);
+        assert_eq!(call, move_call_id(&digest, 0));
+        assert_ne!(call, move_call_id(&digest, 1));
+        assert_ne!(call, event);
+        assert_ne!(ProcessedTransaction::id_for(&digest), event);
+        assert_eq!(
+            object_version_id(&ObjectID::ZERO, 3),
+            object_version_id(&ObjectID::ZERO, 3)
+        );
+        assert_ne!(
+            object_version_id(&ObjectID::ZERO, 3),
+            object_version_id(&ObjectID::ZERO, 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cloned_event_shares_raw_event() -> eyre::Result<()> {
+        let event = TestEvent::new("0x2::test::Event")
+            .seq(1)
+            .fields(serde_json::json!({"amount": "5"}))
+            .build();
+        let processed = DefaultEventProcessor::new().process_event(event).await?;
+
+        let cloned = processed.clone();
+        assert!(Arc::ptr_eq(&processed.event, &cloned.event));
+        let json = serde_json::to_value(&processed)?;
+        let parsed: ProcessedEvent = serde_json::from_value(json)?;
+        assert_eq!(parsed.event.parsed_json, processed.event.parsed_json);
+        Ok(())
     }
 }
@@ -0,0 +1,81 @@
+//! Versions of upgraded packages, for filters that follow package upgrades
+//!
+//! Every upgrade of a Move package is published under a new ID, and events
+//! emitted by the new version carry that ID. A [`PackageLineage`] maps each
+//! known version to the ID of the package's first version, so a filter on
+//! a package keeps matching after it is upgraded.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use sui_types::base_types::ObjectID;
+
+/// Original ID of every known package version, shared by its clones
+#[derive(Debug, Clone, Default)]
+pub struct PackageLineage {
+    originals: Arc<RwLock<HashMap<ObjectID, ObjectID>>>,
+}
+
+impl PackageLineage {
+    /// Create an empty lineage, in which every package is its own original
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `package_id` is a version of the package first published as `original_id`
+    pub fn record(&self, package_id: ObjectID, original_id: ObjectID) {
+        self.originals
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(package_id, original_id);
+    }
+
+    /// ID of the first version of `package_id`, or `package_id` itself if it is unknown
+    pub fn original(&self, package_id: &ObjectID) -> ObjectID {
+        self.originals
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(package_id)
+            .copied()
+            .unwrap_or(*package_id)
+    }
+
+    /// Check if `a` and `b` are versions of the same package
+    pub fn same_package(&self, a: &ObjectID, b: &ObjectID) -> bool {
+        a == b || self.original(a) == self.original(b)
+    }
+
+    /// Number of known package versions
+    pub fn len(&self) -> usize {
+        self.originals
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Check if no package version is known
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrades_map_to_the_original() {
+        let id = |n: u8| ObjectID::from_single_byte(n);
+        let lineage = PackageLineage::new();
+        lineage.record(id(1), id(1));
+        lineage.clone().record(id(2), id(1));
+
+        assert_eq!(lineage.original(&id(2)), id(1));
+        assert_eq!(lineage.original(&id(9)), id(9));
+        assert!(lineage.same_package(&id(2), &id(1)));
+        assert!(!lineage.same_package(&id(2), &id(9)));
+        assert_eq!(lineage.len(), 2);
+    }
+}
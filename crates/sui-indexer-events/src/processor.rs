@@ -1,11 +1,11 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use chrono::Utc;
 use eyre::Result;
 use sui_json_rpc_types::SuiEvent;
 use tracing::{debug, info};
-use uuid::Uuid;
 
-use crate::{EventMetadata, ProcessedEvent};
+use crate::{system_clock, EventMetadata, ProcessedEvent, ProtocolRegistry, SharedClock};
 
 /// Trait for processing events
 #[async_trait]
@@ -24,122 +24,108 @@ pub trait EventProcessor: Send + Sync {
 }
 
 /// Default event processor implementation
-pub struct DefaultEventProcessor;
+pub struct DefaultEventProcessor {
+    protocols: Arc<ProtocolRegistry>,
+    clock: SharedClock,
+}
 
 impl DefaultEventProcessor {
     pub fn new() -> Self {
-        Self
+        Self::with_protocols(Arc::new(ProtocolRegistry::default_builtin()))
+    }
+
+    /// Create a processor that decodes events with the given protocol registry
+    pub fn with_protocols(protocols: Arc<ProtocolRegistry>) -> Self {
+        Self {
+            protocols,
+            clock: system_clock(),
+        }
+    }
+
+    /// Stamp and time events with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
     }
 }
 
 #[async_trait]
 impl EventProcessor for DefaultEventProcessor {
     async fn process_event(&self, event: SuiEvent) -> Result<ProcessedEvent> {
-        let start_time = std::time::Instant::now();
+        let start_time = self.clock.instant();
 
-        // Check if this is a Navi Protocol event
-        let package_id_str = event.package_id.to_string();
-        let is_navi_protocol = package_id_str
-            .contains("81c408448d0d57b3e371ea94de1d40bf852784d3e225de1e74acab3e8395c18f");
+        // Decode events of known protocols
+        let decoded = self.protocols.decode(&event);
 
-        if is_navi_protocol {
+        if let Some(decoded) = &decoded {
             info!(
-                "🚀 NAVI PROTOCOL EVENT DETECTED: {} from module {} (tx: {})",
-                event.type_.name, event.type_.module, event.id.tx_digest
+                protocol = decoded.protocol,
+                action = decoded.action.unwrap_or("other"),
+                event_type = %event.type_.name,
+                module = %event.type_.module,
+                tx_digest = %event.id.tx_digest,
+                sender = %event.sender,
+                "Decoded protocol event"
             );
-
-            // Log detailed event information for Navi Protocol
-            info!(
-                "📊 Navi Event Data: {}",
-                serde_json::to_string_pretty(&event.parsed_json).unwrap_or_default()
+            debug!(
+                protocol = decoded.protocol,
+                tx_digest = %event.id.tx_digest,
+                data = %event.parsed_json,
+                "Protocol event data"
             );
-
-            // Special handling for different Navi event types
-            match event.type_.name.as_str() {
-                name if name.contains("DepositEvent") => {
-                    info!(
-                        "💰 NAVI DEPOSIT EVENT: User {} made a deposit",
-                        event.sender
-                    );
-                    if let Some(amount) = event.parsed_json.get("amount") {
-                        info!("💵 Deposit Amount: {}", amount);
-                    }
-                    if let Some(coin_type) = event.parsed_json.get("coin_type") {
-                        info!("🪙 Coin Type: {}", coin_type);
-                    }
-                }
-                name if name.contains("BorrowEvent") => {
-                    info!("🏦 NAVI BORROW EVENT: User {} borrowed funds", event.sender);
-                    if let Some(amount) = event.parsed_json.get("amount") {
-                        info!("💸 Borrow Amount: {}", amount);
-                    }
-                    if let Some(coin_type) = event.parsed_json.get("coin_type") {
-                        info!("🪙 Coin Type: {}", coin_type);
-                    }
-                }
-                name if name.contains("WithdrawEvent") => {
-                    info!(
-                        "🏧 NAVI WITHDRAW EVENT: User {} withdrew funds",
-                        event.sender
-                    );
-                }
-                name if name.contains("RepayEvent") => {
-                    info!("💳 NAVI REPAY EVENT: User {} repaid loan", event.sender);
-                }
-                _ => {
-                    info!(
-                        "📋 NAVI OTHER EVENT: {} by {}",
-                        event.type_.name, event.sender
-                    );
-                }
-            }
         } else {
             debug!(
-                "📝 Processing event: {} from package {} (tx: {})",
-                event.type_.name, package_id_str, event.id.tx_digest
+                event_type = %event.type_.name,
+                package = %event.package_id,
+                tx_digest = %event.id.tx_digest,
+                "Processing event"
             );
         }
 
         // Extract event fields - simplify for now
-        let fields = serde_json::json!({
+        let mut fields = serde_json::json!({
             "type": event.type_.name.to_string(),
             "parsed_json": event.parsed_json
         });
+        if let Some(decoded) = &decoded {
+            fields["protocol"] = serde_json::Value::Object(decoded.fields.clone());
+        }
 
-        let processing_duration = start_time.elapsed().as_millis() as u64;
+        let processing_duration = self.clock.elapsed(start_time).as_millis() as u64;
 
+        let event = Arc::new(event);
         let processed_event = ProcessedEvent {
-            id: Uuid::new_v4(),
-            event: event.clone(),
+            id: ProcessedEvent::id_for(&event.id),
+            event: Arc::clone(&event),
             transaction_digest: event.id.tx_digest,
             checkpoint_sequence: 0, // Would need to be provided from context
-            timestamp: Utc::now(),
+            timestamp: self.clock.now(),
             package_id: event.package_id,
             module_name: event.type_.module.to_string(),
             event_type: event.type_.name.to_string(),
             sender: event.sender.to_string(),
             fields,
             metadata: EventMetadata {
-                processed_at: Utc::now(),
+                processed_at: self.clock.now(),
                 processing_duration_ms: processing_duration,
                 event_index: 0, // Would need to be provided from context
-                matched_filters: if is_navi_protocol {
-                    vec!["navi_protocol".to_string()]
-                } else {
-                    vec![]
-                },
-                tags: if is_navi_protocol {
-                    vec!["navi".to_string(), "defi".to_string()]
-                } else {
-                    vec![]
-                },
+                matched_filters: decoded
+                    .iter()
+                    .map(|decoded| format!("{}_protocol", decoded.protocol))
+                    .collect(),
+                tags: decoded
+                    .as_ref()
+                    .map(|decoded| decoded.tags.clone())
+                    .unwrap_or_default(),
             },
         };
 
-        if is_navi_protocol {
+        if let Some(decoded) = &decoded {
             info!(
-                "✅ NAVI EVENT PROCESSED: {} (processing time: {}ms)",
-                event.type_.name, processing_duration
+                "✅ {} EVENT PROCESSED: {} (processing time: {}ms)",
+                decoded.protocol.to_uppercase(),
+                event.type_.name,
+                processing_duration
             );
         }
 
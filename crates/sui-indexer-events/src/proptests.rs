@@ -0,0 +1,202 @@
+//! Property tests over generated events: payloads of custom packages are
+//! arbitrary JSON and bytes, and none of them may panic the pipeline.
+
+use proptest::{prelude::*, test_runner::TestCaseError};
+use serde_json::{Map, Value};
+use sui_indexer_config::{EventFilter, FilterPriority};
+use sui_json_rpc_types::{BcsEvent, SuiEvent};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress, TransactionDigest},
+    event::EventID,
+    parse_sui_struct_tag, Identifier,
+};
+
+use crate::{common_filters, EventFilterProcessor, EventTransformer, ProtocolDecoder};
+
+/// Keys the transformer sets over those of the parsed JSON
+const METADATA_KEYS: &[&str] = &[
+    "package_id",
+    "module",
+    "event_type",
+    "sender",
+    "transaction_digest",
+    "event_sequence",
+    "timestamp_ms",
+    "bcs_data",
+];
+
+/// Move identifier that is not a primitive type name, which would not parse in a type tag
+fn identifier() -> impl Strategy<Value = String> {
+    const KEYWORDS: &[&str] = &[
+        "address", "bool", "signer", "vector", "u8", "u16", "u32", "u64", "u128", "u256",
+    ];
+    "[A-Za-z][A-Za-z0-9_]{0,24}".prop_filter("primitive type name", |name| {
+        !KEYWORDS.contains(&name.as_str())
+    })
+}
+
+fn object_id() -> impl Strategy<Value = ObjectID> {
+    let builtin: Vec<ObjectID> = crate::ProtocolRegistry::builtin_decoders()
+        .iter()
+        .flat_map(|decoder| decoder.default_package_ids())
+        .filter_map(|id| id.parse().ok())
+        .collect();
+    prop_oneof![
+        any::<[u8; 32]>().prop_map(ObjectID::new),
+        proptest::sample::select(builtin),
+    ]
+}
+
+/// JSON as Move structs render it: nested objects, arrays, numbers as strings or not
+fn json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(|value| Value::from(value.to_string())),
+        any::<f64>().prop_map(Value::from),
+        ".{0,32}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::from),
+            prop::collection::btree_map(".{0,12}", inner, 0..8)
+                .prop_map(|map| Value::Object(map.into_iter().collect::<Map<_, _>>())),
+        ]
+    })
+}
+
+fn sui_event() -> impl Strategy<Value = SuiEvent> {
+    (
+        any::<[u8; 32]>(),
+        any::<u64>(),
+        object_id(),
+        identifier(),
+        identifier(),
+        any::<[u8; 32]>(),
+        json(),
+        prop::collection::vec(any::<u8>(), 0..256),
+        // Up to the end of year 9999, the range of RFC 3339 timestamps
+        proptest::option::of(0..=253_402_300_799_999u64),
+    )
+        .prop_filter_map(
+            "identifiers form a type tag",
+            |(
+                digest,
+                event_seq,
+                package_id,
+                module,
+                name,
+                sender,
+                parsed_json,
+                bcs,
+                timestamp_ms,
+            )| {
+                Some(SuiEvent {
+                    id: EventID {
+                        tx_digest: TransactionDigest::new(digest),
+                        event_seq,
+                    },
+                    package_id,
+                    transaction_module: Identifier::new(module.clone()).ok()?,
+                    sender: SuiAddress::from(ObjectID::new(sender)),
+                    type_: parse_sui_struct_tag(&format!("{package_id}::{module}::{name}")).ok()?,
+                    parsed_json,
+                    bcs: BcsEvent::new(bcs),
+                    timestamp_ms,
+                })
+            },
+        )
+}
+
+/// Filters as users write them, valid or not
+fn filter() -> impl Strategy<Value = EventFilter> {
+    let text = || proptest::option::of(prop_oneof![identifier(), ".{0,70}"]);
+    let package = prop_oneof![
+        Just(None),
+        object_id().prop_map(|id| Some(id.to_string())),
+        text(),
+    ];
+    (package, text(), text(), text()).prop_map(|(package, module, event_type, sender)| {
+        EventFilter {
+            name: None,
+            package,
+            module,
+            event_type,
+            sender,
+            priority: FilterPriority::Normal,
+        }
+    })
+}
+
+fn runtime() -> std::io::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread().build()
+}
+
+proptest! {
+    #[test]
+    fn transform_keeps_every_payload_field(event in sui_event()) {
+        // Without protocol decoding, which maps payload keys onto others
+        let transformer = EventTransformer::with_config(true, false);
+        let parsed = event.parsed_json.clone();
+        let processed = runtime()?
+            .block_on(transformer.transform_event(event))
+            .map_err(|e| TestCaseError::fail(format!("{e:#}")))?;
+
+        let fields = processed
+            .fields
+            .as_object()
+            .ok_or_else(|| TestCaseError::fail("fields are not an object"))?;
+        if let Value::Object(parsed) = parsed {
+            for (key, value) in &parsed {
+                if !METADATA_KEYS.contains(&key.as_str()) {
+                    prop_assert_eq!(fields.get(key), Some(value));
+                }
+            }
+        }
+        // Processed events are stored and sent as JSON, so they must round-trip
+        let json = serde_json::to_value(&processed)?;
+        prop_assert!(serde_json::from_value::<crate::ProcessedEvent>(json).is_ok());
+    }
+
+    #[test]
+    fn protocol_decoders_accept_any_payload(event in sui_event()) {
+        let registry = crate::ProtocolRegistry::default_builtin();
+        if let Some(decoded) = registry.decode(&event) {
+            prop_assert!(decoded.fields.contains_key("protocol"));
+        }
+    }
+
+    #[test]
+    fn filter_matching_is_consistent(
+        event in sui_event(),
+        filters in prop::collection::vec(filter(), 1..6),
+    ) {
+        let valid: Vec<_> = filters
+            .iter()
+            .map(|filter| common_filters::validate(filter).is_ok())
+            .collect();
+        let processor = EventFilterProcessor::new(filters);
+
+        let matched = processor.matching_filter(&event);
+        prop_assert_eq!(processor.should_process_event(&event), matched.is_some());
+        if let Some((index, _)) = matched {
+            // A filter with a package, module or sender that does not parse never matches
+            prop_assert!(valid[index]);
+        }
+    }
+
+    #[test]
+    fn filter_built_from_event_matches_it(event in sui_event()) {
+        let filter = EventFilter {
+            name: None,
+            package: Some(event.package_id.to_string()),
+            module: Some(event.transaction_module.to_string()),
+            event_type: Some(event.type_.name.to_string()),
+            sender: Some(event.sender.to_string()),
+            priority: FilterPriority::Normal,
+        };
+        prop_assert!(common_filters::validate(&filter).is_ok());
+        prop_assert!(EventFilterProcessor::new(vec![filter]).should_process_event(&event));
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use eyre::Result;
+use serde_json::{Map, Value};
+use sui_indexer_config::ProtocolConfig;
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::ObjectID;
+
+use crate::EventError;
+
+/// Decoder that extracts protocol-specific fields from a protocol's events
+pub trait ProtocolDecoder: Send + Sync {
+    /// Protocol name as used in the `[protocols]` config section
+    fn name(&self) -> &'static str;
+
+    /// Package IDs handled when the config does not override them
+    fn default_package_ids(&self) -> &'static [&'static str];
+
+    /// Tags attached to every event of this protocol
+    fn tags(&self) -> &'static [&'static str];
+
+    /// Normalized action for an event struct name, e.g. `DepositEvent` → `deposit`
+    fn action(&self, event_name: &str) -> Option<&'static str>;
+
+    /// Parsed JSON keys copied into the decoded fields, as `(source, target)` pairs
+    fn field_mappings(&self, action: Option<&str>) -> Vec<(&'static str, String)>;
+
+    /// Decode an event into protocol-specific fields
+    fn decode(&self, event: &SuiEvent) -> Map<String, Value> {
+        let mut fields = Map::new();
+        let action = self.action(event.type_.name.as_str());
+        fields.insert("protocol".to_string(), self.name().into());
+        fields.insert("action".to_string(), action.unwrap_or("unknown").into());
+
+        if let Value::Object(parsed) = &event.parsed_json {
+            for (source, target) in self.field_mappings(action) {
+                if let Some(value) = parsed.get(source) {
+                    fields.entry(target).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        fields
+    }
+}
+
+/// Result of decoding an event with a registered protocol decoder
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    /// Protocol name
+    pub protocol: &'static str,
+    /// Normalized action, if the event type is known to the decoder
+    pub action: Option<&'static str>,
+    /// Protocol-specific fields
+    pub fields: Map<String, Value>,
+    /// Protocol and configured tags
+    pub tags: Vec<String>,
+}
+
+struct RegisteredProtocol {
+    decoder: Arc<dyn ProtocolDecoder>,
+    package_ids: HashSet<ObjectID>,
+    tags: Vec<String>,
+}
+
+/// Registry mapping package IDs to protocol decoders
+#[derive(Default)]
+pub struct ProtocolRegistry {
+    protocols: Vec<RegisteredProtocol>,
+}
+
+impl ProtocolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Built-in protocol decoders
+    pub fn builtin_decoders() -> Vec<Arc<dyn ProtocolDecoder>> {
+        vec![
+            Arc::new(NaviDecoder),
+            Arc::new(CetusDecoder),
+            Arc::new(DeepBookDecoder),
+        ]
+    }
+
+    /// Registry of the built-in decoders with their default settings
+    pub fn default_builtin() -> Self {
+        Self::from_config(&BTreeMap::new()).expect("Built-in package IDs should be valid")
+    }
+
+    /// Build a registry of the built-in decoders with the `[protocols]` settings applied
+    ///
+    /// Decoders without a config entry run with their defaults; unknown names are rejected.
+    pub fn from_config(config: &BTreeMap<String, ProtocolConfig>) -> Result<Self> {
+        let decoders = Self::builtin_decoders();
+        if let Some(unknown) = config.keys().find(|name| {
+            !decoders
+                .iter()
+                .any(|decoder| decoder.name() == name.as_str())
+        }) {
+            return Err(EventError::UnknownProtocol(unknown.clone()).into());
+        }
+
+        let mut registry = Self::new();
+        for decoder in decoders {
+            let settings = config.get(decoder.name()).cloned().unwrap_or_default();
+            registry.register(decoder, &settings)?;
+        }
+        Ok(registry)
+    }
+
+    /// Build a registry holding only the named decoder, with its `[protocols]` settings applied
+    pub fn only(config: &BTreeMap<String, ProtocolConfig>, name: &str) -> Result<Self> {
+        let mut registry = Self::from_config(config)?;
+        registry
+            .protocols
+            .retain(|protocol| protocol.decoder.name() == name);
+        if registry.protocols.is_empty() {
+            return Err(EventError::ProtocolUnavailable(name.to_string()).into());
+        }
+        Ok(registry)
+    }
+
+    /// Register a decoder with the given settings; disabled decoders are skipped
+    pub fn register(
+        &mut self,
+        decoder: Arc<dyn ProtocolDecoder>,
+        settings: &ProtocolConfig,
+    ) -> Result<()> {
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let package_ids = if settings.package_ids.is_empty() {
+            decoder
+                .default_package_ids()
+                .iter()
+                .map(|id| id.to_string())
+                .collect()
+        } else {
+            settings.package_ids.clone()
+        };
+        let package_ids = package_ids
+            .iter()
+            .map(|id| {
+                id.parse::<ObjectID>().map_err(|_| {
+                    eyre::Report::from(EventError::InvalidPackageId {
+                        id: id.clone(),
+                        protocol: decoder.name(),
+                    })
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let mut tags: Vec<String> = decoder.tags().iter().map(|tag| tag.to_string()).collect();
+        tags.extend(settings.tags.iter().cloned());
+
+        self.protocols.push(RegisteredProtocol {
+            decoder,
+            package_ids,
+            tags,
+        });
+        Ok(())
+    }
+
+    /// Names of the enabled protocols
+    pub fn protocols(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.protocols
+            .iter()
+            .map(|protocol| protocol.decoder.name())
+    }
+
+    /// Decode an event if it belongs to a registered protocol
+    ///
+    /// Events match on either the calling package or the package defining the event type.
+    pub fn decode(&self, event: &SuiEvent) -> Option<DecodedEvent> {
+        let type_package = ObjectID::from(event.type_.address);
+        let protocol = self.protocols.iter().find(|protocol| {
+            protocol.package_ids.contains(&event.package_id)
+                || protocol.package_ids.contains(&type_package)
+        })?;
+
+        let decoder = &protocol.decoder;
+        Some(DecodedEvent {
+            protocol: decoder.name(),
+            action: decoder.action(event.type_.name.as_str()),
+            fields: decoder.decode(event),
+            tags: protocol.tags.clone(),
+        })
+    }
+}
+
+/// Navi lending protocol
+pub struct NaviDecoder;
+
+impl ProtocolDecoder for NaviDecoder {
+    fn name(&self) -> &'static str {
+        "navi"
+    }
+
+    fn default_package_ids(&self) -> &'static [&'static str] {
+        &[
+            "0x81c408448d0d57b3e371ea94de1d40bf852784d3e225de1e74acab3e8395c18f",
+            "0xd899cf7d2b5db716bd2cf55599fb0d5ee38a3061e7b6bb6eebf73fa5bc4c81ca",
+        ]
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["navi", "defi", "lending"]
+    }
+
+    fn action(&self, event_name: &str) -> Option<&'static str> {
+        match event_name {
+            "DepositEvent" => Some("deposit"),
+            "WithdrawEvent" => Some("withdraw"),
+            "BorrowEvent" => Some("borrow"),
+            "RepayEvent" => Some("repay"),
+            _ => None,
+        }
+    }
+
+    fn field_mappings(&self, action: Option<&str>) -> Vec<(&'static str, String)> {
+        let amount = match action {
+            Some(action) => format!("{action}_amount"),
+            None => "amount".to_string(),
+        };
+        vec![
+            ("amount", amount),
+            ("reserve", "reserve".to_string()),
+            ("asset_id", "asset_id".to_string()),
+            ("pool_id", "pool_id".to_string()),
+            ("user", "user_address".to_string()),
+            ("sender", "user_address".to_string()),
+        ]
+    }
+}
+
+/// Cetus concentrated-liquidity DEX
+pub struct CetusDecoder;
+
+impl ProtocolDecoder for CetusDecoder {
+    fn name(&self) -> &'static str {
+        "cetus"
+    }
+
+    fn default_package_ids(&self) -> &'static [&'static str] {
+        &["0x1eabed72c53feb3805120a081dc15963c204dc8d091542592abaf7a35689b2fb"]
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["cetus", "defi", "dex"]
+    }
+
+    fn action(&self, event_name: &str) -> Option<&'static str> {
+        match event_name {
+            "SwapEvent" => Some("swap"),
+            "AddLiquidityEvent" => Some("add_liquidity"),
+            "RemoveLiquidityEvent" => Some("remove_liquidity"),
+            "CollectFeeEvent" => Some("collect_fee"),
+            _ => None,
+        }
+    }
+
+    fn field_mappings(&self, _action: Option<&str>) -> Vec<(&'static str, String)> {
+        [
+            ("pool", "pool_id"),
+            ("position", "position_id"),
+            ("atob", "a_to_b"),
+            ("amount_in", "amount_in"),
+            ("amount_out", "amount_out"),
+            ("fee_amount", "fee_amount"),
+            ("amount_a", "amount_a"),
+            ("amount_b", "amount_b"),
+            ("liquidity", "liquidity"),
+        ]
+        .into_iter()
+        .map(|(source, target)| (source, target.to_string()))
+        .collect()
+    }
+}
+
+/// DeepBook v1 central limit order book
+pub struct DeepBookDecoder;
+
+impl ProtocolDecoder for DeepBookDecoder {
+    fn name(&self) -> &'static str {
+        "deepbook"
+    }
+
+    fn default_package_ids(&self) -> &'static [&'static str] {
+        &["0xdee9"]
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["deepbook", "defi", "orderbook"]
+    }
+
+    fn action(&self, event_name: &str) -> Option<&'static str> {
+        match event_name {
+            "OrderPlaced" => Some("place_order"),
+            "OrderFilled" => Some("fill_order"),
+            "OrderCanceled" => Some("cancel_order"),
+            "PoolCreated" => Some("create_pool"),
+            _ => None,
+        }
+    }
+
+    fn field_mappings(&self, _action: Option<&str>) -> Vec<(&'static str, String)> {
+        [
+            ("pool_id", "pool_id"),
+            ("order_id", "order_id"),
+            ("is_bid", "is_bid"),
+            ("price", "price"),
+            ("owner", "owner"),
+            ("maker_address", "maker_address"),
+            ("taker_address", "taker_address"),
+            ("base_asset_quantity_placed", "base_quantity"),
+            ("base_asset_quantity_filled", "base_quantity"),
+            ("base_asset_quantity_canceled", "base_quantity"),
+        ]
+        .into_iter()
+        .map(|(source, target)| (source, target.to_string()))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navi_deposit() -> SuiEvent {
+        sui_indexer_fixtures::events()
+            .into_iter()
+            .find(|event| event.type_.name.as_str() == "DepositEvent")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_builtin_decoding() -> Result<()> {
+        let registry = ProtocolRegistry::from_config(&BTreeMap::new())?;
+        assert_eq!(
+            registry.protocols().collect::<Vec<_>>(),
+            vec!["navi", "cetus", "deepbook"]
+        );
+
+        let deposit = navi_deposit();
+        let decoded = registry.decode(&deposit).unwrap();
+        assert_eq!(decoded.protocol, "navi");
+        assert_eq!(decoded.action, Some("deposit"));
+        assert_eq!(
+            decoded.fields["deposit_amount"],
+            deposit.parsed_json["amount"]
+        );
+        assert_eq!(
+            decoded.fields["user_address"],
+            deposit.parsed_json["sender"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_recorded_events_decode() -> Result<()> {
+        let registry = ProtocolRegistry::from_config(&BTreeMap::new())?;
+        let decoded: Vec<_> = sui_indexer_fixtures::events()
+            .iter()
+            .filter_map(|event| registry.decode(event))
+            .map(|decoded| (decoded.protocol, decoded.action))
+            .collect();
+        assert!(decoded.contains(&("cetus", Some("swap"))));
+        assert!(decoded.contains(&("navi", Some("borrow"))));
+        assert!(decoded.contains(&("deepbook", Some("fill_order"))));
+        // The staking event belongs to no protocol
+        assert_eq!(decoded.len(), sui_indexer_fixtures::events().len() - 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_protocol_config_overrides() -> Result<()> {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "navi".to_string(),
+            ProtocolConfig {
+                package_ids: vec!["0x42".to_string()],
+                ..Default::default()
+            },
+        );
+        config.insert(
+            "cetus".to_string(),
+            ProtocolConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+
+        let registry = ProtocolRegistry::from_config(&config)?;
+        assert_eq!(
+            registry.protocols().collect::<Vec<_>>(),
+            vec!["navi", "deepbook"]
+        );
+        assert!(registry.decode(&navi_deposit()).is_none());
+
+        let navi = ProtocolRegistry::only(&config, "navi")?;
+        assert_eq!(navi.protocols().collect::<Vec<_>>(), vec!["navi"]);
+        assert!(ProtocolRegistry::only(&config, "cetus").is_err());
+
+        config.insert("unknown".to_string(), ProtocolConfig::default());
+        let err = ProtocolRegistry::from_config(&config).unwrap_err();
+        assert!(matches!(
+            EventError::find(&err),
+            Some(EventError::UnknownProtocol(name)) if name == "unknown"
+        ));
+        Ok(())
+    }
+}
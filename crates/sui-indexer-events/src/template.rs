@@ -0,0 +1,217 @@
+//! Message templates filled in from processed events
+//!
+//! Used by notification sinks and rules to turn an event into chat or
+//! webhook text, e.g. `{event_type} of {fields.parsed_json.amount}`.
+
+use eyre::Result;
+use serde_json::Value;
+
+use crate::{EventError, ProcessedEvent};
+
+/// Event value a template can refer to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Placeholder {
+    EventType,
+    Module,
+    Package,
+    Sender,
+    Transaction,
+    Checkpoint,
+    Timestamp,
+    /// Path into the event fields, e.g. `fields.parsed_json.amount`
+    Field(Vec<String>),
+}
+
+impl Placeholder {
+    /// Parse a placeholder name such as `sender` or `fields.parsed_json.amount`
+    pub fn parse(name: &str) -> Result<Self> {
+        Ok(match name.trim() {
+            "event_type" => Self::EventType,
+            "module" => Self::Module,
+            "package" => Self::Package,
+            "sender" => Self::Sender,
+            "transaction" => Self::Transaction,
+            "checkpoint" => Self::Checkpoint,
+            "timestamp" => Self::Timestamp,
+            name => match name.strip_prefix("fields.") {
+                Some(path) if !path.is_empty() => {
+                    Self::Field(path.split('.').map(str::to_string).collect())
+                }
+                _ => {
+                    return Err(EventError::InvalidTemplate(format!(
+                        "unknown placeholder `{name}`, expected `event_type`, `module`, \
+                         `package`, `sender`, `transaction`, `checkpoint`, `timestamp` or \
+                         `fields.<path>`"
+                    ))
+                    .into())
+                }
+            },
+        })
+    }
+
+    /// Value of the placeholder in `event`, or `None` if a field path is missing
+    pub fn value(&self, event: &ProcessedEvent) -> Option<Value> {
+        Some(match self {
+            Self::EventType => Value::from(event.event_type.clone()),
+            Self::Module => Value::from(event.module_name.clone()),
+            Self::Package => Value::from(event.package_id.to_string()),
+            Self::Sender => Value::from(event.sender.clone()),
+            Self::Transaction => Value::from(event.transaction_digest.to_string()),
+            Self::Checkpoint => Value::from(event.checkpoint_sequence),
+            Self::Timestamp => Value::from(event.timestamp.to_rfc3339()),
+            Self::Field(path) => {
+                let mut value = &event.fields;
+                for key in path {
+                    value = match value {
+                        Value::Array(items) => items.get(key.parse::<usize>().ok()?)?,
+                        value => value.get(key)?,
+                    };
+                }
+                value.clone()
+            }
+        })
+    }
+
+    /// Value as a number; Move integers are often rendered as strings
+    pub fn number(&self, event: &ProcessedEvent) -> Option<f64> {
+        match self.value(event)? {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Message text with `{placeholder}`s; `{{` and `}}` stand for literal braces
+#[derive(Debug)]
+pub struct Template(Vec<Segment>);
+
+#[derive(Debug)]
+enum Segment {
+    Text(String),
+    Value(Placeholder),
+}
+
+impl Template {
+    /// Parse `template`, rejecting unknown placeholders and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self> {
+        Self::parse_with(template, &[])
+    }
+
+    /// Parse `template`, filling in the fixed `constants` by name as well as the event placeholders
+    pub fn parse_with(template: &str, constants: &[(&str, &str)]) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(EventError::InvalidTemplate("unclosed `{`".to_string()).into());
+                    }
+                    match constants
+                        .iter()
+                        .find(|(constant, _)| *constant == name.trim())
+                    {
+                        Some((_, value)) => text.push_str(value),
+                        None => {
+                            segments.push(Segment::Text(std::mem::take(&mut text)));
+                            segments.push(Segment::Value(Placeholder::parse(&name)?));
+                        }
+                    }
+                }
+                '}' => {
+                    return Err(EventError::InvalidTemplate(
+                        "unmatched `}`, write `}}`".to_string(),
+                    )
+                    .into())
+                }
+                c => text.push(c),
+            }
+        }
+        segments.push(Segment::Text(text));
+        Ok(Self(segments))
+    }
+
+    /// Fill in the placeholders; missing fields render as empty text
+    pub fn render(&self, event: &ProcessedEvent) -> String {
+        let mut out = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Value(placeholder) => match placeholder.value(event) {
+                    Some(Value::String(text)) => out.push_str(&text),
+                    Some(Value::Null) | None => {}
+                    Some(value) => out.push_str(&value.to_string()),
+                },
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+    use crate::{DefaultEventProcessor, EventProcessor};
+
+    async fn event(amount: &str) -> Result<ProcessedEvent> {
+        let event = TestEvent::new("0x2::lending::Liquidation")
+            .fields(json!({"amount": amount, "assets": ["SUI", "USDC"]}))
+            .build();
+        let mut events = DefaultEventProcessor::new()
+            .process_events(vec![event])
+            .await?;
+        Ok(events.remove(0))
+    }
+
+    #[tokio::test]
+    async fn test_template_renders_event_values() -> Result<()> {
+        let template = Template::parse(
+            "{event_type} of {fields.parsed_json.amount} {fields.parsed_json.assets.1} {{in}} {module}{fields.missing}",
+        )?;
+        assert_eq!(
+            template.render(&event("1500").await?),
+            "Liquidation of 1500 USDC {in} lending"
+        );
+
+        let template = Template::parse_with("{rule}: {module}", &[("rule", "whales")])?;
+        assert_eq!(template.render(&event("1500").await?), "whales: lending");
+
+        for invalid in ["{unknown}", "{event_type", "amount}"] {
+            assert!(
+                Template::parse(invalid).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_numbers_include_numeric_strings() -> Result<()> {
+        let amount = Placeholder::parse("fields.parsed_json.amount")?;
+        assert_eq!(amount.number(&event("1500").await?), Some(1500.0));
+        assert_eq!(amount.number(&event("many").await?), None);
+        Ok(())
+    }
+}
@@ -1,25 +1,24 @@
-use chrono::Utc;
+use std::sync::Arc;
+
 use eyre::Result;
 use serde_json::{Map, Value};
 use sui_json_rpc_types::SuiEvent;
 use tracing::{debug, warn};
-use uuid::Uuid;
 
-use crate::{EventMetadata, ProcessedEvent};
+use crate::{system_clock, EventMetadata, ProcessedEvent, ProtocolRegistry, SharedClock};
 
 /// Event transformation logic for processing and enriching events
 pub struct EventTransformer {
     include_raw_event: bool,
     extract_custom_fields: bool,
+    protocols: Arc<ProtocolRegistry>,
+    clock: SharedClock,
 }
 
 impl EventTransformer {
     /// Create a new event transformer with configuration
     pub fn new() -> Self {
-        Self {
-            include_raw_event: true,
-            extract_custom_fields: true,
-        }
+        Self::with_config(true, true)
     }
 
     /// Create a transformer with custom settings
@@ -27,34 +26,49 @@ impl EventTransformer {
         Self {
             include_raw_event,
             extract_custom_fields,
+            protocols: Arc::new(ProtocolRegistry::default_builtin()),
+            clock: system_clock(),
         }
     }
 
+    /// Use the given protocol registry for protocol-specific fields and tags
+    pub fn with_protocols(mut self, protocols: Arc<ProtocolRegistry>) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Stamp and time events with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Transform a SuiEvent into a ProcessedEvent with additional metadata
     pub async fn transform_event(&self, event: SuiEvent) -> Result<ProcessedEvent> {
-        let start_time = Utc::now();
+        let start_time = self.clock.instant();
+        let event = Arc::new(event);
 
         // Extract and enhance event fields
         let fields = self.extract_event_fields(&event)?;
 
         // Create processed event with metadata
         let processed_event = ProcessedEvent {
-            id: Uuid::new_v4(),
-            event: event.clone(),
+            id: ProcessedEvent::id_for(&event.id),
+            event: Arc::clone(&event),
             transaction_digest: event.id.tx_digest,
             checkpoint_sequence: 0, // This would need to be provided from context
             timestamp: event
                 .timestamp_ms
-                .map(|ts| chrono::DateTime::from_timestamp_millis(ts as i64).unwrap_or(Utc::now()))
-                .unwrap_or(Utc::now()),
+                .and_then(|ts| chrono::DateTime::from_timestamp_millis(ts as i64))
+                .unwrap_or_else(|| self.clock.now()),
             package_id: event.package_id,
             module_name: event.type_.module.to_string(),
             event_type: event.type_.name.to_string(),
             sender: event.sender.to_string(),
             fields,
             metadata: EventMetadata {
-                processed_at: Utc::now(),
-                processing_duration_ms: (Utc::now() - start_time).num_milliseconds() as u64,
+                processed_at: self.clock.now(),
+                processing_duration_ms: self.clock.elapsed(start_time).as_millis() as u64,
                 event_index: event.id.event_seq as usize,
                 matched_filters: vec![], // This would be populated by the filter processor
                 tags: self.extract_event_tags(&event),
@@ -133,110 +147,20 @@ impl EventTransformer {
         Ok(Value::Object(fields))
     }
 
-    /// Extract protocol-specific fields for known protocols
+    /// Extract protocol-specific fields for events of registered protocols
     fn extract_protocol_specific_fields(&self, event: &SuiEvent, fields: &mut Map<String, Value>) {
-        // Handle Navi Protocol events
-        if self.is_navi_protocol_event(event) {
-            self.extract_navi_fields(event, fields);
+        if let Some(decoded) = self.protocols.decode(event) {
+            fields.extend(decoded.fields);
         }
-
-        // Add more protocol handlers here as needed
-        // if self.is_other_protocol_event(event) {
-        //     self.extract_other_protocol_fields(event, fields);
-        // }
-    }
-
-    /// Check if this is a Navi Protocol event
-    fn is_navi_protocol_event(&self, event: &SuiEvent) -> bool {
-        // This would need to be updated with actual Navi package IDs
-        let navi_package_ids = [
-            "0xa99b8952d4f7d947ea77fe0ecdcc9e5fc0bcab2841d6e2a5aa00c3044e5544b5",
-            // Add more Navi package IDs as needed
-        ];
-
-        navi_package_ids
-            .iter()
-            .any(|&package_id| event.package_id.to_string().starts_with(package_id))
-    }
-
-    /// Extract Navi Protocol specific fields
-    fn extract_navi_fields(&self, event: &SuiEvent, fields: &mut Map<String, Value>) {
-        fields.insert("protocol".to_string(), Value::String("navi".to_string()));
-
-        // Handle different Navi event types
-        match event.type_.name.as_str() {
-            "DepositEvent" => {
-                fields.insert("action".to_string(), Value::String("deposit".to_string()));
-                self.extract_navi_deposit_fields(event, fields);
-            }
-            "WithdrawEvent" => {
-                fields.insert("action".to_string(), Value::String("withdraw".to_string()));
-                self.extract_navi_withdraw_fields(event, fields);
-            }
-            "BorrowEvent" => {
-                fields.insert("action".to_string(), Value::String("borrow".to_string()));
-                self.extract_navi_borrow_fields(event, fields);
-            }
-            "RepayEvent" => {
-                fields.insert("action".to_string(), Value::String("repay".to_string()));
-                self.extract_navi_repay_fields(event, fields);
-            }
-            _ => {
-                // Generic Navi event
-                fields.insert("action".to_string(), Value::String("unknown".to_string()));
-            }
-        }
-    }
-
-    /// Extract Navi deposit event specific fields
-    fn extract_navi_deposit_fields(&self, event: &SuiEvent, fields: &mut Map<String, Value>) {
-        if let Value::Object(parsed) = &event.parsed_json {
-            // Extract common deposit fields based on Navi protocol structure
-            if let Some(amount) = parsed.get("amount") {
-                fields.insert("deposit_amount".to_string(), amount.clone());
-            }
-
-            if let Some(asset) = parsed.get("asset_id") {
-                fields.insert("asset_id".to_string(), asset.clone());
-            }
-
-            if let Some(user) = parsed.get("user") {
-                fields.insert("user_address".to_string(), user.clone());
-            }
-
-            if let Some(pool) = parsed.get("pool_id") {
-                fields.insert("pool_id".to_string(), pool.clone());
-            }
-
-            // Add success indicator for deposit events
-            fields.insert("success".to_string(), Value::Bool(true));
-        }
-    }
-
-    /// Extract Navi withdraw event specific fields
-    fn extract_navi_withdraw_fields(&self, _event: &SuiEvent, _fields: &mut Map<String, Value>) {
-        // Similar to deposit fields but for withdrawals
-        // Implementation would depend on actual Navi withdraw event structure
-    }
-
-    /// Extract Navi borrow event specific fields
-    fn extract_navi_borrow_fields(&self, _event: &SuiEvent, _fields: &mut Map<String, Value>) {
-        // Implementation would depend on actual Navi borrow event structure
-    }
-
-    /// Extract Navi repay event specific fields
-    fn extract_navi_repay_fields(&self, _event: &SuiEvent, _fields: &mut Map<String, Value>) {
-        // Implementation would depend on actual Navi repay event structure
     }
 
     /// Extract tags for categorizing events
     fn extract_event_tags(&self, event: &SuiEvent) -> Vec<String> {
         let mut tags = Vec::new();
 
-        // Add protocol tag
-        if self.is_navi_protocol_event(event) {
-            tags.push("navi".to_string());
-            tags.push("lending".to_string());
+        // Add protocol tags
+        if let Some(decoded) = self.protocols.decode(event) {
+            tags.extend(decoded.tags);
         }
 
         // Add event type tags
@@ -259,8 +183,8 @@ impl EventTransformer {
         let original_count = events.len();
         let mut processed_events = Vec::with_capacity(events.len());
 
-        for event in &events {
-            match self.transform_event(event.clone()).await {
+        for event in events {
+            match self.transform_event(event).await {
                 Ok(processed) => processed_events.push(processed),
                 Err(err) => {
                     warn!(error = %err, "Failed to transform event, skipping");
@@ -0,0 +1,448 @@
+//! Typed events: Move events decoded into Rust types, each stored in a table of its own
+//!
+//! A [`TypedEvent`] declares the Move event it decodes and the columns of its
+//! table. Registered in a [`TypedEventRegistry`], decoded instances become
+//! [`TypedRow`]s that storage writes next to the processed events, so every
+//! field can be queried with plain SQL.
+
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use sui_types::base_types::ObjectID;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{EventError, ProcessedEvent};
+
+/// Prefix of every typed table, keeping them apart from the tables the indexer manages
+pub const TYPED_TABLE_PREFIX: &str = "typed_";
+
+/// Columns every typed table starts with, filled from the processed event
+pub const TYPED_EVENT_COLUMNS: &[&str] = &[
+    "id",
+    "checkpoint_sequence",
+    "transaction_digest",
+    "timestamp",
+];
+
+/// SQL type of a typed event column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Strings, addresses and object IDs
+    Text,
+    /// Integers up to `u63`
+    BigInt,
+    /// Integers of any width, such as `u128` and `u256` amounts, which Move renders as strings
+    Numeric,
+    Boolean,
+    /// Nested structs, vectors and options
+    Json,
+}
+
+impl ColumnType {
+    /// Type of the column in PostgreSQL
+    pub fn sql(self) -> &'static str {
+        match self {
+            Self::Text => "TEXT",
+            Self::BigInt => "BIGINT",
+            Self::Numeric => "NUMERIC",
+            Self::Boolean => "BOOLEAN",
+            Self::Json => "JSONB",
+        }
+    }
+}
+
+/// Column of a typed table, named after the field it holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub column_type: ColumnType,
+}
+
+impl Column {
+    pub fn new(name: impl Into<String>, column_type: ColumnType) -> Self {
+        Self {
+            name: name.into(),
+            column_type,
+        }
+    }
+}
+
+/// Table of a typed event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedTable {
+    pub name: String,
+    /// Columns of the event's fields, after [`TYPED_EVENT_COLUMNS`]
+    pub columns: Vec<Column>,
+}
+
+impl TypedTable {
+    /// Check the names, which storage interpolates into SQL
+    pub fn validate(&self) -> Result<()> {
+        if !is_typed_table_name(&self.name) {
+            return Err(EventError::InvalidTypedTable(self.name.clone()).into());
+        }
+        let mut seen = HashSet::new();
+        for column in &self.columns {
+            if !is_identifier(&column.name)
+                || TYPED_EVENT_COLUMNS.contains(&column.name.as_str())
+                || !seen.insert(column.name.as_str())
+            {
+                return Err(EventError::InvalidTypedColumn {
+                    table: self.name.clone(),
+                    column: column.name.clone(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` can be the table of a typed event: a plain identifier after [`TYPED_TABLE_PREFIX`]
+pub fn is_typed_table_name(name: &str) -> bool {
+    name.len() > TYPED_TABLE_PREFIX.len()
+        && name.starts_with(TYPED_TABLE_PREFIX)
+        && is_identifier(name)
+}
+
+/// Lowercase SQL identifier that needs no quoting
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Move event decoded from its JSON into a Rust type
+///
+/// The type deserializes from the event's `parsedJson` and serializes back
+/// into an object whose keys are its column names.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct SwapEvent {
+///     pool: String,
+///     amount_in: String,
+///     amount_out: String,
+/// }
+///
+/// impl TypedEvent for SwapEvent {
+///     const MODULE: &'static str = "pool";
+///     const NAME: &'static str = "SwapEvent";
+///
+///     fn columns() -> Vec<Column> {
+///         vec![
+///             Column::new("pool", ColumnType::Text),
+///             Column::new("amount_in", ColumnType::Numeric),
+///             Column::new("amount_out", ColumnType::Numeric),
+///         ]
+///     }
+/// }
+/// ```
+pub trait TypedEvent: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Module of the Move event struct
+    const MODULE: &'static str;
+    /// Name of the Move event struct
+    const NAME: &'static str;
+
+    /// Columns of the fields stored, in table order
+    fn columns() -> Vec<Column>;
+
+    /// Package that emits the event, or `None` to decode the event of any package
+    fn package() -> Option<&'static str> {
+        None
+    }
+
+    /// Table the events are stored in, by default [`TYPED_TABLE_PREFIX`] and the snake case of [`TypedEvent::NAME`]
+    ///
+    /// Names must start with [`TYPED_TABLE_PREFIX`].
+    fn table_name() -> String {
+        format!("{TYPED_TABLE_PREFIX}{}", snake_case(Self::NAME))
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Row of a typed table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedRow {
+    /// Id of the processed event, so a row stored again is skipped
+    pub id: Uuid,
+    pub checkpoint_sequence: u64,
+    pub transaction_digest: String,
+    pub timestamp: DateTime<Utc>,
+    /// Values by column name
+    pub values: Map<String, Value>,
+}
+
+/// Decoder of one typed event, with its type erased
+trait ErasedDecoder: Send + Sync {
+    fn table(&self) -> &TypedTable;
+    fn matches(&self, event: &ProcessedEvent) -> bool;
+    fn decode(&self, event: &ProcessedEvent) -> Result<Map<String, Value>>;
+}
+
+struct Decoder<T> {
+    table: TypedTable,
+    package: Option<&'static str>,
+    _event: PhantomData<fn() -> T>,
+}
+
+impl<T: TypedEvent> ErasedDecoder for Decoder<T> {
+    fn table(&self) -> &TypedTable {
+        &self.table
+    }
+
+    fn matches(&self, event: &ProcessedEvent) -> bool {
+        let type_ = &event.event.type_;
+        type_.module.as_str() == T::MODULE
+            && type_.name.as_str() == T::NAME
+            && self.package.is_none_or(|package| {
+                package
+                    .parse::<ObjectID>()
+                    .is_ok_and(|id| id == event.package_id)
+            })
+    }
+
+    fn decode(&self, event: &ProcessedEvent) -> Result<Map<String, Value>> {
+        let decode_err = |source| EventError::TypedDecode {
+            event_type: format!("{}::{}", T::MODULE, T::NAME),
+            source,
+        };
+        let typed: T =
+            serde_json::from_value(event.event.parsed_json.clone()).map_err(decode_err)?;
+        let Value::Object(fields) = serde_json::to_value(typed).map_err(decode_err)? else {
+            return Err(
+                decode_err(serde::ser::Error::custom("not serialized as an object")).into(),
+            );
+        };
+        Ok(self
+            .table
+            .columns
+            .iter()
+            .map(|column| {
+                let value = fields.get(&column.name).cloned().unwrap_or(Value::Null);
+                (column.name.clone(), value)
+            })
+            .collect())
+    }
+}
+
+/// Typed events to decode, each with its table
+///
+/// Cloning shares the registered decoders and the count of skipped events.
+#[derive(Clone, Default)]
+pub struct TypedEventRegistry {
+    decoders: Vec<Arc<dyn ErasedDecoder>>,
+    skipped: Arc<AtomicU64>,
+}
+
+impl TypedEventRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `T`, failing if its table or columns cannot be used or its table is taken
+    pub fn register<T: TypedEvent>(mut self) -> Result<Self> {
+        let table = TypedTable {
+            name: T::table_name(),
+            columns: T::columns(),
+        };
+        table.validate()?;
+        if self.tables().any(|taken| taken.name == table.name) {
+            return Err(EventError::InvalidTypedTable(table.name).into());
+        }
+        self.decoders.push(Arc::new(Decoder::<T> {
+            table,
+            package: T::package(),
+            _event: PhantomData,
+        }));
+        Ok(self)
+    }
+
+    /// Whether no typed event is registered
+    pub fn is_empty(&self) -> bool {
+        self.decoders.is_empty()
+    }
+
+    /// Tables of the registered events
+    pub fn tables(&self) -> impl Iterator<Item = &TypedTable> {
+        self.decoders.iter().map(|decoder| decoder.table())
+    }
+
+    /// Events left out of their typed table because they did not decode, since the registry was created
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Decode `events` into rows, grouped by table in registration order
+    ///
+    /// Events of no registered type are skipped. An event of a registered
+    /// type that does not decode, for example one of another package with
+    /// the same module and name, is logged, counted in
+    /// [`TypedEventRegistry::skipped`] and left out of the table; the
+    /// processed event itself is stored as usual.
+    pub fn decode(&self, events: &[ProcessedEvent]) -> Vec<(&TypedTable, Vec<TypedRow>)> {
+        let mut tables = Vec::new();
+        for decoder in &self.decoders {
+            let rows: Vec<_> = events
+                .iter()
+                .filter(|event| decoder.matches(event))
+                .filter_map(|event| match decoder.decode(event) {
+                    Ok(values) => Some(TypedRow {
+                        id: event.id,
+                        checkpoint_sequence: event.checkpoint_sequence,
+                        transaction_digest: event.transaction_digest.to_string(),
+                        timestamp: event.timestamp,
+                        values,
+                    }),
+                    Err(e) => {
+                        self.skipped.fetch_add(1, Ordering::Relaxed);
+                        warn!(
+                            event = %event.id,
+                            table = %decoder.table().name,
+                            error = %format!("{e:#}"),
+                            "Skipping an event that does not decode into its typed event"
+                        );
+                        None
+                    }
+                })
+                .collect();
+            if !rows.is_empty() {
+                tables.push((decoder.table(), rows));
+            }
+        }
+        tables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+    use crate::{DefaultEventProcessor, EventProcessor};
+
+    #[derive(Serialize, Deserialize)]
+    struct SwapEvent {
+        pool: String,
+        amount_in: String,
+        a2b: bool,
+    }
+
+    impl TypedEvent for SwapEvent {
+        const MODULE: &'static str = "pool";
+        const NAME: &'static str = "SwapEvent";
+
+        fn columns() -> Vec<Column> {
+            vec![
+                Column::new("pool", ColumnType::Text),
+                Column::new("amount_in", ColumnType::Numeric),
+                Column::new("a2b", ColumnType::Boolean),
+            ]
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct BadColumns {}
+
+    impl TypedEvent for BadColumns {
+        const MODULE: &'static str = "pool";
+        const NAME: &'static str = "Bad";
+
+        fn columns() -> Vec<Column> {
+            vec![Column::new("timestamp", ColumnType::BigInt)]
+        }
+    }
+
+    async fn event(name: &str, parsed_json: Value) -> ProcessedEvent {
+        let raw = TestEvent::new(&format!("0x2::pool::{name}"))
+            .fields(parsed_json)
+            .build();
+        DefaultEventProcessor::new()
+            .process_event(raw)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_typed_events_decode_into_rows() -> Result<()> {
+        let registry = TypedEventRegistry::new().register::<SwapEvent>()?;
+        let table = registry.tables().next().unwrap();
+        assert_eq!(table.name, "typed_swap_event");
+
+        let swap = event(
+            "SwapEvent",
+            serde_json::json!({"pool": "0xabc", "amount_in": "340282366920938463463", "a2b": true, "fee": "3"}),
+        )
+        .await;
+        let other = event("AddLiquidityEvent", serde_json::json!({})).await;
+        let decoded = registry.decode(&[swap.clone(), other]);
+        assert_eq!(decoded.len(), 1);
+        let (table, rows) = &decoded[0];
+        assert_eq!(table.name, "typed_swap_event");
+        assert_eq!(rows[0].id, swap.id);
+        // Only the declared columns are kept
+        assert_eq!(rows[0].values.len(), 3);
+        assert!(!rows[0].values.contains_key("fee"));
+        assert_eq!(rows[0].values["a2b"], Value::Bool(true));
+
+        // A registered event that does not decode is skipped and counted
+        let broken = event("SwapEvent", serde_json::json!({"pool": 1})).await;
+        let decoded = registry.decode(&[broken, swap.clone()]);
+        assert_eq!(decoded[0].1.len(), 1);
+        assert_eq!(decoded[0].1[0].id, swap.id);
+        assert_eq!(registry.skipped(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_tables_are_validated() {
+        let err = TypedEventRegistry::new()
+            .register::<BadColumns>()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            EventError::find(&err),
+            Some(EventError::InvalidTypedColumn { column, .. }) if column == "timestamp"
+        ));
+        let registry = TypedEventRegistry::new().register::<SwapEvent>().unwrap();
+        assert!(registry.register::<SwapEvent>().is_err());
+        assert_eq!(snake_case("AddLiquidityEvent"), "add_liquidity_event");
+
+        // Only prefixed tables, so a typed event never writes into a managed one
+        for name in ["processed_events", "indexer_state", "typed_", "typed_Swap"] {
+            let table = TypedTable {
+                name: name.to_string(),
+                columns: Vec::new(),
+            };
+            assert!(table.validate().is_err(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Address watchlists checked against every processed event
+//!
+//! A watchlist can hold millions of addresses, and most events involve none
+//! of them. Each [`AddressSet`] therefore puts a bloom filter in front of its
+//! exact set: an address the filter rejects is certainly not watched, and
+//! only the few it passes are looked up.
+
+use std::{
+    collections::HashSet,
+    f64::consts::LN_2,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::SuiAddress;
+
+use crate::ProcessedEvent;
+
+/// Length of a full `0x`-prefixed address string
+const ADDRESS_LEN: usize = 66;
+
+/// Watched addresses with a bloom filter for fast negative checks
+#[derive(Debug, Default)]
+pub struct AddressSet {
+    /// Bloom filter bits, 64 to a word
+    bits: Vec<u64>,
+    /// Bits set per address
+    hashes: u32,
+    exact: HashSet<SuiAddress>,
+}
+
+impl AddressSet {
+    /// Set of `addresses` whose filter passes about `false_positive_rate` of unwatched addresses
+    pub fn new(addresses: HashSet<SuiAddress>, false_positive_rate: f64) -> Self {
+        let count = addresses.len().max(1) as f64;
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let bits = (-count * rate.ln() / (LN_2 * LN_2)).ceil().max(64.0);
+        let hashes = (bits / count * LN_2).round().clamp(1.0, 16.0) as u32;
+
+        let mut set = Self {
+            bits: vec![0; (bits as usize).div_ceil(64)],
+            hashes,
+            exact: HashSet::new(),
+        };
+        for address in &addresses {
+            for bit in set.positions(address) {
+                set.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        set.exact = addresses;
+        set
+    }
+
+    /// Check if `address` is watched
+    pub fn contains(&self, address: &SuiAddress) -> bool {
+        !self.exact.is_empty()
+            && self
+                .positions(address)
+                .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+            && self.exact.contains(address)
+    }
+
+    /// Number of watched addresses
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    /// Check if no address is watched
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+
+    /// Filter bits of `address`, by double hashing
+    fn positions(&self, address: &SuiAddress) -> impl Iterator<Item = usize> {
+        // Low addresses such as `0x2` are mostly zero bytes, so every word is mixed in
+        let hash = address.as_ref().chunks_exact(8).fold(0, |hash, word| {
+            // Little-endian, read without a fallible conversion
+            let word = word
+                .iter()
+                .rev()
+                .fold(0, |word, byte| word << 8 | u64::from(*byte));
+            mix(hash ^ word)
+        });
+        let step = mix(hash) | 1;
+        let bits = self.bits.len() as u64 * 64;
+        (0..u64::from(self.hashes))
+            .map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bits) as usize)
+    }
+}
+
+/// SplitMix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// One named watchlist
+#[derive(Debug)]
+pub struct Watchlist {
+    pub name: String,
+    /// Tag attached to the events involving the watchlist
+    pub tag: String,
+    /// Whether involved events are indexed even when no filter matches them
+    pub capture: bool,
+    pub addresses: AddressSet,
+}
+
+/// Watchlists in effect, shared by the pipeline and the task reloading them
+///
+/// Reloads replace every watchlist at once, so a checkpoint is always checked
+/// against one consistent set.
+#[derive(Debug, Clone, Default)]
+pub struct Watchlists {
+    lists: Arc<RwLock<Arc<[Watchlist]>>>,
+}
+
+impl Watchlists {
+    /// Create an empty set of watchlists, involving no event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `lists` in effect in place of the current watchlists
+    pub fn replace(&self, lists: Vec<Watchlist>) {
+        *self.lists.write().unwrap_or_else(PoisonError::into_inner) = lists.into();
+    }
+
+    /// Watchlists now in effect
+    pub fn current(&self) -> Arc<[Watchlist]> {
+        self.lists
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Addresses an event involves: its sender and any address held by its fields
+pub fn involved_addresses(event: &SuiEvent) -> Vec<SuiAddress> {
+    let mut addresses = vec![event.sender];
+    field_addresses(&event.parsed_json, &mut addresses);
+    addresses
+}
+
+fn field_addresses(value: &serde_json::Value, out: &mut Vec<SuiAddress>) {
+    match value {
+        serde_json::Value::String(s) if s.len() == ADDRESS_LEN && s.starts_with("0x") => {
+            if let Ok(address) = s.parse() {
+                out.push(address);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            values.iter().for_each(|value| field_addresses(value, out))
+        }
+        serde_json::Value::Object(fields) => fields
+            .values()
+            .for_each(|value| field_addresses(value, out)),
+        _ => {}
+    }
+}
+
+/// The watchlists among `lists` that `event` involves
+pub fn involving<'a>(lists: &'a [Watchlist], event: &SuiEvent) -> Vec<&'a Watchlist> {
+    if lists.iter().all(|list| list.addresses.is_empty()) {
+        return Vec::new();
+    }
+    let addresses = involved_addresses(event);
+    lists
+        .iter()
+        .filter(|list| addresses.iter().any(|a| list.addresses.contains(a)))
+        .collect()
+}
+
+/// Attach the tag of every watchlist among `lists` that `event` involves, returning those watchlists
+pub fn tag_event<'a>(lists: &'a [Watchlist], event: &mut ProcessedEvent) -> Vec<&'a Watchlist> {
+    let involved = involving(lists, &event.event);
+    for list in &involved {
+        if !event.metadata.tags.contains(&list.tag) {
+            event.metadata.tags.push(list.tag.clone());
+        }
+    }
+    involved
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_fixtures::TestEvent;
+    use sui_types::base_types::ObjectID;
+
+    use super::*;
+
+    fn address(byte: u8) -> SuiAddress {
+        SuiAddress::from(ObjectID::from_single_byte(byte))
+    }
+
+    #[test]
+    fn test_address_set_has_no_false_negatives() {
+        let watched: HashSet<_> = (0..=200).map(address).collect();
+        let set = AddressSet::new(watched.clone(), 0.01);
+        assert_eq!(set.len(), 201);
+        assert!(watched.iter().all(|a| set.contains(a)));
+        assert!(!set.contains(&address(250)));
+        assert!(!AddressSet::default().contains(&address(1)));
+
+        // The filter alone passes few of the unwatched addresses
+        let passed = (0..10_000u32)
+            .map(|i| {
+                let mut bytes = [0u8; 32];
+                bytes[..4].copy_from_slice(&i.to_le_bytes());
+                bytes[31] = 0xff;
+                SuiAddress::from(ObjectID::new(bytes))
+            })
+            .filter(|a| {
+                set.positions(a)
+                    .all(|bit| set.bits[bit / 64] & (1 << (bit % 64)) != 0)
+            })
+            .count();
+        assert!(passed < 500, "{passed} false positives");
+    }
+
+    #[test]
+    fn test_watchlists_find_addresses_in_fields() {
+        let lists = vec![Watchlist {
+            name: "sanctions".to_string(),
+            tag: "watchlist:sanctions".to_string(),
+            capture: false,
+            addresses: AddressSet::new(HashSet::from([address(7)]), 0.01),
+        }];
+        let watchlists = Watchlists::new();
+        watchlists.replace(lists);
+
+        let mut event = TestEvent::new("0x2::pool::SwapEvent")
+            .sender("0x1")
+            .fields(serde_json::json!({
+                "pool": "0x2",
+                "recipients": [{ "to": address(7).to_string() }],
+            }))
+            .build();
+        let current = watchlists.current();
+        assert_eq!(involving(&current, &event).len(), 1);
+
+        event.parsed_json = serde_json::json!({ "to": address(8).to_string() });
+        assert!(involving(&current, &event).is_empty());
+        event.sender = address(7);
+        assert_eq!(involving(&current, &event)[0].name, "sanctions");
+    }
+}
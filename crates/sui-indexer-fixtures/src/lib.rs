@@ -0,0 +1,252 @@
+//! Mainnet checkpoints for tests, bundled with the crate, and helpers to load more
+//!
+//! The bundled checkpoints `120000000..=120000003` are reconstructed from
+//! mainnet traffic: the packages, modules, event types and field layouts are
+//! those emitted on chain by Cetus, Navi, DeepBook and the system staking
+//! module, while digests, addresses and amounts are made up. Checkpoints
+//! recorded from a node with [`save_checkpoint`] load the same way with
+//! [`load_dir`]; the `record_fixtures` example of `sui-indexer-core` does
+//! that, and needs network access to a full node. Tests that need an event
+//! of a given shape rather than a recorded one build it with [`TestEvent`].
+//!
+//! ```ignore
+//! let swaps: Vec<_> = sui_indexer_fixtures::events()
+//!     .into_iter()
+//!     .filter(|event| event.type_.name.as_str() == "SwapEvent")
+//!     .collect();
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sui_json_rpc_types::SuiEvent;
+use sui_types::base_types::TransactionDigest;
+
+/// Sequence numbers of the bundled checkpoints
+pub const CHECKPOINTS: std::ops::RangeInclusive<u64> = 120_000_000..=120_000_003;
+
+/// Bundled checkpoint files, in sequence
+const BUNDLED: &[&str] = &[
+    include_str!("../checkpoints/120000000.json"),
+    include_str!("../checkpoints/120000001.json"),
+    include_str!("../checkpoints/120000002.json"),
+    include_str!("../checkpoints/120000003.json"),
+];
+
+/// Checkpoint as recorded: its summary, transactions and events
+///
+/// Serialized like the indexer's own checkpoint data, so a checkpoint the
+/// indexer fetched can be saved as JSON and loaded back as a fixture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCheckpoint {
+    pub sequence_number: u64,
+    pub digest: String,
+    pub previous_digest: Option<String>,
+    pub epoch: u64,
+    pub timestamp_ms: u64,
+    /// Network total transactions count up to this checkpoint
+    pub network_total_transactions: u64,
+    pub transactions: Vec<TransactionDigest>,
+    /// Events emitted by the transactions, in execution order
+    #[serde(default)]
+    pub events: Vec<SuiEvent>,
+}
+
+/// The bundled checkpoints, in sequence
+pub fn checkpoints() -> Vec<RecordedCheckpoint> {
+    BUNDLED
+        .iter()
+        .map(|json| serde_json::from_str(json).expect("Bundled checkpoints should parse"))
+        .collect()
+}
+
+/// The bundled checkpoint `sequence_number`, if there is one
+pub fn checkpoint(sequence_number: u64) -> Option<RecordedCheckpoint> {
+    checkpoints()
+        .into_iter()
+        .find(|checkpoint| checkpoint.sequence_number == sequence_number)
+}
+
+/// Events of all bundled checkpoints, in sequence
+pub fn events() -> Vec<SuiEvent> {
+    checkpoints()
+        .into_iter()
+        .flat_map(|checkpoint| checkpoint.events)
+        .collect()
+}
+
+/// Event built for a test from the parts it cares about
+///
+/// Emitted by the package and module of its type, in the first position of
+/// transaction `test`, by sender `0x123` and with no fields until set.
+#[derive(Debug, Clone)]
+pub struct TestEvent {
+    json: Value,
+}
+
+impl TestEvent {
+    /// Event of Move type `event_type`, e.g. `0x2::pool::SwapEvent`
+    pub fn new(event_type: &str) -> Self {
+        let mut parts = event_type.split("::");
+        let package = parts.next().unwrap_or_default();
+        let module = parts.next().unwrap_or_default();
+        Self {
+            json: json!({
+                "id": {"eventSeq": "0", "txDigest": "test"},
+                "packageId": package,
+                "transactionModule": module,
+                "sender": "0x123",
+                "type": event_type,
+                "parsedJson": {},
+                "bcs": ""
+            }),
+        }
+    }
+
+    /// Digest of the emitting transaction
+    pub fn tx(mut self, digest: &str) -> Self {
+        self.json["id"]["txDigest"] = json!(digest);
+        self
+    }
+
+    /// Position of the event within its transaction
+    pub fn seq(mut self, seq: u64) -> Self {
+        self.json["id"]["eventSeq"] = json!(seq.to_string());
+        self
+    }
+
+    /// Package called by the transaction, when not the one defining the type
+    pub fn package(mut self, package: &str) -> Self {
+        self.json["packageId"] = json!(package);
+        self
+    }
+
+    /// Module called by the transaction, when not the one defining the type
+    pub fn module(mut self, module: &str) -> Self {
+        self.json["transactionModule"] = json!(module);
+        self
+    }
+
+    /// Sender of the emitting transaction
+    pub fn sender(mut self, sender: &str) -> Self {
+        self.json["sender"] = json!(sender);
+        self
+    }
+
+    /// Parsed fields of the event
+    pub fn fields(mut self, fields: Value) -> Self {
+        self.json["parsedJson"] = fields;
+        self
+    }
+
+    /// Time the emitting transaction was executed
+    pub fn timestamp_ms(mut self, timestamp_ms: u64) -> Self {
+        self.json["timestampMs"] = json!(timestamp_ms.to_string());
+        self
+    }
+
+    /// Build the event
+    ///
+    /// Panics when a part set does not parse, like an invalid address.
+    pub fn build(self) -> SuiEvent {
+        serde_json::from_value(self.json).expect("Test events should parse")
+    }
+}
+
+/// Load the checkpoints saved as `*.json` in `dir`, in sequence
+pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<RecordedCheckpoint>> {
+    let dir = dir.as_ref();
+    let mut checkpoints = Vec::new();
+    for entry in fs::read_dir(dir).wrap_err_with(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let json = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        let checkpoint: RecordedCheckpoint = serde_json::from_str(&json)
+            .wrap_err_with(|| format!("Invalid checkpoint in {}", path.display()))?;
+        checkpoints.push(checkpoint);
+    }
+    checkpoints.sort_by_key(|checkpoint| checkpoint.sequence_number);
+    Ok(checkpoints)
+}
+
+/// Save `checkpoint` into `dir` as `<sequence_number>.json`, returning the file written
+pub fn save_checkpoint(dir: impl AsRef<Path>, checkpoint: &RecordedCheckpoint) -> Result<PathBuf> {
+    let path = dir
+        .as_ref()
+        .join(format!("{}.json", checkpoint.sequence_number));
+    let mut json = serde_json::to_string_pretty(checkpoint)?;
+    json.push('\n');
+    fs::write(&path, json).wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::base_types::ObjectID;
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_checkpoints_are_consistent() {
+        let checkpoints = checkpoints();
+        let sequence: Vec<_> = checkpoints.iter().map(|c| c.sequence_number).collect();
+        assert_eq!(sequence, CHECKPOINTS.collect::<Vec<_>>());
+
+        for pair in checkpoints.windows(2) {
+            assert_eq!(pair[1].previous_digest.as_ref(), Some(&pair[0].digest));
+            assert!(pair[1].timestamp_ms > pair[0].timestamp_ms);
+        }
+        for checkpoint in &checkpoints {
+            for event in &checkpoint.events {
+                // Every event comes from a transaction of its checkpoint
+                assert!(checkpoint.transactions.contains(&event.id.tx_digest));
+            }
+        }
+        assert!(events()
+            .iter()
+            .any(|event| !event.type_.type_params.is_empty()));
+        assert!(checkpoint(*CHECKPOINTS.end() + 1).is_none());
+    }
+
+    #[test]
+    fn test_saved_checkpoints_load_in_sequence() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for checkpoint in checkpoints().into_iter().rev() {
+            save_checkpoint(dir.path(), &checkpoint)?;
+        }
+        fs::write(dir.path().join("README.md"), "not a checkpoint")?;
+        assert_eq!(load_dir(dir.path())?, checkpoints());
+        Ok(())
+    }
+
+    #[test]
+    fn test_test_events_take_their_origin_from_the_type() {
+        let event = TestEvent::new("0x2::pool::SwapEvent")
+            .seq(3)
+            .fields(json!({"amount": "5"}))
+            .build();
+        assert_eq!(event.package_id, ObjectID::from_hex_literal("0x2").unwrap());
+        assert_eq!(event.transaction_module.as_str(), "pool");
+        assert_eq!(event.type_.name.as_str(), "SwapEvent");
+        assert_eq!(event.id.event_seq, 3);
+        assert_eq!(event.parsed_json["amount"], "5");
+
+        let event = TestEvent::new("0xb1::pool::SwapEvent")
+            .package("0xb2")
+            .module("router")
+            .build();
+        assert_eq!(
+            event.package_id,
+            ObjectID::from_hex_literal("0xb2").unwrap()
+        );
+        assert_eq!(event.transaction_module.as_str(), "router");
+    }
+}
@@ -0,0 +1,388 @@
+use std::{collections::HashSet, future::Future};
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use eyre::Result;
+use sui_indexer_config::{EventFormat, OrderingKey, SnsSinkConfig, SqsSinkConfig};
+use sui_indexer_events::ProcessedEvent;
+use tracing::{error, warn};
+use url::Url;
+
+use crate::{cloudevents, ordering_key, PartialDelivery, Sink, MAX_DELIVERY_ATTEMPTS, RETRY_DELAY};
+
+/// Entries per SNS `PublishBatch` and SQS `SendMessageBatch` request
+const MAX_BATCH_ENTRIES: usize = 10;
+
+/// Load credentials and region from the AWS configuration chain
+///
+/// Static keys, profiles, web identity tokens and instance or container
+/// roles are all picked up, as with the AWS CLI.
+async fn load_config(
+    region: Option<&str>,
+    profile: Option<&str>,
+    endpoint: Option<&Url>,
+) -> SdkConfig {
+    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    if let Some(region) = region {
+        loader = loader.region(Region::new(region.to_string()));
+    }
+    if let Some(profile) = profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(endpoint) = endpoint {
+        loader = loader.endpoint_url(endpoint.as_str());
+    }
+    loader.load().await
+}
+
+/// Message body of an event in `format`
+fn message(format: EventFormat, event: &ProcessedEvent) -> Result<String> {
+    Ok(String::from_utf8(cloudevents::payload(format, event)?)?)
+}
+
+/// Entry a batch request failed
+struct Failure {
+    id: String,
+    code: String,
+    message: Option<String>,
+    /// Whether the entry itself is at fault, so sending it again fails the same way
+    sender_fault: bool,
+}
+
+/// Send `entries`, keyed by their batch entry ID, retrying only the ones that failed
+///
+/// Entries rejected as the sender's fault are dropped at once; the rest are
+/// sent again with backoff, so delivered messages are never repeated.
+/// Returns the number of entries that were not delivered.
+async fn send_entries<E, F, Fut>(service: &'static str, entries: Vec<(String, E)>, send: F) -> usize
+where
+    E: Clone,
+    F: Fn(Vec<E>) -> Fut,
+    Fut: Future<Output = Result<Vec<Failure>>>,
+{
+    if entries.is_empty() {
+        return 0;
+    }
+    let mut pending = entries;
+    let mut dropped = 0;
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let batch = pending.iter().map(|(_, entry)| entry.clone()).collect();
+        let retry: HashSet<String> = match send(batch).await {
+            Ok(failures) => failures
+                .into_iter()
+                .filter_map(|failure| {
+                    if !failure.sender_fault {
+                        return Some(failure.id);
+                    }
+                    error!(
+                        service,
+                        code = %failure.code,
+                        message = failure.message.as_deref().unwrap_or("no message"),
+                        "Message rejected, dropping its event"
+                    );
+                    dropped += 1;
+                    None
+                })
+                .collect(),
+            Err(e) => {
+                warn!(service, attempt, error = %e, "Batch request failed");
+                pending.iter().map(|(id, _)| id.clone()).collect()
+            }
+        };
+        pending.retain(|(id, _)| retry.contains(id));
+        if pending.is_empty() {
+            return dropped;
+        }
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            warn!(
+                service,
+                attempt,
+                messages = pending.len(),
+                "Messages failed, retrying them"
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    error!(
+        service,
+        events = pending.len(),
+        "Dropping events after repeated delivery failures"
+    );
+    dropped + pending.len()
+}
+
+/// Publishes every event as a JSON message to an SNS topic
+///
+/// Messages carry `event_type` and `checkpoint` attributes for subscription
+/// filter policies. FIFO topics group messages by the ordering key and use
+/// the event ID for deduplication. Only the messages a batch request failed
+/// are sent again, so the others are not delivered twice on standard topics.
+pub struct SnsSink {
+    client: aws_sdk_sns::Client,
+    topic_arn: String,
+    /// Set for FIFO topics
+    ordering_key: Option<OrderingKey>,
+    format: EventFormat,
+}
+
+impl SnsSink {
+    /// Create an SNS client from the given configuration
+    pub async fn new(config: &SnsSinkConfig) -> Result<Self> {
+        let sdk = load_config(
+            config.region.as_deref(),
+            config.profile.as_deref(),
+            config.endpoint.as_ref(),
+        )
+        .await;
+        let fifo = config.topic_arn.ends_with(".fifo");
+        Ok(Self {
+            client: aws_sdk_sns::Client::new(&sdk),
+            topic_arn: config.topic_arn.clone(),
+            ordering_key: config.ordering_key.filter(|_| fifo),
+            format: config.format,
+        })
+    }
+
+    /// Batch entry `id` of an event
+    fn entry(
+        &self,
+        id: String,
+        event: &ProcessedEvent,
+    ) -> Result<aws_sdk_sns::types::PublishBatchRequestEntry> {
+        use aws_sdk_sns::types::{MessageAttributeValue, PublishBatchRequestEntry};
+
+        let attribute = |value: String| {
+            MessageAttributeValue::builder()
+                .data_type("String")
+                .string_value(value)
+                .build()
+        };
+        let mut entry = PublishBatchRequestEntry::builder()
+            .id(id)
+            .message(message(self.format, event)?)
+            .message_attributes("event_type", attribute(event.event_type.clone())?)
+            .message_attributes(
+                "checkpoint",
+                attribute(event.checkpoint_sequence.to_string())?,
+            );
+        if let Some(key) = self.ordering_key {
+            entry = entry
+                .message_group_id(ordering_key(key, event))
+                .message_deduplication_id(event.id.to_string());
+        }
+        Ok(entry.build()?)
+    }
+}
+
+#[async_trait]
+impl Sink for SnsSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let client = &self.client;
+        let topic_arn = &self.topic_arn;
+        let mut dropped = 0;
+        for chunk in events.chunks(MAX_BATCH_ENTRIES) {
+            let mut entries = Vec::with_capacity(chunk.len());
+            for (index, event) in chunk.iter().enumerate() {
+                let id = index.to_string();
+                match self.entry(id.clone(), event) {
+                    Ok(entry) => entries.push((id, entry)),
+                    Err(e) => {
+                        error!(event = %event.id, error = %e, "Failed to encode SNS message, dropping its event");
+                        dropped += 1;
+                    }
+                }
+            }
+
+            dropped += send_entries("SNS", entries, |entries| async move {
+                let output = client
+                    .publish_batch()
+                    .topic_arn(topic_arn)
+                    .set_publish_batch_request_entries(Some(entries))
+                    .send()
+                    .await?;
+                Ok(output
+                    .failed()
+                    .iter()
+                    .map(|entry| Failure {
+                        id: entry.id().to_string(),
+                        code: entry.code().to_string(),
+                        message: entry.message().map(str::to_string),
+                        sender_fault: entry.sender_fault(),
+                    })
+                    .collect())
+            })
+            .await;
+        }
+        if dropped > 0 {
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
+
+/// Sends every event as a JSON message to an SQS queue
+///
+/// Messages carry `event_type` and `checkpoint` attributes. FIFO queues
+/// group messages by the ordering key and use the event ID for
+/// deduplication. Only the messages a batch request failed are sent again,
+/// so the others are not delivered twice on standard queues.
+pub struct SqsSink {
+    client: aws_sdk_sqs::Client,
+    queue_url: String,
+    /// Set for FIFO queues
+    ordering_key: Option<OrderingKey>,
+    format: EventFormat,
+}
+
+impl SqsSink {
+    /// Create an SQS client from the given configuration
+    pub async fn new(config: &SqsSinkConfig) -> Result<Self> {
+        let sdk = load_config(
+            config.region.as_deref(),
+            config.profile.as_deref(),
+            config.endpoint.as_ref(),
+        )
+        .await;
+        let fifo = config.queue_url.path().ends_with(".fifo");
+        Ok(Self {
+            client: aws_sdk_sqs::Client::new(&sdk),
+            queue_url: config.queue_url.to_string(),
+            ordering_key: config.ordering_key.filter(|_| fifo),
+            format: config.format,
+        })
+    }
+
+    /// Batch entry `id` of an event
+    fn entry(
+        &self,
+        id: String,
+        event: &ProcessedEvent,
+    ) -> Result<aws_sdk_sqs::types::SendMessageBatchRequestEntry> {
+        use aws_sdk_sqs::types::{MessageAttributeValue, SendMessageBatchRequestEntry};
+
+        let attribute = |value: String| {
+            MessageAttributeValue::builder()
+                .data_type("String")
+                .string_value(value)
+                .build()
+        };
+        let mut entry = SendMessageBatchRequestEntry::builder()
+            .id(id)
+            .message_body(message(self.format, event)?)
+            .message_attributes("event_type", attribute(event.event_type.clone())?)
+            .message_attributes(
+                "checkpoint",
+                attribute(event.checkpoint_sequence.to_string())?,
+            );
+        if let Some(key) = self.ordering_key {
+            entry = entry
+                .message_group_id(ordering_key(key, event))
+                .message_deduplication_id(event.id.to_string());
+        }
+        Ok(entry.build()?)
+    }
+}
+
+#[async_trait]
+impl Sink for SqsSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let client = &self.client;
+        let queue_url = &self.queue_url;
+        let mut dropped = 0;
+        for chunk in events.chunks(MAX_BATCH_ENTRIES) {
+            let mut entries = Vec::with_capacity(chunk.len());
+            for (index, event) in chunk.iter().enumerate() {
+                let id = index.to_string();
+                match self.entry(id.clone(), event) {
+                    Ok(entry) => entries.push((id, entry)),
+                    Err(e) => {
+                        error!(event = %event.id, error = %e, "Failed to encode SQS message, dropping its event");
+                        dropped += 1;
+                    }
+                }
+            }
+
+            dropped += send_entries("SQS", entries, |entries| async move {
+                let output = client
+                    .send_message_batch()
+                    .queue_url(queue_url)
+                    .set_entries(Some(entries))
+                    .send()
+                    .await?;
+                Ok(output
+                    .failed()
+                    .iter()
+                    .map(|entry| Failure {
+                        id: entry.id().to_string(),
+                        code: entry.code().to_string(),
+                        message: entry.message().map(str::to_string),
+                        sender_fault: entry.sender_fault(),
+                    })
+                    .collect())
+            })
+            .await;
+        }
+        if dropped > 0 {
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn failure(id: &str, sender_fault: bool) -> Failure {
+        Failure {
+            id: id.to_string(),
+            code: "InternalError".to_string(),
+            message: None,
+            sender_fault,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_only_failed_entries_are_sent_again() {
+        let batches = Mutex::new(Vec::new());
+        let entries = (0..4).map(|entry| (entry.to_string(), entry)).collect();
+        let dropped = send_entries("test", entries, |batch: Vec<u32>| {
+            let mut batches = batches.lock().unwrap();
+            batches.push(batch);
+            let first = batches.len() == 1;
+            async move {
+                Ok(if first {
+                    vec![failure("1", false), failure("2", true)]
+                } else {
+                    Vec::new()
+                })
+            }
+        })
+        .await;
+
+        assert_eq!(dropped, 1);
+        assert_eq!(*batches.lock().unwrap(), vec![vec![0, 1, 2, 3], vec![1]]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_entries_failing_every_attempt_are_dropped() {
+        let entries = vec![("0".to_string(), 0), ("1".to_string(), 1)];
+        let dropped = send_entries("test", entries, |_: Vec<u32>| async {
+            Err(eyre::eyre!("connection reset"))
+        })
+        .await;
+        assert_eq!(dropped, 2);
+    }
+}
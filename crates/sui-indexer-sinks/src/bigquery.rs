@@ -0,0 +1,196 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use eyre::{Result, WrapErr};
+use gcp_auth::{CustomServiceAccount, TokenProvider};
+use serde::{Deserialize, Serialize};
+use sui_indexer_config::{BigQuerySinkConfig, EventsConfig};
+use sui_indexer_events::ProcessedEvent;
+use tracing::{debug, error, warn};
+use url::Url;
+
+use crate::{
+    warehouse::{Row, TableBuffer, BUFFERED_BATCHES},
+    PartialDelivery, Sink, SinkError,
+};
+
+/// OAuth scope needed to insert rows
+const BIGQUERY_SCOPE: &str = "https://www.googleapis.com/auth/bigquery.insertdata";
+
+/// Public API endpoint, used unless `endpoint` is set
+const DEFAULT_ENDPOINT: &str = "https://bigquery.googleapis.com/";
+
+/// Rows per insert request, as recommended for streaming inserts
+const INSERT_BATCH: usize = 500;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InsertAllRequest<'a> {
+    rows: Vec<InsertRow<'a>>,
+    ignore_unknown_values: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InsertRow<'a> {
+    insert_id: &'a str,
+    json: &'a Row,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InsertAllResponse {
+    #[serde(default)]
+    insert_errors: Vec<InsertError>,
+}
+
+#[derive(Deserialize)]
+struct InsertError {
+    index: usize,
+    #[serde(default)]
+    errors: Vec<ErrorProto>,
+}
+
+#[derive(Deserialize)]
+struct ErrorProto {
+    #[serde(default)]
+    message: String,
+}
+
+/// Micro-batches events into BigQuery tables with streaming inserts
+///
+/// Rows carry the event ID as insert ID, so BigQuery drops the duplicates of
+/// retried inserts. Fields without a column in the table make BigQuery reject
+/// the row, so a schema falling behind the events fails the insert instead
+/// of losing data. Failed rows stay buffered up to [`BUFFERED_BATCHES`]
+/// batches; events arriving beyond that are dropped and counted.
+pub struct BigQuerySink {
+    client: reqwest::Client,
+    auth: Arc<dyn TokenProvider>,
+    dataset_url: Url,
+    max_rows: usize,
+    flush_interval: Duration,
+    buffer: TableBuffer,
+}
+
+impl BigQuerySink {
+    /// Resolve credentials and table routes for the given configuration
+    pub async fn new(config: &BigQuerySinkConfig, events: &EventsConfig) -> Result<Self> {
+        let auth: Arc<dyn TokenProvider> = match &config.credentials_file {
+            Some(path) => Arc::new(
+                CustomServiceAccount::from_file(path)
+                    .wrap_err_with(|| format!("Failed to read {}", path.display()))?,
+            ),
+            None => gcp_auth::provider()
+                .await
+                .wrap_err("Failed to find Google Cloud credentials")?,
+        };
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => Url::parse(DEFAULT_ENDPOINT)?,
+        };
+        let dataset_url = endpoint.join(&format!(
+            "bigquery/v2/projects/{}/datasets/{}/",
+            config.project, config.dataset
+        ))?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            auth,
+            dataset_url,
+            max_rows: config.max_rows.max(1),
+            flush_interval: Duration::from_secs(config.flush_interval.max(1)),
+            buffer: TableBuffer::new(&config.table, &config.tables, events)?,
+        })
+    }
+
+    async fn insert(&self, table: &str, rows: &[Row]) -> Result<()> {
+        let url = self
+            .dataset_url
+            .join(&format!("tables/{table}/insertAll"))?;
+        for chunk in rows.chunks(INSERT_BATCH) {
+            let rows = chunk
+                .iter()
+                .map(|row| InsertRow {
+                    insert_id: row["id"].as_str().unwrap_or_default(),
+                    json: row,
+                })
+                .collect();
+
+            // Tokens are cached and refreshed by the provider
+            let token = self.auth.token(&[BIGQUERY_SCOPE]).await?;
+            let response: InsertAllResponse = self
+                .client
+                .post(url.clone())
+                .bearer_auth(token.as_str())
+                .json(&InsertAllRequest {
+                    rows,
+                    ignore_unknown_values: false,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            // Rejected rows are reported in the body of a successful response
+            if let Some(error) = response.insert_errors.first() {
+                return Err(SinkError::Delivery {
+                    service: "BigQuery",
+                    reason: format!(
+                        "rejected {} rows of `{table}`, row {}: {}",
+                        response.insert_errors.len(),
+                        error.index,
+                        error
+                            .errors
+                            .first()
+                            .map_or("unknown error", |error| error.message.as_str())
+                    ),
+                }
+                .into());
+            }
+        }
+        debug!(table, rows = rows.len(), "Inserted rows into BigQuery");
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for BigQuerySink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let limit = self.max_rows * BUFFERED_BATCHES;
+        let dropped = self.buffer.extend_bounded(events, limit);
+        if self.buffer.len() >= self.max_rows {
+            if let Err(e) = self.flush().await {
+                warn!(
+                    error = %e,
+                    buffered = self.buffer.len(),
+                    "Failed to insert rows into BigQuery, keeping them buffered"
+                );
+            }
+        }
+        if dropped > 0 {
+            error!(
+                events = dropped,
+                limit, "BigQuery inserts keep failing and the buffer is full, dropping events"
+            );
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        while let Some((table, rows)) = self.buffer.first() {
+            self.insert(table, rows).await?;
+            self.buffer.pop_first();
+        }
+        Ok(())
+    }
+
+    fn flush_interval(&self) -> Option<Duration> {
+        Some(self.flush_interval)
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
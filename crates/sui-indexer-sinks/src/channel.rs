@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use eyre::Result;
+use sui_indexer_events::ProcessedEvent;
+use tokio::sync::mpsc;
+
+use crate::{Sink, SinkError};
+
+/// Hands events to a channel read by the embedding application
+///
+/// Sending waits while the channel is full, so a slow reader holds back
+/// the pipeline instead of losing events.
+pub struct ChannelSink {
+    tx: mpsc::Sender<ProcessedEvent>,
+}
+
+impl ChannelSink {
+    /// Create a sink sending every event to `tx`
+    pub fn new(tx: mpsc::Sender<ProcessedEvent>) -> Self {
+        Self { tx }
+    }
+}
+
+#[async_trait]
+impl Sink for ChannelSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        for event in events {
+            if self.tx.send(event.clone()).await.is_err() {
+                return Err(SinkError::ReceiverDropped.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn max_attempts(&self) -> u32 {
+        // A closed channel stays closed
+        1
+    }
+}
@@ -0,0 +1,168 @@
+//! Chat services that notifications and alerts are posted to
+//!
+//! Shared by [`NotificationSink`](crate::NotificationSink) and the alert
+//! notifiers, so both send the same way. Tokens are read from their
+//! secrets once, when a channel is created. Request errors leave out the
+//! URL, which carries the token of Discord webhooks and Telegram bots.
+
+use eyre::{Result, WrapErr};
+use serde_json::{json, Value};
+use sui_indexer_config::{SlackConfig, TelegramConfig};
+use url::Url;
+
+use crate::SinkError;
+
+/// Slack Web API method posting a message
+const SLACK_POST_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// Channel a text message can be posted to
+pub enum ChatChannel {
+    /// Discord channel webhook
+    Discord { client: reqwest::Client, url: Url },
+    /// Slack channel posted to by a bot
+    Slack {
+        client: reqwest::Client,
+        token: String,
+        channel: String,
+    },
+    /// Telegram chat a bot sends to
+    Telegram {
+        client: reqwest::Client,
+        url: String,
+        chat_id: String,
+    },
+}
+
+impl ChatChannel {
+    /// Post to a Discord channel webhook
+    pub fn discord(client: reqwest::Client, url: Url) -> Self {
+        Self::Discord { client, url }
+    }
+
+    /// Post to a Slack channel, reading the bot token
+    pub fn slack(client: reqwest::Client, config: &SlackConfig) -> Result<Self> {
+        Ok(Self::Slack {
+            client,
+            token: config
+                .token
+                .resolve()
+                .wrap_err("Failed to read the Slack token")?,
+            channel: config.channel.clone(),
+        })
+    }
+
+    /// Send to a Telegram chat, reading the bot token
+    pub fn telegram(client: reqwest::Client, config: &TelegramConfig) -> Result<Self> {
+        let token = config
+            .bot_token
+            .resolve()
+            .wrap_err("Failed to read the Telegram bot token")?;
+        Ok(Self::Telegram {
+            client,
+            url: format!("https://api.telegram.org/bot{token}/sendMessage"),
+            chat_id: config.chat_id.clone(),
+        })
+    }
+
+    /// Name used when logging delivery failures
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Discord { .. } => "discord",
+            Self::Slack { .. } => "slack",
+            Self::Telegram { .. } => "telegram",
+        }
+    }
+
+    /// Post `text`, cut to the longest message the service accepts
+    pub async fn post(&self, text: &str) -> Result<()> {
+        match self {
+            Self::Discord { client, url } => {
+                send(
+                    client
+                        .post(url.clone())
+                        .json(&json!({ "content": truncate(text, 2000) })),
+                )
+                .await?;
+            }
+            Self::Slack {
+                client,
+                token,
+                channel,
+            } => {
+                let response: Value = send(
+                    client
+                        .post(SLACK_POST_URL)
+                        .bearer_auth(token)
+                        .json(&json!({ "channel": channel, "text": truncate(text, 4000) })),
+                )
+                .await?
+                .json()
+                .await
+                .map_err(reqwest::Error::without_url)?;
+                // Slack reports API errors in the body of a successful response
+                if response["ok"].as_bool() != Some(true) {
+                    return Err(SinkError::Delivery {
+                        service: "Slack API",
+                        reason: response["error"].to_string(),
+                    }
+                    .into());
+                }
+            }
+            Self::Telegram {
+                client,
+                url,
+                chat_id,
+            } => {
+                send(
+                    client
+                        .post(url)
+                        .json(&json!({ "chat_id": chat_id, "text": truncate(text, 4096) })),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Send a request, failing on an error status without naming the URL
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let response = request
+        .send()
+        .await
+        .map_err(reqwest::Error::without_url)?
+        .error_for_status()
+        .map_err(reqwest::Error::without_url)?;
+    Ok(response)
+}
+
+/// Cut `text` to at most `max` characters
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((end, _)) if text[end..].chars().count() > 1 => format!("{}…", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_short_text() {
+        assert_eq!(truncate("hello", 5), "hello");
+        assert_eq!(truncate("hello!", 5), "hell…");
+    }
+
+    #[tokio::test]
+    async fn test_errors_leave_out_the_bot_token() {
+        // Nothing listens on the discard port
+        let channel = ChatChannel::Telegram {
+            client: reqwest::Client::new(),
+            url: "http://127.0.0.1:9/bot123:secret/sendMessage".to_string(),
+            chat_id: "-100".to_string(),
+        };
+        let err = channel.post("hello").await.unwrap_err();
+        assert!(!format!("{err:#}").contains("secret"), "{err:#}");
+    }
+}
@@ -0,0 +1,112 @@
+use eyre::Result;
+use serde::Serialize;
+use sui_indexer_config::EventFormat;
+use sui_indexer_events::ProcessedEvent;
+
+use crate::SinkError;
+
+/// Content type of one CloudEvent in structured mode
+pub const CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// Content type of a JSON array of CloudEvents
+pub const BATCH_CONTENT_TYPE: &str = "application/cloudevents-batch+json";
+
+/// CloudEvents 1.0 envelope of a processed event
+///
+/// The ID is derived from the transaction and event sequence, so an event
+/// keeps it when re-indexed and consumers can deduplicate on it.
+#[derive(Debug, Serialize)]
+pub struct CloudEvent<'a> {
+    pub specversion: &'static str,
+    /// `<transaction digest>:<event sequence>`
+    pub id: String,
+    /// `/sui/<package>/<module>` of the emitting module
+    pub source: String,
+    /// Move type of the event, e.g. `0x2::pool::SwapEvent`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Digest of the emitting transaction
+    pub subject: String,
+    /// Checkpoint timestamp in RFC 3339
+    pub time: String,
+    pub datacontenttype: &'static str,
+    /// Checkpoint sequence number, an extension attribute
+    pub suicheckpoint: u64,
+    /// The processed event
+    pub data: &'a ProcessedEvent,
+}
+
+impl<'a> CloudEvent<'a> {
+    /// Wrap a processed event
+    pub fn new(event: &'a ProcessedEvent) -> Self {
+        Self {
+            specversion: "1.0",
+            id: format!("{}:{}", event.transaction_digest, event.event.id.event_seq),
+            source: format!("/sui/{}/{}", event.package_id, event.module_name),
+            event_type: event.event.type_.to_string(),
+            subject: event.transaction_digest.to_string(),
+            time: event.timestamp.to_rfc3339(),
+            datacontenttype: "application/json",
+            suicheckpoint: event.checkpoint_sequence,
+            data: event,
+        }
+    }
+}
+
+/// Message payload of an event in `format`
+pub(crate) fn payload(format: EventFormat, event: &ProcessedEvent) -> Result<Vec<u8>> {
+    Ok(match format {
+        EventFormat::Json => serde_json::to_vec(event)?,
+        EventFormat::CloudEvents => serde_json::to_vec(&CloudEvent::new(event))?,
+        EventFormat::Avro | EventFormat::Protobuf => {
+            return Err(SinkError::SchemaRegistryRequired(format!("{format:?}")).into())
+        }
+    })
+}
+
+/// Payload of a batch of events in `format`, a JSON array
+pub(crate) fn batch_payload(format: EventFormat, events: &[ProcessedEvent]) -> Result<Vec<u8>> {
+    Ok(match format {
+        EventFormat::Json => serde_json::to_vec(events)?,
+        EventFormat::CloudEvents => {
+            serde_json::to_vec(&events.iter().map(CloudEvent::new).collect::<Vec<_>>())?
+        }
+        EventFormat::Avro | EventFormat::Protobuf => {
+            return Err(SinkError::BatchUnsupported(format!("{format:?}")).into())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cloudevents_envelope() -> Result<()> {
+        let event = TestEvent::new("0x2::pool::SwapEvent")
+            .seq(3)
+            .fields(serde_json::json!({"amount": "5"}))
+            .build();
+        let events = DefaultEventProcessor::new()
+            .process_events(vec![event])
+            .await?;
+        let event = &events[0];
+
+        let envelope: Value = serde_json::from_slice(&payload(EventFormat::CloudEvents, event)?)?;
+        assert_eq!(envelope["specversion"], "1.0");
+        assert_eq!(envelope["id"], format!("{}:3", event.transaction_digest));
+        assert_eq!(envelope["type"], event.event.type_.to_string());
+        assert_eq!(envelope["data"], serde_json::to_value(event)?);
+
+        let plain: Value = serde_json::from_slice(&payload(EventFormat::Json, event)?)?;
+        assert_eq!(plain, envelope["data"]);
+        let batch: Value =
+            serde_json::from_slice(&batch_payload(EventFormat::CloudEvents, &events)?)?;
+        assert_eq!(batch[0], envelope);
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+/// Failure setting up or delivering to a sink
+///
+/// Delivery failures of the services behind a sink keep the service's own
+/// explanation. They travel in [`eyre::Report`]s like any other error and
+/// are read back with [`SinkError::find`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SinkError {
+    /// A sink whose support was not compiled in
+    #[error("{sink} sinks require building with the `{feature}` feature")]
+    FeatureDisabled {
+        sink: &'static str,
+        feature: &'static str,
+    },
+    /// `outbox.enabled` without storage to keep the outbox in
+    #[error("The outbox requires storage")]
+    OutboxWithoutStorage,
+    /// An event format that needs a schema registry without one configured
+    #[error("The {0} format requires a schema registry")]
+    SchemaRegistryRequired(String),
+    /// An event format that cannot encode a batch as one JSON payload
+    #[error("The {0} format cannot encode a batch as JSON")]
+    BatchUnsupported(String),
+    /// An event that does not fit the schema of its type
+    #[error("Event does not match its schema: {0}")]
+    SchemaMismatch(String),
+    /// A schema registry URL that cannot have paths appended
+    #[error("Invalid schema registry URL {0}")]
+    InvalidRegistryUrl(String),
+    /// The receiving end of a channel sink went away
+    #[error("Event stream receiver was dropped")]
+    ReceiverDropped,
+    /// A warehouse table without a column for some event fields
+    #[error("Table `{table}` has no column for {columns}; add them to the table")]
+    MissingColumns { table: String, columns: String },
+    /// The service behind a sink refused or failed the delivery
+    #[error("{service} error: {reason}")]
+    Delivery {
+        service: &'static str,
+        reason: String,
+    },
+}
+
+impl SinkError {
+    /// The sink error behind `err`, if that is what failed
+    pub fn find(err: &eyre::Report) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use eyre::Result;
+use futures::future::try_join_all;
+use rdkafka::{
+    message::{Header, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig,
+};
+use sui_indexer_config::{EventFormat, KafkaSinkConfig};
+use sui_indexer_events::ProcessedEvent;
+
+use crate::{cloudevents, schema_registry::EventEncoder, Sink};
+
+/// Time a message may wait in the producer queue before delivery fails
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Produces every event as a message keyed by transaction digest
+///
+/// CloudEvents messages carry a `content-type` header for structured mode.
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+    encoder: EventEncoder,
+}
+
+impl KafkaSink {
+    /// Create a Kafka producer from the given configuration
+    pub fn new(config: &KafkaSinkConfig) -> Result<Self> {
+        let mut client = ClientConfig::new();
+        client.set("bootstrap.servers", &config.brokers);
+        for (key, value) in &config.properties {
+            client.set(key, value);
+        }
+
+        Ok(Self {
+            producer: client.create()?,
+            topic: config.topic.clone(),
+            encoder: EventEncoder::new(config.format, config.schema_registry.as_ref())?,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for KafkaSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let mut messages = Vec::with_capacity(events.len());
+        for event in events {
+            messages.push((
+                event.transaction_digest.to_string(),
+                self.encoder.encode(event).await?,
+            ));
+        }
+
+        let (producer, topic) = (&self.producer, self.topic.as_str());
+        let cloudevents = self.encoder.format() == EventFormat::CloudEvents;
+        try_join_all(messages.iter().map(|(key, payload)| async move {
+            let mut record = FutureRecord::to(topic).key(key).payload(payload);
+            if cloudevents {
+                record = record.headers(OwnedHeaders::new().insert(Header {
+                    key: "content-type",
+                    value: Some(cloudevents::CONTENT_TYPE),
+                }));
+            }
+            producer
+                .send(record, QUEUE_TIMEOUT)
+                .await
+                .map_err(|(e, _)| e)
+        }))
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,789 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use eyre::{Result, WrapErr};
+#[cfg(any(feature = "aws", feature = "pubsub"))]
+use sui_indexer_config::OrderingKey;
+use sui_indexer_config::{EventsConfig, OutboxConfig, SinkConfig};
+use sui_indexer_events::{EventFilterProcessor, ProcessedEvent};
+use sui_indexer_storage::{OutboxEntry, StorageManager};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+use tracing::{error, info, warn};
+
+#[cfg(feature = "aws")]
+pub mod aws;
+#[cfg(feature = "bigquery")]
+pub mod bigquery;
+pub mod channel;
+pub mod chat;
+pub mod cloudevents;
+mod error;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod nats;
+pub mod notification;
+mod outbox;
+#[cfg(feature = "pubsub")]
+pub mod pubsub;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod s3_parquet;
+pub mod schema_registry;
+pub mod snowflake;
+mod warehouse;
+pub mod webhook;
+
+#[cfg(feature = "aws")]
+pub use aws::{SnsSink, SqsSink};
+#[cfg(feature = "bigquery")]
+pub use bigquery::BigQuerySink;
+pub use channel::ChannelSink;
+pub use error::SinkError;
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaSink;
+pub use nats::NatsSink;
+pub use notification::NotificationSink;
+#[cfg(feature = "pubsub")]
+pub use pubsub::PubSubSink;
+pub use s3_parquet::S3ParquetSink;
+pub use snowflake::SnowflakeSink;
+pub use webhook::WebhookSink;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisSink;
+
+/// Batches queued per sink before delivery applies back-pressure to the pipeline
+const SINK_QUEUE_CAPACITY: usize = 64;
+
+/// Delivery attempts per batch before it is dropped
+const MAX_DELIVERY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first delivery retry, doubled on every further attempt
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// External output that receives events after they are stored
+#[async_trait]
+pub trait Sink: Send {
+    /// Deliver a batch of events
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()>;
+
+    /// Deliver any buffered events
+    async fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// How often `flush` is called while the sink is idle
+    fn flush_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Times a failed batch is sent; sinks retrying on their own return 1
+    fn max_attempts(&self) -> u32 {
+        MAX_DELIVERY_ATTEMPTS
+    }
+}
+
+/// Error of a sink that delivered part of a batch and gave up on the rest
+#[derive(Debug)]
+pub struct PartialDelivery {
+    /// Events that were not delivered
+    pub dropped: usize,
+}
+
+impl fmt::Display for PartialDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} events could not be delivered", self.dropped)
+    }
+}
+
+impl std::error::Error for PartialDelivery {}
+
+/// Ordering key or message group of an event
+#[cfg(any(feature = "aws", feature = "pubsub"))]
+pub(crate) fn ordering_key(key: OrderingKey, event: &ProcessedEvent) -> String {
+    match key {
+        OrderingKey::Sender => event.sender.clone(),
+        OrderingKey::Transaction => event.transaction_digest.to_string(),
+        OrderingKey::Package => event.package_id.to_string(),
+        OrderingKey::EventType => event.event_type.clone(),
+    }
+}
+
+/// Create the sink `name` described by a configuration entry
+///
+/// Warehouse sinks route events to tables by the filters of `events`.
+/// Webhook sinks record retried and failed deliveries in `storage` if given.
+pub async fn build_sink(
+    name: &str,
+    config: &SinkConfig,
+    events: &EventsConfig,
+    storage: Option<&StorageManager>,
+) -> Result<Box<dyn Sink>> {
+    Ok(match config {
+        #[cfg(feature = "kafka")]
+        SinkConfig::Kafka(config) => Box::new(KafkaSink::new(config)?),
+        #[cfg(not(feature = "kafka"))]
+        SinkConfig::Kafka(_) => {
+            return Err(SinkError::FeatureDisabled {
+                sink: "Kafka",
+                feature: "kafka",
+            }
+            .into())
+        }
+        SinkConfig::Nats(config) => Box::new(NatsSink::connect(config).await?),
+        SinkConfig::Webhook(config) => {
+            let sink = WebhookSink::new(config)?;
+            Box::new(match storage {
+                Some(storage) => sink.with_deliveries(name.to_string(), storage.clone()),
+                None => sink,
+            })
+        }
+        SinkConfig::S3Parquet(config) => Box::new(S3ParquetSink::new(config)?),
+        SinkConfig::Notification(config) => Box::new(NotificationSink::new(config)?),
+        #[cfg(feature = "redis")]
+        SinkConfig::Redis(config) => Box::new(RedisSink::connect(config).await?),
+        #[cfg(not(feature = "redis"))]
+        SinkConfig::Redis(_) => {
+            return Err(SinkError::FeatureDisabled {
+                sink: "Redis",
+                feature: "redis",
+            }
+            .into())
+        }
+        #[cfg(feature = "pubsub")]
+        SinkConfig::PubSub(config) => Box::new(PubSubSink::new(config).await?),
+        #[cfg(not(feature = "pubsub"))]
+        SinkConfig::PubSub(_) => {
+            return Err(SinkError::FeatureDisabled {
+                sink: "Pub/Sub",
+                feature: "pubsub",
+            }
+            .into())
+        }
+        #[cfg(feature = "aws")]
+        SinkConfig::Sns(config) => Box::new(SnsSink::new(config).await?),
+        #[cfg(feature = "aws")]
+        SinkConfig::Sqs(config) => Box::new(SqsSink::new(config).await?),
+        #[cfg(not(feature = "aws"))]
+        SinkConfig::Sns(_) | SinkConfig::Sqs(_) => {
+            return Err(SinkError::FeatureDisabled {
+                sink: "AWS",
+                feature: "aws",
+            }
+            .into())
+        }
+        #[cfg(feature = "bigquery")]
+        SinkConfig::BigQuery(config) => Box::new(BigQuerySink::new(config, events).await?),
+        #[cfg(not(feature = "bigquery"))]
+        SinkConfig::BigQuery(_) => {
+            return Err(SinkError::FeatureDisabled {
+                sink: "BigQuery",
+                feature: "bigquery",
+            }
+            .into())
+        }
+        SinkConfig::Snowflake(config) => Box::new(SnowflakeSink::new(config, events)?),
+    })
+}
+
+/// Fans stored events out to the configured sinks
+///
+/// Every sink runs in its own task behind a bounded queue, so a slow sink
+/// only holds back the pipeline once its queue is full. With the outbox,
+/// sinks instead read the messages committed along with the events.
+#[derive(Default)]
+pub struct SinkManager {
+    sinks: Vec<SinkHandle>,
+    dead_letters: DeadLetters,
+    pause: SinkPause,
+    outbox: Option<(StorageManager, OutboxConfig)>,
+}
+
+/// Count of events dropped because a sink could not accept them
+///
+/// Events are dead-lettered when delivery still fails after the last retry
+/// or when the sink task has stopped. Clones share the same count.
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetters(Arc<AtomicU64>);
+
+impl DeadLetters {
+    /// Events dropped since the sinks started
+    pub fn count(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn add(&self, events: usize) {
+        self.0.fetch_add(events as u64, Ordering::Relaxed);
+    }
+}
+
+/// Switch holding back every sink of a [`SinkManager`] while storage is degraded
+///
+/// Paused sinks neither send nor flush; queued batches and outbox messages
+/// wait until the switch is released. Clones share the same switch.
+#[derive(Debug, Clone)]
+pub struct SinkPause(Arc<watch::Sender<bool>>);
+
+impl Default for SinkPause {
+    fn default() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+}
+
+impl SinkPause {
+    /// Hold back deliveries, returning whether they were flowing
+    pub fn pause(&self) -> bool {
+        self.0
+            .send_if_modified(|paused| !std::mem::replace(paused, true))
+    }
+
+    /// Let deliveries flow again, returning whether they were held back
+    pub fn resume(&self) -> bool {
+        self.0
+            .send_if_modified(|paused| std::mem::replace(paused, false))
+    }
+
+    /// Check if deliveries are held back
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until deliveries may flow
+    pub(crate) async fn wait_while_paused(&self) {
+        // The sender lives in `self`, so waiting cannot fail
+        let _ = self.0.subscribe().wait_for(|paused| !paused).await;
+    }
+}
+
+/// Queue depths of the sinks of a [`SinkManager`], readable while it runs
+#[derive(Clone, Default)]
+pub struct SinkQueues(Vec<(String, mpsc::WeakSender<Batch>)>);
+
+impl SinkQueues {
+    /// Batches waiting per sink name, omitting sinks that have stopped
+    pub fn depths(&self) -> BTreeMap<String, usize> {
+        self.0
+            .iter()
+            .filter_map(|(name, tx)| {
+                let tx = tx.upgrade()?;
+                Some((name.clone(), tx.max_capacity() - tx.capacity()))
+            })
+            .collect()
+    }
+
+    /// Batches each sink queue holds before delivery applies back-pressure
+    pub fn capacity(&self) -> usize {
+        SINK_QUEUE_CAPACITY
+    }
+}
+
+/// Events queued for a sink
+struct Batch {
+    events: Vec<ProcessedEvent>,
+    /// Flush the sink right after sending, so buffering sinks do not hold the events
+    flush: bool,
+}
+
+struct SinkHandle {
+    name: String,
+    selector: EventFilterProcessor,
+    /// Tags whose events reach the sink whatever its filters select
+    routes: Vec<String>,
+    input: SinkInput,
+    task: JoinHandle<()>,
+}
+
+impl SinkHandle {
+    /// Check if the sink takes `event`, through its filters or a routed tag
+    fn selects(&self, event: &ProcessedEvent) -> bool {
+        self.selector.should_process_event(&event.event)
+            || event
+                .metadata
+                .tags
+                .iter()
+                .any(|tag| self.routes.contains(tag))
+    }
+}
+
+enum SinkInput {
+    /// Batches queued in memory
+    Queue(mpsc::Sender<Batch>),
+    /// Wake-ups of an outbox dispatcher
+    Outbox(mpsc::Sender<()>),
+}
+
+impl SinkManager {
+    /// Create and start the enabled sinks, resolving their filter names
+    /// against the configured event filters
+    ///
+    /// Sinks are fed from the outbox if it is enabled, which needs `storage`.
+    pub async fn from_config(
+        sinks: &BTreeMap<String, SinkConfig>,
+        events: &EventsConfig,
+        outbox: &OutboxConfig,
+        storage: Option<&StorageManager>,
+    ) -> Result<Self> {
+        let mut manager = Self::default();
+        if outbox.enabled {
+            let storage = storage.ok_or(SinkError::OutboxWithoutStorage)?;
+            manager = manager.with_outbox(storage.clone(), outbox.clone());
+        }
+
+        for (name, config) in sinks {
+            if !config.enabled() {
+                continue;
+            }
+
+            let selected = events
+                .named_filters(config.filters())
+                .wrap_err_with(|| format!("Invalid filters for sink `{name}`"))?;
+            let selector = EventFilterProcessor::new(selected);
+            let sink = build_sink(name, config, events, storage)
+                .await
+                .wrap_err_with(|| format!("Failed to create sink `{name}`"))?;
+
+            info!(
+                sink = %name,
+                kind = config.kind(),
+                filters = ?config.filters(),
+                "Sink ready"
+            );
+            manager.add(name.clone(), sink, selector);
+        }
+
+        Ok(manager)
+    }
+
+    /// Feed sinks added afterwards from the outbox in `storage`
+    pub fn with_outbox(mut self, storage: StorageManager, config: OutboxConfig) -> Self {
+        self.outbox = Some((storage, config));
+        self
+    }
+
+    /// Start delivering events matching `selector` to a sink
+    pub fn add(&mut self, name: String, sink: Box<dyn Sink>, selector: EventFilterProcessor) {
+        let (input, task) = match &self.outbox {
+            Some((storage, config)) => {
+                let (tx, rx) = mpsc::channel(1);
+                let dispatcher = outbox::OutboxDispatcher {
+                    name: name.clone(),
+                    sink,
+                    storage: storage.clone(),
+                    config: config.clone(),
+                    wake: rx,
+                    pause: self.pause.clone(),
+                    dead_letters: self.dead_letters.clone(),
+                };
+                (SinkInput::Outbox(tx), tokio::spawn(dispatcher.run()))
+            }
+            None => {
+                let (tx, rx) = mpsc::channel(SINK_QUEUE_CAPACITY);
+                let task = tokio::spawn(run_sink(
+                    name.clone(),
+                    sink,
+                    rx,
+                    self.dead_letters.clone(),
+                    self.pause.clone(),
+                ));
+                (SinkInput::Queue(tx), task)
+            }
+        };
+        self.sinks.push(SinkHandle {
+            name,
+            selector,
+            routes: Vec::new(),
+            input,
+            task,
+        });
+    }
+
+    /// Also deliver events tagged `tag` to each sink named in `sinks`
+    ///
+    /// Names of sinks that are not running are ignored.
+    pub fn route(&mut self, tag: &str, sinks: &[String]) {
+        for handle in &mut self.sinks {
+            if sinks.contains(&handle.name) && !handle.routes.iter().any(|route| route == tag) {
+                handle.routes.push(tag.to_string());
+            }
+        }
+    }
+
+    /// Check if sinks are fed from the outbox
+    pub fn uses_outbox(&self) -> bool {
+        self.outbox.is_some()
+    }
+
+    /// Outbox messages of the events selected by each sink, to commit along with the events
+    pub fn outbox_entries(&self, events: &[ProcessedEvent]) -> Vec<OutboxEntry> {
+        self.sinks
+            .iter()
+            .flat_map(|handle| {
+                events
+                    .iter()
+                    .filter(|event| handle.selects(event))
+                    .map(|event| OutboxEntry {
+                        sink: handle.name.clone(),
+                        event: event.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Events dropped by any sink
+    pub fn dead_letters(&self) -> DeadLetters {
+        self.dead_letters.clone()
+    }
+
+    /// Switch holding back every sink, for the pipeline to pause them while storage fails
+    pub fn pause_switch(&self) -> SinkPause {
+        self.pause.clone()
+    }
+
+    /// Handle reporting how many batches wait in each sink's queue
+    pub fn queues(&self) -> SinkQueues {
+        SinkQueues(
+            self.sinks
+                .iter()
+                .filter_map(|handle| match &handle.input {
+                    SinkInput::Queue(tx) => Some((handle.name.clone(), tx.downgrade())),
+                    SinkInput::Outbox(_) => None,
+                })
+                .collect(),
+        )
+    }
+
+    /// Check if no sinks are running
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Queue the events selected by each sink for delivery
+    ///
+    /// Outbox dispatchers are only woken, as the events were committed to the outbox.
+    pub async fn deliver(&self, events: &[ProcessedEvent]) {
+        self.queue(events, false).await;
+    }
+
+    /// Queue the events selected by each sink ahead of their checkpoint, flushing them on arrival
+    ///
+    /// Sinks fed from the outbox are skipped; they read the events once
+    /// their checkpoint is committed.
+    pub async fn deliver_now(&self, events: &[ProcessedEvent]) {
+        self.queue(events, true).await;
+    }
+
+    async fn queue(&self, events: &[ProcessedEvent], flush: bool) {
+        for handle in &self.sinks {
+            let tx = match &handle.input {
+                SinkInput::Queue(tx) => tx,
+                SinkInput::Outbox(_) if flush => continue,
+                SinkInput::Outbox(wake) => {
+                    // A full channel already holds a wake-up
+                    let _ = wake.try_send(());
+                    continue;
+                }
+            };
+            let selected: Vec<ProcessedEvent> = events
+                .iter()
+                .filter(|event| handle.selects(event))
+                .cloned()
+                .collect();
+            if selected.is_empty() {
+                continue;
+            }
+
+            let batch = Batch {
+                events: selected,
+                flush,
+            };
+            if let Err(mpsc::error::SendError(dropped)) = tx.send(batch).await {
+                let dropped = dropped.events.len();
+                warn!(sink = %handle.name, events = dropped, "Sink stopped, dropping events");
+                self.dead_letters.add(dropped);
+            }
+        }
+    }
+
+    /// Deliver queued events, flush buffered ones and stop all sinks
+    ///
+    /// Paused sinks are released first; everything they hold was committed.
+    pub async fn shutdown(self) {
+        self.pause.resume();
+        for handle in self.sinks {
+            drop(handle.input);
+            if let Err(e) = handle.task.await {
+                error!(sink = %handle.name, error = %e, "Sink task failed");
+            }
+        }
+    }
+}
+
+/// Deliver queued batches to a sink until its queue is closed
+async fn run_sink(
+    name: String,
+    mut sink: Box<dyn Sink>,
+    mut rx: mpsc::Receiver<Batch>,
+    dead_letters: DeadLetters,
+    pause: SinkPause,
+) {
+    let mut flush = sink.flush_interval().map(tokio::time::interval);
+
+    loop {
+        let tick = async {
+            match flush.as_mut() {
+                Some(interval) => {
+                    interval.tick().await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            batch = rx.recv() => match batch {
+                Some(batch) => {
+                    pause.wait_while_paused().await;
+                    dead_letters.add(deliver_with_retry(&name, sink.as_mut(), &batch.events).await);
+                    if batch.flush {
+                        if let Err(e) = sink.flush().await {
+                            warn!(sink = %name, error = %e, "Failed to flush sink");
+                        }
+                    }
+                }
+                None => break,
+            },
+            _ = tick => {
+                pause.wait_while_paused().await;
+                if let Err(e) = sink.flush().await {
+                    warn!(sink = %name, error = %e, "Failed to flush sink");
+                }
+            }
+        }
+    }
+
+    if let Err(e) = sink.flush().await {
+        error!(sink = %name, error = %e, "Failed to flush sink on shutdown");
+    }
+}
+
+/// Deliver a batch, retrying with backoff; returns the number of events dropped
+async fn deliver_with_retry(name: &str, sink: &mut dyn Sink, events: &[ProcessedEvent]) -> usize {
+    let mut delay = RETRY_DELAY;
+    let max_attempts = sink.max_attempts().max(1);
+
+    for attempt in 1..=max_attempts {
+        match sink.send(events).await {
+            Ok(()) => return 0,
+            Err(e) if attempt < max_attempts => {
+                warn!(sink = %name, attempt, error = %e, "Sink delivery failed, retrying");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => {
+                if let Some(partial) = e.downcast_ref::<PartialDelivery>() {
+                    return partial.dropped;
+                }
+                error!(
+                    sink = %name,
+                    events = events.len(),
+                    error = %e,
+                    "Dropping events after repeated sink delivery failures"
+                );
+            }
+        }
+    }
+    events.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use sui_indexer_config::{EventFilter, EventFormat, FilterPriority, WebhookSinkConfig};
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    /// Sink recording the modules of delivered events
+    struct RecordingSink(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl Sink for RecordingSink {
+        async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+            let mut delivered = self.0.lock().unwrap();
+            delivered.extend(events.iter().map(|event| event.module_name.clone()));
+            Ok(())
+        }
+    }
+
+    /// Sink holding sent events until it is flushed
+    struct BufferingSink {
+        buffer: Vec<String>,
+        flushed: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Sink for BufferingSink {
+        async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+            self.buffer
+                .extend(events.iter().map(|event| event.module_name.clone()));
+            Ok(())
+        }
+
+        async fn flush(&mut self) -> Result<()> {
+            self.flushed.lock().unwrap().append(&mut self.buffer);
+            Ok(())
+        }
+    }
+
+    async fn events() -> Result<Vec<ProcessedEvent>> {
+        let events = ["coin", "pool"]
+            .into_iter()
+            .map(|module| TestEvent::new(&format!("0x2::{module}::Event")).build())
+            .collect();
+        DefaultEventProcessor::new().process_events(events).await
+    }
+
+    fn filter(name: &str, module: &str) -> EventFilter {
+        EventFilter {
+            name: Some(name.to_string()),
+            package: None,
+            module: Some(module.to_string()),
+            event_type: None,
+            sender: None,
+            priority: FilterPriority::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_filter_rejected() {
+        let mut sinks = BTreeMap::new();
+        sinks.insert(
+            "hook".to_string(),
+            SinkConfig::Webhook(WebhookSinkConfig {
+                enabled: true,
+                filters: vec!["missing".to_string()],
+                format: EventFormat::Json,
+                url: "http://localhost:8080/events".parse().unwrap(),
+                headers: BTreeMap::new(),
+                batch_size: 100,
+                timeout: 30,
+                secret: None,
+                max_attempts: 5,
+                retry_delay: 1,
+                max_retry_delay: 60,
+                concurrency: 4,
+            }),
+        );
+
+        let events = EventsConfig {
+            filters: vec![filter("pools", "pool")],
+            ..EventsConfig::default()
+        };
+        let err = SinkManager::from_config(&sinks, &events, &OutboxConfig::default(), None)
+            .await
+            .err()
+            .expect("unknown filter should be rejected");
+        assert!(format!("{err:#}").contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_respects_selected_filters() -> Result<()> {
+        let all = Arc::new(Mutex::new(Vec::new()));
+        let pools = Arc::new(Mutex::new(Vec::new()));
+
+        let mut manager = SinkManager::default();
+        manager.add(
+            "all".to_string(),
+            Box::new(RecordingSink(all.clone())),
+            EventFilterProcessor::default(),
+        );
+        manager.add(
+            "pools".to_string(),
+            Box::new(RecordingSink(pools.clone())),
+            EventFilterProcessor::new(vec![filter("pools", "pool")]),
+        );
+
+        manager.deliver(&events().await?).await;
+
+        // Routed tags reach a sink its filters would not select
+        manager.route(
+            "watchlist:sanctions",
+            &["pools".to_string(), "gone".to_string()],
+        );
+        let mut tagged = events().await?;
+        tagged[0]
+            .metadata
+            .tags
+            .push("watchlist:sanctions".to_string());
+        manager.deliver(&tagged).await;
+        manager.shutdown().await;
+
+        assert_eq!(*all.lock().unwrap(), vec!["coin", "pool", "coin", "pool"]);
+        assert_eq!(*pools.lock().unwrap(), vec!["pool", "coin", "pool"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_priority_deliveries_are_flushed_on_arrival() -> Result<()> {
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = SinkManager::default();
+        manager.add(
+            "buffered".to_string(),
+            Box::new(BufferingSink {
+                buffer: Vec::new(),
+                flushed: flushed.clone(),
+            }),
+            EventFilterProcessor::default(),
+        );
+
+        manager.deliver(&events().await?).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(flushed.lock().unwrap().is_empty());
+
+        // Flushing the priority batch also releases what was buffered before it
+        manager.deliver_now(&events().await?[1..]).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(*flushed.lock().unwrap(), vec!["coin", "pool", "pool"]);
+        manager.shutdown().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paused_sinks_hold_deliveries() -> Result<()> {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = SinkManager::default();
+        manager.add(
+            "all".to_string(),
+            Box::new(RecordingSink(delivered.clone())),
+            EventFilterProcessor::default(),
+        );
+        let pause = manager.pause_switch();
+        assert!(pause.pause());
+        assert!(!pause.pause());
+
+        manager.deliver(&events().await?).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(delivered.lock().unwrap().is_empty());
+
+        assert!(pause.resume());
+        manager.deliver(&events().await?).await;
+        // Shutting down releases the switch and delivers whatever was held
+        pause.pause();
+        manager.shutdown().await;
+        assert_eq!(delivered.lock().unwrap().len(), 4);
+        assert!(!pause.is_paused());
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use eyre::Result;
+use sui_indexer_config::{EventFormat, NatsSinkConfig};
+use sui_indexer_events::ProcessedEvent;
+
+use crate::{cloudevents, schema_registry::EventEncoder, Sink};
+
+/// Publishes every event as a message on a NATS subject
+///
+/// CloudEvents messages carry a `Content-Type` header for structured mode.
+pub struct NatsSink {
+    client: async_nats::Client,
+    subject: String,
+    encoder: EventEncoder,
+}
+
+impl NatsSink {
+    /// Connect to the NATS server of the given configuration
+    pub async fn connect(config: &NatsSinkConfig) -> Result<Self> {
+        Ok(Self {
+            client: async_nats::connect(config.url.as_str()).await?,
+            subject: config.subject.clone(),
+            encoder: EventEncoder::new(config.format, config.schema_registry.as_ref())?,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for NatsSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        for event in events {
+            let payload = self.encoder.encode(event).await?;
+            match self.encoder.format() {
+                EventFormat::CloudEvents => {
+                    let mut headers = async_nats::HeaderMap::new();
+                    headers.insert("Content-Type", cloudevents::CONTENT_TYPE);
+                    self.client
+                        .publish_with_headers(self.subject.clone(), headers, payload.into())
+                        .await?;
+                }
+                _ => {
+                    self.client
+                        .publish(self.subject.clone(), payload.into())
+                        .await?;
+                }
+            }
+        }
+        // Publishing only buffers; wait until the server has the messages
+        self.client.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use eyre::Result;
+use sui_indexer_config::NotificationSinkConfig;
+use sui_indexer_events::{Placeholder, ProcessedEvent, Template};
+use tracing::warn;
+
+use crate::{chat::ChatChannel, PartialDelivery, Sink};
+
+/// Timeout of a single chat API request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts a formatted message per selected event to Discord, Slack or Telegram
+///
+/// Messages over `max_per_minute` are skipped; the next message that goes
+/// out says how many were. Failed posts are logged and not retried.
+pub struct NotificationSink {
+    template: Template,
+    threshold: Option<(Placeholder, f64)>,
+    limit: RateLimit,
+    /// Events skipped by the rate limit since the last message
+    skipped: u64,
+    channels: Vec<ChatChannel>,
+}
+
+impl NotificationSink {
+    /// Create a notification sink, checking its template and threshold field and reading its tokens
+    pub fn new(config: &NotificationSinkConfig) -> Result<Self> {
+        let template = Template::parse(&config.template)?;
+        let threshold = config
+            .threshold
+            .as_ref()
+            .map(|threshold| {
+                Ok::<_, eyre::Report>((Placeholder::parse(&threshold.field)?, threshold.min))
+            })
+            .transpose()?;
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let mut channels = Vec::new();
+        if let Some(discord) = &config.discord {
+            channels.push(ChatChannel::discord(
+                client.clone(),
+                discord.webhook_url.clone(),
+            ));
+        }
+        if let Some(slack) = &config.slack {
+            channels.push(ChatChannel::slack(client.clone(), slack)?);
+        }
+        if let Some(telegram) = &config.telegram {
+            channels.push(ChatChannel::telegram(client, telegram)?);
+        }
+
+        Ok(Self {
+            template,
+            threshold,
+            limit: RateLimit::per_minute(config.max_per_minute.max(1)),
+            skipped: 0,
+            channels,
+        })
+    }
+}
+
+#[async_trait]
+impl Sink for NotificationSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let mut dropped = 0;
+        for event in events {
+            if let Some((field, min)) = &self.threshold {
+                if field.number(event).is_none_or(|value| value < *min) {
+                    continue;
+                }
+            }
+            if !self.limit.try_take(Instant::now()) {
+                if self.skipped == 0 {
+                    warn!("Notification rate limit reached, skipping events");
+                }
+                self.skipped += 1;
+                continue;
+            }
+
+            let mut text = self.template.render(event);
+            if self.skipped > 0 {
+                text.push_str(&format!(
+                    "\n({} more events were skipped by the rate limit)",
+                    self.skipped
+                ));
+                self.skipped = 0;
+            }
+            let mut failed = false;
+            for channel in &self.channels {
+                if let Err(e) = channel.post(&text).await {
+                    warn!(channel = channel.name(), error = %e, "Failed to post notification");
+                    failed = true;
+                }
+            }
+            if failed {
+                dropped += 1;
+            }
+        }
+        if dropped > 0 {
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
+
+/// Token bucket allowing bursts of up to a minute's worth of messages
+#[derive(Debug)]
+pub struct RateLimit {
+    capacity: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimit {
+    /// Limit of `messages` per minute, starting full
+    pub fn per_minute(messages: u32) -> Self {
+        Self {
+            capacity: f64::from(messages),
+            tokens: f64::from(messages),
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Take one message from the bucket at `now`, returning `false` if it is empty
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.capacity / 60.0).min(self.capacity);
+        self.refilled = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_refills_over_a_minute() {
+        let start = Instant::now();
+        let mut limit = RateLimit::per_minute(2);
+        limit.refilled = start;
+        assert!(limit.try_take(start));
+        assert!(limit.try_take(start));
+        assert!(!limit.try_take(start));
+        assert!(!limit.try_take(start + Duration::from_secs(20)));
+        assert!(limit.try_take(start + Duration::from_secs(31)));
+    }
+}
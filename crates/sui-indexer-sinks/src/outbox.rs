@@ -0,0 +1,435 @@
+use std::time::Duration;
+
+use eyre::Result;
+use sui_indexer_config::OutboxConfig;
+use sui_indexer_events::ProcessedEvent;
+use sui_indexer_storage::StorageManager;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+use crate::{DeadLetters, Sink, SinkPause};
+
+/// Delivers the outbox messages of one sink in order, deleting them once delivered
+///
+/// Failed deliveries are retried with backoff, so every committed message
+/// reaches the sink at least once, unless it fails `max_attempts` times and
+/// becomes a dead letter that is kept but no longer sent. A message failing
+/// in a batch holds back only itself and the messages after it. Buffering sinks
+/// have their messages deleted only after the next flush.
+pub(crate) struct OutboxDispatcher {
+    pub(crate) name: String,
+    pub(crate) sink: Box<dyn Sink>,
+    pub(crate) storage: StorageManager,
+    pub(crate) config: OutboxConfig,
+    /// Woken after every commit, closed on shutdown
+    pub(crate) wake: mpsc::Receiver<()>,
+    /// Held while storage is degraded, as acknowledgements would fail
+    pub(crate) pause: SinkPause,
+    /// Counts messages that became dead letters
+    pub(crate) dead_letters: DeadLetters,
+}
+
+impl OutboxDispatcher {
+    /// Deliver messages until the wake-up channel is closed and the outbox is drained
+    pub(crate) async fn run(mut self) {
+        let poll_interval = Duration::from_millis(self.config.poll_interval);
+        let mut flush = self.sink.flush_interval().map(tokio::time::interval);
+        let buffered = flush.is_some();
+        // Highest ID handed to the sink and the IDs still to delete
+        let mut after = 0;
+        let mut unacked = Vec::new();
+        let mut open = true;
+        let mut delay = Duration::from_secs(self.config.retry_delay);
+
+        loop {
+            loop {
+                self.pause.wait_while_paused().await;
+                match self.send_pending(after).await {
+                    Ok(None) => break,
+                    Ok(Some(ids)) => {
+                        after = ids.last().copied().unwrap_or(after);
+                        unacked.extend(ids);
+                        if !buffered {
+                            self.ack(&mut unacked).await;
+                        }
+                        delay = Duration::from_secs(self.config.retry_delay);
+                    }
+                    Err(e) => {
+                        warn!(sink = %self.name, error = %e, "Outbox delivery failed, retrying");
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {}
+                            woken = self.wake.recv(), if open => open = woken.is_some(),
+                        }
+                        delay = (delay * 2).min(Duration::from_secs(self.config.max_retry_delay));
+                        if !open {
+                            // Left in the outbox for the next run
+                            break;
+                        }
+                    }
+                }
+            }
+            if !open {
+                break;
+            }
+
+            let tick = async {
+                match flush.as_mut() {
+                    Some(interval) => {
+                        interval.tick().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                woken = self.wake.recv() => open = woken.is_some(),
+                _ = tokio::time::sleep(poll_interval) => {}
+                _ = tick => {
+                    self.pause.wait_while_paused().await;
+                    self.flush(&mut unacked).await;
+                }
+            }
+        }
+
+        self.flush(&mut unacked).await;
+    }
+
+    /// Send the next batch of pending messages, returning the IDs of those delivered if there were any
+    ///
+    /// When the sink rejects a batch its messages are sent one at a time, so
+    /// the ones before a failing message are delivered and only the failing
+    /// message counts a failed attempt. Messages that fail for the last time
+    /// become dead letters, which counts as progress: the next batch starts
+    /// after them.
+    async fn send_pending(&mut self, after: i64) -> Result<Option<Vec<i64>>> {
+        let messages = self
+            .storage
+            .outbox_pending(&self.name, after, self.config.batch_size as u32)
+            .await?;
+        if messages.is_empty() {
+            return Ok(None);
+        }
+
+        // Messages up to the first one that cannot be decoded, which fails on its own
+        let mut ids = Vec::with_capacity(messages.len());
+        let mut events = Vec::with_capacity(messages.len());
+        for message in messages {
+            match serde_json::from_value::<ProcessedEvent>(message.payload) {
+                Ok(event) => {
+                    ids.push(message.id);
+                    events.push(event);
+                }
+                Err(e) if ids.is_empty() => return self.fail(message.id, e.into()).await,
+                Err(_) => break,
+            }
+        }
+
+        let Err(e) = self.sink.send(&events).await else {
+            return Ok(Some(ids));
+        };
+        if events.len() == 1 {
+            return self.fail(ids[0], e).await;
+        }
+        debug!(sink = %self.name, messages = events.len(), error = %e, "Outbox batch failed, sending its messages one at a time");
+        let mut delivered = Vec::with_capacity(ids.len());
+        for (id, event) in ids.into_iter().zip(&events) {
+            if let Err(e) = self.sink.send(std::slice::from_ref(event)).await {
+                if delivered.is_empty() {
+                    return self.fail(id, e).await;
+                }
+                // Sent again as the head of the next batch
+                break;
+            }
+            delivered.push(id);
+        }
+        Ok(Some(delivered))
+    }
+
+    /// Count a failed delivery of a message, which is progress if it became a dead letter
+    async fn fail(&mut self, id: i64, error: eyre::Report) -> Result<Option<Vec<i64>>> {
+        match self
+            .storage
+            .fail_outbox(&[id], &format!("{error:#}"), self.config.max_attempts)
+            .await
+        {
+            Ok(0) => {}
+            Ok(dead) => {
+                error!(
+                    sink = %self.name,
+                    message = id,
+                    attempts = self.config.max_attempts,
+                    error = %error,
+                    "Giving up on outbox message after repeated delivery failures"
+                );
+                self.dead_letters.add(dead);
+                return Ok(Some(Vec::new()));
+            }
+            Err(record) => {
+                warn!(sink = %self.name, error = %record, "Failed to record outbox delivery failure");
+            }
+        }
+        Err(error)
+    }
+
+    /// Flush a buffering sink and delete the messages it wrote
+    async fn flush(&mut self, unacked: &mut Vec<i64>) {
+        match self.sink.flush().await {
+            Ok(()) => self.ack(unacked).await,
+            Err(e) => warn!(sink = %self.name, error = %e, "Failed to flush sink"),
+        }
+    }
+
+    /// Delete delivered messages, keeping them for the next try if that fails
+    async fn ack(&mut self, unacked: &mut Vec<i64>) {
+        if unacked.is_empty() {
+            return;
+        }
+        match self.storage.ack_outbox(unacked).await {
+            Ok(()) => unacked.clear(),
+            Err(e) => error!(
+                sink = %self.name,
+                messages = unacked.len(),
+                error = %e,
+                "Failed to delete delivered outbox messages"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+    use sui_indexer_storage::{MemoryStorage, OutboxEntry};
+    use tokio::{task::JoinHandle, time::Instant};
+
+    use super::*;
+
+    /// Sink recording the modules of delivered events and when it was called
+    #[derive(Default)]
+    struct TestSink {
+        /// Modules of events whose batches are rejected
+        rejected: Vec<String>,
+        /// Sends failing before the sink starts accepting
+        outage: usize,
+        /// Failing flushes before the sink starts flushing
+        flush_outage: usize,
+        flush_interval: Option<Duration>,
+        buffer: Vec<String>,
+        delivered: Arc<Mutex<Vec<String>>>,
+        sends: Arc<Mutex<Vec<Instant>>>,
+    }
+
+    #[async_trait]
+    impl Sink for TestSink {
+        async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+            self.sends.lock().unwrap().push(Instant::now());
+            if self.outage > 0 {
+                self.outage -= 1;
+                eyre::bail!("connection refused");
+            }
+            if let Some(event) = events
+                .iter()
+                .find(|event| self.rejected.contains(&event.module_name))
+            {
+                eyre::bail!("rejected {}", event.module_name);
+            }
+            let modules = events.iter().map(|event| event.module_name.clone());
+            match self.flush_interval {
+                Some(_) => self.buffer.extend(modules),
+                None => self.delivered.lock().unwrap().extend(modules),
+            }
+            Ok(())
+        }
+
+        async fn flush(&mut self) -> Result<()> {
+            if self.flush_outage > 0 {
+                self.flush_outage -= 1;
+                eyre::bail!("flush timed out");
+            }
+            self.delivered.lock().unwrap().append(&mut self.buffer);
+            Ok(())
+        }
+
+        fn flush_interval(&self) -> Option<Duration> {
+            self.flush_interval
+        }
+    }
+
+    fn config() -> OutboxConfig {
+        OutboxConfig {
+            enabled: true,
+            poll_interval: 100,
+            batch_size: 10,
+            retry_delay: 1,
+            max_retry_delay: 4,
+            max_attempts: 3,
+        }
+    }
+
+    /// Storage holding one outbox message per module for the `test` sink
+    async fn storage(modules: &[&str]) -> Result<StorageManager> {
+        let events = modules
+            .iter()
+            .map(|module| TestEvent::new(&format!("0x2::{module}::Event")).build())
+            .collect();
+        let events = DefaultEventProcessor::new().process_events(events).await?;
+        let outbox = events
+            .iter()
+            .map(|event| OutboxEntry {
+                sink: "test".to_string(),
+                event: event.clone(),
+            })
+            .collect();
+        let storage = StorageManager::from_backend(MemoryStorage::new());
+        storage.commit_with_outbox(1, &events, outbox).await?;
+        Ok(storage)
+    }
+
+    /// Start delivering the outbox of `storage`, returning the wake-up sender that stops it once dropped
+    fn dispatch(
+        storage: &StorageManager,
+        sink: TestSink,
+        config: OutboxConfig,
+        dead_letters: &DeadLetters,
+    ) -> (mpsc::Sender<()>, JoinHandle<()>) {
+        let (wake, rx) = mpsc::channel(1);
+        let dispatcher = OutboxDispatcher {
+            name: "test".to_string(),
+            sink: Box::new(sink),
+            storage: storage.clone(),
+            config,
+            wake: rx,
+            pause: SinkPause::default(),
+            dead_letters: dead_letters.clone(),
+        };
+        (wake, tokio::spawn(dispatcher.run()))
+    }
+
+    /// Messages still waiting and dead letters of the `test` sink
+    async fn backlog(storage: &StorageManager) -> Result<(i64, i64)> {
+        Ok(storage
+            .outbox_backlog()
+            .await?
+            .into_iter()
+            .find(|backlog| backlog.sink == "test")
+            .map_or((0, 0), |backlog| (backlog.messages, backlog.dead_letters)))
+    }
+
+    /// Wait until `done` holds, failing after a minute of paused time
+    async fn wait_until(mut done: impl FnMut() -> bool) {
+        for _ in 0..600 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("timed out waiting for the dispatcher");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_messages_are_delivered_in_order_and_deleted() -> Result<()> {
+        let storage = storage(&["a", "b", "c", "d", "e"]).await?;
+        let sink = TestSink::default();
+        let delivered = sink.delivered.clone();
+        let sends = sink.sends.clone();
+        let config = OutboxConfig {
+            batch_size: 2,
+            ..config()
+        };
+
+        let (wake, task) = dispatch(&storage, sink, config, &DeadLetters::default());
+        drop(wake);
+        task.await?;
+
+        assert_eq!(*delivered.lock().unwrap(), ["a", "b", "c", "d", "e"]);
+        assert_eq!(sends.lock().unwrap().len(), 3);
+        assert_eq!(backlog(&storage).await?, (0, 0));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_buffered_messages_are_deleted_only_after_a_flush() -> Result<()> {
+        let storage = storage(&["a", "b", "c"]).await?;
+        let sink = TestSink {
+            flush_outage: 1,
+            flush_interval: Some(Duration::from_secs(60)),
+            ..TestSink::default()
+        };
+        let delivered = sink.delivered.clone();
+        let sends = sink.sends.clone();
+
+        let (wake, task) = dispatch(&storage, sink, config(), &DeadLetters::default());
+        wait_until(|| !sends.lock().unwrap().is_empty()).await;
+        // Sent, but the first flush failed
+        assert!(delivered.lock().unwrap().is_empty());
+        assert_eq!(backlog(&storage).await?, (3, 0));
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(*delivered.lock().unwrap(), ["a", "b", "c"]);
+        assert_eq!(backlog(&storage).await?, (0, 0));
+        assert_eq!(sends.lock().unwrap().len(), 1);
+
+        drop(wake);
+        task.await?;
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_deliveries_are_retried_with_backoff() -> Result<()> {
+        let storage = storage(&["a"]).await?;
+        let sink = TestSink {
+            outage: 4,
+            ..TestSink::default()
+        };
+        let delivered = sink.delivered.clone();
+        let sends = sink.sends.clone();
+        let config = OutboxConfig {
+            max_attempts: 10,
+            ..config()
+        };
+
+        let (wake, task) = dispatch(&storage, sink, config, &DeadLetters::default());
+        wait_until(|| !delivered.lock().unwrap().is_empty()).await;
+        drop(wake);
+        task.await?;
+
+        // Doubled from `retry_delay` up to `max_retry_delay`
+        let delays: Vec<u64> = sends
+            .lock()
+            .unwrap()
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 4]);
+        assert_eq!(*delivered.lock().unwrap(), ["a"]);
+        assert_eq!(backlog(&storage).await?, (0, 0));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_only_the_failing_message_becomes_a_dead_letter() -> Result<()> {
+        let storage = storage(&["a", "b", "c", "d", "e"]).await?;
+        let sink = TestSink {
+            rejected: vec!["c".to_string()],
+            ..TestSink::default()
+        };
+        let delivered = sink.delivered.clone();
+        let dead_letters = DeadLetters::default();
+
+        let (wake, task) = dispatch(&storage, sink, config(), &dead_letters);
+        wait_until(|| delivered.lock().unwrap().len() == 4).await;
+        drop(wake);
+        task.await?;
+
+        assert_eq!(*delivered.lock().unwrap(), ["a", "b", "d", "e"]);
+        assert_eq!(dead_letters.count(), 1);
+        assert_eq!(backlog(&storage).await?, (0, 1));
+        let backlog = storage.outbox_backlog().await?;
+        assert_eq!(backlog[0].attempts, 3);
+        assert_eq!(backlog[0].last_error.as_deref(), Some("rejected c"));
+        Ok(())
+    }
+}
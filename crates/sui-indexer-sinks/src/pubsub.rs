@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use eyre::{Result, WrapErr};
+use gcp_auth::{CustomServiceAccount, TokenProvider};
+use serde::Serialize;
+use sui_indexer_config::{EventFormat, OrderingKey, PubSubSinkConfig};
+use sui_indexer_events::ProcessedEvent;
+use tracing::{error, warn};
+use url::Url;
+
+use crate::{cloudevents, ordering_key, PartialDelivery, Sink, MAX_DELIVERY_ATTEMPTS, RETRY_DELAY};
+
+/// OAuth scope needed to publish
+const PUBSUB_SCOPE: &str = "https://www.googleapis.com/auth/pubsub";
+
+/// Public API endpoint, used unless `endpoint` is set
+const DEFAULT_ENDPOINT: &str = "https://pubsub.googleapis.com/";
+
+#[derive(Serialize)]
+struct PublishRequest<'a> {
+    messages: &'a [PubsubMessage],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PubsubMessage {
+    /// Base64 of the event JSON
+    data: String,
+    attributes: BTreeMap<&'static str, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ordering_key: Option<String>,
+}
+
+/// Publishes every event as a JSON message to a Google Cloud Pub/Sub topic
+///
+/// Messages carry `event_type` and `checkpoint` attributes for subscription
+/// filters, and the ordering key if one is configured. A failed publish
+/// request is retried on its own, so earlier requests of the batch are not
+/// published twice.
+pub struct PubSubSink {
+    client: reqwest::Client,
+    auth: Arc<dyn TokenProvider>,
+    publish_url: Url,
+    ordering_key: Option<OrderingKey>,
+    batch_size: usize,
+    format: EventFormat,
+}
+
+impl PubSubSink {
+    /// Resolve credentials for the topic of the given configuration
+    pub async fn new(config: &PubSubSinkConfig) -> Result<Self> {
+        let auth: Arc<dyn TokenProvider> = match &config.credentials_file {
+            Some(path) => Arc::new(
+                CustomServiceAccount::from_file(path)
+                    .wrap_err_with(|| format!("Failed to read {}", path.display()))?,
+            ),
+            None => gcp_auth::provider()
+                .await
+                .wrap_err("Failed to find Google Cloud credentials")?,
+        };
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => Url::parse(DEFAULT_ENDPOINT)?,
+        };
+        let publish_url = endpoint.join(&format!(
+            "v1/projects/{}/topics/{}:publish",
+            config.project, config.topic
+        ))?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            auth,
+            publish_url,
+            ordering_key: config.ordering_key,
+            batch_size: config.batch_size.clamp(1, 1000),
+            format: config.format,
+        })
+    }
+}
+
+impl PubSubSink {
+    /// Publish one request's worth of messages
+    async fn publish(&self, messages: &[PubsubMessage]) -> Result<()> {
+        // Tokens are cached and refreshed by the provider
+        let token = self.auth.token(&[PUBSUB_SCOPE]).await?;
+        self.client
+            .post(self.publish_url.clone())
+            .bearer_auth(token.as_str())
+            .json(&PublishRequest { messages })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for PubSubSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let mut dropped = 0;
+        for chunk in events.chunks(self.batch_size) {
+            let mut messages = Vec::with_capacity(chunk.len());
+            for event in chunk {
+                match cloudevents::payload(self.format, event) {
+                    Ok(payload) => messages.push(PubsubMessage {
+                        data: STANDARD.encode(payload),
+                        attributes: BTreeMap::from([
+                            ("event_type", event.event_type.clone()),
+                            ("checkpoint", event.checkpoint_sequence.to_string()),
+                        ]),
+                        ordering_key: self.ordering_key.map(|key| ordering_key(key, event)),
+                    }),
+                    Err(e) => {
+                        error!(event = %event.id, error = %e, "Failed to encode Pub/Sub message, dropping its event");
+                        dropped += 1;
+                    }
+                }
+            }
+            if messages.is_empty() {
+                continue;
+            }
+
+            // A publish request succeeds or fails as a whole, so only the failed one is sent again
+            let mut delay = RETRY_DELAY;
+            for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+                match self.publish(&messages).await {
+                    Ok(()) => break,
+                    Err(e) if attempt < MAX_DELIVERY_ATTEMPTS => {
+                        warn!(attempt, error = %e, "Pub/Sub publish failed, retrying");
+                        tokio::time::sleep(delay).await;
+                        delay *= 2;
+                    }
+                    Err(e) => {
+                        error!(
+                            events = messages.len(),
+                            error = %e,
+                            "Dropping events after repeated Pub/Sub publish failures"
+                        );
+                        dropped += messages.len();
+                    }
+                }
+            }
+        }
+        if dropped > 0 {
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
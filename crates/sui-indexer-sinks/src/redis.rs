@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use eyre::{Result, WrapErr};
+use redis::aio::ConnectionManager;
+use sui_indexer_config::{RedisMode, RedisSinkConfig};
+use sui_indexer_events::ProcessedEvent;
+
+use crate::{schema_registry::EventEncoder, Sink};
+
+/// Writes every event to a Redis stream or pub/sub channel
+///
+/// Stream entries carry the event in their `event` field next to its
+/// `checkpoint` and `event_type`, so consumers can skip entries unparsed.
+pub struct RedisSink {
+    connection: ConnectionManager,
+    target: Target,
+    encoder: EventEncoder,
+}
+
+impl RedisSink {
+    /// Connect to the Redis server of the given configuration
+    pub async fn connect(config: &RedisSinkConfig) -> Result<Self> {
+        let client = redis::Client::open(config.url.as_str()).wrap_err("Invalid Redis URL")?;
+        Ok(Self {
+            // Reconnects on its own after the connection drops
+            connection: ConnectionManager::new(client).await?,
+            target: Target {
+                mode: config.mode,
+                key: config.key.clone(),
+                max_len: config.max_len,
+            },
+            encoder: EventEncoder::new(config.format, config.schema_registry.as_ref())?,
+        })
+    }
+}
+
+/// Stream or channel the events are written to
+struct Target {
+    mode: RedisMode,
+    key: String,
+    max_len: Option<u64>,
+}
+
+impl Target {
+    /// Commands writing a batch of encoded events
+    fn pipeline(&self, payloads: &[(&ProcessedEvent, Vec<u8>)]) -> redis::Pipeline {
+        // One round trip per batch; a transaction keeps a retried batch from half applying
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for (event, payload) in payloads {
+            match self.mode {
+                RedisMode::Stream => {
+                    let command = pipe.cmd("XADD").arg(&self.key);
+                    if let Some(max_len) = self.max_len {
+                        command.arg("MAXLEN").arg("~").arg(max_len);
+                    }
+                    command
+                        .arg("*")
+                        .arg("checkpoint")
+                        .arg(event.checkpoint_sequence)
+                        .arg("event_type")
+                        .arg(&event.event_type)
+                        .arg("event")
+                        .arg(payload)
+                        .ignore();
+                }
+                RedisMode::PubSub => {
+                    pipe.cmd("PUBLISH").arg(&self.key).arg(payload).ignore();
+                }
+            }
+        }
+        pipe
+    }
+}
+
+#[async_trait]
+impl Sink for RedisSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let mut payloads = Vec::with_capacity(events.len());
+        for event in events {
+            payloads.push((event, self.encoder.encode(event).await?));
+        }
+        self.target
+            .pipeline(&payloads)
+            .query_async::<()>(&mut self.connection)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    async fn events() -> Result<Vec<ProcessedEvent>> {
+        let mut events = DefaultEventProcessor::new()
+            .process_events(vec![
+                TestEvent::new("0x2::pool::SwapEvent").seq(0).build(),
+                TestEvent::new("0x2::pool::MintEvent").seq(1).build(),
+            ])
+            .await?;
+        for (checkpoint, event) in (7..).zip(&mut events) {
+            event.checkpoint_sequence = checkpoint;
+        }
+        Ok(events)
+    }
+
+    /// Commands of a packed pipeline, as the server reads them
+    fn commands(pipe: &redis::Pipeline) -> Vec<Vec<String>> {
+        let packed = String::from_utf8(pipe.get_packed_pipeline()).unwrap();
+        let mut lines = packed.split("\r\n");
+        let mut commands = Vec::new();
+        while let Some(header) = lines.next().and_then(|line| line.strip_prefix('*')) {
+            let args = header.parse().unwrap();
+            commands.push(
+                (0..args)
+                    .map(|_| {
+                        lines.next(); // bulk length
+                        lines.next().unwrap().to_string()
+                    })
+                    .collect(),
+            );
+        }
+        commands
+    }
+
+    #[tokio::test]
+    async fn test_stream_entries_are_written_in_one_transaction() -> Result<()> {
+        let events = events().await?;
+        let payloads: Vec<_> = events
+            .iter()
+            .map(|event| (event, event.event_type.as_bytes().to_vec()))
+            .collect();
+        let target = Target {
+            mode: RedisMode::Stream,
+            key: "events".to_string(),
+            max_len: Some(1000),
+        };
+
+        let commands = commands(&target.pipeline(&payloads));
+        assert_eq!(commands.len(), 4);
+        assert_eq!(commands[0], ["MULTI"]);
+        assert_eq!(commands[3], ["EXEC"]);
+        for (command, event) in commands[1..3].iter().zip(&events) {
+            let checkpoint = event.checkpoint_sequence.to_string();
+            let expected = [
+                "XADD",
+                "events",
+                "MAXLEN",
+                "~",
+                "1000",
+                "*",
+                "checkpoint",
+                checkpoint.as_str(),
+                "event_type",
+                event.event_type.as_str(),
+                "event",
+                event.event_type.as_str(),
+            ];
+            assert_eq!(command, &expected);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_publishes_each_payload_to_the_channel() -> Result<()> {
+        let events = events().await?;
+        let payloads: Vec<_> = events.iter().map(|event| (event, b"{}".to_vec())).collect();
+        let target = Target {
+            mode: RedisMode::PubSub,
+            key: "sui".to_string(),
+            max_len: Some(1000),
+        };
+
+        let commands = commands(&target.pipeline(&payloads));
+        assert_eq!(
+            commands,
+            [
+                vec!["MULTI"],
+                vec!["PUBLISH", "sui", "{}"],
+                vec!["PUBLISH", "sui", "{}"],
+                vec!["EXEC"],
+            ]
+        );
+        Ok(())
+    }
+}
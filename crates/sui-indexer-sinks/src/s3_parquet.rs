@@ -0,0 +1,247 @@
+use std::{sync::Arc, time::Duration};
+
+use arrow_array::{ArrayRef, RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use async_trait::async_trait;
+use eyre::Result;
+use object_store::{aws::AmazonS3Builder, path::Path, ObjectStore, ObjectStoreExt};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use sui_indexer_config::S3ParquetSinkConfig;
+use sui_indexer_events::ProcessedEvent;
+use tracing::{debug, warn};
+
+use crate::Sink;
+
+/// Buffers events and writes them to S3 as Parquet files
+///
+/// Files are named after the first and last checkpoint they contain and
+/// the id of their first event, so uploading a batch again, after a failed
+/// or timed out attempt or when re-indexing a range, overwrites the same
+/// object instead of duplicating rows. A batch keeps its rows and name
+/// until it is uploaded; no further events are accepted while it fails.
+pub struct S3ParquetSink {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    max_rows: usize,
+    flush_interval: Duration,
+    buffer: Vec<ProcessedEvent>,
+    /// Batch cut from the buffer and not uploaded yet, with its object path
+    sealed: Option<(Path, Vec<ProcessedEvent>)>,
+}
+
+impl S3ParquetSink {
+    /// Create an S3 Parquet sink, reading credentials from the standard AWS environment
+    pub fn new(config: &S3ParquetSinkConfig) -> Result<Self> {
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(&config.bucket);
+        if let Some(region) = &config.region {
+            builder = builder.with_region(region);
+        }
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder
+                .with_endpoint(endpoint.as_str())
+                .with_allow_http(endpoint.scheme() == "http");
+        }
+
+        Ok(Self {
+            store: Arc::new(builder.build()?),
+            prefix: config.prefix.trim_matches('/').to_string(),
+            max_rows: config.max_rows.max(1),
+            flush_interval: Duration::from_secs(config.flush_interval.max(1)),
+            buffer: Vec::new(),
+            sealed: None,
+        })
+    }
+
+    /// Object path for the batch running from `first` to `last`, the same whenever it is uploaded
+    fn object_path(&self, first: &ProcessedEvent, last: &ProcessedEvent) -> Path {
+        let file = format!(
+            "{:020}-{:020}-{}.parquet",
+            first.checkpoint_sequence, last.checkpoint_sequence, first.id
+        );
+        if self.prefix.is_empty() {
+            Path::from(file)
+        } else {
+            Path::from(format!("{}/{file}", self.prefix))
+        }
+    }
+
+    /// Cut the buffered events into a batch with a fixed object path, unless one is pending
+    fn seal(&mut self) {
+        if self.sealed.is_some() {
+            return;
+        }
+        let (Some(first), Some(last)) = (self.buffer.first(), self.buffer.last()) else {
+            return;
+        };
+        let path = self.object_path(first, last);
+        self.sealed = Some((path, std::mem::take(&mut self.buffer)));
+    }
+
+    /// Upload the sealed batch, keeping it for the next attempt if that fails
+    async fn upload_sealed(&mut self) -> Result<()> {
+        let Some((path, events)) = &self.sealed else {
+            return Ok(());
+        };
+        let file = write_parquet(&events_record_batch(events)?)?;
+        self.store.put(path, file.into()).await?;
+
+        debug!(%path, rows = events.len(), "Wrote Parquet file");
+        self.sealed = None;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for S3ParquetSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        // Failing here leaves `events` out, so sending them again adds them once
+        self.upload_sealed().await?;
+        self.buffer.extend_from_slice(events);
+        if self.buffer.len() >= self.max_rows {
+            self.seal();
+            // The events are accepted; the batch is uploaded again with the next send or flush
+            if let Err(e) = self.upload_sealed().await {
+                warn!(error = %e, "Failed to write Parquet file, retrying later");
+            }
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.upload_sealed().await?;
+        self.seal();
+        self.upload_sealed().await
+    }
+
+    fn flush_interval(&self) -> Option<Duration> {
+        Some(self.flush_interval)
+    }
+}
+
+/// Arrow schema of processed events written to Parquet
+pub fn events_schema() -> SchemaRef {
+    let text = |name| Field::new(name, DataType::Utf8, false);
+    Arc::new(Schema::new(vec![
+        text("id"),
+        Field::new("checkpoint_sequence", DataType::UInt64, false),
+        text("transaction_digest"),
+        Field::new("event_index", DataType::UInt64, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        text("package_id"),
+        text("module_name"),
+        text("event_type"),
+        text("sender"),
+        text("fields"),
+    ]))
+}
+
+/// Convert processed events into a record batch with [`events_schema`]
+pub fn events_record_batch(events: &[ProcessedEvent]) -> Result<RecordBatch> {
+    let text = |value: fn(&ProcessedEvent) -> String| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(events.iter().map(value)))
+    };
+
+    let columns: Vec<ArrayRef> = vec![
+        text(|event| event.id.to_string()),
+        Arc::new(UInt64Array::from_iter_values(
+            events.iter().map(|event| event.checkpoint_sequence),
+        )),
+        text(|event| event.transaction_digest.to_string()),
+        Arc::new(UInt64Array::from_iter_values(
+            events.iter().map(|event| event.metadata.event_index as u64),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(
+                events
+                    .iter()
+                    .map(|event| event.timestamp.timestamp_millis()),
+            )
+            .with_timezone("UTC"),
+        ),
+        text(|event| event.package_id.to_string()),
+        text(|event| event.module_name.clone()),
+        text(|event| event.event_type.clone()),
+        text(|event| event.sender.clone()),
+        text(|event| event.fields.to_string()),
+    ];
+
+    Ok(RecordBatch::try_new(events_schema(), columns)?)
+}
+
+/// Encode a record batch as a Snappy-compressed Parquet file
+pub fn write_parquet(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut file = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut file, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sending_a_batch_again_overwrites_its_files() -> Result<()> {
+        let events = DefaultEventProcessor::new()
+            .process_events(
+                (0..3)
+                    .map(|seq| TestEvent::new("0x2::coin::Event").seq(seq).build())
+                    .collect(),
+            )
+            .await?;
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let sink = || S3ParquetSink {
+            store: store.clone(),
+            prefix: "events".to_string(),
+            max_rows: 2,
+            flush_interval: Duration::from_secs(60),
+            buffer: Vec::new(),
+            sealed: None,
+        };
+
+        for _ in 0..2 {
+            let mut sink = sink();
+            sink.send(&events).await?;
+            sink.flush().await?;
+        }
+        let files: Vec<_> = store.list(None).try_collect().await?;
+        assert_eq!(files.len(), 1);
+        assert!(files[0]
+            .location
+            .as_ref()
+            .ends_with(&format!("-{}.parquet", events[0].id)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_events_to_parquet() -> Result<()> {
+        let event = TestEvent::new("0x2::coin::Event")
+            .fields(serde_json::json!({"amount": "5"}))
+            .build();
+        let events = DefaultEventProcessor::new()
+            .process_events(vec![event])
+            .await?;
+
+        let batch = events_record_batch(&events)?;
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.schema(), events_schema());
+
+        let file = write_parquet(&batch)?;
+        assert!(file.starts_with(b"PAR1") && file.ends_with(b"PAR1"));
+        Ok(())
+    }
+}
@@ -0,0 +1,565 @@
+use std::collections::HashMap;
+
+use apache_avro::{types::Value as AvroValue, Schema};
+use eyre::{Result, WrapErr};
+use prost::Message;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sui_indexer_config::{EventFormat, SchemaRegistryConfig};
+use sui_indexer_events::ProcessedEvent;
+use url::Url;
+
+use crate::{cloudevents, SinkError};
+
+/// Leading byte of the Confluent wire format, followed by the schema ID
+const MAGIC_BYTE: u8 = 0;
+
+/// Protobuf schema of a processed event, matching [`EventMessage`]
+pub const PROTOBUF_SCHEMA: &str = r#"syntax = "proto3";
+
+package sui.indexer;
+
+message ProcessedEvent {
+  string id = 1;
+  string transaction_digest = 2;
+  uint64 event_sequence = 3;
+  uint64 checkpoint = 4;
+  int64 timestamp_ms = 5;
+  string package_id = 6;
+  string module = 7;
+  string event_type = 8;
+  string sender = 9;
+  string fields = 10;
+  repeated string matched_filters = 11;
+  repeated string tags = 12;
+}
+"#;
+
+/// Processed event as written in protobuf payloads
+///
+/// Event fields differ per type, so they are carried as a JSON string.
+/// Avro payloads hold the same fields, with the event fields as a record
+/// of their own, see [`AvroSchema`].
+#[derive(Clone, PartialEq, Message)]
+pub struct EventMessage {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub transaction_digest: String,
+    #[prost(uint64, tag = "3")]
+    pub event_sequence: u64,
+    #[prost(uint64, tag = "4")]
+    pub checkpoint: u64,
+    #[prost(int64, tag = "5")]
+    pub timestamp_ms: i64,
+    #[prost(string, tag = "6")]
+    pub package_id: String,
+    #[prost(string, tag = "7")]
+    pub module: String,
+    /// Full Move type of the event
+    #[prost(string, tag = "8")]
+    pub event_type: String,
+    #[prost(string, tag = "9")]
+    pub sender: String,
+    /// Event fields as JSON
+    #[prost(string, tag = "10")]
+    pub fields: String,
+    #[prost(string, repeated, tag = "11")]
+    pub matched_filters: Vec<String>,
+    #[prost(string, repeated, tag = "12")]
+    pub tags: Vec<String>,
+}
+
+impl EventMessage {
+    /// Message of a processed event
+    pub fn new(event: &ProcessedEvent) -> Result<Self> {
+        Ok(Self {
+            id: event.id.to_string(),
+            transaction_digest: event.transaction_digest.to_string(),
+            event_sequence: event.event.id.event_seq,
+            checkpoint: event.checkpoint_sequence,
+            timestamp_ms: event.timestamp.timestamp_millis(),
+            package_id: event.package_id.to_string(),
+            module: event.module_name.clone(),
+            event_type: event.event.type_.to_string(),
+            sender: event.sender.clone(),
+            fields: serde_json::to_string(&event.fields)?,
+            matched_filters: event.metadata.matched_filters.clone(),
+            tags: event.metadata.tags.clone(),
+        })
+    }
+}
+
+/// Shape of an event field in an Avro schema, inferred from its JSON
+///
+/// Every field may also be null, so Move options and fields added to a
+/// type later still fit the schema registered first.
+#[derive(Debug, Clone, PartialEq)]
+enum FieldType {
+    Boolean,
+    Long,
+    Double,
+    /// Strings, and values of a shape not known yet as their JSON text
+    String,
+    Array(Box<FieldType>),
+    /// Record name and its fields in order, by their JSON keys
+    Record(String, Vec<(String, FieldType)>),
+}
+
+impl FieldType {
+    /// Shape of `value`, naming records after `name`
+    fn infer(value: &Value, name: &str) -> Self {
+        match value {
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(number) if number.is_i64() => Self::Long,
+            Value::Number(_) => Self::Double,
+            Value::Array(items) => Self::Array(Box::new(
+                items
+                    .first()
+                    .map_or(Self::String, |item| Self::infer(item, name)),
+            )),
+            Value::Object(fields) => Self::Record(
+                avro_name(name),
+                fields
+                    .iter()
+                    .map(|(field, value)| {
+                        (
+                            field.clone(),
+                            Self::infer(value, &format!("{name}_{field}")),
+                        )
+                    })
+                    .collect(),
+            ),
+            Value::Null | Value::String(_) => Self::String,
+        }
+    }
+
+    /// Avro schema of a value of this shape, or null
+    fn schema(&self) -> Value {
+        let schema = match self {
+            Self::Boolean => json!("boolean"),
+            Self::Long => json!("long"),
+            Self::Double => json!("double"),
+            Self::String => json!("string"),
+            Self::Array(items) => json!({"type": "array", "items": items.schema()}),
+            Self::Record(name, fields) => json!({
+                "type": "record",
+                "name": name,
+                "fields": fields
+                    .iter()
+                    .map(|(field, shape)| json!({"name": avro_name(field), "type": shape.schema(), "default": null}))
+                    .collect::<Vec<_>>(),
+            }),
+        };
+        json!(["null", schema])
+    }
+
+    /// Avro value of `value` in the union of [`FieldType::schema`]
+    fn value(&self, value: &Value) -> Result<AvroValue> {
+        if value.is_null() {
+            return Ok(AvroValue::Union(0, Box::new(AvroValue::Null)));
+        }
+        let avro = match (self, value) {
+            (Self::Boolean, Value::Bool(value)) => AvroValue::Boolean(*value),
+            (Self::Long, Value::Number(number)) if number.is_i64() => {
+                AvroValue::Long(number.as_i64().unwrap_or_default())
+            }
+            (Self::Double, Value::Number(number)) => {
+                AvroValue::Double(number.as_f64().unwrap_or_default())
+            }
+            (Self::String, Value::String(value)) => AvroValue::String(value.clone()),
+            (Self::String, value) => AvroValue::String(value.to_string()),
+            (Self::Array(items), Value::Array(values)) => AvroValue::Array(
+                values
+                    .iter()
+                    .map(|value| items.value(value))
+                    .collect::<Result<_>>()?,
+            ),
+            (Self::Record(_, fields), Value::Object(values)) => AvroValue::Record(
+                fields
+                    .iter()
+                    .map(|(field, shape)| {
+                        let value = values.get(field).unwrap_or(&Value::Null);
+                        Ok((avro_name(field), shape.value(value)?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            (shape, value) => {
+                return Err(
+                    SinkError::SchemaMismatch(format!("expected {shape:?}, found {value}")).into(),
+                )
+            }
+        };
+        Ok(AvroValue::Union(1, Box::new(avro)))
+    }
+}
+
+/// Avro schema of the events of one type, with their fields as a record of their own
+///
+/// The shape of the fields is taken from the first event of the type seen,
+/// and later events of the type are written in it.
+pub struct AvroSchema {
+    schema: Schema,
+    /// Schema JSON as registered
+    text: String,
+    fields: FieldType,
+}
+
+impl AvroSchema {
+    /// Schema of the type of `event`, inferred from its fields
+    pub fn infer(event: &ProcessedEvent) -> Result<Self> {
+        let event_type = event.event.type_.name.to_string();
+        let fields = FieldType::infer(&event.fields, &format!("{event_type}Fields"));
+        let text = json!({
+            "type": "record",
+            "name": avro_name(&event_type),
+            "namespace": "sui.indexer",
+            "fields": [
+                {"name": "id", "type": "string"},
+                {"name": "transaction_digest", "type": "string"},
+                {"name": "event_sequence", "type": "long"},
+                {"name": "checkpoint", "type": "long"},
+                {"name": "timestamp_ms", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+                {"name": "package_id", "type": "string"},
+                {"name": "module", "type": "string"},
+                {"name": "event_type", "type": "string"},
+                {"name": "sender", "type": "string"},
+                {"name": "fields", "type": fields.schema()},
+                {"name": "matched_filters", "type": {"type": "array", "items": "string"}},
+                {"name": "tags", "type": {"type": "array", "items": "string"}}
+            ]
+        })
+        .to_string();
+        let schema = Schema::parse_str(&text)
+            .map_err(|e| SinkError::SchemaMismatch(format!("invalid Avro schema: {e}")))?;
+        Ok(Self {
+            schema,
+            text,
+            fields,
+        })
+    }
+
+    /// Avro binary encoding of an event of the type
+    pub fn encode(&self, event: &ProcessedEvent) -> Result<Vec<u8>> {
+        let message = EventMessage::new(event)?;
+        let strings = |values: Vec<String>| {
+            AvroValue::Array(values.into_iter().map(AvroValue::String).collect())
+        };
+        let record = AvroValue::Record(vec![
+            ("id".to_string(), AvroValue::String(message.id)),
+            (
+                "transaction_digest".to_string(),
+                AvroValue::String(message.transaction_digest),
+            ),
+            (
+                "event_sequence".to_string(),
+                AvroValue::Long(message.event_sequence as i64),
+            ),
+            (
+                "checkpoint".to_string(),
+                AvroValue::Long(message.checkpoint as i64),
+            ),
+            (
+                "timestamp_ms".to_string(),
+                AvroValue::TimestampMillis(message.timestamp_ms),
+            ),
+            (
+                "package_id".to_string(),
+                AvroValue::String(message.package_id),
+            ),
+            ("module".to_string(), AvroValue::String(message.module)),
+            (
+                "event_type".to_string(),
+                AvroValue::String(message.event_type),
+            ),
+            ("sender".to_string(), AvroValue::String(message.sender)),
+            ("fields".to_string(), self.fields.value(&event.fields)?),
+            (
+                "matched_filters".to_string(),
+                strings(message.matched_filters),
+            ),
+            ("tags".to_string(), strings(message.tags)),
+        ]);
+        apache_avro::to_avro_datum(&self.schema, record)
+            .map_err(|e| SinkError::SchemaMismatch(format!("{}: {e}", event.event.type_)).into())
+    }
+}
+
+/// `name` with the characters Avro names may not hold replaced by underscores
+fn avro_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name
+    } else {
+        format!("_{name}")
+    }
+}
+
+/// Payload in the Confluent wire format: magic byte, big-endian schema ID, body
+fn frame(schema_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(5 + body.len());
+    payload.push(MAGIC_BYTE);
+    payload.extend_from_slice(&schema_id.to_be_bytes());
+    payload.extend_from_slice(body);
+    payload
+}
+
+#[derive(Deserialize)]
+struct Registered {
+    id: u32,
+}
+
+/// Client of a Confluent Schema Registry registering one subject per event type
+///
+/// Registering a schema the subject already has returns its existing ID,
+/// so every subject is registered once per process and its ID cached.
+pub struct SchemaRegistry {
+    client: reqwest::Client,
+    url: Url,
+    username: Option<String>,
+    password: Option<String>,
+    subject_prefix: String,
+    /// Schema IDs by subject
+    ids: HashMap<String, u32>,
+}
+
+impl SchemaRegistry {
+    /// Create a client, resolving the password secret
+    pub fn new(config: &SchemaRegistryConfig) -> Result<Self> {
+        if config.url.cannot_be_a_base() {
+            return Err(SinkError::InvalidRegistryUrl(config.url.to_string()).into());
+        }
+        let password = config
+            .password
+            .as_ref()
+            .map(|secret| secret.resolve())
+            .transpose()?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            username: config.username.clone(),
+            password,
+            subject_prefix: config.subject_prefix.clone(),
+            ids: HashMap::new(),
+        })
+    }
+
+    /// Subject of an event: the prefix and its Move type without type arguments
+    pub fn subject(&self, event: &ProcessedEvent) -> String {
+        let event_type = event.event.type_.to_string();
+        let base = event_type.split('<').next().unwrap_or_default();
+        format!("{}{base}", self.subject_prefix)
+    }
+
+    /// ID of `schema`, of `schema_type` `AVRO` or `PROTOBUF`, under `subject`, registering it if needed
+    pub async fn schema_id(
+        &mut self,
+        subject: &str,
+        schema_type: &str,
+        schema: &str,
+    ) -> Result<u32> {
+        if let Some(id) = self.ids.get(subject) {
+            return Ok(*id);
+        }
+
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .map_err(|()| SinkError::InvalidRegistryUrl(self.url.to_string()))?
+            .pop_if_empty()
+            .extend(["subjects", subject, "versions"]);
+
+        let mut request = self.client.post(url).json(&serde_json::json!({
+            "schemaType": schema_type,
+            "schema": schema,
+        }));
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+        let registered: Registered = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .wrap_err_with(|| format!("Failed to register schema for subject `{subject}`"))?;
+
+        self.ids.insert(subject.to_string(), registered.id);
+        Ok(registered.id)
+    }
+}
+
+/// Encodes events in the payload format of a message sink
+pub struct EventEncoder {
+    format: EventFormat,
+    /// Set for the formats framed with a registered schema
+    registry: Option<SchemaRegistry>,
+    /// Avro schemas by subject
+    avro: HashMap<String, AvroSchema>,
+}
+
+impl EventEncoder {
+    /// Create an encoder; Avro and protobuf need a schema registry
+    pub fn new(format: EventFormat, registry: Option<&SchemaRegistryConfig>) -> Result<Self> {
+        let registry = match registry {
+            Some(config) if format.uses_schema_registry() => Some(SchemaRegistry::new(config)?),
+            None if format.uses_schema_registry() => {
+                return Err(SinkError::SchemaRegistryRequired(format!("{format:?}")).into())
+            }
+            _ => None,
+        };
+        Ok(Self {
+            format,
+            registry,
+            avro: HashMap::new(),
+        })
+    }
+
+    /// Format events are encoded in
+    pub fn format(&self) -> EventFormat {
+        self.format
+    }
+
+    /// Message payload of an event
+    pub async fn encode(&mut self, event: &ProcessedEvent) -> Result<Vec<u8>> {
+        let Some(registry) = &mut self.registry else {
+            return cloudevents::payload(self.format, event);
+        };
+        let subject = registry.subject(event);
+        let (schema_id, body) = match self.format {
+            EventFormat::Protobuf => {
+                // Message indexes, a single 0 for the first message of the schema
+                let mut body = vec![0];
+                EventMessage::new(event)?.encode(&mut body)?;
+                let id = registry
+                    .schema_id(&subject, "PROTOBUF", PROTOBUF_SCHEMA)
+                    .await?;
+                (id, body)
+            }
+            _ => {
+                if !self.avro.contains_key(&subject) {
+                    self.avro.insert(subject.clone(), AvroSchema::infer(event)?);
+                }
+                let schema = &self.avro[&subject];
+                let body = schema.encode(event)?;
+                (
+                    registry.schema_id(&subject, "AVRO", &schema.text).await?,
+                    body,
+                )
+            }
+        };
+        Ok(frame(schema_id, &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    async fn event() -> Result<ProcessedEvent> {
+        let event = TestEvent::new("0x2::pool::SwapEvent<0x2::sui::SUI>")
+            .seq(3)
+            .fields(serde_json::json!({
+                "amount": "5",
+                "pool": {"id": "0x1", "fee": 3},
+                "path": [],
+                "owner": null
+            }))
+            .build();
+        let mut events = DefaultEventProcessor::new()
+            .process_events(vec![event])
+            .await?;
+        Ok(events.remove(0))
+    }
+
+    #[tokio::test]
+    async fn test_avro_payload_round_trips_with_typed_fields() -> Result<()> {
+        let event = event().await?;
+        let schema = AvroSchema::infer(&event)?;
+        assert!(schema.text.contains(r#""name":"SwapEvent""#));
+
+        let payload = frame(7, &schema.encode(&event)?);
+        assert_eq!(payload[..5], [MAGIC_BYTE, 0, 0, 0, 7]);
+        let AvroValue::Record(record) =
+            apache_avro::from_avro_datum(&schema.schema, &mut &payload[5..], None)?
+        else {
+            panic!("expected a record");
+        };
+        let field = |record: &[(String, AvroValue)], name: &str| {
+            record
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        };
+        assert_eq!(field(&record, "event_sequence"), Some(AvroValue::Long(3)));
+        let Some(AvroValue::Union(1, fields)) = field(&record, "fields") else {
+            panic!("expected event fields");
+        };
+        let AvroValue::Record(fields) = *fields else {
+            panic!("expected a record of event fields");
+        };
+        let some = |value| AvroValue::Union(1, Box::new(value));
+        assert_eq!(
+            field(&fields, "amount"),
+            Some(some(AvroValue::String("5".to_string())))
+        );
+        assert_eq!(
+            field(&fields, "owner"),
+            Some(AvroValue::Union(0, Box::new(AvroValue::Null)))
+        );
+        let Some(AvroValue::Union(1, pool)) = field(&fields, "pool") else {
+            panic!("expected the pool record");
+        };
+        assert!(
+            matches!(*pool, AvroValue::Record(ref pool) if field(pool, "fee") == Some(some(AvroValue::Long(3))))
+        );
+
+        // Later events of the type are written in the schema of the first
+        let mut other = event.clone();
+        other.fields["owner"] = serde_json::json!("0x9");
+        assert!(schema.encode(&other).is_ok());
+        other.fields["pool"] = serde_json::json!("0x1");
+        let err = schema.encode(&other).unwrap_err();
+        assert!(matches!(
+            SinkError::find(&err),
+            Some(SinkError::SchemaMismatch(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_protobuf_payload_round_trips() -> Result<()> {
+        let event = event().await?;
+        let message = EventMessage::new(&event)?;
+        assert_eq!(message.event_sequence, 3);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&message.fields)?,
+            event.fields
+        );
+
+        let payload = frame(7, &message.encode_to_vec());
+        assert_eq!(payload[..5], [MAGIC_BYTE, 0, 0, 0, 7]);
+        assert_eq!(EventMessage::decode(&payload[5..])?, message);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subject_per_event_type() -> Result<()> {
+        let registry = SchemaRegistry::new(&SchemaRegistryConfig {
+            url: "http://localhost:8081".parse()?,
+            username: None,
+            password: None,
+            subject_prefix: "sui-".to_string(),
+        })?;
+        let subject = registry.subject(&event().await?);
+        assert!(subject.starts_with("sui-0x"));
+        assert!(subject.ends_with("::pool::SwapEvent"));
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use eyre::{Result, WrapErr};
+use object_store::{aws::AmazonS3Builder, path::Path, ObjectStore, ObjectStoreExt};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sui_indexer_config::{EventsConfig, SnowflakeSinkConfig, SnowflakeTokenType};
+use sui_indexer_events::ProcessedEvent;
+use tracing::{debug, error, warn};
+use url::Url;
+
+use crate::{
+    warehouse::{Row, TableBuffer, BUFFERED_BATCHES},
+    PartialDelivery, Sink, SinkError,
+};
+
+/// Seconds Snowflake runs a load before cancelling it
+const STATEMENT_TIMEOUT: u64 = 300;
+
+/// Delay between polls of a load still running
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatementResponse {
+    #[serde(default)]
+    statement_handle: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    /// Rows of the first result partition, as text
+    #[serde(default)]
+    data: Vec<Vec<Option<String>>>,
+}
+
+/// Micro-batches events into Snowflake tables through an external stage
+///
+/// Rows are written as newline-delimited JSON to the bucket behind the stage
+/// and loaded with `COPY INTO`, matching columns by name. `COPY INTO` would
+/// skip fields without a column, so the table's columns are checked first
+/// and a load with such fields fails instead. Files are named after their
+/// checkpoint range and content, so Snowflake skips files it already loaded
+/// when a range is re-indexed. Failed rows stay buffered up to
+/// [`BUFFERED_BATCHES`] batches; events arriving beyond that are dropped and
+/// counted.
+pub struct SnowflakeSink {
+    client: reqwest::Client,
+    statements_url: Url,
+    token: String,
+    token_type: &'static str,
+    context: serde_json::Map<String, serde_json::Value>,
+    stage: String,
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    max_rows: usize,
+    flush_interval: Duration,
+    buffer: TableBuffer,
+    /// Upper-case column names of the tables loaded so far
+    columns: HashMap<String, HashSet<String>>,
+}
+
+impl SnowflakeSink {
+    /// Create a Snowflake sink, reading bucket credentials from the standard AWS environment
+    pub fn new(config: &SnowflakeSinkConfig, events: &EventsConfig) -> Result<Self> {
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(&config.bucket);
+        if let Some(region) = &config.region {
+            builder = builder.with_region(region);
+        }
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder
+                .with_endpoint(endpoint.as_str())
+                .with_allow_http(endpoint.scheme() == "http");
+        }
+
+        let mut context = serde_json::Map::new();
+        context.insert("database".into(), config.database.clone().into());
+        context.insert("schema".into(), config.schema.clone().into());
+        if let Some(warehouse) = &config.warehouse {
+            context.insert("warehouse".into(), warehouse.clone().into());
+        }
+        if let Some(role) = &config.role {
+            context.insert("role".into(), role.clone().into());
+        }
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            statements_url: Url::parse(&format!(
+                "https://{}.snowflakecomputing.com/api/v2/statements/",
+                config.account
+            ))?,
+            token: config
+                .token
+                .resolve()
+                .wrap_err("Failed to read the Snowflake token")?,
+            token_type: match config.token_type {
+                SnowflakeTokenType::ProgrammaticAccessToken => "PROGRAMMATIC_ACCESS_TOKEN",
+                SnowflakeTokenType::Oauth => "OAUTH",
+                SnowflakeTokenType::KeypairJwt => "KEYPAIR_JWT",
+            },
+            context,
+            stage: config.stage.trim_start_matches('@').to_string(),
+            store: Arc::new(builder.build()?),
+            prefix: config.prefix.trim_matches('/').to_string(),
+            max_rows: config.max_rows.max(1),
+            flush_interval: Duration::from_secs(config.flush_interval.max(1)),
+            buffer: TableBuffer::new(&config.table, &config.tables, events)?,
+            columns: HashMap::new(),
+        })
+    }
+
+    /// Fail unless `table` has a column for each of `fields`
+    ///
+    /// Columns are read once per table and again when a field is missing,
+    /// so columns added to the table are picked up without a restart.
+    async fn check_columns(&mut self, table: &str, fields: &BTreeSet<String>) -> Result<()> {
+        let known = self
+            .columns
+            .get(table)
+            .is_some_and(|columns| missing_columns(fields, columns).is_empty());
+        if known {
+            return Ok(());
+        }
+
+        let described = self
+            .execute(&format!("DESCRIBE TABLE {table}"))
+            .await
+            .wrap_err_with(|| format!("Failed to read the columns of `{table}`"))?;
+        // The first column of DESCRIBE TABLE is the column name
+        let columns: HashSet<String> = described
+            .data
+            .into_iter()
+            .filter_map(|row| row.into_iter().next().flatten())
+            .map(|name| name.to_ascii_uppercase())
+            .collect();
+        let missing = missing_columns(fields, &columns);
+        self.columns.insert(table.to_string(), columns);
+        if !missing.is_empty() {
+            return Err(SinkError::MissingColumns {
+                table: table.to_string(),
+                columns: missing.join(", "),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Stage the rows of `table` as a file and copy it into the table
+    async fn load(&self, table: &str, rows: &[Row]) -> Result<()> {
+        let mut file = Vec::new();
+        for row in rows {
+            serde_json::to_writer(&mut file, row)?;
+            file.push(b'\n');
+        }
+        let checkpoint = |row: Option<&Row>| {
+            row.and_then(|row| row["checkpoint_sequence"].as_u64())
+                .unwrap_or_default()
+        };
+        let hash = hex::encode(&Sha256::digest(&file)[..8]);
+        let name = format!(
+            "{:020}-{:020}-{hash}.json",
+            checkpoint(rows.first()),
+            checkpoint(rows.last())
+        );
+        let path = if self.prefix.is_empty() {
+            Path::from(format!("{table}/{name}"))
+        } else {
+            Path::from(format!("{}/{table}/{name}", self.prefix))
+        };
+        self.store.put(&path, file.into()).await?;
+
+        self.execute(&format!(
+            "COPY INTO {table} FROM @{}/{table}/ FILES = ('{name}') \
+             FILE_FORMAT = (TYPE = JSON) MATCH_BY_COLUMN_NAME = CASE_INSENSITIVE",
+            self.stage
+        ))
+        .await
+        .wrap_err_with(|| format!("Failed to load {path} into `{table}`"))?;
+        debug!(table, rows = rows.len(), %path, "Loaded rows into Snowflake");
+        Ok(())
+    }
+
+    /// Run a statement with the SQL API, waiting for its result
+    async fn execute(&self, statement: &str) -> Result<StatementResponse> {
+        let mut body = self.context.clone();
+        body.insert("statement".into(), statement.into());
+        body.insert("timeout".into(), json!(STATEMENT_TIMEOUT));
+        let mut request = self.client.post(self.statements_url.clone()).json(&body);
+
+        loop {
+            let response = request
+                .bearer_auth(&self.token)
+                .header("X-Snowflake-Authorization-Token-Type", self.token_type)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .send()
+                .await?;
+            let status = response.status();
+            // Error bodies are not always JSON
+            let result: StatementResponse =
+                serde_json::from_slice(&response.bytes().await?).unwrap_or_default();
+            if !status.is_success() {
+                return Err(SinkError::Delivery {
+                    service: "Snowflake SQL API",
+                    reason: format!("{status}: {}", result.message.unwrap_or_default()),
+                }
+                .into());
+            }
+            // 202 means the statement is still running
+            if status != reqwest::StatusCode::ACCEPTED {
+                return Ok(result);
+            }
+            let handle = result.statement_handle.ok_or_else(|| SinkError::Delivery {
+                service: "Snowflake SQL API",
+                reason: "no statement handle in the response".to_string(),
+            })?;
+            tokio::time::sleep(POLL_INTERVAL).await;
+            request = self.client.get(self.statements_url.join(&handle)?);
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for SnowflakeSink {
+    async fn send(&mut self, events: &[ProcessedEvent]) -> Result<()> {
+        let limit = self.max_rows * BUFFERED_BATCHES;
+        let dropped = self.buffer.extend_bounded(events, limit);
+        if self.buffer.len() >= self.max_rows {
+            if let Err(e) = self.flush().await {
+                warn!(
+                    error = %e,
+                    buffered = self.buffer.len(),
+                    "Failed to load rows into Snowflake, keeping them buffered"
+                );
+            }
+        }
+        if dropped > 0 {
+            error!(
+                events = dropped,
+                limit, "Snowflake loads keep failing and the buffer is full, dropping events"
+            );
+            return Err(PartialDelivery { dropped }.into());
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        while let Some((table, rows)) = self.buffer.first() {
+            let table = table.to_string();
+            let fields = rows.iter().flat_map(|row| row.keys().cloned()).collect();
+            self.check_columns(&table, &fields).await?;
+            if let Some((table, rows)) = self.buffer.first() {
+                self.load(table, rows).await?;
+            }
+            self.buffer.pop_first();
+        }
+        Ok(())
+    }
+
+    fn flush_interval(&self) -> Option<Duration> {
+        Some(self.flush_interval)
+    }
+
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
+
+/// Fields without a column among the upper-case `columns`, as columns match case-insensitively
+fn missing_columns(fields: &BTreeSet<String>, columns: &HashSet<String>) -> Vec<String> {
+    fields
+        .iter()
+        .filter(|field| !columns.contains(&field.to_ascii_uppercase()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_columns_match_case_insensitively() {
+        let fields = BTreeSet::from(["id".to_string(), "amount_in".to_string()]);
+        let columns = HashSet::from(["ID".to_string()]);
+        assert_eq!(missing_columns(&fields, &columns), vec!["amount_in"]);
+
+        let columns = HashSet::from(["ID".to_string(), "AMOUNT_IN".to_string()]);
+        assert!(missing_columns(&fields, &columns).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use serde_json::{Map, Value};
+use sui_indexer_config::EventsConfig;
+use sui_indexer_events::{EventFilterProcessor, ProcessedEvent};
+
+/// Warehouse row: column names and JSON values
+pub(crate) type Row = Map<String, Value>;
+
+/// Batches of `max_rows` a warehouse sink keeps while its loads fail, before it drops new events
+pub(crate) const BUFFERED_BATCHES: usize = 10;
+
+/// Rows buffered per target table of a warehouse sink
+///
+/// An event goes to the table of the first configured filter it matches,
+/// in the order of `events.filters`, and to the default table otherwise.
+pub(crate) struct TableBuffer {
+    routes: EventFilterProcessor,
+    tables: Vec<String>,
+    default: String,
+    rows: BTreeMap<String, Vec<Row>>,
+}
+
+impl TableBuffer {
+    /// Route events to `tables` by filter name, falling back to `default`
+    pub(crate) fn new(
+        default: &str,
+        tables: &BTreeMap<String, String>,
+        events: &EventsConfig,
+    ) -> Result<Self> {
+        let names: Vec<String> = tables.keys().cloned().collect();
+        // Checks every name, whose order is then taken from the event filters
+        events.named_filters(&names)?;
+        let filters: Vec<_> = events
+            .filters
+            .iter()
+            .filter(|filter| {
+                filter
+                    .name
+                    .as_ref()
+                    .is_some_and(|name| tables.contains_key(name))
+            })
+            .cloned()
+            .collect();
+        let tables = filters
+            .iter()
+            .filter_map(|filter| tables.get(filter.name.as_deref()?).cloned())
+            .collect();
+
+        Ok(Self {
+            routes: EventFilterProcessor::new(filters),
+            tables,
+            default: default.to_string(),
+            rows: BTreeMap::new(),
+        })
+    }
+
+    /// Buffer the rows of `events` under their tables
+    pub(crate) fn extend(&mut self, events: &[ProcessedEvent]) {
+        for event in events {
+            let table = match self.routes.matching_filter(&event.event) {
+                Some((index, _)) => &self.tables[index],
+                None => &self.default,
+            };
+            self.rows.entry(table.clone()).or_default().push(row(event));
+        }
+    }
+
+    /// Buffer as many of `events` as fit under `limit` rows, returning how many did not
+    pub(crate) fn extend_bounded(&mut self, events: &[ProcessedEvent], limit: usize) -> usize {
+        let room = limit.saturating_sub(self.len()).min(events.len());
+        self.extend(&events[..room]);
+        events.len() - room
+    }
+
+    /// Rows buffered across all tables
+    pub(crate) fn len(&self) -> usize {
+        self.rows.values().map(Vec::len).sum()
+    }
+
+    /// The buffered rows of the first table, to load before [`Self::pop_first`]
+    pub(crate) fn first(&self) -> Option<(&str, &[Row])> {
+        self.rows
+            .first_key_value()
+            .map(|(table, rows)| (table.as_str(), rows.as_slice()))
+    }
+
+    /// Drop the rows returned by [`Self::first`] once they are loaded
+    pub(crate) fn pop_first(&mut self) {
+        self.rows.pop_first();
+    }
+}
+
+/// Row of an event: its metadata, then a column per decoded field
+///
+/// Fields come from the protocol decoder if it recognized the event and from
+/// the Move event JSON otherwise. Names are converted to snake case, nested
+/// values are written as JSON text and null values are left out.
+pub(crate) fn row(event: &ProcessedEvent) -> Row {
+    let mut row = Map::new();
+    row.insert("id".into(), event.id.to_string().into());
+    row.insert(
+        "checkpoint_sequence".into(),
+        event.checkpoint_sequence.into(),
+    );
+    row.insert(
+        "transaction_digest".into(),
+        event.transaction_digest.to_string().into(),
+    );
+    row.insert("event_index".into(), event.metadata.event_index.into());
+    row.insert("timestamp".into(), event.timestamp.to_rfc3339().into());
+    row.insert("package_id".into(), event.package_id.to_string().into());
+    row.insert("module_name".into(), event.module_name.clone().into());
+    row.insert("event_type".into(), event.event_type.clone().into());
+    row.insert("sender".into(), event.sender.clone().into());
+
+    let decoded = event
+        .fields
+        .get("protocol")
+        .or_else(|| event.fields.get("parsed_json"));
+    if let Some(Value::Object(fields)) = decoded {
+        for (name, value) in fields {
+            let value = match value {
+                Value::Null => continue,
+                Value::Array(_) | Value::Object(_) => Value::String(value.to_string()),
+                value => value.clone(),
+            };
+            let mut column = column_name(name);
+            if row.contains_key(&column) {
+                column = format!("field_{column}");
+            }
+            row.insert(column, value);
+        }
+    }
+    row
+}
+
+/// Snake-case column name of a field, e.g. `amountIn` becomes `amount_in`
+fn column_name(field: &str) -> String {
+    let mut column = String::with_capacity(field.len() + 4);
+    let mut previous_lower = false;
+    for c in field.chars() {
+        if c.is_ascii_uppercase() && previous_lower {
+            column.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        column.push(if c.is_ascii_alphanumeric() {
+            c.to_ascii_lowercase()
+        } else {
+            '_'
+        });
+    }
+    if !column.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        column.insert(0, '_');
+    }
+    column
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::{EventFilter, FilterPriority};
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+
+    async fn events() -> Result<Vec<ProcessedEvent>> {
+        let events = ["pool", "coin"]
+            .into_iter()
+            .map(|module| {
+                TestEvent::new(&format!("0x2::{module}::Event"))
+                    .fields(serde_json::json!({"amountIn": "5", "sender": "0x456", "path": ["SUI"], "memo": null}))
+                    .build()
+            })
+            .collect();
+        DefaultEventProcessor::new().process_events(events).await
+    }
+
+    #[tokio::test]
+    async fn test_row_maps_decoded_fields_to_columns() -> Result<()> {
+        let event = &events().await?[0];
+        let row = row(event);
+        assert_eq!(row["module_name"], "pool");
+        assert_eq!(row["amount_in"], "5");
+        assert_eq!(row["sender"], event.sender);
+        assert_eq!(row["field_sender"], "0x456");
+        assert_eq!(row["path"], r#"["SUI"]"#);
+        assert!(!row.contains_key("memo"));
+
+        assert_eq!(column_name("TokenX.Amount"), "token_x_amount");
+        assert_eq!(column_name("0x2"), "_0x2");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_events_routed_by_filter() -> Result<()> {
+        let events_config = EventsConfig {
+            filters: vec![EventFilter {
+                name: Some("pools".to_string()),
+                package: None,
+                module: Some("pool".to_string()),
+                event_type: None,
+                sender: None,
+                priority: FilterPriority::Normal,
+            }],
+            ..EventsConfig::default()
+        };
+        let tables = BTreeMap::from([("pools".to_string(), "pool_events".to_string())]);
+        let mut buffer = TableBuffer::new("events", &tables, &events_config)?;
+        buffer.extend(&events().await?);
+        assert_eq!(buffer.len(), 2);
+
+        let (table, rows) = buffer.first().expect("rows are buffered");
+        assert_eq!(
+            (table, rows[0]["module_name"].as_str()),
+            ("events", Some("coin"))
+        );
+        buffer.pop_first();
+        let (table, rows) = buffer.first().expect("rows are buffered");
+        assert_eq!(
+            (table, rows[0]["module_name"].as_str()),
+            ("pool_events", Some("pool"))
+        );
+        buffer.pop_first();
+        assert!(buffer.first().is_none());
+
+        let unknown = BTreeMap::from([("missing".to_string(), "x".to_string())]);
+        assert!(TableBuffer::new("events", &unknown, &events_config).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_buffer_stops_at_its_limit() -> Result<()> {
+        let events = events().await?;
+        let mut buffer = TableBuffer::new("events", &BTreeMap::new(), &EventsConfig::default())?;
+        assert_eq!(buffer.extend_bounded(&events, 3), 0);
+        assert_eq!(buffer.extend_bounded(&events, 3), 1);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.extend_bounded(&events, 3), 2);
+        Ok(())
+    }
+}
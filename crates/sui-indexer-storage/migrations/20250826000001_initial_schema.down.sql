@@ -0,0 +1,5 @@
+-- Revert: 20250826000001_initial_schema
+
+DROP TABLE IF EXISTS checkpoint_progress;
+DROP TABLE IF EXISTS transactions;
+DROP TABLE IF EXISTS events;
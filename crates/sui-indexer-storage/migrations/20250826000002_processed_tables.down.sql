@@ -0,0 +1,5 @@
+-- Revert: 20250826000002_processed_tables
+
+DROP TABLE IF EXISTS indexer_state;
+DROP TABLE IF EXISTS processed_events;
+DROP TABLE IF EXISTS processed_transactions;
@@ -0,0 +1,11 @@
+-- Revert: 20250901000001_storage_toggles
+
+DROP INDEX IF EXISTS idx_events_timestamp_processed;
+DROP INDEX IF EXISTS idx_transactions_timestamp_processed;
+
+-- Rows stored without raw data keep a JSON null so the columns can be required again
+UPDATE processed_events SET event_data = 'null'::jsonb WHERE event_data IS NULL;
+ALTER TABLE processed_events ALTER COLUMN event_data SET NOT NULL;
+
+UPDATE processed_transactions SET effects = 'null'::jsonb WHERE effects IS NULL;
+ALTER TABLE processed_transactions ALTER COLUMN effects SET NOT NULL;
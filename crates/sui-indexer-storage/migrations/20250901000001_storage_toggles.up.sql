@@ -0,0 +1,15 @@
+-- Optional raw event and effects storage
+-- Migration: 20250901000001_storage_toggles
+
+-- Raw event JSON is skipped when storage.store_raw_event is disabled
+ALTER TABLE processed_events ALTER COLUMN event_data DROP NOT NULL;
+
+-- Effects are skipped when storage.store_transactions_effects is disabled
+ALTER TABLE processed_transactions ALTER COLUMN effects DROP NOT NULL;
+
+-- Retention deletes by timestamp
+CREATE INDEX IF NOT EXISTS idx_events_timestamp_processed
+ON processed_events (timestamp);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_timestamp_processed
+ON processed_transactions (timestamp);
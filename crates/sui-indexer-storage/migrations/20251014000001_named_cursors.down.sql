@@ -0,0 +1,7 @@
+-- Revert: 20251014000001_named_cursors
+
+-- Only the default cursor survives without a pipeline column
+DELETE FROM indexer_state WHERE pipeline <> 'default';
+
+DROP INDEX IF EXISTS idx_indexer_state_pipeline;
+ALTER TABLE indexer_state DROP COLUMN IF EXISTS pipeline;
@@ -0,0 +1,8 @@
+-- Named checkpoint cursors
+-- Migration: 20251014000001_named_cursors
+
+-- Every cursor update appends a row; the newest row per pipeline is its cursor
+ALTER TABLE indexer_state ADD COLUMN IF NOT EXISTS pipeline TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_indexer_state_pipeline
+ON indexer_state (pipeline, id DESC);
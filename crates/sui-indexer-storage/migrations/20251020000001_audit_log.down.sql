@@ -0,0 +1,3 @@
+-- Revert: 20251020000001_audit_log
+
+DROP TABLE IF EXISTS audit_log;
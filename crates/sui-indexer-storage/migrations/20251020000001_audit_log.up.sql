@@ -0,0 +1,15 @@
+-- Audit log of operator actions
+-- Migration: 20251020000001_audit_log
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Who acted: the OS user of a CLI command or the caller of the admin API
+    actor TEXT NOT NULL,
+    -- Where the action came from: `cli` or `admin_api`
+    source TEXT NOT NULL,
+    action TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_occurred_at ON audit_log (occurred_at DESC);
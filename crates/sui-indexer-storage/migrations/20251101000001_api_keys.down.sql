@@ -0,0 +1,3 @@
+-- Revert: 20251101000001_api_keys
+
+DROP TABLE IF EXISTS api_keys;
@@ -0,0 +1,14 @@
+-- API keys accepted by the control endpoint and the gRPC API
+-- Migration: 20251101000001_api_keys
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    name TEXT PRIMARY KEY,
+    -- Hex-encoded SHA-256 of the key; the key itself is never stored
+    key_hash TEXT NOT NULL UNIQUE,
+    -- `read` or `admin`
+    scope TEXT NOT NULL,
+    -- Requests per second, unlimited if NULL
+    rate_limit DOUBLE PRECISION,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
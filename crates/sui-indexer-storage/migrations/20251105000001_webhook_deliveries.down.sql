@@ -0,0 +1,3 @@
+-- Revert: 20251105000001_webhook_deliveries
+
+DROP TABLE IF EXISTS webhook_deliveries;
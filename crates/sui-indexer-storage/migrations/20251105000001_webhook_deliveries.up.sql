@@ -0,0 +1,24 @@
+-- Webhook requests that needed retries or were given up on
+-- Migration: 20251105000001_webhook_deliveries
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    -- Also sent as the `X-Sui-Indexer-Delivery` header
+    id UUID PRIMARY KEY,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sink TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- `delivered` after retries, or `failed` once all attempts were used
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    events INTEGER NOT NULL,
+    first_checkpoint BIGINT NOT NULL,
+    last_checkpoint BIGINT NOT NULL,
+    -- HTTP status of the last attempt, if the endpoint answered
+    response_status INTEGER,
+    error TEXT,
+    -- Request body of failed deliveries, to redeliver by hand
+    payload JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_recorded_at
+    ON webhook_deliveries (recorded_at DESC);
@@ -0,0 +1,3 @@
+-- Revert: 20251110000001_outbox
+
+DROP TABLE IF EXISTS outbox;
@@ -0,0 +1,17 @@
+-- Sink messages written in the same transaction as the events they carry
+-- Migration: 20251110000001_outbox
+
+CREATE TABLE IF NOT EXISTS outbox (
+    -- Messages of a sink are delivered in id order
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sink TEXT NOT NULL,
+    checkpoint_sequence BIGINT NOT NULL,
+    -- The processed event as JSON
+    payload JSONB NOT NULL,
+    -- Failed delivery attempts so far
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_sink_id ON outbox (sink, id);
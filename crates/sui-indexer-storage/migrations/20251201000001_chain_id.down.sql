@@ -0,0 +1,3 @@
+-- Revert: 20251201000001_chain_id
+
+ALTER TABLE indexer_state DROP COLUMN IF EXISTS chain_id;
@@ -0,0 +1,5 @@
+-- Chain the indexed data comes from
+-- Migration: 20251201000001_chain_id
+
+-- Cursor rows record the chain identifier of the node they were indexed from
+ALTER TABLE indexer_state ADD COLUMN IF NOT EXISTS chain_id TEXT;
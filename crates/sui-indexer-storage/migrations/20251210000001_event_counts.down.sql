@@ -0,0 +1,3 @@
+-- Revert: 20251210000001_event_counts
+
+DROP TABLE IF EXISTS event_counts;
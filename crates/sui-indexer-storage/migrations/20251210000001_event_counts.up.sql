@@ -0,0 +1,19 @@
+-- Append-only event counts per checkpoint, for totals as of any checkpoint
+-- Migration: 20251210000001_event_counts
+
+-- Events stored at each checkpoint, by package and event type. Rows are never
+-- pruned with the events, so totals of pruned history stay available.
+CREATE TABLE IF NOT EXISTS event_counts (
+    checkpoint_sequence BIGINT NOT NULL,
+    package_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    events BIGINT NOT NULL,
+    PRIMARY KEY (checkpoint_sequence, package_id, event_type)
+);
+
+-- Count the events indexed before this migration
+INSERT INTO event_counts (checkpoint_sequence, package_id, event_type, events)
+SELECT checkpoint_sequence, package_id, event_type, COUNT(*)
+FROM processed_events
+GROUP BY checkpoint_sequence, package_id, event_type
+ON CONFLICT DO NOTHING;
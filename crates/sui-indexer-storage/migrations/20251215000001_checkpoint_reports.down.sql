@@ -0,0 +1,3 @@
+-- Revert: 20251215000001_checkpoint_reports
+
+DROP TABLE IF EXISTS checkpoint_reports;
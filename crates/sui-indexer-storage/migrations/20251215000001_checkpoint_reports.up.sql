@@ -0,0 +1,28 @@
+-- Processing report of every committed checkpoint, for audit and performance analysis
+-- Migration: 20251215000001_checkpoint_reports
+
+-- One row per pipeline and checkpoint, replaced when the checkpoint is
+-- processed again. Rows are pruned with the events they describe.
+CREATE TABLE IF NOT EXISTS checkpoint_reports (
+    pipeline TEXT NOT NULL,
+    checkpoint_sequence BIGINT NOT NULL,
+    transactions BIGINT NOT NULL,
+    -- Events in the checkpoint, those matching a filter and those skipped
+    events_seen BIGINT NOT NULL,
+    events_matched BIGINT NOT NULL,
+    events_skipped BIGINT NOT NULL,
+    -- Processed events written to storage
+    events_stored BIGINT NOT NULL,
+    -- Matched events by filter name
+    matched_by_filter JSONB NOT NULL DEFAULT '{}',
+    -- Time spent in each pipeline stage
+    fetch_ms BIGINT NOT NULL,
+    transform_ms BIGINT NOT NULL,
+    store_ms BIGINT NOT NULL,
+    deliver_ms BIGINT NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (pipeline, checkpoint_sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkpoint_reports_processed_at
+    ON checkpoint_reports (processed_at);
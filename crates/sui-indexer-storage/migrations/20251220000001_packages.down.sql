@@ -0,0 +1,3 @@
+-- Revert: 20251220000001_packages
+
+DROP TABLE IF EXISTS packages;
@@ -0,0 +1,18 @@
+-- Published and upgraded Move packages with their lineage
+-- Migration: 20251220000001_packages
+
+-- One row per package version. `original_id` is the ID of the first
+-- version, shared by every upgrade of the package.
+CREATE TABLE IF NOT EXISTS packages (
+    package_id TEXT PRIMARY KEY,
+    original_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    modules TEXT[] NOT NULL DEFAULT '{}',
+    tx_digest TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    checkpoint_sequence BIGINT NOT NULL,
+    published_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_packages_original_id
+    ON packages (original_id, version);
@@ -0,0 +1,3 @@
+-- Revert: 20251222000001_move_calls
+
+DROP TABLE IF EXISTS move_calls;
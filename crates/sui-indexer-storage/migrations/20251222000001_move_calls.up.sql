@@ -0,0 +1,25 @@
+-- Move calls made by indexed transactions
+-- Migration: 20251222000001_move_calls
+
+-- One row per Move call command of a programmable transaction, so calls
+-- are indexed whether or not they emit events.
+CREATE TABLE IF NOT EXISTS move_calls (
+    id UUID NOT NULL,
+    checkpoint_sequence BIGINT NOT NULL,
+    transaction_digest TEXT NOT NULL,
+    command_index INTEGER NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    package_id TEXT NOT NULL,
+    module_name TEXT NOT NULL,
+    function_name TEXT NOT NULL,
+    type_arguments TEXT[] NOT NULL DEFAULT '{}',
+    sender TEXT NOT NULL,
+    PRIMARY KEY (transaction_digest, command_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_move_calls_target
+    ON move_calls (package_id, module_name, function_name);
+CREATE INDEX IF NOT EXISTS idx_move_calls_order
+    ON move_calls (checkpoint_sequence DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_move_calls_timestamp
+    ON move_calls (timestamp);
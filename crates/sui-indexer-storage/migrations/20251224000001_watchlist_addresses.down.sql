@@ -0,0 +1,3 @@
+-- Revert: 20251224000001_watchlist_addresses
+
+DROP TABLE IF EXISTS watchlist_addresses;
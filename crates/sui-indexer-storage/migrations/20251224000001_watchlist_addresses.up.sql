@@ -0,0 +1,11 @@
+-- Addresses of watchlists managed at runtime
+-- Migration: 20251224000001_watchlist_addresses
+
+-- Watchlists with `database = true` watch the addresses stored under their
+-- name here, in addition to the ones in the configuration.
+CREATE TABLE IF NOT EXISTS watchlist_addresses (
+    watchlist TEXT NOT NULL,
+    address TEXT NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (watchlist, address)
+);
@@ -0,0 +1,3 @@
+-- Revert: 20251226000001_object_versions
+
+DROP TABLE IF EXISTS object_versions;
@@ -0,0 +1,24 @@
+-- Version history of tracked objects
+-- Migration: 20251226000001_object_versions
+
+-- One row per version of an object listed in `objects.ids`, with its
+-- contents, so the state of e.g. a pool can be read at any point in time.
+CREATE TABLE IF NOT EXISTS object_versions (
+    id UUID NOT NULL,
+    object_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    checkpoint_sequence BIGINT NOT NULL,
+    transaction_digest TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    change_kind TEXT NOT NULL,
+    digest TEXT,
+    object_type TEXT,
+    owner TEXT,
+    contents JSONB,
+    PRIMARY KEY (object_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_object_versions_order
+    ON object_versions (checkpoint_sequence DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_object_versions_timestamp
+    ON object_versions (timestamp);
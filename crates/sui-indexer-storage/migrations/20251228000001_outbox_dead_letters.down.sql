@@ -0,0 +1,6 @@
+-- Revert: 20251228000001_outbox_dead_letters
+
+DROP INDEX IF EXISTS idx_outbox_sink_pending;
+CREATE INDEX IF NOT EXISTS idx_outbox_sink_id ON outbox (sink, id);
+
+ALTER TABLE outbox DROP COLUMN IF EXISTS dead_at;
@@ -0,0 +1,8 @@
+-- Outbox messages given up on after repeated delivery failures
+-- Migration: 20251228000001_outbox_dead_letters
+
+-- Set once a message failed `outbox.max_attempts` deliveries; dead letters are kept but no longer sent
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS dead_at TIMESTAMPTZ;
+
+DROP INDEX IF EXISTS idx_outbox_sink_id;
+CREATE INDEX IF NOT EXISTS idx_outbox_sink_pending ON outbox (sink, id) WHERE dead_at IS NULL;
@@ -0,0 +1,4 @@
+-- Revert: 20251230000001_derived_ids
+
+-- Rewritten ids stay derived; only the list of pending rewrites is dropped
+DROP TABLE IF EXISTS id_rewrites;
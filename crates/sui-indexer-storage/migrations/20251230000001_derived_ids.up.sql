@@ -0,0 +1,14 @@
+-- Ids derived from the stored data
+-- Migration: 20251230000001_derived_ids
+
+-- Events, transactions, Move calls and object versions get ids derived from
+-- what they hold, so storing one again yields the same id. Rows stored by
+-- earlier releases carry random ids; the indexer rewrites the tables listed
+-- here after migrating and removes each one once its rows are done.
+CREATE TABLE IF NOT EXISTS id_rewrites (
+    table_name TEXT PRIMARY KEY
+);
+
+INSERT INTO id_rewrites (table_name)
+VALUES ('processed_events'), ('processed_transactions'), ('move_calls'), ('object_versions')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,40 @@
+-- Revert: 20260105000001_schema_comments
+
+COMMENT ON TABLE checkpoint_progress IS NULL;
+COMMENT ON TABLE transactions IS NULL;
+COMMENT ON TABLE events IS NULL;
+COMMENT ON TABLE indexer_state IS NULL;
+COMMENT ON TABLE processed_events IS NULL;
+COMMENT ON TABLE processed_transactions IS NULL;
+COMMENT ON TABLE audit_log IS NULL;
+COMMENT ON TABLE api_keys IS NULL;
+COMMENT ON TABLE webhook_deliveries IS NULL;
+COMMENT ON TABLE outbox IS NULL;
+COMMENT ON TABLE event_counts IS NULL;
+COMMENT ON TABLE checkpoint_reports IS NULL;
+COMMENT ON TABLE packages IS NULL;
+COMMENT ON TABLE move_calls IS NULL;
+COMMENT ON TABLE watchlist_addresses IS NULL;
+COMMENT ON TABLE object_versions IS NULL;
+COMMENT ON TABLE id_rewrites IS NULL;
+COMMENT ON COLUMN processed_events.event_data IS NULL;
+COMMENT ON COLUMN processed_transactions.effects IS NULL;
+COMMENT ON COLUMN indexer_state.pipeline IS NULL;
+COMMENT ON COLUMN indexer_state.chain_id IS NULL;
+COMMENT ON COLUMN audit_log.actor IS NULL;
+COMMENT ON COLUMN audit_log.source IS NULL;
+COMMENT ON COLUMN api_keys.key_hash IS NULL;
+COMMENT ON COLUMN api_keys.scope IS NULL;
+COMMENT ON COLUMN api_keys.rate_limit IS NULL;
+COMMENT ON COLUMN webhook_deliveries.id IS NULL;
+COMMENT ON COLUMN webhook_deliveries.status IS NULL;
+COMMENT ON COLUMN webhook_deliveries.response_status IS NULL;
+COMMENT ON COLUMN webhook_deliveries.payload IS NULL;
+COMMENT ON COLUMN outbox.id IS NULL;
+COMMENT ON COLUMN outbox.payload IS NULL;
+COMMENT ON COLUMN outbox.attempts IS NULL;
+COMMENT ON COLUMN outbox.dead_at IS NULL;
+COMMENT ON COLUMN checkpoint_reports.events_seen IS NULL;
+COMMENT ON COLUMN checkpoint_reports.events_stored IS NULL;
+COMMENT ON COLUMN checkpoint_reports.matched_by_filter IS NULL;
+COMMENT ON COLUMN checkpoint_reports.fetch_ms IS NULL;
@@ -0,0 +1,43 @@
+-- Descriptions of tables and columns
+-- Migration: 20260105000001_schema_comments
+
+-- Stored in the catalog, where `schema dump` and `\d+` read them
+COMMENT ON TABLE checkpoint_progress IS 'Checkpoint progress tracking';
+COMMENT ON TABLE transactions IS 'Transactions table';
+COMMENT ON TABLE events IS 'Events table';
+COMMENT ON TABLE indexer_state IS 'Indexer state table for checkpoint tracking';
+COMMENT ON TABLE processed_events IS 'Processed events table';
+COMMENT ON TABLE processed_transactions IS 'Processed transactions table';
+COMMENT ON TABLE audit_log IS 'Audit log of operator actions';
+COMMENT ON TABLE api_keys IS 'API keys accepted by the control endpoint and the gRPC API';
+COMMENT ON TABLE webhook_deliveries IS 'Webhook requests that needed retries or were given up on';
+COMMENT ON TABLE outbox IS 'Sink messages written in the same transaction as the events they carry';
+COMMENT ON TABLE event_counts IS 'Events stored at each checkpoint, by package and event type. Rows are never pruned with the events, so totals of pruned history stay available.';
+COMMENT ON TABLE checkpoint_reports IS 'One row per pipeline and checkpoint, replaced when the checkpoint is processed again. Rows are pruned with the events they describe.';
+COMMENT ON TABLE packages IS 'One row per package version. `original_id` is the ID of the first version, shared by every upgrade of the package.';
+COMMENT ON TABLE move_calls IS 'One row per Move call command of a programmable transaction, so calls are indexed whether or not they emit events.';
+COMMENT ON TABLE watchlist_addresses IS 'Watchlists with `database = true` watch the addresses stored under their name here, in addition to the ones in the configuration.';
+COMMENT ON TABLE object_versions IS 'One row per version of an object listed in `objects.ids`, with its contents, so the state of e.g. a pool can be read at any point in time.';
+COMMENT ON TABLE id_rewrites IS 'Tables whose rows may still carry random ids from earlier releases; the indexer derives their ids after migrating.';
+
+COMMENT ON COLUMN processed_events.event_data IS 'Raw event JSON, skipped when storage.store_raw_event is disabled';
+COMMENT ON COLUMN processed_transactions.effects IS 'Skipped when storage.store_transactions_effects is disabled';
+COMMENT ON COLUMN indexer_state.pipeline IS 'Every cursor update appends a row; the newest row per pipeline is its cursor';
+COMMENT ON COLUMN indexer_state.chain_id IS 'Chain identifier of the node the cursor was indexed from';
+COMMENT ON COLUMN audit_log.actor IS 'Who acted: the OS user of a CLI command or the caller of the admin API';
+COMMENT ON COLUMN audit_log.source IS 'Where the action came from: `cli` or `admin_api`';
+COMMENT ON COLUMN api_keys.key_hash IS 'Hex-encoded SHA-256 of the key; the key itself is never stored';
+COMMENT ON COLUMN api_keys.scope IS '`read` or `admin`';
+COMMENT ON COLUMN api_keys.rate_limit IS 'Requests per second, unlimited if NULL';
+COMMENT ON COLUMN webhook_deliveries.id IS 'Also sent as the `X-Sui-Indexer-Delivery` header';
+COMMENT ON COLUMN webhook_deliveries.status IS '`delivered` after retries, or `failed` once all attempts were used';
+COMMENT ON COLUMN webhook_deliveries.response_status IS 'HTTP status of the last attempt, if the endpoint answered';
+COMMENT ON COLUMN webhook_deliveries.payload IS 'Request body of failed deliveries, to redeliver by hand';
+COMMENT ON COLUMN outbox.id IS 'Messages of a sink are delivered in id order';
+COMMENT ON COLUMN outbox.payload IS 'The processed event as JSON';
+COMMENT ON COLUMN outbox.attempts IS 'Failed delivery attempts so far';
+COMMENT ON COLUMN outbox.dead_at IS 'Set once a message failed `outbox.max_attempts` deliveries; dead letters are kept but no longer sent';
+COMMENT ON COLUMN checkpoint_reports.events_seen IS 'Events in the checkpoint, those matching a filter and those skipped';
+COMMENT ON COLUMN checkpoint_reports.events_stored IS 'Processed events written to storage';
+COMMENT ON COLUMN checkpoint_reports.matched_by_filter IS 'Matched events by filter name';
+COMMENT ON COLUMN checkpoint_reports.fetch_ms IS 'Time spent in each pipeline stage';
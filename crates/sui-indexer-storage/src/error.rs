@@ -0,0 +1,151 @@
+use eyre::Report;
+
+/// Failure of a storage backend, telling failures worth retrying from the rest
+///
+/// Backends raise the variants that carry details themselves; database
+/// errors are classified from the [`sqlx::Error`] they carry when read back
+/// with [`StorageError::find`], so they keep their own message and context.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StorageError {
+    /// The database could not be reached or had no connection to spare
+    #[error("The database is unavailable")]
+    Unavailable,
+    /// The transaction conflicted with a concurrent one and was rolled back
+    #[error("The transaction conflicted with a concurrent one")]
+    Conflict,
+    /// The statement was cancelled, e.g. for running past `database.statement_timeout`
+    #[error("The statement timed out or was cancelled")]
+    Timeout,
+    /// The database refused the statement, e.g. for a constraint or a schema mismatch
+    #[error("The database rejected the statement")]
+    Rejected,
+    /// A replay output table name that cannot be used
+    #[error("Invalid table name `{0}`: use lowercase letters, digits and underscores")]
+    InvalidTable(String),
+    /// A `database.schema` that cannot be used
+    #[error("Invalid schema name `{0}`: use lowercase letters, digits and underscores")]
+    InvalidSchema(String),
+    /// An output table that is not a dedicated replay or benchmark table
+    #[error(
+        "Refusing to write into `{0}`: output tables must start with `replay_` or `benchmark_` and may not be managed by the indexer"
+    )]
+    LiveTable(String),
+    /// The schema is behind and may not be migrated automatically
+    #[error(
+        "Database has {0} pending migration(s) and database.auto_migrate is off; run `sui-indexer migrate up` first"
+    )]
+    PendingMigrations(usize),
+    /// An event to rebuild was stored without its raw JSON
+    #[error("Event {0} was stored without raw event data (store_raw_event = false)")]
+    MissingRawEvent(uuid::Uuid),
+    /// An applied migration whose SQL differs from the one in this release
+    #[error("Migration {0} was changed after it was applied")]
+    ChangedMigration(i64),
+    /// A migration that failed part way and left the schema inconsistent
+    #[error("Migration {0} failed part way; fix the schema by hand before migrating")]
+    DirtyMigration(i64),
+    /// A database without an applied migration where one is needed
+    #[error("The database has no applied migrations")]
+    NoMigrations,
+    /// A snapshot that cannot be restored into the database
+    #[error("Cannot restore the snapshot: {0}")]
+    InvalidSnapshot(String),
+    /// A stored value that does not parse back
+    #[error("Stored {column} is invalid: {reason}")]
+    InvalidRow {
+        column: &'static str,
+        reason: String,
+    },
+    /// A page token not made by [`PageCursor::encode`](crate::PageCursor::encode)
+    #[error("Invalid page cursor `{0}`")]
+    InvalidCursor(String),
+    /// An API key name that is already taken
+    #[error("Failed to store API key {0}: a key with that name exists")]
+    DuplicateApiKey(String),
+}
+
+impl StorageError {
+    /// The storage error behind `err`: one raised by a backend, or the class of its database error
+    pub fn find(err: &Report) -> Option<Self> {
+        if let Some(typed) = err.downcast_ref::<Self>() {
+            return Some(typed.clone());
+        }
+        err.downcast_ref::<sqlx::Error>().map(Self::from_sqlx)
+    }
+
+    /// Classify a database error
+    pub fn from_sqlx(err: &sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::WorkerCrashed => Self::Unavailable,
+            sqlx::Error::Database(db) => Self::from_code(db.code().as_deref()),
+            _ => Self::Rejected,
+        }
+    }
+
+    /// Classify a database error by its SQLSTATE code
+    ///
+    /// Only errors in the statement or its data are final; every other
+    /// class, such as a lost connection or a replica that became read-only
+    /// on failover, can clear up by itself.
+    pub fn from_code(code: Option<&str>) -> Self {
+        match code {
+            // serialization_failure, deadlock_detected and lock_not_available
+            Some("40001" | "40P01" | "55P03") => Self::Conflict,
+            // query_canceled, raised by statement_timeout
+            Some("57014") => Self::Timeout,
+            // Data exceptions, integrity constraint violations, syntax errors and access rule violations
+            Some(code)
+                if code.starts_with("22") || code.starts_with("23") || code.starts_with("42") =>
+            {
+                Self::Rejected
+            }
+            _ => Self::Unavailable,
+        }
+    }
+
+    /// Whether the same write can succeed when tried again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Unavailable | Self::Conflict | Self::Timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::WrapErr;
+
+    use super::*;
+
+    #[test]
+    fn test_database_errors_are_classified() {
+        let err = Err::<(), _>(sqlx::Error::PoolTimedOut)
+            .wrap_err("Failed to store events")
+            .unwrap_err();
+        assert_eq!(StorageError::find(&err), Some(StorageError::Unavailable));
+        assert!(StorageError::Unavailable.is_retryable());
+
+        let err = Report::from(sqlx::Error::RowNotFound);
+        assert_eq!(StorageError::find(&err), Some(StorageError::Rejected));
+        assert!(!StorageError::Rejected.is_retryable());
+
+        for (code, class) in [
+            ("57014", StorageError::Timeout),
+            ("55P03", StorageError::Conflict),
+            ("25006", StorageError::Unavailable),
+            ("08006", StorageError::Unavailable),
+            ("23505", StorageError::Rejected),
+            ("22P02", StorageError::Rejected),
+            ("42P01", StorageError::Rejected),
+        ] {
+            assert_eq!(StorageError::from_code(Some(code)), class, "{code}");
+        }
+        assert!(StorageError::Timeout.is_retryable());
+
+        let err = Report::from(StorageError::InvalidTable("Events".to_string()));
+        assert!(!StorageError::find(&err).unwrap().is_retryable());
+        assert_eq!(StorageError::find(&eyre::eyre!("plain")), None);
+    }
+}
@@ -0,0 +1,347 @@
+/// File writers for exporting stored events and transactions
+use std::{fmt, io::Write, str::FromStr, sync::Arc};
+
+use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::Serialize;
+
+use crate::{EventRecord, TransactionRecord};
+
+/// Rows buffered per Parquet row group
+const PARQUET_BATCH_ROWS: usize = 8192;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "parquet" => Ok(Self::Parquet),
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            _ => Err(format!(
+                "unknown export format `{s}`, expected parquet, csv or ndjson"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// Type of an exported column
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnType {
+    Int64,
+    Timestamp,
+    Text,
+}
+
+/// Value of an exported column
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Int64(i64),
+    Timestamp(DateTime<Utc>),
+    Text(String),
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int64(value) => write!(f, "{value}"),
+            Self::Timestamp(value) => f.write_str(&value.to_rfc3339()),
+            Self::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+/// A stored row that can be written by an [`ExportWriter`]
+pub trait ExportRecord: Serialize {
+    /// Column names and types, in output order
+    const COLUMNS: &'static [(&'static str, ColumnType)];
+
+    /// Values of this row, matching [`Self::COLUMNS`]
+    fn cells(&self) -> Vec<Cell>;
+}
+
+impl ExportRecord for EventRecord {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("id", ColumnType::Text),
+        ("checkpoint_sequence", ColumnType::Int64),
+        ("transaction_digest", ColumnType::Text),
+        ("timestamp", ColumnType::Timestamp),
+        ("package_id", ColumnType::Text),
+        ("module_name", ColumnType::Text),
+        ("event_type", ColumnType::Text),
+        ("sender", ColumnType::Text),
+        ("fields", ColumnType::Text),
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.id.to_string()),
+            Cell::Int64(self.checkpoint_sequence),
+            Cell::Text(self.transaction_digest.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Text(self.package_id.clone()),
+            Cell::Text(self.module_name.clone()),
+            Cell::Text(self.event_type.clone()),
+            Cell::Text(self.sender.clone()),
+            Cell::Text(self.fields.to_string()),
+        ]
+    }
+}
+
+impl ExportRecord for TransactionRecord {
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("id", ColumnType::Text),
+        ("checkpoint_sequence", ColumnType::Int64),
+        ("digest", ColumnType::Text),
+        ("timestamp", ColumnType::Timestamp),
+        ("sender", ColumnType::Text),
+        ("gas_used", ColumnType::Int64),
+        ("status", ColumnType::Text),
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.id.to_string()),
+            Cell::Int64(self.checkpoint_sequence),
+            Cell::Text(self.digest.clone()),
+            Cell::Timestamp(self.timestamp),
+            Cell::Text(self.sender.clone()),
+            Cell::Int64(self.gas_used),
+            Cell::Text(self.status.clone()),
+        ]
+    }
+}
+
+/// Writes exported rows to a file in one of the [`ExportFormat`]s
+///
+/// Rows are written as they arrive; Parquet output buffers at most one row
+/// group in memory.
+pub struct ExportWriter<W: Write + Send, R: ExportRecord> {
+    output: Output<W>,
+    buffer: Vec<R>,
+    rows: u64,
+}
+
+enum Output<W: Write + Send> {
+    Parquet(Box<ArrowWriter<W>>),
+    Csv(W),
+    Ndjson(W),
+}
+
+impl<W: Write + Send, R: ExportRecord> ExportWriter<W, R> {
+    /// Start an export to `writer`, writing the header if the format has one
+    pub fn new(mut writer: W, format: ExportFormat) -> Result<Self> {
+        let output = match format {
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Output::Parquet(Box::new(ArrowWriter::try_new(
+                    writer,
+                    schema::<R>(),
+                    Some(properties),
+                )?))
+            }
+            ExportFormat::Csv => {
+                let header: Vec<&str> = R::COLUMNS.iter().map(|(name, _)| *name).collect();
+                writeln!(writer, "{}", header.join(","))?;
+                Output::Csv(writer)
+            }
+            ExportFormat::Ndjson => Output::Ndjson(writer),
+        };
+
+        Ok(Self {
+            output,
+            buffer: Vec::new(),
+            rows: 0,
+        })
+    }
+
+    /// Append one row
+    pub fn write(&mut self, record: R) -> Result<()> {
+        self.rows += 1;
+        match &mut self.output {
+            Output::Parquet(_) => {
+                self.buffer.push(record);
+                if self.buffer.len() >= PARQUET_BATCH_ROWS {
+                    self.flush_batch()?;
+                }
+            }
+            Output::Csv(writer) => {
+                let row: Vec<String> = record
+                    .cells()
+                    .iter()
+                    .map(|cell| csv_field(&cell.to_string()))
+                    .collect();
+                writeln!(writer, "{}", row.join(","))?;
+            }
+            Output::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, &record)?;
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of rows written so far
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Write any buffered rows and the file footer, returning the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.flush_batch()?;
+        match self.output {
+            Output::Parquet(writer) => Ok(writer.into_inner()?),
+            Output::Csv(mut writer) | Output::Ndjson(mut writer) => {
+                writer.flush()?;
+                Ok(writer)
+            }
+        }
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        let Output::Parquet(writer) = &mut self.output else {
+            return Ok(());
+        };
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        writer.write(&record_batch(&self.buffer)?)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+/// Arrow schema of exported rows of type `R`
+pub fn schema<R: ExportRecord>() -> SchemaRef {
+    let fields: Vec<Field> = R::COLUMNS
+        .iter()
+        .map(|(name, column)| {
+            let data_type = match column {
+                ColumnType::Int64 => DataType::Int64,
+                ColumnType::Timestamp => {
+                    DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()))
+                }
+                ColumnType::Text => DataType::Utf8,
+            };
+            Field::new(*name, data_type, false)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// Arrow record batch holding `records`
+pub fn record_batch<R: ExportRecord>(records: &[R]) -> Result<RecordBatch> {
+    let rows: Vec<Vec<Cell>> = records.iter().map(ExportRecord::cells).collect();
+
+    let columns: Vec<ArrayRef> = R::COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(index, (_, column))| -> ArrayRef {
+            let cells = rows.iter().map(|row| &row[index]);
+            match column {
+                ColumnType::Int64 => {
+                    Arc::new(Int64Array::from_iter_values(cells.map(|cell| match cell {
+                        Cell::Int64(value) => *value,
+                        _ => unreachable!("column type mismatch"),
+                    })))
+                }
+                ColumnType::Timestamp => Arc::new(
+                    TimestampMillisecondArray::from_iter_values(cells.map(|cell| match cell {
+                        Cell::Timestamp(value) => value.timestamp_millis(),
+                        _ => unreachable!("column type mismatch"),
+                    }))
+                    .with_timezone("UTC"),
+                ),
+                ColumnType::Text => Arc::new(StringArray::from_iter_values(
+                    cells.map(|cell| cell.to_string()),
+                )),
+            }
+        })
+        .collect();
+
+    Ok(RecordBatch::try_new(schema::<R>(), columns)?)
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(checkpoint: i64) -> TransactionRecord {
+        TransactionRecord {
+            id: uuid::Uuid::new_v4(),
+            checkpoint_sequence: checkpoint,
+            digest: "digest".to_string(),
+            timestamp: Utc::now(),
+            sender: "0x1".to_string(),
+            gas_used: 1000,
+            status: "failure: \"abort\", code 1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_export_formats() -> Result<()> {
+        let mut csv = ExportWriter::new(Vec::new(), ExportFormat::Csv)?;
+        csv.write(transaction(7))?;
+        let csv = String::from_utf8(csv.finish()?)?;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id,checkpoint_sequence,digest,timestamp,sender,gas_used,status"
+        );
+        assert!(lines[1].ends_with(",0x1,1000,\"failure: \"\"abort\"\", code 1\""));
+
+        let mut ndjson = ExportWriter::new(Vec::new(), ExportFormat::Ndjson)?;
+        ndjson.write(transaction(7))?;
+        ndjson.write(transaction(8))?;
+        let ndjson = String::from_utf8(ndjson.finish()?)?;
+        assert_eq!(ndjson.lines().count(), 2);
+
+        let mut parquet = ExportWriter::new(Vec::new(), ExportFormat::Parquet)?;
+        for checkpoint in 0..3 {
+            parquet.write(transaction(checkpoint))?;
+        }
+        assert_eq!(parquet.rows(), 3);
+        let file = parquet.finish()?;
+        assert!(file.starts_with(b"PAR1") && file.ends_with(b"PAR1"));
+        Ok(())
+    }
+}
@@ -0,0 +1,380 @@
+/// Derived ids for rows stored with random ones by earlier releases
+use std::{collections::HashSet, str::FromStr};
+
+use eyre::Result;
+use sqlx::{Connection, PgConnection, Row};
+use sui_indexer_events::{move_call_id, object_version_id, ProcessedEvent, ProcessedTransaction};
+use sui_types::{
+    base_types::{ObjectID, TransactionDigest},
+    event::EventID,
+};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::postgres::typed_tables;
+
+/// Rows read and rewritten per statement
+const BATCH_SIZE: i64 = 1000;
+
+/// Derive the ids of the rows of every table listed in `id_rewrites`
+///
+/// Events, transactions, Move calls and object versions get ids derived from
+/// what they hold; the migration introducing them lists the tables that may
+/// still hold random ones. Tables are rewritten a batch at a time and dropped
+/// from the list once done, so an interrupted rewrite carries on with the next
+/// run.
+pub async fn rewrite_ids(conn: &mut PgConnection) -> Result<()> {
+    let listed: bool = sqlx::query_scalar("SELECT to_regclass('id_rewrites') IS NOT NULL")
+        .fetch_one(&mut *conn)
+        .await?;
+    if !listed {
+        return Ok(());
+    }
+
+    let tables: Vec<String> = sqlx::query_scalar("SELECT table_name FROM id_rewrites")
+        .fetch_all(&mut *conn)
+        .await?;
+    for table in tables {
+        let rewritten = match table.as_str() {
+            "processed_events" => rewrite_events(conn).await?,
+            "processed_transactions" => rewrite_transactions(conn).await?,
+            "move_calls" => rewrite_move_calls(conn).await?,
+            "object_versions" => rewrite_object_versions(conn).await?,
+            _ => {
+                warn!(table, "Skipping id rewrite of an unknown table");
+                0
+            }
+        };
+        sqlx::query("DELETE FROM id_rewrites WHERE table_name = $1")
+            .bind(&table)
+            .execute(&mut *conn)
+            .await?;
+        if rewritten > 0 {
+            info!(table, rewritten, "Derived the ids of stored rows");
+        }
+    }
+    Ok(())
+}
+
+/// Derive event ids from the transaction digest and event sequence number
+///
+/// An event stored more than once under random ids is kept once, and the
+/// rows decoded from it into typed tables follow its id.
+async fn rewrite_events(conn: &mut PgConnection) -> Result<u64> {
+    let typed = typed_tables(&mut *conn).await?;
+    let (mut after, mut rewritten, mut underived) = (Uuid::nil(), 0, 0);
+    loop {
+        // Rewritten ids may sort after the cursor; they are derived when read again
+        let rows = sqlx::query(
+            "SELECT id, transaction_digest,
+                    COALESCE(event_data->'id'->>'eventSeq', fields->>'event_sequence') AS event_seq
+             FROM processed_events WHERE id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(after)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *conn)
+        .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = last.get("id");
+
+        let mut ids = Vec::new();
+        for row in &rows {
+            let id: Uuid = row.get("id");
+            let event_seq = row
+                .get::<Option<String>, _>("event_seq")
+                .and_then(|seq| seq.parse().ok());
+            let derived = TransactionDigest::from_str(row.get("transaction_digest"))
+                .ok()
+                .zip(event_seq)
+                .map(|(tx_digest, event_seq)| {
+                    ProcessedEvent::id_for(&EventID {
+                        tx_digest,
+                        event_seq,
+                    })
+                });
+            match derived {
+                Some(derived) if derived != id => ids.push((id, derived)),
+                Some(_) => {}
+                None => underived += 1,
+            }
+        }
+        rewritten += replace_event_ids(conn, &typed, ids).await?;
+    }
+    if underived > 0 {
+        warn!(
+            events = underived,
+            "Kept the random ids of events stored without their sequence number"
+        );
+    }
+    Ok(rewritten)
+}
+
+/// Give events their derived ids, dropping those whose derived id is taken
+async fn replace_event_ids(
+    conn: &mut PgConnection,
+    typed: &[String],
+    ids: Vec<(Uuid, Uuid)>,
+) -> Result<u64> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let mut tx = conn.begin().await?;
+    let derived: Vec<Uuid> = ids.iter().map(|(_, derived)| *derived).collect();
+    let mut taken: HashSet<Uuid> =
+        sqlx::query_scalar("SELECT id FROM processed_events WHERE id = ANY($1)")
+            .bind(&derived)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+    let (mut old, mut new, mut duplicates) = (Vec::new(), Vec::new(), Vec::new());
+    for (id, derived) in ids {
+        if taken.insert(derived) {
+            old.push(id);
+            new.push(derived);
+        } else {
+            duplicates.push(id);
+        }
+    }
+
+    for table in std::iter::once("processed_events").chain(typed.iter().map(String::as_str)) {
+        sqlx::query(&format!("DELETE FROM {table} WHERE id = ANY($1)"))
+            .bind(&duplicates)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "UPDATE {table} AS t SET id = m.new
+             FROM UNNEST($1::UUID[], $2::UUID[]) AS m(old, new)
+             WHERE t.id = m.old"
+        ))
+        .bind(&old)
+        .bind(&new)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(old.len() as u64)
+}
+
+/// Derive transaction ids from their digest
+async fn rewrite_transactions(conn: &mut PgConnection) -> Result<u64> {
+    let (mut after, mut rewritten) = (String::new(), 0);
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, digest FROM processed_transactions
+             WHERE digest > $1 ORDER BY digest LIMIT $2",
+        )
+        .bind(&after)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *conn)
+        .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = last.get("digest");
+
+        let (mut digests, mut ids) = (Vec::new(), Vec::new());
+        for row in &rows {
+            let digest: String = row.get("digest");
+            let Ok(parsed) = TransactionDigest::from_str(&digest) else {
+                continue;
+            };
+            let derived = ProcessedTransaction::id_for(&parsed);
+            if derived != row.get::<Uuid, _>("id") {
+                digests.push(digest);
+                ids.push(derived);
+            }
+        }
+        rewritten += sqlx::query(
+            "UPDATE processed_transactions AS t SET id = m.id
+             FROM UNNEST($1::TEXT[], $2::UUID[]) AS m(digest, id)
+             WHERE t.digest = m.digest",
+        )
+        .bind(&digests)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    Ok(rewritten)
+}
+
+/// Derive Move call ids from their transaction digest and command index
+async fn rewrite_move_calls(conn: &mut PgConnection) -> Result<u64> {
+    let (mut after, mut rewritten) = ((String::new(), -1i32), 0);
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, transaction_digest, command_index FROM move_calls
+             WHERE (transaction_digest, command_index) > ($1, $2)
+             ORDER BY transaction_digest, command_index LIMIT $3",
+        )
+        .bind(&after.0)
+        .bind(after.1)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *conn)
+        .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = (last.get("transaction_digest"), last.get("command_index"));
+
+        let (mut digests, mut indexes, mut ids) = (Vec::new(), Vec::new(), Vec::new());
+        for row in &rows {
+            let digest: String = row.get("transaction_digest");
+            let command_index: i32 = row.get("command_index");
+            let Ok(parsed) = TransactionDigest::from_str(&digest) else {
+                continue;
+            };
+            let derived = move_call_id(&parsed, command_index as u32);
+            if derived != row.get::<Uuid, _>("id") {
+                digests.push(digest);
+                indexes.push(command_index);
+                ids.push(derived);
+            }
+        }
+        rewritten += sqlx::query(
+            "UPDATE move_calls AS t SET id = m.id
+             FROM UNNEST($1::TEXT[], $2::INTEGER[], $3::UUID[]) AS m(digest, command_index, id)
+             WHERE t.transaction_digest = m.digest AND t.command_index = m.command_index",
+        )
+        .bind(&digests)
+        .bind(&indexes)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    Ok(rewritten)
+}
+
+/// Derive object version ids from the object ID and version
+async fn rewrite_object_versions(conn: &mut PgConnection) -> Result<u64> {
+    let (mut after, mut rewritten) = ((String::new(), -1i64), 0);
+    loop {
+        let rows = sqlx::query(
+            "SELECT id, object_id, version FROM object_versions
+             WHERE (object_id, version) > ($1, $2)
+             ORDER BY object_id, version LIMIT $3",
+        )
+        .bind(&after.0)
+        .bind(after.1)
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *conn)
+        .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after = (last.get("object_id"), last.get("version"));
+
+        let (mut objects, mut versions, mut ids) = (Vec::new(), Vec::new(), Vec::new());
+        for row in &rows {
+            let object: String = row.get("object_id");
+            let version: i64 = row.get("version");
+            let Ok(parsed) = ObjectID::from_str(&object) else {
+                continue;
+            };
+            let derived = object_version_id(&parsed, version as u64);
+            if derived != row.get::<Uuid, _>("id") {
+                objects.push(object);
+                versions.push(version);
+                ids.push(derived);
+            }
+        }
+        rewritten += sqlx::query(
+            "UPDATE object_versions AS t SET id = m.id
+             FROM UNNEST($1::TEXT[], $2::BIGINT[], $3::UUID[]) AS m(object_id, version, id)
+             WHERE t.object_id = m.object_id AND t.version = m.version",
+        )
+        .bind(&objects)
+        .bind(&versions)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    }
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_indexer_config::StorageConfig;
+
+    use super::*;
+    use crate::{
+        migrations,
+        test_db::{database_url, scratch_pool},
+    };
+
+    /// Base58 of the all-zero digest
+    const DIGEST: &str = "11111111111111111111111111111111";
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_random_ids_are_rewritten() -> Result<()> {
+        let url = database_url()?;
+        let pool = scratch_pool(&url, &format!("ids_test_{}", std::process::id())).await?;
+        migrations::run_migrations(&pool, &StorageConfig::default()).await?;
+
+        // The same event stored twice, as a replay did before ids were derived
+        for _ in 0..2 {
+            sqlx::query(
+                "INSERT INTO processed_events (id, transaction_digest, checkpoint_sequence, timestamp,
+                     package_id, module_name, event_type, sender, fields, metadata, processed_at)
+                 VALUES (gen_random_uuid(), $1, 42, NOW(), '0x2', 'pool', '0x2::pool::Swap', '0x1',
+                     '{\"event_sequence\": \"3\"}', '{}', NOW())",
+            )
+            .bind(DIGEST)
+            .execute(&pool)
+            .await?;
+        }
+        for statement in [
+            "INSERT INTO processed_transactions (id, transaction_data, digest, checkpoint_sequence,
+                 timestamp, sender, gas_used, status, effects, metadata, processed_at)
+             VALUES (gen_random_uuid(), '{}', $1, 42, NOW(), '0x1', 0, 'success', '{}', '{}', NOW())",
+            "INSERT INTO move_calls (id, checkpoint_sequence, transaction_digest, command_index,
+                 timestamp, package_id, module_name, function_name, sender)
+             VALUES (gen_random_uuid(), 42, $1, 1, NOW(), '0x2', 'pool', 'swap', '0x1')",
+            "INSERT INTO object_versions (id, object_id, version, checkpoint_sequence,
+                 transaction_digest, timestamp, change_kind)
+             VALUES (gen_random_uuid(), '0x5', 7, 42, $1, NOW(), 'mutated')",
+            "INSERT INTO id_rewrites (table_name)
+             VALUES ('processed_events'), ('processed_transactions'), ('move_calls'), ('object_versions')
+             ON CONFLICT DO NOTHING",
+        ] {
+            sqlx::query(statement).bind(DIGEST).execute(&pool).await?;
+        }
+
+        let mut conn = pool.acquire().await?;
+        rewrite_ids(&mut conn).await?;
+
+        let digest = TransactionDigest::new([0; 32]);
+        let events: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM processed_events")
+            .fetch_all(&pool)
+            .await?;
+        let event_id = ProcessedEvent::id_for(&EventID {
+            tx_digest: digest,
+            event_seq: 3,
+        });
+        assert_eq!(events, [event_id]);
+        let id = |sql| sqlx::query_scalar::<_, Uuid>(sql).fetch_one(&pool);
+        assert_eq!(
+            id("SELECT id FROM processed_transactions").await?,
+            ProcessedTransaction::id_for(&digest)
+        );
+        assert_eq!(
+            id("SELECT id FROM move_calls").await?,
+            move_call_id(&digest, 1)
+        );
+        assert_eq!(
+            id("SELECT id FROM object_versions").await?,
+            object_version_id(&ObjectID::from_str("0x5")?, 7)
+        );
+        let pending: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM id_rewrites")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(pending, 0);
+        Ok(())
+    }
+}
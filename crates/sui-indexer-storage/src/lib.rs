@@ -1,29 +1,79 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
+use chrono::{DateTime, Utc};
 use eyre::Result;
-use sui_indexer_config::DatabaseConfig;
-use sui_indexer_events::{ProcessedEvent, ProcessedTransaction};
+use futures::stream::BoxStream;
+use sui_indexer_config::{ApiScope, DatabaseConfig, StorageConfig};
+use sui_indexer_events::{
+    ProcessedEvent, ProcessedTransaction, TypedEventRegistry, TypedRow, TypedTable,
+};
 
+pub mod error;
+pub mod export;
+pub mod ids;
+pub mod memory;
 pub mod migrations;
 pub mod models;
+pub mod partitions;
 pub mod postgres;
+pub mod query;
+pub mod schema_doc;
+pub mod snapshot;
+#[cfg(test)]
+mod test_db;
 
+pub use error::StorageError;
+pub use export::{ExportFormat, ExportWriter};
+pub use memory::MemoryStorage;
 pub use models::*;
 pub use postgres::PostgresStorage;
+pub use query::{
+    CheckpointTotals, EventQuery, EventRecord, IndexStats, MoveCallQuery, MoveCallRecord,
+    ObjectVersionQuery, ObjectVersionRecord, PageCursor, RawEventRecord, TransactionQuery,
+    TransactionRecord, ValueCount,
+};
+pub use schema_doc::SchemaDoc;
+pub use snapshot::SnapshotManifest;
+
+/// Cursor advanced by the live pipeline
+pub const DEFAULT_PIPELINE: &str = "default";
+
+/// Whether `err` was caused by the database, as opposed to bad input or a bug
+pub fn is_database_error(err: &eyre::Report) -> bool {
+    err.chain()
+        .any(|cause| cause.is::<sqlx::Error>() || cause.is::<sqlx::migrate::MigrateError>())
+}
+
+/// Rows of one typed event table, as decoded by [`TypedEventRegistry::decode`]
+pub type TypedRows<'a> = (&'a TypedTable, Vec<TypedRow>);
 
 /// Storage trait for different backend implementations
 #[async_trait::async_trait]
 pub trait Storage: Send + Sync {
+    /// Backend name as shown in startup reports
+    fn name(&self) -> &'static str;
+
     /// Initialize the storage backend
     async fn initialize(&self) -> Result<()>;
 
     /// Store a single event
     async fn store_event(&self, event: &ProcessedEvent) -> Result<()> {
-        self.store_events(vec![event.clone()]).await
+        self.store_events(std::slice::from_ref(event)).await
     }
 
     /// Store a batch of events
-    async fn store_events(&self, events: Vec<ProcessedEvent>) -> Result<()>;
+    async fn store_events(&self, events: &[ProcessedEvent]) -> Result<()> {
+        self.store_events_with_rows(events, &[]).await
+    }
+
+    /// Store a batch of events and the rows of their typed events in one transaction
+    ///
+    /// Rows already stored are skipped, like events.
+    async fn store_events_with_rows(
+        &self,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<()>;
 
     /// Store a single transaction
     async fn store_transaction(&self, transaction: &ProcessedTransaction) -> Result<()> {
@@ -40,6 +90,59 @@ pub trait Storage: Send + Sync {
         end: u64,
     ) -> Result<Vec<ProcessedEvent>>;
 
+    /// Delete the events and typed rows stored for a checkpoint range, returning the number of events removed
+    async fn delete_events_by_checkpoint_range(&self, start: u64, end: u64) -> Result<u64>;
+
+    /// Delete the events with `ids` and their typed rows and store `events` and `typed` in one transaction, returning the number removed
+    async fn replace_events(
+        &self,
+        ids: &[uuid::Uuid],
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<u64>;
+
+    /// Find stored events matching a query, newest first
+    async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>>;
+
+    /// Find stored transactions matching a query, newest first
+    async fn query_transactions(&self, query: &TransactionQuery) -> Result<Vec<TransactionRecord>>;
+
+    /// Summarize what was indexed since `since`, listing the `top` most frequent values
+    async fn stats_since(&self, since: DateTime<Utc>, top: u32) -> Result<IndexStats>;
+
+    /// Cumulative event counts as of `checkpoint`, listing the `top` most frequent values
+    ///
+    /// Counted from the append-only per-checkpoint totals, which outlive pruned events.
+    async fn totals_at(&self, checkpoint: u64, top: u32) -> Result<CheckpointTotals>;
+
+    /// Stream the stored events of checkpoints `start..=end` in checkpoint order
+    fn export_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<EventRecord>>;
+
+    /// Stream the raw events stored for checkpoints `start..=end` in checkpoint order
+    fn export_raw_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<RawEventRecord>>;
+
+    /// Stream the stored events matching `query` in checkpoint order, ignoring `after` and `limit`
+    fn export_matching_events(&self, query: &EventQuery) -> BoxStream<'_, Result<EventRecord>>;
+
+    /// Stream the stored transactions matching `query` in checkpoint order, ignoring `after` and `limit`
+    fn export_matching_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> BoxStream<'_, Result<TransactionRecord>>;
+
+    /// Create `table` shaped like the events table if needed and clear its checkpoints `start..=end`
+    async fn reset_event_table(&self, table: &str, start: u64, end: u64) -> Result<()>;
+
+    /// Store events into `table` instead of the events table
+    async fn store_events_in(&self, table: &str, events: &[ProcessedEvent]) -> Result<()>;
+
+    /// Create the table of a typed event if needed and add the columns it lacks
+    async fn migrate_typed_table(&self, table: &TypedTable) -> Result<()>;
+
+    /// Stream the stored transactions of checkpoints `start..=end` in checkpoint order
+    fn export_transactions(&self, start: u64, end: u64)
+        -> BoxStream<'_, Result<TransactionRecord>>;
+
     /// Get the latest processed checkpoint
     async fn get_latest_checkpoint(&self) -> Result<Option<u64>>;
 
@@ -51,11 +154,148 @@ pub trait Storage: Send + Sync {
     /// Update checkpoint progress
     async fn update_checkpoint_progress(&self, checkpoint: u64) -> Result<()>;
 
+    /// Store the events, typed rows and outbox messages of a checkpoint and advance the progress in one transaction
+    async fn commit_with_outbox(
+        &self,
+        checkpoint: u64,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+        outbox: Vec<OutboxEntry>,
+    ) -> Result<()>;
+
+    /// Outbox messages of a sink with an ID above `after`, oldest first, leaving out dead letters
+    async fn outbox_pending(&self, sink: &str, after: i64, limit: u32)
+        -> Result<Vec<OutboxRecord>>;
+
+    /// Remove delivered outbox messages
+    async fn ack_outbox(&self, ids: &[i64]) -> Result<()>;
+
+    /// Count a failed delivery attempt of outbox messages
+    ///
+    /// Messages reaching `max_attempts` failed attempts become dead letters;
+    /// returns how many did.
+    async fn fail_outbox(&self, ids: &[i64], error: &str, max_attempts: u32) -> Result<usize>;
+
+    /// Messages waiting in the outbox, per sink
+    async fn outbox_backlog(&self) -> Result<Vec<OutboxBacklog>>;
+
     /// Update the last processed checkpoint (alias for update_checkpoint_progress)
     async fn update_last_processed_checkpoint(&self, checkpoint: u64) -> Result<()> {
         self.update_checkpoint_progress(checkpoint).await
     }
 
+    /// Last processed checkpoint of a named cursor
+    async fn get_cursor(&self, pipeline: &str) -> Result<Option<u64>>;
+
+    /// Move a named cursor to `checkpoint`
+    async fn set_cursor(&self, pipeline: &str, checkpoint: u64) -> Result<()>;
+
+    /// Chain identifier the stored data was indexed from, if recorded
+    async fn get_chain_id(&self) -> Result<Option<String>>;
+
+    /// Record the chain identifier the data is indexed from
+    async fn set_chain_id(&self, chain_id: &str) -> Result<()>;
+
+    /// Current position of every named cursor
+    async fn list_cursors(&self) -> Result<Vec<CursorRecord>>;
+
+    /// Record the processing report of a committed checkpoint, replacing an earlier one
+    async fn record_checkpoint_report(&self, report: &CheckpointReport) -> Result<()>;
+
+    /// Reports of `pipeline` for checkpoints `start..=end`, in checkpoint order
+    async fn checkpoint_reports(
+        &self,
+        pipeline: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<CheckpointReport>>;
+
+    /// Store published package versions, keeping versions already stored
+    async fn store_packages(&self, packages: &[PackageRecord]) -> Result<()>;
+
+    /// Stored package versions, of the lineage of `original_id` if given, by original ID and version
+    async fn packages(&self, original_id: Option<&str>) -> Result<Vec<PackageRecord>>;
+
+    /// Store Move calls, keeping calls already stored
+    async fn store_move_calls(&self, calls: &[MoveCallRecord]) -> Result<()>;
+
+    /// Stored Move calls matching `query`, newest first
+    async fn query_move_calls(&self, query: &MoveCallQuery) -> Result<Vec<MoveCallRecord>>;
+
+    /// Store versions of tracked objects, keeping versions already stored
+    async fn store_object_versions(&self, versions: &[ObjectVersionRecord]) -> Result<()>;
+
+    /// Stored object versions matching `query`, newest first
+    async fn query_object_versions(
+        &self,
+        query: &ObjectVersionQuery,
+    ) -> Result<Vec<ObjectVersionRecord>>;
+
+    /// Add `addresses` to the stored watchlist `watchlist`, returning how many were new
+    async fn add_watchlist_addresses(&self, watchlist: &str, addresses: &[String]) -> Result<u64>;
+
+    /// Remove `addresses` from the stored watchlist `watchlist`, returning how many were stored
+    async fn remove_watchlist_addresses(
+        &self,
+        watchlist: &str,
+        addresses: &[String],
+    ) -> Result<u64>;
+
+    /// Addresses stored under the watchlist `watchlist`, in address order
+    async fn watchlist_addresses(&self, watchlist: &str) -> Result<Vec<String>>;
+
+    /// Append an operator action to the audit log
+    async fn record_audit(&self, entry: &AuditEntry) -> Result<()>;
+
+    /// The `limit` most recent audit log entries, newest first
+    async fn audit_log(&self, limit: u32) -> Result<Vec<AuditRecord>>;
+
+    /// Store an API key by the hash of its value
+    async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scope: ApiScope,
+        rate_limit: Option<f64>,
+    ) -> Result<()>;
+
+    /// Every stored API key, including revoked ones, by name
+    async fn api_keys(&self) -> Result<Vec<ApiKeyRecord>>;
+
+    /// Revoke a stored API key, returning whether an active key was revoked
+    async fn revoke_api_key(&self, name: &str) -> Result<bool>;
+
+    /// Record a webhook delivery that was retried or failed
+    async fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+
+    /// The `limit` most recently recorded webhook deliveries, newest first
+    async fn webhook_deliveries(
+        &self,
+        limit: u32,
+        failed_only: bool,
+    ) -> Result<Vec<WebhookDeliveryRecord>>;
+
+    /// Delete events, their typed rows and transactions older than `cutoff`
+    async fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport>;
+
+    /// Report what `prune_before` would remove without deleting anything
+    async fn prunable_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport>;
+
+    /// Periodic housekeeping such as creating upcoming partitions
+    async fn maintain(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Connections held by the backend, if it pools them
+    fn connection_stats(&self) -> Option<ConnectionStats> {
+        None
+    }
+
+    /// Time taken to check a connection out of the pool, if the backend pools them
+    async fn acquire_wait(&self) -> Result<Option<Duration>> {
+        Ok(None)
+    }
+
     /// Health check for storage backend
     async fn health_check(&self) -> Result<bool>;
 }
@@ -64,30 +304,59 @@ pub trait Storage: Send + Sync {
 #[derive(Clone)]
 pub struct StorageManager {
     backend: Arc<dyn Storage>,
+    typed: TypedEventRegistry,
 }
 
 impl StorageManager {
     /// Create a new storage manager with PostgreSQL backend
-    pub async fn new_postgres(config: DatabaseConfig) -> Result<Self> {
-        let backend = PostgresStorage::new(config).await?;
+    pub async fn new_postgres(config: DatabaseConfig, storage: StorageConfig) -> Result<Self> {
+        let backend = PostgresStorage::new(config, storage).await?;
         Ok(Self {
             backend: Arc::new(backend),
+            typed: TypedEventRegistry::default(),
         })
     }
 
+    /// Create a storage manager over any backend, such as [`MemoryStorage`]
+    pub fn from_backend(backend: impl Storage + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            typed: TypedEventRegistry::default(),
+        }
+    }
+
+    /// Also store the events of `registry`'s typed events in their own tables
+    ///
+    /// The tables are created or given their new columns by [`StorageManager::initialize`].
+    pub fn with_typed_events(mut self, registry: TypedEventRegistry) -> Self {
+        self.typed = registry;
+        self
+    }
+
     /// Initialize the storage backend
     pub async fn initialize(&self) -> Result<()> {
-        self.backend.initialize().await
+        self.backend.initialize().await?;
+        for table in self.typed.tables() {
+            self.backend.migrate_typed_table(table).await?;
+        }
+        Ok(())
     }
 
     /// Store a single event
     pub async fn store_event(&self, event: &ProcessedEvent) -> Result<()> {
-        self.backend.store_event(event).await
+        self.store_events(std::slice::from_ref(event)).await
+    }
+
+    /// Store events, with the rows of the typed events among them
+    pub async fn store_events(&self, events: &[ProcessedEvent]) -> Result<()> {
+        self.backend
+            .store_events_with_rows(events, &self.typed.decode(events))
+            .await
     }
 
-    /// Store events
-    pub async fn store_events(&self, events: Vec<ProcessedEvent>) -> Result<()> {
-        self.backend.store_events(events).await
+    /// Events left out of their typed table because they did not decode
+    pub fn typed_events_skipped(&self) -> u64 {
+        self.typed.skipped()
     }
 
     /// Store a single transaction
@@ -111,6 +380,89 @@ impl StorageManager {
             .await
     }
 
+    /// Delete the events stored for a checkpoint range
+    pub async fn delete_events_by_checkpoint_range(&self, start: u64, end: u64) -> Result<u64> {
+        self.backend
+            .delete_events_by_checkpoint_range(start, end)
+            .await
+    }
+
+    /// Delete the events with `ids` and store `events` in one transaction
+    pub async fn replace_events(
+        &self,
+        ids: &[uuid::Uuid],
+        events: &[ProcessedEvent],
+    ) -> Result<u64> {
+        self.backend
+            .replace_events(ids, events, &self.typed.decode(events))
+            .await
+    }
+
+    /// Find stored events matching a query, newest first
+    pub async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        self.backend.query_events(query).await
+    }
+
+    /// Find stored transactions matching a query, newest first
+    pub async fn query_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> Result<Vec<TransactionRecord>> {
+        self.backend.query_transactions(query).await
+    }
+
+    /// Summarize what was indexed since `since`, listing the `top` most frequent values
+    pub async fn stats_since(&self, since: DateTime<Utc>, top: u32) -> Result<IndexStats> {
+        self.backend.stats_since(since, top).await
+    }
+
+    /// Cumulative event counts as of `checkpoint`, listing the `top` most frequent values
+    pub async fn totals_at(&self, checkpoint: u64, top: u32) -> Result<CheckpointTotals> {
+        self.backend.totals_at(checkpoint, top).await
+    }
+
+    /// Stream the stored events of checkpoints `start..=end` in checkpoint order
+    pub fn export_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<EventRecord>> {
+        self.backend.export_events(start, end)
+    }
+
+    /// Stream the raw events stored for checkpoints `start..=end` in checkpoint order
+    pub fn export_raw_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<RawEventRecord>> {
+        self.backend.export_raw_events(start, end)
+    }
+
+    /// Stream the stored events matching `query` in checkpoint order, ignoring `after` and `limit`
+    pub fn export_matching_events(&self, query: &EventQuery) -> BoxStream<'_, Result<EventRecord>> {
+        self.backend.export_matching_events(query)
+    }
+
+    /// Stream the stored transactions matching `query` in checkpoint order, ignoring `after` and `limit`
+    pub fn export_matching_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        self.backend.export_matching_transactions(query)
+    }
+
+    /// Create `table` shaped like the events table if needed and clear its checkpoints `start..=end`
+    pub async fn reset_event_table(&self, table: &str, start: u64, end: u64) -> Result<()> {
+        self.backend.reset_event_table(table, start, end).await
+    }
+
+    /// Store events into `table` instead of the events table
+    pub async fn store_events_in(&self, table: &str, events: &[ProcessedEvent]) -> Result<()> {
+        self.backend.store_events_in(table, events).await
+    }
+
+    /// Stream the stored transactions of checkpoints `start..=end` in checkpoint order
+    pub fn export_transactions(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        self.backend.export_transactions(start, end)
+    }
+
     /// Get the latest processed checkpoint
     pub async fn get_latest_checkpoint(&self) -> Result<Option<u64>> {
         self.backend.get_latest_checkpoint().await
@@ -126,6 +478,43 @@ impl StorageManager {
         self.backend.update_checkpoint_progress(checkpoint).await
     }
 
+    /// Store the events and outbox messages of a checkpoint and advance the progress in one transaction
+    pub async fn commit_with_outbox(
+        &self,
+        checkpoint: u64,
+        events: &[ProcessedEvent],
+        outbox: Vec<OutboxEntry>,
+    ) -> Result<()> {
+        self.backend
+            .commit_with_outbox(checkpoint, events, &self.typed.decode(events), outbox)
+            .await
+    }
+
+    /// Outbox messages of a sink with an ID above `after`, oldest first, leaving out dead letters
+    pub async fn outbox_pending(
+        &self,
+        sink: &str,
+        after: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxRecord>> {
+        self.backend.outbox_pending(sink, after, limit).await
+    }
+
+    /// Remove delivered outbox messages
+    pub async fn ack_outbox(&self, ids: &[i64]) -> Result<()> {
+        self.backend.ack_outbox(ids).await
+    }
+
+    /// Count a failed delivery attempt of outbox messages, returning how many became dead letters
+    pub async fn fail_outbox(&self, ids: &[i64], error: &str, max_attempts: u32) -> Result<usize> {
+        self.backend.fail_outbox(ids, error, max_attempts).await
+    }
+
+    /// Messages waiting in the outbox, per sink
+    pub async fn outbox_backlog(&self) -> Result<Vec<OutboxBacklog>> {
+        self.backend.outbox_backlog().await
+    }
+
     /// Update the last processed checkpoint
     pub async fn update_last_processed_checkpoint(&self, checkpoint: u64) -> Result<()> {
         self.backend
@@ -133,6 +522,192 @@ impl StorageManager {
             .await
     }
 
+    /// Last processed checkpoint of a named cursor
+    pub async fn get_cursor(&self, pipeline: &str) -> Result<Option<u64>> {
+        self.backend.get_cursor(pipeline).await
+    }
+
+    /// Move a named cursor to `checkpoint`
+    pub async fn set_cursor(&self, pipeline: &str, checkpoint: u64) -> Result<()> {
+        self.backend.set_cursor(pipeline, checkpoint).await
+    }
+
+    /// Chain identifier the stored data was indexed from, if recorded
+    pub async fn get_chain_id(&self) -> Result<Option<String>> {
+        self.backend.get_chain_id().await
+    }
+
+    /// Record the chain identifier the data is indexed from
+    pub async fn set_chain_id(&self, chain_id: &str) -> Result<()> {
+        self.backend.set_chain_id(chain_id).await
+    }
+
+    /// Current position of every named cursor
+    pub async fn list_cursors(&self) -> Result<Vec<CursorRecord>> {
+        self.backend.list_cursors().await
+    }
+
+    /// Record the processing report of a committed checkpoint, replacing an earlier one
+    pub async fn record_checkpoint_report(&self, report: &CheckpointReport) -> Result<()> {
+        self.backend.record_checkpoint_report(report).await
+    }
+
+    /// Reports of `pipeline` for checkpoints `start..=end`, in checkpoint order
+    pub async fn checkpoint_reports(
+        &self,
+        pipeline: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<CheckpointReport>> {
+        self.backend.checkpoint_reports(pipeline, start, end).await
+    }
+
+    /// Store published package versions, keeping versions already stored
+    pub async fn store_packages(&self, packages: &[PackageRecord]) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+        self.backend.store_packages(packages).await
+    }
+
+    /// Stored package versions, of the lineage of `original_id` if given, by original ID and version
+    pub async fn packages(&self, original_id: Option<&str>) -> Result<Vec<PackageRecord>> {
+        self.backend.packages(original_id).await
+    }
+
+    /// Store Move calls, keeping calls already stored
+    pub async fn store_move_calls(&self, calls: &[MoveCallRecord]) -> Result<()> {
+        if calls.is_empty() {
+            return Ok(());
+        }
+        self.backend.store_move_calls(calls).await
+    }
+
+    /// Stored Move calls matching `query`, newest first
+    pub async fn query_move_calls(&self, query: &MoveCallQuery) -> Result<Vec<MoveCallRecord>> {
+        self.backend.query_move_calls(query).await
+    }
+
+    /// Store versions of tracked objects, keeping versions already stored
+    pub async fn store_object_versions(&self, versions: &[ObjectVersionRecord]) -> Result<()> {
+        if versions.is_empty() {
+            return Ok(());
+        }
+        self.backend.store_object_versions(versions).await
+    }
+
+    /// Stored object versions matching `query`, newest first
+    pub async fn query_object_versions(
+        &self,
+        query: &ObjectVersionQuery,
+    ) -> Result<Vec<ObjectVersionRecord>> {
+        self.backend.query_object_versions(query).await
+    }
+
+    /// Add `addresses` to the stored watchlist `watchlist`, returning how many were new
+    pub async fn add_watchlist_addresses(
+        &self,
+        watchlist: &str,
+        addresses: &[String],
+    ) -> Result<u64> {
+        self.backend
+            .add_watchlist_addresses(watchlist, addresses)
+            .await
+    }
+
+    /// Remove `addresses` from the stored watchlist `watchlist`, returning how many were stored
+    pub async fn remove_watchlist_addresses(
+        &self,
+        watchlist: &str,
+        addresses: &[String],
+    ) -> Result<u64> {
+        self.backend
+            .remove_watchlist_addresses(watchlist, addresses)
+            .await
+    }
+
+    /// Addresses stored under the watchlist `watchlist`, in address order
+    pub async fn watchlist_addresses(&self, watchlist: &str) -> Result<Vec<String>> {
+        self.backend.watchlist_addresses(watchlist).await
+    }
+
+    /// Append an operator action to the audit log
+    pub async fn record_audit(&self, entry: &AuditEntry) -> Result<()> {
+        self.backend.record_audit(entry).await
+    }
+
+    /// The `limit` most recent audit log entries, newest first
+    pub async fn audit_log(&self, limit: u32) -> Result<Vec<AuditRecord>> {
+        self.backend.audit_log(limit).await
+    }
+
+    /// Store an API key by the hash of its value
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scope: ApiScope,
+        rate_limit: Option<f64>,
+    ) -> Result<()> {
+        self.backend
+            .create_api_key(name, key_hash, scope, rate_limit)
+            .await
+    }
+
+    /// Every stored API key, including revoked ones, by name
+    pub async fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        self.backend.api_keys().await
+    }
+
+    /// Revoke a stored API key, returning whether an active key was revoked
+    pub async fn revoke_api_key(&self, name: &str) -> Result<bool> {
+        self.backend.revoke_api_key(name).await
+    }
+
+    /// Record a webhook delivery that was retried or failed
+    pub async fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        self.backend.record_webhook_delivery(delivery).await
+    }
+
+    /// The `limit` most recently recorded webhook deliveries, newest first
+    pub async fn webhook_deliveries(
+        &self,
+        limit: u32,
+        failed_only: bool,
+    ) -> Result<Vec<WebhookDeliveryRecord>> {
+        self.backend.webhook_deliveries(limit, failed_only).await
+    }
+
+    /// Delete events, their typed rows and transactions older than `cutoff`
+    pub async fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        self.backend.prune_before(cutoff).await
+    }
+
+    /// Report what `prune_before` would remove without deleting anything
+    pub async fn prunable_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        self.backend.prunable_before(cutoff).await
+    }
+
+    /// Run periodic storage housekeeping
+    pub async fn maintain(&self) -> Result<()> {
+        self.backend.maintain().await
+    }
+
+    /// Name of the storage backend
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Connections held by the backend, if it pools them
+    pub fn connection_stats(&self) -> Option<ConnectionStats> {
+        self.backend.connection_stats()
+    }
+
+    /// Time taken to check a connection out of the pool, if the backend pools them
+    pub async fn acquire_wait(&self) -> Result<Option<Duration>> {
+        self.backend.acquire_wait().await
+    }
+
     /// Health check
     pub async fn health_check(&self) -> Result<bool> {
         self.backend.health_check().await
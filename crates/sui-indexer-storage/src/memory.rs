@@ -0,0 +1,1388 @@
+/// In-memory storage backend
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use futures::{stream::BoxStream, StreamExt};
+use sui_indexer_config::ApiScope;
+use sui_indexer_events::{ProcessedEvent, ProcessedTransaction, TypedRow, TypedTable};
+
+use crate::{
+    postgres::output_table, query, ApiKeyRecord, AuditEntry, AuditRecord, CheckpointReport,
+    CheckpointTotals, CursorRecord, EventQuery, EventRecord, IndexStats, MoveCallQuery,
+    MoveCallRecord, ObjectVersionQuery, ObjectVersionRecord, OutboxBacklog, OutboxEntry,
+    OutboxRecord, PackageRecord, PruneReport, RawEventRecord, Storage, StorageError,
+    TransactionQuery, TransactionRecord, TypedRows, ValueCount, WebhookDelivery,
+    WebhookDeliveryRecord, DEFAULT_PIPELINE,
+};
+
+/// Storage kept in process memory, for embedding the indexer and for tests
+///
+/// Nothing survives a restart. A [`MemoryStorage::cursors_only`] store drops
+/// events and transactions as they are stored, so it does not grow with the chain.
+/// Clones share the stored data.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    discard_data: bool,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    events: Vec<ProcessedEvent>,
+    /// Ids of `events`, to skip events stored again
+    event_ids: HashSet<uuid::Uuid>,
+    /// Events handed to the default table, counting ones skipped as already stored
+    events_written: u64,
+    /// Events stored per checkpoint, package and event type, kept when events are pruned
+    event_counts: BTreeMap<(u64, String, String), i64>,
+    transactions: Vec<TransactionRecord>,
+    /// Replay output tables by name
+    tables: HashMap<String, Vec<ProcessedEvent>>,
+    /// Typed event tables by name, their rows by event id
+    typed: HashMap<String, BTreeMap<uuid::Uuid, TypedRow>>,
+    cursors: BTreeMap<String, CursorRecord>,
+    /// Checkpoint reports by pipeline and checkpoint
+    reports: BTreeMap<(String, u64), CheckpointReport>,
+    /// Package versions by package ID
+    packages: BTreeMap<String, PackageRecord>,
+    /// Move calls by transaction digest and command index
+    move_calls: BTreeMap<(String, i32), MoveCallRecord>,
+    /// Versions of tracked objects by object ID and version
+    object_versions: BTreeMap<(String, i64), ObjectVersionRecord>,
+    /// Watchlist addresses by watchlist name
+    watchlists: BTreeMap<String, BTreeSet<String>>,
+    outbox: BTreeMap<i64, OutboxRecord>,
+    next_outbox_id: i64,
+    audit: Vec<AuditRecord>,
+    api_keys: BTreeMap<String, ApiKeyRecord>,
+    deliveries: Vec<WebhookDeliveryRecord>,
+    chain_id: Option<String>,
+}
+
+impl MemoryStorage {
+    /// Create an empty store that keeps everything written to it
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty store that only keeps cursors and bookkeeping, not indexed data
+    pub fn cursors_only() -> Self {
+        Self {
+            discard_data: true,
+            ..Self::default()
+        }
+    }
+
+    /// Events written to the default table so far, counting ones skipped as already stored
+    ///
+    /// Stored events are deduplicated by id, so writing an event twice is
+    /// only visible here; tests of exactly-once delivery compare it with the
+    /// events they expect.
+    pub fn events_written(&self) -> u64 {
+        self.state().events_written
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The state stays consistent even if a holder panicked, as no update spans an await
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn store_into(&self, table: Option<&str>, events: &[ProcessedEvent]) {
+        if self.discard_data {
+            return;
+        }
+        let mut state = self.state();
+        match table {
+            Some(table) => state
+                .tables
+                .entry(table.to_string())
+                .or_default()
+                .extend_from_slice(events),
+            None => {
+                let State {
+                    events: stored,
+                    event_ids,
+                    events_written,
+                    event_counts,
+                    ..
+                } = &mut *state;
+                *events_written += events.len() as u64;
+                for event in events.iter().filter(|event| event_ids.insert(event.id)) {
+                    let key = (
+                        event.checkpoint_sequence,
+                        event.package_id.to_string(),
+                        event.event_type.clone(),
+                    );
+                    *event_counts.entry(key).or_default() += 1;
+                    stored.push(event.clone());
+                }
+            }
+        }
+    }
+
+    /// Store typed rows, skipping rows already stored
+    fn store_typed_rows(&self, typed: &[TypedRows<'_>]) {
+        if self.discard_data {
+            return;
+        }
+        let mut state = self.state();
+        for (table, rows) in typed {
+            let stored = state.typed.entry(table.name.clone()).or_default();
+            for row in rows {
+                stored.entry(row.id).or_insert_with(|| row.clone());
+            }
+        }
+    }
+
+    /// Remove the typed rows `remove` selects from every typed table
+    fn remove_typed_rows(&self, remove: impl Fn(&TypedRow) -> bool) {
+        for rows in self.state().typed.values_mut() {
+            rows.retain(|_, row| !remove(row));
+        }
+    }
+
+    /// Stored events of checkpoints `start..=end` in checkpoint order
+    fn events_between(&self, start: u64, end: u64) -> Vec<ProcessedEvent> {
+        let mut events: Vec<_> = self
+            .state()
+            .events
+            .iter()
+            .filter(|event| (start..=end).contains(&event.checkpoint_sequence))
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| (event.checkpoint_sequence, event.id));
+        events
+    }
+}
+
+/// Queryable columns of a stored event
+fn event_record(event: &ProcessedEvent) -> EventRecord {
+    EventRecord {
+        id: event.id,
+        checkpoint_sequence: event.checkpoint_sequence as i64,
+        transaction_digest: event.transaction_digest.to_string(),
+        timestamp: event.timestamp,
+        package_id: event.package_id.to_string(),
+        module_name: event.module_name.clone(),
+        event_type: event.event_type.clone(),
+        sender: event.sender.clone(),
+        fields: event.fields.clone(),
+    }
+}
+
+/// The `top` most frequent values, most frequent first
+fn top_values(values: impl Iterator<Item = String>, top: u32) -> Vec<ValueCount> {
+    top_counts(values.map(|value| (value, 1)), top)
+}
+
+/// The `top` values with the highest summed counts, highest first
+fn top_counts(values: impl Iterator<Item = (String, i64)>, top: u32) -> Vec<ValueCount> {
+    let mut counts = BTreeMap::<String, i64>::new();
+    for (value, count) in values {
+        *counts.entry(value).or_default() += count;
+    }
+    let mut counts: Vec<_> = counts
+        .into_iter()
+        .map(|(value, count)| ValueCount { value, count })
+        .collect();
+    // Stable, so equal counts stay in value order
+    counts.sort_by_key(|count| Reverse(count.count));
+    counts.truncate(top as usize);
+    counts
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn store_events_with_rows(
+        &self,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<()> {
+        self.store_into(None, events);
+        self.store_typed_rows(typed);
+        Ok(())
+    }
+
+    async fn store_transactions(&self, transactions: Vec<ProcessedTransaction>) -> Result<()> {
+        if self.discard_data {
+            return Ok(());
+        }
+        let mut state = self.state();
+        for tx in transactions {
+            let digest = tx.transaction.digest.to_string();
+            // Digests are unique, as in PostgreSQL
+            if state
+                .transactions
+                .iter()
+                .any(|stored| stored.digest == digest)
+            {
+                continue;
+            }
+            state.transactions.push(TransactionRecord {
+                id: tx.id,
+                checkpoint_sequence: tx.checkpoint_sequence as i64,
+                digest,
+                timestamp: tx.timestamp,
+                sender: "0x0".to_string(),
+                gas_used: tx.metadata.gas_used.unwrap_or(0) as i64,
+                status: tx.metadata.success.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn get_events_by_checkpoint_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<ProcessedEvent>> {
+        let mut events = self.events_between(start, end);
+        events.sort_by_key(|event| (event.checkpoint_sequence, event.metadata.processed_at));
+        Ok(events)
+    }
+
+    async fn delete_events_by_checkpoint_range(&self, start: u64, end: u64) -> Result<u64> {
+        self.remove_typed_rows(|row| (start..=end).contains(&row.checkpoint_sequence));
+        let mut state = self.state();
+        let before = state.events.len();
+        let State {
+            events,
+            event_ids,
+            event_counts,
+            ..
+        } = &mut *state;
+        // The range is indexed again, which counts its events anew
+        event_counts.retain(|(checkpoint, _, _), _| !(start..=end).contains(checkpoint));
+        events.retain(|event| {
+            let keep = !(start..=end).contains(&event.checkpoint_sequence);
+            if !keep {
+                event_ids.remove(&event.id);
+            }
+            keep
+        });
+        Ok((before - state.events.len()) as u64)
+    }
+
+    async fn replace_events(
+        &self,
+        ids: &[uuid::Uuid],
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<u64> {
+        self.remove_typed_rows(|row| ids.contains(&row.id));
+        let deleted = {
+            let mut state = self.state();
+            let before = state.events.len();
+            let State {
+                events: stored,
+                event_ids,
+                event_counts,
+                ..
+            } = &mut *state;
+            stored.retain(|event| {
+                let keep = !ids.contains(&event.id);
+                if !keep {
+                    event_ids.remove(&event.id);
+                    let key = (
+                        event.checkpoint_sequence,
+                        event.package_id.to_string(),
+                        event.event_type.clone(),
+                    );
+                    if let Some(count) = event_counts.get_mut(&key) {
+                        *count -= 1;
+                    }
+                }
+                keep
+            });
+            (before - state.events.len()) as u64
+        };
+        self.store_into(None, events);
+        self.store_typed_rows(typed);
+        Ok(deleted)
+    }
+
+    async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        let events = self
+            .state()
+            .events
+            .iter()
+            .map(event_record)
+            .filter(|event| query.matches(event))
+            .collect();
+        Ok(query::page(
+            events,
+            EventRecord::cursor,
+            query.after,
+            query.limit,
+        ))
+    }
+
+    async fn query_transactions(&self, query: &TransactionQuery) -> Result<Vec<TransactionRecord>> {
+        let transactions = self
+            .state()
+            .transactions
+            .iter()
+            .filter(|transaction| query.matches(transaction))
+            .cloned()
+            .collect();
+        Ok(query::page(
+            transactions,
+            TransactionRecord::cursor,
+            query.after,
+            query.limit,
+        ))
+    }
+
+    async fn stats_since(&self, since: DateTime<Utc>, top: u32) -> Result<IndexStats> {
+        let state = self.state();
+        let events: Vec<_> = state
+            .events
+            .iter()
+            .filter(|event| event.timestamp >= since)
+            .collect();
+        let transactions: Vec<_> = state
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.timestamp >= since)
+            .collect();
+        let lag_secs: f64 = events
+            .iter()
+            .map(|event| {
+                (event.metadata.processed_at - event.timestamp).num_milliseconds() as f64 / 1000.0
+            })
+            .sum();
+
+        Ok(IndexStats {
+            since,
+            events: events.len() as u64,
+            transactions: transactions.len() as u64,
+            failed_transactions: transactions
+                .iter()
+                .filter(|transaction| transaction.status == "false")
+                .count() as u64,
+            average_lag_secs: (!events.is_empty()).then(|| lag_secs / events.len() as f64),
+            event_types: top_values(events.iter().map(|event| event.event_type.clone()), top),
+            packages: top_values(events.iter().map(|event| event.package_id.to_string()), top),
+        })
+    }
+
+    async fn totals_at(&self, checkpoint: u64, top: u32) -> Result<CheckpointTotals> {
+        let state = self.state();
+        let counts: Vec<_> = state
+            .event_counts
+            .iter()
+            .take_while(|((counted, _, _), _)| *counted <= checkpoint)
+            .collect();
+
+        Ok(CheckpointTotals {
+            checkpoint,
+            events: counts.iter().map(|(_, count)| **count as u64).sum(),
+            event_types: top_counts(
+                counts
+                    .iter()
+                    .map(|((_, _, event_type), count)| (event_type.clone(), **count)),
+                top,
+            ),
+            packages: top_counts(
+                counts
+                    .iter()
+                    .map(|((_, package, _), count)| (package.clone(), **count)),
+                top,
+            ),
+        })
+    }
+
+    fn export_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<EventRecord>> {
+        let events: Vec<_> = self
+            .events_between(start, end)
+            .iter()
+            .map(|event| Ok(event_record(event)))
+            .collect();
+        futures::stream::iter(events).boxed()
+    }
+
+    fn export_raw_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<RawEventRecord>> {
+        let events: Vec<_> = self
+            .events_between(start, end)
+            .into_iter()
+            .map(|event| {
+                Ok(RawEventRecord {
+                    checkpoint_sequence: event.checkpoint_sequence as i64,
+                    timestamp: event.timestamp,
+                    event_data: Some(serde_json::to_value(&event.event)?),
+                })
+            })
+            .collect();
+        futures::stream::iter(events).boxed()
+    }
+
+    async fn reset_event_table(&self, table: &str, start: u64, end: u64) -> Result<()> {
+        output_table(table)?;
+        self.state()
+            .tables
+            .entry(table.to_string())
+            .or_default()
+            .retain(|event| !(start..=end).contains(&event.checkpoint_sequence));
+        Ok(())
+    }
+
+    async fn store_events_in(&self, table: &str, events: &[ProcessedEvent]) -> Result<()> {
+        self.store_into(Some(output_table(table)?), events);
+        Ok(())
+    }
+
+    async fn migrate_typed_table(&self, table: &TypedTable) -> Result<()> {
+        table.validate()?;
+        self.state().typed.entry(table.name.clone()).or_default();
+        Ok(())
+    }
+
+    fn export_matching_events(&self, query: &EventQuery) -> BoxStream<'_, Result<EventRecord>> {
+        let mut events: Vec<_> = self
+            .state()
+            .events
+            .iter()
+            .map(event_record)
+            .filter(|event| query.matches(event))
+            .collect();
+        events.sort_by_key(|event| (event.checkpoint_sequence, event.id));
+        futures::stream::iter(events.into_iter().map(Ok)).boxed()
+    }
+
+    fn export_matching_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        let mut transactions: Vec<_> = self
+            .state()
+            .transactions
+            .iter()
+            .filter(|transaction| query.matches(transaction))
+            .cloned()
+            .collect();
+        transactions.sort_by_key(|transaction| (transaction.checkpoint_sequence, transaction.id));
+        futures::stream::iter(transactions.into_iter().map(Ok)).boxed()
+    }
+
+    fn export_transactions(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        let range = start as i64..=end as i64;
+        let mut transactions: Vec<_> = self
+            .state()
+            .transactions
+            .iter()
+            .filter(|transaction| range.contains(&transaction.checkpoint_sequence))
+            .cloned()
+            .collect();
+        transactions.sort_by_key(|transaction| (transaction.checkpoint_sequence, transaction.id));
+        futures::stream::iter(transactions.into_iter().map(Ok)).boxed()
+    }
+
+    async fn get_latest_checkpoint(&self) -> Result<Option<u64>> {
+        self.get_cursor(DEFAULT_PIPELINE).await
+    }
+
+    async fn update_checkpoint_progress(&self, checkpoint: u64) -> Result<()> {
+        self.set_cursor(DEFAULT_PIPELINE, checkpoint).await
+    }
+
+    async fn commit_with_outbox(
+        &self,
+        checkpoint: u64,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+        outbox: Vec<OutboxEntry>,
+    ) -> Result<()> {
+        let payloads = outbox
+            .iter()
+            .map(|entry| serde_json::to_value(&entry.event))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.store_into(None, events);
+        self.store_typed_rows(typed);
+        let mut state = self.state();
+        for (entry, payload) in outbox.into_iter().zip(payloads) {
+            state.next_outbox_id += 1;
+            let id = state.next_outbox_id;
+            state.outbox.insert(
+                id,
+                OutboxRecord {
+                    id,
+                    created_at: Utc::now(),
+                    sink: entry.sink,
+                    checkpoint_sequence: entry.event.checkpoint_sequence as i64,
+                    payload,
+                    attempts: 0,
+                    last_error: None,
+                    dead_at: None,
+                },
+            );
+        }
+        state.cursors.insert(
+            DEFAULT_PIPELINE.to_string(),
+            CursorRecord {
+                pipeline: DEFAULT_PIPELINE.to_string(),
+                checkpoint_sequence: checkpoint as i64,
+                updated_at: Some(Utc::now().naive_utc()),
+            },
+        );
+        Ok(())
+    }
+
+    async fn outbox_pending(
+        &self,
+        sink: &str,
+        after: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxRecord>> {
+        Ok(self
+            .state()
+            .outbox
+            .range(after + 1..)
+            .map(|(_, message)| message)
+            .filter(|message| message.sink == sink && message.dead_at.is_none())
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn ack_outbox(&self, ids: &[i64]) -> Result<()> {
+        let mut state = self.state();
+        for id in ids {
+            state.outbox.remove(id);
+        }
+        Ok(())
+    }
+
+    async fn fail_outbox(&self, ids: &[i64], error: &str, max_attempts: u32) -> Result<usize> {
+        let mut state = self.state();
+        let mut dead = 0;
+        for id in ids {
+            if let Some(message) = state.outbox.get_mut(id) {
+                message.attempts += 1;
+                message.last_error = Some(error.to_string());
+                if message.attempts as u32 >= max_attempts {
+                    message.dead_at = Some(Utc::now());
+                    dead += 1;
+                }
+            }
+        }
+        Ok(dead)
+    }
+
+    async fn outbox_backlog(&self) -> Result<Vec<OutboxBacklog>> {
+        let mut backlog = BTreeMap::<&str, OutboxBacklog>::new();
+        let state = self.state();
+        // Pending messages first, so the oldest one describes the sink
+        let messages = state
+            .outbox
+            .values()
+            .filter(|message| message.dead_at.is_none());
+        let dead = state
+            .outbox
+            .values()
+            .filter(|message| message.dead_at.is_some());
+        for message in messages.chain(dead) {
+            let sink = backlog
+                .entry(&message.sink)
+                .or_insert_with(|| OutboxBacklog {
+                    sink: message.sink.clone(),
+                    messages: 0,
+                    dead_letters: 0,
+                    oldest: message.created_at,
+                    attempts: message.attempts,
+                    last_error: message.last_error.clone(),
+                });
+            if message.dead_at.is_none() {
+                sink.messages += 1;
+            } else {
+                sink.dead_letters += 1;
+            }
+        }
+        Ok(backlog.into_values().collect())
+    }
+
+    async fn get_cursor(&self, pipeline: &str) -> Result<Option<u64>> {
+        Ok(self
+            .state()
+            .cursors
+            .get(pipeline)
+            .map(|cursor| cursor.checkpoint_sequence as u64))
+    }
+
+    async fn set_cursor(&self, pipeline: &str, checkpoint: u64) -> Result<()> {
+        self.state().cursors.insert(
+            pipeline.to_string(),
+            CursorRecord {
+                pipeline: pipeline.to_string(),
+                checkpoint_sequence: checkpoint as i64,
+                updated_at: Some(Utc::now().naive_utc()),
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_chain_id(&self) -> Result<Option<String>> {
+        Ok(self.state().chain_id.clone())
+    }
+
+    async fn set_chain_id(&self, chain_id: &str) -> Result<()> {
+        self.state().chain_id = Some(chain_id.to_string());
+        Ok(())
+    }
+
+    async fn list_cursors(&self) -> Result<Vec<CursorRecord>> {
+        Ok(self.state().cursors.values().cloned().collect())
+    }
+
+    async fn record_checkpoint_report(&self, report: &CheckpointReport) -> Result<()> {
+        let key = (
+            report.pipeline.clone(),
+            report.checkpoint_sequence.max(0) as u64,
+        );
+        self.state().reports.insert(key, report.clone());
+        Ok(())
+    }
+
+    async fn checkpoint_reports(
+        &self,
+        pipeline: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<CheckpointReport>> {
+        if start > end {
+            return Ok(Vec::new());
+        }
+        let range = (pipeline.to_string(), start)..=(pipeline.to_string(), end);
+        Ok(self
+            .state()
+            .reports
+            .range(range)
+            .map(|(_, report)| report.clone())
+            .collect())
+    }
+
+    async fn store_packages(&self, packages: &[PackageRecord]) -> Result<()> {
+        let mut state = self.state();
+        for package in packages {
+            state
+                .packages
+                .entry(package.package_id.clone())
+                .or_insert_with(|| package.clone());
+        }
+        Ok(())
+    }
+
+    async fn packages(&self, original_id: Option<&str>) -> Result<Vec<PackageRecord>> {
+        let mut packages: Vec<_> = self
+            .state()
+            .packages
+            .values()
+            .filter(|package| original_id.is_none_or(|id| package.original_id == id))
+            .cloned()
+            .collect();
+        packages.sort_by(|a, b| {
+            (a.original_id.as_str(), a.version).cmp(&(b.original_id.as_str(), b.version))
+        });
+        Ok(packages)
+    }
+
+    async fn store_move_calls(&self, calls: &[MoveCallRecord]) -> Result<()> {
+        if self.discard_data {
+            return Ok(());
+        }
+        let mut state = self.state();
+        for call in calls {
+            state
+                .move_calls
+                .entry((call.transaction_digest.clone(), call.command_index))
+                .or_insert_with(|| call.clone());
+        }
+        Ok(())
+    }
+
+    async fn query_move_calls(&self, query: &MoveCallQuery) -> Result<Vec<MoveCallRecord>> {
+        let calls = self
+            .state()
+            .move_calls
+            .values()
+            .filter(|call| query.matches(call))
+            .cloned()
+            .collect();
+        Ok(query::page(
+            calls,
+            MoveCallRecord::cursor,
+            query.after,
+            query.limit,
+        ))
+    }
+
+    async fn add_watchlist_addresses(&self, watchlist: &str, addresses: &[String]) -> Result<u64> {
+        let mut state = self.state();
+        let stored = state.watchlists.entry(watchlist.to_string()).or_default();
+        Ok(addresses
+            .iter()
+            .filter(|address| stored.insert(address.to_string()))
+            .count() as u64)
+    }
+
+    async fn remove_watchlist_addresses(
+        &self,
+        watchlist: &str,
+        addresses: &[String],
+    ) -> Result<u64> {
+        let mut state = self.state();
+        let Some(stored) = state.watchlists.get_mut(watchlist) else {
+            return Ok(0);
+        };
+        Ok(addresses
+            .iter()
+            .filter(|address| stored.remove(address.as_str()))
+            .count() as u64)
+    }
+
+    async fn watchlist_addresses(&self, watchlist: &str) -> Result<Vec<String>> {
+        Ok(self
+            .state()
+            .watchlists
+            .get(watchlist)
+            .map(|stored| stored.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn store_object_versions(&self, versions: &[ObjectVersionRecord]) -> Result<()> {
+        if self.discard_data {
+            return Ok(());
+        }
+        let mut state = self.state();
+        for version in versions {
+            state
+                .object_versions
+                .entry((version.object_id.clone(), version.version))
+                .or_insert_with(|| version.clone());
+        }
+        Ok(())
+    }
+
+    async fn query_object_versions(
+        &self,
+        query: &ObjectVersionQuery,
+    ) -> Result<Vec<ObjectVersionRecord>> {
+        let versions = self
+            .state()
+            .object_versions
+            .values()
+            .filter(|version| query.matches(version))
+            .cloned()
+            .collect();
+        Ok(query::page(
+            versions,
+            ObjectVersionRecord::cursor,
+            query.after,
+            query.limit,
+        ))
+    }
+
+    async fn record_audit(&self, entry: &AuditEntry) -> Result<()> {
+        let mut state = self.state();
+        let id = state.audit.len() as i64 + 1;
+        state.audit.push(AuditRecord {
+            id,
+            occurred_at: Utc::now(),
+            actor: entry.actor.clone(),
+            source: entry.source.clone(),
+            action: entry.action.clone(),
+            details: entry.details.clone(),
+        });
+        Ok(())
+    }
+
+    async fn audit_log(&self, limit: u32) -> Result<Vec<AuditRecord>> {
+        Ok(self
+            .state()
+            .audit
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scope: ApiScope,
+        rate_limit: Option<f64>,
+    ) -> Result<()> {
+        let mut state = self.state();
+        if state.api_keys.contains_key(name) {
+            return Err(StorageError::DuplicateApiKey(name.to_string()).into());
+        }
+        state.api_keys.insert(
+            name.to_string(),
+            ApiKeyRecord {
+                name: name.to_string(),
+                key_hash: key_hash.to_string(),
+                scope: scope.to_string(),
+                rate_limit,
+                created_at: Utc::now(),
+                revoked_at: None,
+            },
+        );
+        Ok(())
+    }
+
+    async fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        Ok(self.state().api_keys.values().cloned().collect())
+    }
+
+    async fn revoke_api_key(&self, name: &str) -> Result<bool> {
+        match self.state().api_keys.get_mut(name) {
+            Some(key) if key.revoked_at.is_none() => {
+                key.revoked_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        self.state().deliveries.push(WebhookDeliveryRecord {
+            id: delivery.id,
+            recorded_at: Utc::now(),
+            sink: delivery.sink.clone(),
+            url: delivery.url.clone(),
+            status: delivery.status.clone(),
+            attempts: delivery.attempts,
+            events: delivery.events,
+            first_checkpoint: delivery.first_checkpoint,
+            last_checkpoint: delivery.last_checkpoint,
+            response_status: delivery.response_status,
+            error: delivery.error.clone(),
+            payload: delivery.payload.clone(),
+        });
+        Ok(())
+    }
+
+    async fn webhook_deliveries(
+        &self,
+        limit: u32,
+        failed_only: bool,
+    ) -> Result<Vec<WebhookDeliveryRecord>> {
+        Ok(self
+            .state()
+            .deliveries
+            .iter()
+            .rev()
+            .filter(|delivery| !failed_only || delivery.status == "failed")
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        self.remove_typed_rows(|row| row.timestamp < cutoff);
+        let mut state = self.state();
+        let (events, transactions) = (state.events.len(), state.transactions.len());
+        let State {
+            events: stored,
+            event_ids,
+            ..
+        } = &mut *state;
+        stored.retain(|event| {
+            let keep = event.timestamp >= cutoff;
+            if !keep {
+                event_ids.remove(&event.id);
+            }
+            keep
+        });
+        state
+            .transactions
+            .retain(|transaction| transaction.timestamp >= cutoff);
+        state
+            .reports
+            .retain(|_, report| report.processed_at >= cutoff);
+        state
+            .deliveries
+            .retain(|delivery| delivery.recorded_at >= cutoff);
+        state.move_calls.retain(|_, call| call.timestamp >= cutoff);
+        state
+            .object_versions
+            .retain(|_, version| version.timestamp >= cutoff);
+        Ok(PruneReport {
+            partitions: Vec::new(),
+            events: (events - state.events.len()) as u64,
+            transactions: (transactions - state.transactions.len()) as u64,
+        })
+    }
+
+    async fn prunable_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        let state = self.state();
+        Ok(PruneReport {
+            partitions: Vec::new(),
+            events: state
+                .events
+                .iter()
+                .filter(|event| event.timestamp < cutoff)
+                .count() as u64,
+            transactions: state
+                .transactions
+                .iter()
+                .filter(|transaction| transaction.timestamp < cutoff)
+                .count() as u64,
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+    use crate::PageCursor;
+
+    async fn events(count: u64) -> Result<Vec<ProcessedEvent>> {
+        let raw = (0..count)
+            .map(|seq| {
+                let event_type = if seq % 2 == 0 {
+                    "0x2::pool::SwapEvent"
+                } else {
+                    "0x2::pool::MintEvent"
+                };
+                TestEvent::new(event_type).seq(seq).build()
+            })
+            .collect();
+        let mut events = DefaultEventProcessor::new().process_events(raw).await?;
+        for (checkpoint, event) in events.iter_mut().enumerate() {
+            event.checkpoint_sequence = checkpoint as u64;
+        }
+        Ok(events)
+    }
+
+    #[tokio::test]
+    async fn test_queries_page_newest_first() -> Result<()> {
+        let storage = MemoryStorage::new();
+        storage.store_events(&events(5).await?).await?;
+
+        let query = EventQuery {
+            event_type: Some("SwapEvent".to_string()),
+            limit: Some(2),
+            ..EventQuery::default()
+        };
+        let first = storage.query_events(&query).await?;
+        let checkpoints: Vec<_> = first.iter().map(|e| e.checkpoint_sequence).collect();
+        assert_eq!(checkpoints, [4, 2]);
+
+        let after = PageCursor::next_page(&first, 2, EventRecord::cursor);
+        let rest = storage.query_events(&EventQuery { after, ..query }).await?;
+        let checkpoints: Vec<_> = rest.iter().map(|e| e.checkpoint_sequence).collect();
+        assert_eq!(checkpoints, [0]);
+
+        let stats = storage.stats_since(DateTime::<Utc>::MIN_UTC, 1).await?;
+        assert_eq!(stats.events, 5);
+        assert_eq!(stats.event_types[0].value, "SwapEvent");
+        assert_eq!(stats.event_types[0].count, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stats_since_summarize_the_window() -> Result<()> {
+        crate::test_db::check_stats_since(&MemoryStorage::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_pages_stay_stable_while_events_are_stored() -> Result<()> {
+        crate::test_db::check_stable_pages(&MemoryStorage::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_lists_newest_first() -> Result<()> {
+        crate::test_db::check_audit_log(&MemoryStorage::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_events_stored_again_are_skipped() -> Result<()> {
+        let storage = MemoryStorage::new();
+        storage.store_events(&events(3).await?).await?;
+        storage.store_events(&events(4).await?).await?;
+        assert_eq!(storage.query_events(&EventQuery::default()).await?.len(), 4);
+        // Only the write count shows the events written twice
+        assert_eq!(storage.clone().events_written(), 7);
+
+        // Deleted events can be indexed again
+        assert_eq!(storage.delete_events_by_checkpoint_range(0, 1).await?, 2);
+        storage.store_events(&events(4).await?).await?;
+        assert_eq!(storage.query_events(&EventQuery::default()).await?.len(), 4);
+        Ok(())
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct SwapEvent {
+        #[serde(default)]
+        pool: Option<String>,
+    }
+
+    impl sui_indexer_events::TypedEvent for SwapEvent {
+        const MODULE: &'static str = "pool";
+        const NAME: &'static str = "SwapEvent";
+
+        fn columns() -> Vec<sui_indexer_events::Column> {
+            vec![sui_indexer_events::Column::new(
+                "pool",
+                sui_indexer_events::ColumnType::Text,
+            )]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_rows_follow_their_events() -> Result<()> {
+        let registry = sui_indexer_events::TypedEventRegistry::new().register::<SwapEvent>()?;
+        let storage = MemoryStorage::new();
+        let table = registry.tables().next().unwrap();
+        storage.migrate_typed_table(table).await?;
+        let typed_rows = |storage: &MemoryStorage| storage.state().typed["typed_swap_event"].len();
+
+        for count in [3, 5] {
+            let events = events(count).await?;
+            storage
+                .store_events_with_rows(&events, &registry.decode(&events))
+                .await?;
+        }
+        // Only the swaps, each once
+        assert_eq!(typed_rows(&storage), 3);
+        assert!(storage.state().typed["typed_swap_event"]
+            .values()
+            .all(|row| row.values["pool"].is_null()));
+
+        // Deleting a range and replacing events take their rows along
+        storage.delete_events_by_checkpoint_range(4, 4).await?;
+        assert_eq!(typed_rows(&storage), 2);
+        let swap = events(1).await?;
+        storage.replace_events(&[swap[0].id], &[], &[]).await?;
+        assert_eq!(typed_rows(&storage), 1);
+        storage
+            .prune_before(Utc::now() + chrono::Duration::hours(1))
+            .await?;
+        assert_eq!(typed_rows(&storage), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_totals_at_checkpoint_outlive_pruning() -> Result<()> {
+        let storage = MemoryStorage::new();
+        storage.store_events(&events(5).await?).await?;
+
+        let totals = storage.totals_at(2, 10).await?;
+        assert_eq!(totals.events, 3);
+        assert_eq!(totals.event_types[0].value, "SwapEvent");
+        assert_eq!(totals.event_types[0].count, 2);
+        assert_eq!(storage.totals_at(u64::MAX, 1).await?.events, 5);
+
+        // Re-indexed events are not counted twice, and pruned ones still count
+        storage.delete_events_by_checkpoint_range(3, 4).await?;
+        storage.store_events(&events(5).await?).await?;
+        assert_eq!(storage.totals_at(4, 1).await?.events, 5);
+        storage.prune_before(DateTime::<Utc>::MAX_UTC).await?;
+        assert_eq!(storage.totals_at(4, 1).await?.events, 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_reports_are_replaced_per_pipeline() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let processed_at = Utc::now();
+        let report = |pipeline: &str, checkpoint: i64, stored: i64| CheckpointReport {
+            pipeline: pipeline.to_string(),
+            checkpoint_sequence: checkpoint,
+            transactions: 1,
+            events_seen: 3,
+            events_matched: stored,
+            events_skipped: 3 - stored,
+            events_stored: stored,
+            matched_by_filter: json!({"swaps": stored}),
+            fetch_ms: 5,
+            transform_ms: 1,
+            store_ms: 2,
+            deliver_ms: 0,
+            processed_at,
+        };
+        for checkpoint in 0..4 {
+            storage
+                .record_checkpoint_report(&report(DEFAULT_PIPELINE, checkpoint, 1))
+                .await?;
+        }
+        storage
+            .record_checkpoint_report(&report("replay", 1, 3))
+            .await?;
+        // Processing a checkpoint again replaces its report
+        storage
+            .record_checkpoint_report(&report(DEFAULT_PIPELINE, 2, 2))
+            .await?;
+
+        let reports = storage.checkpoint_reports(DEFAULT_PIPELINE, 1, 2).await?;
+        assert_eq!(
+            reports,
+            vec![
+                report(DEFAULT_PIPELINE, 1, 1),
+                report(DEFAULT_PIPELINE, 2, 2)
+            ]
+        );
+        assert_eq!(storage.checkpoint_reports("replay", 0, 10).await?.len(), 1);
+        assert!(storage
+            .checkpoint_reports(DEFAULT_PIPELINE, 3, 2)
+            .await?
+            .is_empty());
+
+        storage.prune_before(DateTime::<Utc>::MAX_UTC).await?;
+        assert!(storage
+            .checkpoint_reports(DEFAULT_PIPELINE, 0, 10)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_webhook_deliveries_are_pruned() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let delivery = WebhookDelivery {
+            id: uuid::Uuid::new_v4(),
+            sink: "alerts".to_string(),
+            url: "https://hooks.example.com/sui".to_string(),
+            status: "failed".to_string(),
+            attempts: 5,
+            events: 2,
+            first_checkpoint: 10,
+            last_checkpoint: 11,
+            response_status: Some(503),
+            error: Some("Service Unavailable".to_string()),
+            payload: Some(json!([])),
+        };
+        storage.record_webhook_delivery(&delivery).await?;
+
+        storage
+            .prune_before(Utc::now() - chrono::Duration::hours(1))
+            .await?;
+        assert_eq!(storage.webhook_deliveries(10, true).await?.len(), 1);
+        storage.prune_before(DateTime::<Utc>::MAX_UTC).await?;
+        assert!(storage.webhook_deliveries(10, false).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_packages_are_listed_by_lineage() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let published_at = Utc::now();
+        let package = |package_id: &str, original_id: &str, version: i64| PackageRecord {
+            package_id: package_id.to_string(),
+            original_id: original_id.to_string(),
+            version,
+            modules: vec!["pool".to_string()],
+            tx_digest: format!("tx-{package_id}"),
+            sender: "0x1".to_string(),
+            checkpoint_sequence: version,
+            published_at,
+        };
+        storage
+            .store_packages(&[package("0xb2", "0xb1", 2), package("0xa1", "0xa1", 1)])
+            .await?;
+        storage
+            .store_packages(&[package("0xb1", "0xb1", 1), package("0xb2", "0xb2", 9)])
+            .await?;
+
+        let lineage = storage.packages(Some("0xb1")).await?;
+        assert_eq!(
+            lineage,
+            vec![package("0xb1", "0xb1", 1), package("0xb2", "0xb1", 2)]
+        );
+        let ids: Vec<_> = storage
+            .packages(None)
+            .await?
+            .into_iter()
+            .map(|package| package.package_id)
+            .collect();
+        assert_eq!(ids, ["0xa1", "0xb1", "0xb2"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_versions_are_stored_once_and_queried_by_object() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let version = |object_id: &str, version: i64, checkpoint: i64| ObjectVersionRecord {
+            id: uuid::Uuid::new_v4(),
+            object_id: object_id.to_string(),
+            version,
+            checkpoint_sequence: checkpoint,
+            transaction_digest: "tx1".to_string(),
+            timestamp: Utc::now(),
+            change_kind: "mutated".to_string(),
+            digest: Some("digest".to_string()),
+            object_type: Some("0xa1::pool::Pool".to_string()),
+            owner: Some("shared".to_string()),
+            contents: Some(json!({"reserve_a": version * 100})),
+        };
+        let (first, second) = (version("0x5", 1, 10), version("0x5", 2, 11));
+        storage
+            .store_object_versions(&[first.clone(), second.clone(), version("0x6", 1, 10)])
+            .await?;
+        // Stored again, e.g. by a retried commit, under a new ID
+        storage
+            .store_object_versions(&[version("0x5", 2, 11)])
+            .await?;
+
+        let history = storage
+            .query_object_versions(&ObjectVersionQuery {
+                object_id: Some("0x5".to_string()),
+                ..ObjectVersionQuery::default()
+            })
+            .await?;
+        assert_eq!(history, vec![second, first]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_move_calls_are_stored_once_and_queried_by_target() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let call = |digest: &str, command_index: i32, function: &str| MoveCallRecord {
+            id: uuid::Uuid::new_v4(),
+            checkpoint_sequence: 5,
+            transaction_digest: digest.to_string(),
+            command_index,
+            timestamp: Utc::now(),
+            package_id: "0xa1".to_string(),
+            module_name: "pool".to_string(),
+            function_name: function.to_string(),
+            type_arguments: vec!["0x2::sui::SUI".to_string()],
+            sender: "0x1".to_string(),
+        };
+        let swap = call("tx1", 0, "swap");
+        storage
+            .store_move_calls(&[swap.clone(), call("tx1", 1, "add_liquidity")])
+            .await?;
+        // Stored again, e.g. by a retried commit, under a new ID
+        storage.store_move_calls(&[call("tx1", 0, "swap")]).await?;
+
+        let swaps = storage
+            .query_move_calls(&MoveCallQuery {
+                function: Some("swap".to_string()),
+                ..MoveCallQuery::default()
+            })
+            .await?;
+        assert_eq!(swaps, vec![swap]);
+        assert_eq!(swaps[0].target(), "0xa1::pool::swap");
+        let all = storage
+            .query_move_calls(&MoveCallQuery {
+                module: Some("pool".to_string()),
+                ..MoveCallQuery::default()
+            })
+            .await?;
+        assert_eq!(all.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watchlist_addresses_are_kept_per_watchlist() -> Result<()> {
+        let storage = MemoryStorage::new();
+        let addresses = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            storage
+                .add_watchlist_addresses("sanctions", &addresses(&["0xb", "0xa"]))
+                .await?,
+            2
+        );
+        assert_eq!(
+            storage
+                .add_watchlist_addresses("sanctions", &addresses(&["0xa", "0xc"]))
+                .await?,
+            1
+        );
+        storage
+            .add_watchlist_addresses("vip", &addresses(&["0xa"]))
+            .await?;
+        assert_eq!(
+            storage
+                .remove_watchlist_addresses("sanctions", &addresses(&["0xc", "0xd"]))
+                .await?,
+            1
+        );
+
+        assert_eq!(
+            storage.watchlist_addresses("sanctions").await?,
+            ["0xa", "0xb"]
+        );
+        assert!(storage.watchlist_addresses("unknown").await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cursors_only_keeps_progress_and_outbox() -> Result<()> {
+        let storage = MemoryStorage::cursors_only();
+        let outbox = events(3)
+            .await?
+            .into_iter()
+            .map(|event| OutboxEntry {
+                sink: "kafka".to_string(),
+                event,
+            })
+            .collect();
+        storage
+            .commit_with_outbox(7, &events(3).await?, &[], outbox)
+            .await?;
+
+        assert_eq!(storage.get_latest_checkpoint().await?, Some(7));
+        assert!(storage
+            .query_events(&EventQuery::default())
+            .await?
+            .is_empty());
+
+        let pending = storage.outbox_pending("kafka", 0, 2).await?;
+        assert_eq!(pending.len(), 2);
+        storage.ack_outbox(&[pending[0].id]).await?;
+        assert_eq!(
+            storage
+                .fail_outbox(&[pending[1].id], "timed out", 2)
+                .await?,
+            0
+        );
+        let backlog = storage.outbox_backlog().await?;
+        assert_eq!(backlog[0].messages, 2);
+        assert_eq!(backlog[0].attempts, 1);
+
+        // A second failure reaches max_attempts and stops the message from being sent
+        assert_eq!(
+            storage
+                .fail_outbox(&[pending[1].id], "timed out", 2)
+                .await?,
+            1
+        );
+        let pending = storage.outbox_pending("kafka", 0, 10).await?;
+        assert_eq!(pending.len(), 1);
+        let backlog = storage.outbox_backlog().await?;
+        assert_eq!((backlog[0].messages, backlog[0].dead_letters), (1, 1));
+        assert_eq!(backlog[0].attempts, 0);
+        Ok(())
+    }
+}
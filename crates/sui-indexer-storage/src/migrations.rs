@@ -1,17 +1,293 @@
 /// Database migrations using sqlx migrate functionality
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use eyre::Result;
+use serde::Serialize;
+use sqlx::{
+    migrate::{Migrate, Migration, Migrator},
+    PgConnection, PgPool, Row,
+};
+use sui_indexer_config::StorageConfig;
 use tracing::info;
 
-/// Run database migrations using sqlx migrate
-pub async fn run_migrations(pool: &sqlx::PgPool) -> Result<()> {
-    info!("Running database migrations using sqlx migrate");
+use crate::{ids, partitions, SchemaDoc, StorageError};
 
-    // Run all pending migrations from the migrations directory within this crate
-    sqlx::migrate!("./migrations")
-        .run(pool)
-        .await
-        .map_err(|e| eyre::eyre!("Failed to run migrations: {}", e))?;
+/// Migrations embedded from the migrations directory within this crate
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Description of the embedded migrations, kept equal to [`describe_schema`] by a test
+const SCHEMA_JSON: &str = include_str!("../schema.json");
+
+/// State of one known migration in the database
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    /// Migration version, the timestamp prefix of its file name
+    pub version: i64,
+    /// Migration description
+    pub description: String,
+    /// When the migration was applied, if it was
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Whether the migration was left half-applied by a failure
+    pub dirty: bool,
+}
 
+/// Run database migrations using sqlx migrate
+///
+/// When partitioning is configured and the events table does not exist yet, it is
+/// created as a partitioned table first; the migrations then leave it in place.
+pub async fn run_migrations(pool: &PgPool, storage: &StorageConfig) -> Result<()> {
+    info!("Running database migrations using sqlx migrate");
+    migrate_up(pool, storage, None).await?;
     info!("Database migrations completed successfully");
     Ok(())
 }
+
+/// Every known migration with whether and when it was applied
+pub async fn migration_status(pool: &PgPool) -> Result<Vec<MigrationStatus>> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    let mut applied: HashMap<i64, (DateTime<Utc>, bool)> = HashMap::new();
+    if exists {
+        for row in sqlx::query("SELECT version, installed_on, success FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await?
+        {
+            let version: i64 = row.get("version");
+            applied.insert(
+                version,
+                (row.get("installed_on"), !row.get::<bool, _>("success")),
+            );
+        }
+    }
+
+    Ok(up_migrations()
+        .map(|migration| {
+            let (applied_at, dirty) = applied.get(&migration.version).copied().unzip();
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied_at,
+                dirty: dirty.unwrap_or(false),
+            }
+        })
+        .collect())
+}
+
+/// Apply pending migrations up to and including `target` (all if unset)
+///
+/// Rows still holding random ids are given derived ones under the same lock.
+/// Returns the versions applied, in order.
+pub async fn migrate_up(
+    pool: &PgPool,
+    storage: &StorageConfig,
+    target: Option<i64>,
+) -> Result<Vec<i64>> {
+    partitions::create_partitioned_table(pool, storage.partitioning).await?;
+
+    let mut conn = pool.acquire().await?;
+    // The lock is held by the session, so release it even if a migration fails
+    conn.lock().await?;
+    let result = match apply_up(&mut conn, target).await {
+        Ok(versions) => ids::rewrite_ids(&mut conn).await.map(|()| versions),
+        Err(e) => Err(e),
+    };
+    conn.unlock().await?;
+    let versions = result?;
+
+    partitions::ensure_partitions(pool, storage.partitioning, Utc::now()).await?;
+    Ok(versions)
+}
+
+/// Revert applied migrations newer than `target`, or only the latest one if unset
+///
+/// Returns the versions reverted, newest first.
+pub async fn migrate_down(pool: &PgPool, target: Option<i64>) -> Result<Vec<i64>> {
+    let mut conn = pool.acquire().await?;
+    conn.lock().await?;
+    let result = apply_down(&mut conn, target).await;
+    conn.unlock().await?;
+    result
+}
+
+/// Pending migrations that `migrate_up` would apply
+pub async fn pending_migrations(pool: &PgPool) -> Result<Vec<i64>> {
+    Ok(migration_status(pool)
+        .await?
+        .into_iter()
+        .filter(|status| status.applied_at.is_none())
+        .map(|status| status.version)
+        .collect())
+}
+
+async fn apply_up(conn: &mut PgConnection, target: Option<i64>) -> Result<Vec<i64>> {
+    let applied = applied_versions(conn).await?;
+
+    let mut versions = Vec::new();
+    for migration in up_migrations().filter(|m| target.is_none_or(|target| m.version <= target)) {
+        match applied.get(&migration.version) {
+            Some(checksum) if *checksum != *migration.checksum => {
+                return Err(StorageError::ChangedMigration(migration.version).into());
+            }
+            Some(_) => {}
+            None => {
+                info!(version = migration.version, description = %migration.description, "Applying migration");
+                conn.apply(migration).await?;
+                versions.push(migration.version);
+            }
+        }
+    }
+    Ok(versions)
+}
+
+async fn apply_down(conn: &mut PgConnection, target: Option<i64>) -> Result<Vec<i64>> {
+    let applied = applied_versions(conn).await?;
+    let target = match target {
+        Some(target) => target,
+        // Everything below the newest applied migration stays in place
+        None => applied.keys().max().map_or(i64::MAX, |latest| latest - 1),
+    };
+
+    let mut versions = Vec::new();
+    for migration in MIGRATOR
+        .iter()
+        .rev()
+        .filter(|m| m.migration_type.is_down_migration())
+        .filter(|m| m.version > target && applied.contains_key(&m.version))
+    {
+        info!(version = migration.version, description = %migration.description, "Reverting migration");
+        conn.revert(migration).await?;
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+/// Tables created by the embedded migrations, in the order they are created
+pub fn managed_tables() -> Vec<String> {
+    let mut tables: Vec<String> = Vec::new();
+    for migration in up_migrations() {
+        let words: Vec<String> = migration
+            .sql
+            .lines()
+            .map(|line| line.split("--").next().unwrap_or_default())
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == '(' || c == ';'))
+            .filter(|word| !word.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect();
+        for (index, pair) in words.windows(2).enumerate() {
+            if pair != ["create", "table"] {
+                continue;
+            }
+            let name = match words.get(index + 2..index + 5) {
+                Some([a, b, c]) if a == "if" && b == "not" && c == "exists" => words.get(index + 5),
+                _ => words.get(index + 2),
+            };
+            if let Some(name) = name {
+                if !tables.contains(name) {
+                    tables.push(name.clone());
+                }
+            }
+        }
+    }
+    tables
+}
+
+/// Tables, columns and indexes the embedded migrations create, read without a database
+pub fn schema_doc() -> Result<SchemaDoc> {
+    Ok(serde_json::from_str(SCHEMA_JSON)?)
+}
+
+/// Describe the embedded migrations from the catalog of `pool`'s database
+///
+/// The migrations are applied one at a time to a scratch schema, described
+/// from the catalog after each, and rolled back. `schema.json` holds the
+/// result, so [`schema_doc`] needs no database.
+pub async fn describe_schema(pool: &PgPool) -> Result<SchemaDoc> {
+    let mut tx = pool.begin().await?;
+    let schema = format!("schema_doc_{}", std::process::id());
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!("SET LOCAL search_path TO {schema}"))
+        .execute(&mut *tx)
+        .await?;
+
+    let mut doc = SchemaDoc::default();
+    for migration in up_migrations() {
+        sqlx::raw_sql(&migration.sql).execute(&mut *tx).await?;
+        doc.record(&mut tx, migration.version).await?;
+    }
+    tx.rollback().await?;
+    Ok(doc)
+}
+
+fn up_migrations() -> impl Iterator<Item = &'static Migration> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+}
+
+/// Checksums of applied migrations by version, refusing to continue past a failed one
+async fn applied_versions(conn: &mut PgConnection) -> Result<HashMap<i64, Vec<u8>>> {
+    conn.ensure_migrations_table().await?;
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(StorageError::DirtyMigration(version).into());
+    }
+
+    Ok(conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|applied| (applied.version, applied.checksum.into_owned()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_managed_tables_are_read_from_the_migrations() {
+        let tables = managed_tables();
+        assert_eq!(tables[..2], ["checkpoint_progress", "transactions"]);
+        for table in [
+            "processed_events",
+            "indexer_state",
+            "audit_log",
+            "object_versions",
+        ] {
+            assert!(tables.iter().any(|t| t == table), "{table} is managed");
+        }
+    }
+
+    #[test]
+    fn test_every_migration_can_be_reverted() {
+        for up in up_migrations() {
+            assert!(
+                MIGRATOR
+                    .iter()
+                    .any(|m| m.version == up.version && m.migration_type.is_down_migration()),
+                "migration {} has no down migration",
+                up.version
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_doc_describes_the_latest_migration() -> Result<()> {
+        let doc = schema_doc()?;
+        let latest = up_migrations().map(|migration| migration.version).max();
+        assert_eq!(
+            Some(doc.version),
+            latest,
+            "schema.json is outdated, regenerate it as the migrations README describes"
+        );
+
+        let mut managed = managed_tables();
+        managed.sort();
+        let tables: Vec<_> = doc.tables.iter().map(|table| table.name.clone()).collect();
+        assert_eq!(tables, managed);
+        Ok(())
+    }
+}
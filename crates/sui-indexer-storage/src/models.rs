@@ -44,6 +44,193 @@ pub struct IndexerStateModel {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Current position of a named checkpoint cursor
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CursorRecord {
+    pub pipeline: String,
+    pub checkpoint_sequence: i64,
+    pub updated_at: Option<chrono::NaiveDateTime>,
+}
+
+/// An operator action to record in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Who acted, such as the OS user of a CLI command
+    pub actor: String,
+    /// Where the action came from: `cli`, `admin_api` or `indexer`
+    pub source: String,
+    /// What was done, such as `cursor_set` or `prune`
+    pub action: String,
+    /// Parameters and outcome of the action
+    pub details: serde_json::Value,
+}
+
+/// An operator action recorded in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub actor: String,
+    pub source: String,
+    pub action: String,
+    pub details: serde_json::Value,
+}
+
+/// An API key stored in the `api_keys` table; only its hash is kept
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKeyRecord {
+    pub name: String,
+    /// Hex-encoded SHA-256 of the key
+    pub key_hash: String,
+    /// `read` or `admin`
+    pub scope: String,
+    /// Requests per second, unlimited if unset
+    pub rate_limit: Option<f64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Outcome of a webhook request that needed retries or was given up on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: uuid::Uuid,
+    pub sink: String,
+    pub url: String,
+    /// `delivered` or `failed`
+    pub status: String,
+    pub attempts: i32,
+    pub events: i32,
+    pub first_checkpoint: i64,
+    pub last_checkpoint: i64,
+    /// HTTP status of the last attempt, if the endpoint answered
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    /// Request body, kept for failed deliveries
+    pub payload: Option<serde_json::Value>,
+}
+
+/// A webhook delivery recorded in the `webhook_deliveries` table
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookDeliveryRecord {
+    pub id: uuid::Uuid,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub sink: String,
+    pub url: String,
+    pub status: String,
+    pub attempts: i32,
+    pub events: i32,
+    pub first_checkpoint: i64,
+    pub last_checkpoint: i64,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Message for a sink, written to the outbox with the event it carries
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub sink: String,
+    pub event: sui_indexer_events::ProcessedEvent,
+}
+
+/// A message waiting in the `outbox` table
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OutboxRecord {
+    pub id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub sink: String,
+    pub checkpoint_sequence: i64,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    /// When the message was given up on; dead letters are no longer sent
+    pub dead_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Messages waiting in the outbox for one sink
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OutboxBacklog {
+    pub sink: String,
+    /// Messages still to be delivered
+    pub messages: i64,
+    /// Messages given up on after `outbox.max_attempts` failed deliveries
+    pub dead_letters: i64,
+    pub oldest: chrono::DateTime<chrono::Utc>,
+    /// Failed attempts of the oldest message
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+/// Processing report of one committed checkpoint, kept in the `checkpoint_reports` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct CheckpointReport {
+    /// Pipeline that processed the checkpoint
+    pub pipeline: String,
+    pub checkpoint_sequence: i64,
+    pub transactions: i64,
+    /// Events in the checkpoint
+    pub events_seen: i64,
+    /// Events matching a filter, or every event when none is configured
+    pub events_matched: i64,
+    /// Events no filter matched
+    pub events_skipped: i64,
+    /// Processed events written to storage
+    pub events_stored: i64,
+    /// Matched events by filter name, empty when no filter is configured
+    pub matched_by_filter: serde_json::Value,
+    pub fetch_ms: i64,
+    pub transform_ms: i64,
+    pub store_ms: i64,
+    pub deliver_ms: i64,
+    pub processed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One version of a published Move package, kept in the `packages` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct PackageRecord {
+    pub package_id: String,
+    /// ID of the package's first version, shared by all its upgrades
+    pub original_id: String,
+    pub version: i64,
+    /// Names of the package's modules
+    pub modules: Vec<String>,
+    /// Transaction that published or upgraded the package
+    pub tx_digest: String,
+    pub sender: String,
+    pub checkpoint_sequence: i64,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Rows removed, or that would be removed, by a retention run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruneReport {
+    /// Event partitions dropped as a whole
+    pub partitions: Vec<String>,
+    /// Event rows removed, including those in dropped partitions
+    pub events: u64,
+    /// Transaction rows removed
+    pub transactions: u64,
+}
+
+/// Connections held by a storage backend's pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    /// Connections currently open, idle or in use
+    pub open: u32,
+    /// Open connections not in use
+    pub idle: u32,
+    /// Connections the pool may open, 0 if unbounded or unknown
+    #[serde(default)]
+    pub max: u32,
+}
+
+impl ConnectionStats {
+    /// Open connections currently in use
+    pub fn in_use(&self) -> u32 {
+        self.open.saturating_sub(self.idle)
+    }
+}
+
 /// Processed events tracking to avoid reprocessing
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ProcessedEventModel {
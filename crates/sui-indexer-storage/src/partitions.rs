@@ -0,0 +1,245 @@
+/// Time-range partition management for the processed events table
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use eyre::Result;
+use sqlx::{PgPool, Row};
+use sui_indexer_config::PartitionStrategy;
+use tracing::{info, warn};
+
+/// Table partitioned when a partitioning strategy is configured
+pub const PARTITIONED_TABLE: &str = "processed_events";
+
+/// A time-range partition of the processed events table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    /// Partition table name
+    pub name: String,
+    /// Inclusive lower bound
+    pub start: NaiveDate,
+    /// Exclusive upper bound
+    pub end: NaiveDate,
+}
+
+impl Partition {
+    /// Partition containing `date`, or `None` if partitioning is disabled
+    pub fn containing(strategy: PartitionStrategy, date: NaiveDate) -> Option<Self> {
+        let (start, end, suffix) = match strategy {
+            PartitionStrategy::None => return None,
+            PartitionStrategy::Daily => (
+                date,
+                date.checked_add_days(Days::new(1))?,
+                date.format("%Y%m%d").to_string(),
+            ),
+            PartitionStrategy::Monthly => {
+                let start = date.with_day(1)?;
+                (
+                    start,
+                    start.checked_add_months(Months::new(1))?,
+                    start.format("%Y%m").to_string(),
+                )
+            }
+        };
+
+        Some(Self {
+            name: format!("{PARTITIONED_TABLE}_p{suffix}"),
+            start,
+            end,
+        })
+    }
+
+    /// Parse a partition back from its table name
+    pub fn from_name(strategy: PartitionStrategy, name: &str) -> Option<Self> {
+        let suffix = name.strip_prefix(PARTITIONED_TABLE)?.strip_prefix("_p")?;
+        let date = match strategy {
+            PartitionStrategy::None => return None,
+            PartitionStrategy::Daily => NaiveDate::parse_from_str(suffix, "%Y%m%d").ok()?,
+            PartitionStrategy::Monthly => {
+                NaiveDate::parse_from_str(&format!("{suffix}01"), "%Y%m%d").ok()?
+            }
+        };
+
+        Self::containing(strategy, date).filter(|partition| partition.name == name)
+    }
+}
+
+/// Create the processed events table as a partitioned table if it does not exist yet
+///
+/// Existing tables are left untouched, since Postgres cannot partition a table in place.
+pub async fn create_partitioned_table(pool: &PgPool, strategy: PartitionStrategy) -> Result<()> {
+    if strategy == PartitionStrategy::None {
+        return Ok(());
+    }
+
+    let exists: bool = sqlx::query("SELECT to_regclass($1) IS NOT NULL AS exists")
+        .bind(PARTITIONED_TABLE)
+        .fetch_one(pool)
+        .await?
+        .get("exists");
+
+    if exists {
+        if !is_partitioned(pool).await? {
+            warn!(
+                table = PARTITIONED_TABLE,
+                "Table already exists unpartitioned, ignoring storage.partitioning"
+            );
+        }
+        return Ok(());
+    }
+
+    info!(
+        table = PARTITIONED_TABLE,
+        ?strategy,
+        "Creating partitioned table"
+    );
+    sqlx::query(
+        "CREATE TABLE processed_events (
+            id UUID NOT NULL,
+            event_data JSONB,
+            transaction_digest TEXT NOT NULL,
+            checkpoint_sequence BIGINT NOT NULL,
+            timestamp TIMESTAMP NOT NULL,
+            package_id TEXT NOT NULL,
+            module_name TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            sender TEXT NOT NULL,
+            fields JSONB NOT NULL,
+            metadata JSONB NOT NULL,
+            processed_at TIMESTAMP NOT NULL,
+            created_at TIMESTAMP DEFAULT NOW(),
+            PRIMARY KEY (id, timestamp)
+        ) PARTITION BY RANGE (timestamp)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Catch-all so inserts never fail if partition maintenance falls behind
+    sqlx::query("CREATE TABLE processed_events_default PARTITION OF processed_events DEFAULT")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Check if the processed events table is partitioned
+pub async fn is_partitioned(pool: &PgPool) -> Result<bool> {
+    let row = sqlx::query(
+        "SELECT EXISTS (
+            SELECT 1 FROM pg_partitioned_table WHERE partrelid = to_regclass($1)
+        ) AS partitioned",
+    )
+    .bind(PARTITIONED_TABLE)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("partitioned"))
+}
+
+/// Create the current and next partitions so inserts never hit the default partition
+pub async fn ensure_partitions(
+    pool: &PgPool,
+    strategy: PartitionStrategy,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let Some(current) = Partition::containing(strategy, now.date_naive()) else {
+        return Ok(());
+    };
+    if !is_partitioned(pool).await? {
+        return Ok(());
+    }
+
+    let next = Partition::containing(strategy, current.end);
+    for partition in std::iter::once(current).chain(next) {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} PARTITION OF {PARTITIONED_TABLE}
+             FOR VALUES FROM ('{}') TO ('{}')",
+            partition.name, partition.start, partition.end
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Partitions that lie entirely before `cutoff`
+pub async fn expired_partitions(
+    pool: &PgPool,
+    strategy: PartitionStrategy,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<Partition>> {
+    if strategy == PartitionStrategy::None || !is_partitioned(pool).await? {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query(
+        "SELECT child.relname AS name
+         FROM pg_inherits
+         JOIN pg_class child ON child.oid = pg_inherits.inhrelid
+         WHERE pg_inherits.inhparent = to_regclass($1)",
+    )
+    .bind(PARTITIONED_TABLE)
+    .fetch_all(pool)
+    .await?;
+
+    let mut expired: Vec<_> = rows
+        .iter()
+        .filter_map(|row| Partition::from_name(strategy, row.get("name")))
+        .filter(|partition| partition.end <= cutoff.date_naive())
+        .collect();
+    expired.sort_by_key(|partition| partition.start);
+    Ok(expired)
+}
+
+/// Number of rows stored in a partition
+pub async fn count_rows(pool: &PgPool, partition: &Partition) -> Result<u64> {
+    let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", partition.name))
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+/// Drop a partition together with its rows
+pub async fn drop_partition(pool: &PgPool, partition: &Partition) -> Result<()> {
+    info!(partition = %partition.name, "Dropping expired partition");
+    sqlx::query(&format!("DROP TABLE IF EXISTS {}", partition.name))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_partition_bounds() {
+        let daily = Partition::containing(PartitionStrategy::Daily, date("2025-02-28")).unwrap();
+        assert_eq!(daily.name, "processed_events_p20250228");
+        assert_eq!(daily.end, date("2025-03-01"));
+
+        let monthly =
+            Partition::containing(PartitionStrategy::Monthly, date("2025-12-15")).unwrap();
+        assert_eq!(monthly.name, "processed_events_p202512");
+        assert_eq!(monthly.start, date("2025-12-01"));
+        assert_eq!(monthly.end, date("2026-01-01"));
+
+        assert!(Partition::containing(PartitionStrategy::None, date("2025-12-15")).is_none());
+    }
+
+    #[test]
+    fn test_partition_from_name() {
+        let partition =
+            Partition::from_name(PartitionStrategy::Monthly, "processed_events_p202509").unwrap();
+        assert_eq!(partition.start, date("2025-09-01"));
+
+        assert!(
+            Partition::from_name(PartitionStrategy::Monthly, "processed_events_default").is_none()
+        );
+        assert!(
+            Partition::from_name(PartitionStrategy::Daily, "processed_events_p202509").is_none()
+        );
+    }
+}
@@ -1,64 +1,147 @@
 /// PostgreSQL storage backend implementation
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
-use eyre::Result;
-use sqlx::{PgPool, Row};
-use sui_indexer_config::DatabaseConfig;
-use sui_indexer_events::{ProcessedEvent, ProcessedTransaction};
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions, PgRow, PgSslMode},
+    FromRow, PgExecutor, PgPool, Postgres, QueryBuilder, Row,
+};
+use sui_indexer_config::{ApiScope, DatabaseConfig, SslMode, StorageConfig};
+use sui_indexer_events::{
+    is_typed_table_name, ProcessedEvent, ProcessedTransaction, TypedRow, TypedTable,
+    TYPED_TABLE_PREFIX,
+};
+use sui_json_rpc_types::BcsEvent;
 use tracing::{error, info};
 
-use crate::Storage;
+use crate::{
+    migrations::{self, MigrationStatus},
+    partitions, query, snapshot, ApiKeyRecord, AuditEntry, AuditRecord, CheckpointReport,
+    CheckpointTotals, ConnectionStats, CursorRecord, EventQuery, EventRecord, IndexStats,
+    MoveCallQuery, MoveCallRecord, ObjectVersionQuery, ObjectVersionRecord, OutboxBacklog,
+    OutboxEntry, OutboxRecord, PackageRecord, PageCursor, PruneReport, RawEventRecord,
+    SnapshotManifest, Storage, StorageError, TransactionQuery, TransactionRecord, TypedRows,
+    WebhookDelivery, WebhookDeliveryRecord, DEFAULT_PIPELINE,
+};
+
+/// Rows read per statement by the filtered exports
+const EXPORT_PAGE: u32 = 10_000;
 
 /// PostgreSQL storage implementation
 pub struct PostgresStorage {
     pool: PgPool,
+    storage: StorageConfig,
+    auto_migrate: bool,
+    /// Chain identifier written with every cursor row once recorded
+    chain_id: OnceLock<String>,
 }
 
 impl PostgresStorage {
     /// Create a new PostgreSQL storage backend
-    pub async fn new(config: DatabaseConfig) -> Result<Self> {
-        let pool = PgPool::connect(&config.url).await?;
+    pub async fn new(config: DatabaseConfig, storage: StorageConfig) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(Duration::from_secs(config.connect_timeout))
+            .idle_timeout(config.idle_timeout.map(Duration::from_secs))
+            .connect_with(connect_options(&config)?)
+            .await?;
+        if let Some(schema) = &config.schema {
+            // Tables are created in the first schema of the search path, which must exist
+            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {schema}"))
+                .execute(&pool)
+                .await
+                .wrap_err_with(|| format!("Failed to create schema {schema}"))?;
+        }
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            storage,
+            auto_migrate: config.auto_migrate,
+            chain_id: OnceLock::new(),
+        })
     }
 
-    /// Get the database pool
-    pub fn pool(&self) -> &PgPool {
-        &self.pool
+    /// Every known migration with whether and when it was applied
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        migrations::migration_status(&self.pool).await
     }
-}
 
-#[async_trait]
-impl Storage for PostgresStorage {
-    async fn initialize(&self) -> Result<()> {
-        info!("Initializing PostgreSQL storage backend");
+    /// Apply pending migrations up to and including `target` (all if unset)
+    pub async fn migrate_up(&self, target: Option<i64>) -> Result<Vec<i64>> {
+        migrations::migrate_up(&self.pool, &self.storage, target).await
+    }
 
-        // Run migrations to create tables
-        crate::migrations::run_migrations(&self.pool).await?;
+    /// Revert migrations newer than `target`, or only the latest one if unset
+    pub async fn migrate_down(&self, target: Option<i64>) -> Result<Vec<i64>> {
+        migrations::migrate_down(&self.pool, target).await
+    }
 
-        Ok(())
+    /// Write a consistent snapshot of the indexed data and cursors to `dir`
+    pub async fn create_snapshot(&self, dir: &Path) -> Result<SnapshotManifest> {
+        snapshot::create_snapshot(&self.pool, dir).await
+    }
+
+    /// Load the snapshot in `dir` into this database, which must be empty
+    pub async fn restore_snapshot(&self, dir: &Path) -> Result<SnapshotManifest> {
+        snapshot::restore_snapshot(&self.pool, &self.storage, dir).await
+    }
+
+    /// On-disk size in bytes of the whole database
+    pub async fn database_size(&self) -> Result<u64> {
+        let size: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(size as u64)
+    }
+
+    /// On-disk size in bytes of a table with its indexes and partitions, zero if absent
+    pub async fn table_size(&self, table: &str) -> Result<u64> {
+        let size: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(pg_total_relation_size(relid)), 0)::BIGINT
+             FROM pg_partition_tree(to_regclass($1))",
+        )
+        .bind(table)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(size as u64)
     }
 
-    async fn store_events(&self, events: Vec<ProcessedEvent>) -> Result<()> {
+    /// Batch insert events into `table`, which has the events table's columns
+    async fn insert_events<'c>(
+        &self,
+        executor: impl PgExecutor<'c>,
+        table: &str,
+        events: &[ProcessedEvent],
+    ) -> Result<()> {
         info!("Storing {} events", events.len());
 
         if events.is_empty() {
             return Ok(());
         }
 
-        // Batch insert events
-        let mut query_builder = sqlx::QueryBuilder::new(
-            "INSERT INTO processed_events (
+        // Events of the live table are also counted per checkpoint, in the same statement
+        let counted = table == "processed_events";
+        let mut query_builder = sqlx::QueryBuilder::new(format!(
+            "{}INSERT INTO {table} (
                 id, event_data, transaction_digest, checkpoint_sequence,
                 timestamp, package_id, module_name, event_type,
                 sender, fields, metadata, processed_at
             ) ",
-        );
+            if counted { "WITH inserted AS (" } else { "" }
+        ));
 
         query_builder.push_values(events, |mut b, event| {
             b.push_bind(event.id)
-                .push_bind(
-                    serde_json::to_value(&event.event).expect("Event should serialize to JSON"),
-                )
+                .push_bind(self.raw_event(event))
                 .push_bind(event.transaction_digest.to_string())
                 .push_bind(event.checkpoint_sequence as i64)
                 .push_bind(event.timestamp)
@@ -74,12 +157,349 @@ impl Storage for PostgresStorage {
                 .push_bind(event.metadata.processed_at);
         });
 
+        // Ids are derived from the event, so an event stored again is skipped
+        query_builder.push(" ON CONFLICT DO NOTHING");
+        if counted {
+            // Only the events actually inserted are counted
+            query_builder.push(
+                " RETURNING checkpoint_sequence, package_id, event_type)
+                INSERT INTO event_counts (checkpoint_sequence, package_id, event_type, events)
+                SELECT checkpoint_sequence, package_id, event_type, COUNT(*) FROM inserted
+                GROUP BY checkpoint_sequence, package_id, event_type
+                ON CONFLICT (checkpoint_sequence, package_id, event_type)
+                DO UPDATE SET events = event_counts.events + EXCLUDED.events",
+            );
+        }
         let query = query_builder.build();
-        query.execute(&self.pool).await?;
+        query.execute(executor).await?;
 
         Ok(())
     }
 
+    /// Raw event JSON to store for an event, honoring the storage toggles
+    fn raw_event(&self, event: &ProcessedEvent) -> Option<serde_json::Value> {
+        if !self.storage.store_raw_event {
+            return None;
+        }
+
+        let raw = if self.storage.store_bcs {
+            serde_json::to_value(&*event.event)
+        } else {
+            // Only copy the event when its BCS bytes have to be left out
+            let mut raw = (*event.event).clone();
+            raw.bcs = BcsEvent::new(Vec::new());
+            serde_json::to_value(&raw)
+        };
+        Some(raw.expect("Event should serialize to JSON"))
+    }
+
+    /// Insert the rows decoded for a typed event table, skipping ones already stored
+    async fn insert_typed_rows<'c>(
+        &self,
+        executor: impl PgExecutor<'c>,
+        table: &TypedTable,
+        rows: &[TypedRow],
+    ) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        table.validate()?;
+        let records: Vec<serde_json::Value> = rows
+            .iter()
+            .map(|row| {
+                let mut record = row.values.clone();
+                record.insert("id".to_string(), row.id.to_string().into());
+                record.insert(
+                    "checkpoint_sequence".to_string(),
+                    row.checkpoint_sequence.into(),
+                );
+                record.insert(
+                    "transaction_digest".to_string(),
+                    row.transaction_digest.clone().into(),
+                );
+                record.insert("timestamp".to_string(), row.timestamp.to_rfc3339().into());
+                record.into()
+            })
+            .collect();
+        // Postgres casts each value to the type of the column of the same name
+        let name = &table.name;
+        sqlx::query(&format!(
+            "INSERT INTO {name}
+             SELECT * FROM jsonb_populate_recordset(NULL::{name}, $1)
+             ON CONFLICT (id) DO NOTHING"
+        ))
+        .bind(serde_json::Value::Array(records))
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Store events and their typed rows in the open transaction `tx`
+    async fn insert_events_with_rows(
+        &self,
+        tx: &mut sqlx::PgConnection,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<()> {
+        for chunk in events.chunks(COMMIT_CHUNK) {
+            self.insert_events(&mut *tx, "processed_events", chunk)
+                .await?;
+        }
+        for (table, rows) in typed {
+            for chunk in rows.chunks(COMMIT_CHUNK) {
+                self.insert_typed_rows(&mut *tx, table, chunk).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the database pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+/// Rows per insert statement of a transactional commit, well below the bind parameter limit
+const COMMIT_CHUNK: usize = 1000;
+
+/// Typed event tables in the current schema, all named with [`TYPED_TABLE_PREFIX`]
+///
+/// Includes the tables of typed events no longer registered, so their rows
+/// are cleaned up along with the events they were decoded from.
+pub(crate) async fn typed_tables<'c>(executor: impl PgExecutor<'c>) -> Result<Vec<String>> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT tablename::TEXT FROM pg_tables
+         WHERE schemaname = current_schema() AND starts_with(tablename, $1)",
+    )
+    .bind(TYPED_TABLE_PREFIX)
+    .fetch_all(executor)
+    .await?;
+    Ok(tables
+        .into_iter()
+        .filter(|table| is_typed_table_name(table))
+        .collect())
+}
+
+/// Record a cursor position, indexed from the chain `chain_id` if known
+async fn insert_cursor<'c>(
+    executor: impl PgExecutor<'c>,
+    pipeline: &str,
+    checkpoint: u64,
+    chain_id: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO indexer_state (pipeline, checkpoint_sequence, chain_id, updated_at)
+         VALUES ($1, $2, $3, NOW())",
+    )
+    .bind(pipeline)
+    .bind(checkpoint as i64)
+    .bind(chain_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Prefixes of the tables replays and benchmarks may write to and clear
+pub const OUTPUT_TABLE_PREFIXES: &[&str] = &["replay_", "benchmark_"];
+
+/// Check a table name given for replay or benchmark output, which is interpolated into SQL
+pub(crate) fn output_table(table: &str) -> Result<&str> {
+    if !is_identifier(table) {
+        return Err(StorageError::InvalidTable(table.to_string()).into());
+    }
+    // Rows of the output table are deleted, so it may never be one the indexer manages
+    if !OUTPUT_TABLE_PREFIXES
+        .iter()
+        .any(|prefix| table.starts_with(prefix))
+        || migrations::managed_tables()
+            .iter()
+            .any(|managed| managed == table)
+    {
+        return Err(StorageError::LiveTable(table.to_string()).into());
+    }
+    Ok(table)
+}
+
+/// Check if `name` can be used unquoted in a statement
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Stream the rows `sql` selects a page at a time, each page starting after the last row of the one before
+///
+/// Reading by pages holds no connection between them, so a slow reader
+/// does not keep one from the pool.
+fn export_pages<'a, R>(
+    pool: &'a PgPool,
+    sql: impl Fn(Option<PageCursor>) -> QueryBuilder<'static, Postgres> + Send + Sync + 'a,
+    cursor: fn(&R) -> PageCursor,
+) -> BoxStream<'a, Result<R>>
+where
+    R: for<'r> FromRow<'r, PgRow> + Send + Unpin + 'a,
+{
+    let sql = Arc::new(sql);
+    // `None` once the last page was read
+    futures::stream::try_unfold(Some(None), move |after| {
+        let sql = sql.clone();
+        async move {
+            let Some(after) = after else {
+                return Ok(None);
+            };
+            let rows: Vec<R> = sql(after).build_query_as().fetch_all(pool).await?;
+            let next = PageCursor::next_page(&rows, EXPORT_PAGE, cursor).map(Some);
+            Ok(Some((
+                futures::stream::iter(rows.into_iter().map(Ok)),
+                next,
+            )))
+        }
+    })
+    .try_flatten()
+    .boxed()
+}
+
+/// Assemble connection options from the URL and the structured database settings
+///
+/// Structured settings override the matching parts of the URL; anything left
+/// unset falls back to the standard `PG*` environment variables.
+pub fn connect_options(config: &DatabaseConfig) -> Result<PgConnectOptions> {
+    let mut options = match &config.url {
+        Some(url) => PgConnectOptions::from_str(url).wrap_err("Invalid database.url")?,
+        None => PgConnectOptions::new(),
+    };
+
+    if let Some(host) = &config.host {
+        options = options.host(host);
+    }
+    if let Some(port) = config.port {
+        options = options.port(port);
+    }
+    if let Some(user) = &config.user {
+        options = options.username(user);
+    }
+    if let Some(password) = config.password()? {
+        options = options.password(&password);
+    }
+    if let Some(dbname) = &config.dbname {
+        options = options.database(dbname);
+    }
+    if let Some(sslmode) = config.sslmode {
+        options = options.ssl_mode(match sslmode {
+            SslMode::Disable => PgSslMode::Disable,
+            SslMode::Allow => PgSslMode::Allow,
+            SslMode::Prefer => PgSslMode::Prefer,
+            SslMode::Require => PgSslMode::Require,
+            SslMode::VerifyCa => PgSslMode::VerifyCa,
+            SslMode::VerifyFull => PgSslMode::VerifyFull,
+        });
+    }
+    if let Some(timeout) = config.statement_timeout {
+        options = options.options([("statement_timeout", format!("{timeout}s"))]);
+    }
+    // TIMESTAMP columns hold UTC, whatever zone the server, role or URL defaults to
+    options = options.options([("TimeZone", "UTC")]);
+    if let Some(schema) = &config.schema {
+        if !is_identifier(schema) {
+            return Err(StorageError::InvalidSchema(schema.clone()).into());
+        }
+        options = options.options([("search_path", schema.as_str())]);
+    }
+
+    Ok(options)
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        info!("Initializing PostgreSQL storage backend");
+
+        if self.auto_migrate {
+            return migrations::run_migrations(&self.pool, &self.storage).await;
+        }
+
+        let pending = migrations::pending_migrations(&self.pool).await?;
+        if !pending.is_empty() {
+            return Err(StorageError::PendingMigrations(pending.len()).into());
+        }
+        partitions::ensure_partitions(&self.pool, self.storage.partitioning, Utc::now()).await
+    }
+
+    async fn store_events_with_rows(
+        &self,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<()> {
+        if typed.iter().all(|(_, rows)| rows.is_empty()) {
+            return self
+                .insert_events(&self.pool, "processed_events", events)
+                .await;
+        }
+        let mut tx = self.pool.begin().await?;
+        self.insert_events_with_rows(&mut tx, events, typed).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn store_events_in(&self, table: &str, events: &[ProcessedEvent]) -> Result<()> {
+        self.insert_events(&self.pool, output_table(table)?, events)
+            .await
+    }
+
+    async fn reset_event_table(&self, table: &str, start: u64, end: u64) -> Result<()> {
+        let table = output_table(table)?;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (LIKE processed_events INCLUDING DEFAULTS)"
+        ))
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(&format!(
+            "DELETE FROM {table} WHERE checkpoint_sequence BETWEEN $1 AND $2"
+        ))
+        .bind(start as i64)
+        .bind(end as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn migrate_typed_table(&self, table: &TypedTable) -> Result<()> {
+        table.validate()?;
+        let name = &table.name;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {name} (
+                id UUID PRIMARY KEY,
+                checkpoint_sequence BIGINT NOT NULL,
+                transaction_digest TEXT NOT NULL,
+                timestamp TIMESTAMPTZ NOT NULL
+            )"
+        ))
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {name}_checkpoint_idx ON {name} (checkpoint_sequence)"
+        ))
+        .execute(&self.pool)
+        .await?;
+        // Fields added to the type become new columns; removed ones are left in place
+        for column in &table.columns {
+            sqlx::query(&format!(
+                "ALTER TABLE {name} ADD COLUMN IF NOT EXISTS {} {}",
+                column.name,
+                column.column_type.sql()
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
     async fn store_transactions(&self, transactions: Vec<ProcessedTransaction>) -> Result<()> {
         info!("Storing {} transactions", transactions.len());
 
@@ -96,7 +516,11 @@ impl Storage for PostgresStorage {
             ) ",
         );
 
-        query_builder.push_values(transactions, |mut b, tx| {
+        let store_effects = self.storage.store_transactions_effects;
+        query_builder.push_values(transactions, |mut b, mut tx| {
+            if !store_effects {
+                tx.transaction.effects = None;
+            }
             b.push_bind(tx.id)
                 .push_bind(
                     serde_json::to_value(&tx.transaction)
@@ -108,16 +532,18 @@ impl Storage for PostgresStorage {
                 .push_bind("0x0".to_string()) // Placeholder for sender - would need proper extraction
                 .push_bind(tx.metadata.gas_used.unwrap_or(0) as i64)
                 .push_bind(tx.metadata.success.to_string())
-                .push_bind(
-                    serde_json::to_value(&tx.transaction.effects)
-                        .expect("Transaction effects should serialize to JSON"),
-                )
+                .push_bind(tx.transaction.effects.as_ref().map(|effects| {
+                    serde_json::to_value(effects)
+                        .expect("Transaction effects should serialize to JSON")
+                }))
                 .push_bind(
                     serde_json::to_value(&tx.metadata)
                         .expect("Transaction metadata should serialize to JSON"),
                 )
                 .push_bind(tx.metadata.processed_at);
         });
+        // A checkpoint indexed again, or a commit retried, keeps the first copy
+        query_builder.push(" ON CONFLICT (digest) DO NOTHING");
 
         let query = query_builder.build();
         query.execute(&self.pool).await?;
@@ -147,19 +573,26 @@ impl Storage for PostgresStorage {
 
         let mut events = Vec::new();
         for row in rows {
+            let id: uuid::Uuid = row.get("id");
+            let event_data: Option<serde_json::Value> = row.get("event_data");
+            let event_data = event_data.ok_or(StorageError::MissingRawEvent(id))?;
             let event = ProcessedEvent {
-                id: row.get("id"),
-                event: serde_json::from_value(row.get("event_data"))?,
-                transaction_digest: row
-                    .get::<String, _>("transaction_digest")
-                    .parse()
-                    .map_err(|e| eyre::eyre!("Failed to parse transaction digest: {}", e))?,
+                id,
+                event: serde_json::from_value(event_data)?,
+                transaction_digest: row.get::<String, _>("transaction_digest").parse().map_err(
+                    |e| StorageError::InvalidRow {
+                        column: "transaction_digest",
+                        reason: format!("{e}"),
+                    },
+                )?,
                 checkpoint_sequence: row.get::<i64, _>("checkpoint_sequence") as u64,
                 timestamp: row.get("timestamp"),
-                package_id: row
-                    .get::<String, _>("package_id")
-                    .parse()
-                    .map_err(|e| eyre::eyre!("Failed to parse package ID: {}", e))?,
+                package_id: row.get::<String, _>("package_id").parse().map_err(|e| {
+                    StorageError::InvalidRow {
+                        column: "package_id",
+                        reason: format!("{e}"),
+                    }
+                })?,
                 module_name: row.get("module_name"),
                 event_type: row.get("event_type"),
                 sender: row.get("sender"),
@@ -172,35 +605,808 @@ impl Storage for PostgresStorage {
         Ok(events)
     }
 
-    async fn get_latest_checkpoint(&self) -> Result<Option<u64>> {
-        let row =
-            sqlx::query("SELECT checkpoint_sequence FROM indexer_state ORDER BY id DESC LIMIT 1")
-                .fetch_optional(&self.pool)
-                .await?;
+    async fn delete_events_by_checkpoint_range(&self, start: u64, end: u64) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let deleted = sqlx::query(
+            "DELETE FROM processed_events
+             WHERE checkpoint_sequence >= $1 AND checkpoint_sequence <= $2",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        // The range is indexed again, which counts its events anew
+        sqlx::query(
+            "DELETE FROM event_counts
+             WHERE checkpoint_sequence >= $1 AND checkpoint_sequence <= $2",
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .execute(&mut *tx)
+        .await?;
+        for table in typed_tables(&mut *tx).await? {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE checkpoint_sequence >= $1 AND checkpoint_sequence <= $2"
+            ))
+            .bind(start as i64)
+            .bind(end as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
 
-        if let Some(row) = row {
-            Ok(Some(row.get::<i64, _>("checkpoint_sequence") as u64))
-        } else {
-            Ok(None)
+        info!(
+            "Deleted {} events for checkpoint range {}-{}",
+            deleted, start, end
+        );
+        Ok(deleted)
+    }
+
+    async fn replace_events(
+        &self,
+        ids: &[uuid::Uuid],
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        // Removed events are taken off the per-checkpoint counts again
+        let deleted: i64 = sqlx::query_scalar(
+            "WITH deleted AS (
+                DELETE FROM processed_events WHERE id = ANY($1)
+                RETURNING checkpoint_sequence, package_id, event_type
+            ), removed AS (
+                SELECT checkpoint_sequence, package_id, event_type, COUNT(*) AS events
+                FROM deleted GROUP BY checkpoint_sequence, package_id, event_type
+            ), uncounted AS (
+                UPDATE event_counts SET events = event_counts.events - removed.events
+                FROM removed
+                WHERE event_counts.checkpoint_sequence = removed.checkpoint_sequence
+                  AND event_counts.package_id = removed.package_id
+                  AND event_counts.event_type = removed.event_type
+                RETURNING 1
+            )
+            SELECT COUNT(*) FROM deleted",
+        )
+        .bind(ids)
+        .fetch_one(&mut *tx)
+        .await?;
+        for table in typed_tables(&mut *tx).await? {
+            sqlx::query(&format!("DELETE FROM {table} WHERE id = ANY($1)"))
+                .bind(ids)
+                .execute(&mut *tx)
+                .await?;
         }
+        self.insert_events_with_rows(&mut tx, events, typed).await?;
+        tx.commit().await?;
+        Ok(deleted as u64)
+    }
+
+    async fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRecord>> {
+        let events = query
+            .to_sql()
+            .build_query_as::<EventRecord>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(events)
+    }
+
+    async fn query_transactions(&self, query: &TransactionQuery) -> Result<Vec<TransactionRecord>> {
+        let transactions = query
+            .to_sql()
+            .build_query_as::<TransactionRecord>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(transactions)
+    }
+
+    async fn stats_since(&self, since: DateTime<Utc>, top: u32) -> Result<IndexStats> {
+        // The timestamps are TIMESTAMPs holding UTC; a TIMESTAMPTZ bind would be shifted by the session zone
+        let start = since.naive_utc();
+        let events = sqlx::query(query::EVENT_TOTALS)
+            .bind(start)
+            .fetch_one(&self.pool)
+            .await?;
+        let transactions = sqlx::query(query::TRANSACTION_TOTALS)
+            .bind(start)
+            .fetch_one(&self.pool)
+            .await?;
+        let top_values = |column| async move {
+            sqlx::query_as(&query::top_values_sql(column))
+                .bind(start)
+                .bind(i64::from(top))
+                .fetch_all(&self.pool)
+                .await
+        };
+
+        Ok(IndexStats {
+            since,
+            events: events.get::<i64, _>("events") as u64,
+            transactions: transactions.get::<i64, _>("transactions") as u64,
+            failed_transactions: transactions.get::<i64, _>("failed_transactions") as u64,
+            average_lag_secs: events.get("average_lag_secs"),
+            event_types: top_values("event_type").await?,
+            packages: top_values("package_id").await?,
+        })
+    }
+
+    async fn totals_at(&self, checkpoint: u64, top: u32) -> Result<CheckpointTotals> {
+        let checkpoint_bound = checkpoint.min(i64::MAX as u64) as i64;
+        let events: i64 = sqlx::query_scalar(query::EVENT_TOTAL_AT)
+            .bind(checkpoint_bound)
+            .fetch_one(&self.pool)
+            .await?;
+        let top_totals = |column| async move {
+            sqlx::query_as(&query::top_totals_sql(column))
+                .bind(checkpoint_bound)
+                .bind(i64::from(top))
+                .fetch_all(&self.pool)
+                .await
+        };
+
+        Ok(CheckpointTotals {
+            checkpoint,
+            events: events as u64,
+            event_types: top_totals("event_type").await?,
+            packages: top_totals("package_id").await?,
+        })
+    }
+
+    fn export_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<EventRecord>> {
+        sqlx::query_as(query::EXPORT_EVENTS)
+            .bind(start as i64)
+            .bind(end as i64)
+            .fetch(&self.pool)
+            .map_err(Into::into)
+            .boxed()
+    }
+
+    fn export_raw_events(&self, start: u64, end: u64) -> BoxStream<'_, Result<RawEventRecord>> {
+        sqlx::query_as(query::EXPORT_RAW_EVENTS)
+            .bind(start as i64)
+            .bind(end as i64)
+            .fetch(&self.pool)
+            .map_err(Into::into)
+            .boxed()
+    }
+
+    fn export_matching_events(&self, query: &EventQuery) -> BoxStream<'_, Result<EventRecord>> {
+        let query = query.clone();
+        export_pages(
+            &self.pool,
+            move |after| query.to_export_sql(after, EXPORT_PAGE),
+            EventRecord::cursor,
+        )
+    }
+
+    fn export_matching_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        let query = query.clone();
+        export_pages(
+            &self.pool,
+            move |after| query.to_export_sql(after, EXPORT_PAGE),
+            TransactionRecord::cursor,
+        )
+    }
+
+    fn export_transactions(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        sqlx::query_as(query::EXPORT_TRANSACTIONS)
+            .bind(start as i64)
+            .bind(end as i64)
+            .fetch(&self.pool)
+            .map_err(Into::into)
+            .boxed()
+    }
+
+    async fn get_latest_checkpoint(&self) -> Result<Option<u64>> {
+        self.get_cursor(DEFAULT_PIPELINE).await
     }
 
     async fn update_checkpoint_progress(&self, checkpoint: u64) -> Result<()> {
         info!("Updating checkpoint progress to {}", checkpoint);
+        self.set_cursor(DEFAULT_PIPELINE, checkpoint).await
+    }
+
+    async fn commit_with_outbox(
+        &self,
+        checkpoint: u64,
+        events: &[ProcessedEvent],
+        typed: &[TypedRows<'_>],
+        outbox: Vec<OutboxEntry>,
+    ) -> Result<()> {
+        let messages = outbox
+            .iter()
+            .map(|entry| {
+                Ok((
+                    &entry.sink,
+                    &entry.event,
+                    serde_json::to_value(&entry.event)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut tx = self.pool.begin().await?;
+        self.insert_events_with_rows(&mut tx, events, typed).await?;
+        for chunk in messages.chunks(COMMIT_CHUNK) {
+            let mut query_builder =
+                sqlx::QueryBuilder::new("INSERT INTO outbox (sink, checkpoint_sequence, payload) ");
+            query_builder.push_values(chunk, |mut b, (sink, event, payload)| {
+                b.push_bind(*sink)
+                    .push_bind(event.checkpoint_sequence as i64)
+                    .push_bind(payload);
+            });
+            query_builder.build().execute(&mut *tx).await?;
+        }
+        insert_cursor(
+            &mut *tx,
+            DEFAULT_PIPELINE,
+            checkpoint,
+            self.chain_id.get().map(String::as_str),
+        )
+        .await?;
+        tx.commit().await?;
+
+        info!(
+            "Committed checkpoint {} with {} outbox messages",
+            checkpoint,
+            outbox.len()
+        );
+        Ok(())
+    }
 
+    async fn outbox_pending(
+        &self,
+        sink: &str,
+        after: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxRecord>> {
+        let messages = sqlx::query_as::<_, OutboxRecord>(
+            "SELECT id, created_at, sink, checkpoint_sequence, payload, attempts, last_error, dead_at
+             FROM outbox WHERE sink = $1 AND id > $2 AND dead_at IS NULL ORDER BY id LIMIT $3",
+        )
+        .bind(sink)
+        .bind(after)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    async fn ack_outbox(&self, ids: &[i64]) -> Result<()> {
+        sqlx::query("DELETE FROM outbox WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fail_outbox(&self, ids: &[i64], error: &str, max_attempts: u32) -> Result<usize> {
+        let dead: Vec<bool> = sqlx::query_scalar(
+            "UPDATE outbox SET attempts = attempts + 1, last_error = $2,
+                    dead_at = CASE WHEN attempts + 1 >= $3 THEN NOW() END
+             WHERE id = ANY($1) RETURNING dead_at IS NOT NULL",
+        )
+        .bind(ids)
+        .bind(error)
+        .bind(i64::from(max_attempts))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(dead.into_iter().filter(|dead| *dead).count())
+    }
+
+    async fn outbox_backlog(&self) -> Result<Vec<OutboxBacklog>> {
+        let backlog = sqlx::query_as::<_, OutboxBacklog>(
+            "SELECT DISTINCT ON (sink) sink,
+                    COUNT(*) FILTER (WHERE dead_at IS NULL) OVER (PARTITION BY sink) AS messages,
+                    COUNT(*) FILTER (WHERE dead_at IS NOT NULL) OVER (PARTITION BY sink) AS dead_letters,
+                    created_at AS oldest, attempts, last_error
+             FROM outbox ORDER BY sink, dead_at IS NOT NULL, id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(backlog)
+    }
+
+    async fn get_cursor(&self, pipeline: &str) -> Result<Option<u64>> {
+        let row = sqlx::query(
+            "SELECT checkpoint_sequence FROM indexer_state
+             WHERE pipeline = $1 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(pipeline)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| row.get::<i64, _>("checkpoint_sequence") as u64))
+    }
+
+    async fn set_cursor(&self, pipeline: &str, checkpoint: u64) -> Result<()> {
+        insert_cursor(
+            &self.pool,
+            pipeline,
+            checkpoint,
+            self.chain_id.get().map(String::as_str),
+        )
+        .await
+    }
+
+    async fn get_chain_id(&self) -> Result<Option<String>> {
+        let chain_id = sqlx::query_scalar(
+            "SELECT chain_id FROM indexer_state
+             WHERE chain_id IS NOT NULL ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(chain_id)
+    }
+
+    async fn set_chain_id(&self, chain_id: &str) -> Result<()> {
+        // A database indexed before chains were recorded adopts the chain on its newest row
+        sqlx::query(
+            "UPDATE indexer_state SET chain_id = $1
+             WHERE id = (SELECT MAX(id) FROM indexer_state) AND chain_id IS NULL",
+        )
+        .bind(chain_id)
+        .execute(&self.pool)
+        .await?;
+        // A fresh database records it with its first cursor
+        let _ = self.chain_id.set(chain_id.to_string());
+        Ok(())
+    }
+
+    async fn list_cursors(&self) -> Result<Vec<CursorRecord>> {
+        let cursors = sqlx::query_as::<_, CursorRecord>(
+            "SELECT DISTINCT ON (pipeline) pipeline, checkpoint_sequence, updated_at
+             FROM indexer_state ORDER BY pipeline, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cursors)
+    }
+
+    async fn record_checkpoint_report(&self, report: &CheckpointReport) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO checkpoint_reports (pipeline, checkpoint_sequence, transactions,
+                 events_seen, events_matched, events_skipped, events_stored, matched_by_filter,
+                 fetch_ms, transform_ms, store_ms, deliver_ms, processed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT (pipeline, checkpoint_sequence) DO UPDATE SET
+                 transactions = EXCLUDED.transactions,
+                 events_seen = EXCLUDED.events_seen,
+                 events_matched = EXCLUDED.events_matched,
+                 events_skipped = EXCLUDED.events_skipped,
+                 events_stored = EXCLUDED.events_stored,
+                 matched_by_filter = EXCLUDED.matched_by_filter,
+                 fetch_ms = EXCLUDED.fetch_ms,
+                 transform_ms = EXCLUDED.transform_ms,
+                 store_ms = EXCLUDED.store_ms,
+                 deliver_ms = EXCLUDED.deliver_ms,
+                 processed_at = EXCLUDED.processed_at",
+        )
+        .bind(&report.pipeline)
+        .bind(report.checkpoint_sequence)
+        .bind(report.transactions)
+        .bind(report.events_seen)
+        .bind(report.events_matched)
+        .bind(report.events_skipped)
+        .bind(report.events_stored)
+        .bind(&report.matched_by_filter)
+        .bind(report.fetch_ms)
+        .bind(report.transform_ms)
+        .bind(report.store_ms)
+        .bind(report.deliver_ms)
+        .bind(report.processed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn checkpoint_reports(
+        &self,
+        pipeline: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<CheckpointReport>> {
+        let reports = sqlx::query_as::<_, CheckpointReport>(
+            "SELECT pipeline, checkpoint_sequence, transactions, events_seen, events_matched,
+                    events_skipped, events_stored, matched_by_filter, fetch_ms, transform_ms,
+                    store_ms, deliver_ms, processed_at
+             FROM checkpoint_reports
+             WHERE pipeline = $1 AND checkpoint_sequence BETWEEN $2 AND $3
+             ORDER BY checkpoint_sequence",
+        )
+        .bind(pipeline)
+        .bind(start as i64)
+        .bind(end as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reports)
+    }
+
+    async fn store_packages(&self, packages: &[PackageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for package in packages {
+            sqlx::query(
+                "INSERT INTO packages (package_id, original_id, version, modules, tx_digest,
+                     sender, checkpoint_sequence, published_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 ON CONFLICT (package_id) DO NOTHING",
+            )
+            .bind(&package.package_id)
+            .bind(&package.original_id)
+            .bind(package.version)
+            .bind(&package.modules)
+            .bind(&package.tx_digest)
+            .bind(&package.sender)
+            .bind(package.checkpoint_sequence)
+            .bind(package.published_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn packages(&self, original_id: Option<&str>) -> Result<Vec<PackageRecord>> {
+        let packages = sqlx::query_as::<_, PackageRecord>(
+            "SELECT package_id, original_id, version, modules, tx_digest, sender,
+                    checkpoint_sequence, published_at
+             FROM packages
+             WHERE $1::TEXT IS NULL OR original_id = $1
+             ORDER BY original_id, version",
+        )
+        .bind(original_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(packages)
+    }
+
+    async fn store_move_calls(&self, calls: &[MoveCallRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for call in calls {
+            sqlx::query(
+                "INSERT INTO move_calls (id, checkpoint_sequence, transaction_digest, command_index,
+                     timestamp, package_id, module_name, function_name, type_arguments, sender)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (transaction_digest, command_index) DO NOTHING",
+            )
+            .bind(call.id)
+            .bind(call.checkpoint_sequence)
+            .bind(&call.transaction_digest)
+            .bind(call.command_index)
+            .bind(call.timestamp)
+            .bind(&call.package_id)
+            .bind(&call.module_name)
+            .bind(&call.function_name)
+            .bind(&call.type_arguments)
+            .bind(&call.sender)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn query_move_calls(&self, query: &MoveCallQuery) -> Result<Vec<MoveCallRecord>> {
+        let calls = query
+            .to_sql()
+            .build_query_as::<MoveCallRecord>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(calls)
+    }
+
+    async fn add_watchlist_addresses(&self, watchlist: &str, addresses: &[String]) -> Result<u64> {
+        let added = sqlx::query(
+            "INSERT INTO watchlist_addresses (watchlist, address)
+             SELECT $1, address FROM UNNEST($2::TEXT[]) AS address
+             ON CONFLICT (watchlist, address) DO NOTHING",
+        )
+        .bind(watchlist)
+        .bind(addresses)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(added)
+    }
+
+    async fn remove_watchlist_addresses(
+        &self,
+        watchlist: &str,
+        addresses: &[String],
+    ) -> Result<u64> {
+        let removed = sqlx::query(
+            "DELETE FROM watchlist_addresses WHERE watchlist = $1 AND address = ANY($2)",
+        )
+        .bind(watchlist)
+        .bind(addresses)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(removed)
+    }
+
+    async fn watchlist_addresses(&self, watchlist: &str) -> Result<Vec<String>> {
+        let addresses = sqlx::query_scalar(
+            "SELECT address FROM watchlist_addresses WHERE watchlist = $1 ORDER BY address",
+        )
+        .bind(watchlist)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(addresses)
+    }
+
+    async fn store_object_versions(&self, versions: &[ObjectVersionRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for version in versions {
+            sqlx::query(
+                "INSERT INTO object_versions (id, object_id, version, checkpoint_sequence,
+                     transaction_digest, timestamp, change_kind, digest, object_type, owner, contents)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT (object_id, version) DO NOTHING",
+            )
+            .bind(version.id)
+            .bind(&version.object_id)
+            .bind(version.version)
+            .bind(version.checkpoint_sequence)
+            .bind(&version.transaction_digest)
+            .bind(version.timestamp)
+            .bind(&version.change_kind)
+            .bind(&version.digest)
+            .bind(&version.object_type)
+            .bind(&version.owner)
+            .bind(&version.contents)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn query_object_versions(
+        &self,
+        query: &ObjectVersionQuery,
+    ) -> Result<Vec<ObjectVersionRecord>> {
+        let versions = query
+            .to_sql()
+            .build_query_as::<ObjectVersionRecord>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(versions)
+    }
+
+    async fn record_audit(&self, entry: &AuditEntry) -> Result<()> {
         sqlx::query(
-            "INSERT INTO indexer_state (checkpoint_sequence, updated_at) 
-             VALUES ($1, NOW())
-             ON CONFLICT (id) 
-             DO UPDATE SET checkpoint_sequence = $1, updated_at = NOW()",
+            "INSERT INTO audit_log (actor, source, action, details) VALUES ($1, $2, $3, $4)",
         )
-        .bind(checkpoint as i64)
+        .bind(&entry.actor)
+        .bind(&entry.source)
+        .bind(&entry.action)
+        .bind(&entry.details)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    async fn audit_log(&self, limit: u32) -> Result<Vec<AuditRecord>> {
+        let records = sqlx::query_as::<_, AuditRecord>(
+            "SELECT id, occurred_at, actor, source, action, details
+             FROM audit_log ORDER BY id DESC LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn create_api_key(
+        &self,
+        name: &str,
+        key_hash: &str,
+        scope: ApiScope,
+        rate_limit: Option<f64>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO api_keys (name, key_hash, scope, rate_limit) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(name)
+        .bind(key_hash)
+        .bind(scope.to_string())
+        .bind(rate_limit)
+        .execute(&self.pool)
+        .await
+        .wrap_err_with(|| format!("Failed to store API key {name}"))?;
+
+        Ok(())
+    }
+
+    async fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        let keys = sqlx::query_as::<_, ApiKeyRecord>(
+            "SELECT name, key_hash, scope, rate_limit, created_at, revoked_at
+             FROM api_keys ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    async fn revoke_api_key(&self, name: &str) -> Result<bool> {
+        let revoked = sqlx::query(
+            "UPDATE api_keys SET revoked_at = NOW() WHERE name = $1 AND revoked_at IS NULL",
+        )
+        .bind(name)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(revoked > 0)
+    }
+
+    async fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO webhook_deliveries (id, sink, url, status, attempts, events,
+                 first_checkpoint, last_checkpoint, response_status, error, payload)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        )
+        .bind(delivery.id)
+        .bind(&delivery.sink)
+        .bind(&delivery.url)
+        .bind(&delivery.status)
+        .bind(delivery.attempts)
+        .bind(delivery.events)
+        .bind(delivery.first_checkpoint)
+        .bind(delivery.last_checkpoint)
+        .bind(delivery.response_status)
+        .bind(&delivery.error)
+        .bind(&delivery.payload)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn webhook_deliveries(
+        &self,
+        limit: u32,
+        failed_only: bool,
+    ) -> Result<Vec<WebhookDeliveryRecord>> {
+        let deliveries = sqlx::query_as::<_, WebhookDeliveryRecord>(
+            "SELECT id, recorded_at, sink, url, status, attempts, events, first_checkpoint,
+                    last_checkpoint, response_status, error, payload
+             FROM webhook_deliveries WHERE NOT $2 OR status = 'failed'
+             ORDER BY recorded_at DESC LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .bind(failed_only)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    async fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        let expired =
+            partitions::expired_partitions(&self.pool, self.storage.partitioning, cutoff).await?;
+        for partition in expired {
+            report.events += partitions::count_rows(&self.pool, &partition).await?;
+            partitions::drop_partition(&self.pool, &partition).await?;
+            report.partitions.push(partition.name);
+        }
+        if !report.partitions.is_empty() {
+            info!(
+                "Dropped {} expired event partition(s)",
+                report.partitions.len()
+            );
+        }
+
+        // Events and transactions keep UTC in TIMESTAMP columns, bound as queries bind them
+        let naive_cutoff = cutoff.naive_utc();
+        report.events += sqlx::query("DELETE FROM processed_events WHERE timestamp < $1")
+            .bind(naive_cutoff)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        report.transactions =
+            sqlx::query("DELETE FROM processed_transactions WHERE timestamp < $1")
+                .bind(naive_cutoff)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        sqlx::query("DELETE FROM checkpoint_reports WHERE processed_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE recorded_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM move_calls WHERE timestamp < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM object_versions WHERE timestamp < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        for table in typed_tables(&self.pool).await? {
+            sqlx::query(&format!("DELETE FROM {table} WHERE timestamp < $1"))
+                .bind(cutoff)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        info!(
+            "Pruned {} events and {} transactions older than {}",
+            report.events, report.transactions, cutoff
+        );
+        Ok(report)
+    }
+
+    async fn prunable_before(&self, cutoff: DateTime<Utc>) -> Result<PruneReport> {
+        let partitions =
+            partitions::expired_partitions(&self.pool, self.storage.partitioning, cutoff).await?;
+        let events: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM processed_events WHERE timestamp < $1")
+                .bind(cutoff.naive_utc())
+                .fetch_one(&self.pool)
+                .await?;
+        let transactions: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM processed_transactions WHERE timestamp < $1")
+                .bind(cutoff.naive_utc())
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(PruneReport {
+            partitions: partitions.into_iter().map(|p| p.name).collect(),
+            events: events as u64,
+            transactions: transactions as u64,
+        })
+    }
+
+    async fn maintain(&self) -> Result<()> {
+        partitions::ensure_partitions(&self.pool, self.storage.partitioning, Utc::now()).await
+    }
+
+    fn connection_stats(&self) -> Option<ConnectionStats> {
+        Some(ConnectionStats {
+            open: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+            max: self.pool.options().get_max_connections(),
+        })
+    }
+
+    async fn acquire_wait(&self) -> Result<Option<Duration>> {
+        let started = Instant::now();
+        let connection = self.pool.acquire().await?;
+        let waited = started.elapsed();
+        drop(connection);
+        Ok(Some(waited))
+    }
+
     async fn health_check(&self) -> Result<bool> {
         match sqlx::query("SELECT 1").execute(&self.pool).await {
             Ok(_) => Ok(true),
@@ -211,3 +1417,198 @@ impl Storage for PostgresStorage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDateTime, TimeZone};
+    use sqlx::{Connection, PgConnection};
+    use sui_indexer_events::{DefaultEventProcessor, EventProcessor};
+    use sui_indexer_fixtures::TestEvent;
+
+    use super::*;
+    use crate::test_db::{
+        check_audit_log, check_stable_pages, check_stats_since, database_url, scratch_pool,
+    };
+
+    /// Migrated storage at `url` whose tables live in `schema`, created afresh
+    async fn scratch_storage(url: &str, schema: &str) -> Result<PostgresStorage> {
+        scratch_pool(url, schema).await?;
+        let config = DatabaseConfig {
+            url: Some(url.to_string()),
+            schema: Some(schema.to_string()),
+            ..DatabaseConfig::default()
+        };
+        let storage = PostgresStorage::new(config, StorageConfig::default()).await?;
+        storage.migrate_up(None).await?;
+        Ok(storage)
+    }
+
+    #[test]
+    fn test_output_table_names() {
+        assert!(output_table("replay_events_v2").is_ok());
+        assert!(output_table("benchmark_events").is_ok());
+        assert!(output_table("processed_events").is_err());
+        assert!(output_table("processed_events_p202510").is_err());
+        assert!(output_table("processed_events_default").is_err());
+        assert!(output_table("processed_transactions").is_err());
+        assert!(output_table("indexer_state").is_err());
+        assert!(output_table("cetus_replay").is_err());
+        assert!(output_table("events; DROP TABLE x").is_err());
+        assert!(output_table("2fast").is_err());
+    }
+
+    #[test]
+    fn test_connect_options_override_url() -> Result<()> {
+        let config = DatabaseConfig {
+            url: Some("postgresql://indexer@db.internal:5433/sui".to_string()),
+            port: Some(6432),
+            password: Some("p@ss:w/rd#1".to_string()),
+            dbname: Some("sui_mainnet".to_string()),
+            sslmode: Some(SslMode::VerifyFull),
+            ..DatabaseConfig::default()
+        };
+
+        let options = connect_options(&config)?;
+        assert!(options
+            .get_options()
+            .is_some_and(|o| o.contains("TimeZone=UTC")));
+        assert_eq!(options.get_host(), "db.internal");
+        assert_eq!(options.get_port(), 6432);
+        assert_eq!(options.get_username(), "indexer");
+        assert_eq!(options.get_database(), Some("sui_mainnet"));
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::VerifyFull));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_timestamps_stay_utc_when_the_url_asks_for_another_zone() -> Result<()> {
+        let url = database_url()?;
+        let schema = format!("timezone_test_{}", std::process::id());
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let tokyo = format!("{url}{separator}options=-c%20TimeZone%3DAsia%2FTokyo");
+        let storage = scratch_storage(&tokyo, &schema).await?;
+        let zone: String = sqlx::query_scalar("SHOW TimeZone")
+            .fetch_one(&storage.pool)
+            .await?;
+        assert_eq!(zone, "UTC");
+
+        let at = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let mut events = DefaultEventProcessor::new()
+            .process_events(vec![TestEvent::new("0x2::pool::SwapEvent").build()])
+            .await?;
+        events[0].timestamp = at;
+        storage.store_events(&events).await?;
+
+        // TIMESTAMP values read the same in any zone, so this shows the wall-clock time stored
+        let mut conn = PgConnection::connect_with(
+            &PgConnectOptions::from_str(&url)?.options([("search_path", schema.as_str())]),
+        )
+        .await?;
+        sqlx::query("SET TimeZone = 'Asia/Tokyo'")
+            .execute(&mut conn)
+            .await?;
+        let stored: NaiveDateTime = sqlx::query_scalar("SELECT timestamp FROM processed_events")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!(stored, at.naive_utc());
+
+        let window = |since: DateTime<Utc>| EventQuery {
+            since: Some(since),
+            until: Some(since + chrono::Duration::minutes(2)),
+            ..EventQuery::default()
+        };
+        let found = storage
+            .query_events(&window(at - chrono::Duration::minutes(1)))
+            .await?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].timestamp, at);
+        assert!(storage
+            .query_events(&window(at + chrono::Duration::minutes(1)))
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_stats_since_summarize_the_window() -> Result<()> {
+        let url = database_url()?;
+        let storage = scratch_storage(&url, &format!("stats_test_{}", std::process::id())).await?;
+        check_stats_since(&storage).await
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_pool_reports_its_connections_and_checkout_time() -> Result<()> {
+        let url = database_url()?;
+        let storage = scratch_storage(&url, &format!("pool_test_{}", std::process::id())).await?;
+
+        assert!(storage.acquire_wait().await?.is_some());
+        let stats = storage.connection_stats().unwrap();
+        assert_eq!(stats.max, DatabaseConfig::default().max_connections);
+        assert!(stats.open >= 1);
+        // The checked out connection went back to the pool
+        assert_eq!(stats.in_use(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_pages_stay_stable_while_events_are_stored() -> Result<()> {
+        let url = database_url()?;
+        let storage = scratch_storage(&url, &format!("page_test_{}", std::process::id())).await?;
+        check_stable_pages(&storage).await
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_audit_log_lists_newest_first() -> Result<()> {
+        let url = database_url()?;
+        let storage = scratch_storage(&url, &format!("audit_test_{}", std::process::id())).await?;
+        check_audit_log(&storage).await
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_prune_removes_what_queries_show_before_the_cutoff() -> Result<()> {
+        let url = database_url()?;
+        let storage = scratch_storage(&url, &format!("prune_test_{}", std::process::id())).await?;
+
+        let cutoff = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let raw = (0..4)
+            .map(|seq| TestEvent::new("0x2::pool::SwapEvent").seq(seq).build())
+            .collect();
+        let mut events = DefaultEventProcessor::new().process_events(raw).await?;
+        for (hours, event) in [-2, -1, 1, 2].into_iter().zip(&mut events) {
+            event.timestamp = cutoff + chrono::Duration::hours(hours);
+        }
+        storage.store_events(&events).await?;
+
+        let before = EventQuery {
+            until: Some(cutoff),
+            ..EventQuery::default()
+        };
+        let shown = storage.query_events(&before).await?.len() as u64;
+        assert_eq!(shown, 2);
+        assert_eq!(storage.prunable_before(cutoff).await?.events, shown);
+        assert_eq!(storage.prune_before(cutoff).await?.events, shown);
+        assert!(storage.query_events(&before).await?.is_empty());
+        assert_eq!(storage.query_events(&EventQuery::default()).await?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_connect_options_check_schema() {
+        let config = |schema: &str| DatabaseConfig {
+            schema: Some(schema.to_string()),
+            ..DatabaseConfig::default()
+        };
+        assert!(connect_options(&config("sui_testnet")).is_ok());
+        let err = connect_options(&config("testnet; DROP SCHEMA public")).unwrap_err();
+        assert!(matches!(
+            StorageError::find(&err),
+            Some(StorageError::InvalidSchema(_))
+        ));
+    }
+}
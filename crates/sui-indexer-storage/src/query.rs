@@ -0,0 +1,834 @@
+/// Read-side queries over indexed data
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::Serialize;
+use sqlx::{FromRow, Postgres, QueryBuilder};
+
+use crate::StorageError;
+
+/// Criteria for selecting stored events; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    /// Event struct name, e.g. `SwapEvent`
+    pub event_type: Option<String>,
+    /// Address that emitted the transaction
+    pub sender: Option<String>,
+    /// Package that defines the event
+    pub package: Option<String>,
+    /// Module that emitted the event
+    pub module: Option<String>,
+    /// Only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only events before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only events in checkpoints at or after this one
+    pub from_checkpoint: Option<u64>,
+    /// Only events in checkpoints at or before this one
+    pub to_checkpoint: Option<u64>,
+    /// Maximum number of events to return, newest first (all if unset)
+    pub limit: Option<u32>,
+    /// Only events after this position in the newest-first order
+    pub after: Option<PageCursor>,
+}
+
+/// Criteria for selecting stored transactions; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct TransactionQuery {
+    /// Transaction digest
+    pub digest: Option<String>,
+    /// Only transactions that succeeded (`true`) or failed (`false`) on chain
+    pub success: Option<bool>,
+    /// Only transactions at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only transactions before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only transactions in checkpoints at or after this one
+    pub from_checkpoint: Option<u64>,
+    /// Only transactions in checkpoints at or before this one
+    pub to_checkpoint: Option<u64>,
+    /// Maximum number of transactions to return, newest first (all if unset)
+    pub limit: Option<u32>,
+    /// Only transactions after this position in the newest-first order
+    pub after: Option<PageCursor>,
+}
+
+/// Criteria for selecting stored Move calls; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct MoveCallQuery {
+    /// Package of the called function
+    pub package: Option<String>,
+    /// Module of the called function
+    pub module: Option<String>,
+    /// Name of the called function
+    pub function: Option<String>,
+    /// Sender of the calling transaction
+    pub sender: Option<String>,
+    /// Only calls at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only calls before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only calls in checkpoints at or after this one
+    pub from_checkpoint: Option<u64>,
+    /// Only calls in checkpoints at or before this one
+    pub to_checkpoint: Option<u64>,
+    /// Maximum number of calls to return, newest first (all if unset)
+    pub limit: Option<u32>,
+    /// Only calls after this position in the newest-first order
+    pub after: Option<PageCursor>,
+}
+
+/// Criteria for selecting stored object versions; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct ObjectVersionQuery {
+    /// Object whose versions to return
+    pub object_id: Option<String>,
+    /// Only versions written at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only versions written before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only versions written in checkpoints at or after this one
+    pub from_checkpoint: Option<u64>,
+    /// Only versions written in checkpoints at or before this one
+    pub to_checkpoint: Option<u64>,
+    /// Maximum number of versions to return, newest first (all if unset)
+    pub limit: Option<u32>,
+    /// Only versions after this position in the newest-first order
+    pub after: Option<PageCursor>,
+}
+
+/// Position of a stored row in the newest-first order of query results
+///
+/// Rows are ordered by checkpoint and then ID, both descending, so rows stored
+/// after a page was read sort before it and never shift the pages that follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub checkpoint: u64,
+    pub id: uuid::Uuid,
+}
+
+impl PageCursor {
+    /// Opaque token handed to API clients: URL-safe base64 of checkpoint and ID
+    pub fn encode(&self) -> String {
+        let mut bytes = [0; 24];
+        bytes[..8].copy_from_slice(&self.checkpoint.to_be_bytes());
+        bytes[8..].copy_from_slice(self.id.as_bytes());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Read back a token made by [`PageCursor::encode`]
+    pub fn decode(token: &str) -> Result<Self> {
+        let bytes: [u8; 24] = URL_SAFE_NO_PAD
+            .decode(token)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| StorageError::InvalidCursor(token.to_string()))?;
+        let (checkpoint, id) = bytes.split_at(8);
+        Ok(Self {
+            checkpoint: u64::from_be_bytes(checkpoint.try_into()?),
+            id: uuid::Uuid::from_slice(id)?,
+        })
+    }
+
+    /// Cursor after the last of `rows`, if the page was full and more may follow
+    pub fn next_page<T>(rows: &[T], limit: u32, cursor: impl Fn(&T) -> Self) -> Option<Self> {
+        if rows.len() < limit as usize {
+            return None;
+        }
+        rows.last().map(cursor)
+    }
+}
+
+/// Stored event as returned by [`EventQuery`]
+///
+/// Unlike `ProcessedEvent` this does not need the raw event JSON, so it works
+/// regardless of the `store_raw_event` setting.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EventRecord {
+    pub id: uuid::Uuid,
+    pub checkpoint_sequence: i64,
+    pub transaction_digest: String,
+    pub timestamp: DateTime<Utc>,
+    pub package_id: String,
+    pub module_name: String,
+    pub event_type: String,
+    pub sender: String,
+    pub fields: serde_json::Value,
+}
+
+impl EventRecord {
+    /// Position of this event in query results
+    pub fn cursor(&self) -> PageCursor {
+        PageCursor {
+            checkpoint: self.checkpoint_sequence as u64,
+            id: self.id,
+        }
+    }
+}
+
+/// Stored transaction as returned by [`TransactionQuery`] and transaction exports
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TransactionRecord {
+    pub id: uuid::Uuid,
+    pub checkpoint_sequence: i64,
+    pub digest: String,
+    pub timestamp: DateTime<Utc>,
+    pub sender: String,
+    pub gas_used: i64,
+    pub status: String,
+}
+
+impl TransactionRecord {
+    /// Position of this transaction in query results
+    pub fn cursor(&self) -> PageCursor {
+        PageCursor {
+            checkpoint: self.checkpoint_sequence as u64,
+            id: self.id,
+        }
+    }
+}
+
+/// Move call made by a command of an indexed transaction, kept in the `move_calls` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct MoveCallRecord {
+    pub id: uuid::Uuid,
+    pub checkpoint_sequence: i64,
+    pub transaction_digest: String,
+    /// Position of the call among the transaction's commands
+    pub command_index: i32,
+    pub timestamp: DateTime<Utc>,
+    pub package_id: String,
+    pub module_name: String,
+    pub function_name: String,
+    /// Type arguments of the call, e.g. `0x2::sui::SUI`
+    pub type_arguments: Vec<String>,
+    pub sender: String,
+}
+
+impl MoveCallRecord {
+    /// Position of this call in query results
+    pub fn cursor(&self) -> PageCursor {
+        PageCursor {
+            checkpoint: self.checkpoint_sequence as u64,
+            id: self.id,
+        }
+    }
+
+    /// Called function as `package::module::function`
+    pub fn target(&self) -> String {
+        format!(
+            "{}::{}::{}",
+            self.package_id, self.module_name, self.function_name
+        )
+    }
+}
+
+/// Version of a tracked object, kept in the `object_versions` table
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct ObjectVersionRecord {
+    pub id: uuid::Uuid,
+    pub object_id: String,
+    pub version: i64,
+    pub checkpoint_sequence: i64,
+    pub transaction_digest: String,
+    pub timestamp: DateTime<Utc>,
+    /// `created`, `mutated`, `unwrapped`, `wrapped` or `deleted`
+    pub change_kind: String,
+    /// Digest of the version; unset once the object is wrapped or deleted
+    pub digest: Option<String>,
+    pub object_type: Option<String>,
+    pub owner: Option<String>,
+    /// Fields of the version as JSON, if they were fetched
+    pub contents: Option<serde_json::Value>,
+}
+
+impl ObjectVersionRecord {
+    /// Position of this version in query results
+    pub fn cursor(&self) -> PageCursor {
+        PageCursor {
+            checkpoint: self.checkpoint_sequence as u64,
+            id: self.id,
+        }
+    }
+}
+
+/// Raw event JSON stored with an event, as read back for replays
+#[derive(Debug, Clone, FromRow)]
+pub struct RawEventRecord {
+    pub checkpoint_sequence: i64,
+    pub timestamp: DateTime<Utc>,
+    /// `None` when the event was stored with `store_raw_event` disabled
+    pub event_data: Option<serde_json::Value>,
+}
+
+/// Summary of the data indexed since a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStats {
+    /// Start of the summarized window
+    pub since: DateTime<Utc>,
+    /// Events stored in the window
+    pub events: u64,
+    /// Transactions stored in the window
+    pub transactions: u64,
+    /// Stored transactions that failed on chain
+    pub failed_transactions: u64,
+    /// Mean seconds between an event's checkpoint and it being stored
+    pub average_lag_secs: Option<f64>,
+    /// Most frequent event types, most frequent first
+    pub event_types: Vec<ValueCount>,
+    /// Packages emitting the most events, most frequent first
+    pub packages: Vec<ValueCount>,
+}
+
+/// Cumulative event counts as of a checkpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointTotals {
+    /// Last checkpoint counted
+    pub checkpoint: u64,
+    /// Events stored up to and including the checkpoint
+    pub events: u64,
+    /// Most frequent event types, most frequent first
+    pub event_types: Vec<ValueCount>,
+    /// Packages emitting the most events, most frequent first
+    pub packages: Vec<ValueCount>,
+}
+
+/// Number of stored rows sharing a value
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Event count and mean indexing lag since `$1`, in UTC
+pub(crate) const EVENT_TOTALS: &str = "SELECT COUNT(*) AS events,
+        AVG(EXTRACT(EPOCH FROM processed_at - timestamp))::FLOAT8 AS average_lag_secs
+     FROM processed_events WHERE timestamp >= $1";
+
+/// Transaction and failed transaction counts since `$1`, in UTC
+pub(crate) const TRANSACTION_TOTALS: &str = "SELECT COUNT(*) AS transactions,
+        COUNT(*) FILTER (WHERE status = 'false') AS failed_transactions
+     FROM processed_transactions WHERE timestamp >= $1";
+
+/// The `$2` most frequent values of an events column since `$1`, in UTC
+pub(crate) fn top_values_sql(column: &str) -> String {
+    format!(
+        "SELECT {column} AS value, COUNT(*) AS count FROM processed_events
+         WHERE timestamp >= $1 GROUP BY {column} ORDER BY count DESC, value LIMIT $2"
+    )
+}
+
+/// Events counted up to checkpoint `$1`
+pub(crate) const EVENT_TOTAL_AT: &str = "SELECT COALESCE(SUM(events), 0)::BIGINT AS events
+     FROM event_counts WHERE checkpoint_sequence <= $1";
+
+/// The `$2` most frequent values of an event counts column up to checkpoint `$1`
+pub(crate) fn top_totals_sql(column: &str) -> String {
+    format!(
+        "SELECT {column} AS value, SUM(events)::BIGINT AS count FROM event_counts
+         WHERE checkpoint_sequence <= $1 GROUP BY {column} ORDER BY count DESC, value LIMIT $2"
+    )
+}
+
+/// Selects [`EventRecord`] columns; timestamps are stored without a zone, in UTC
+macro_rules! select_events {
+    () => {
+        "SELECT id, checkpoint_sequence, transaction_digest,
+                timestamp AT TIME ZONE 'UTC' AS timestamp, package_id, module_name,
+                event_type, sender, fields
+         FROM processed_events"
+    };
+}
+
+/// Stored events of a checkpoint range in checkpoint order
+pub(crate) const EXPORT_EVENTS: &str = concat!(
+    select_events!(),
+    " WHERE checkpoint_sequence BETWEEN $1 AND $2 ORDER BY checkpoint_sequence, id"
+);
+
+/// Raw events of a checkpoint range in checkpoint order
+pub(crate) const EXPORT_RAW_EVENTS: &str = "SELECT checkpoint_sequence,
+        timestamp AT TIME ZONE 'UTC' AS timestamp, event_data
+     FROM processed_events
+     WHERE checkpoint_sequence BETWEEN $1 AND $2 ORDER BY checkpoint_sequence, id";
+
+/// Selects [`TransactionRecord`] columns
+macro_rules! select_transactions {
+    () => {
+        "SELECT id, checkpoint_sequence, digest,
+                timestamp AT TIME ZONE 'UTC' AS timestamp, sender, gas_used, status
+         FROM processed_transactions"
+    };
+}
+
+/// Stored transactions of a checkpoint range in checkpoint order
+pub(crate) const EXPORT_TRANSACTIONS: &str = concat!(
+    select_transactions!(),
+    " WHERE checkpoint_sequence BETWEEN $1 AND $2 ORDER BY checkpoint_sequence, id"
+);
+
+/// Selects [`MoveCallRecord`] columns
+const SELECT_MOVE_CALLS: &str = "SELECT id, checkpoint_sequence, transaction_digest, command_index,
+        timestamp, package_id, module_name, function_name, type_arguments, sender
+     FROM move_calls";
+
+/// Selects [`ObjectVersionRecord`] columns
+const SELECT_OBJECT_VERSIONS: &str = "SELECT id, object_id, version, checkpoint_sequence,
+        transaction_digest, timestamp, change_kind, digest, object_type, owner, contents
+     FROM object_versions";
+
+/// Append the time and checkpoint bounds shared by the queries of stored rows
+fn push_bounds(
+    builder: &mut QueryBuilder<'static, Postgres>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    from_checkpoint: Option<u64>,
+    to_checkpoint: Option<u64>,
+) {
+    // The column is a TIMESTAMP holding UTC; a TIMESTAMPTZ bind would be shifted by the session zone
+    if let Some(since) = since {
+        builder
+            .push(" AND timestamp >= ")
+            .push_bind(since.naive_utc());
+    }
+    if let Some(until) = until {
+        builder
+            .push(" AND timestamp < ")
+            .push_bind(until.naive_utc());
+    }
+    if let Some(from) = from_checkpoint {
+        builder
+            .push(" AND checkpoint_sequence >= ")
+            .push_bind(from as i64);
+    }
+    if let Some(to) = to_checkpoint {
+        builder
+            .push(" AND checkpoint_sequence <= ")
+            .push_bind(to as i64);
+    }
+}
+
+/// Append the checkpoint order of exports, starting after `after`, and a page size
+fn push_export_order(
+    builder: &mut QueryBuilder<'static, Postgres>,
+    after: Option<PageCursor>,
+    page: u32,
+) {
+    if let Some(after) = after {
+        builder
+            .push(" AND (checkpoint_sequence, id) > (")
+            .push_bind(after.checkpoint as i64)
+            .push(", ")
+            .push_bind(after.id)
+            .push(")");
+    }
+    builder
+        .push(" ORDER BY checkpoint_sequence, id LIMIT ")
+        .push_bind(i64::from(page));
+}
+
+/// Append the newest-first order, starting after `after`, and an optional limit
+fn push_order(
+    builder: &mut QueryBuilder<'static, Postgres>,
+    after: Option<PageCursor>,
+    limit: Option<u32>,
+) {
+    if let Some(after) = after {
+        builder
+            .push(" AND (checkpoint_sequence, id) < (")
+            .push_bind(after.checkpoint as i64)
+            .push(", ")
+            .push_bind(after.id)
+            .push(")");
+    }
+    // The ID breaks ties so the order, and thus every cursor, is total
+    builder.push(" ORDER BY checkpoint_sequence DESC, id DESC");
+    if let Some(limit) = limit {
+        builder.push(" LIMIT ").push_bind(i64::from(limit));
+    }
+}
+
+impl EventQuery {
+    /// Build the SQL selecting the matching rows of `processed_events`
+    pub(crate) fn to_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = self.filter_sql();
+        push_order(&mut builder, self.after, self.limit);
+        builder
+    }
+
+    /// Build the SQL selecting a page of the matching rows in checkpoint order, ignoring `after` and `limit`
+    pub(crate) fn to_export_sql(
+        &self,
+        after: Option<PageCursor>,
+        page: u32,
+    ) -> QueryBuilder<'static, Postgres> {
+        let mut builder = self.filter_sql();
+        push_export_order(&mut builder, after, page);
+        builder
+    }
+
+    fn filter_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(concat!(select_events!(), " WHERE TRUE"));
+
+        let columns = [
+            ("event_type", &self.event_type),
+            ("sender", &self.sender),
+            ("package_id", &self.package),
+            ("module_name", &self.module),
+        ];
+        for (column, value) in columns {
+            if let Some(value) = value {
+                builder
+                    .push(format_args!(" AND {column} = "))
+                    .push_bind(value.clone());
+            }
+        }
+        push_bounds(
+            &mut builder,
+            self.since,
+            self.until,
+            self.from_checkpoint,
+            self.to_checkpoint,
+        );
+        builder
+    }
+
+    /// Whether a stored event meets the criteria, ignoring `after` and `limit`
+    pub fn matches(&self, event: &EventRecord) -> bool {
+        let columns = [
+            (&event.event_type, &self.event_type),
+            (&event.sender, &self.sender),
+            (&event.package_id, &self.package),
+            (&event.module_name, &self.module),
+        ];
+        columns
+            .into_iter()
+            .all(|(column, value)| value.as_ref().is_none_or(|value| value == column))
+            && in_bounds(
+                event.timestamp,
+                event.checkpoint_sequence,
+                self.since,
+                self.until,
+                self.from_checkpoint,
+                self.to_checkpoint,
+            )
+    }
+}
+
+impl TransactionQuery {
+    /// Build the SQL selecting the matching rows of `processed_transactions`
+    pub(crate) fn to_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = self.filter_sql();
+        push_order(&mut builder, self.after, self.limit);
+        builder
+    }
+
+    /// Build the SQL selecting a page of the matching rows in checkpoint order, ignoring `after` and `limit`
+    pub(crate) fn to_export_sql(
+        &self,
+        after: Option<PageCursor>,
+        page: u32,
+    ) -> QueryBuilder<'static, Postgres> {
+        let mut builder = self.filter_sql();
+        push_export_order(&mut builder, after, page);
+        builder
+    }
+
+    fn filter_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(concat!(select_transactions!(), " WHERE TRUE"));
+
+        if let Some(digest) = &self.digest {
+            builder.push(" AND digest = ").push_bind(digest.clone());
+        }
+        // Stored as the text of the success flag
+        if let Some(success) = self.success {
+            builder
+                .push(" AND status = ")
+                .push_bind(success.to_string());
+        }
+        push_bounds(
+            &mut builder,
+            self.since,
+            self.until,
+            self.from_checkpoint,
+            self.to_checkpoint,
+        );
+        builder
+    }
+
+    /// Whether a stored transaction meets the criteria, ignoring `after` and `limit`
+    pub fn matches(&self, transaction: &TransactionRecord) -> bool {
+        self.digest
+            .as_ref()
+            .is_none_or(|digest| *digest == transaction.digest)
+            && self
+                .success
+                .is_none_or(|success| success.to_string() == transaction.status)
+            && in_bounds(
+                transaction.timestamp,
+                transaction.checkpoint_sequence,
+                self.since,
+                self.until,
+                self.from_checkpoint,
+                self.to_checkpoint,
+            )
+    }
+}
+
+impl MoveCallQuery {
+    /// Build the SQL selecting the matching rows of `move_calls`
+    pub(crate) fn to_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(SELECT_MOVE_CALLS);
+        builder.push(" WHERE TRUE");
+
+        let columns = [
+            ("package_id", &self.package),
+            ("module_name", &self.module),
+            ("function_name", &self.function),
+            ("sender", &self.sender),
+        ];
+        for (column, value) in columns {
+            if let Some(value) = value {
+                builder
+                    .push(format_args!(" AND {column} = "))
+                    .push_bind(value.clone());
+            }
+        }
+        push_bounds(
+            &mut builder,
+            self.since,
+            self.until,
+            self.from_checkpoint,
+            self.to_checkpoint,
+        );
+        push_order(&mut builder, self.after, self.limit);
+        builder
+    }
+
+    /// Whether a stored call meets the criteria, ignoring `after` and `limit`
+    pub fn matches(&self, call: &MoveCallRecord) -> bool {
+        let columns = [
+            (&call.package_id, &self.package),
+            (&call.module_name, &self.module),
+            (&call.function_name, &self.function),
+            (&call.sender, &self.sender),
+        ];
+        columns
+            .into_iter()
+            .all(|(column, value)| value.as_ref().is_none_or(|value| value == column))
+            && in_bounds(
+                call.timestamp,
+                call.checkpoint_sequence,
+                self.since,
+                self.until,
+                self.from_checkpoint,
+                self.to_checkpoint,
+            )
+    }
+}
+
+impl ObjectVersionQuery {
+    /// Build the SQL selecting the matching rows of `object_versions`
+    pub(crate) fn to_sql(&self) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(SELECT_OBJECT_VERSIONS);
+        builder.push(" WHERE TRUE");
+        if let Some(object_id) = &self.object_id {
+            builder
+                .push(" AND object_id = ")
+                .push_bind(object_id.clone());
+        }
+        push_bounds(
+            &mut builder,
+            self.since,
+            self.until,
+            self.from_checkpoint,
+            self.to_checkpoint,
+        );
+        push_order(&mut builder, self.after, self.limit);
+        builder
+    }
+
+    /// Whether a stored version meets the criteria, ignoring `after` and `limit`
+    pub fn matches(&self, version: &ObjectVersionRecord) -> bool {
+        self.object_id
+            .as_ref()
+            .is_none_or(|object_id| *object_id == version.object_id)
+            && in_bounds(
+                version.timestamp,
+                version.checkpoint_sequence,
+                self.since,
+                self.until,
+                self.from_checkpoint,
+                self.to_checkpoint,
+            )
+    }
+}
+
+/// Whether a row falls within the time and checkpoint bounds, as [`push_bounds`] selects
+fn in_bounds(
+    timestamp: DateTime<Utc>,
+    checkpoint: i64,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    from_checkpoint: Option<u64>,
+    to_checkpoint: Option<u64>,
+) -> bool {
+    since.is_none_or(|since| timestamp >= since)
+        && until.is_none_or(|until| timestamp < until)
+        && from_checkpoint.is_none_or(|from| checkpoint >= from as i64)
+        && to_checkpoint.is_none_or(|to| checkpoint <= to as i64)
+}
+
+/// Sort rows newest first and cut out the page after `after`, as [`push_order`] does
+pub(crate) fn page<T>(
+    mut rows: Vec<T>,
+    cursor: impl Fn(&T) -> PageCursor,
+    after: Option<PageCursor>,
+    limit: Option<u32>,
+) -> Vec<T> {
+    let key = |cursor: PageCursor| (cursor.checkpoint, cursor.id);
+    rows.sort_by_key(|row| std::cmp::Reverse(key(cursor(row))));
+    if let Some(after) = after {
+        rows.retain(|row| key(cursor(row)) < key(after));
+    }
+    if let Some(limit) = limit {
+        rows.truncate(limit as usize);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_binds_only_set_criteria() {
+        let query = EventQuery {
+            event_type: Some("SwapEvent".to_string()),
+            since: Some(Utc::now()),
+            limit: Some(5),
+            ..EventQuery::default()
+        };
+
+        let sql = query.to_sql().into_sql();
+        assert!(sql.contains("AND event_type = $1"));
+        assert!(sql.contains("AND timestamp >= $2"));
+        assert!(sql.ends_with("LIMIT $3"));
+        assert!(!sql.contains("sender ="));
+
+        let all = EventQuery {
+            from_checkpoint: Some(10),
+            to_checkpoint: Some(10),
+            ..EventQuery::default()
+        };
+        let sql = all.to_sql().into_sql();
+        assert!(sql.contains("checkpoint_sequence >= $1 AND checkpoint_sequence <= $2"));
+        assert!(!sql.contains("LIMIT"));
+
+        let page = all.to_export_sql(
+            Some(PageCursor {
+                checkpoint: 10,
+                id: uuid::Uuid::nil(),
+            }),
+            100,
+        );
+        let sql = page.into_sql();
+        assert!(sql.contains("AND (checkpoint_sequence, id) > ($3, $4)"));
+        assert!(sql.ends_with("ORDER BY checkpoint_sequence, id LIMIT $5"));
+
+        let versions = ObjectVersionQuery {
+            object_id: Some("0x5".to_string()),
+            limit: Some(10),
+            ..ObjectVersionQuery::default()
+        };
+        let sql = versions.to_sql().into_sql();
+        assert!(sql.contains("FROM object_versions WHERE TRUE AND object_id = $1"));
+        assert!(sql.ends_with("LIMIT $2"));
+
+        let transactions = TransactionQuery {
+            success: Some(false),
+            to_checkpoint: Some(10),
+            limit: Some(20),
+            ..TransactionQuery::default()
+        };
+        let sql = transactions.to_sql().into_sql();
+        assert!(sql.contains("FROM processed_transactions WHERE TRUE AND status = $1"));
+        assert!(sql.contains("AND checkpoint_sequence <= $2"));
+        assert!(sql.ends_with("LIMIT $3"));
+
+        let calls = MoveCallQuery {
+            module: Some("pool".to_string()),
+            function: Some("swap".to_string()),
+            ..MoveCallQuery::default()
+        };
+        let sql = calls.to_sql().into_sql();
+        assert!(
+            sql.contains("FROM move_calls WHERE TRUE AND module_name = $1 AND function_name = $2")
+        );
+        assert!(!sql.contains("LIMIT"));
+
+        let cursor = PageCursor {
+            checkpoint: 42,
+            id: uuid::Uuid::new_v4(),
+        };
+        let page = EventQuery {
+            sender: Some("0x1".to_string()),
+            limit: Some(2),
+            after: Some(cursor),
+            ..EventQuery::default()
+        };
+        let sql = page.to_sql().into_sql();
+        assert!(sql.contains(
+            "AND (checkpoint_sequence, id) < ($2, $3) ORDER BY checkpoint_sequence DESC, id DESC"
+        ));
+
+        let top = top_values_sql("package_id");
+        assert!(top.contains("GROUP BY package_id ORDER BY count DESC"));
+    }
+
+    #[test]
+    fn test_cursor_tokens_round_trip() {
+        let cursor = PageCursor {
+            checkpoint: u64::MAX - 1,
+            id: uuid::Uuid::new_v4(),
+        };
+        let token = cursor.encode();
+        assert!(token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(PageCursor::decode(&token).unwrap(), cursor);
+
+        for token in ["not-a-cursor", "", &token[..token.len() - 2], "a+b/c=="] {
+            let err = PageCursor::decode(token).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<StorageError>(),
+                Some(StorageError::InvalidCursor(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_page_follows_the_cursor_newest_first() {
+        let id = |n: u128| uuid::Uuid::from_u128(n);
+        let row = |checkpoint: u64, n: u128| PageCursor {
+            checkpoint,
+            id: id(n),
+        };
+        let rows = vec![row(1, 1), row(3, 1), row(2, 2), row(2, 1), row(3, 2)];
+
+        let first = page(rows.clone(), |row| *row, None, Some(2));
+        assert_eq!(first, [row(3, 2), row(3, 1)]);
+        let after = PageCursor::next_page(&first, 2, |row| *row);
+        assert_eq!(after, Some(row(3, 1)));
+
+        // Rows stored after the first page sort before the cursor and leave the rest alone
+        let mut grown = rows.clone();
+        grown.extend([row(4, 1), row(3, 3)]);
+        let second = page(grown.clone(), |row| *row, after, Some(2));
+        assert_eq!(second, [row(2, 2), row(2, 1)]);
+        let after = PageCursor::next_page(&second, 2, |row| *row);
+        let last = page(grown, |row| *row, after, Some(2));
+        assert_eq!(last, [row(1, 1)]);
+        assert_eq!(PageCursor::next_page(&last, 2, |row| *row), None);
+    }
+}
@@ -0,0 +1,418 @@
+/// Reference of the tables, columns and indexes created by the migrations
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+/// Tables of the current schema with their comments
+const TABLES: &str = "
+    SELECT c.relname::TEXT AS name, obj_description(c.oid, 'pg_class') AS description
+    FROM pg_class c
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    WHERE n.nspname = current_schema() AND c.relkind IN ('r', 'p')
+    ORDER BY c.relname";
+
+/// Columns in definition order, with the single-column unique and foreign key constraints
+const COLUMNS: &str = "
+    SELECT c.relname::TEXT AS table_name, a.attname::TEXT AS name,
+           format_type(a.atttypid, a.atttypmod) AS data_type,
+           NOT a.attnotnull AS nullable,
+           pg_get_expr(d.adbin, d.adrelid) AS default_value,
+           EXISTS (
+               SELECT 1 FROM pg_constraint k
+               WHERE k.conrelid = c.oid AND k.contype = 'u' AND k.conkey = ARRAY[a.attnum]
+           ) AS is_unique,
+           (
+               SELECT format('%s(%s)', f.relname, string_agg(fa.attname, ', ' ORDER BY key.position))
+               FROM pg_constraint k
+               JOIN pg_class f ON f.oid = k.confrelid
+               CROSS JOIN LATERAL UNNEST(k.confkey) WITH ORDINALITY AS key(attnum, position)
+               JOIN pg_attribute fa ON fa.attrelid = k.confrelid AND fa.attnum = key.attnum
+               WHERE k.conrelid = c.oid AND k.contype = 'f' AND a.attnum = ANY(k.conkey)
+               GROUP BY k.oid, f.relname
+               LIMIT 1
+           ) AS referenced,
+           col_description(c.oid, a.attnum) AS description
+    FROM pg_attribute a
+    JOIN pg_class c ON c.oid = a.attrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+    WHERE n.nspname = current_schema() AND c.relkind IN ('r', 'p')
+      AND a.attnum > 0 AND NOT a.attisdropped
+    ORDER BY c.relname, a.attnum";
+
+/// Primary key columns in key order
+const PRIMARY_KEYS: &str = "
+    SELECT c.relname::TEXT AS table_name, a.attname::TEXT AS name
+    FROM pg_constraint k
+    JOIN pg_class c ON c.oid = k.conrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    CROSS JOIN LATERAL UNNEST(k.conkey) WITH ORDINALITY AS key(attnum, position)
+    JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = key.attnum
+    WHERE n.nspname = current_schema() AND k.contype = 'p'
+    ORDER BY c.relname, key.position";
+
+/// Indexes other than primary keys, with their key columns or expressions and sort order
+const INDEXES: &str = "
+    SELECT t.relname::TEXT AS table_name, i.relname::TEXT AS name,
+           ARRAY(
+               SELECT pg_get_indexdef(x.indexrelid, key.position::INTEGER, true)
+                   || CASE WHEN x.indoption[key.position - 1] & 1 = 1 THEN ' DESC' ELSE '' END
+               FROM generate_series(1, x.indnkeyatts) AS key(position)
+               ORDER BY key.position
+           ) AS columns,
+           x.indisunique AS is_unique,
+           NULLIF(am.amname::TEXT, 'btree') AS method,
+           pg_get_expr(x.indpred, x.indrelid, true) AS predicate
+    FROM pg_index x
+    JOIN pg_class i ON i.oid = x.indexrelid
+    JOIN pg_class t ON t.oid = x.indrelid
+    JOIN pg_namespace n ON n.oid = t.relnamespace
+    JOIN pg_am am ON am.oid = i.relam
+    WHERE n.nspname = current_schema() AND NOT x.indisprimary
+    ORDER BY t.relname, i.relname";
+
+/// Tables the migrations leave in the database, as its catalog describes them
+///
+/// `schema.json` records it for the embedded migrations. Tables created at
+/// runtime, such as typed event tables, replay output tables and event
+/// partitions, are not part of it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaDoc {
+    /// Newest migration applied
+    pub version: i64,
+    /// Tables by name
+    pub tables: Vec<TableDoc>,
+}
+
+/// A table with its columns and indexes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDoc {
+    /// Table name
+    pub name: String,
+    /// Comment on the table
+    pub description: Option<String>,
+    /// Migration that created the table
+    pub created_in: i64,
+    /// Columns in definition order
+    pub columns: Vec<ColumnDoc>,
+    /// Columns of the primary key, empty without one
+    pub primary_key: Vec<String>,
+    /// Indexes and unique constraints
+    pub indexes: Vec<IndexDoc>,
+}
+
+/// A table column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnDoc {
+    /// Column name
+    pub name: String,
+    /// SQL type as PostgreSQL reports it
+    pub data_type: String,
+    /// Whether the column accepts NULL
+    pub nullable: bool,
+    /// Default expression
+    pub default: Option<String>,
+    /// Whether the column alone is unique
+    pub unique: bool,
+    /// Table and columns referenced by a foreign key
+    pub references: Option<String>,
+    /// Comment on the column
+    pub description: Option<String>,
+    /// Migration that added the column
+    pub added_in: i64,
+}
+
+/// An index or unique constraint of a table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexDoc {
+    /// Index name
+    pub name: String,
+    /// Indexed columns or expressions, with their sort order
+    pub columns: Vec<String>,
+    /// Whether the index enforces uniqueness
+    pub unique: bool,
+    /// Access method other than the default B-tree
+    pub method: Option<String>,
+    /// Condition of a partial index
+    pub predicate: Option<String>,
+}
+
+impl SchemaDoc {
+    /// Describe the tables of the current schema, as left by migration `version`
+    ///
+    /// Tables and columns described before keep the migration that created
+    /// them, so recording after every migration tells when each appeared.
+    pub async fn record(&mut self, conn: &mut PgConnection, version: i64) -> Result<()> {
+        let mut tables: Vec<TableDoc> = sqlx::query(TABLES)
+            .fetch_all(&mut *conn)
+            .await?
+            .iter()
+            .map(|row| TableDoc {
+                name: row.get("name"),
+                description: row.get("description"),
+                created_in: version,
+                columns: Vec::new(),
+                primary_key: Vec::new(),
+                indexes: Vec::new(),
+            })
+            .collect();
+        for row in sqlx::query(COLUMNS).fetch_all(&mut *conn).await? {
+            if let Some(table) = table_mut(&mut tables, &row) {
+                table.columns.push(ColumnDoc {
+                    name: row.get("name"),
+                    data_type: row.get("data_type"),
+                    nullable: row.get("nullable"),
+                    default: row.get("default_value"),
+                    unique: row.get("is_unique"),
+                    references: row.get("referenced"),
+                    description: row.get("description"),
+                    added_in: version,
+                });
+            }
+        }
+        for row in sqlx::query(PRIMARY_KEYS).fetch_all(&mut *conn).await? {
+            if let Some(table) = table_mut(&mut tables, &row) {
+                table.primary_key.push(row.get("name"));
+            }
+        }
+        for row in sqlx::query(INDEXES).fetch_all(&mut *conn).await? {
+            if let Some(table) = table_mut(&mut tables, &row) {
+                table.indexes.push(IndexDoc {
+                    name: row.get("name"),
+                    columns: row.get("columns"),
+                    unique: row.get("is_unique"),
+                    method: row.get("method"),
+                    predicate: row.get("predicate"),
+                });
+            }
+        }
+
+        for table in &mut tables {
+            let Some(previous) = self.tables.iter().find(|t| t.name == table.name) else {
+                continue;
+            };
+            table.created_in = previous.created_in;
+            for column in &mut table.columns {
+                if let Some(previous) = previous.columns.iter().find(|c| c.name == column.name) {
+                    column.added_in = previous.added_in;
+                }
+            }
+        }
+        self.version = version;
+        self.tables = tables;
+        Ok(())
+    }
+
+    /// Render the description as Markdown, one section per table
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Database schema\n\nTables, columns and indexes created by the migrations up to `{}`, \
+             generated by `sui-indexer schema dump`.\n",
+            self.version
+        );
+        for table in &self.tables {
+            out.push_str(&format!("\n## `{}`\n\n", table.name));
+            if let Some(description) = &table.description {
+                out.push_str(&format!("{description}\n\n"));
+            }
+            out.push_str(&format!("Created by migration `{}`.\n\n", table.created_in));
+            out.push_str("| Column | Type | Nullable | Default | Key | Description |\n");
+            out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+            for column in &table.columns {
+                let mut key = Vec::new();
+                if table.primary_key.contains(&column.name) {
+                    key.push("primary key".to_string());
+                }
+                if column.unique {
+                    key.push("unique".to_string());
+                }
+                if let Some(references) = &column.references {
+                    key.push(format!("references `{references}`"));
+                }
+                let mut description = column.description.clone().unwrap_or_default();
+                if column.added_in != table.created_in {
+                    if !description.is_empty() {
+                        description.push(' ');
+                    }
+                    description.push_str(&format!("Added by migration `{}`.", column.added_in));
+                }
+                out.push_str(&format!(
+                    "| `{}` | `{}` | {} | {} | {} | {} |\n",
+                    column.name,
+                    cell(&column.data_type),
+                    if column.nullable { "yes" } else { "no" },
+                    column
+                        .default
+                        .as_ref()
+                        .map(|default| format!("`{}`", cell(default)))
+                        .unwrap_or_default(),
+                    cell(&key.join(", ")),
+                    cell(&description),
+                ));
+            }
+            if !table.indexes.is_empty() {
+                out.push_str("\nIndexes:\n\n");
+            }
+            for index in &table.indexes {
+                let columns: Vec<String> = index
+                    .columns
+                    .iter()
+                    .map(|column| format!("`{column}`"))
+                    .collect();
+                out.push_str(&format!("- `{}` on {}", index.name, columns.join(", ")));
+                if index.unique {
+                    out.push_str(", unique");
+                }
+                if let Some(method) = &index.method {
+                    out.push_str(&format!(", using `{method}`"));
+                }
+                if let Some(predicate) = &index.predicate {
+                    out.push_str(&format!(", where `{predicate}`"));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Table of `tables` named in the `table_name` column of `row`
+fn table_mut<'a>(tables: &'a mut [TableDoc], row: &PgRow) -> Option<&'a mut TableDoc> {
+    let name: &str = row.get("table_name");
+    tables.iter_mut().find(|table| table.name == name)
+}
+
+/// Markdown table cell text
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        migrations,
+        test_db::{database_url, scratch_pool},
+    };
+
+    #[test]
+    fn test_markdown_lists_columns_and_indexes() {
+        let column = |name: &str, data_type: &str, added_in| ColumnDoc {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable: false,
+            default: None,
+            unique: false,
+            references: None,
+            description: None,
+            added_in,
+        };
+        let doc = SchemaDoc {
+            version: 20250102000001,
+            tables: vec![TableDoc {
+                name: "swaps".to_string(),
+                description: Some("One row per swap".to_string()),
+                created_in: 20250101000001,
+                columns: vec![
+                    ColumnDoc {
+                        references: Some("pools(id)".to_string()),
+                        ..column("pool_id", "bigint", 20250101000001)
+                    },
+                    ColumnDoc {
+                        nullable: true,
+                        default: Some("'unknown'::text".to_string()),
+                        description: Some("Who swapped | if known".to_string()),
+                        ..column("sender", "text", 20250102000001)
+                    },
+                ],
+                primary_key: vec!["pool_id".to_string()],
+                indexes: vec![IndexDoc {
+                    name: "idx_swaps_sender".to_string(),
+                    columns: vec!["sender DESC".to_string()],
+                    unique: true,
+                    method: None,
+                    predicate: Some("sender IS NOT NULL".to_string()),
+                }],
+            }],
+        };
+
+        let markdown = doc.to_markdown();
+        assert!(markdown.contains("up to `20250102000001`"));
+        assert!(markdown.contains("## `swaps`\n\nOne row per swap\n\n"));
+        assert!(markdown
+            .contains("| `pool_id` | `bigint` | no |  | primary key, references `pools(id)` |  |"));
+        assert!(markdown.contains(
+            "| `sender` | `text` | yes | `'unknown'::text` |  | Who swapped \\| if known \
+             Added by migration `20250102000001`. |"
+        ));
+        assert!(markdown
+            .contains("- `idx_swaps_sender` on `sender DESC`, unique, where `sender IS NOT NULL`"));
+    }
+
+    #[test]
+    fn test_embedded_schema_is_described() -> Result<()> {
+        let doc = migrations::schema_doc()?;
+        let table = |name: &str| {
+            doc.tables
+                .iter()
+                .find(|table| table.name == name)
+                .expect("table is described")
+        };
+
+        let indexer_state = table("indexer_state");
+        let chain_id = indexer_state
+            .columns
+            .iter()
+            .find(|column| column.name == "chain_id")
+            .expect("chain_id is described");
+        assert!(chain_id.nullable);
+        assert_eq!(chain_id.added_in, 20251201000001);
+        assert_eq!(indexer_state.created_in, 20250826000002);
+        assert!(indexer_state
+            .indexes
+            .iter()
+            .any(|index| index.columns == ["pipeline", "id DESC"]));
+
+        assert_eq!(
+            table("audit_log").description.as_deref(),
+            Some("Audit log of operator actions")
+        );
+        assert_eq!(
+            table("move_calls").primary_key,
+            ["transaction_digest", "command_index"]
+        );
+        let events = table("processed_events");
+        assert!(events
+            .columns
+            .iter()
+            .any(|column| column.name == "event_data" && column.nullable));
+        Ok(())
+    }
+
+    /// Compares `schema.json` with the catalog, rewriting it with `SUI_INDEXER_UPDATE_SCHEMA=1`
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_embedded_schema_matches_the_catalog() -> Result<()> {
+        let url = database_url()?;
+        let pool = scratch_pool(&url, &format!("schema_doc_test_{}", std::process::id())).await?;
+        let doc = migrations::describe_schema(&pool).await?;
+
+        // The migrations were applied to a schema that was rolled back
+        let left: bool = sqlx::query_scalar("SELECT to_regclass('indexer_state') IS NOT NULL")
+            .fetch_one(&pool)
+            .await?;
+        assert!(!left);
+
+        if std::env::var_os("SUI_INDEXER_UPDATE_SCHEMA").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/schema.json");
+            std::fs::write(path, serde_json::to_string_pretty(&doc)? + "\n")?;
+            return Ok(());
+        }
+        assert_eq!(
+            doc,
+            migrations::schema_doc()?,
+            "schema.json is outdated, regenerate it with SUI_INDEXER_UPDATE_SCHEMA=1"
+        );
+        Ok(())
+    }
+}
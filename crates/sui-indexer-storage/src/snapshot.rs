@@ -0,0 +1,339 @@
+/// Consistent snapshots of the indexed data, for cloning environments and recovery drills
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use sui_indexer_config::StorageConfig;
+use tracing::info;
+
+use crate::{migrations, CursorRecord, StorageError};
+
+/// Version of the snapshot layout written by [`create_snapshot`]
+pub const SNAPSHOT_FORMAT: u32 = 2;
+
+/// File describing a snapshot, written last so an incomplete snapshot has none
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Rows inserted per statement on restore
+const RESTORE_BATCH_ROWS: usize = 1000;
+
+/// Contents of a snapshot's manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Layout version, [`SNAPSHOT_FORMAT`] when written by this release
+    pub format: u32,
+    /// When the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    /// Latest migration applied to the source database
+    pub schema_version: i64,
+    /// Every checkpoint cursor at the time of the snapshot
+    pub cursors: Vec<CursorRecord>,
+    /// Rows in the snapshot by table, each written to `<table>.ndjson`
+    pub tables: BTreeMap<String, u64>,
+}
+
+/// Write every table the migrations created to `dir`
+///
+/// Everything is read in one repeatable-read transaction, so the snapshot is
+/// consistent even while an indexer keeps writing.
+pub async fn create_snapshot(pool: &PgPool, dir: &Path) -> Result<SnapshotManifest> {
+    std::fs::create_dir_all(dir).wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let schema_version = applied_version(&mut tx)
+        .await?
+        .ok_or(StorageError::NoMigrations)?;
+    let cursors = sqlx::query_as::<_, CursorRecord>(
+        "SELECT DISTINCT ON (pipeline) pipeline, checkpoint_sequence, updated_at
+         FROM indexer_state ORDER BY pipeline, id DESC",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut tables = BTreeMap::new();
+    for table in migrations::managed_tables() {
+        // Tables of migrations newer than the database's schema
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(&table)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !exists {
+            continue;
+        }
+        let path = dir.join(format!("{table}.ndjson"));
+        let mut file = BufWriter::new(
+            File::create(&path).wrap_err_with(|| format!("Failed to create {}", path.display()))?,
+        );
+        let sql = format!("SELECT row_to_json(t) FROM {table} t");
+        let mut rows = sqlx::query_scalar::<_, serde_json::Value>(&sql).fetch(&mut *tx);
+
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            serde_json::to_writer(&mut file, &row)?;
+            writeln!(file)?;
+            count += 1;
+        }
+        file.flush()?;
+        info!(table, rows = count, "Snapshotted table");
+        tables.insert(table, count);
+    }
+    tx.commit().await?;
+
+    let manifest = SnapshotManifest {
+        format: SNAPSHOT_FORMAT,
+        created_at: Utc::now(),
+        schema_version,
+        cursors,
+        tables,
+    };
+    let path = dir.join(MANIFEST_FILE);
+    std::fs::write(&path, serde_json::to_vec_pretty(&manifest)?)
+        .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+    Ok(manifest)
+}
+
+/// Load the snapshot in `dir` into an empty database
+///
+/// The database is migrated to the snapshot's schema version first and must
+/// not be ahead of it; run `migrate up` afterwards to bring it current. All
+/// rows are written in one transaction, so a failed restore leaves the
+/// database empty.
+pub async fn restore_snapshot(
+    pool: &PgPool,
+    storage: &StorageConfig,
+    dir: &Path,
+) -> Result<SnapshotManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let manifest: SnapshotManifest = serde_json::from_slice(
+        &std::fs::read(&path).wrap_err_with(|| format!("Failed to read {}", path.display()))?,
+    )
+    .wrap_err_with(|| format!("Invalid snapshot manifest {}", path.display()))?;
+    if manifest.format != SNAPSHOT_FORMAT {
+        return Err(StorageError::InvalidSnapshot(format!(
+            "format {} is not supported, expected {SNAPSHOT_FORMAT}",
+            manifest.format
+        ))
+        .into());
+    }
+
+    migrations::migrate_up(pool, storage, Some(manifest.schema_version)).await?;
+    let mut tx = pool.begin().await?;
+    if let Some(version) = applied_version(&mut tx)
+        .await?
+        .filter(|version| *version > manifest.schema_version)
+    {
+        return Err(StorageError::InvalidSnapshot(format!(
+            "database schema {version} is newer than the snapshot's {}; restore onto a fresh database",
+            manifest.schema_version
+        ))
+        .into());
+    }
+    // Table names are interpolated into SQL, so only those the migrations create are restored
+    let managed = migrations::managed_tables();
+    if let Some(table) = manifest
+        .tables
+        .keys()
+        .find(|table| !managed.contains(table))
+    {
+        return Err(StorageError::InvalidSnapshot(format!(
+            "{table} is not a table the migrations create"
+        ))
+        .into());
+    }
+    // In the order the migrations create them
+    let tables: Vec<_> = managed
+        .iter()
+        .filter_map(|table| Some((table, *manifest.tables.get(table)?)))
+        .collect();
+
+    for (table, _) in &tables {
+        let populated: bool = sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {table})"))
+            .fetch_one(&mut *tx)
+            .await?;
+        if populated {
+            return Err(StorageError::InvalidSnapshot(format!(
+                "{table} already holds rows; restore onto a fresh database"
+            ))
+            .into());
+        }
+    }
+
+    for (table, expected) in tables {
+        let path = dir.join(format!("{table}.ndjson"));
+        let restored = restore_table(&mut tx, table, &path)
+            .await
+            .wrap_err_with(|| format!("Failed to restore {}", path.display()))?;
+        if restored != expected {
+            return Err(StorageError::InvalidSnapshot(format!(
+                "{} holds {restored} rows but the manifest lists {expected}",
+                path.display()
+            ))
+            .into());
+        }
+        reset_sequences(&mut tx, table).await?;
+        info!(table, rows = restored, "Restored table");
+    }
+    tx.commit().await?;
+    Ok(manifest)
+}
+
+/// Insert the JSON rows of `path` into `table`, returning how many there were
+async fn restore_table(conn: &mut PgConnection, table: &str, path: &Path) -> Result<u64> {
+    let file = BufReader::new(File::open(path)?);
+    let mut batch = Vec::with_capacity(RESTORE_BATCH_ROWS);
+    let mut count = 0;
+
+    for line in file.lines() {
+        batch.push(serde_json::from_str::<serde_json::Value>(&line?)?);
+        if batch.len() == RESTORE_BATCH_ROWS {
+            count += insert_rows(conn, table, std::mem::take(&mut batch)).await?;
+        }
+    }
+    if !batch.is_empty() {
+        count += insert_rows(conn, table, batch).await?;
+    }
+    Ok(count)
+}
+
+async fn insert_rows(
+    conn: &mut PgConnection,
+    table: &str,
+    rows: Vec<serde_json::Value>,
+) -> Result<u64> {
+    let result = sqlx::query(&format!(
+        "INSERT INTO {table} SELECT * FROM json_populate_recordset(NULL::{table}, $1::json)"
+    ))
+    .bind(serde_json::Value::Array(rows))
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Move the sequences of `table`'s serial columns past the restored ids
+async fn reset_sequences(conn: &mut PgConnection, table: &str) -> Result<()> {
+    let columns: Vec<String> = sqlx::query_scalar(
+        "SELECT attname::TEXT FROM pg_attribute
+         WHERE attrelid = $1::regclass AND attnum > 0 AND NOT attisdropped
+           AND pg_get_serial_sequence($1, attname) IS NOT NULL",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await?;
+    for column in columns {
+        sqlx::query(&format!(
+            "SELECT setval(pg_get_serial_sequence($1, $2), COALESCE(MAX({column}), 0) + 1, false)
+             FROM {table}"
+        ))
+        .bind(table)
+        .bind(&column)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Latest migration applied to the database, if any
+async fn applied_version(conn: &mut PgConnection) -> Result<Option<i64>> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&mut *conn)
+        .await?;
+    if !exists {
+        return Ok(None);
+    }
+    Ok(
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+            .fetch_one(&mut *conn)
+            .await?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::{database_url, scratch_pool};
+
+    /// Rows of `table` as JSON text, in a stable order
+    async fn rows(pool: &PgPool, table: &str) -> Result<serde_json::Value> {
+        let sql = format!(
+            "SELECT COALESCE(json_agg(row_to_json(t)::TEXT ORDER BY row_to_json(t)::TEXT), '[]')
+             FROM {table} t"
+        );
+        Ok(sqlx::query_scalar(&sql).fetch_one(pool).await?)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires SUI_INDEXER_TEST_DATABASE_URL"]
+    async fn test_snapshot_round_trip() -> Result<()> {
+        let url = database_url()?;
+        let schema = format!("snapshot_test_{}", std::process::id());
+        let source = scratch_pool(&url, &format!("{schema}_source")).await?;
+        let target = scratch_pool(&url, &format!("{schema}_target")).await?;
+        let storage = StorageConfig::default();
+        migrations::run_migrations(&source, &storage).await?;
+
+        for statement in [
+            "INSERT INTO indexer_state (pipeline, checkpoint_sequence, chain_id) VALUES ('default', 42, '35834a8a')",
+            "INSERT INTO processed_events (id, transaction_digest, checkpoint_sequence, timestamp, package_id,
+                 module_name, event_type, sender, fields, metadata, processed_at)
+             VALUES (gen_random_uuid(), 'tx1', 42, NOW(), '0x2', 'pool', '0x2::pool::Swap', '0x1',
+                 '{\"amount\": \"10\"}', '{}', NOW())",
+            "INSERT INTO event_counts VALUES (42, '0x2', '0x2::pool::Swap', 1)",
+            "INSERT INTO audit_log (actor, source, action) VALUES ('ops', 'cli', 'prune')",
+            "INSERT INTO outbox (sink, checkpoint_sequence, payload) VALUES ('kafka', 42, '{}')",
+            "INSERT INTO packages (package_id, original_id, version, tx_digest, sender, checkpoint_sequence, published_at)
+             VALUES ('0x2', '0x2', 1, 'tx0', '0x0', 1, NOW())",
+        ] {
+            sqlx::query(statement).execute(&source).await?;
+        }
+
+        let dir = tempfile::tempdir()?;
+        let created = create_snapshot(&source, dir.path()).await?;
+        let mut managed = migrations::managed_tables();
+        managed.sort();
+        assert_eq!(created.tables.keys().cloned().collect::<Vec<_>>(), managed);
+        assert_eq!(created.tables["audit_log"], 1);
+        assert_eq!(created.cursors[0].checkpoint_sequence, 42);
+
+        let restored = restore_snapshot(&target, &storage, dir.path()).await?;
+        assert_eq!(restored.tables, created.tables);
+        for table in created.tables.keys() {
+            assert_eq!(
+                rows(&source, table).await?,
+                rows(&target, table).await?,
+                "{table}"
+            );
+        }
+        // Serial ids carry on after the restored rows
+        sqlx::query(
+            "INSERT INTO audit_log (actor, source, action) VALUES ('ops', 'cli', 'restore')",
+        )
+        .execute(&target)
+        .await?;
+        // Nothing is restored over existing rows
+        assert!(restore_snapshot(&target, &storage, dir.path())
+            .await
+            .is_err());
+
+        for pool in [source, target] {
+            let schema: String = sqlx::query_scalar("SELECT current_schema()")
+                .fetch_one(&pool)
+                .await?;
+            sqlx::query(&format!("DROP SCHEMA {schema} CASCADE"))
+                .execute(&pool)
+                .await?;
+        }
+        Ok(())
+    }
+}
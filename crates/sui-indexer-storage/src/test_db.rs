@@ -0,0 +1,180 @@
+/// Scratch databases for the tests ignored unless run against `SUI_INDEXER_TEST_DATABASE_URL`,
+/// and checks every backend must pass
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use eyre::{Result, WrapErr};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    PgPool,
+};
+use sui_indexer_events::{DefaultEventProcessor, EventProcessor, ProcessedTransaction};
+use sui_indexer_fixtures::TestEvent;
+use sui_json_rpc_types::SuiTransactionBlockResponse;
+use sui_types::digests::TransactionDigest;
+
+use crate::{AuditEntry, EventQuery, EventRecord, PageCursor, Storage};
+
+/// Database the ignored tests run against, e.g. with `cargo test -- --ignored`
+pub(crate) fn database_url() -> Result<String> {
+    std::env::var("SUI_INDEXER_TEST_DATABASE_URL")
+        .wrap_err("SUI_INDEXER_TEST_DATABASE_URL must point at a scratch database")
+}
+
+/// Pool whose tables live in `schema`, created afresh in the database at `url`
+pub(crate) async fn scratch_pool(url: &str, schema: &str) -> Result<PgPool> {
+    let admin = PgPool::connect(url).await?;
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&admin)
+        .await?;
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&admin)
+        .await?;
+    let options = PgConnectOptions::from_str(url)?.options([("search_path", schema)]);
+    Ok(PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(options)
+        .await?)
+}
+
+/// Check that `stats_since` of an empty `storage` only summarizes rows from the window start on
+pub(crate) async fn check_stats_since(storage: &dyn Storage) -> Result<()> {
+    let since = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+    let hours = [-2, -1, 1, 2, 3];
+
+    let raw = (0..hours.len() as u64)
+        .map(|seq| {
+            let event_type = if seq % 2 == 0 {
+                "0x2::pool::SwapEvent"
+            } else {
+                "0x2::pool::MintEvent"
+            };
+            TestEvent::new(event_type).seq(seq).build()
+        })
+        .collect();
+    let mut events = DefaultEventProcessor::new().process_events(raw).await?;
+    for (checkpoint, (hours, event)) in hours.into_iter().zip(&mut events).enumerate() {
+        event.checkpoint_sequence = checkpoint as u64;
+        event.timestamp = since + Duration::hours(hours);
+        event.metadata.processed_at = event.timestamp + Duration::seconds(2);
+    }
+    storage.store_events(&events).await?;
+
+    // Responses without effects read as failed on chain
+    let transaction = |byte: u8, at: DateTime<Utc>, success: bool| {
+        let response = SuiTransactionBlockResponse::new(TransactionDigest::new([byte; 32]));
+        let mut transaction = ProcessedTransaction::from_response(response, at);
+        transaction.metadata.success = success;
+        transaction
+    };
+    storage
+        .store_transactions(vec![
+            transaction(1, since - Duration::hours(1), false),
+            transaction(2, since, true),
+            transaction(3, since + Duration::hours(1), false),
+        ])
+        .await?;
+
+    let stats = storage.stats_since(since, 1).await?;
+    assert_eq!(stats.since, since);
+    assert_eq!(stats.events, 3);
+    assert_eq!(stats.transactions, 2);
+    assert_eq!(stats.failed_transactions, 1);
+    assert_eq!(stats.average_lag_secs, Some(2.0));
+    assert_eq!(stats.event_types.len(), 1);
+    assert_eq!(stats.event_types[0].value, "SwapEvent");
+    assert_eq!(stats.event_types[0].count, 2);
+    assert_eq!(stats.packages[0].count, 3);
+
+    let empty = storage.stats_since(since + Duration::days(1), 10).await?;
+    assert_eq!(empty.events, 0);
+    assert_eq!(empty.average_lag_secs, None);
+    assert!(empty.event_types.is_empty());
+    Ok(())
+}
+
+/// Check that the audit log of an empty `storage` keeps entries as recorded, newest first
+pub(crate) async fn check_audit_log(storage: &dyn Storage) -> Result<()> {
+    assert!(storage.audit_log(10).await?.is_empty());
+
+    let before = Utc::now() - Duration::seconds(5);
+    for (action, checkpoint) in [("cursor_set", 100), ("prune", 200), ("pause", 300)] {
+        storage
+            .record_audit(&AuditEntry {
+                actor: "alice".to_string(),
+                source: "cli".to_string(),
+                action: action.to_string(),
+                details: serde_json::json!({ "checkpoint": checkpoint }),
+            })
+            .await?;
+    }
+
+    let log = storage.audit_log(2).await?;
+    let actions: Vec<_> = log.iter().map(|record| record.action.as_str()).collect();
+    assert_eq!(actions, ["pause", "prune"]);
+    assert!(log[0].id > log[1].id);
+    assert_eq!(log[1].actor, "alice");
+    assert_eq!(log[1].source, "cli");
+    assert_eq!(log[1].details, serde_json::json!({ "checkpoint": 200 }));
+    assert!(log.iter().all(|record| record.occurred_at >= before));
+    assert_eq!(storage.audit_log(10).await?.len(), 3);
+    Ok(())
+}
+
+/// Check that paging through the events of an empty `storage` returns each
+/// event once, newest first, while newer events are stored between pages
+pub(crate) async fn check_stable_pages(storage: &dyn Storage) -> Result<()> {
+    // Two events per checkpoint, so pages break between events of one checkpoint
+    let raw = (0..12)
+        .map(|seq| TestEvent::new("0x2::pool::SwapEvent").seq(seq).build())
+        .collect();
+    let mut events = DefaultEventProcessor::new().process_events(raw).await?;
+    for (index, event) in events.iter_mut().enumerate() {
+        event.checkpoint_sequence = index as u64 / 2;
+    }
+    let (stored, newer) = events.split_at(7);
+    storage.store_events(stored).await?;
+
+    let mut pages: Vec<Vec<EventRecord>> = Vec::new();
+    let mut after = None;
+    loop {
+        let query = EventQuery {
+            limit: Some(3),
+            after,
+            ..EventQuery::default()
+        };
+        let page = storage.query_events(&query).await?;
+        after = PageCursor::next_page(&page, 3, EventRecord::cursor);
+        pages.push(page);
+        // Newer events land ahead of every page already read
+        if pages.len() == 1 {
+            storage.store_events(newer).await?;
+        }
+        if after.is_none() {
+            break;
+        }
+    }
+
+    let sizes: Vec<_> = pages.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [3, 3, 1]);
+    let paged: Vec<_> = pages.concat().iter().map(EventRecord::cursor).collect();
+    let mut expected: Vec<_> = stored
+        .iter()
+        .map(|event| PageCursor {
+            checkpoint: event.checkpoint_sequence,
+            id: event.id,
+        })
+        .collect();
+    expected.sort_by_key(|cursor| std::cmp::Reverse((cursor.checkpoint, cursor.id)));
+    assert_eq!(paged, expected);
+
+    let query = EventQuery {
+        limit: Some(3),
+        ..EventQuery::default()
+    };
+    assert_eq!(
+        storage.query_events(&query).await?[0].checkpoint_sequence,
+        5
+    );
+    Ok(())
+}
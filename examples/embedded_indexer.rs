@@ -0,0 +1,47 @@
+use eyre::Result;
+use sui_indexer_config::{
+    EventFilter, FilterPriority, IndexerConfig, Network, NetworkConfig, StartMode,
+};
+use sui_indexer_core::IndexerCore;
+use tracing::info;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt::init();
+
+    info!("🚀 Starting embedded indexer");
+
+    // No database: progress is kept in memory, so start at the chain tip
+    let mut config = IndexerConfig {
+        network: NetworkConfig::for_network(Network::Testnet),
+        ..Default::default()
+    };
+    config.events.start_mode = StartMode::Latest;
+    config.events.filters = vec![EventFilter {
+        name: None,
+        package: Some("0x2".to_string()),
+        module: Some("coin".to_string()),
+        event_type: None,
+        sender: None,
+        priority: FilterPriority::Normal,
+    }];
+
+    let indexer = IndexerCore::embedded(config).await?;
+    let mut events = indexer.start_with_stream().await?;
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(event) => info!(
+                    "📝 {} in checkpoint {} (tx: {})",
+                    event.event_type, event.checkpoint_sequence, event.transaction_digest
+                ),
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    events.shutdown().await
+}
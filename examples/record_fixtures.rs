@@ -0,0 +1,62 @@
+//! Record checkpoints from a full node as test fixtures
+//!
+//! Fetches `count` checkpoints from `first` on and saves each as
+//! `<sequence_number>.json` in `dir`, where
+//! [`sui_indexer_fixtures::load_dir`] reads them back. Recording needs
+//! network access to a full node: mainnet by default, or the one at
+//! `SUI_GRPC_URL`.
+//!
+//! ```bash
+//! cargo run -p sui-indexer-core --example record_fixtures -- <first> <count> [dir]
+//! ```
+
+use std::path::PathBuf;
+
+use eyre::{eyre, Result, WrapErr};
+use sui_indexer_config::{Network, NetworkConfig};
+use sui_indexer_core::SuiClient;
+use sui_indexer_fixtures::RecordedCheckpoint;
+use tracing::info;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut args = std::env::args().skip(1);
+    let usage = || eyre!("Usage: record_fixtures <first> <count> [dir]");
+    let first: u64 = args
+        .next()
+        .ok_or_else(usage)?
+        .parse()
+        .wrap_err("Invalid first checkpoint")?;
+    let count: u64 = args
+        .next()
+        .ok_or_else(usage)?
+        .parse()
+        .wrap_err("Invalid checkpoint count")?;
+    let dir = PathBuf::from(args.next().unwrap_or_else(|| "checkpoints".to_string()));
+    std::fs::create_dir_all(&dir)
+        .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut network = NetworkConfig::for_network(Network::Mainnet);
+    if let Ok(url) = std::env::var("SUI_GRPC_URL") {
+        network.grpc_url = Some(url.parse().wrap_err("Invalid SUI_GRPC_URL")?);
+    }
+    let client = SuiClient::new(network)
+        .await
+        .wrap_err("Failed to connect to the full node")?;
+
+    for sequence_number in first..first + count {
+        let checkpoint = client.get_checkpoint(sequence_number).await?;
+        // Recorded checkpoints keep the fields of the fetched ones that tests replay
+        let recorded: RecordedCheckpoint =
+            serde_json::from_value(serde_json::to_value(&checkpoint)?)?;
+        let path = sui_indexer_fixtures::save_checkpoint(&dir, &recorded)?;
+        info!(
+            "Recorded checkpoint {sequence_number} with {} events to {}",
+            recorded.events.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use eyre::Result;
-use sui_indexer_config::{EventFilter, IndexerConfig};
+use sui_indexer_config::{EventFilter, FilterPriority, IndexerConfig, Network, NetworkConfig};
 use sui_indexer_core::IndexerCore;
 use sui_indexer_events::{EventProcessor, ProcessedEvent};
 use sui_json_rpc_types::SuiEvent;
@@ -17,6 +17,12 @@ impl SimpleEventProcessor {
     }
 }
 
+impl Default for SimpleEventProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl EventProcessor for SimpleEventProcessor {
     async fn process_event(&self, event: SuiEvent) -> Result<ProcessedEvent> {
@@ -27,6 +33,7 @@ impl EventProcessor for SimpleEventProcessor {
         );
 
         // Create processed event
+        let event = Arc::new(event);
         let processed_event = ProcessedEvent {
             id: uuid::Uuid::new_v4(),
             event: event.clone(),
@@ -59,18 +66,19 @@ async fn main() -> Result<()> {
     info!("🚀 Starting Simple Event Indexer");
 
     // Create basic configuration
-    let mut config = IndexerConfig::default();
-    config.network.grpc_url = "https://fullnode.testnet.sui.io:443"
-        .parse()
-        .expect("Valid URL");
-    config.network.network = "testnet".to_string();
+    let mut config = IndexerConfig {
+        network: NetworkConfig::for_network(Network::Testnet),
+        ..Default::default()
+    };
 
     // Add a simple filter to monitor coin events
     config.events.filters = vec![EventFilter {
+        name: None,
         package: Some("0x2".to_string()),
         module: Some("coin".to_string()),
         event_type: None,
         sender: None,
+        priority: FilterPriority::Normal,
     }];
 
     // Create simple processor
@@ -0,0 +1,41 @@
+//! Raw BCS bytes and payloads of custom packages: the indexer carries the
+//! bytes through as `bcs_data` and must accept whatever a package emits.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sui_indexer_events::{EventTransformer, ProtocolRegistry};
+use sui_json_rpc_types::{BcsEvent, SuiEvent};
+
+fuzz_target!(|data: &[u8]| {
+    // The first bytes pick the payload, the rest are the BCS bytes
+    let split = data
+        .first()
+        .map_or(0, |len| usize::from(*len).min(data.len() - 1));
+    let (payload, bcs) = data.get(1..).unwrap_or_default().split_at(split);
+    let parsed_json = serde_json::from_slice(payload).unwrap_or(serde_json::Value::Null);
+
+    for package in ["0x2", "0xdee9"] {
+        let mut event = serde_json::from_value::<SuiEvent>(serde_json::json!({
+            "id": {"eventSeq": "0", "txDigest": "11111111111111111111111111111111"},
+            "packageId": package,
+            "transactionModule": "clob_v2",
+            "sender": "0x1",
+            "type": format!("{package}::clob_v2::OrderFilled"),
+            "parsedJson": {},
+            "bcs": "",
+        }))
+        .unwrap();
+        event.parsed_json = parsed_json.clone();
+        event.bcs = BcsEvent::new(bcs.to_vec());
+
+        let _ = ProtocolRegistry::default_builtin().decode(&event);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let processed = runtime
+            .block_on(EventTransformer::with_config(true, true).transform_event(event))
+            .unwrap();
+        serde_json::to_vec(&processed).unwrap();
+    }
+});
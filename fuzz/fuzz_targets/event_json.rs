@@ -0,0 +1,33 @@
+//! Events as a node returns them: any JSON that deserializes must go through
+//! filtering, protocol decoding and transformation without panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sui_indexer_events::{EventFilterProcessor, EventTransformer, ProtocolRegistry};
+use sui_json_rpc_types::SuiEvent;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(event) = serde_json::from_slice::<SuiEvent>(data) else {
+        return;
+    };
+
+    let filter = sui_indexer_config::EventFilter {
+        name: None,
+        package: Some(event.package_id.to_string()),
+        module: Some(event.transaction_module.to_string()),
+        event_type: Some(event.type_.name.to_string()),
+        sender: None,
+        priority: sui_indexer_config::FilterPriority::Normal,
+    };
+    let _ = EventFilterProcessor::new(vec![filter]).should_process_event(&event);
+    let _ = ProtocolRegistry::default_builtin().decode(&event);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let processed = runtime
+        .block_on(EventTransformer::with_config(true, true).transform_event(event))
+        .unwrap();
+    serde_json::to_vec(&processed).unwrap();
+});
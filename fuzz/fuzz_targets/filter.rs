@@ -0,0 +1,38 @@
+//! Filters as users write them in config: validation may reject any of them,
+//! but neither it nor matching may panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sui_indexer_config::{EventFilter, FilterPriority};
+use sui_indexer_events::{common_filters, EventFilterProcessor};
+use sui_json_rpc_types::SuiEvent;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let mut fields = text.split('\n').map(|field| match field {
+        "" => None,
+        field => Some(field.to_string()),
+    });
+    let filter = EventFilter {
+        name: None,
+        package: fields.next().flatten(),
+        module: fields.next().flatten(),
+        event_type: fields.next().flatten(),
+        sender: fields.next().flatten(),
+        priority: FilterPriority::Normal,
+    };
+    let _ = common_filters::validate(&filter);
+
+    let event = serde_json::from_value::<SuiEvent>(serde_json::json!({
+        "id": {"eventSeq": "0", "txDigest": "11111111111111111111111111111111"},
+        "packageId": "0x2",
+        "transactionModule": "pool",
+        "sender": "0x1",
+        "type": "0x2::pool::SwapEvent",
+        "parsedJson": {},
+        "bcs": "",
+    }))
+    .unwrap();
+    let _ = EventFilterProcessor::new(vec![filter]).should_process_event(&event);
+});